@@ -1,13 +1,42 @@
+mod mini_tokio;
 mod own_future;
+mod select;
 
+use crate::mini_tokio::{MiniTokio, spawn};
 use crate::own_future::Delay;
+use crate::select::{Either, select};
+use std::env;
+use std::pin::pin;
 use std::time::{Duration, Instant};
+use tokio::io::AsyncReadExt;
 use tokio::net::TcpStream;
 
-#[tokio::main]
-async fn main() {
-    //how_async_works().await;
-    use_my_future().await;
+fn main() {
+    // `how_async_works` does I/O with tokio's TcpStream, which needs tokio's
+    // reactor: MiniTokio only polls futures.
+    if env::args().nth(1).as_deref() == Some("how-async-works") {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(how_async_works());
+        return;
+    }
+
+    // `cargo run -- select [address]` connects, then reads, with a timeout.
+    if env::args().nth(1).as_deref() == Some("select") {
+        let address = env::args()
+            .nth(2)
+            .unwrap_or_else(|| "127.0.0.1:6379".to_string());
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(read_or_timeout(&address));
+        return;
+    }
+
+    let mini_tokio = MiniTokio::new();
+    mini_tokio.spawn(async {
+        // A task may spawn more tasks onto the executor running it.
+        spawn(use_my_future());
+        use_my_future().await;
+    });
+    mini_tokio.run();
 }
 
 async fn how_async_works() {
@@ -25,6 +54,32 @@ async fn my_async_fn() {
     println!("async TCP operation complete");
 }
 
+/// Connects to `address` and reads what it sends first, giving up on each
+/// step after 500ms: each step races against a `Delay` with `select`.
+async fn read_or_timeout(address: &str) {
+    let timeout = || Delay {
+        when: Instant::now() + Duration::from_millis(500),
+    };
+
+    let connect = pin!(TcpStream::connect(address));
+    let mut stream = match select(connect, timeout()).await {
+        Either::Left(Ok(stream)) => stream,
+        Either::Left(Err(e)) => return println!("failed to connect to {address}: {e}"),
+        // The connect future is dropped with the `Select`, which cancels it.
+        Either::Right(_) => return println!("no connection to {address} within 500ms"),
+    };
+    println!("connected to {address}");
+
+    let mut buf = [0; 1024];
+    let read = pin!(stream.read(&mut buf));
+    match select(read, timeout()).await {
+        Either::Left(Ok(0)) => println!("{address} closed the connection"),
+        Either::Left(Ok(n)) => println!("read {n} bytes: {:?}", String::from_utf8_lossy(&buf[..n])),
+        Either::Left(Err(e)) => println!("failed to read: {e}"),
+        Either::Right(_) => println!("nothing to read within 500ms"),
+    }
+}
+
 async fn use_my_future() {
     let when = Instant::now() + Duration::from_millis(10);
     let future = Delay { when };
@@ -0,0 +1,134 @@
+use std::cell::RefCell;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, mpsc};
+use std::task::{Context, Wake, Waker};
+
+type BoxFuture = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+thread_local! {
+    /// The spawner of the executor running on this thread, used by `spawn`.
+    static CURRENT: RefCell<Option<Spawner>> = const { RefCell::new(None) };
+}
+
+/// A minimal executor: tasks are polled when they are scheduled, i.e. when
+/// they are spawned and every time their waker is woken.
+///
+/// The scheduled tasks are sent over a channel: the waker of a task holds a
+/// sender, so a task woken from another thread (e.g. by a timer) is sent back
+/// to the executor.
+pub(crate) struct MiniTokio {
+    scheduled: mpsc::Receiver<Arc<Task>>,
+    spawner: Spawner,
+}
+
+/// Schedules tasks onto a `MiniTokio`.
+#[derive(Clone)]
+struct Spawner {
+    sender: mpsc::Sender<Arc<Task>>,
+    /// Tasks spawned and not completed yet: `run` returns once there are none.
+    active: Arc<AtomicUsize>,
+}
+
+/// A spawned future, along with what it needs to schedule itself again.
+struct Task {
+    /// `None` once the future completed. The `Mutex` makes `Task` `Sync`, as
+    /// required to share it in a `Waker`: only the executor thread locks it.
+    future: Mutex<Option<BoxFuture>>,
+    spawner: Spawner,
+}
+
+impl MiniTokio {
+    pub(crate) fn new() -> Self {
+        let (sender, scheduled) = mpsc::channel();
+        Self {
+            scheduled,
+            spawner: Spawner {
+                sender,
+                active: Arc::new(AtomicUsize::new(0)),
+            },
+        }
+    }
+
+    /// Spawns a future onto the mini-tokio instance.
+    pub(crate) fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawner.spawn(future);
+    }
+
+    /// Polls the scheduled tasks until all the spawned ones completed.
+    pub(crate) fn run(&self) {
+        CURRENT.set(Some(self.spawner.clone()));
+
+        while self.spawner.active.load(Ordering::SeqCst) > 0 {
+            // The executor keeps a sender itself, so receiving never fails.
+            let task = self.scheduled.recv().unwrap();
+            task.poll();
+        }
+
+        CURRENT.set(None);
+    }
+}
+
+/// Spawns a future onto the `MiniTokio` running on the current thread.
+///
+/// # Panics
+///
+/// Panics if called outside of `MiniTokio::run`.
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    CURRENT.with_borrow(|spawner| match spawner {
+        Some(spawner) => spawner.spawn(future),
+        None => panic!("spawn called outside of a MiniTokio"),
+    });
+}
+
+impl Spawner {
+    fn spawn<F>(&self, future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.active.fetch_add(1, Ordering::SeqCst);
+        let task = Arc::new(Task {
+            future: Mutex::new(Some(Box::pin(future))),
+            spawner: self.clone(),
+        });
+        task.schedule();
+    }
+}
+
+impl Task {
+    fn schedule(self: &Arc<Self>) {
+        // Fails only once the executor is gone, nobody would poll the task.
+        let _ = self.spawner.sender.send(self.clone());
+    }
+
+    fn poll(self: Arc<Self>) {
+        // The task is its own waker: waking it schedules it again.
+        let waker = Waker::from(self.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut future = self.future.lock().unwrap();
+        // A completed task may still be woken by a stale waker.
+        if let Some(pending) = future.as_mut()
+            && pending.as_mut().poll(&mut cx).is_ready()
+        {
+            *future = None;
+            self.spawner.active.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Wake for Task {
+    fn wake(self: Arc<Self>) {
+        self.schedule();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.schedule();
+    }
+}
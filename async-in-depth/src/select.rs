@@ -0,0 +1,54 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// The output of the future which completed first.
+#[derive(Debug)]
+pub(crate) enum Either<A, B> {
+    Left(A),
+    Right(B),
+}
+
+/// Waits for the first of two futures to complete, see `select`.
+pub(crate) struct Select<A, B> {
+    left: A,
+    right: B,
+}
+
+/// Returns a future completing with the output of `left` or `right`,
+/// whichever completes first. The other one is dropped with the `Select`.
+///
+/// The futures must be `Unpin`: pin them first, e.g. with `std::pin::pin!`.
+/// What `tokio::select!` adds on top: any number of branches, pattern
+/// matching on their outputs, and a random polling order, so that the first
+/// branch doesn't always win when both are ready.
+pub(crate) fn select<A, B>(left: A, right: B) -> Select<A, B>
+where
+    A: Future + Unpin,
+    B: Future + Unpin,
+{
+    Select { left, right }
+}
+
+impl<A, B> Future for Select<A, B>
+where
+    A: Future + Unpin,
+    B: Future + Unpin,
+{
+    type Output = Either<A::Output, B::Output>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        // Both children get the same `Context`, so the same waker: the task
+        // is woken by whichever child makes progress first, and the executor
+        // can't tell which one it was. Hence both are polled every time, the
+        // one which wasn't woken just returns `Pending` again.
+        if let Poll::Ready(out) = Pin::new(&mut self.left).poll(cx) {
+            return Poll::Ready(Either::Left(out));
+        }
+        if let Poll::Ready(out) = Pin::new(&mut self.right).poll(cx) {
+            return Poll::Ready(Either::Right(out));
+        }
+        // Each child registered the waker where it waits, e.g. the socket
+        // with the reactor: the task is woken when either is ready.
+        Poll::Pending
+    }
+}
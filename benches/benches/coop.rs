@@ -0,0 +1,84 @@
+//! Coop budget: draining a channel with and without the budget.
+//!
+//! The channel is filled before the consumer starts, so `recv` is always
+//! ready. With the budget, the consumer yields to the scheduler every 128
+//! messages, which keeps the other tasks responsive. Wrapped in
+//! `unconstrained`, it drains the channel in one poll: more throughput, no
+//! fairness.
+
+use benches::{mini_rt, tokio_rt};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+const MESSAGES: usize = 100_000;
+
+fn coop(c: &mut Criterion) {
+    let mut group = c.benchmark_group("coop");
+    group.throughput(Throughput::Elements(MESSAGES as u64));
+
+    let rt = mini_rt();
+    for unconstrained in [false, true] {
+        let name = if unconstrained {
+            "mini/unconstrained"
+        } else {
+            "mini/budgeted"
+        };
+        group.bench_function(name, |b| {
+            use mini_runtime_v2::sync::mpsc;
+            use mini_runtime_v2::task;
+
+            b.iter(|| {
+                rt.block_on(async {
+                    let (tx, mut rx) = mpsc::channel(MESSAGES);
+                    for i in 0..MESSAGES {
+                        tx.try_send(i).unwrap();
+                    }
+                    drop(tx);
+
+                    let drain = async move { while rx.recv().await.is_some() {} };
+                    if unconstrained {
+                        mini_runtime_v2::spawn(task::unconstrained(drain)).await
+                    } else {
+                        mini_runtime_v2::spawn(drain).await
+                    }
+                    .unwrap();
+                })
+            })
+        });
+    }
+
+    let rt = tokio_rt();
+    for unconstrained in [false, true] {
+        let name = if unconstrained {
+            "tokio/unconstrained"
+        } else {
+            "tokio/budgeted"
+        };
+        group.bench_function(name, |b| {
+            use tokio::sync::mpsc;
+            use tokio::task;
+
+            b.iter(|| {
+                rt.block_on(async {
+                    let (tx, mut rx) = mpsc::channel(MESSAGES);
+                    for i in 0..MESSAGES {
+                        tx.try_send(i).unwrap();
+                    }
+                    drop(tx);
+
+                    let drain = async move { while rx.recv().await.is_some() {} };
+                    if unconstrained {
+                        tokio::spawn(task::unconstrained(drain)).await
+                    } else {
+                        tokio::spawn(drain).await
+                    }
+                    .unwrap();
+                })
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, coop);
+criterion_main!(benches);
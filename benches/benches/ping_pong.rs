@@ -0,0 +1,73 @@
+//! Ping-pong latency: two tasks bounce a message `ROUND_TRIPS` times.
+//!
+//! Every hop wakes the other task, so the benchmark measures the latency of
+//! a wakeup through a channel and the scheduler, not the channel's
+//! throughput. The mini runtime has no oneshot channel: both runtimes use a
+//! pair of mpsc channels of capacity 1.
+
+use benches::{mini_rt, tokio_rt};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+const ROUND_TRIPS: usize = 1_000;
+
+fn ping_pong(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ping_pong");
+    group.throughput(Throughput::Elements(ROUND_TRIPS as u64));
+
+    let rt = mini_rt();
+    group.bench_function("mini", |b| {
+        use mini_runtime_v2::sync::mpsc;
+
+        b.iter(|| {
+            rt.block_on(async {
+                let (ping_tx, mut ping_rx) = mpsc::channel(1);
+                let (pong_tx, mut pong_rx) = mpsc::channel(1);
+
+                let ponger = mini_runtime_v2::spawn(async move {
+                    while let Some(i) = ping_rx.recv().await {
+                        pong_tx.send(i).await.unwrap();
+                    }
+                });
+
+                for i in 0..ROUND_TRIPS {
+                    ping_tx.send(i).await.unwrap();
+                    assert_eq!(pong_rx.recv().await, Some(i));
+                }
+
+                drop(ping_tx);
+                ponger.await.unwrap();
+            })
+        })
+    });
+
+    let rt = tokio_rt();
+    group.bench_function("tokio", |b| {
+        use tokio::sync::mpsc;
+
+        b.iter(|| {
+            rt.block_on(async {
+                let (ping_tx, mut ping_rx) = mpsc::channel(1);
+                let (pong_tx, mut pong_rx) = mpsc::channel(1);
+
+                let ponger = tokio::spawn(async move {
+                    while let Some(i) = ping_rx.recv().await {
+                        pong_tx.send(i).await.unwrap();
+                    }
+                });
+
+                for i in 0..ROUND_TRIPS {
+                    ping_tx.send(i).await.unwrap();
+                    assert_eq!(pong_rx.recv().await, Some(i));
+                }
+
+                drop(ping_tx);
+                ponger.await.unwrap();
+            })
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, ping_pong);
+criterion_main!(benches);
@@ -0,0 +1,45 @@
+//! Spawn throughput: spawning `TASKS` trivial tasks and joining them all.
+//!
+//! Measures the cost of allocating a task, scheduling it and completing its
+//! `JoinHandle`, the tasks themselves do nothing.
+
+use benches::{mini_rt, tokio_rt};
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+
+const TASKS: usize = 1_000;
+
+fn spawn(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spawn");
+    group.throughput(Throughput::Elements(TASKS as u64));
+
+    let rt = mini_rt();
+    group.bench_function("mini", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let handles: Vec<_> = (0..TASKS)
+                    .map(|i| mini_runtime_v2::spawn(async move { i }))
+                    .collect();
+                for handle in handles {
+                    handle.await.unwrap();
+                }
+            })
+        })
+    });
+
+    let rt = tokio_rt();
+    group.bench_function("tokio", |b| {
+        b.iter(|| {
+            rt.block_on(async {
+                let handles: Vec<_> = (0..TASKS).map(|i| tokio::spawn(async move { i })).collect();
+                for handle in handles {
+                    handle.await.unwrap();
+                }
+            })
+        })
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, spawn);
+criterion_main!(benches);
@@ -0,0 +1,32 @@
+//! Timer accuracy: how long a `sleep(SLEEP)` actually takes.
+//!
+//! The closer the measured time is to `SLEEP`, the more accurate the timer.
+//! Tokio's timer wheel has a 1ms resolution and rounds deadlines up, so both
+//! runtimes are expected to oversleep a little; the difference shows in the
+//! mean and the spread of the samples.
+
+use benches::{mini_rt, tokio_rt};
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::time::Duration;
+
+const SLEEP: Duration = Duration::from_millis(1);
+
+fn timer(c: &mut Criterion) {
+    let mut group = c.benchmark_group("timer");
+    group.sample_size(50);
+
+    let rt = mini_rt();
+    group.bench_function("mini", |b| {
+        b.iter(|| rt.block_on(async { mini_runtime_v2::time::sleep(SLEEP).await }))
+    });
+
+    let rt = tokio_rt();
+    group.bench_function("tokio", |b| {
+        b.iter(|| rt.block_on(async { tokio::time::sleep(SLEEP).await }))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, timer);
+criterion_main!(benches);
@@ -0,0 +1,24 @@
+//! Benchmarks running the same workloads on `mini-runtime-v2` and Tokio.
+//!
+//! Run with `cargo bench`, or a single workload with e.g.
+//! `cargo bench --bench spawn`. Every benchmark group has a `mini` and a
+//! `tokio` entry: both runtimes use a current-thread scheduler with the same
+//! drivers enabled, the numbers are directly comparable.
+//!
+//! The HTML reports are written to `target/criterion`.
+
+/// Builds the current-thread mini runtime the benchmarks run on.
+pub fn mini_rt() -> mini_runtime_v2::runtime::Runtime {
+    mini_runtime_v2::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+/// Builds the current-thread Tokio runtime the benchmarks run on.
+pub fn tokio_rt() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
@@ -0,0 +1,199 @@
+//! Procedural macros for `mini-runtime-v2`.
+//!
+//! `#[main]` and `#[test]` turn an `async fn` into a regular function that
+//! builds a runtime and blocks on the body, like `#[tokio::main]` and
+//! `#[tokio::test]` do for Tokio. They are re-exported by `mini-runtime-v2`
+//! when its `macros` feature is enabled, use them from there.
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::{quote, quote_spanned};
+use syn::parse::Parser;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Expr, ExprLit, ItemFn, Lit, MetaNameValue, Token};
+
+/// Marks an async function to be executed by the selected runtime.
+///
+/// ```
+/// #[mini_runtime_v2::main]
+/// async fn main() {
+///     println!("Hello world");
+/// }
+/// ```
+///
+/// expands to
+///
+/// ```
+/// fn main() {
+///     let body = async {
+///         println!("Hello world");
+///     };
+///
+///     mini_runtime_v2::runtime::Builder::new_current_thread()
+///         .enable_all()
+///         .build()
+///         .expect("Failed building the Runtime")
+///         .block_on(body)
+/// }
+/// ```
+///
+/// # Arguments
+///
+/// - `flavor = "current_thread"`: the scheduler to use. The current-thread
+///   scheduler is the only one implemented, it is also the default.
+/// - `start_paused = true`: starts the runtime's clock paused, see
+///   `Builder::start_paused`.
+#[allow(clippy::needless_doctest_main)]
+#[proc_macro_attribute]
+pub fn main(args: TokenStream, item: TokenStream) -> TokenStream {
+    entry(args, item, false)
+}
+
+/// Marks an async function to be executed by the runtime, suitable to the
+/// test environment.
+///
+/// ```
+/// # use mini_runtime_v2::time::{self, Duration};
+/// #[mini_runtime_v2::test(start_paused = true)]
+/// async fn sleeps_for_an_hour() {
+///     time::sleep(Duration::from_secs(3600)).await;
+/// }
+/// # fn main() {}
+/// ```
+///
+/// Takes the same arguments as [`main`](macro@main).
+#[proc_macro_attribute]
+pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
+    entry(args, item, true)
+}
+
+/// Runtime configuration parsed from the attribute arguments.
+struct Config {
+    start_paused: bool,
+}
+
+fn entry(args: TokenStream, item: TokenStream, is_test: bool) -> TokenStream {
+    let input = match syn::parse::<ItemFn>(item) {
+        Ok(input) => input,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    let config = match parse_config(args, &input, is_test) {
+        Ok(config) => config,
+        Err(e) => return e.to_compile_error().into(),
+    };
+
+    expand(input, config, is_test).into()
+}
+
+fn parse_config(args: TokenStream, input: &ItemFn, is_test: bool) -> syn::Result<Config> {
+    let macro_name = if is_test { "test" } else { "main" };
+
+    if input.sig.asyncness.is_none() {
+        return Err(syn::Error::new_spanned(
+            input.sig.fn_token,
+            format!(
+                "the `async` keyword is missing from the function declaration of #[{macro_name}]"
+            ),
+        ));
+    }
+
+    if is_test && !input.sig.inputs.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.sig.inputs,
+            "the test function cannot accept arguments",
+        ));
+    }
+
+    let mut config = Config {
+        start_paused: false,
+    };
+
+    let args = Punctuated::<MetaNameValue, Token![,]>::parse_terminated.parse(args)?;
+    for arg in args {
+        let name = arg
+            .path
+            .get_ident()
+            .map(ToString::to_string)
+            .unwrap_or_default();
+
+        match name.as_str() {
+            "flavor" => match lit_str(&arg.value)?.as_str() {
+                "current_thread" => {}
+                "multi_thread" => {
+                    return Err(syn::Error::new_spanned(
+                        &arg.value,
+                        "the multi-thread runtime is not implemented, use `current_thread`",
+                    ));
+                }
+                flavor => {
+                    return Err(syn::Error::new_spanned(
+                        &arg.value,
+                        format!("unknown flavor `{flavor}`, expected `current_thread`"),
+                    ));
+                }
+            },
+            "start_paused" => config.start_paused = lit_bool(&arg.value)?,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &arg.path,
+                    "unknown attribute, expected one of `flavor`, `start_paused`",
+                ));
+            }
+        }
+    }
+
+    Ok(config)
+}
+
+fn lit_str(expr: &Expr) -> syn::Result<String> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Str(s), ..
+        }) => Ok(s.value()),
+        _ => Err(syn::Error::new_spanned(expr, "expected a string literal")),
+    }
+}
+
+fn lit_bool(expr: &Expr) -> syn::Result<bool> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Bool(b), ..
+        }) => Ok(b.value),
+        _ => Err(syn::Error::new_spanned(expr, "expected a boolean literal")),
+    }
+}
+
+fn expand(mut input: ItemFn, config: Config, is_test: bool) -> proc_macro2::TokenStream {
+    input.sig.asyncness = None;
+
+    let body = &input.block;
+    let start_paused = config.start_paused.then(|| quote! { .start_paused(true) });
+
+    // Errors in the body point at the body, not at the attribute.
+    let last_span = body
+        .stmts
+        .last()
+        .map_or_else(Span::call_site, |stmt| stmt.span());
+    let block = quote_spanned! {last_span=>
+        {
+            let body = async #body;
+
+            ::mini_runtime_v2::runtime::Builder::new_current_thread()
+                .enable_all()
+                #start_paused
+                .build()
+                .expect("Failed building the Runtime")
+                .block_on(body)
+        }
+    };
+    input.block = syn::parse2(block).expect("generated block is valid");
+
+    let header = is_test.then(|| quote! { #[::core::prelude::v1::test] });
+
+    quote! {
+        #header
+        #input
+    }
+}
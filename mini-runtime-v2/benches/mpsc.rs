@@ -0,0 +1,117 @@
+//! Compares bounded and unbounded mpsc channels.
+//!
+//! Run with `cargo bench --bench mpsc`. A producer task sends `MESSAGES`
+//! values as fast as it can while a consumer task receives them. For each
+//! channel, the benchmark reports the throughput and the largest number of
+//! values queued at once: a bounded channel trades some throughput (the
+//! producer has to wait for the consumer) for a queue that never grows past
+//! its capacity, while an unbounded channel buffers everything.
+
+use mini_runtime_v2::runtime::{self, Runtime};
+use mini_runtime_v2::sync::mpsc;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering::Relaxed};
+use std::time::{Duration, Instant};
+
+const MESSAGES: usize = 1_000_000;
+
+/// Tracks the number of values in the channel.
+#[derive(Default)]
+struct Queued {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl Queued {
+    fn sent(&self) {
+        let current = self.current.fetch_add(1, Relaxed) + 1;
+        self.peak.fetch_max(current, Relaxed);
+    }
+
+    fn received(&self) {
+        self.current.fetch_sub(1, Relaxed);
+    }
+}
+
+fn main() {
+    let rt = runtime::Builder::new_current_thread().build().unwrap();
+
+    println!("{MESSAGES} messages, 1 producer, 1 consumer");
+    for capacity in [1, 16, 1024] {
+        let (elapsed, peak) = bounded(&rt, capacity);
+        report(&format!("bounded({capacity})"), elapsed, peak);
+    }
+
+    let (elapsed, peak) = unbounded(&rt);
+    report("unbounded", elapsed, peak);
+}
+
+fn bounded(rt: &Runtime, capacity: usize) -> (Duration, usize) {
+    let queued = Arc::new(Queued::default());
+
+    let elapsed = rt.block_on({
+        let queued = queued.clone();
+        async move {
+            let (tx, mut rx) = mpsc::channel(capacity);
+            let start = Instant::now();
+
+            let producer = mini_runtime_v2::spawn({
+                let queued = queued.clone();
+                async move {
+                    for i in 0..MESSAGES {
+                        // Reserve first: the value is only counted once
+                        // there is room for it.
+                        let permit = tx.reserve().await.unwrap();
+                        queued.sent();
+                        permit.send(i);
+                    }
+                }
+            });
+
+            while rx.recv().await.is_some() {
+                queued.received();
+            }
+            producer.await.unwrap();
+
+            start.elapsed()
+        }
+    });
+
+    (elapsed, queued.peak.load(Relaxed))
+}
+
+fn unbounded(rt: &Runtime) -> (Duration, usize) {
+    let queued = Arc::new(Queued::default());
+
+    let elapsed = rt.block_on({
+        let queued = queued.clone();
+        async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let start = Instant::now();
+
+            let producer = mini_runtime_v2::spawn({
+                let queued = queued.clone();
+                async move {
+                    for i in 0..MESSAGES {
+                        queued.sent();
+                        tx.send(i).unwrap();
+                    }
+                }
+            });
+
+            while rx.recv().await.is_some() {
+                queued.received();
+            }
+            producer.await.unwrap();
+
+            start.elapsed()
+        }
+    });
+
+    (elapsed, queued.peak.load(Relaxed))
+}
+
+fn report(name: &str, elapsed: Duration, peak: usize) {
+    let rate = MESSAGES as f64 / elapsed.as_secs_f64() / 1_000_000.0;
+    println!("{name:>14}: {elapsed:>10.2?} ({rate:.2} M msg/s), peak queue length: {peak}");
+}
@@ -0,0 +1,91 @@
+//! Compares timer resolutions under many concurrent sleeps.
+//!
+//! Run with `cargo bench --bench timer`. `SLEEPS` tasks each sleep once,
+//! their deadlines spread over `SPREAD`. For each resolution, the benchmark
+//! reports the number of times the time driver fired timers, the timers
+//! fired per tick, the worker parks, and how late the sleeps completed: a
+//! coarser resolution fires the timers by larger batches and parks less,
+//! at the cost of precision.
+
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::time::{self, Duration, Instant};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering::Relaxed};
+
+const SLEEPS: u64 = 10_000;
+
+const SPREAD: Duration = Duration::from_millis(500);
+
+/// Lateness of the sleeps, in microseconds.
+#[derive(Default)]
+struct Lateness {
+    total: AtomicU64,
+    max: AtomicU64,
+}
+
+impl Lateness {
+    fn record(&self, late: Duration) {
+        let micros = late.as_micros() as u64;
+        self.total.fetch_add(micros, Relaxed);
+        self.max.fetch_max(micros, Relaxed);
+    }
+}
+
+fn main() {
+    println!("{SLEEPS} sleeps over {SPREAD:?}");
+    for resolution in [
+        Duration::ZERO,
+        Duration::from_millis(1),
+        Duration::from_millis(10),
+    ] {
+        run(resolution);
+    }
+}
+
+fn run(resolution: Duration) {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_time()
+        .timer_resolution(resolution)
+        .build()
+        .unwrap();
+
+    let lateness = Arc::new(Lateness::default());
+
+    let elapsed = rt.block_on({
+        let lateness = lateness.clone();
+        async move {
+            let start = Instant::now();
+
+            let sleeps: Vec<_> = (0..SLEEPS)
+                .map(|i| {
+                    let lateness = lateness.clone();
+                    let deadline = start + SPREAD * i as u32 / SLEEPS as u32;
+                    mini_runtime_v2::spawn(async move {
+                        time::sleep_until(deadline).await;
+                        lateness.record(deadline.elapsed());
+                    })
+                })
+                .collect();
+
+            for sleep in sleeps {
+                sleep.await.unwrap();
+            }
+
+            start.elapsed()
+        }
+    });
+
+    let metrics = rt.metrics();
+    let fired = metrics.timer_fired_count();
+    let ticks = metrics.timer_tick_count();
+    let parks = metrics.worker_park_count(0);
+    let mean_late = lateness.total.load(Relaxed) / SLEEPS;
+    let max_late = lateness.max.load(Relaxed);
+
+    println!(
+        "{:>8}: {elapsed:>10.2?}, {ticks:>5} ticks ({:>6.1} timers/tick), {parks:>5} parks, \
+         late by {mean_late} us on average, {max_late} us at most",
+        format!("{resolution:?}"),
+        fired as f64 / ticks.max(1) as f64,
+    );
+}
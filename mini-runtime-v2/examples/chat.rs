@@ -0,0 +1,181 @@
+//! A multi-client chat room.
+//!
+//! Run with `cargo run --example chat` and connect several clients with e.g.
+//! `nc 127.0.0.1 6146`. Each client first sends its name, then every line it
+//! sends is relayed to the other clients of the room.
+//!
+//! The room is a `broadcast` channel: every client task subscribes to it and
+//! forwards the messages of the others to its socket. A client task waits on
+//! several events at once with `select!`: a message of the room, the end of
+//! its connection and the shutdown of the server. The lines of a client are
+//! read by a task of its own, since `read_line` can't be dropped midway
+//! without losing data.
+//!
+//! A client that doesn't send its name within `NAME_TIMEOUT` is disconnected.
+//! A client too slow to keep up with the room misses the oldest messages and
+//! is told how many.
+//!
+//! Press ctrl-c to stop the server: the clients are told and disconnected.
+
+use mini_runtime_v2::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use mini_runtime_v2::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
+use mini_runtime_v2::net::{TcpListener, TcpStream};
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::select;
+use mini_runtime_v2::signal::unix::{SignalKind, signal};
+use mini_runtime_v2::sync::CancellationToken;
+use mini_runtime_v2::sync::broadcast::{self, error::RecvError};
+use mini_runtime_v2::task::{self, JoinSet};
+use mini_runtime_v2::time::{self, Duration};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+const NAME_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Number of messages kept for the clients falling behind.
+const ROOM_CAPACITY: usize = 64;
+
+/// A message of the room.
+#[derive(Clone)]
+struct Message {
+    /// Address of the client which sent it, `None` for the server.
+    from: Option<SocketAddr>,
+    text: Arc<str>,
+}
+
+impl Message {
+    fn server(text: String) -> Message {
+        Message {
+            from: None,
+            text: text.into(),
+        }
+    }
+}
+
+fn main() -> io::Result<()> {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    rt.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:6146").await?;
+        println!("Listening on {}", listener.local_addr()?);
+
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let (room, _) = broadcast::channel(ROOM_CAPACITY);
+        let shutdown = CancellationToken::new();
+        let mut clients = JoinSet::new();
+
+        loop {
+            select! {
+                accepted = listener.accept() => {
+                    let (socket, peer) = accepted?;
+                    let (room, shutdown) = (room.clone(), shutdown.clone());
+                    clients.spawn(async move {
+                        if let Err(e) = client(socket, peer, room, shutdown).await {
+                            eprintln!("{peer}: {e}");
+                        }
+                    });
+                }
+                // Reap the tasks of the clients which left.
+                Some(_) = clients.join_next() => {}
+                _ = interrupt.recv() => break,
+            }
+        }
+
+        println!("Shutting down, disconnecting {} client(s)", clients.len());
+        shutdown.cancel();
+        while clients.join_next().await.is_some() {}
+
+        Ok(())
+    })
+}
+
+async fn client(
+    socket: TcpStream,
+    peer: SocketAddr,
+    room: broadcast::Sender<Message>,
+    shutdown: CancellationToken,
+) -> io::Result<()> {
+    let (reader, mut writer) = socket.into_split();
+    let mut reader = BufReader::new(reader);
+
+    writer.write_all(b"Welcome! What is your name?\n").await?;
+    let mut name = String::new();
+    match time::timeout(NAME_TIMEOUT, reader.read_line(&mut name)).await {
+        Ok(Ok(0)) => return Ok(()),
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => return Err(e),
+        Err(_) => {
+            writer.write_all(b"Too slow, bye!\n").await?;
+            return Ok(());
+        }
+    }
+    let name: Arc<str> = name.trim().into();
+
+    // Subscribe before announcing the client, so that it sees its own
+    // announcement and every message sent afterwards.
+    let mut messages = room.subscribe();
+    let _ = room.send(Message::server(format!("* {name} joined the room")));
+    println!("{peer}: joined as {name}");
+
+    let mut lines =
+        task::spawn(relay_lines(reader, peer, name.clone(), room.clone())).abort_on_drop();
+
+    loop {
+        select! {
+            message = messages.recv() => match message {
+                Ok(message) if message.from == Some(peer) => {}
+                Ok(message) => send_line(&mut writer, &message.text).await?,
+                Err(RecvError::Lagged(n)) => {
+                    send_line(&mut writer, &format!("* missed {n} message(s)")).await?;
+                }
+                Err(RecvError::Closed) => break,
+            },
+            // The client closed its connection.
+            _ = &mut lines => break,
+            _ = shutdown.cancelled() => {
+                send_line(&mut writer, "* the server is shutting down").await?;
+                break;
+            }
+        }
+    }
+
+    let _ = room.send(Message::server(format!("* {name} left the room")));
+    println!("{peer}: left");
+    Ok(())
+}
+
+/// Sends the lines received from the client to the room, until the client
+/// closes the connection.
+async fn relay_lines(
+    mut reader: BufReader<OwnedReadHalf>,
+    peer: SocketAddr,
+    name: Arc<str>,
+    room: broadcast::Sender<Message>,
+) {
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+        match reader.read_line(&mut line).await {
+            Ok(0) => break,
+            Ok(_) => {}
+            Err(e) => {
+                eprintln!("{peer}: failed to read: {e}");
+                break;
+            }
+        }
+
+        let message = Message {
+            from: Some(peer),
+            text: format!("{name}: {}", line.trim_end()).into(),
+        };
+        let _ = room.send(message);
+    }
+}
+
+async fn send_line(writer: &mut OwnedWriteHalf, line: &str) -> io::Result<()> {
+    writer.write_all(line.as_bytes()).await?;
+    writer.write_all(b"\n").await
+}
@@ -0,0 +1,43 @@
+//! Finding out why a program hangs with `Handle::dump`.
+//!
+//! Run with `cargo run --example dump --features task_dump`. Two tasks wait
+//! for messages that are never sent, so `block_on` would never return. A
+//! watchdog thread prints a dump of the live tasks, which shows them in the
+//! `idle` state with their name and the place they were spawned from, then
+//! unblocks the program.
+
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::sync::mpsc;
+use mini_runtime_v2::task;
+use std::thread;
+use std::time::Duration;
+
+fn main() {
+    let rt = runtime::Builder::new_current_thread().build().unwrap();
+    let handle = rt.handle().clone();
+
+    let (done_tx, mut done_rx) = mpsc::channel::<()>(1);
+
+    thread::spawn(move || {
+        thread::sleep(Duration::from_millis(100));
+        eprintln!("block_on is still running after 100ms, dumping tasks:");
+        eprintln!("{}", handle.dump());
+
+        done_tx.try_send(()).unwrap();
+    });
+
+    rt.block_on(async {
+        // Both senders are kept alive, the receivers wait forever.
+        let (_tx1, mut rx1) = mpsc::channel::<u32>(1);
+        let (_tx2, mut rx2) = mpsc::channel::<u32>(1);
+
+        task::Builder::new()
+            .name("reader-1")
+            .spawn(async move { rx1.recv().await });
+        task::Builder::new()
+            .name("reader-2")
+            .spawn(async move { rx2.recv().await });
+
+        done_rx.recv().await;
+    });
+}
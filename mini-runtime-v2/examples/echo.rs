@@ -0,0 +1,230 @@
+//! A TCP echo server.
+//!
+//! Run with `cargo run --example echo` and connect with e.g.
+//! `nc 127.0.0.1 6142`: every line sent is echoed back.
+//!
+//! A connection idle for `IDLE_TIMEOUT` is closed. The idle timeouts of all
+//! the connections are tracked by the accept loop in a single `DelayQueue`:
+//! the connection tasks report their activity, which pushes their timeout
+//! back, and the connections whose timeout expires are cancelled.
+//!
+//! At most `MAX_CONNECTIONS` connections are served at once: the accept
+//! loop takes a semaphore permit for each connection, and stops accepting
+//! while none is left. The next clients wait in the listen backlog of the OS
+//! until a connection is closed.
+//!
+//! Press ctrl-c (or send `SIGTERM`) to stop the server: the open connections
+//! are closed and deregistered from the I/O driver before the process exits.
+
+use mini_runtime_v2::future::poll_fn;
+use mini_runtime_v2::io::{self, AsyncReadExt, AsyncWriteExt};
+use mini_runtime_v2::net::tcp::LimitedIncoming;
+use mini_runtime_v2::net::{TcpListener, TcpStream};
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::signal::unix::{Signal, SignalKind, signal};
+use mini_runtime_v2::stream::Stream;
+use mini_runtime_v2::sync::mpsc::{self, Receiver, Sender};
+use mini_runtime_v2::sync::{CancellationToken, OwnedSemaphorePermit, Semaphore};
+use mini_runtime_v2::task::JoinSet;
+use mini_runtime_v2::time::Duration;
+use mini_runtime_v2::time::delay_queue::{DelayQueue, Key};
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::pin::{Pin, pin};
+use std::sync::Arc;
+use std::task::{Poll, ready};
+
+const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+
+const MAX_CONNECTIONS: usize = 4;
+
+/// Reported by a connection task to the accept loop.
+enum Event {
+    /// Data was received from the peer.
+    Active(SocketAddr),
+    /// The connection is closed.
+    Closed(SocketAddr),
+}
+
+/// What the accept loop does next.
+enum Action {
+    Accept(TcpStream, OwnedSemaphorePermit),
+    Event(Event),
+    Expired(SocketAddr),
+    Shutdown,
+}
+
+/// A connection, as tracked by the accept loop.
+struct Connection {
+    /// Entry of the connection in the idle timeouts.
+    timeout: Key,
+    /// Cancels the connection task once idle.
+    token: CancellationToken,
+}
+
+fn main() -> io::Result<()> {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    rt.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:6142").await?;
+        println!("Listening on {}", listener.local_addr()?);
+
+        let mut incoming = listener
+            .incoming()
+            .with_limit(Arc::new(Semaphore::new(MAX_CONNECTIONS)));
+
+        let mut interrupt = signal(SignalKind::interrupt())?;
+        let mut terminate = signal(SignalKind::terminate())?;
+        let mut clients = JoinSet::new();
+
+        let (events_tx, mut events) = mpsc::channel(64);
+        let mut timeouts = DelayQueue::new();
+        let mut connections = HashMap::new();
+
+        loop {
+            let action = next_action(
+                &mut incoming,
+                &mut events,
+                &mut timeouts,
+                &mut interrupt,
+                &mut terminate,
+            )
+            .await?;
+
+            match action {
+                Action::Accept(socket, permit) => {
+                    // The client may already be gone.
+                    let Ok(peer) = socket.peer_addr() else {
+                        continue;
+                    };
+
+                    let token = CancellationToken::new();
+                    let timeout = timeouts.insert(peer, IDLE_TIMEOUT);
+                    connections.insert(
+                        peer,
+                        Connection {
+                            timeout,
+                            token: token.clone(),
+                        },
+                    );
+                    clients.spawn(echo(socket, peer, token, events_tx.clone(), permit));
+                }
+                Action::Event(Event::Active(peer)) => {
+                    if let Some(conn) = connections.get(&peer) {
+                        timeouts.reset(&conn.timeout, IDLE_TIMEOUT);
+                    }
+                }
+                Action::Event(Event::Closed(peer)) => {
+                    // Already gone if the connection was closed as idle.
+                    if let Some(conn) = connections.remove(&peer) {
+                        timeouts.remove(&conn.timeout);
+                    }
+                }
+                Action::Expired(peer) => {
+                    if let Some(conn) = connections.remove(&peer) {
+                        conn.token.cancel();
+                    }
+                }
+                Action::Shutdown => break,
+            }
+        }
+
+        println!("Shutting down, closing {} connection(s)", clients.len());
+
+        // Aborting a client task drops its socket, which deregisters it from
+        // the I/O driver.
+        clients.abort_all();
+        while clients.join_next().await.is_some() {}
+
+        Ok(())
+    })
+}
+
+/// Waits for the next connection, connection event, idle timeout or shutdown
+/// signal.
+async fn next_action(
+    incoming: &mut LimitedIncoming<'_>,
+    events: &mut Receiver<Event>,
+    timeouts: &mut DelayQueue<SocketAddr>,
+    interrupt: &mut Signal,
+    terminate: &mut Signal,
+) -> io::Result<Action> {
+    poll_fn(|cx| {
+        if interrupt.poll_recv(cx).is_ready() || terminate.poll_recv(cx).is_ready() {
+            return Poll::Ready(Ok(Action::Shutdown));
+        }
+
+        // The accept loop holds a sender: the channel is never closed.
+        if let Poll::Ready(Some(event)) = events.poll_recv(cx) {
+            return Poll::Ready(Ok(Action::Event(event)));
+        }
+
+        // `None` when no connection is open: nothing to wait for.
+        if let Poll::Ready(Some(expired)) = timeouts.poll_expired(cx) {
+            return Poll::Ready(Ok(Action::Expired(expired.into_inner())));
+        }
+
+        // Pending while `MAX_CONNECTIONS` connections are open.
+        match ready!(Pin::new(&mut *incoming).poll_next(cx)) {
+            Some(Ok((socket, permit))) => Poll::Ready(Ok(Action::Accept(socket, permit))),
+            Some(Err(e)) => Poll::Ready(Err(e)),
+            // The semaphore is never closed.
+            None => Poll::Ready(Ok(Action::Shutdown)),
+        }
+    })
+    .await
+}
+
+/// Echoes the data received from `peer`, holding `_permit` until the
+/// connection is closed.
+async fn echo(
+    mut socket: TcpStream,
+    peer: SocketAddr,
+    token: CancellationToken,
+    events: Sender<Event>,
+    _permit: OwnedSemaphorePermit,
+) {
+    let mut buf = vec![0; 1024];
+    let mut echoed = 0;
+
+    loop {
+        let read = {
+            let mut read = pin!(socket.read(&mut buf));
+            let mut cancelled = pin!(token.cancelled());
+
+            poll_fn(|cx| {
+                if cancelled.as_mut().poll(cx).is_ready() {
+                    return Poll::Ready(None);
+                }
+                read.as_mut().poll(cx).map(Some)
+            })
+            .await
+        };
+
+        let n = match read {
+            None => {
+                println!("{peer}: idle for {IDLE_TIMEOUT:?}, closing");
+                break;
+            }
+            Some(Ok(0)) => break,
+            Some(Ok(n)) => n,
+            Some(Err(e)) => {
+                eprintln!("{peer}: failed to read: {e}");
+                break;
+            }
+        };
+
+        let _ = events.send(Event::Active(peer)).await;
+        if let Err(e) = socket.write_all(&buf[..n]).await {
+            eprintln!("{peer}: failed to write: {e}");
+            break;
+        }
+        echoed += n;
+    }
+
+    println!("{peer}: echoed {echoed} bytes");
+    let _ = events.send(Event::Closed(peer)).await;
+}
@@ -0,0 +1,78 @@
+//! A TCP file server.
+//!
+//! Run with `cargo run --example file_server [ROOT]` and request a file with
+//! e.g. `echo Cargo.toml | nc 127.0.0.1 6145`. The server reads one path per
+//! connection, relative to `ROOT` (the current directory by default), and
+//! streams the file back.
+//!
+//! Files are read through `fs::File`: every read runs on the blocking pool, so
+//! a slow disk doesn't stall the other connections.
+
+use mini_runtime_v2::fs::File;
+use mini_runtime_v2::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use mini_runtime_v2::net::{TcpListener, TcpStream};
+use mini_runtime_v2::runtime;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Arc;
+
+fn main() -> io::Result<()> {
+    let root = Arc::new(PathBuf::from(
+        std::env::args().nth(1).unwrap_or_else(|| ".".to_string()),
+    ));
+
+    let rt = runtime::Builder::new_current_thread().enable_io().build()?;
+
+    rt.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:6145").await?;
+        println!("Serving {} on {}", root.display(), listener.local_addr()?);
+
+        loop {
+            let (socket, peer) = listener.accept().await?;
+            let root = root.clone();
+
+            mini_runtime_v2::spawn(async move {
+                match serve(&root, socket).await {
+                    Ok(Some((path, n))) => println!("{peer}: sent {path} ({n} bytes)"),
+                    Ok(None) => {}
+                    Err(e) => eprintln!("{peer}: {e}"),
+                }
+            });
+        }
+    })
+}
+
+/// Reads the requested path and sends the file, returns the path and the
+/// number of bytes sent.
+async fn serve(root: &Path, socket: TcpStream) -> io::Result<Option<(String, u64)>> {
+    let mut reader = BufReader::new(&socket);
+    let mut writer = &socket;
+
+    let mut line = String::new();
+    if reader.read_line(&mut line).await? == 0 {
+        return Ok(None);
+    }
+    let requested = line.trim().to_string();
+
+    // Only plain relative paths: no `..`, no absolute path escaping `root`.
+    let relative = Path::new(&requested);
+    if !relative
+        .components()
+        .all(|c| matches!(c, Component::Normal(_) | Component::CurDir))
+    {
+        writer.write_all(b"ERROR invalid path\n").await?;
+        return writer.shutdown().await.map(|_| None);
+    }
+
+    let mut file = match File::open(root.join(relative)).await {
+        Ok(file) => file,
+        Err(e) => {
+            writer.write_all(format!("ERROR {e}\n").as_bytes()).await?;
+            return writer.shutdown().await.map(|_| None);
+        }
+    };
+
+    let n = io::copy(&mut file, &mut writer).await?;
+    writer.shutdown().await?;
+
+    Ok(Some((requested, n)))
+}
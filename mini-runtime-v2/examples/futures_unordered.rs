@@ -0,0 +1,49 @@
+//! Task-level versus sub-task-level concurrency.
+//!
+//! Run with `cargo run --example futures_unordered`. The same three sleeps
+//! run concurrently twice: first as three tasks of a `JoinSet`, each with
+//! its own task id, then as three futures of a `FuturesUnordered`, all
+//! polled by a single task. Both take as long as the longest sleep.
+
+use mini_runtime_v2::future::FuturesUnordered;
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::stream::StreamExt;
+use mini_runtime_v2::task::{self, JoinSet};
+use mini_runtime_v2::time::{self, Duration};
+use std::time::Instant;
+
+async fn nap(ms: u64) -> String {
+    time::sleep(Duration::from_millis(ms)).await;
+    format!("slept {ms}ms in task {}", task::id())
+}
+
+fn main() {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let start = Instant::now();
+        let mut set = JoinSet::new();
+        for ms in [30, 10, 20] {
+            set.spawn(nap(ms));
+        }
+        while let Some(res) = set.join_next().await {
+            println!("JoinSet: {}", res.unwrap());
+        }
+        println!("tasks done in {:?}\n", start.elapsed());
+
+        // Polled by a single task.
+        task::spawn(async {
+            let start = Instant::now();
+            let mut futures: FuturesUnordered<_> = [30, 10, 20].into_iter().map(nap).collect();
+            while let Some(msg) = futures.next().await {
+                println!("FuturesUnordered: {msg}");
+            }
+            println!("futures done in {:?}", start.elapsed());
+        })
+        .await
+        .unwrap();
+    });
+}
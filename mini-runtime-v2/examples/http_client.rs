@@ -0,0 +1,131 @@
+//! An HTTP/1.1 client, with a timeout and retries.
+//!
+//! Run with `cargo run --example http_client [HOST[:PORT]] [PATH]`, e.g.
+//! against `python3 -m http.server` started in another terminal (the
+//! default is `localhost:8000 /`). The response's status line and headers
+//! are printed, followed by the size of its body.
+//!
+//! Each attempt, from resolving the host to reading the last byte of the
+//! response, is bounded by `time::timeout`. Failed connections, timeouts and
+//! `5xx` responses are retried with jittered exponential backoff, see the
+//! `retry` example; other errors and responses are final.
+
+use mini_runtime_v2::io::{self, AsyncReadExt, AsyncWriteExt};
+use mini_runtime_v2::net::TcpStream;
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::time::{self, Duration};
+
+const ATTEMPT_TIMEOUT: Duration = Duration::from_secs(5);
+const MAX_ATTEMPTS: u32 = 4;
+const INITIAL_BACKOFF: Duration = Duration::from_millis(100);
+
+struct Response {
+    status: u16,
+    head: String,
+    body: Vec<u8>,
+}
+
+fn main() -> io::Result<()> {
+    let mut args = std::env::args().skip(1);
+    let host = args.next().unwrap_or_else(|| "localhost:8000".to_string());
+    let path = args.next().unwrap_or_else(|| "/".to_string());
+
+    let rt = runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    rt.block_on(async {
+        let response = get_with_retries(&host, &path).await?;
+        println!("{}", response.head);
+        println!("({} bytes of body)", response.body.len());
+        Ok(())
+    })
+}
+
+async fn get_with_retries(host: &str, path: &str) -> io::Result<Response> {
+    let mut backoff = INITIAL_BACKOFF;
+    let mut attempt = 1;
+
+    loop {
+        let error = match time::timeout(ATTEMPT_TIMEOUT, get(host, path)).await {
+            Ok(Ok(response)) if response.status < 500 => return Ok(response),
+            Ok(Ok(response)) if attempt == MAX_ATTEMPTS => {
+                // Give the last response to the caller, as is.
+                eprintln!(
+                    "attempt {attempt}: server error {}, giving up",
+                    response.status
+                );
+                return Ok(response);
+            }
+            Ok(Ok(response)) => io::Error::other(format!("server error {}", response.status)),
+            Ok(Err(e)) if is_transient(&e) => e,
+            Ok(Err(e)) => return Err(e),
+            Err(_) => io::Error::new(
+                io::ErrorKind::TimedOut,
+                format!("no response within {ATTEMPT_TIMEOUT:?}"),
+            ),
+        };
+
+        if attempt == MAX_ATTEMPTS {
+            eprintln!("attempt {attempt}: {error}, giving up");
+            return Err(error);
+        }
+
+        // Sleep between `backoff / 2` and `backoff`.
+        let half = backoff.as_millis() as u64 / 2;
+        let delay = Duration::from_millis(half + runtime::rng().gen_range(0..half + 1));
+        eprintln!("attempt {attempt}: {error}, retrying in {delay:?}");
+        time::sleep(delay).await;
+
+        backoff *= 2;
+        attempt += 1;
+    }
+}
+
+/// Whether a new attempt may succeed where this one failed.
+fn is_transient(e: &io::Error) -> bool {
+    matches!(
+        e.kind(),
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::UnexpectedEof
+            | io::ErrorKind::TimedOut
+    )
+}
+
+/// Sends a `GET` request, reads the whole response.
+async fn get(host: &str, path: &str) -> io::Result<Response> {
+    let mut stream = TcpStream::connect(host).await?;
+
+    // Without keep-alive, the end of the body is the end of the stream.
+    let request = format!(
+        "GET {path} HTTP/1.1\r\nHost: {host}\r\nUser-Agent: mini-runtime\r\nConnection: close\r\n\r\n"
+    );
+    stream.write_all(request.as_bytes()).await?;
+
+    let mut raw = Vec::new();
+    stream.read_to_end(&mut raw).await?;
+    parse(raw)
+}
+
+fn parse(mut raw: Vec<u8>) -> io::Result<Response> {
+    let invalid = |msg| io::Error::new(io::ErrorKind::InvalidData, msg);
+
+    let end = match raw.windows(4).position(|w| w == b"\r\n\r\n") {
+        Some(end) => end,
+        None => return Err(io::ErrorKind::UnexpectedEof.into()),
+    };
+    let body = raw.split_off(end + 4);
+    raw.truncate(end);
+    let head = String::from_utf8(raw).map_err(|_| invalid("response head is not UTF-8"))?;
+
+    // E.g. `HTTP/1.1 200 OK`.
+    let status = head
+        .split(' ')
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or_else(|| invalid("malformed status line"))?;
+
+    Ok(Response { status, head, body })
+}
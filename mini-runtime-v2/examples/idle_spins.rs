@@ -0,0 +1,50 @@
+//! Parking versus spinning.
+//!
+//! Run with `cargo run --release --example idle_spins`. Another thread sends
+//! messages through a channel, a task of the runtime receives them. Every
+//! message finds the worker idle: without spinning, it parks and is unparked
+//! once per message. With `max_idle_spins`, the worker often picks up the
+//! next message before parking, at the cost of CPU time spent spinning.
+
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+const MESSAGES: u32 = 20_000;
+
+fn run(max_idle_spins: u32) {
+    let rt = runtime::Builder::new_current_thread()
+        .max_idle_spins(max_idle_spins)
+        .build()
+        .unwrap();
+
+    let (tx, mut rx) = mpsc::channel(MESSAGES as usize);
+    let producer = thread::spawn(move || {
+        for i in 0..MESSAGES {
+            tx.try_send(i).unwrap();
+            // Leave the worker idle between two messages.
+            let start = Instant::now();
+            while start.elapsed() < Duration::from_micros(5) {}
+        }
+    });
+
+    let start = Instant::now();
+    rt.block_on(async move { while rx.recv().await.is_some() {} });
+    let elapsed = start.elapsed();
+    producer.join().unwrap();
+
+    let metrics = rt.metrics();
+    println!(
+        "max_idle_spins = {max_idle_spins:>5}: {elapsed:>10.2?}, parks: {:>6}, unparks: {:>6}, idle spins: {:>9}",
+        metrics.worker_park_count(0),
+        metrics.worker_unpark_count(0),
+        metrics.worker_idle_spin_count(0),
+    );
+}
+
+fn main() {
+    for max_idle_spins in [0, 100, 10_000] {
+        run(max_idle_spins);
+    }
+}
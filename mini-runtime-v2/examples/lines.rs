@@ -0,0 +1,53 @@
+//! A line-delimited TCP server.
+//!
+//! Run with `cargo run --example lines` and connect with e.g.
+//! `nc 127.0.0.1 6143`. Each line received is answered with its number and
+//! the upper-cased line, `QUIT` closes the connection.
+
+use mini_runtime_v2::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+use mini_runtime_v2::net::{TcpListener, TcpStream};
+use mini_runtime_v2::runtime;
+
+fn main() -> io::Result<()> {
+    let rt = runtime::Builder::new_current_thread().enable_io().build()?;
+
+    rt.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:6143").await?;
+        println!("Listening on {}", listener.local_addr()?);
+
+        loop {
+            let (socket, peer) = listener.accept().await?;
+
+            mini_runtime_v2::spawn(async move {
+                if let Err(e) = process(socket).await {
+                    eprintln!("{peer}: {e}");
+                }
+            });
+        }
+    })
+}
+
+async fn process(socket: TcpStream) -> io::Result<()> {
+    let mut reader = BufReader::new(&socket);
+    let mut writer = BufWriter::new(&socket);
+    let mut line = String::new();
+
+    for n in 1.. {
+        line.clear();
+        if reader.read_line(&mut line).await? == 0 {
+            break;
+        }
+
+        let line = line.trim_end();
+        if line == "QUIT" {
+            break;
+        }
+
+        writer
+            .write_all(format!("{n}: {}\n", line.to_uppercase()).as_bytes())
+            .await?;
+        writer.flush().await?;
+    }
+
+    writer.shutdown().await
+}
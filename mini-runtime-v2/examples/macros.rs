@@ -0,0 +1,29 @@
+//! Entering the runtime with the attribute macros.
+//!
+//! Run with `cargo run --example macros --features macros`. `#[main]` builds
+//! a current-thread runtime with all the drivers enabled and blocks on the
+//! body of `main`, like `#[tokio::main(flavor = "current_thread")]`.
+//!
+//! The clock starts paused: the three tasks sleep for hours of virtual time,
+//! yet the program exits right away, the timers firing in deadline order.
+
+use mini_runtime_v2::task::JoinSet;
+use mini_runtime_v2::time::{self, Duration};
+use std::time::Instant;
+
+#[mini_runtime_v2::main(flavor = "current_thread", start_paused = true)]
+async fn main() {
+    let start = Instant::now();
+    let mut set = JoinSet::new();
+
+    for hours in [3, 1, 2] {
+        set.spawn(async move {
+            time::sleep(Duration::from_secs(hours * 3600)).await;
+            println!("woke up after {hours}h");
+        });
+    }
+
+    while set.join_next().await.is_some() {}
+
+    println!("done in {:?} of wall-clock time", start.elapsed());
+}
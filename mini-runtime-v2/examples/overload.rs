@@ -0,0 +1,60 @@
+//! Backpressure on cross-thread spawns.
+//!
+//! Run with `cargo run --example overload`. A producer thread spawns tasks
+//! faster than the runtime polls them. The runtime is built with
+//! `max_pending_spawns`: the first producer sheds the load with `try_spawn`,
+//! the second one is slowed down to the pace of the runtime by `spawn`. The
+//! injection queue never holds more than the bound.
+
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::time::{self, Duration};
+use std::thread;
+
+const MAX_PENDING_SPAWNS: usize = 16;
+
+/// A task keeping the runtime busy for a while.
+async fn work() {
+    thread::sleep(Duration::from_micros(200));
+}
+
+fn main() {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_time()
+        .max_pending_spawns(MAX_PENDING_SPAWNS)
+        .build()
+        .unwrap();
+
+    let handle = rt.handle().clone();
+    let shedding = thread::spawn(move || {
+        let mut rejected = 0;
+        for _ in 0..1000 {
+            if handle.try_spawn(work()).is_err() {
+                rejected += 1;
+            }
+        }
+        rejected
+    });
+
+    let handle = rt.handle().clone();
+    let blocking = thread::spawn(move || {
+        let mut max_depth = 0;
+        for _ in 0..1000 {
+            handle.spawn(work());
+            max_depth = max_depth.max(handle.metrics().injection_queue_depth());
+        }
+        max_depth
+    });
+
+    // Drive the runtime while the producers run.
+    rt.block_on(async {
+        while !(shedding.is_finished() && blocking.is_finished()) {
+            time::sleep(Duration::from_millis(10)).await;
+        }
+    });
+
+    println!("try_spawn rejected {} tasks", shedding.join().unwrap());
+    println!(
+        "spawn waited for room, max queue depth: {} (bound: {MAX_PENDING_SPAWNS})",
+        blocking.join().unwrap()
+    );
+}
@@ -0,0 +1,60 @@
+//! Driving a Unix pipe with `AsyncFd`.
+//!
+//! Run with `cargo run --example pipe`. The runtime has no pipe type, but
+//! `mio` has: both ends are registered with the reactor through `AsyncFd`,
+//! a task writes messages to the pipe while the main task reads them.
+
+use mini_runtime_v2::io::{self, AsyncFd, Interest};
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::task;
+use mini_runtime_v2::time::{self, Duration};
+use mio::unix::pipe;
+use std::io::{Read, Write};
+
+fn main() -> io::Result<()> {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    rt.block_on(async {
+        let (tx, rx) = pipe::new()?;
+        let tx = AsyncFd::with_interest(tx, Interest::WRITABLE)?;
+        let rx = AsyncFd::with_interest(rx, Interest::READABLE)?;
+
+        task::spawn(async move {
+            for i in 0..3 {
+                let msg = format!("message {i}\n");
+
+                loop {
+                    let mut guard = tx.writable().await?;
+                    match guard.try_io(|tx| tx.get_ref().write(msg.as_bytes())) {
+                        Ok(result) => {
+                            result?;
+                            break;
+                        }
+                        Err(_would_block) => continue,
+                    }
+                }
+
+                time::sleep(Duration::from_millis(100)).await;
+            }
+
+            // Dropping the write end closes the pipe.
+            io::Result::Ok(())
+        });
+
+        let mut buf = [0; 1024];
+        loop {
+            let mut guard = rx.readable().await?;
+            match guard.try_io(|rx| rx.get_ref().read(&mut buf)) {
+                Ok(Ok(0)) => break,
+                Ok(Ok(n)) => print!("read: {}", String::from_utf8_lossy(&buf[..n])),
+                Ok(Err(e)) => return Err(e),
+                Err(_would_block) => continue,
+            }
+        }
+
+        println!("write end closed");
+        Ok(())
+    })
+}
@@ -0,0 +1,60 @@
+//! Detecting a task that blocks the executor with the poll time histogram.
+//!
+//! Run with `cargo run --example poll_time`. A hundred well-behaved tasks
+//! yield to the scheduler between short computations, while one task calls
+//! `std::thread::sleep`, blocking the only worker thread. The histogram of
+//! the poll durations shows the well-behaved polls in the microsecond
+//! buckets and the blocking ones far to the right.
+//!
+//! The histogram doesn't tell which task blocks. Instrumenting the suspects
+//! with `FutureExt::instrument_poll` does: each prints a summary of its polls
+//! when it is dropped.
+
+use mini_runtime_v2::future::FutureExt;
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::task::JoinSet;
+use mini_runtime_v2::time::{self, Duration};
+
+fn main() {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_time()
+        .enable_metrics_poll_time_histogram()
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let mut set = JoinSet::new();
+
+        for i in 0..100u64 {
+            let compute = async move {
+                for _ in 0..10 {
+                    std::hint::black_box((0..1_000).fold(i, u64::wrapping_add));
+                    time::sleep(Duration::from_millis(1)).await;
+                }
+            };
+
+            match i {
+                0 => set.spawn(compute.instrument_poll("compute")),
+                _ => set.spawn(compute),
+            };
+        }
+
+        let blocking = async {
+            for _ in 0..3 {
+                // Don't do this: use `task::spawn_blocking` instead.
+                std::thread::sleep(Duration::from_millis(20));
+                time::sleep(Duration::from_millis(1)).await;
+            }
+        };
+        set.spawn(blocking.instrument_poll("blocking"));
+
+        while set.join_next().await.is_some() {}
+    });
+
+    let histogram = rt.metrics().poll_time_histogram();
+    println!("{} polls:", histogram.total());
+    print!("{histogram}");
+
+    let slow = histogram.count_at_least(Duration::from_millis(10));
+    println!("{slow} polls blocked the executor for 10ms or more");
+}
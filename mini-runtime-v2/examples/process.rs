@@ -0,0 +1,49 @@
+//! Spawns child processes on the runtime.
+//!
+//! Run with `cargo run --example process`: pipes a few lines through `sort`
+//! reading its output asynchronously, then collects the output of `ls`.
+
+use mini_runtime_v2::io::{self, AsyncBufReadExt, AsyncWriteExt, BufReader};
+use mini_runtime_v2::process::Command;
+use mini_runtime_v2::runtime;
+use std::process::Stdio;
+
+fn main() -> io::Result<()> {
+    let rt = runtime::Builder::new_current_thread().enable_io().build()?;
+
+    rt.block_on(async {
+        let mut child = Command::new("sort")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()?;
+
+        let mut stdin = child.stdin.take().expect("stdin is piped");
+        let stdout = child.stdout.take().expect("stdout is piped");
+
+        // `sort` only writes once its input is closed: feed it from a
+        // separate task, dropping stdin when done.
+        let writer = mini_runtime_v2::spawn(async move {
+            stdin.write_all(b"pear\napple\nbanana\n").await?;
+            stdin.shutdown().await
+        });
+
+        let mut reader = BufReader::new(stdout);
+        let mut line = String::new();
+        while reader.read_line(&mut line).await? != 0 {
+            print!("sorted: {line}");
+            line.clear();
+        }
+
+        writer.await??;
+        println!("sort exited with {}", child.wait().await?);
+
+        let output = Command::new("ls").arg("-1").output().await?;
+        println!(
+            "ls exited with {} and printed {} line(s)",
+            output.status,
+            output.stdout.iter().filter(|b| **b == b'\n').count()
+        );
+
+        Ok(())
+    })
+}
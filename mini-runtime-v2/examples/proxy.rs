@@ -0,0 +1,57 @@
+//! A TCP proxy, forwarding every connection to a server.
+//!
+//! Start the echo example first (`cargo run --example echo`), then run this
+//! one with `cargo run --example proxy` and connect to `127.0.0.1:8081`.
+//!
+//! Each direction of the connection is copied by its own task, using the
+//! owned halves returned by `TcpStream::into_split`.
+//!
+//! The server is given by host name: `localhost` is resolved on the blocking
+//! pool, and its addresses, e.g. `::1` and `127.0.0.1`, are raced by
+//! `TcpStream::connect`.
+
+use mini_runtime_v2::io::{self, AsyncWriteExt};
+use mini_runtime_v2::net::{TcpListener, TcpStream};
+use mini_runtime_v2::runtime;
+
+const LISTEN_ADDR: &str = "127.0.0.1:8081";
+const SERVER_ADDR: &str = "localhost:6142";
+
+fn main() -> io::Result<()> {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    rt.block_on(async {
+        let listener = TcpListener::bind(LISTEN_ADDR).await?;
+        println!("Listening on {LISTEN_ADDR}, proxying to {SERVER_ADDR}");
+
+        loop {
+            let (inbound, peer) = listener.accept().await?;
+
+            mini_runtime_v2::spawn(async move {
+                if let Err(e) = transfer(inbound).await {
+                    eprintln!("{peer}: failed to transfer: {e}");
+                }
+            });
+        }
+    })
+}
+
+async fn transfer(inbound: TcpStream) -> io::Result<()> {
+    let outbound = TcpStream::connect(SERVER_ADDR).await?;
+
+    let (mut ri, mut wi) = inbound.into_split();
+    let (mut ro, mut wo) = outbound.into_split();
+
+    let client_to_server = mini_runtime_v2::spawn(async move {
+        io::copy(&mut ri, &mut wo).await?;
+        wo.shutdown().await
+    });
+
+    // The server to client direction runs on the current task.
+    io::copy(&mut ro, &mut wi).await?;
+    wi.shutdown().await?;
+
+    client_to_server.await?
+}
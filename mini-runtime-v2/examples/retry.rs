@@ -0,0 +1,63 @@
+//! Retries with jittered exponential backoff.
+//!
+//! Run with `cargo run --example retry`. Several tasks call a flaky
+//! operation, retrying on failure. Without jitter, tasks failing together
+//! would retry together and fail together again; the random jitter from
+//! `runtime::rng()` spreads their retries over time.
+
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::task::JoinSet;
+use mini_runtime_v2::time::{self, Duration};
+use std::time::Instant;
+
+const MAX_ATTEMPTS: u32 = 5;
+
+/// Fails two times out of three.
+async fn flaky() -> Result<(), &'static str> {
+    time::sleep(Duration::from_millis(5)).await;
+    if runtime::rng().gen_range(0..3) == 0 {
+        Ok(())
+    } else {
+        Err("unavailable")
+    }
+}
+
+async fn with_retries(client: u32, start: Instant) {
+    let mut backoff = Duration::from_millis(10);
+    for attempt in 1..=MAX_ATTEMPTS {
+        match flaky().await {
+            Ok(()) => {
+                println!(
+                    "client {client}: succeeded on attempt {attempt} at {:?}",
+                    start.elapsed()
+                );
+                return;
+            }
+            Err(e) if attempt < MAX_ATTEMPTS => {
+                // Sleep between `backoff / 2` and `backoff`.
+                let half = backoff.as_millis() as u64 / 2;
+                let jitter = runtime::rng().gen_range(0..half + 1);
+                println!("client {client}: {e}, retrying in {}ms", half + jitter);
+                time::sleep(Duration::from_millis(half + jitter)).await;
+                backoff *= 2;
+            }
+            Err(e) => println!("client {client}: {e}, giving up"),
+        }
+    }
+}
+
+fn main() {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let start = Instant::now();
+        let mut clients = JoinSet::new();
+        for client in 0..4 {
+            clients.spawn(with_retries(client, start));
+        }
+        while clients.join_next().await.is_some() {}
+    });
+}
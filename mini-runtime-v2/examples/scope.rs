@@ -0,0 +1,66 @@
+//! Structured concurrency with `task::scope`.
+//!
+//! Run with `cargo run --example scope`. The first scope waits for all its
+//! workers even though its body returns right away. The second one gives its
+//! workers a deadline: the slow ones are cancelled, and a worker watching the
+//! scope's token gets to clean up before it stops.
+
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::sync::mpsc;
+use mini_runtime_v2::task;
+use mini_runtime_v2::time::{self, Duration, Instant};
+
+fn main() {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let start = Instant::now();
+        let (tx, mut rx) = mpsc::channel(3);
+
+        task::scope(async |scope| {
+            for (id, delay) in [(1, 30), (2, 10), (3, 20)] {
+                let tx = tx.clone();
+                scope.spawn(async move {
+                    time::sleep(Duration::from_millis(delay)).await;
+                    tx.send(id).await.unwrap();
+                });
+            }
+            println!("all workers spawned");
+        })
+        .await;
+
+        drop(tx);
+        let mut done = Vec::new();
+        while let Some(id) = rx.recv().await {
+            done.push(id);
+        }
+        println!(
+            "scope returned after {:?}, workers done in order {done:?}",
+            start.elapsed()
+        );
+
+        let start = Instant::now();
+        task::scope(async |scope| {
+            for (id, delay) in [(1, 10), (2, 500), (3, 1000)] {
+                scope.spawn(async move {
+                    time::sleep(Duration::from_millis(delay)).await;
+                    println!("worker {id} finished");
+                });
+            }
+
+            let token = scope.token();
+            scope.spawn(async move {
+                token.cancelled().await;
+                println!("cleaning up after cancellation");
+            });
+
+            time::sleep(Duration::from_millis(50)).await;
+            scope.cancel();
+        })
+        .await;
+        println!("cancelled scope returned after {:?}", start.elapsed());
+    });
+}
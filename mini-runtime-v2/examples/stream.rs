@@ -0,0 +1,71 @@
+//! Iterating over asynchronous values with `Stream`.
+//!
+//! Run with `cargo run --example stream`. A producer task sends numbers on a
+//! channel at an increasing pace; the consumer chains `filter`, `map` and
+//! `take` on the receiver like it would on an iterator. A second consumer
+//! uses `timeout` to notice when the producer goes quiet, and gives up: the
+//! producer, waiting on `Sender::closed`, stops generating messages right
+//! away instead of finding out on its next `send`.
+
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::select;
+use mini_runtime_v2::stream::StreamExt;
+use mini_runtime_v2::sync::mpsc;
+use mini_runtime_v2::time::{self, Duration};
+
+fn main() {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let (tx, rx) = mpsc::channel(4);
+
+        mini_runtime_v2::spawn(async move {
+            for i in 0.. {
+                if tx.send(i).await.is_err() {
+                    // The consumer is gone
+                    break;
+                }
+            }
+        });
+
+        // `take` drops the receiver after 5 items, which stops the producer.
+        let mut squares = rx.filter(|n| n % 2 == 0).map(|n| n * n).take(5);
+        while let Some(n) = squares.next().await {
+            println!("square of an even number: {n}");
+        }
+
+        let (tx, rx) = mpsc::channel(4);
+
+        let producer = mini_runtime_v2::spawn(async move {
+            for delay in [10, 20, 300, 10] {
+                select! {
+                    _ = time::sleep(Duration::from_millis(delay)) => {
+                        let _ = tx.send(delay).await;
+                    }
+                    _ = tx.closed() => {
+                        println!("the consumer is gone, stop producing");
+                        return;
+                    }
+                }
+            }
+        });
+
+        let mut messages = rx.timeout(Duration::from_millis(100));
+        while let Some(res) = messages.next().await {
+            match res {
+                Ok(delay) => println!("received a message after {delay} ms"),
+                Err(e) => {
+                    println!("no message: {e}");
+                    break;
+                }
+            }
+        }
+
+        // Dropping the receiver closes the channel.
+        drop(messages);
+        producer.await.unwrap();
+    });
+}
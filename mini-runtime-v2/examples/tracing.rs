@@ -0,0 +1,133 @@
+//! Telling apart the logs of concurrent tasks.
+//!
+//! Run with `cargo run --example tracing --features tracing`. Each poll of a
+//! task runs in a `task` span carrying its id and name, so the events of the
+//! three interleaved workers are prefixed with `task{id=… name=…}` without
+//! passing the id around, like the thread ids printed by the tls-rust
+//! example.
+//!
+//! The scheduler tracing reports each tick of the scheduling loop in the
+//! `worker` span: the polls of the workers are grouped by tick, and the
+//! `driver_checked` ticks are the ones where the timers fired.
+//!
+//! A program would normally install `tracing_subscriber::fmt`, the subscriber
+//! below only prints the innermost span of each event.
+
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::task;
+use mini_runtime_v2::time::sleep;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber, info};
+
+fn main() {
+    tracing::subscriber::set_global_default(Printer::default()).unwrap();
+
+    let rt = runtime::Builder::new_current_thread()
+        .enable_all()
+        .enable_scheduler_tracing(true)
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let workers: Vec<_> = (1..=3)
+            .map(|worker| {
+                task::Builder::new()
+                    .name(&format!("worker-{worker}"))
+                    .spawn(async move {
+                        for step in 1..=2 {
+                            info!(worker, step, "working");
+                            sleep(Duration::from_millis(10 * worker)).await;
+                        }
+                        info!(worker, "done");
+                    })
+            })
+            .collect();
+
+        // `block_on` is not a task: its events have no task span.
+        info!("waiting for the workers");
+        for worker in workers {
+            worker.await.unwrap();
+        }
+    });
+}
+
+thread_local! {
+    /// The spans entered on this thread, innermost last.
+    static ENTERED: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Prints each event as `span{fields}: message fields`.
+#[derive(Default)]
+struct Printer {
+    next_id: AtomicU64,
+    /// The formatted name and fields of the open spans.
+    spans: Mutex<HashMap<u64, String>>,
+}
+
+impl Subscriber for Printer {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let mut fields = Fields::default();
+        attrs.record(&mut fields);
+        let span = format!("{}{{{}}}", attrs.metadata().name(), fields.0.trim_start());
+        self.spans.lock().unwrap().insert(id, span);
+        Id::from_u64(id)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let span = ENTERED.with_borrow(|entered| {
+            let spans = self.spans.lock().unwrap();
+            entered
+                .last()
+                .and_then(|id| spans.get(id))
+                .map(|span| format!("{}: ", span))
+                .unwrap_or_default()
+        });
+        println!("{}{}", span, fields.0.trim_start());
+    }
+
+    fn enter(&self, id: &Id) {
+        ENTERED.with_borrow_mut(|entered| entered.push(id.into_u64()));
+    }
+
+    fn exit(&self, _: &Id) {
+        ENTERED.with_borrow_mut(|entered| entered.pop());
+    }
+
+    fn try_close(&self, id: Id) -> bool {
+        self.spans.lock().unwrap().remove(&id.into_u64());
+        true
+    }
+}
+
+/// Formats fields as `name=value`, the message without its name.
+#[derive(Default)]
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.0, " {:?}", value);
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! A UDP echo server.
+//!
+//! Run with `cargo run --example udp_echo` and send datagrams with e.g.
+//! `nc -u 127.0.0.1 6144`: every datagram is sent back to its origin.
+
+use mini_runtime_v2::io;
+use mini_runtime_v2::net::UdpSocket;
+use mini_runtime_v2::runtime;
+
+fn main() -> io::Result<()> {
+    let rt = runtime::Builder::new_current_thread().enable_io().build()?;
+
+    rt.block_on(async {
+        let socket = UdpSocket::bind("127.0.0.1:6144").await?;
+        println!("Listening on {}", socket.local_addr()?);
+
+        let mut buf = vec![0; 64 * 1024];
+
+        loop {
+            let (n, peer) = socket.recv_from(&mut buf).await?;
+            let amt = socket.send_to(&buf[..n], peer).await?;
+            println!("Echoed {amt}/{n} bytes to {peer}");
+        }
+    })
+}
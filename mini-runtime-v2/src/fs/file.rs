@@ -0,0 +1,351 @@
+//! Types for working with [`File`].
+
+use crate::fs::asyncify;
+use crate::future::poll_fn;
+use crate::io::{AsyncRead, AsyncWrite};
+use crate::task::{JoinHandle, spawn_blocking};
+use std::fmt;
+use std::fs::File as StdFile;
+use std::future::Future;
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Maximum number of bytes moved by a single blocking read or write.
+const MAX_BUF: usize = 2 * 1024 * 1024;
+
+/// A reference to an open file on the filesystem.
+///
+/// This is a specialized version of [`std::fs::File`] for usage from the
+/// Mini runtime. Every read and write is executed on the blocking pool, the
+/// `File` only waits for the result.
+///
+/// Writes are "write-behind": `poll_write` copies the data into an internal
+/// buffer, hands it to the blocking pool and returns immediately. An error
+/// reported by the background write is returned by the next operation on the
+/// file. Call [`flush`] (or [`sync_all`]) to make sure all the data reached
+/// the OS before dropping the `File`, otherwise the last write may be lost.
+///
+/// ```no_run
+/// use mini_runtime_v2::fs::File;
+/// use mini_runtime_v2::io::{AsyncReadExt, AsyncWriteExt};
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build()?;
+/// # rt.block_on(async {
+///
+/// let mut file = File::create("foo.txt").await?;
+/// file.write_all(b"hello, world!").await?;
+/// file.flush().await?;
+///
+/// let mut contents = Vec::new();
+/// File::open("foo.txt").await?.read_to_end(&mut contents).await?;
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+///
+/// [`flush`]: crate::io::AsyncWriteExt::flush
+/// [`sync_all`]: File::sync_all
+pub struct File {
+    std: Arc<StdFile>,
+    state: State,
+
+    /// Kind of the error reported by the last background write, returned by
+    /// the next operation.
+    last_write_err: Option<io::ErrorKind>,
+}
+
+enum State {
+    /// No operation in flight, the buffer may still hold data read ahead.
+    Idle(Option<Buf>),
+    /// An operation runs on the blocking pool, it owns the buffer.
+    Busy(JoinHandle<(Operation, Buf)>),
+}
+
+enum Operation {
+    Read(io::Result<usize>),
+    Write(io::Result<()>),
+}
+
+/// Bytes moved between the `File` and the blocking pool.
+#[derive(Default)]
+struct Buf {
+    buf: Vec<u8>,
+    pos: usize,
+}
+
+impl File {
+    /// Attempts to open a file in read-only mode.
+    ///
+    /// See [`std::fs::File::open`] for details.
+    pub async fn open(path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let std = asyncify(|| StdFile::open(path)).await?;
+
+        Ok(File::from_std(std))
+    }
+
+    /// Opens a file in write-only mode.
+    ///
+    /// This function will create a file if it does not exist, and will
+    /// truncate it if it does. See [`std::fs::File::create`] for details.
+    pub async fn create(path: impl AsRef<Path>) -> io::Result<File> {
+        let path = path.as_ref().to_owned();
+        let std = asyncify(|| StdFile::create(path)).await?;
+
+        Ok(File::from_std(std))
+    }
+
+    /// Converts a [`std::fs::File`] to a [`File`].
+    pub fn from_std(std: StdFile) -> File {
+        File {
+            std: Arc::new(std),
+            state: State::Idle(Some(Buf::default())),
+            last_write_err: None,
+        }
+    }
+
+    /// Attempts to sync all OS-internal metadata to disk.
+    ///
+    /// The in-flight write, if any, completes first.
+    pub async fn sync_all(&mut self) -> io::Result<()> {
+        poll_fn(|cx| self.poll_complete_inflight(cx)).await?;
+
+        let std = self.std.clone();
+        asyncify(move || std.sync_all()).await
+    }
+
+    /// Queries metadata about the underlying file.
+    pub async fn metadata(&self) -> io::Result<std::fs::Metadata> {
+        let std = self.std.clone();
+        asyncify(move || std.metadata()).await
+    }
+
+    /// Waits for the operation running on the blocking pool, if any, and
+    /// returns the error of the last write.
+    fn poll_complete_inflight(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let State::Busy(rx) = &mut self.state {
+            let (op, mut buf) = ready!(Pin::new(rx).poll(cx))?;
+
+            match op {
+                // A read was interrupted by a call to `flush`: drop the data
+                // read ahead, the position of the file already moved past it
+                // but there is no reader anymore to consume it.
+                Operation::Read(_) => buf.clear(),
+                Operation::Write(Err(e)) => self.last_write_err = Some(e.kind()),
+                Operation::Write(Ok(())) => {}
+            }
+
+            self.state = State::Idle(Some(buf));
+        }
+
+        match self.last_write_err.take() {
+            Some(kind) => Poll::Ready(Err(kind.into())),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
+
+impl AsyncRead for File {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        dst: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let me = self.get_mut();
+
+        loop {
+            match &mut me.state {
+                State::Idle(buf_cell) => {
+                    let mut buf = buf_cell.take().unwrap();
+
+                    if !buf.is_empty() || dst.is_empty() {
+                        let n = buf.copy_to(dst);
+                        *buf_cell = Some(buf);
+                        return Poll::Ready(Ok(n));
+                    }
+
+                    let max = dst.len().min(MAX_BUF);
+                    let std = me.std.clone();
+
+                    me.state = State::Busy(spawn_blocking(move || {
+                        let res = buf.read_from(&mut &*std, max);
+                        (Operation::Read(res), buf)
+                    }));
+                }
+                State::Busy(rx) => {
+                    let (op, mut buf) = ready!(Pin::new(rx).poll(cx))?;
+
+                    match op {
+                        Operation::Read(Ok(_)) => {
+                            let n = buf.copy_to(dst);
+                            me.state = State::Idle(Some(buf));
+                            return Poll::Ready(Ok(n));
+                        }
+                        Operation::Read(Err(e)) => {
+                            assert!(buf.is_empty());
+
+                            me.state = State::Idle(Some(buf));
+                            return Poll::Ready(Err(e));
+                        }
+                        Operation::Write(Ok(())) => {
+                            assert!(buf.is_empty());
+                            me.state = State::Idle(Some(buf));
+                        }
+                        Operation::Write(Err(e)) => {
+                            assert!(me.last_write_err.is_none());
+                            me.last_write_err = Some(e.kind());
+                            me.state = State::Idle(Some(buf));
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl AsyncWrite for File {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        src: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let me = self.get_mut();
+
+        if let Some(kind) = me.last_write_err.take() {
+            return Poll::Ready(Err(kind.into()));
+        }
+
+        loop {
+            match &mut me.state {
+                State::Idle(buf_cell) => {
+                    let mut buf = buf_cell.take().unwrap();
+
+                    // Data read ahead but not consumed: the position of the
+                    // file is past it, move it back before writing.
+                    let seek = if !buf.is_empty() {
+                        Some(SeekFrom::Current(buf.discard_read()))
+                    } else {
+                        None
+                    };
+
+                    let n = buf.copy_from(src);
+                    let std = me.std.clone();
+
+                    me.state = State::Busy(spawn_blocking(move || {
+                        let res = if let Some(seek) = seek {
+                            (&*std).seek(seek).and_then(|_| buf.write_to(&mut &*std))
+                        } else {
+                            buf.write_to(&mut &*std)
+                        };
+
+                        (Operation::Write(res), buf)
+                    }));
+
+                    return Poll::Ready(Ok(n));
+                }
+                State::Busy(_) => {
+                    ready!(me.poll_complete_inflight(cx))?;
+                }
+            }
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_complete_inflight(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.get_mut().poll_complete_inflight(cx)
+    }
+}
+
+impl From<StdFile> for File {
+    fn from(std: StdFile) -> Self {
+        Self::from_std(std)
+    }
+}
+
+impl fmt::Debug for File {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("mini_runtime_v2::fs::File")
+            .field("std", &self.std)
+            .finish()
+    }
+}
+
+// ===== impl Buf =====
+
+impl Buf {
+    fn len(&self) -> usize {
+        self.buf.len() - self.pos
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn clear(&mut self) {
+        self.buf.clear();
+        self.pos = 0;
+    }
+
+    /// Copies the buffered bytes to `dst`, returns the number of bytes copied.
+    fn copy_to(&mut self, dst: &mut [u8]) -> usize {
+        let n = self.len().min(dst.len());
+        dst[..n].copy_from_slice(&self.buf[self.pos..self.pos + n]);
+        self.pos += n;
+
+        if self.pos == self.buf.len() {
+            self.clear();
+        }
+
+        n
+    }
+
+    /// Copies at most `MAX_BUF` bytes of `src` into the empty buffer.
+    fn copy_from(&mut self, src: &[u8]) -> usize {
+        assert!(self.is_empty());
+
+        let n = src.len().min(MAX_BUF);
+        self.buf.extend_from_slice(&src[..n]);
+        n
+    }
+
+    /// Drops the buffered bytes, returns the offset to seek back to the
+    /// position of the first one.
+    fn discard_read(&mut self) -> i64 {
+        let ret = -(self.len() as i64);
+        self.clear();
+        ret
+    }
+
+    fn read_from<T: Read>(&mut self, rd: &mut T, max: usize) -> io::Result<usize> {
+        assert!(self.is_empty());
+        self.buf.resize(max, 0);
+
+        let res = loop {
+            match rd.read(&mut self.buf) {
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                res => break res,
+            }
+        };
+
+        match res {
+            Ok(n) => self.buf.truncate(n),
+            Err(_) => self.buf.clear(),
+        }
+
+        res
+    }
+
+    fn write_to<T: Write>(&mut self, wr: &mut T) -> io::Result<()> {
+        assert_eq!(self.pos, 0);
+
+        // `write_all` already ignores interrupts
+        let res = wr.write_all(&self.buf);
+        self.clear();
+        res
+    }
+}
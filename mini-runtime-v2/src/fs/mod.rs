@@ -0,0 +1,39 @@
+//! Asynchronous file utilities.
+//!
+//! Operating systems don't offer a readiness model for regular files: a file
+//! is always "ready", reads and writes simply block the calling thread. Like
+//! tokio, this module fakes asynchronous file I/O by running the blocking
+//! `std::fs` calls on the blocking pool (see [`spawn_blocking`]) and awaiting
+//! the result.
+//!
+//! This is fine for a few large operations, but every call pays for a hop to
+//! another thread, so prefer reading whole files with [`read`] or
+//! [`read_to_string`] over many small reads on a [`File`].
+//!
+//! [`spawn_blocking`]: crate::task::spawn_blocking
+
+mod file;
+pub use self::file::File;
+
+mod read;
+pub use self::read::read;
+
+mod read_to_string;
+pub use self::read_to_string::read_to_string;
+
+mod write;
+pub use self::write::write;
+
+use std::io;
+
+/// Runs the blocking `f` on the blocking pool and waits for its result.
+pub(crate) async fn asyncify<F, T>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match crate::task::spawn_blocking(f).await {
+        Ok(res) => res,
+        Err(_) => Err(io::Error::other("background task failed")),
+    }
+}
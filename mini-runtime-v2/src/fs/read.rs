@@ -0,0 +1,16 @@
+use crate::fs::asyncify;
+use std::io;
+use std::path::Path;
+
+/// Reads the entire contents of a file into a bytes vector.
+///
+/// This is the async equivalent of [`std::fs::read`].
+///
+/// This operation is implemented by running the equivalent blocking operation
+/// on a separate thread pool using [`spawn_blocking`].
+///
+/// [`spawn_blocking`]: crate::task::spawn_blocking
+pub async fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || std::fs::read(path)).await
+}
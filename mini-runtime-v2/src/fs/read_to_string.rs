@@ -0,0 +1,28 @@
+use crate::fs::asyncify;
+use std::io;
+use std::path::Path;
+
+/// Creates a future which will open a file for reading and read the entire
+/// contents into a string and return said string.
+///
+/// This is the async equivalent of [`std::fs::read_to_string`].
+///
+/// This operation is implemented by running the equivalent blocking operation
+/// on a separate thread pool using [`spawn_blocking`].
+///
+/// ```no_run
+/// # use mini_runtime_v2::fs;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build()?;
+/// # rt.block_on(async {
+/// let contents = fs::read_to_string("foo.txt").await?;
+/// println!("foo.txt contains {} bytes", contents.len());
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+///
+/// [`spawn_blocking`]: crate::task::spawn_blocking
+pub async fn read_to_string(path: impl AsRef<Path>) -> io::Result<String> {
+    let path = path.as_ref().to_owned();
+    asyncify(move || std::fs::read_to_string(path)).await
+}
@@ -0,0 +1,49 @@
+use std::future::Future;
+
+mod instrumented;
+pub use instrumented::Instrumented;
+
+/// An extension trait for the [`Future`] trait that provides some
+/// convenient adapters.
+///
+/// It is implemented for every `Future`, import it to call the methods:
+///
+/// ```
+/// use mini_runtime_v2::future::FutureExt;
+/// ```
+pub trait FutureExt: Future {
+    /// Measures the polls of this future.
+    ///
+    /// The returned future counts the polls of this future and the time
+    /// they take. When it is dropped, completed or not, it logs a summary
+    /// tagged with `name`: with the `tracing` feature as a `tracing` event,
+    /// otherwise on the standard error.
+    ///
+    /// The [poll time histogram] tells that some task blocks the executor,
+    /// instrumenting the suspects tells which one:
+    ///
+    /// ```
+    /// # use mini_runtime_v2::future::FutureExt;
+    /// # async fn fetch(_url: &str) {}
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+    /// # rt.block_on(async {
+    /// # let url = "https://example.com";
+    /// let body = fetch(url).instrument_poll("fetch").await;
+    /// // future `fetch` completed: 12 polls, busy for 48.2ms, longest poll 45.1ms
+    /// # });
+    /// ```
+    ///
+    /// A future that is well-behaved spends a few microseconds per poll. A
+    /// long poll means the future ran blocking code, or too much computation,
+    /// between two `.await`s.
+    ///
+    /// [poll time histogram]: crate::runtime::RuntimeMetrics::poll_time_histogram
+    fn instrument_poll(self, name: &'static str) -> Instrumented<Self>
+    where
+        Self: Sized,
+    {
+        Instrumented::new(self, name)
+    }
+}
+
+impl<F: ?Sized> FutureExt for F where F: Future {}
@@ -0,0 +1,100 @@
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+pin_project! {
+    /// Future for the [`instrument_poll`](super::FutureExt::instrument_poll)
+    /// method.
+    ///
+    /// Logs a summary of the polls when dropped.
+    #[derive(Debug)]
+    #[must_use = "futures do nothing unless you `.await` or poll them"]
+    pub struct Instrumented<F> {
+        #[pin]
+        future: F,
+        name: &'static str,
+        polls: u64,
+        busy: Duration,
+        longest_poll: Duration,
+        completed: bool,
+    }
+
+    impl<F> PinnedDrop for Instrumented<F> {
+        fn drop(this: Pin<&mut Self>) {
+            this.log_summary();
+        }
+    }
+}
+
+impl<F> Instrumented<F> {
+    pub(super) fn new(future: F, name: &'static str) -> Instrumented<F> {
+        Instrumented {
+            future,
+            name,
+            polls: 0,
+            busy: Duration::ZERO,
+            longest_poll: Duration::ZERO,
+            completed: false,
+        }
+    }
+
+    /// Returns the number of times the future was polled.
+    pub fn poll_count(&self) -> u64 {
+        self.polls
+    }
+
+    /// Returns the total time spent polling the future.
+    pub fn busy_duration(&self) -> Duration {
+        self.busy
+    }
+
+    /// Returns the duration of the longest poll of the future.
+    pub fn longest_poll(&self) -> Duration {
+        self.longest_poll
+    }
+
+    #[cfg(feature = "tracing")]
+    fn log_summary(&self) {
+        tracing::info!(
+            name = self.name,
+            polls = self.polls,
+            busy = ?self.busy,
+            longest_poll = ?self.longest_poll,
+            completed = self.completed,
+            "future dropped",
+        );
+    }
+
+    #[cfg(not(feature = "tracing"))]
+    fn log_summary(&self) {
+        let state = match self.completed {
+            true => "completed",
+            false => "dropped before completion",
+        };
+        eprintln!(
+            "future `{}` {state}: {} polls, busy for {:?}, longest poll {:?}",
+            self.name, self.polls, self.busy, self.longest_poll,
+        );
+    }
+}
+
+impl<F: Future> Future for Instrumented<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<F::Output> {
+        let me = self.project();
+
+        let start = Instant::now();
+        let res = me.future.poll(cx);
+        let elapsed = start.elapsed();
+
+        *me.polls += 1;
+        *me.busy += elapsed;
+        *me.longest_poll = (*me.longest_poll).max(elapsed);
+        *me.completed = res.is_ready();
+
+        res
+    }
+}
@@ -0,0 +1,280 @@
+use crate::stream::Stream;
+use crate::util::loom::sync::atomic::AtomicBool;
+use crate::util::loom::sync::atomic::Ordering::{AcqRel, Release};
+use crate::util::loom::sync::{Arc, Mutex};
+use crate::util::{Wake, waker_ref};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+/// A set of futures polled concurrently within the current task, yielding
+/// their outputs in completion order.
+///
+/// Each future gets its own waker: when it is woken, only that future is
+/// polled again, not the whole set. The set is polled through its
+/// [`Stream`] implementation, e.g. with [`StreamExt::next`]; it yields
+/// `None` when it is empty, futures may be pushed again afterwards.
+///
+/// ```
+/// # use mini_runtime_v2::future::FuturesUnordered;
+/// # use mini_runtime_v2::stream::StreamExt;
+/// # use mini_runtime_v2::time::{self, Duration};
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+/// # rt.block_on(async {
+/// let mut set = FuturesUnordered::new();
+/// set.push(time::sleep(Duration::from_millis(20)));
+/// set.push(time::sleep(Duration::from_millis(10)));
+///
+/// // Both sleeps run at the same time: done after ~20ms.
+/// while set.next().await.is_some() {}
+/// # });
+/// ```
+///
+/// The futures don't need to be `Send` or `'static`, unlike spawned tasks,
+/// but they only run while the set is polled.
+///
+/// [`StreamExt::next`]: crate::stream::StreamExt::next
+#[must_use = "streams do nothing unless polled"]
+pub struct FuturesUnordered<F> {
+    /// The futures, indexed by the `index` of their node. `None` in the free
+    /// slots.
+    slots: Vec<Option<Slot<F>>>,
+
+    /// Indices of the free slots, reused before growing `slots`.
+    free: Vec<usize>,
+
+    /// Number of futures in the set.
+    len: usize,
+
+    /// Queue of the woken futures, shared with their wakers.
+    ready: Arc<ReadyQueue>,
+}
+
+struct Slot<F> {
+    future: Pin<Box<F>>,
+    node: Arc<Node>,
+}
+
+/// Waker of one future of the set.
+struct Node {
+    /// Slot of the future in the set.
+    index: usize,
+
+    /// Set while the node is in the ready queue, so that a future woken
+    /// several times is polled once.
+    queued: AtomicBool,
+
+    ready: Arc<ReadyQueue>,
+}
+
+struct ReadyQueue {
+    inner: Mutex<ReadyInner>,
+}
+
+struct ReadyInner {
+    /// Woken futures, in wake order. A node may outlive its future: the
+    /// stale nodes are skipped when popped.
+    queue: VecDeque<Arc<Node>>,
+
+    /// Waker of the task polling the set.
+    waker: Option<Waker>,
+
+    /// Set when the `FuturesUnordered` is dropped: the queue is not used
+    /// anymore, and a node pushed to it would never be freed.
+    closed: bool,
+}
+
+impl<F> FuturesUnordered<F> {
+    /// Creates an empty set.
+    pub fn new() -> FuturesUnordered<F> {
+        FuturesUnordered {
+            slots: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+            ready: Arc::new(ReadyQueue {
+                inner: Mutex::new(ReadyInner {
+                    queue: VecDeque::new(),
+                    waker: None,
+                    closed: false,
+                }),
+            }),
+        }
+    }
+
+    /// Returns the number of futures in the set.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the set contains no future.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Adds a future to the set.
+    ///
+    /// The future is not polled until the set is: it is first polled on the
+    /// next call to `poll_next`.
+    pub fn push(&mut self, future: F) {
+        let index = self.free.pop().unwrap_or_else(|| {
+            self.slots.push(None);
+            self.slots.len() - 1
+        });
+
+        let node = Arc::new(Node {
+            index,
+            queued: AtomicBool::new(false),
+            ready: self.ready.clone(),
+        });
+
+        // A new future must be polled once to register its wakers.
+        Wake::wake_by_ref(&node);
+
+        self.slots[index] = Some(Slot {
+            future: Box::pin(future),
+            node,
+        });
+        self.len += 1;
+    }
+}
+
+impl<F: Future> Stream for FuturesUnordered<F> {
+    type Item = F::Output;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<F::Output>> {
+        let me = self.get_mut();
+
+        if me.len == 0 {
+            return Poll::Ready(None);
+        }
+
+        me.ready.register(cx.waker());
+
+        let mut polled = 0;
+        while let Some(node) = me.ready.pop() {
+            // Cleared before the poll: a wakeup during the poll queues the
+            // future again.
+            node.queued.store(false, Release);
+
+            let Some(slot) = me.slots[node.index]
+                .as_mut()
+                .filter(|slot| Arc::ptr_eq(&slot.node, &node))
+            else {
+                // The future completed, the slot may have been reused.
+                continue;
+            };
+
+            let waker = waker_ref(&node);
+            let mut future_cx = Context::from_waker(&waker);
+
+            if let Poll::Ready(output) = slot.future.as_mut().poll(&mut future_cx) {
+                me.slots[node.index] = None;
+                me.free.push(node.index);
+                me.len -= 1;
+                return Poll::Ready(Some(output));
+            }
+
+            // A future waking itself on every poll would keep the loop
+            // going: once as many futures as the set holds were polled,
+            // yield to the scheduler.
+            polled += 1;
+            if polled == me.len {
+                cx.waker().wake_by_ref();
+                return Poll::Pending;
+            }
+        }
+
+        Poll::Pending
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<F> Default for FuturesUnordered<F> {
+    fn default() -> Self {
+        FuturesUnordered::new()
+    }
+}
+
+impl<F> FromIterator<F> for FuturesUnordered<F> {
+    fn from_iter<I: IntoIterator<Item = F>>(iter: I) -> Self {
+        let mut set = FuturesUnordered::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<F> Extend<F> for FuturesUnordered<F> {
+    fn extend<I: IntoIterator<Item = F>>(&mut self, iter: I) {
+        for future in iter {
+            self.push(future);
+        }
+    }
+}
+
+impl<F> Drop for FuturesUnordered<F> {
+    fn drop(&mut self) {
+        // The queued nodes reference the queue: clear it to break the
+        // cycle, and keep it empty for the wakers still alive.
+        let queue = {
+            let mut inner = self.ready.inner.lock().unwrap();
+            inner.closed = true;
+            std::mem::take(&mut inner.queue)
+        };
+        drop(queue);
+    }
+}
+
+impl<F> fmt::Debug for FuturesUnordered<F> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("FuturesUnordered")
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+impl ReadyQueue {
+    /// Stores the waker of the task polling the set.
+    fn register(&self, waker: &Waker) {
+        let mut inner = self.inner.lock().unwrap();
+        match &inner.waker {
+            Some(current) if current.will_wake(waker) => {}
+            _ => inner.waker = Some(waker.clone()),
+        }
+    }
+
+    fn pop(&self) -> Option<Arc<Node>> {
+        self.inner.lock().unwrap().queue.pop_front()
+    }
+}
+
+impl Wake for Node {
+    fn wake(arc_self: Arc<Self>) {
+        Wake::wake_by_ref(&arc_self);
+    }
+
+    /// Queues the future and wakes the task polling the set.
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        if arc_self.queued.swap(true, AcqRel) {
+            return;
+        }
+
+        let waker = {
+            let mut inner = arc_self.ready.inner.lock().unwrap();
+            if inner.closed {
+                return;
+            }
+            inner.queue.push_back(arc_self.clone());
+            inner.waker.clone()
+        };
+
+        // Outside of the lock: the task may be polled right away.
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
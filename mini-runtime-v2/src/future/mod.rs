@@ -0,0 +1,46 @@
+//! Asynchronous values.
+//!
+//! Concurrency comes in two flavors. [`task::spawn`] and [`JoinSet`] create
+//! tasks: each future is scheduled on its own, may run on another thread,
+//! and must be `Send + 'static`. [`FuturesUnordered`] runs many futures
+//! within the task that polls it: they take turns on that task's poll, can
+//! borrow from the task's stack, and a panic in one of them brings the
+//! whole task down. Sub-task concurrency is cheaper, spawning allocates and
+//! schedules a task, but the futures never run in parallel.
+//!
+//! ```
+//! use mini_runtime_v2::future::FuturesUnordered;
+//! use mini_runtime_v2::stream::StreamExt;
+//! # use std::net::SocketAddr;
+//! # async fn resolve(host: &str) -> Option<SocketAddr> {
+//! #     mini_runtime_v2::net::lookup_host((host, 80)).await.ok()?.next()
+//! # }
+//! # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+//! # rt.block_on(async {
+//! # let hosts = ["127.0.0.1", "::1"];
+//!
+//! let mut lookups: FuturesUnordered<_> = hosts.iter().map(|host| resolve(host)).collect();
+//!
+//! while let Some(addr) = lookups.next().await {
+//!     println!("{addr:?}");
+//! }
+//! # });
+//! ```
+//!
+//! [`poll_fn`] turns a function polling a resource into a future, the
+//! building block of the `async fn` wrappers around `poll_*` methods.
+//!
+//! [`FutureExt::instrument_poll`] measures the polls of a single future, to
+//! find the one blocking the executor.
+//!
+//! [`task::spawn`]: crate::task::spawn
+//! [`JoinSet`]: crate::task::JoinSet
+
+mod future_ext;
+pub use future_ext::{FutureExt, Instrumented};
+
+mod futures_unordered;
+pub use futures_unordered::FuturesUnordered;
+
+mod poll_fn;
+pub use poll_fn::{PollFn, poll_fn};
@@ -0,0 +1,56 @@
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Future for the [`poll_fn`] function.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct PollFn<F> {
+    f: F,
+}
+
+/// Creates a future that wraps a function returning [`Poll`].
+///
+/// Polling the future calls the function. It turns a `poll_*` method into a
+/// future without writing a type for it:
+///
+/// ```
+/// # use mini_runtime_v2::future::poll_fn;
+/// # use std::collections::VecDeque;
+/// # use std::task::{Context, Poll};
+/// # struct Receiver<T>(VecDeque<T>);
+/// # impl<T> Receiver<T> {
+/// #     fn poll_recv(&mut self, _cx: &mut Context<'_>) -> Poll<Option<T>> {
+/// #         Poll::Ready(self.0.pop_front())
+/// #     }
+/// pub async fn recv(&mut self) -> Option<T> {
+///     poll_fn(|cx| self.poll_recv(cx)).await
+/// }
+/// # }
+/// ```
+pub fn poll_fn<T, F>(f: F) -> PollFn<F>
+where
+    F: FnMut(&mut Context<'_>) -> Poll<T>,
+{
+    PollFn { f }
+}
+
+// The closure is never pinned: it is only called through `&mut`.
+impl<F> Unpin for PollFn<F> {}
+
+impl<T, F> Future for PollFn<F>
+where
+    F: FnMut(&mut Context<'_>) -> Poll<T>,
+{
+    type Output = T;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+        (self.get_mut().f)(cx)
+    }
+}
+
+impl<F> fmt::Debug for PollFn<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PollFn").finish()
+    }
+}
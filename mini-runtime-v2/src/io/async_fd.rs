@@ -0,0 +1,273 @@
+use crate::future::poll_fn;
+use crate::runtime::io::{Direction, Ready, ReadyEvent, Registration};
+use crate::runtime::scheduler;
+use mio::Interest;
+use mio::event::Source;
+use std::fmt;
+use std::io;
+use std::task::{Context, Poll};
+
+/// Associates any I/O resource implementing [`mio::event::Source`] with the
+/// reactor, exposing its readiness.
+///
+/// The runtime's net types (`TcpStream`, `UdpSocket`...) read and write for
+/// you. `AsyncFd` brings other resources to the runtime, e.g. a pipe, an
+/// eventfd or a device: it doesn't perform any I/O itself, it waits for the
+/// resource to become readable or writable, the caller then performs the
+/// non-blocking operation.
+///
+/// The readiness is cached: once the reactor reported the resource
+/// readable, [`readable`] returns right away until the readiness is cleared
+/// with [`AsyncFdReadyGuard::clear_ready`]. The readiness must be cleared when
+/// the operation fails with `WouldBlock`, otherwise the task busy-loops on a
+/// stale event; [`AsyncFdReadyGuard::try_io`] does it for you.
+///
+/// ```
+/// # use mini_runtime_v2::io::{AsyncFd, Interest};
+/// # use std::io::{Read, Write};
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_io().build()?;
+/// # rt.block_on(async {
+/// let (tx, rx) = mio::unix::pipe::new()?;
+/// let rx = AsyncFd::with_interest(rx, Interest::READABLE)?;
+/// # (&tx).write_all(b"hello")?;
+///
+/// let mut buf = [0; 1024];
+/// let n = loop {
+///     let mut guard = rx.readable().await?;
+///
+///     match guard.try_io(|rx| rx.get_ref().read(&mut buf)) {
+///         Ok(result) => break result?,
+///         // The readiness was stale and was cleared, wait for the next event.
+///         Err(_would_block) => continue,
+///     }
+/// };
+/// # assert_eq!(&buf[..n], b"hello");
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+///
+/// The resource is deregistered from the reactor when the `AsyncFd` is
+/// dropped, or when it is taken back with [`into_inner`].
+///
+/// [`readable`]: AsyncFd::readable
+/// [`into_inner`]: AsyncFd::into_inner
+pub struct AsyncFd<T: Source> {
+    registration: Registration,
+    inner: Option<T>,
+}
+
+/// Represents an I/O resource that was reported ready by the reactor.
+///
+/// Returned by [`AsyncFd::readable`] and [`AsyncFd::writable`]. Dropping the
+/// guard keeps the readiness: call [`clear_ready`](Self::clear_ready) once
+/// the operation returned `WouldBlock`.
+#[must_use = "You must explicitly choose whether to clear the readiness state by calling a method on ReadyGuard"]
+pub struct AsyncFdReadyGuard<'a, T: Source> {
+    async_fd: &'a AsyncFd<T>,
+    event: Option<ReadyEvent>,
+}
+
+/// The operation passed to [`AsyncFdReadyGuard::try_io`] would block, the
+/// readiness was cleared.
+#[derive(Debug)]
+pub struct TryIoError(());
+
+impl<T: Source> AsyncFd<T> {
+    /// Creates an `AsyncFd` interested in both read and write readiness.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_io`].
+    ///
+    /// [`enable_io`]: crate::runtime::Builder::enable_io
+    #[track_caller]
+    pub fn new(inner: T) -> io::Result<Self> {
+        AsyncFd::with_interest(inner, Interest::READABLE | Interest::WRITABLE)
+    }
+
+    /// Creates an `AsyncFd` interested in the given readiness only, e.g. the
+    /// read end of a pipe can't be registered for write readiness on every
+    /// platform.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_io`].
+    ///
+    /// [`enable_io`]: crate::runtime::Builder::enable_io
+    #[track_caller]
+    pub fn with_interest(mut inner: T, interest: Interest) -> io::Result<Self> {
+        let handle = scheduler::Handle::current();
+        let registration =
+            Registration::new_with_interest_and_handle(&mut inner, interest, handle)?;
+
+        Ok(AsyncFd {
+            registration,
+            inner: Some(inner),
+        })
+    }
+
+    /// Returns a shared reference to the inner resource.
+    pub fn get_ref(&self) -> &T {
+        self.inner.as_ref().unwrap()
+    }
+
+    /// Returns a mutable reference to the inner resource.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.as_mut().unwrap()
+    }
+
+    /// Deregisters the resource from the reactor and returns it.
+    pub fn into_inner(mut self) -> T {
+        let mut inner = self.inner.take().unwrap();
+        let _ = self.registration.deregister(&mut inner);
+        inner
+    }
+
+    /// Waits for the resource to become readable.
+    ///
+    /// Completes right away if the cached readiness says so, the readiness
+    /// may be stale: see [`AsyncFdReadyGuard::try_io`].
+    pub async fn readable(&self) -> io::Result<AsyncFdReadyGuard<'_, T>> {
+        poll_fn(|cx| self.poll_read_ready(cx)).await
+    }
+
+    /// Waits for the resource to become writable.
+    ///
+    /// Completes right away if the cached readiness says so, the readiness
+    /// may be stale: see [`AsyncFdReadyGuard::try_io`].
+    pub async fn writable(&self) -> io::Result<AsyncFdReadyGuard<'_, T>> {
+        poll_fn(|cx| self.poll_write_ready(cx)).await
+    }
+
+    /// Polls for read readiness.
+    ///
+    /// When the method returns `Poll::Pending`, the waker in `cx` is
+    /// notified once the resource becomes readable. Only the waker of the
+    /// most recent call is notified.
+    pub fn poll_read_ready(
+        &self,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<AsyncFdReadyGuard<'_, T>>> {
+        self.poll_ready(cx, Direction::Read)
+    }
+
+    /// Polls for write readiness.
+    ///
+    /// When the method returns `Poll::Pending`, the waker in `cx` is
+    /// notified once the resource becomes writable. Only the waker of the
+    /// most recent call is notified.
+    pub fn poll_write_ready(
+        &self,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<AsyncFdReadyGuard<'_, T>>> {
+        self.poll_ready(cx, Direction::Write)
+    }
+
+    fn poll_ready(
+        &self,
+        cx: &mut Context<'_>,
+        direction: Direction,
+    ) -> Poll<io::Result<AsyncFdReadyGuard<'_, T>>> {
+        let event = ready!(self.registration.poll_ready(cx, direction))?;
+
+        Poll::Ready(Ok(AsyncFdReadyGuard {
+            async_fd: self,
+            event: Some(event),
+        }))
+    }
+}
+
+impl<T: Source> Drop for AsyncFd<T> {
+    fn drop(&mut self) {
+        if let Some(mut inner) = self.inner.take() {
+            // Ignore errors
+            let _ = self.registration.deregister(&mut inner);
+        }
+    }
+}
+
+impl<T: Source + fmt::Debug> fmt::Debug for AsyncFd<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AsyncFd")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+impl<'a, T: Source> AsyncFdReadyGuard<'a, T> {
+    /// Clears the readiness the guard was created for.
+    ///
+    /// The next call to `readable` or `writable` waits for a new event from
+    /// the reactor. Only clear the readiness once an operation returned
+    /// `WouldBlock`: clearing it while data is left unread may wait for an
+    /// event that never comes.
+    pub fn clear_ready(&mut self) {
+        if let Some(event) = self.event.take() {
+            self.async_fd.registration.clear_readiness(event);
+        }
+    }
+
+    /// Keeps the readiness, this is also what dropping the guard does.
+    pub fn retain_ready(&mut self) {
+        self.event = None;
+    }
+
+    /// Returns `true` if the reactor reported the resource closed in the
+    /// direction of the guard, e.g. the write end of a pipe was dropped.
+    pub fn is_closed(&self) -> bool {
+        self.event.is_some_and(|event| {
+            !event
+                .ready
+                .intersection(Ready::READ_CLOSED | Ready::WRITE_CLOSED)
+                .is_empty()
+        })
+    }
+
+    /// Performs the operation `f`, clearing the readiness if it fails with
+    /// `WouldBlock`.
+    ///
+    /// Returns `Err(TryIoError)` if the operation would block, the caller
+    /// should then wait for readiness again. Otherwise, returns the result
+    /// of the operation.
+    pub fn try_io<R>(
+        &mut self,
+        f: impl FnOnce(&'a AsyncFd<T>) -> io::Result<R>,
+    ) -> Result<io::Result<R>, TryIoError> {
+        match f(self.async_fd) {
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                self.clear_ready();
+                Err(TryIoError(()))
+            }
+            result => Ok(result),
+        }
+    }
+
+    /// Returns the `AsyncFd` the guard was created from.
+    pub fn get_ref(&self) -> &'a AsyncFd<T> {
+        self.async_fd
+    }
+
+    /// Returns the inner resource.
+    pub fn get_inner(&self) -> &'a T {
+        self.async_fd.get_ref()
+    }
+}
+
+impl<T: Source + fmt::Debug> fmt::Debug for AsyncFdReadyGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReadyGuard")
+            .field("async_fd", &self.async_fd)
+            .finish()
+    }
+}
+
+impl fmt::Display for TryIoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("operation would block")
+    }
+}
+
+impl std::error::Error for TryIoError {}
@@ -0,0 +1,62 @@
+//! Traits, helpers, and type definitions for asynchronous I/O functionality.
+//!
+//! This module is the asynchronous version of `std::io`. Primarily, it
+//! defines two traits, [`AsyncRead`] and [`AsyncWrite`], which are
+//! asynchronous versions of the [`Read`] and [`Write`] traits in the standard
+//! library.
+//!
+//! Unlike the standard traits, [`AsyncRead`] and [`AsyncWrite`] are not used
+//! directly: their methods are `poll_*` functions meant to be called by other
+//! futures. [`AsyncReadExt`] and [`AsyncWriteExt`] provide the familiar
+//! `read`, `write`, `write_all`... methods returning futures to `.await`.
+//!
+//! [`BufReader`] and [`BufWriter`] add buffering on top of any reader or
+//! writer. [`BufReader`] also implements [`AsyncBufRead`], which makes
+//! [`AsyncBufReadExt::read_line`] and [`AsyncBufReadExt::read_until`]
+//! available to parse line-delimited protocols.
+//!
+//! ```no_run
+//! use mini_runtime_v2::io::{self, AsyncReadExt, AsyncWriteExt};
+//! # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+//! # rt.block_on(async {
+//! # let socket = mini_runtime_v2::net::TcpStream::connect("127.0.0.1:8080").await?;
+//!
+//! let (mut rd, mut wr) = (&socket, &socket);
+//! io::copy(&mut rd, &mut wr).await?;
+//! # Ok::<_, io::Error>(())
+//! # })?;
+//! # Ok::<_, io::Error>(())
+//! ```
+//!
+//! [`AsyncFd`] integrates any other resource implementing
+//! [`mio::event::Source`] (a pipe, an eventfd...) with the reactor: it waits
+//! for the resource to become readable or writable, the caller performs the
+//! non-blocking I/O.
+//!
+//! [`Read`]: std::io::Read
+//! [`Write`]: std::io::Write
+
+mod async_fd;
+pub use self::async_fd::{AsyncFd, AsyncFdReadyGuard, TryIoError};
+
+mod async_buf_read;
+pub use self::async_buf_read::AsyncBufRead;
+
+mod async_read;
+pub use self::async_read::AsyncRead;
+
+mod async_write;
+pub use self::async_write::AsyncWrite;
+
+mod poll_evented;
+pub(crate) use poll_evented::PollEvented;
+
+mod util;
+pub use self::util::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, BufWriter, copy};
+
+/// The readiness an [`AsyncFd`] is registered for.
+pub use mio::Interest;
+
+// Re-export some types from `std::io` so that users don't have to deal with
+// conflicts when `use`ing `mini_runtime_v2::io` and `std::io`.
+pub use std::io::{Error, ErrorKind, Result};
@@ -0,0 +1,122 @@
+use crate::runtime::io::{Direction, Registration};
+use crate::runtime::scheduler;
+use mio::Interest;
+use mio::event::Source;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::ops::Deref;
+use std::task::{Context, Poll};
+
+/// Associates an I/O resource that implements the [`std::io::Read`] and/or
+/// [`std::io::Write`] traits with the reactor that drives it.
+///
+/// `PollEvented` uses [`Registration`] internally to take a type that
+/// implements [`mio::event::Source`] as well as [`std::io::Read`] and/or
+/// [`std::io::Write`] and associate it with a reactor that will drive it.
+///
+/// Once the [`mio::event::Source`] type is wrapped by `PollEvented`, it can be
+/// used from within the future's execution model. The `poll_read` and
+/// `poll_write` functions perform the I/O operation, and park the task when
+/// the resource is not ready: on `WouldBlock` the cached readiness is cleared
+/// and the task waits for the next readiness event.
+pub(crate) struct PollEvented<E: Source> {
+    io: Option<E>,
+    registration: Registration,
+}
+
+impl<E: Source> PollEvented<E> {
+    /// Creates a new `PollEvented` associated with the default reactor, for
+    /// all interest.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if thread-local runtime is not set or if the I/O
+    /// driver is disabled.
+    #[track_caller]
+    pub(crate) fn new(io: E) -> io::Result<Self> {
+        PollEvented::new_with_interest(io, Interest::READABLE | Interest::WRITABLE)
+    }
+
+    /// Creates a new `PollEvented` associated with the default reactor, for
+    /// specific `Interest` state.
+    #[track_caller]
+    pub(crate) fn new_with_interest(io: E, interest: Interest) -> io::Result<Self> {
+        Self::new_with_interest_and_handle(io, interest, scheduler::Handle::current())
+    }
+
+    #[track_caller]
+    pub(crate) fn new_with_interest_and_handle(
+        mut io: E,
+        interest: Interest,
+        handle: scheduler::Handle,
+    ) -> io::Result<Self> {
+        let registration = Registration::new_with_interest_and_handle(&mut io, interest, handle)?;
+        Ok(Self {
+            io: Some(io),
+            registration,
+        })
+    }
+
+    /// Returns a reference to the registration.
+    pub(crate) fn registration(&self) -> &Registration {
+        &self.registration
+    }
+
+    /// Deregisters the inner io from the registration and returns it.
+    #[allow(dead_code)]
+    pub(crate) fn into_inner(mut self) -> io::Result<E> {
+        let mut inner = self.io.take().unwrap(); // As io shouldn't ever be None, just unwrap here.
+        self.registration.deregister(&mut inner)?;
+        Ok(inner)
+    }
+}
+
+impl<E: Source> PollEvented<E> {
+    pub(crate) fn poll_read<'a>(
+        &'a self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>>
+    where
+        &'a E: Read + 'a,
+    {
+        self.registration
+            .poll_io(cx, Direction::Read, || self.io.as_ref().unwrap().read(buf))
+    }
+
+    pub(crate) fn poll_write<'a>(
+        &'a self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>>
+    where
+        &'a E: Write + 'a,
+    {
+        self.registration.poll_io(cx, Direction::Write, || {
+            self.io.as_ref().unwrap().write(buf)
+        })
+    }
+}
+
+impl<E: Source> Deref for PollEvented<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        self.io.as_ref().unwrap()
+    }
+}
+
+impl<E: Source + fmt::Debug> fmt::Debug for PollEvented<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PollEvented").field("io", &self.io).finish()
+    }
+}
+
+impl<E: Source> Drop for PollEvented<E> {
+    fn drop(&mut self) {
+        if let Some(mut io) = self.io.take() {
+            // Ignore errors
+            let _ = self.registration.deregister(&mut io);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use crate::io::AsyncBufRead;
+use crate::io::util::read_line::{ReadLine, read_line};
+use crate::io::util::read_until::{ReadUntil, read_until};
+
+/// An extension trait which adds utility methods to [`AsyncBufRead`] types.
+///
+/// ```no_run
+/// use mini_runtime_v2::io::{AsyncBufReadExt, BufReader};
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+/// # let socket = mini_runtime_v2::net::TcpStream::connect("127.0.0.1:8080").await?;
+///
+/// let mut reader = BufReader::new(socket);
+/// let mut line = String::new();
+///
+/// while reader.read_line(&mut line).await? != 0 {
+///     // ...
+///     line.clear();
+/// }
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub trait AsyncBufReadExt: AsyncBufRead {
+    /// Reads all bytes into `buf` until the delimiter `byte` or EOF is
+    /// reached.
+    ///
+    /// Equivalent to:
+    ///
+    /// ```ignore
+    /// async fn read_until(&mut self, byte: u8, buf: &mut Vec<u8>) -> io::Result<usize>;
+    /// ```
+    ///
+    /// The delimiter, if found, is appended to `buf`. The number of bytes
+    /// appended is returned, `0` means the end of the stream was reached.
+    ///
+    /// # Cancel safety
+    ///
+    /// If the future is dropped before it completes, the bytes read so far
+    /// stay in `buf`, so calling `read_until` again picks up where the
+    /// previous call left off.
+    fn read_until<'a>(&'a mut self, byte: u8, buf: &'a mut Vec<u8>) -> ReadUntil<'a, Self>
+    where
+        Self: Unpin,
+    {
+        read_until(self, byte, buf)
+    }
+
+    /// Reads all bytes until a newline (the `0xA` byte) is reached, and
+    /// appends them to the provided `String` buffer.
+    ///
+    /// Equivalent to:
+    ///
+    /// ```ignore
+    /// async fn read_line(&mut self, buf: &mut String) -> io::Result<usize>;
+    /// ```
+    ///
+    /// The newline, if found, is appended to `buf`. The number of bytes
+    /// appended is returned, `0` means the end of the stream was reached.
+    ///
+    /// # Errors
+    ///
+    /// If the read bytes are not valid UTF-8, an error of kind
+    /// [`InvalidData`] is returned and `buf` is left unchanged.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is not cancellation safe: if the future is dropped before
+    /// it completes, the line read so far is lost and `buf` is left empty.
+    /// Use [`read_until`] in a `select!`-like loop instead.
+    ///
+    /// [`InvalidData`]: std::io::ErrorKind::InvalidData
+    /// [`read_until`]: AsyncBufReadExt::read_until
+    fn read_line<'a>(&'a mut self, buf: &'a mut String) -> ReadLine<'a, Self>
+    where
+        Self: Unpin,
+    {
+        read_line(self, buf)
+    }
+}
+
+impl<R: AsyncBufRead + ?Sized> AsyncBufReadExt for R {}
@@ -0,0 +1,63 @@
+use crate::io::AsyncRead;
+use crate::io::util::read::{Read, read};
+use crate::io::util::read_to_end::{ReadToEnd, read_to_end};
+
+/// Reads bytes from a source.
+///
+/// Implemented as an extension trait, adding utility methods to all
+/// [`AsyncRead`] types. Callers will tend to import this trait instead of
+/// [`AsyncRead`].
+///
+/// ```no_run
+/// use mini_runtime_v2::io::AsyncReadExt;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+/// # let mut socket = mini_runtime_v2::net::TcpStream::connect("127.0.0.1:8080").await?;
+///
+/// let mut buffer = [0; 10];
+///
+/// // read up to 10 bytes
+/// let n = socket.read(&mut buffer[..]).await?;
+/// # let _ = n;
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub trait AsyncReadExt: AsyncRead {
+    /// Pulls some bytes from this source into the specified buffer,
+    /// returning how many bytes were read.
+    ///
+    /// Equivalent to:
+    ///
+    /// ```ignore
+    /// async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+    /// ```
+    ///
+    /// A return value of `0` means the end of the stream was reached, or that
+    /// `buf` has a length of `0`.
+    fn read<'a>(&'a mut self, buf: &'a mut [u8]) -> Read<'a, Self>
+    where
+        Self: Unpin,
+    {
+        read(self, buf)
+    }
+
+    /// Reads all bytes until EOF in this source, placing them into `buf`.
+    ///
+    /// Equivalent to:
+    ///
+    /// ```ignore
+    /// async fn read_to_end(&mut self, buf: &mut Vec<u8>) -> io::Result<usize>;
+    /// ```
+    ///
+    /// All bytes read from this source will be appended to the specified
+    /// buffer `buf`. On success, the total number of bytes read is returned.
+    fn read_to_end<'a>(&'a mut self, buf: &'a mut Vec<u8>) -> ReadToEnd<'a, Self>
+    where
+        Self: Unpin,
+    {
+        read_to_end(self, buf)
+    }
+}
+
+impl<R: AsyncRead + ?Sized> AsyncReadExt for R {}
@@ -0,0 +1,86 @@
+use crate::io::AsyncWrite;
+use crate::io::util::flush::{Flush, flush};
+use crate::io::util::shutdown::{Shutdown, shutdown};
+use crate::io::util::write::{Write, write};
+use crate::io::util::write_all::{WriteAll, write_all};
+
+/// Writes bytes to a sink.
+///
+/// Implemented as an extension trait, adding utility methods to all
+/// [`AsyncWrite`] types. Callers will tend to import this trait instead of
+/// [`AsyncWrite`].
+///
+/// ```no_run
+/// use mini_runtime_v2::io::AsyncWriteExt;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+/// # let mut socket = mini_runtime_v2::net::TcpStream::connect("127.0.0.1:8080").await?;
+///
+/// socket.write_all(b"hello world").await?;
+/// socket.flush().await?;
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub trait AsyncWriteExt: AsyncWrite {
+    /// Writes a buffer into this writer, returning how many bytes were
+    /// written.
+    ///
+    /// Equivalent to:
+    ///
+    /// ```ignore
+    /// async fn write(&mut self, buf: &[u8]) -> io::Result<usize>;
+    /// ```
+    fn write<'a>(&'a mut self, src: &'a [u8]) -> Write<'a, Self>
+    where
+        Self: Unpin,
+    {
+        write(self, src)
+    }
+
+    /// Attempts to write an entire buffer into this writer.
+    ///
+    /// Equivalent to:
+    ///
+    /// ```ignore
+    /// async fn write_all(&mut self, buf: &[u8]) -> io::Result<()>;
+    /// ```
+    fn write_all<'a>(&'a mut self, src: &'a [u8]) -> WriteAll<'a, Self>
+    where
+        Self: Unpin,
+    {
+        write_all(self, src)
+    }
+
+    /// Flushes this output stream, ensuring that all intermediately buffered
+    /// contents reach their destination.
+    ///
+    /// Equivalent to:
+    ///
+    /// ```ignore
+    /// async fn flush(&mut self) -> io::Result<()>;
+    /// ```
+    fn flush(&mut self) -> Flush<'_, Self>
+    where
+        Self: Unpin,
+    {
+        flush(self)
+    }
+
+    /// Shuts down the output stream, ensuring that the value can be dropped
+    /// cleanly.
+    ///
+    /// Equivalent to:
+    ///
+    /// ```ignore
+    /// async fn shutdown(&mut self) -> io::Result<()>;
+    /// ```
+    fn shutdown(&mut self) -> Shutdown<'_, Self>
+    where
+        Self: Unpin,
+    {
+        shutdown(self)
+    }
+}
+
+impl<W: AsyncWrite + ?Sized> AsyncWriteExt for W {}
@@ -0,0 +1,62 @@
+use crate::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use std::io;
+
+/// Size of the buffer `copy` moves the data through.
+const DEFAULT_BUF_SIZE: usize = 8 * 1024;
+
+/// Asynchronously copies the entire contents of a reader into a writer.
+///
+/// This function returns a future that will continuously read data from
+/// `reader` and then write it into `writer` in a streaming fashion until
+/// `reader` returns EOF or fails.
+///
+/// On success, the total number of bytes that were copied from `reader` to
+/// `writer` is returned.
+///
+/// This is an asynchronous version of [`std::io::copy`][std].
+///
+/// [std]: std::io::copy
+///
+/// # Errors
+///
+/// The returned future will return an error immediately if any call to
+/// `poll_read` or `poll_write` returns an error.
+///
+/// # Examples
+///
+/// ```
+/// use mini_runtime_v2::io;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build()?;
+/// # rt.block_on(async {
+///
+/// let mut reader: &[u8] = b"hello";
+/// let mut writer: Vec<u8> = vec![];
+///
+/// io::copy(&mut reader, &mut writer).await?;
+///
+/// assert_eq!(&b"hello"[..], &writer[..]);
+/// # Ok::<_, io::Error>(())
+/// # })?;
+/// # Ok::<_, io::Error>(())
+/// ```
+pub async fn copy<R, W>(reader: &mut R, writer: &mut W) -> io::Result<u64>
+where
+    R: AsyncRead + Unpin + ?Sized,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let mut buf = vec![0; DEFAULT_BUF_SIZE].into_boxed_slice();
+    let mut amt = 0;
+
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            // The reader reached EOF, make sure everything written so far
+            // reaches the destination.
+            writer.flush().await?;
+            return Ok(amt);
+        }
+
+        writer.write_all(&buf[..n]).await?;
+        amt += n as u64;
+    }
+}
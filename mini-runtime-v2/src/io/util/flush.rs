@@ -0,0 +1,36 @@
+use crate::io::AsyncWrite;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A future used to fully flush an I/O object.
+///
+/// Created by the [`AsyncWriteExt::flush`][flush] function.
+///
+/// [flush]: crate::io::AsyncWriteExt::flush
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Flush<'a, A: ?Sized> {
+    a: &'a mut A,
+}
+
+/// Creates a future which will entirely flush an I/O object.
+pub(crate) fn flush<A>(a: &mut A) -> Flush<'_, A>
+where
+    A: AsyncWrite + Unpin + ?Sized,
+{
+    Flush { a }
+}
+
+impl<A> Future for Flush<'_, A>
+where
+    A: AsyncWrite + Unpin + ?Sized,
+{
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+        Pin::new(&mut *me.a).poll_flush(cx)
+    }
+}
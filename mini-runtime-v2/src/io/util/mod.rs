@@ -0,0 +1,26 @@
+mod async_buf_read_ext;
+pub use async_buf_read_ext::AsyncBufReadExt;
+
+mod async_read_ext;
+pub use async_read_ext::AsyncReadExt;
+
+mod async_write_ext;
+pub use async_write_ext::AsyncWriteExt;
+
+mod buf_reader;
+pub use buf_reader::BufReader;
+
+mod buf_writer;
+pub use buf_writer::BufWriter;
+
+mod copy;
+pub use copy::copy;
+
+mod flush;
+mod read;
+mod read_line;
+mod read_to_end;
+mod read_until;
+mod shutdown;
+mod write;
+mod write_all;
@@ -0,0 +1,100 @@
+use crate::io::AsyncBufRead;
+use crate::io::util::read_until::read_until_internal;
+use std::future::Future;
+use std::io;
+use std::mem;
+use std::pin::Pin;
+use std::string::FromUtf8Error;
+use std::task::{Context, Poll};
+
+/// Future for the [`read_line`](crate::io::AsyncBufReadExt::read_line) method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadLine<'a, R: ?Sized> {
+    reader: &'a mut R,
+    /// This is the buffer we were provided. It will be replaced with an empty
+    /// string while reading to postpone utf-8 handling until after reading.
+    output: &'a mut String,
+    /// The actual allocation of the string is moved into this vector instead.
+    buf: Vec<u8>,
+    /// The number of bytes appended to buf. This can be less than buf.len() if
+    /// the buffer was not empty when the operation was started.
+    read: usize,
+}
+
+pub(crate) fn read_line<'a, R>(reader: &'a mut R, string: &'a mut String) -> ReadLine<'a, R>
+where
+    R: AsyncBufRead + ?Sized + Unpin,
+{
+    ReadLine {
+        reader,
+        buf: mem::take(string).into_bytes(),
+        output: string,
+        read: 0,
+    }
+}
+
+fn put_back_original_data(output: &mut String, mut vector: Vec<u8>, num_bytes_read: usize) {
+    let original_len = vector.len() - num_bytes_read;
+    vector.truncate(original_len);
+    *output = String::from_utf8(vector).expect("The original data must be valid utf-8.");
+}
+
+/// This handles the various failure cases and puts the string back into
+/// `output`.
+fn finish_string_read(
+    io_res: io::Result<usize>,
+    utf8_res: Result<String, FromUtf8Error>,
+    read: usize,
+    output: &mut String,
+) -> Poll<io::Result<usize>> {
+    match (io_res, utf8_res) {
+        (Ok(num_bytes), Ok(string)) => {
+            debug_assert_eq!(read, 0);
+            *output = string;
+            Poll::Ready(Ok(num_bytes))
+        }
+        (Err(io_err), Ok(string)) => {
+            *output = string;
+            Poll::Ready(Err(io_err))
+        }
+        (Ok(num_bytes), Err(utf8_err)) => {
+            debug_assert_eq!(read, 0);
+            put_back_original_data(output, utf8_err.into_bytes(), num_bytes);
+
+            Poll::Ready(Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )))
+        }
+        (Err(io_err), Err(utf8_err)) => {
+            put_back_original_data(output, utf8_err.into_bytes(), read);
+
+            Poll::Ready(Err(io_err))
+        }
+    }
+}
+
+impl<R: AsyncBufRead + ?Sized + Unpin> Future for ReadLine<'_, R> {
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+
+        let io_res = ready!(read_until_internal(
+            Pin::new(&mut *me.reader),
+            cx,
+            b'\n',
+            &mut me.buf,
+            &mut me.read
+        ));
+        let utf8_res = String::from_utf8(mem::take(&mut me.buf));
+
+        // At this point both buf and output are empty. The allocation is in
+        // utf8_res.
+
+        debug_assert!(me.buf.is_empty());
+        debug_assert!(me.output.is_empty());
+        finish_string_read(io_res, utf8_res, me.read, me.output)
+    }
+}
@@ -0,0 +1,73 @@
+use crate::io::AsyncRead;
+use std::future::Future;
+use std::io;
+use std::mem;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Number of bytes the buffer grows by when it is full.
+const PROBE_SIZE: usize = 32;
+
+/// Future for the [`read_to_end`](crate::io::AsyncReadExt::read_to_end)
+/// method.
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct ReadToEnd<'a, R: ?Sized> {
+    reader: &'a mut R,
+    buf: &'a mut Vec<u8>,
+    /// The number of bytes appended to buf. This can be less than buf.len() if
+    /// the buffer was not empty when the operation was started.
+    read: usize,
+}
+
+pub(crate) fn read_to_end<'a, R>(reader: &'a mut R, buffer: &'a mut Vec<u8>) -> ReadToEnd<'a, R>
+where
+    R: AsyncRead + Unpin + ?Sized,
+{
+    ReadToEnd {
+        reader,
+        buf: buffer,
+        read: 0,
+    }
+}
+
+impl<A> Future for ReadToEnd<'_, A>
+where
+    A: AsyncRead + ?Sized + Unpin,
+{
+    type Output = io::Result<usize>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+
+        loop {
+            if me.buf.capacity() - me.buf.len() < PROBE_SIZE {
+                me.buf.reserve(PROBE_SIZE);
+            }
+
+            // Read into the spare capacity, zeroed so it can be handed out as
+            // an initialized slice.
+            let len = me.buf.len();
+            me.buf.resize(me.buf.capacity(), 0);
+
+            let res = Pin::new(&mut *me.reader).poll_read(cx, &mut me.buf[len..]);
+            let n = match res {
+                Poll::Ready(Ok(n)) => n,
+                Poll::Ready(Err(e)) => {
+                    me.buf.truncate(len);
+                    return Poll::Ready(Err(e));
+                }
+                Poll::Pending => {
+                    me.buf.truncate(len);
+                    return Poll::Pending;
+                }
+            };
+
+            me.buf.truncate(len + n);
+            if n == 0 {
+                return Poll::Ready(Ok(mem::replace(&mut me.read, 0)));
+            }
+            me.read += n;
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use crate::io::AsyncWrite;
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A future used to shutdown an I/O object.
+///
+/// Created by the [`AsyncWriteExt::shutdown`][shutdown] function.
+///
+/// [shutdown]: crate::io::AsyncWriteExt::shutdown
+#[derive(Debug)]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Shutdown<'a, A: ?Sized> {
+    a: &'a mut A,
+}
+
+/// Creates a future which will shutdown an I/O object.
+pub(crate) fn shutdown<A>(a: &mut A) -> Shutdown<'_, A>
+where
+    A: AsyncWrite + Unpin + ?Sized,
+{
+    Shutdown { a }
+}
+
+impl<A> Future for Shutdown<'_, A>
+where
+    A: AsyncWrite + Unpin + ?Sized,
+{
+    type Output = io::Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+        Pin::new(&mut *me.a).poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,21 @@
+#[macro_use]
+pub mod macros;
+pub mod fs;
+pub mod future;
+pub mod io;
+pub mod net;
+#[cfg(unix)]
+pub mod process;
+pub mod runtime;
+#[cfg(unix)]
+pub mod signal;
+pub mod stream;
+pub mod sync;
+pub mod task;
+pub mod time;
+mod util;
+
+pub use task::spawn;
+
+#[cfg(feature = "macros")]
+pub use mini_runtime_v2_macros::{main, test};
@@ -3,3 +3,12 @@ mod thread_local;
 
 #[macro_use]
 mod pin;
+
+#[macro_use]
+mod ready;
+
+#[macro_use]
+mod select;
+
+#[doc(hidden)]
+pub mod support;
@@ -0,0 +1,32 @@
+/// Extracts the successful type of a `Poll<T>`.
+///
+/// Returns `Poll::Pending` from the enclosing function if the value is
+/// `Pending`, so that a `poll` function reads like straight-line code:
+///
+/// ```
+/// # use mini_runtime_v2::net::{TcpListener, TcpStream};
+/// # use mini_runtime_v2::ready;
+/// # use mini_runtime_v2::stream::Stream;
+/// # use std::io;
+/// # use std::pin::Pin;
+/// # use std::task::{Context, Poll};
+/// # struct Incoming {
+/// #     listener: TcpListener,
+/// # }
+/// # impl Stream for Incoming {
+/// #     type Item = io::Result<TcpStream>;
+/// fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+///     let (socket, _) = ready!(self.listener.poll_accept(cx))?;
+///     Poll::Ready(Some(Ok(socket)))
+/// }
+/// # }
+/// ```
+#[macro_export]
+macro_rules! ready {
+    ($e:expr $(,)?) => {
+        match $e {
+            std::task::Poll::Ready(t) => t,
+            std::task::Poll::Pending => return std::task::Poll::Pending,
+        }
+    };
+}
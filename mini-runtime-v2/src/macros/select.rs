@@ -0,0 +1,205 @@
+/// Waits on multiple concurrent branches, returning when the **first** branch
+/// completes, cancelling the remaining branches.
+///
+/// The `select!` macro must be used inside of async functions, closures, and
+/// blocks. It accepts one or more branches, with the following pattern:
+///
+/// ```text
+/// <pattern> = <async expression> => <handler>,
+/// ```
+///
+/// and an optional last `else => <expression>` branch.
+///
+/// All the async expressions are evaluated first, then their futures are
+/// polled concurrently by the current task. When one completes, its output
+/// is matched against the branch's pattern:
+///
+/// - if it matches, the other futures are dropped and the handler is
+///   evaluated with the pattern's bindings, as the value of `select!`;
+/// - otherwise the branch is disabled and `select!` keeps waiting on the
+///   other branches.
+///
+/// When all the branches are disabled, the `else` expression is evaluated.
+/// `select!` panics if there is none.
+///
+/// The handlers are evaluated outside of any loop or closure: `break`,
+/// `continue`, `return` and `?` apply to the enclosing code. This makes
+/// `select!` in a loop the usual way to handle several event sources:
+///
+/// ```
+/// use mini_runtime_v2::select;
+/// # use mini_runtime_v2::sync::{CancellationToken, mpsc};
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+/// # rt.block_on(async {
+/// # let (tx, mut rx) = mpsc::unbounded_channel();
+/// # tx.send(1).unwrap();
+/// # drop(tx);
+/// # let token = CancellationToken::new();
+/// # token.cancel();
+///
+/// loop {
+///     select! {
+///         Some(msg) = rx.recv() => println!("got {msg}"),
+///         _ = token.cancelled() => break,
+///         else => break,
+///     }
+/// }
+/// # });
+/// ```
+///
+/// The branches are polled in a random order on every poll, so that a
+/// branch that is always ready doesn't starve the others.
+///
+/// # Cancel safety
+///
+/// The futures of the branches that did not complete are dropped. Only use
+/// futures which can be dropped without losing data, e.g. `mpsc::Receiver::recv`,
+/// or create the future once outside of the loop and select on `&mut fut`.
+///
+/// # Patterns
+///
+/// To decide whether a branch is disabled, the pattern is first matched
+/// against a reference to the output: binding modifiers like `ref` and `mut`
+/// are not supported.
+///
+/// Up to 16 branches are supported.
+#[macro_export]
+macro_rules! select {
+    // All the branches were parsed: generate the code.
+    (@ {
+        ids: [$($unused:tt)*],
+        count: $count:expr,
+        branches: [$( ($id:ident $n:tt) [$p:pat] [$f:expr] [$h:tt] )*],
+        else: [$($else:tt)?]
+    }) => {{
+        #[allow(non_camel_case_types)]
+        enum Out<$($id,)*> {
+            $($id($id),)*
+            Disabled,
+        }
+
+        const BRANCHES: u32 = $count;
+
+        // The futures are dropped before the handler runs, which may use what
+        // they borrowed.
+        let output = {
+            $( let mut $id = ::std::pin::pin!(::std::option::Option::Some($f)); )*
+
+            $crate::future::poll_fn(|cx| {
+                let start = $crate::macros::support::thread_rng_n(BRANCHES);
+                let mut pending = false;
+
+                for i in 0..BRANCHES {
+                    #[allow(clippy::modulo_one)]
+                    match (start + i) % BRANCHES {
+                        $(
+                            $n => {
+                                let fut = match $id.as_mut().as_pin_mut() {
+                                    ::std::option::Option::Some(fut) => fut,
+                                    // Disabled.
+                                    ::std::option::Option::None => continue,
+                                };
+                                let out = match ::std::future::Future::poll(fut, cx) {
+                                    ::std::task::Poll::Ready(out) => out,
+                                    ::std::task::Poll::Pending => {
+                                        pending = true;
+                                        continue;
+                                    }
+                                };
+
+                                // A completed future must not be polled again.
+                                $id.set(::std::option::Option::None);
+
+                                #[allow(unused_variables, unreachable_patterns)]
+                                match &out {
+                                    $p => {}
+                                    _ => continue,
+                                }
+                                return ::std::task::Poll::Ready(Out::$id(out));
+                            }
+                        )*
+                        _ => unreachable!("select! branch index out of range"),
+                    }
+                }
+
+                if pending {
+                    ::std::task::Poll::Pending
+                } else {
+                    ::std::task::Poll::Ready(Out::Disabled)
+                }
+            })
+            .await
+        };
+
+        match output {
+            $( Out::$id($p) => $h, )*
+            Out::Disabled => $crate::select!(@else $($else)?),
+            #[allow(unreachable_patterns)]
+            _ => unreachable!("failed to match bind"),
+        }
+    }};
+
+    (@else) => {
+        panic!("all branches are disabled and there is no else branch")
+    };
+    (@else $else:tt) => {
+        $else
+    };
+
+    // The `else` branch, last.
+    (@ { ids: $ids:tt, count: $count:expr, branches: $branches:tt, else: [] }
+        else => $h:expr $(,)?
+    ) => {
+        $crate::select!(@ { ids: $ids, count: $count, branches: $branches, else: [$h] })
+    };
+
+    // A branch with a block handler, the comma is optional.
+    (@ { ids: [$next:tt $($ids:tt)*], count: $count:expr, branches: [$($branches:tt)*], else: [] }
+        $p:pat = $f:expr => $h:block $(, $($rest:tt)*)?
+    ) => {
+        $crate::select!(@ {
+            ids: [$($ids)*],
+            count: $count + 1,
+            branches: [$($branches)* $next [$p] [$f] [$h]],
+            else: []
+        } $($($rest)*)?)
+    };
+    (@ { ids: [$next:tt $($ids:tt)*], count: $count:expr, branches: [$($branches:tt)*], else: [] }
+        $p:pat = $f:expr => $h:block $($rest:tt)+
+    ) => {
+        $crate::select!(@ {
+            ids: [$($ids)*],
+            count: $count + 1,
+            branches: [$($branches)* $next [$p] [$f] [$h]],
+            else: []
+        } $($rest)+)
+    };
+
+    // A branch with an expression handler.
+    (@ { ids: [$next:tt $($ids:tt)*], count: $count:expr, branches: [$($branches:tt)*], else: [] }
+        $p:pat = $f:expr => $h:expr $(, $($rest:tt)*)?
+    ) => {
+        $crate::select!(@ {
+            ids: [$($ids)*],
+            count: $count + 1,
+            branches: [$($branches)* $next [$p] [$f] [$h]],
+            else: []
+        } $($($rest)*)?)
+    };
+
+    (@ $($t:tt)*) => {
+        compile_error!("invalid `select!` syntax, or more than 16 branches")
+    };
+
+    ($($t:tt)+) => {
+        $crate::select!(@ {
+            ids: [
+                (_0 0) (_1 1) (_2 2) (_3 3) (_4 4) (_5 5) (_6 6) (_7 7)
+                (_8 8) (_9 9) (_10 10) (_11 11) (_12 12) (_13 13) (_14 14) (_15 15)
+            ],
+            count: 0,
+            branches: [],
+            else: []
+        } $($t)+)
+    };
+}
@@ -0,0 +1,8 @@
+//! Items used by the expansion of the exported macros, not public API.
+
+use crate::runtime::context;
+
+/// Returns a random number in `0..n`, from the rng of the current thread.
+pub fn thread_rng_n(n: u32) -> u32 {
+    context::with_rng(|rng| rng.fastrand_n(n))
+}
@@ -1,20 +1,44 @@
-#[macro_use]
-pub mod macros;
-mod runtime;
-mod task;
-mod util;
-
-use crate::task::JoinHandle;
-pub use task::spawn;
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::task::{self, JoinSet};
 
 fn main() {
+    // Outside of a runtime, `try_spawn` fails where `spawn` would panic.
+    if let Err(e) = task::try_spawn(async {}) {
+        println!("cannot spawn yet: {e}");
+    }
+
     runtime::Builder::new_current_thread()
         .build()
         .unwrap()
         .block_on(async {
-            let _: JoinHandle<i32> = spawn(async move { 5 + 3 });
-            let _: JoinHandle<i32> = spawn(async move { 5 + 3 });
-            let _: JoinHandle<i32> = spawn(async move { 5 + 3 });
-            let _: JoinHandle<i32> = spawn(async move { 5 + 3 });
+            let mut set: JoinSet<i32> = JoinSet::new();
+
+            for i in 0..4 {
+                let id: task::Id = set.spawn(async move {
+                    println!("task {} is running", task::id());
+                    5 + i
+                });
+                println!("spawned task {id}");
+            }
+
+            println!("{} tasks in the set", set.len());
+
+            while let Some(result) = set.join_next().await {
+                let result: Result<i32, task::JoinError> = result;
+                match result {
+                    Ok(value) => println!("a task returned {value}"),
+                    Err(e) => println!("{e}"),
+                }
+            }
+
+            // Aborted tasks complete with a cancelled `JoinError`.
+            set.spawn(async { 0 });
+            set.abort_all();
+            if let Some(Err(e)) = set.join_next().await {
+                println!("{e}");
+            }
+
+            assert!(set.is_empty());
+            println!("block_on runs outside of a task: {:?}", task::try_id());
         });
 }
@@ -0,0 +1,163 @@
+use crate::runtime::task::JoinHandle;
+use crate::task;
+use std::io;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV4, SocketAddrV6};
+
+/// Converts or resolves without blocking to one or more `SocketAddr` values.
+///
+/// The asynchronous counterpart of [`std::net::ToSocketAddrs`], implemented
+/// for the same types. IP addresses are converted right away, host names are
+/// resolved with the system resolver on the blocking pool, see
+/// [`spawn_blocking`](crate::task::spawn_blocking), so that a slow DNS server
+/// doesn't block the runtime's thread.
+///
+/// This trait is sealed and is intended to be used only through the
+/// functions taking an address, e.g. [`TcpStream::connect`] or
+/// [`lookup_host`].
+///
+/// [`TcpStream::connect`]: crate::net::TcpStream::connect
+pub trait ToSocketAddrs: sealed::ToSocketAddrsPriv {}
+
+/// Performs a DNS resolution.
+///
+/// The returned iterator may not actually yield any values depending on the
+/// outcome of any resolution performed.
+///
+/// ```no_run
+/// use mini_runtime_v2::net;
+///
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+/// for addr in net::lookup_host("localhost:3000").await? {
+///     println!("socket address is {}", addr);
+/// }
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+///
+/// # Panics
+///
+/// This function panics if called outside of a Mini runtime when `host` is
+/// not an IP address.
+pub async fn lookup_host<T>(host: T) -> io::Result<impl Iterator<Item = SocketAddr>>
+where
+    T: ToSocketAddrs,
+{
+    to_socket_addrs(host).await.map(Vec::into_iter)
+}
+
+/// Converts or resolves `addr`, in the order returned by the resolver.
+pub(crate) async fn to_socket_addrs<T: ToSocketAddrs>(addr: T) -> io::Result<Vec<SocketAddr>> {
+    match addr.to_socket_addrs(sealed::Internal) {
+        sealed::MaybeReady::Ready(addrs) => Ok(addrs),
+        sealed::MaybeReady::Blocking(handle) => handle.await?,
+    }
+}
+
+/// Resolves `host` on the blocking pool, unless it is an IP address.
+fn resolve(host: &str, port: Option<u16>) -> sealed::MaybeReady {
+    let parsed = match port {
+        None => host.parse::<SocketAddr>().ok(),
+        Some(port) => host
+            .parse::<IpAddr>()
+            .ok()
+            .map(|ip| SocketAddr::new(ip, port)),
+    };
+    if let Some(addr) = parsed {
+        return sealed::MaybeReady::Ready(vec![addr]);
+    }
+
+    let host = host.to_owned();
+    sealed::MaybeReady::Blocking(task::spawn_blocking(move || {
+        let addrs = match port {
+            None => std::net::ToSocketAddrs::to_socket_addrs(&host)?,
+            Some(port) => std::net::ToSocketAddrs::to_socket_addrs(&(&host[..], port))?,
+        };
+        Ok(addrs.collect())
+    }))
+}
+
+macro_rules! ready_addrs {
+    ($($ty:ty => |$addr:ident| $conv:expr;)*) => {$(
+        impl ToSocketAddrs for $ty {}
+
+        impl sealed::ToSocketAddrsPriv for $ty {
+            fn to_socket_addrs(&self, _: sealed::Internal) -> sealed::MaybeReady {
+                let $addr = self;
+                sealed::MaybeReady::Ready($conv)
+            }
+        }
+    )*};
+}
+
+ready_addrs! {
+    SocketAddr => |addr| vec![*addr];
+    SocketAddrV4 => |addr| vec![SocketAddr::V4(*addr)];
+    SocketAddrV6 => |addr| vec![SocketAddr::V6(*addr)];
+    (IpAddr, u16) => |addr| vec![SocketAddr::new(addr.0, addr.1)];
+    (Ipv4Addr, u16) => |addr| vec![SocketAddr::new(IpAddr::V4(addr.0), addr.1)];
+    (Ipv6Addr, u16) => |addr| vec![SocketAddr::new(IpAddr::V6(addr.0), addr.1)];
+    [SocketAddr] => |addrs| addrs.to_vec();
+}
+
+impl ToSocketAddrs for str {}
+
+impl sealed::ToSocketAddrsPriv for str {
+    fn to_socket_addrs(&self, _: sealed::Internal) -> sealed::MaybeReady {
+        resolve(self, None)
+    }
+}
+
+impl ToSocketAddrs for String {}
+
+impl sealed::ToSocketAddrsPriv for String {
+    fn to_socket_addrs(&self, _: sealed::Internal) -> sealed::MaybeReady {
+        resolve(self, None)
+    }
+}
+
+impl ToSocketAddrs for (&str, u16) {}
+
+impl sealed::ToSocketAddrsPriv for (&str, u16) {
+    fn to_socket_addrs(&self, _: sealed::Internal) -> sealed::MaybeReady {
+        resolve(self.0, Some(self.1))
+    }
+}
+
+impl ToSocketAddrs for (String, u16) {}
+
+impl sealed::ToSocketAddrsPriv for (String, u16) {
+    fn to_socket_addrs(&self, _: sealed::Internal) -> sealed::MaybeReady {
+        resolve(&self.0, Some(self.1))
+    }
+}
+
+impl<T: ToSocketAddrs + ?Sized> ToSocketAddrs for &T {}
+
+impl<T: ToSocketAddrs + ?Sized> sealed::ToSocketAddrsPriv for &T {
+    fn to_socket_addrs(&self, internal: sealed::Internal) -> sealed::MaybeReady {
+        (**self).to_socket_addrs(internal)
+    }
+}
+
+pub(crate) mod sealed {
+    //! The trait is sealed: the types it returns are not nameable outside
+    //! of the crate.
+
+    use super::*;
+
+    pub trait ToSocketAddrsPriv {
+        fn to_socket_addrs(&self, internal: Internal) -> MaybeReady;
+    }
+
+    /// Prevents calling `to_socket_addrs` outside of the crate.
+    #[derive(Debug, Clone, Copy)]
+    pub struct Internal;
+
+    /// The addresses, or the pending resolution of a host name.
+    pub enum MaybeReady {
+        Ready(Vec<SocketAddr>),
+        Blocking(JoinHandle<io::Result<Vec<SocketAddr>>>),
+    }
+}
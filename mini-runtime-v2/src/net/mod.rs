@@ -0,0 +1,21 @@
+//! TCP/UDP bindings for the Mini runtime.
+//!
+//! This module contains the TCP/UDP networking types, similar to the standard
+//! library, which can be used to implement networking protocols.
+//!
+//! The types are backed by the runtime's I/O driver, so the runtime must be
+//! built with [`enable_io`].
+//!
+//! [`enable_io`]: crate::runtime::Builder::enable_io
+
+mod addr;
+pub(crate) use addr::to_socket_addrs;
+pub use addr::{ToSocketAddrs, lookup_host};
+
+pub mod tcp;
+pub use tcp::listener::TcpListener;
+pub use tcp::socket::TcpSocket;
+pub use tcp::stream::TcpStream;
+
+mod udp;
+pub use udp::UdpSocket;
@@ -0,0 +1,123 @@
+use crate::net::{TcpListener, TcpStream};
+use crate::stream::Stream;
+use crate::sync::{OwnedSemaphorePermit, Semaphore};
+use std::io;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Stream returned by the [`TcpListener::incoming`] function representing
+/// the stream of sockets received from a listener.
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct Incoming<'a> {
+    inner: &'a TcpListener,
+}
+
+/// Stream returned by [`Incoming::with_limit`], accepting connections only
+/// while a permit of the semaphore is available.
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct LimitedIncoming<'a> {
+    inner: &'a TcpListener,
+    limit: Arc<Semaphore>,
+
+    /// Permit acquired for the next connection, kept if `accept` fails.
+    permit: Option<OwnedSemaphorePermit>,
+
+    /// Position in the semaphore's wait queue, while waiting for a permit.
+    waiter: Option<u64>,
+}
+
+impl<'a> Incoming<'a> {
+    pub(crate) fn new(listener: &TcpListener) -> Incoming<'_> {
+        Incoming { inner: listener }
+    }
+
+    /// Limits the number of connections handled at once to the permits of
+    /// `limit`.
+    ///
+    /// Each connection is yielded with a permit of the semaphore, which the
+    /// connection's task holds until the connection is closed. While no
+    /// permit is available, the stream stops accepting: new connections wait
+    /// in the listen backlog of the OS instead of piling up as tasks.
+    ///
+    /// Closing the semaphore ends the stream.
+    ///
+    /// ```no_run
+    /// use mini_runtime_v2::stream::StreamExt;
+    /// use mini_runtime_v2::sync::Semaphore;
+    /// use std::sync::Arc;
+    /// # use mini_runtime_v2::net::{TcpListener, TcpStream};
+    /// # use mini_runtime_v2::task;
+    /// # async fn process_socket(_socket: TcpStream) {}
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+    /// # rt.block_on(async {
+    /// # let listener = TcpListener::bind("127.0.0.1:8080").await?;
+    ///
+    /// let limit = Arc::new(Semaphore::new(100));
+    /// let mut incoming = listener.incoming().with_limit(limit);
+    ///
+    /// while let Some(conn) = incoming.next().await {
+    ///     let (socket, permit) = conn?;
+    ///     task::spawn(async move {
+    ///         process_socket(socket).await;
+    ///         drop(permit);
+    ///     });
+    /// }
+    /// # Ok::<_, std::io::Error>(())
+    /// # })?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    pub fn with_limit(self, limit: Arc<Semaphore>) -> LimitedIncoming<'a> {
+        LimitedIncoming {
+            inner: self.inner,
+            limit,
+            permit: None,
+            waiter: None,
+        }
+    }
+}
+
+impl Stream for Incoming<'_> {
+    type Item = io::Result<TcpStream>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let (socket, _) = ready!(self.inner.poll_accept(cx))?;
+        Poll::Ready(Some(Ok(socket)))
+    }
+}
+
+impl LimitedIncoming<'_> {
+    /// Returns the semaphore limiting the connections.
+    pub fn limit(&self) -> &Arc<Semaphore> {
+        &self.limit
+    }
+}
+
+impl Stream for LimitedIncoming<'_> {
+    type Item = io::Result<(TcpStream, OwnedSemaphorePermit)>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.get_mut();
+
+        if me.permit.is_none() {
+            match ready!(me.limit.poll_acquire_owned(cx, &mut me.waiter)) {
+                Ok(permit) => me.permit = Some(permit),
+                Err(_) => return Poll::Ready(None),
+            }
+        }
+
+        let (socket, _) = ready!(me.inner.poll_accept(cx))?;
+        let permit = me.permit.take().expect("permit acquired above");
+        Poll::Ready(Some(Ok((socket, permit))))
+    }
+}
+
+impl Drop for LimitedIncoming<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.waiter {
+            self.limit.cancel_acquire(id);
+        }
+    }
+}
@@ -0,0 +1,202 @@
+use crate::io::PollEvented;
+use crate::net::TcpStream;
+use crate::net::tcp::Incoming;
+use crate::net::{ToSocketAddrs, to_socket_addrs};
+use crate::runtime::io::Direction;
+use std::fmt;
+use std::io;
+use std::net::{self, SocketAddr};
+use std::task::{Context, Poll};
+
+/// A TCP socket server, listening for connections.
+///
+/// You can accept a new connection by using the [`accept`](TcpListener::accept)
+/// method. To set socket options before binding, e.g. `SO_REUSEADDR`, create
+/// the listener from a [`TcpSocket`](crate::net::TcpSocket).
+///
+/// # Examples
+///
+/// Using `accept`:
+///
+/// ```no_run
+/// use mini_runtime_v2::net::TcpListener;
+/// # use mini_runtime_v2::net::TcpStream;
+/// # async fn process_socket(_socket: TcpStream) {}
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+///
+/// let listener = TcpListener::bind("127.0.0.1:8080").await?;
+///
+/// loop {
+///     let (socket, _) = listener.accept().await?;
+///     process_socket(socket).await;
+/// }
+/// # #[allow(unreachable_code)]
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub struct TcpListener {
+    io: PollEvented<mio::net::TcpListener>,
+}
+
+impl TcpListener {
+    /// Creates a new `TcpListener`, which will be bound to the specified
+    /// address.
+    ///
+    /// The returned listener is ready for accepting connections.
+    ///
+    /// Binding with a port number of 0 will request that the OS assigns a port
+    /// to this listener. The port allocated can be queried via the
+    /// `local_addr` method.
+    ///
+    /// If `addr` yields multiple addresses, bind will be attempted with each
+    /// of the addresses until one succeeds and returns the listener. If none
+    /// of the addresses succeed in creating a listener, the error returned
+    /// from the last attempt (the last address) is returned. A host name is
+    /// resolved on the blocking pool, see [`ToSocketAddrs`].
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_io`].
+    ///
+    /// [`enable_io`]: crate::runtime::Builder::enable_io
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<TcpListener> {
+        let mut last_err = None;
+
+        for addr in to_socket_addrs(addr).await? {
+            match TcpListener::bind_addr(addr) {
+                Ok(listener) => return Ok(listener),
+                Err(e) => last_err = Some(e),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "could not resolve to any address",
+            )
+        }))
+    }
+
+    fn bind_addr(addr: SocketAddr) -> io::Result<TcpListener> {
+        let listener = mio::net::TcpListener::bind(addr)?;
+        TcpListener::new(listener)
+    }
+
+    /// Accepts a new incoming connection from this listener.
+    ///
+    /// This function will yield once a new TCP connection is established. When
+    /// established, the corresponding [`TcpStream`] and the remote peer's
+    /// address will be returned.
+    pub async fn accept(&self) -> io::Result<(TcpStream, SocketAddr)> {
+        let (mio, addr) = self
+            .io
+            .registration()
+            .async_io(Direction::Read, || self.io.accept())
+            .await?;
+
+        let stream = TcpStream::new(mio)?;
+        Ok((stream, addr))
+    }
+
+    /// Polls to accept a new incoming connection to this listener.
+    ///
+    /// If there is no connection to accept, `Poll::Pending` is returned and the
+    /// current task will be notified by a waker. Note that on multiple calls
+    /// to `poll_accept`, only the `Waker` from the `Context` passed to the most
+    /// recent call is scheduled to receive a wakeup.
+    pub fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<io::Result<(TcpStream, SocketAddr)>> {
+        let (mio, addr) = ready!(
+            self.io
+                .registration()
+                .poll_io(cx, Direction::Read, || self.io.accept())
+        )?;
+
+        let stream = TcpStream::new(mio)?;
+        Poll::Ready(Ok((stream, addr)))
+    }
+
+    /// Returns a stream over the connections being received on this listener.
+    ///
+    /// The returned stream will never return `None` and will also not yield
+    /// the peer's `SocketAddr` structure. Iterating over it is equivalent to
+    /// calling [`accept`] in a loop.
+    ///
+    /// ```no_run
+    /// use mini_runtime_v2::stream::StreamExt;
+    /// # use mini_runtime_v2::net::{TcpListener, TcpStream};
+    /// # async fn process_socket(_socket: TcpStream) {}
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+    /// # rt.block_on(async {
+    /// # let listener = TcpListener::bind("127.0.0.1:8080").await?;
+    ///
+    /// let mut incoming = listener.incoming();
+    ///
+    /// while let Some(socket) = incoming.next().await {
+    ///     process_socket(socket?).await;
+    /// }
+    /// # Ok::<_, std::io::Error>(())
+    /// # })?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    ///
+    /// Use [`Incoming::with_limit`] to bound the number of connections
+    /// handled at once.
+    ///
+    /// [`accept`]: TcpListener::accept
+    pub fn incoming(&self) -> Incoming<'_> {
+        Incoming::new(self)
+    }
+
+    /// Creates new `TcpListener` from a `std::net::TcpListener`.
+    ///
+    /// The caller is responsible for ensuring that the listener is in
+    /// non-blocking mode.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_io`].
+    ///
+    /// [`enable_io`]: crate::runtime::Builder::enable_io
+    #[track_caller]
+    pub fn from_std(listener: net::TcpListener) -> io::Result<TcpListener> {
+        let io = mio::net::TcpListener::from_std(listener);
+        TcpListener::new(io)
+    }
+
+    #[track_caller]
+    pub(crate) fn new(listener: mio::net::TcpListener) -> io::Result<TcpListener> {
+        let io = PollEvented::new(listener)?;
+        Ok(TcpListener { io })
+    }
+
+    /// Returns the local address that this listener is bound to.
+    ///
+    /// This can be useful, for example, when binding to port 0 to figure out
+    /// which port was actually bound.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.io.local_addr()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet
+    /// sent from this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.io.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.io.ttl()
+    }
+}
+
+impl fmt::Debug for TcpListener {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.io.fmt(f)
+    }
+}
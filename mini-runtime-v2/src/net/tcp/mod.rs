@@ -0,0 +1,16 @@
+//! TCP utility types.
+
+mod incoming;
+pub use incoming::{Incoming, LimitedIncoming};
+
+pub(crate) mod listener;
+
+pub(crate) mod socket;
+
+mod split;
+pub use split::{ReadHalf, WriteHalf};
+
+mod split_owned;
+pub use split_owned::{OwnedReadHalf, OwnedWriteHalf, ReuniteError};
+
+pub(crate) mod stream;
@@ -0,0 +1,224 @@
+use crate::net::{TcpListener, TcpStream};
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// A TCP socket that has not yet been converted to a `TcpStream` or
+/// `TcpListener`.
+///
+/// `TcpSocket` wraps an operating system socket and enables the caller to
+/// configure the socket before establishing a TCP connection or accepting
+/// inbound connections. Some options, like `SO_REUSEADDR`, only take effect
+/// when set before the socket is bound.
+///
+/// Calling `TcpStream::connect("127.0.0.1:8080")` is equivalent to:
+///
+/// ```no_run
+/// use mini_runtime_v2::net::TcpSocket;
+///
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+/// let addr = "127.0.0.1:8080".parse().unwrap();
+/// let socket = TcpSocket::new_v4()?;
+/// let stream = socket.connect(addr).await?;
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+///
+/// Restarting a server on the address of a connection still in `TIME_WAIT`:
+///
+/// ```no_run
+/// # use mini_runtime_v2::net::TcpSocket;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # let _guard = rt.enter();
+/// # let addr = "127.0.0.1:8080".parse().unwrap();
+/// let socket = TcpSocket::new_v4()?;
+/// socket.set_reuseaddr(true)?;
+/// socket.bind(addr)?;
+///
+/// let listener = socket.listen(1024)?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub struct TcpSocket {
+    inner: socket2::Socket,
+}
+
+impl TcpSocket {
+    /// Creates a new socket configured for IPv4.
+    ///
+    /// The socket is non-blocking, it can be used outside of a runtime until
+    /// it is converted by [`connect`](TcpSocket::connect) or
+    /// [`listen`](TcpSocket::listen).
+    pub fn new_v4() -> io::Result<TcpSocket> {
+        TcpSocket::new(socket2::Domain::IPV4)
+    }
+
+    /// Creates a new socket configured for IPv6.
+    ///
+    /// See [`new_v4`](TcpSocket::new_v4).
+    pub fn new_v6() -> io::Result<TcpSocket> {
+        TcpSocket::new(socket2::Domain::IPV6)
+    }
+
+    fn new(domain: socket2::Domain) -> io::Result<TcpSocket> {
+        let inner =
+            socket2::Socket::new(domain, socket2::Type::STREAM, Some(socket2::Protocol::TCP))?;
+        inner.set_nonblocking(true)?;
+        Ok(TcpSocket { inner })
+    }
+
+    /// Allows the socket to bind to an in-use address.
+    ///
+    /// Behavior is platform specific. Refer to the target platform's
+    /// documentation for more details.
+    pub fn set_reuseaddr(&self, reuseaddr: bool) -> io::Result<()> {
+        self.inner.set_reuse_address(reuseaddr)
+    }
+
+    /// Retrieves the value set for `SO_REUSEADDR` on this socket.
+    pub fn reuseaddr(&self) -> io::Result<bool> {
+        self.inner.reuse_address()
+    }
+
+    /// Allows the socket to bind to an in-use port. Only available for unix
+    /// systems (excluding Solaris & Illumos).
+    ///
+    /// Behavior is platform specific. Refer to the target platform's
+    /// documentation for more details.
+    #[cfg(all(unix, not(target_os = "solaris"), not(target_os = "illumos")))]
+    pub fn set_reuseport(&self, reuseport: bool) -> io::Result<()> {
+        self.inner.set_reuse_port(reuseport)
+    }
+
+    /// Retrieves the value set for `SO_REUSEPORT` on this socket.
+    #[cfg(all(unix, not(target_os = "solaris"), not(target_os = "illumos")))]
+    pub fn reuseport(&self) -> io::Result<bool> {
+        self.inner.reuse_port()
+    }
+
+    /// Sets the size of the TCP send buffer on this socket.
+    ///
+    /// On most operating systems, this sets the `SO_SNDBUF` socket option.
+    pub fn set_send_buffer_size(&self, size: u32) -> io::Result<()> {
+        self.inner.set_send_buffer_size(size as usize)
+    }
+
+    /// Returns the size of the TCP send buffer for this socket.
+    pub fn send_buffer_size(&self) -> io::Result<u32> {
+        self.inner.send_buffer_size().map(|n| n as u32)
+    }
+
+    /// Sets the size of the TCP receive buffer on this socket.
+    ///
+    /// On most operating systems, this sets the `SO_RCVBUF` socket option.
+    pub fn set_recv_buffer_size(&self, size: u32) -> io::Result<()> {
+        self.inner.set_recv_buffer_size(size as usize)
+    }
+
+    /// Returns the size of the TCP receive buffer for this socket.
+    pub fn recv_buffer_size(&self) -> io::Result<u32> {
+        self.inner.recv_buffer_size().map(|n| n as u32)
+    }
+
+    /// Sets the linger duration of this socket by setting the `SO_LINGER`
+    /// option, see [`TcpStream::set_linger`].
+    pub fn set_linger(&self, dur: Option<Duration>) -> io::Result<()> {
+        self.inner.set_linger(dur)
+    }
+
+    /// Reads the linger duration for this socket by getting the `SO_LINGER`
+    /// option.
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        self.inner.linger()
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option on this socket, see
+    /// [`TcpStream::set_nodelay`].
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.inner.set_nodelay(nodelay)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket.
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.inner.nodelay()
+    }
+
+    /// Gets the local address of this socket.
+    ///
+    /// Will fail on windows if called before `bind`.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.inner.local_addr().and_then(|addr| {
+            addr.as_socket().ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidInput, "not an IP socket address")
+            })
+        })
+    }
+
+    /// Binds the socket to the given address.
+    ///
+    /// This calls the `bind(2)` operating-system function. Behavior is
+    /// platform specific. Refer to the target platform's documentation for
+    /// more details.
+    pub fn bind(&self, addr: SocketAddr) -> io::Result<()> {
+        self.inner.bind(&addr.into())
+    }
+
+    /// Establishes a TCP connection with a peer at the specified socket
+    /// address.
+    ///
+    /// The `TcpSocket` is consumed. Once the connection is established, a
+    /// connected [`TcpStream`] is returned. If the connection fails, the
+    /// encountered error is returned.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_io`].
+    ///
+    /// [`enable_io`]: crate::runtime::Builder::enable_io
+    pub async fn connect(self, addr: SocketAddr) -> io::Result<TcpStream> {
+        // The socket is non-blocking: the connection is established in the
+        // background, `connect_mio` waits for it.
+        match self.inner.connect(&addr.into()) {
+            Ok(()) => {}
+            #[cfg(unix)]
+            Err(e) if e.raw_os_error() == Some(libc::EINPROGRESS) => {}
+            #[cfg(not(unix))]
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {}
+            Err(e) => return Err(e),
+        }
+
+        let std: std::net::TcpStream = self.inner.into();
+        TcpStream::connect_mio(mio::net::TcpStream::from_std(std)).await
+    }
+
+    /// Converts the socket into a `TcpListener`.
+    ///
+    /// `backlog` defines the maximum number of pending connections are queued
+    /// by the operating system at any given time. Connection are removed from
+    /// the queue with [`TcpListener::accept`]. When the queue is full, the
+    /// operating-system will start rejecting connections.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_io`].
+    ///
+    /// [`enable_io`]: crate::runtime::Builder::enable_io
+    #[track_caller]
+    pub fn listen(self, backlog: u32) -> io::Result<TcpListener> {
+        let backlog = backlog.try_into().unwrap_or(i32::MAX);
+        self.inner.listen(backlog)?;
+
+        let std: std::net::TcpListener = self.inner.into();
+        TcpListener::new(mio::net::TcpListener::from_std(std))
+    }
+}
+
+impl fmt::Debug for TcpSocket {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.fmt(fmt)
+    }
+}
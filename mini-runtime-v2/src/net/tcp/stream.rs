@@ -0,0 +1,391 @@
+use crate::future::FuturesUnordered;
+use crate::future::poll_fn;
+use crate::io::{AsyncRead, AsyncWrite, PollEvented};
+use crate::net::tcp::split::split;
+use crate::net::tcp::split_owned::split_owned;
+use crate::net::tcp::{OwnedReadHalf, OwnedWriteHalf, ReadHalf, WriteHalf};
+use crate::net::{ToSocketAddrs, to_socket_addrs};
+use crate::runtime::scheduler;
+use crate::stream::Stream;
+use crate::time::Sleep;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::net::{Shutdown, SocketAddr};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+/// Delay after which the next address is tried while a connection attempt is
+/// still in progress, as recommended by RFC 8305.
+const CONNECTION_ATTEMPT_DELAY: Duration = Duration::from_millis(250);
+
+/// A TCP stream between a local and a remote socket.
+///
+/// A TCP stream can either be created by connecting to an endpoint, via the
+/// [`connect`] method, or by [accepting] a connection from a [listener].
+///
+/// Reading and writing to a `TcpStream` is usually done using the
+/// convenience methods found on the [`AsyncReadExt`] and [`AsyncWriteExt`]
+/// traits. Both traits are also implemented for `&TcpStream`, so the same
+/// socket can be read and written concurrently:
+///
+/// ```no_run
+/// # use mini_runtime_v2::io;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+/// # let socket = mini_runtime_v2::net::TcpStream::connect("127.0.0.1:8080").await?;
+/// let (mut rd, mut wr) = (&socket, &socket);
+/// io::copy(&mut rd, &mut wr).await?;
+/// # Ok::<_, io::Error>(())
+/// # })?;
+/// # Ok::<_, io::Error>(())
+/// ```
+///
+/// [`connect`]: TcpStream::connect
+/// [accepting]: crate::net::TcpListener::accept
+/// [listener]: crate::net::TcpListener
+/// [`AsyncReadExt`]: trait@crate::io::AsyncReadExt
+/// [`AsyncWriteExt`]: trait@crate::io::AsyncWriteExt
+pub struct TcpStream {
+    io: PollEvented<mio::net::TcpStream>,
+}
+
+impl TcpStream {
+    /// Opens a TCP connection to a remote host.
+    ///
+    /// `addr` is an address of the remote host. A host name is resolved on
+    /// the blocking pool, see [`ToSocketAddrs`]. If `addr` yields multiple
+    /// addresses, connect will be attempted with each of the addresses until
+    /// a connection is successful. If none of the addresses result in a
+    /// successful connection, the error returned from the last connection
+    /// attempt is returned.
+    ///
+    /// The attempts follow a simplified "Happy Eyeballs" (RFC 8305): the
+    /// addresses alternate between IPv6 and IPv4, starting with the family
+    /// of the first address, and the next attempt starts as soon as the
+    /// previous one fails or after 250ms without an answer, without
+    /// cancelling it. The first connection established wins. A host whose
+    /// IPv6 route is broken is thus reached over IPv4 after 250ms rather than
+    /// after the OS connect timeout. If the runtime was built without
+    /// [`enable_time`], the attempts are made one after the other.
+    ///
+    /// ```no_run
+    /// use mini_runtime_v2::net::TcpStream;
+    ///
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+    /// # rt.block_on(async {
+    /// let stream = TcpStream::connect("localhost:6142").await?;
+    /// # Ok::<_, std::io::Error>(())
+    /// # })?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_io`].
+    ///
+    /// [`enable_io`]: crate::runtime::Builder::enable_io
+    /// [`enable_time`]: crate::runtime::Builder::enable_time
+    pub async fn connect<A: ToSocketAddrs>(addr: A) -> io::Result<TcpStream> {
+        let addrs = interleave_families(to_socket_addrs(addr).await?);
+        let mut addrs = addrs.into_iter();
+
+        // Without the time driver, an attempt is only started once the
+        // previous one failed.
+        let handle = scheduler::Handle::current();
+        let mut delay = (addrs.len() > 1 && handle.driver().is_time_enabled())
+            .then(|| Sleep::new_after(handle, CONNECTION_ATTEMPT_DELAY));
+
+        let mut attempts = FuturesUnordered::new();
+        let mut last_err = None;
+
+        loop {
+            if let Some(addr) = addrs.next() {
+                attempts.push(TcpStream::connect_addr(addr));
+                if let Some(delay) = &mut delay {
+                    delay.reset_after(CONNECTION_ATTEMPT_DELAY);
+                }
+            }
+
+            if attempts.is_empty() {
+                break;
+            }
+
+            let more = addrs.len() > 0;
+            let attempt = poll_fn(|cx| {
+                if let Poll::Ready(Some(res)) = Pin::new(&mut attempts).poll_next(cx) {
+                    return Poll::Ready(Some(res));
+                }
+                match &mut delay {
+                    // Start the next attempt alongside the pending ones.
+                    Some(delay) if more => Pin::new(delay).poll(cx).map(|()| None),
+                    _ => Poll::Pending,
+                }
+            })
+            .await;
+
+            match attempt {
+                Some(Ok(stream)) => return Ok(stream),
+                Some(Err(e)) => last_err = Some(e),
+                None => {}
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "could not resolve to any address",
+            )
+        }))
+    }
+
+    /// Establishes a connection to the specified `addr`.
+    async fn connect_addr(addr: SocketAddr) -> io::Result<TcpStream> {
+        let sys = mio::net::TcpStream::connect(addr)?;
+        TcpStream::connect_mio(sys).await
+    }
+
+    pub(crate) async fn connect_mio(sys: mio::net::TcpStream) -> io::Result<TcpStream> {
+        let stream = TcpStream::new(sys)?;
+
+        // Once we've connected, wait for the stream to be writable as
+        // that's when the actual connection has been initiated. Once we're
+        // writable we check for `take_socket_error` to see if the connect
+        // actually hit an error or not.
+        //
+        // If all that succeeded then we ship everything on up.
+        poll_fn(|cx| stream.io.registration().poll_write_ready(cx)).await?;
+
+        if let Some(e) = stream.io.take_error()? {
+            return Err(e);
+        }
+
+        Ok(stream)
+    }
+
+    #[track_caller]
+    pub(crate) fn new(connected: mio::net::TcpStream) -> io::Result<TcpStream> {
+        let io = PollEvented::new(connected)?;
+        Ok(TcpStream { io })
+    }
+
+    /// Returns the local address that this stream is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.io.local_addr()
+    }
+
+    /// Returns the remote address that this stream is connected to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.io.peer_addr()
+    }
+
+    /// Sets the value of the `TCP_NODELAY` option on this socket.
+    ///
+    /// If set, this option disables the Nagle algorithm. This means that
+    /// segments are always sent as soon as possible, even if there is only a
+    /// small amount of data.
+    pub fn set_nodelay(&self, nodelay: bool) -> io::Result<()> {
+        self.io.set_nodelay(nodelay)
+    }
+
+    /// Gets the value of the `TCP_NODELAY` option on this socket.
+    pub fn nodelay(&self) -> io::Result<bool> {
+        self.io.nodelay()
+    }
+
+    /// Sets the linger duration of this socket by setting the `SO_LINGER`
+    /// option.
+    ///
+    /// This option controls the action taken when a stream has unsent
+    /// messages and the stream is closed. If `SO_LINGER` is set, the system
+    /// shall block the process until it can transmit the data or until the
+    /// time expires.
+    ///
+    /// If `SO_LINGER` is not specified, and the stream is closed, the system
+    /// handles the call in a way that allows the process to continue as
+    /// quickly as possible. A zero duration resets the connection on close
+    /// instead of the graceful shutdown.
+    pub fn set_linger(&self, dur: Option<Duration>) -> io::Result<()> {
+        socket2::SockRef::from(&*self.io).set_linger(dur)
+    }
+
+    /// Reads the linger duration for this socket by getting the `SO_LINGER`
+    /// option.
+    pub fn linger(&self) -> io::Result<Option<Duration>> {
+        socket2::SockRef::from(&*self.io).linger()
+    }
+
+    /// Sets the value for the `IP_TTL` option on this socket.
+    ///
+    /// This value sets the time-to-live field that is used in every packet
+    /// sent from this socket.
+    pub fn set_ttl(&self, ttl: u32) -> io::Result<()> {
+        self.io.set_ttl(ttl)
+    }
+
+    /// Gets the value of the `IP_TTL` option for this socket.
+    pub fn ttl(&self) -> io::Result<u32> {
+        self.io.ttl()
+    }
+
+    /// Splits a `TcpStream` into a read half and a write half, which can be
+    /// used to read and write the stream concurrently.
+    ///
+    /// This method is more efficient than [`into_split`], but the halves
+    /// cannot be moved into independently spawned tasks.
+    ///
+    /// [`into_split`]: TcpStream::into_split()
+    pub fn split(&mut self) -> (ReadHalf<'_>, WriteHalf<'_>) {
+        split(self)
+    }
+
+    /// Splits a `TcpStream` into a read half and a write half, which can be
+    /// used to read and write the stream concurrently.
+    ///
+    /// Unlike [`split`], the owned halves can be moved to separate tasks,
+    /// however this comes at the cost of a heap allocation.
+    ///
+    /// **Note:** Dropping the write half will shut down the write half of
+    /// the TCP stream. This is equivalent to calling [`shutdown()`] on the
+    /// `TcpStream`.
+    ///
+    /// ```no_run
+    /// # use mini_runtime_v2::io::{AsyncReadExt, AsyncWriteExt};
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+    /// # rt.block_on(async {
+    /// # let socket = mini_runtime_v2::net::TcpStream::connect("127.0.0.1:8080").await?;
+    /// # let mut buf = [0; 1024];
+    /// let (mut rd, mut wr) = socket.into_split();
+    ///
+    /// mini_runtime_v2::spawn(async move {
+    ///     wr.write_all(b"hello\r\n").await?;
+    ///     // ...
+    /// #   Ok::<_, std::io::Error>(())
+    /// });
+    ///
+    /// let n = rd.read(&mut buf).await?;
+    /// # let _ = n;
+    /// # Ok::<_, std::io::Error>(())
+    /// # })?;
+    /// # Ok::<_, std::io::Error>(())
+    /// ```
+    ///
+    /// [`split`]: TcpStream::split()
+    /// [`shutdown()`]: fn@crate::io::AsyncWriteExt::shutdown
+    pub fn into_split(self) -> (OwnedReadHalf, OwnedWriteHalf) {
+        split_owned(self)
+    }
+
+    pub(crate) fn poll_read_priv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.io.poll_read(cx, buf)
+    }
+
+    pub(crate) fn poll_write_priv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.io.poll_write(cx, buf)
+    }
+
+    /// Shuts down the write half of the connection.
+    pub(crate) fn shutdown_std(&self, how: Shutdown) -> io::Result<()> {
+        self.io.shutdown(how)
+    }
+}
+
+/// Reorders `addrs` to alternate between the address families, starting with
+/// the family of the first address.
+fn interleave_families(addrs: Vec<SocketAddr>) -> Vec<SocketAddr> {
+    let Some(first) = addrs.first() else {
+        return addrs;
+    };
+
+    let first_is_v6 = first.is_ipv6();
+    let (preferred, other): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == first_is_v6);
+
+    let mut interleaved = Vec::with_capacity(preferred.len() + other.len());
+    let (mut preferred, mut other) = (preferred.into_iter(), other.into_iter());
+    loop {
+        match (preferred.next(), other.next()) {
+            (None, None) => return interleaved,
+            (a, b) => interleaved.extend(a.into_iter().chain(b)),
+        }
+    }
+}
+
+impl AsyncRead for TcpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.poll_read_priv(cx, buf)
+    }
+}
+
+impl AsyncWrite for TcpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.poll_write_priv(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // tcp flush is a no-op
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.shutdown_std(Shutdown::Write)?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncRead for &TcpStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.poll_read_priv(cx, buf)
+    }
+}
+
+impl AsyncWrite for &TcpStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.poll_write_priv(cx, buf)
+    }
+
+    #[inline]
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        // tcp flush is a no-op
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.shutdown_std(Shutdown::Write)?;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl fmt::Debug for TcpStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.io.fmt(f)
+    }
+}
@@ -0,0 +1,217 @@
+use crate::io::PollEvented;
+use crate::runtime::io::Direction;
+use std::fmt;
+use std::io;
+use std::net::{self, SocketAddr, ToSocketAddrs};
+
+/// A UDP socket.
+///
+/// UDP is "connectionless", unlike TCP. Meaning, regardless of what address
+/// you've bound to, a `UdpSocket` is free to communicate with many different
+/// remotes. There are basically two main ways to use `UdpSocket`:
+///
+/// * one to many: [`bind`](UdpSocket::bind) and use [`send_to`](UdpSocket::send_to)
+///   and [`recv_from`](UdpSocket::recv_from) to communicate with many different
+///   addresses
+/// * one to one: [`connect`](UdpSocket::connect) and associate with a single
+///   address, using [`send`](UdpSocket::send) and [`recv`](UdpSocket::recv) to
+///   communicate only with that remote address
+///
+/// All methods take `&self`, so the socket can be shared between tasks, e.g.
+/// behind an `Arc`, to receive and send concurrently.
+///
+/// # Example: one to many (bind)
+///
+/// ```no_run
+/// use mini_runtime_v2::net::UdpSocket;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+///
+/// let sock = UdpSocket::bind("0.0.0.0:8080").await?;
+/// let mut buf = [0; 1024];
+/// loop {
+///     let (len, addr) = sock.recv_from(&mut buf).await?;
+///     println!("{len:?} bytes received from {addr:?}");
+///
+///     let len = sock.send_to(&buf[..len], addr).await?;
+///     println!("{len:?} bytes sent");
+/// }
+/// # #[allow(unreachable_code)]
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub struct UdpSocket {
+    io: PollEvented<mio::net::UdpSocket>,
+}
+
+impl UdpSocket {
+    /// This function will create a new UDP socket and attempt to bind it to
+    /// the `addr` provided.
+    ///
+    /// Binding with a port number of 0 will request that the OS assigns a port
+    /// to this socket. The port allocated can be queried via the `local_addr`
+    /// method.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_io`].
+    ///
+    /// [`enable_io`]: crate::runtime::Builder::enable_io
+    pub async fn bind<A: ToSocketAddrs>(addr: A) -> io::Result<UdpSocket> {
+        let mut last_err = None;
+
+        for addr in addr.to_socket_addrs()? {
+            match UdpSocket::bind_addr(addr) {
+                Ok(socket) => return Ok(socket),
+                Err(e) => last_err = Some(e),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "could not resolve to any address",
+            )
+        }))
+    }
+
+    fn bind_addr(addr: SocketAddr) -> io::Result<UdpSocket> {
+        let sys = mio::net::UdpSocket::bind(addr)?;
+        UdpSocket::new(sys)
+    }
+
+    #[track_caller]
+    fn new(socket: mio::net::UdpSocket) -> io::Result<UdpSocket> {
+        let io = PollEvented::new(socket)?;
+        Ok(UdpSocket { io })
+    }
+
+    /// Creates new `UdpSocket` from a previously bound `std::net::UdpSocket`.
+    ///
+    /// The caller is responsible for ensuring that the socket is in
+    /// non-blocking mode.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_io`].
+    ///
+    /// [`enable_io`]: crate::runtime::Builder::enable_io
+    #[track_caller]
+    pub fn from_std(socket: net::UdpSocket) -> io::Result<UdpSocket> {
+        let io = mio::net::UdpSocket::from_std(socket);
+        UdpSocket::new(io)
+    }
+
+    /// Returns the local address that this socket is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.io.local_addr()
+    }
+
+    /// Returns the socket address of the remote peer this socket was connected
+    /// to.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.io.peer_addr()
+    }
+
+    /// Connects the UDP socket setting the default destination for send() and
+    /// limiting packets that are read via `recv` from the address specified in
+    /// `addr`.
+    pub async fn connect<A: ToSocketAddrs>(&self, addr: A) -> io::Result<()> {
+        let mut last_err = None;
+
+        for addr in addr.to_socket_addrs()? {
+            match self.io.connect(addr) {
+                Ok(()) => return Ok(()),
+                Err(e) => last_err = Some(e),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "could not resolve to any address",
+            )
+        }))
+    }
+
+    /// Sends data on the socket to the remote address that the socket is
+    /// connected to.
+    ///
+    /// The [`connect`] method will connect this socket to a remote address.
+    /// This method will fail if the socket is not connected.
+    ///
+    /// On success, the number of bytes sent is returned, otherwise, the
+    /// encountered error is returned.
+    ///
+    /// [`connect`]: method@Self::connect
+    pub async fn send(&self, buf: &[u8]) -> io::Result<usize> {
+        self.io
+            .registration()
+            .async_io(Direction::Write, || self.io.send(buf))
+            .await
+    }
+
+    /// Receives a single datagram message on the socket from the remote
+    /// address to which it is connected. On success, returns the number of
+    /// bytes read.
+    ///
+    /// The function must be called with valid byte array `buf` of sufficient
+    /// size to hold the message bytes. If a message is too long to fit in the
+    /// supplied buffer, excess bytes may be discarded.
+    ///
+    /// The [`connect`] method will connect this socket to a remote address.
+    /// This method will fail if the socket is not connected.
+    ///
+    /// [`connect`]: method@Self::connect
+    pub async fn recv(&self, buf: &mut [u8]) -> io::Result<usize> {
+        self.io
+            .registration()
+            .async_io(Direction::Read, || self.io.recv(buf))
+            .await
+    }
+
+    /// Sends data on the socket to the given address. On success, returns the
+    /// number of bytes written.
+    ///
+    /// Address type can be any implementor of [`ToSocketAddrs`] trait. See its
+    /// documentation for concrete examples.
+    ///
+    /// It is possible for `addr` to yield multiple addresses, but `send_to`
+    /// will only send data to the first address yielded by `addr`.
+    pub async fn send_to<A: ToSocketAddrs>(&self, buf: &[u8], target: A) -> io::Result<usize> {
+        match target.to_socket_addrs()?.next() {
+            Some(target) => {
+                self.io
+                    .registration()
+                    .async_io(Direction::Write, || self.io.send_to(buf, target))
+                    .await
+            }
+            None => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "no addresses to send data to",
+            )),
+        }
+    }
+
+    /// Receives a single datagram message on the socket. On success, returns
+    /// the number of bytes read and the origin.
+    ///
+    /// The function must be called with valid byte array `buf` of sufficient
+    /// size to hold the message bytes. If a message is too long to fit in the
+    /// supplied buffer, excess bytes may be discarded.
+    pub async fn recv_from(&self, buf: &mut [u8]) -> io::Result<(usize, SocketAddr)> {
+        self.io
+            .registration()
+            .async_io(Direction::Read, || self.io.recv_from(buf))
+            .await
+    }
+}
+
+impl fmt::Debug for UdpSocket {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.io.fmt(f)
+    }
+}
@@ -0,0 +1,628 @@
+//! An implementation of asynchronous process management for the Mini runtime.
+//!
+//! This module provides a [`Command`] struct that imitates the interface of the
+//! [`std::process::Command`] type in the standard library, but provides
+//! asynchronous versions of functions that create processes. These functions
+//! (`spawn`, `status`, `output` and their variants) return "future aware"
+//! types that interoperate with the runtime. The asynchronous process support
+//! is provided through signal handling on Unix: the runtime listens for
+//! `SIGCHLD` and re-checks the children waiting to be reaped.
+//!
+//! The runtime must be built with [`enable_io`], both the pipes and the
+//! `SIGCHLD` notifications go through the I/O driver.
+//!
+//! # Examples
+//!
+//! Here we spawn the `echo` command and wait for it to complete:
+//!
+//! ```
+//! use mini_runtime_v2::process::Command;
+//! # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+//! # rt.block_on(async {
+//!
+//! // The usage is similar as with the standard library's `Command` type
+//! let mut child = Command::new("echo")
+//!     .arg("hello")
+//!     .arg("world")
+//!     .spawn()
+//!     .expect("failed to spawn");
+//!
+//! // Await until the command completes
+//! let status = child.wait().await?;
+//! println!("the command exited with: {}", status);
+//! # Ok::<_, std::io::Error>(())
+//! # })?;
+//! # Ok::<_, std::io::Error>(())
+//! ```
+//!
+//! Reading the output of a child line by line:
+//!
+//! ```no_run
+//! use mini_runtime_v2::io::{AsyncBufReadExt, BufReader};
+//! use mini_runtime_v2::process::Command;
+//! use std::process::Stdio;
+//! # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+//! # rt.block_on(async {
+//!
+//! let mut child = Command::new("cat").stdout(Stdio::piped()).spawn()?;
+//! let stdout = child.stdout.take().expect("child did not have a handle to stdout");
+//!
+//! let mut reader = BufReader::new(stdout);
+//! let mut line = String::new();
+//! while reader.read_line(&mut line).await? != 0 {
+//!     println!("Line: {}", line);
+//!     line.clear();
+//! }
+//! # Ok::<_, std::io::Error>(())
+//! # })?;
+//! # Ok::<_, std::io::Error>(())
+//! ```
+//!
+//! # Caveats
+//!
+//! Unlike the standard library, a [`Child`] that is dropped before it exited
+//! is not leaked as a zombie: it is queued and reaped the next time the
+//! runtime handles a child process. Use [`Command::kill_on_drop`] to kill it
+//! instead of letting it run in the background.
+//!
+//! [`enable_io`]: crate::runtime::Builder::enable_io
+
+mod unix;
+
+use crate::future::poll_fn;
+use crate::io::{AsyncRead, AsyncReadExt, AsyncWrite, PollEvented};
+use std::ffi::OsStr;
+use std::future::Future;
+use std::io;
+use std::path::Path;
+use std::pin::Pin;
+use std::process::{ExitStatus, Output, Stdio};
+use std::task::{Context, Poll};
+
+/// This structure mimics the API of [`std::process::Command`] found in the
+/// standard library, but replaces functions that create a process with an
+/// asynchronous variant. The main provided asynchronous functions are
+/// [spawn](Command::spawn), [status](Command::status), and
+/// [output](Command::output).
+///
+/// `Command` uses asynchronous versions of some `std` types (for example
+/// [`Child`]).
+#[derive(Debug)]
+pub struct Command {
+    std: std::process::Command,
+    kill_on_drop: bool,
+}
+
+impl Command {
+    /// Constructs a new `Command` for launching the program at
+    /// path `program`, with the following default configuration:
+    ///
+    /// * No arguments to the program
+    /// * Inherit the current process's environment
+    /// * Inherit the current process's working directory
+    /// * Inherit stdin/stdout/stderr for `spawn` or `status`, but create pipes
+    ///   for `output`
+    pub fn new<S: AsRef<OsStr>>(program: S) -> Command {
+        Self::from(std::process::Command::new(program))
+    }
+
+    /// Cheaply convert to a `&std::process::Command` for places where the
+    /// type from the standard library is expected.
+    pub fn as_std(&self) -> &std::process::Command {
+        &self.std
+    }
+
+    /// Adds an argument to pass to the program.
+    pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Command {
+        self.std.arg(arg);
+        self
+    }
+
+    /// Adds multiple arguments to pass to the program.
+    pub fn args<I, S>(&mut self, args: I) -> &mut Command
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.std.args(args);
+        self
+    }
+
+    /// Inserts or updates an environment variable mapping.
+    pub fn env<K, V>(&mut self, key: K, val: V) -> &mut Command
+    where
+        K: AsRef<OsStr>,
+        V: AsRef<OsStr>,
+    {
+        self.std.env(key, val);
+        self
+    }
+
+    /// Removes an environment variable mapping.
+    pub fn env_remove<K: AsRef<OsStr>>(&mut self, key: K) -> &mut Command {
+        self.std.env_remove(key);
+        self
+    }
+
+    /// Clears the entire environment map for the child process.
+    pub fn env_clear(&mut self) -> &mut Command {
+        self.std.env_clear();
+        self
+    }
+
+    /// Sets the working directory for the child process.
+    pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Command {
+        self.std.current_dir(dir);
+        self
+    }
+
+    /// Sets configuration for the child process's standard input (stdin)
+    /// handle.
+    ///
+    /// Defaults to [`inherit`] when used with `spawn` or `status`, and
+    /// defaults to [`piped`] when used with `output`.
+    ///
+    /// [`inherit`]: std::process::Stdio::inherit
+    /// [`piped`]: std::process::Stdio::piped
+    pub fn stdin<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.std.stdin(cfg);
+        self
+    }
+
+    /// Sets configuration for the child process's standard output (stdout)
+    /// handle.
+    ///
+    /// Defaults to [`inherit`] when used with `spawn` or `status`, and
+    /// defaults to [`piped`] when used with `output`.
+    ///
+    /// [`inherit`]: std::process::Stdio::inherit
+    /// [`piped`]: std::process::Stdio::piped
+    pub fn stdout<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.std.stdout(cfg);
+        self
+    }
+
+    /// Sets configuration for the child process's standard error (stderr)
+    /// handle.
+    ///
+    /// Defaults to [`inherit`] when used with `spawn` or `status`, and
+    /// defaults to [`piped`] when used with `output`.
+    ///
+    /// [`inherit`]: std::process::Stdio::inherit
+    /// [`piped`]: std::process::Stdio::piped
+    pub fn stderr<T: Into<Stdio>>(&mut self, cfg: T) -> &mut Command {
+        self.std.stderr(cfg);
+        self
+    }
+
+    /// Controls whether a `kill` operation should be invoked on a spawned
+    /// child process when its corresponding `Child` handle is dropped.
+    ///
+    /// By default, this value is assumed to be `false`, meaning the next
+    /// spawned process will not be killed on drop, similar to the behavior of
+    /// the standard library.
+    pub fn kill_on_drop(&mut self, kill_on_drop: bool) -> &mut Command {
+        self.kill_on_drop = kill_on_drop;
+        self
+    }
+
+    /// Executes the command as a child process, returning a handle to it.
+    ///
+    /// By default, stdin, stdout and stderr are inherited from the parent.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_io`].
+    ///
+    /// [`enable_io`]: crate::runtime::Builder::enable_io
+    #[track_caller]
+    pub fn spawn(&mut self) -> io::Result<Child> {
+        let spawned = unix::spawn_child(&mut self.std)?;
+
+        Ok(Child {
+            child: FusedChild::Child(ChildDropGuard {
+                inner: spawned.child,
+                kill_on_drop: self.kill_on_drop,
+            }),
+            stdin: spawned.stdin.map(|inner| ChildStdin { inner }),
+            stdout: spawned.stdout.map(|inner| ChildStdout { inner }),
+            stderr: spawned.stderr.map(|inner| ChildStderr { inner }),
+        })
+    }
+
+    /// Executes the command as a child process, waiting for it to finish and
+    /// collecting its exit status.
+    ///
+    /// By default, stdin, stdout and stderr are inherited from the parent.
+    /// If any input/output handles are set to a pipe then they will be
+    /// immediately closed after the child is spawned.
+    pub async fn status(&mut self) -> io::Result<ExitStatus> {
+        let mut child = self.spawn()?;
+
+        // Ignore any configured pipes, the child's status is all we care
+        // about.
+        child.stdin.take();
+        child.stdout.take();
+        child.stderr.take();
+
+        child.wait().await
+    }
+
+    /// Executes the command as a child process, waiting for it to finish and
+    /// collecting all of its output.
+    ///
+    /// By default, stdout and stderr are captured (and used to provide the
+    /// resulting output). Stdin is not inherited from the parent and any
+    /// attempt by the child process to read from the stdin stream will result
+    /// in the stream immediately closing.
+    pub async fn output(&mut self) -> io::Result<Output> {
+        self.std.stdout(Stdio::piped());
+        self.std.stderr(Stdio::piped());
+
+        let child = self.spawn()?;
+
+        child.wait_with_output().await
+    }
+}
+
+impl From<std::process::Command> for Command {
+    fn from(std: std::process::Command) -> Command {
+        Command {
+            std,
+            kill_on_drop: false,
+        }
+    }
+}
+
+/// A drop guard which can ensure the child process is killed on drop if
+/// specified.
+#[derive(Debug)]
+struct ChildDropGuard {
+    inner: unix::Child,
+    kill_on_drop: bool,
+}
+
+impl ChildDropGuard {
+    fn start_kill(&mut self) -> io::Result<()> {
+        let ret = self.inner.start_kill();
+
+        if ret.is_ok() {
+            self.kill_on_drop = false;
+        }
+
+        ret
+    }
+
+    fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        let ret = self.inner.try_wait();
+
+        if let Ok(Some(_)) = ret {
+            // Avoid the overhead of trying to kill a reaped process
+            self.kill_on_drop = false;
+        }
+
+        ret
+    }
+
+    fn poll_wait(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<ExitStatus>> {
+        let ret = self.inner.poll_wait(cx);
+
+        if let Poll::Ready(Ok(_)) = ret {
+            // Avoid the overhead of trying to kill a reaped process
+            self.kill_on_drop = false;
+        }
+
+        ret
+    }
+}
+
+impl Drop for ChildDropGuard {
+    fn drop(&mut self) {
+        if self.kill_on_drop {
+            drop(self.inner.start_kill());
+        }
+    }
+}
+
+/// Keeps track of the exit status of a child process without worrying about
+/// polling the underlying futures even after they have completed.
+#[derive(Debug)]
+enum FusedChild {
+    Child(ChildDropGuard),
+    Done(ExitStatus),
+}
+
+/// Representation of a child process spawned onto an event loop.
+///
+/// # Caveats
+///
+/// Similar to the behavior to the standard library, and unlike the futures
+/// paradigm of dropping-implies-cancellation, a spawned process will, by
+/// default, continue to execute even after the `Child` handle has been
+/// dropped.
+///
+/// The [`Command::kill_on_drop`] method can be used to modify this behavior
+/// and kill the child process if the `Child` wrapper is dropped before it
+/// has exited.
+#[derive(Debug)]
+pub struct Child {
+    child: FusedChild,
+
+    /// The handle for writing to the child's standard input (stdin), if it
+    /// has been captured. To avoid partially moving the `child` and thus
+    /// blocking yourself from calling functions on `child` while using
+    /// `stdin`, you might find it helpful to do:
+    ///
+    /// ```
+    /// # async fn dox(mut child: mini_runtime_v2::process::Child) {
+    /// let stdin = child.stdin.take().unwrap();
+    /// # }
+    /// ```
+    pub stdin: Option<ChildStdin>,
+
+    /// The handle for reading from the child's standard output (stdout), if it
+    /// has been captured. You might find it helpful to do
+    ///
+    /// ```
+    /// # async fn dox(mut child: mini_runtime_v2::process::Child) {
+    /// let stdout = child.stdout.take().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// to avoid partially moving the `child` and thus blocking yourself from
+    /// calling functions on `child` while using `stdout`.
+    pub stdout: Option<ChildStdout>,
+
+    /// The handle for reading from the child's standard error (stderr), if it
+    /// has been captured. You might find it helpful to do
+    ///
+    /// ```
+    /// # async fn dox(mut child: mini_runtime_v2::process::Child) {
+    /// let stderr = child.stderr.take().unwrap();
+    /// # }
+    /// ```
+    ///
+    /// to avoid partially moving the `child` and thus blocking yourself from
+    /// calling functions on `child` while using `stderr`.
+    pub stderr: Option<ChildStderr>,
+}
+
+impl Child {
+    /// Returns the OS-assigned process identifier associated with this child
+    /// while it is still running.
+    ///
+    /// Once the child has been polled to completion this will return `None`.
+    /// This is done to avoid confusion on platforms like Unix where the OS
+    /// identifier could be reused once the process has completed.
+    pub fn id(&self) -> Option<u32> {
+        match &self.child {
+            FusedChild::Child(child) => Some(child.inner.id()),
+            FusedChild::Done(_) => None,
+        }
+    }
+
+    /// Attempts to force the child to exit, but does not wait for the request
+    /// to take effect.
+    ///
+    /// On Unix platforms, this is the equivalent to sending a `SIGKILL`. Note
+    /// that on Unix platforms it is possible for a zombie process to remain
+    /// after a kill is sent; to avoid this, the caller should ensure that
+    /// either `child.wait().await` or `child.try_wait()` is invoked
+    /// successfully.
+    pub fn start_kill(&mut self) -> io::Result<()> {
+        match &mut self.child {
+            FusedChild::Child(child) => child.start_kill(),
+            FusedChild::Done(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid argument: can't kill an exited process",
+            )),
+        }
+    }
+
+    /// Forces the child to exit.
+    ///
+    /// This is equivalent to sending a `SIGKILL` on unix platforms followed
+    /// by [`wait`](Child::wait).
+    pub async fn kill(&mut self) -> io::Result<()> {
+        self.start_kill()?;
+        self.wait().await?;
+        Ok(())
+    }
+
+    /// Waits for the child to exit completely, returning the status that it
+    /// exited with. This function will continue to have the same return value
+    /// after it has been called at least once.
+    ///
+    /// The stdin handle to the child process, if any, will be closed
+    /// before waiting. This helps avoid deadlock: it ensures that the
+    /// child does not block waiting for input from the parent, while
+    /// the parent waits for the child to exit.
+    ///
+    /// # Cancel safety
+    ///
+    /// This function is cancel safe.
+    pub async fn wait(&mut self) -> io::Result<ExitStatus> {
+        // Ensure stdin is closed so the child isn't stuck waiting on
+        // input while the parent is waiting for it to exit.
+        drop(self.stdin.take());
+
+        match &mut self.child {
+            FusedChild::Done(exit) => Ok(*exit),
+            FusedChild::Child(child) => {
+                let ret = poll_fn(|cx| child.poll_wait(cx)).await;
+
+                if let Ok(exit) = ret {
+                    self.child = FusedChild::Done(exit);
+                }
+
+                ret
+            }
+        }
+    }
+
+    /// Attempts to collect the exit status of the child if it has already
+    /// exited.
+    ///
+    /// This function will not block the calling thread and will only
+    /// check to see if the child process has exited or not. If the child has
+    /// exited then on Unix the process ID is reaped. This function is
+    /// guaranteed to repeatedly return a successful exit status so long as the
+    /// child has already exited.
+    ///
+    /// If the child has exited, then `Ok(Some(status))` is returned. If the
+    /// exit status is not available at this time then `Ok(None)` is returned.
+    /// If an error occurs, then that error is returned.
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        match &mut self.child {
+            FusedChild::Done(exit) => Ok(Some(*exit)),
+            FusedChild::Child(guard) => {
+                let ret = guard.try_wait();
+
+                if let Ok(Some(exit)) = ret {
+                    self.child = FusedChild::Done(exit);
+                }
+
+                ret
+            }
+        }
+    }
+
+    /// Returns a future that will resolve to an `Output`, containing the exit
+    /// status, stdout, and stderr of the child process.
+    ///
+    /// The returned future will simultaneously waits for the child to exit and
+    /// collect all remaining output on the stdout/stderr handles, returning an
+    /// `Output` instance.
+    ///
+    /// The stdin handle to the child process, if any, will be closed before
+    /// waiting. This helps avoid deadlock: it ensures that the child does not
+    /// block waiting for input from the parent, while the parent waits for
+    /// the child to exit.
+    ///
+    /// By default, stdin, stdout and stderr are inherited from the parent. In
+    /// order to capture the output into this `Output` it is necessary to
+    /// create new pipes between parent and child. Use `stdout(Stdio::piped())`
+    /// or `stderr(Stdio::piped())`, respectively, when creating a `Command`.
+    pub async fn wait_with_output(mut self) -> io::Result<Output> {
+        async fn read_to_end<A: AsyncRead + Unpin>(io: &mut Option<A>) -> io::Result<Vec<u8>> {
+            let mut vec = Vec::new();
+            if let Some(io) = io.as_mut() {
+                io.read_to_end(&mut vec).await?;
+            }
+            Ok(vec)
+        }
+
+        let mut stdout_pipe = self.stdout.take();
+        let mut stderr_pipe = self.stderr.take();
+
+        // Both pipes must be drained at the same time: a child blocked on a
+        // full stderr pipe would never close its stdout.
+        let (stdout, stderr) = {
+            let stdout_fut = read_to_end(&mut stdout_pipe);
+            let stderr_fut = read_to_end(&mut stderr_pipe);
+            pin!(stdout_fut, stderr_fut);
+            let mut stdout = None;
+            let mut stderr = None;
+
+            poll_fn(|cx| {
+                if stdout.is_none()
+                    && let Poll::Ready(res) = stdout_fut.as_mut().poll(cx)
+                {
+                    stdout = Some(res?);
+                }
+                if stderr.is_none()
+                    && let Poll::Ready(res) = stderr_fut.as_mut().poll(cx)
+                {
+                    stderr = Some(res?);
+                }
+                if stdout.is_some() && stderr.is_some() {
+                    Poll::Ready(Ok::<_, io::Error>(()))
+                } else {
+                    Poll::Pending
+                }
+            })
+            .await?;
+
+            (stdout.unwrap_or_default(), stderr.unwrap_or_default())
+        };
+
+        let status = self.wait().await?;
+
+        // Drop happens after `wait` so that the pipes stay open until the
+        // child has exited.
+        drop(stdout_pipe);
+        drop(stderr_pipe);
+
+        Ok(Output {
+            status,
+            stdout,
+            stderr,
+        })
+    }
+}
+
+/// The standard input stream for spawned children.
+///
+/// This type implements the `AsyncWrite` trait to pass data to the stdin
+/// handle of a child process asynchronously.
+#[derive(Debug)]
+pub struct ChildStdin {
+    inner: PollEvented<mio::unix::pipe::Sender>,
+}
+
+/// The standard output stream for spawned children.
+///
+/// This type implements the `AsyncRead` trait to read data from the stdout
+/// handle of a child process asynchronously.
+#[derive(Debug)]
+pub struct ChildStdout {
+    inner: PollEvented<mio::unix::pipe::Receiver>,
+}
+
+/// The standard error stream for spawned children.
+///
+/// This type implements the `AsyncRead` trait to read data from the stderr
+/// handle of a child process asynchronously.
+#[derive(Debug)]
+pub struct ChildStderr {
+    inner: PollEvented<mio::unix::pipe::Receiver>,
+}
+
+impl AsyncWrite for ChildStdin {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.inner.poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncRead for ChildStdout {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.inner.poll_read(cx, buf)
+    }
+}
+
+impl AsyncRead for ChildStderr {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        self.inner.poll_read(cx, buf)
+    }
+}
@@ -0,0 +1,173 @@
+//! Unix handling of child processes.
+//!
+//! Right now the only "fancy" thing about this is how we implement the
+//! `Future` implementation on `Child` to get the exit status. Unix offers
+//! no way to register a child with epoll, and the only real way to get a
+//! notification when a process exits is the SIGCHLD signal.
+//!
+//! Signal handling in general is *super* hairy and complicated, and it's even
+//! more complicated here with the fact that signals are coalesced, so we may
+//! not get a SIGCHLD-per-child.
+//!
+//! Our best approximation here is to check *all spawned processes* for all
+//! SIGCHLD signals received. To do that we create a `Signal`, implemented in
+//! the `signal` module, for each child, and every time any SIGCHLD is
+//! received the children re-check their status with `try_wait`.
+
+use crate::io::PollEvented;
+use crate::signal::unix::{Signal, SignalKind, signal};
+use mio::unix::pipe;
+use std::io;
+use std::process::{self, ExitStatus};
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+/// Children dropped before they exited, reaped the next time a child is
+/// spawned or waited on.
+static ORPHANS: Mutex<Vec<process::Child>> = Mutex::new(Vec::new());
+
+pub(crate) struct Spawned {
+    pub(crate) child: Child,
+    pub(crate) stdin: Option<PollEvented<pipe::Sender>>,
+    pub(crate) stdout: Option<PollEvented<pipe::Receiver>>,
+    pub(crate) stderr: Option<PollEvented<pipe::Receiver>>,
+}
+
+#[track_caller]
+pub(crate) fn spawn_child(cmd: &mut process::Command) -> io::Result<Spawned> {
+    // Listen for SIGCHLD before spawning, so a child exiting right away
+    // can't be missed.
+    let signal = signal(SignalKind::child())?;
+
+    reap_orphans();
+
+    let mut child = cmd.spawn()?;
+    let stdin = child.stdin.take().map(pipe::Sender::from);
+    let stdout = child.stdout.take().map(pipe::Receiver::from);
+    let stderr = child.stderr.take().map(pipe::Receiver::from);
+
+    let stdin = stdin.map(stdio).transpose()?;
+    let stdout = stdout.map(stdio).transpose()?;
+    let stderr = stderr.map(stdio).transpose()?;
+
+    Ok(Spawned {
+        child: Child {
+            inner: Some(child),
+            signal,
+        },
+        stdin,
+        stdout,
+        stderr,
+    })
+}
+
+/// Puts a pipe end in non-blocking mode and registers it with the I/O driver.
+#[track_caller]
+fn stdio<E>(io: E) -> io::Result<PollEvented<E>>
+where
+    E: mio::event::Source + NonBlocking,
+{
+    io.set_nonblocking()?;
+    PollEvented::new(io)
+}
+
+trait NonBlocking {
+    fn set_nonblocking(&self) -> io::Result<()>;
+}
+
+impl NonBlocking for pipe::Sender {
+    fn set_nonblocking(&self) -> io::Result<()> {
+        pipe::Sender::set_nonblocking(self, true)
+    }
+}
+
+impl NonBlocking for pipe::Receiver {
+    fn set_nonblocking(&self) -> io::Result<()> {
+        pipe::Receiver::set_nonblocking(self, true)
+    }
+}
+
+/// A spawned child process, reaped when SIGCHLD reports a status change.
+#[derive(Debug)]
+pub(crate) struct Child {
+    /// Always `Some`, taken on drop to queue a still running child as an
+    /// orphan.
+    inner: Option<process::Child>,
+    signal: Signal,
+}
+
+impl Child {
+    fn inner(&self) -> &process::Child {
+        self.inner.as_ref().expect("inner has gone away")
+    }
+
+    fn inner_mut(&mut self) -> &mut process::Child {
+        self.inner.as_mut().expect("inner has gone away")
+    }
+
+    pub(crate) fn id(&self) -> u32 {
+        self.inner().id()
+    }
+
+    pub(crate) fn start_kill(&mut self) -> io::Result<()> {
+        self.inner_mut().kill()
+    }
+
+    pub(crate) fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.inner_mut().try_wait()
+    }
+
+    pub(crate) fn poll_wait(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<ExitStatus>> {
+        reap_orphans();
+
+        loop {
+            // Register with the signal stream before checking the child
+            // status: a SIGCHLD delivered in between then wakes the task.
+            let signal = self.signal.poll_recv(cx);
+
+            if let Some(status) = self.try_wait()? {
+                return Poll::Ready(Ok(status));
+            }
+
+            let registered_interest = match signal {
+                Poll::Pending => true,
+                Poll::Ready(Some(())) => false,
+                // Nothing would wake the task up anymore.
+                Poll::Ready(None) => {
+                    return Poll::Ready(Err(io::Error::other(
+                        "the runtime delivering SIGCHLD shut down",
+                    )));
+                }
+            };
+
+            // If our child hasn't exited yet, and we received a signal for
+            // some other child, check again: the waker is only registered
+            // once `poll_recv` returns `Pending`.
+            if !registered_interest {
+                continue;
+            }
+
+            return Poll::Pending;
+        }
+    }
+}
+
+impl Drop for Child {
+    fn drop(&mut self) {
+        if let Some(mut child) = self.inner.take()
+            && let Ok(None) = child.try_wait()
+        {
+            ORPHANS.lock().unwrap().push(child);
+        }
+    }
+}
+
+/// Reaps the orphaned children that exited so far.
+fn reap_orphans() {
+    // Another thread is already reaping, skip rather than block.
+    let Ok(mut orphans) = ORPHANS.try_lock() else {
+        return;
+    };
+
+    orphans.retain_mut(|child| matches!(child.try_wait(), Ok(None)));
+}
@@ -0,0 +1,13 @@
+//! Abstracts out the APIs necessary to `Runtime` for integrating the blocking
+//! pool.
+//!
+//! Blocking functions can't run on the thread driving the scheduler without
+//! stalling every other task, so they are handed to a pool of threads. The
+//! closure is wrapped in a future that completes on its first poll, which lets
+//! the pool reuse the task system: the caller gets a regular `JoinHandle`.
+
+mod pool;
+pub(crate) use pool::{BlockingPool, Spawner, spawn_blocking};
+
+mod task;
+use task::BlockingTask;
@@ -0,0 +1,391 @@
+//! Thread pool for blocking operations
+
+use crate::runtime::blocking::BlockingTask;
+use crate::runtime::task::{self, JoinHandle, Notified};
+use crate::runtime::{context, scheduler};
+use crate::util::loom::sync::{Arc, Condvar, Mutex};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub(crate) struct BlockingPool {
+    spawner: Spawner,
+}
+
+#[derive(Clone)]
+pub(crate) struct Spawner {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// State shared between worker threads.
+    shared: Mutex<Shared>,
+
+    /// Pool threads wait on this.
+    condvar: Condvar,
+
+    /// Notified when a thread exits after shutdown began, `shutdown` waits
+    /// on this.
+    exited: Condvar,
+
+    /// Maximum number of threads.
+    thread_cap: usize,
+
+    /// How long an idle thread waits for a task before exiting.
+    keep_alive: Duration,
+}
+
+struct Shared {
+    /// Tasks waiting for a thread.
+    queue: VecDeque<Notified>,
+
+    /// Number of spawned threads.
+    num_th: usize,
+
+    /// Number of threads waiting for a task.
+    num_idle: u32,
+
+    /// Number of pending notifications, used to tell spurious wakeups from
+    /// real ones.
+    num_notify: u32,
+
+    /// Set when the runtime shuts down.
+    shutdown: bool,
+
+    /// Prior to shutdown, we clean up `JoinHandles` by having each timed-out
+    /// thread join on the previous timed-out thread. This is not strictly
+    /// necessary but helps avoid Valgrind false positives.
+    last_exiting_thread: Option<thread::JoinHandle<()>>,
+
+    /// This holds the `JoinHandles` for all running threads; on shutdown, the
+    /// thread calling shutdown handles joining on these.
+    worker_threads: HashMap<usize, thread::JoinHandle<()>>,
+
+    /// This is a counter used to iterate `worker_threads` in a consistent
+    /// order (for loom's benefit).
+    worker_thread_index: usize,
+}
+
+/// Runs the provided function on an executor dedicated to blocking
+/// operations.
+#[track_caller]
+pub(crate) fn spawn_blocking<F, R>(func: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    let rt = scheduler::Handle::current();
+    rt.blocking_spawner().spawn_blocking(&rt, func)
+}
+
+// ===== impl BlockingPool =====
+
+impl BlockingPool {
+    pub(crate) fn new(thread_cap: usize, keep_alive: Duration) -> BlockingPool {
+        BlockingPool {
+            spawner: Spawner {
+                inner: Arc::new(Inner {
+                    shared: Mutex::new(Shared {
+                        queue: VecDeque::new(),
+                        num_th: 0,
+                        num_idle: 0,
+                        num_notify: 0,
+                        shutdown: false,
+                        last_exiting_thread: None,
+                        worker_threads: HashMap::new(),
+                        worker_thread_index: 0,
+                    }),
+                    condvar: Condvar::new(),
+                    exited: Condvar::new(),
+                    thread_cap,
+                    keep_alive,
+                }),
+            },
+        }
+    }
+
+    pub(crate) fn spawner(&self) -> &Spawner {
+        &self.spawner
+    }
+
+    /// Cancels the queued tasks and waits for the running ones to complete.
+    ///
+    /// With a `timeout`, the threads still running a task at the deadline
+    /// are detached: they exit on their own once their task returns.
+    pub(crate) fn shutdown(&mut self, timeout: Option<Duration>) {
+        let mut shared = self.spawner.inner.shared.lock().unwrap();
+
+        // The function can be called multiple times. First, by explicitly
+        // calling `shutdown` then by the drop handler. Only the first call
+        // does any work.
+        if shared.shutdown {
+            return;
+        }
+
+        shared.shutdown = true;
+        let queued: Vec<Notified> = shared.queue.drain(..).collect();
+        drop(shared);
+
+        self.spawner.inner.condvar.notify_all();
+
+        for task in queued {
+            task.shutdown();
+        }
+
+        // Blocking functions can't be interrupted: this waits for the ones
+        // already running to return.
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let mut shared = self.spawner.inner.shared.lock().unwrap();
+        while shared.num_th > 0 {
+            shared = match deadline {
+                None => self.spawner.inner.exited.wait(shared).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        break;
+                    }
+                    let (lock, _) = self
+                        .spawner
+                        .inner
+                        .exited
+                        .wait_timeout(shared, deadline - now)
+                        .unwrap();
+                    lock
+                }
+            };
+        }
+
+        let all_exited = shared.num_th == 0;
+        let last_exited_thread = shared.last_exiting_thread.take();
+        let workers = std::mem::take(&mut shared.worker_threads);
+        drop(shared);
+
+        // Dropping the `JoinHandle`s of the threads still running detaches
+        // them. The others are about to exit, joining them is quick.
+        if all_exited {
+            if let Some(handle) = last_exited_thread {
+                let _ = handle.join();
+            }
+
+            for (_id, handle) in workers {
+                let _ = handle.join();
+            }
+        }
+    }
+}
+
+impl Drop for BlockingPool {
+    fn drop(&mut self) {
+        self.shutdown(None);
+    }
+}
+
+impl fmt::Debug for BlockingPool {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("BlockingPool").finish()
+    }
+}
+
+// ===== impl Spawner =====
+
+impl Spawner {
+    #[track_caller]
+    pub(crate) fn spawn_blocking<F, R>(&self, rt: &scheduler::Handle, func: F) -> JoinHandle<R>
+    where
+        F: FnOnce() -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let meta = task::SpawnMeta::unnamed();
+        let (task, handle) = task::new_task(BlockingTask::new(func), rt.clone(), meta);
+
+        match self.spawn_task(task, rt) {
+            // The runtime is shutting down: the task was cancelled, the
+            // `JoinHandle` reports it.
+            Ok(()) | Err(SpawnError::ShuttingDown) => handle,
+            Err(SpawnError::NoThreads(e)) => panic!("OS can't spawn worker thread: {e}"),
+        }
+    }
+
+    /// Returns the number of tasks waiting for a thread.
+    pub(crate) fn queue_depth(&self) -> usize {
+        self.inner.shared.lock().unwrap().queue.len()
+    }
+
+    fn spawn_task(&self, task: Notified, rt: &scheduler::Handle) -> Result<(), SpawnError> {
+        let mut shared = self.inner.shared.lock().unwrap();
+
+        if shared.shutdown {
+            // Shutdown the task: it's fine to shutdown this task (even if
+            // mandatory) because it was scheduled after the shutdown of the
+            // runtime began.
+            drop(shared);
+            task.shutdown();
+
+            // no need to even push this task; it would never get picked up
+            return Err(SpawnError::ShuttingDown);
+        }
+
+        shared.queue.push_back(task);
+
+        if shared.num_idle == 0 {
+            // No threads are able to process the task.
+
+            if shared.num_th == self.inner.thread_cap {
+                // At max number of threads
+            } else {
+                let id = shared.worker_thread_index;
+
+                match self.spawn_thread(rt, id) {
+                    Ok(handle) => {
+                        shared.num_th += 1;
+                        shared.worker_thread_index += 1;
+                        shared.worker_threads.insert(id, handle);
+                    }
+                    Err(ref e) if is_temporary_os_thread_error(e) && shared.num_th > 0 => {
+                        // OK, we failed to spawn a new thread, but since
+                        // there are other workers, the queued task will be
+                        // picked up eventually.
+                    }
+                    Err(e) => {
+                        // The task can't be run, don't leave it in the queue.
+                        let task = shared.queue.pop_back();
+                        drop(shared);
+                        if let Some(task) = task {
+                            task.shutdown();
+                        }
+                        return Err(SpawnError::NoThreads(e));
+                    }
+                }
+            }
+        } else {
+            // Notify an idle worker thread. The notification counter
+            // is used to count the needed amount of notifications
+            // exactly. Thread libraries may generate spurious
+            // wakeups, this counter is used to keep us in a
+            // consistent state.
+            shared.num_idle -= 1;
+            shared.num_notify += 1;
+            self.inner.condvar.notify_one();
+        }
+
+        Ok(())
+    }
+
+    fn spawn_thread(
+        &self,
+        rt: &scheduler::Handle,
+        id: usize,
+    ) -> io::Result<thread::JoinHandle<()>> {
+        let rt = rt.clone();
+        let inner = self.inner.clone();
+
+        thread::Builder::new()
+            .name("mini-runtime-blocking".to_string())
+            .spawn(move || {
+                // Blocking functions may use the runtime, e.g. to spawn tasks
+                // or to block on futures via a handle.
+                let _enter = context::try_set_current(&rt);
+
+                inner.run(id);
+            })
+    }
+}
+
+impl fmt::Debug for Spawner {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("blocking::Spawner").finish()
+    }
+}
+
+/// Why a blocking task could not be handed to the pool.
+enum SpawnError {
+    /// Pool is shutting down and the task was not scheduled
+    ShuttingDown,
+    /// There are no worker threads available to take the task
+    /// and the OS failed to spawn a new one
+    NoThreads(io::Error),
+}
+
+// Tells whether the error when spawning a thread is temporary.
+#[inline]
+fn is_temporary_os_thread_error(error: &io::Error) -> bool {
+    matches!(error.kind(), io::ErrorKind::WouldBlock)
+}
+
+impl Inner {
+    fn run(&self, worker_thread_id: usize) {
+        let mut shared = self.shared.lock().unwrap();
+        let mut join_on_thread = None;
+
+        'main: loop {
+            // BUSY
+            while let Some(task) = shared.queue.pop_front() {
+                drop(shared);
+                task.run();
+
+                shared = self.shared.lock().unwrap();
+            }
+
+            // IDLE
+            shared.num_idle += 1;
+
+            while !shared.shutdown {
+                let (lock, timeout) = self.condvar.wait_timeout(shared, self.keep_alive).unwrap();
+
+                shared = lock;
+
+                if shared.num_notify != 0 {
+                    // We have received a legitimate wakeup,
+                    // acknowledge it by decrementing the counter
+                    // and transition to the BUSY state.
+                    shared.num_notify -= 1;
+                    break;
+                }
+
+                // Even if the condvar "timed out", if the pool is entering the
+                // shutdown phase, we want to perform the cleanup logic.
+                if !shared.shutdown && timeout.timed_out() {
+                    // We'll join the prior timed-out thread's JoinHandle after
+                    // dropping the lock. This isn't done when shutting down,
+                    // because the thread calling shutdown will handle joining
+                    // everything.
+                    let my_handle = shared.worker_threads.remove(&worker_thread_id);
+                    join_on_thread = std::mem::replace(&mut shared.last_exiting_thread, my_handle);
+
+                    break 'main;
+                }
+
+                // Spurious wakeup detected, go back to sleep.
+            }
+
+            if shared.shutdown {
+                // The queue was drained by `BlockingPool::shutdown`, the
+                // tasks pushed since are cancelled by `spawn_task`.
+                break;
+            }
+        }
+
+        // Thread exit
+        shared.num_th -= 1;
+
+        // num_idle should now be tracked exactly, panic
+        // with a descriptive message if it is not the
+        // case.
+        shared.num_idle = shared
+            .num_idle
+            .checked_sub(1)
+            .expect("num_idle underflowed on thread exit");
+
+        if shared.shutdown {
+            self.exited.notify_all();
+        }
+
+        drop(shared);
+
+        if let Some(handle) = join_on_thread {
+            let _ = handle.join();
+        }
+    }
+}
@@ -1,9 +1,16 @@
-use crate::runtime::Runtime;
+use crate::runtime::blocking::BlockingPool;
+use crate::runtime::driver::{self, Driver};
 use crate::runtime::handle::Handle;
 use crate::runtime::scheduler::CurrentThread;
+use crate::runtime::scheduler::current_thread::EVENT_INTERVAL;
+use crate::runtime::{Config, Runtime, TaskCallback, TaskHooks, TaskMeta, ThreadId, WorkerMetrics};
 use crate::util::rand::{RngSeed, RngSeedGenerator};
 use std::io;
-use std::thread::ThreadId;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default time an idle blocking thread waits for work before exiting.
+const BLOCKING_KEEP_ALIVE: Duration = Duration::from_secs(10);
 
 #[derive(Clone, Copy)]
 pub(crate) enum Kind {
@@ -15,6 +22,51 @@ pub struct Builder {
     /// Runtime type
     kind: Kind,
 
+    /// Whether or not to enable the I/O driver
+    enable_io: bool,
+
+    /// Whether or not to enable the time driver
+    enable_time: bool,
+
+    /// Whether or not the clock should start paused.
+    start_paused: bool,
+
+    /// Granularity the timer deadlines are rounded up to
+    timer_resolution: Duration,
+
+    /// Number of events processed by the I/O driver per tick
+    nevents: usize,
+
+    /// Cap on the number of threads of the blocking pool
+    max_blocking_threads: usize,
+
+    /// How long an idle blocking thread is kept alive
+    keep_alive: Option<Duration>,
+
+    /// To run before each task is spawned.
+    before_spawn: Option<TaskCallback>,
+
+    /// To run after each task is terminated.
+    after_termination: Option<TaskCallback>,
+
+    /// Bound on the tasks spawned from other threads waiting to be polled
+    max_pending_spawns: Option<usize>,
+
+    /// Number of times an idle worker checks for new work before parking
+    max_idle_spins: u32,
+
+    /// Number of task polls between two checks of the drivers
+    event_interval: u32,
+
+    /// Whether or not to measure the poll time of the tasks
+    metrics_poll_time_histogram: bool,
+
+    /// Whether or not to warn about tasks that lose their waker
+    detect_lost_wakeups: bool,
+
+    /// Whether or not to report each round of the scheduling loop
+    scheduler_tracing: bool,
+
     /// Specify a random number generator seed to provide deterministic results
     pub(super) seed_generator: RngSeedGenerator,
 }
@@ -31,10 +83,427 @@ impl Builder {
     pub(crate) fn new(kind: Kind) -> Builder {
         Builder {
             kind,
+
+            // I/O defaults to "off"
+            enable_io: false,
+
+            // Time defaults to "off"
+            enable_time: false,
+
+            // The clock starts unpaused
+            start_paused: false,
+
+            // Timers fire at their exact deadline
+            timer_resolution: Duration::ZERO,
+
+            nevents: 1024,
+
+            max_blocking_threads: 512,
+            keep_alive: None,
+
+            before_spawn: None,
+            after_termination: None,
+
+            max_pending_spawns: None,
+
+            // Park right away
+            max_idle_spins: 0,
+
+            event_interval: EVENT_INTERVAL,
+
+            metrics_poll_time_histogram: false,
+
+            detect_lost_wakeups: false,
+
+            scheduler_tracing: false,
+
             seed_generator: RngSeedGenerator::new(RngSeed::new()),
         }
     }
 
+    /// Enables the I/O driver.
+    ///
+    /// Doing this enables using net types on the runtime. The driver is
+    /// disabled by default: without it, creating a socket panics with "IO is
+    /// disabled".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mini_runtime_v2::runtime;
+    ///
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .enable_io()
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn enable_io(&mut self) -> &mut Self {
+        self.enable_io = true;
+        self
+    }
+
+    /// Enables the time driver.
+    ///
+    /// Doing this enables using `time` types on the runtime. The driver is
+    /// disabled by default: without it, creating a `Sleep` or a `Timeout`
+    /// panics with "timers are disabled".
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mini_runtime_v2::runtime;
+    ///
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .enable_time()
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn enable_time(&mut self) -> &mut Self {
+        self.enable_time = true;
+        self
+    }
+
+    /// Enables both I/O and time drivers.
+    ///
+    /// Doing this is a shorthand for calling `enable_io` and `enable_time`
+    /// individually.
+    pub fn enable_all(&mut self) -> &mut Self {
+        self.enable_io();
+        self.enable_time();
+        self
+    }
+
+    /// Controls if the runtime's clock starts paused or advancing.
+    ///
+    /// Pausing time requires the time driver to be enabled, the option has
+    /// no effect otherwise. While the clock is paused, a runtime that has no
+    /// work to do doesn't sleep until the next timer: the clock jumps to its
+    /// deadline. See [`time::pause`] for more details.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::{runtime, time};
+    /// # use std::time::Duration;
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .enable_time()
+    ///     .start_paused(true)
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// rt.block_on(async {
+    ///     // Completes right away
+    ///     time::sleep(Duration::from_secs(3600)).await;
+    /// });
+    /// ```
+    ///
+    /// [`time::pause`]: crate::time::pause
+    pub fn start_paused(&mut self, start_paused: bool) -> &mut Self {
+        self.start_paused = start_paused;
+        self
+    }
+
+    /// Rounds the timer deadlines up to a multiple of `resolution`.
+    ///
+    /// Each distinct deadline costs the driver a wakeup: 10,000 sleeps
+    /// spread over a second wake the runtime up to 10,000 times. Rounded to
+    /// 10ms, the same sleeps share 100 deadlines, and the driver fires them
+    /// by batches. The price is precision: a timer never fires early, but
+    /// up to `resolution` late.
+    ///
+    /// [`RuntimeMetrics::timer_fired_count`] and
+    /// [`RuntimeMetrics::timer_tick_count`] show how many timers fire per
+    /// wakeup.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::runtime;
+    /// # use std::time::Duration;
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .enable_time()
+    ///     .timer_resolution(Duration::from_millis(10))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Default: `Duration::ZERO`, the deadlines are not rounded.
+    ///
+    /// [`RuntimeMetrics::timer_fired_count`]: crate::runtime::RuntimeMetrics::timer_fired_count
+    /// [`RuntimeMetrics::timer_tick_count`]: crate::runtime::RuntimeMetrics::timer_tick_count
+    pub fn timer_resolution(&mut self, resolution: Duration) -> &mut Self {
+        self.timer_resolution = resolution;
+        self
+    }
+
+    /// Sets the maximum number of I/O events processed per tick.
+    ///
+    /// Default: 1024
+    pub fn max_io_events_per_tick(&mut self, capacity: usize) -> &mut Self {
+        self.nevents = capacity;
+        self
+    }
+
+    /// Specifies the limit for additional threads spawned by the Runtime.
+    ///
+    /// These threads are used for blocking operations like tasks spawned
+    /// through [`spawn_blocking`] or the `fs` module. Unlike the scheduler
+    /// thread, they are not always active and will exit if left idle for too
+    /// long. You can change this timeout duration with [`thread_keep_alive`].
+    ///
+    /// Once the limit is reached, blocking tasks are queued until a thread
+    /// becomes idle.
+    ///
+    /// The default value is 512.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `val` is not larger than `0`.
+    ///
+    /// [`spawn_blocking`]: crate::task::spawn_blocking
+    /// [`thread_keep_alive`]: Builder::thread_keep_alive
+    #[track_caller]
+    pub fn max_blocking_threads(&mut self, val: usize) -> &mut Self {
+        assert!(val > 0, "Max blocking threads cannot be set to 0");
+        self.max_blocking_threads = val;
+        self
+    }
+
+    /// Sets a custom timeout for a thread in the blocking pool.
+    ///
+    /// By default, the timeout for a thread is set to 10 seconds.
+    pub fn thread_keep_alive(&mut self, duration: Duration) -> &mut Self {
+        self.keep_alive = Some(duration);
+        self
+    }
+
+    /// Bounds the number of tasks spawned from other threads that wait to be
+    /// polled.
+    ///
+    /// A task spawned from outside of the thread driving the runtime, e.g.
+    /// with [`Handle::spawn`] or from a [`spawn_blocking`] thread, goes
+    /// through the runtime's injection queue. Without a bound, a producer
+    /// spawning faster than the runtime polls grows the queue until the
+    /// process runs out of memory. With a bound, once `val` tasks are
+    /// pending, [`Handle::spawn`] blocks the calling thread until the runtime
+    /// catches up, and [`Handle::try_spawn`] returns the future back.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::runtime;
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .max_pending_spawns(1024)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// The tasks spawned by the tasks of the runtime are not bounded: the
+    /// thread spawning them is the one that would drain the queue. Beware
+    /// that a blocked spawner waits forever if no thread drives the runtime
+    /// with [`Runtime::block_on`].
+    ///
+    /// Default: unbounded.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `val` is not larger than `0`.
+    ///
+    /// [`Handle::spawn`]: crate::runtime::Handle::spawn
+    /// [`Handle::try_spawn`]: crate::runtime::Handle::try_spawn
+    /// [`spawn_blocking`]: crate::task::spawn_blocking
+    /// [`Runtime::block_on`]: crate::runtime::Runtime::block_on
+    #[track_caller]
+    pub fn max_pending_spawns(&mut self, val: usize) -> &mut Self {
+        assert!(val > 0, "Max pending spawns cannot be set to 0");
+        self.max_pending_spawns = Some(val);
+        self
+    }
+
+    /// Sets the number of times an idle worker checks for new work before
+    /// parking.
+    ///
+    /// A worker with no task to run parks: it blocks in the driver until an
+    /// event arrives. Parking is a system call, and so is the unpark by a
+    /// thread waking a task: when tasks are woken from other threads at a
+    /// high rate, the worker may park and be unparked once per task. Spinning
+    /// first trades CPU time for latency: the worker checks the injection
+    /// queue `val` times, picking up work sent by other threads without the
+    /// round trip through the kernel. I/O events and timers are only seen
+    /// once the worker parks.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::runtime;
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .max_idle_spins(100)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`RuntimeMetrics::worker_idle_spin_count`] and
+    /// [`RuntimeMetrics::worker_park_count`] tell whether spinning pays off.
+    ///
+    /// Default: 0, the worker parks right away.
+    ///
+    /// [`RuntimeMetrics::worker_idle_spin_count`]: crate::runtime::RuntimeMetrics::worker_idle_spin_count
+    /// [`RuntimeMetrics::worker_park_count`]: crate::runtime::RuntimeMetrics::worker_park_count
+    pub fn max_idle_spins(&mut self, val: u32) -> &mut Self {
+        self.max_idle_spins = val;
+        self
+    }
+
+    /// Sets the number of task polls after which the scheduler checks the
+    /// drivers for new events.
+    ///
+    /// The scheduler polls its tasks as long as there are some ready to run,
+    /// and only checks for I/O events and expired timers when it runs out of
+    /// tasks, or every `event_interval` polls. Without the latter, a task
+    /// waking itself on every poll would keep the run queue from ever
+    /// becoming empty, and starve the sockets and timers for good.
+    ///
+    /// A lower value serves the I/O events and timers with less latency, at
+    /// the cost of more system calls while the runtime is busy.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::runtime;
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .event_interval(31)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Default: 61.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if `val` is not larger than `0`.
+    #[track_caller]
+    pub fn event_interval(&mut self, val: u32) -> &mut Self {
+        assert!(val > 0, "Event interval cannot be set to 0");
+        self.event_interval = val;
+        self
+    }
+
+    /// Executes function `f` just before a task is spawned.
+    ///
+    /// `f` is called within the thread spawning the task, before the task
+    /// is first scheduled. It receives the [`TaskMeta`] of the task, e.g. to
+    /// count the live tasks per spawn location. The tasks of
+    /// [`spawn_blocking`] are included.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::runtime;
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .on_task_spawn(|meta| println!("spawned {} at {}", meta.id(), meta.spawned_at()))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`spawn_blocking`]: crate::task::spawn_blocking
+    pub fn on_task_spawn<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&TaskMeta<'_>) + Send + Sync + 'static,
+    {
+        self.before_spawn = Some(Arc::new(f));
+        self
+    }
+
+    /// Executes function `f` just after a task is terminated.
+    ///
+    /// `f` is called within the thread that terminated the task, once the
+    /// task completed, panicked or was cancelled, and its `JoinHandle` was
+    /// notified. A panic of `f` is ignored.
+    pub fn on_task_terminate<F>(&mut self, f: F) -> &mut Self
+    where
+        F: Fn(&TaskMeta<'_>) + Send + Sync + 'static,
+    {
+        self.after_termination = Some(Arc::new(f));
+        self
+    }
+
+    /// Enables measuring the poll time of the tasks.
+    ///
+    /// The durations are counted in a histogram, read with
+    /// [`RuntimeMetrics::poll_time_histogram`]. Measuring reads the clock
+    /// twice per poll, so it is disabled by default.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::runtime;
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .enable_metrics_poll_time_histogram()
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// [`RuntimeMetrics::poll_time_histogram`]: crate::runtime::RuntimeMetrics::poll_time_histogram
+    pub fn enable_metrics_poll_time_histogram(&mut self) -> &mut Self {
+        self.metrics_poll_time_histogram = true;
+        self
+    }
+
+    /// Warns about the tasks that can never be woken.
+    ///
+    /// A future returning `Pending` must make sure it is woken later: it
+    /// stores a clone of the waker where the event it waits for will find it,
+    /// or wakes itself. A future that forgets to, e.g. a hand-written
+    /// `poll` returning `Pending` before registering, is never polled again
+    /// and its task hangs silently.
+    ///
+    /// With the detection enabled, the runtime counts the clones of each
+    /// task's waker. A task returning `Pending` while none is alive, and
+    /// not woken during its poll, is reported with its spawn location: as a
+    /// `tracing` warning with the `tracing` feature, otherwise on the
+    /// standard error. [`RuntimeMetrics::worker_lost_wakeup_count`] counts them.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::runtime;
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .detect_lost_wakeups(cfg!(debug_assertions))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// A future may legitimately wait for its task to be aborted, e.g.
+    /// [`std::future::pending`]: it is reported too.
+    ///
+    /// Default: `false`.
+    ///
+    /// [`RuntimeMetrics::worker_lost_wakeup_count`]: crate::runtime::RuntimeMetrics::worker_lost_wakeup_count
+    pub fn detect_lost_wakeups(&mut self, enable: bool) -> &mut Self {
+        self.detect_lost_wakeups = enable;
+        self
+    }
+
+    /// Enables or disables an event for each tick of the scheduling loop.
+    ///
+    /// A tick is a round of the loop driving the scheduler: it polls the
+    /// tasks in the run queue, at most [`event_interval`] of them, then
+    /// checks the drivers, or parks the thread if the queue ran empty. Each
+    /// round is reported with:
+    ///
+    /// - `tick`, the tick counter of the scheduler core, incremented before
+    ///   each attempt to pop a task;
+    /// - `tasks_polled`, the number of tasks polled during the round;
+    /// - `driver_checked`, whether the drivers were checked for I/O and
+    ///   timer events at the end of the round. An empty queue does not park
+    ///   the thread if a task was woken meanwhile.
+    ///
+    /// The events are emitted at the `TRACE` level inside the `worker` span
+    /// with the `tracing` feature, otherwise printed on the standard error.
+    /// Lining them up with the `task` spans shows how the polls of the tasks
+    /// are interleaved with the I/O and timer events.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::runtime;
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .enable_all()
+    ///     .enable_scheduler_tracing(true)
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Default: `false`.
+    ///
+    /// [`event_interval`]: Builder::event_interval
+    pub fn enable_scheduler_tracing(&mut self, enable: bool) -> &mut Self {
+        self.scheduler_tracing = enable;
+        self
+    }
+
     pub fn build(&mut self) -> io::Result<Runtime> {
         match &self.kind {
             Kind::CurrentThread => self.build_current_thread_runtime(),
@@ -44,31 +513,73 @@ impl Builder {
     fn build_current_thread_runtime(&mut self) -> io::Result<Runtime> {
         use crate::runtime::runtime::Scheduler;
 
-        let (scheduler, handle) = self.build_current_thread_runtime_components(None)?;
+        let (scheduler, handle, blocking_pool) =
+            self.build_current_thread_runtime_components(None)?;
 
         Ok(Runtime::from_parts(
             Scheduler::CurrentThread(scheduler),
             handle,
+            blocking_pool,
         ))
     }
 
+    fn hooks(&self) -> TaskHooks {
+        TaskHooks {
+            task_spawn_callback: self.before_spawn.clone(),
+            task_terminate_callback: self.after_termination.clone(),
+        }
+    }
+
+    fn get_cfg(&self) -> driver::Cfg {
+        driver::Cfg {
+            enable_io: self.enable_io,
+            enable_time: self.enable_time,
+            nevents: self.nevents,
+            start_paused: self.start_paused,
+            timer_resolution: self.timer_resolution,
+        }
+    }
+
     fn build_current_thread_runtime_components(
         &mut self,
         local_tid: Option<ThreadId>,
-    ) -> io::Result<(CurrentThread, Handle)> {
+    ) -> io::Result<(CurrentThread, Handle, BlockingPool)> {
         use crate::runtime::scheduler;
 
+        let (driver, driver_handle) = Driver::new(self.get_cfg())?;
+
+        // Blocking pool
+        let blocking_pool = BlockingPool::new(
+            self.max_blocking_threads,
+            self.keep_alive.unwrap_or(BLOCKING_KEEP_ALIVE),
+        );
+        let blocking_spawner = blocking_pool.spawner().clone();
+
         // And now put a single-threaded scheduler on top of the timer. When
         // there are no futures ready to do something, it'll let the timer or
         // the reactor to generate some new stimuli for the futures to continue
         // in their life.
-        let (scheduler, handle) =
-            CurrentThread::new(self.seed_generator.next_generator(), local_tid);
+        let (scheduler, handle) = CurrentThread::new(
+            driver,
+            driver_handle,
+            blocking_spawner,
+            Config {
+                task_hooks: self.hooks(),
+                max_pending_spawns: self.max_pending_spawns,
+                max_idle_spins: self.max_idle_spins,
+                event_interval: self.event_interval,
+                detect_lost_wakeups: self.detect_lost_wakeups,
+                scheduler_tracing: self.scheduler_tracing,
+                seed_generator: self.seed_generator.next_generator(),
+            },
+            WorkerMetrics::new(self.metrics_poll_time_histogram),
+            local_tid,
+        );
 
         let handle = Handle {
             inner: scheduler::Handle::CurrentThread(handle),
         };
 
-        Ok((scheduler, handle))
+        Ok((scheduler, handle, blocking_pool))
     }
 }
@@ -0,0 +1,29 @@
+use crate::runtime::TaskHooks;
+use crate::util::RngSeedGenerator;
+
+/// Scheduler configuration, gathered by the `Builder`.
+pub(crate) struct Config {
+    /// Hooks called when tasks are spawned and terminated
+    pub(crate) task_hooks: TaskHooks,
+
+    /// Bound on the tasks spawned from other threads waiting to be polled,
+    /// `None` if unbounded
+    pub(crate) max_pending_spawns: Option<usize>,
+
+    /// Number of times an idle worker checks for new work before parking
+    pub(crate) max_idle_spins: u32,
+
+    /// Number of task polls between two checks of the drivers
+    pub(crate) event_interval: u32,
+
+    /// Whether to warn about the tasks returning `Pending` without keeping
+    /// their waker
+    pub(crate) detect_lost_wakeups: bool,
+
+    /// Whether to report each round of the scheduling loop
+    pub(crate) scheduler_tracing: bool,
+
+    /// Random number generator seed to configure runtimes to act in a
+    /// deterministic way.
+    pub(crate) seed_generator: RngSeedGenerator,
+}
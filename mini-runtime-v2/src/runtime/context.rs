@@ -1,20 +1,38 @@
 mod current;
 
-pub(crate) use current::{SetCurrentGuard, with_current};
+pub(crate) use current::{SetCurrentGuard, try_set_current, with_current};
 use std::cell::Cell;
 
 mod runtime;
-pub(crate) use runtime::{EnterRuntime, enter_runtime};
+pub(crate) use runtime::{EnterRuntime, current_enter_context, enter_runtime};
 
 mod blocking;
 pub(crate) use blocking::BlockingRegionGuard;
 
+mod scoped;
+use scoped::Scoped;
+
+use crate::runtime::ThreadId;
+use crate::runtime::coop;
+use crate::runtime::scheduler;
+use crate::runtime::task::Id;
 use crate::util::rand::FastRand;
+use std::fmt::Write;
+use std::thread::AccessError;
 
 struct Context {
+    /// Uniquely identifies the current thread
+    thread_id: Cell<Option<ThreadId>>,
+
     /// Handle to the runtime scheduler running on the current thread.
     current: current::HandleCell,
 
+    /// Handle to the scheduler's internal "context"
+    scheduler: Scoped<scheduler::Context>,
+
+    /// Id of the task currently being polled on this thread.
+    current_task_id: Cell<Option<Id>>,
+
     /// Tracks if the current thread is currently driving a runtime.
     /// Note, that if this is set to "entered", the current scheduler
     /// handle may not reference the runtime currently executing. This
@@ -22,6 +40,9 @@ struct Context {
     /// within a runtime.
     runtime: Cell<EnterRuntime>,
 
+    /// Budget of the task being polled, see `coop`.
+    budget: Cell<coop::Budget>,
+
     /// Used for fair scheduling, random selection, jitter, etc.
     ///
     /// Uses Lock-free & lightweight FastRand (compare to Global RNG (thread_rng)),
@@ -32,7 +53,13 @@ struct Context {
 mini_runtime_thread_local! {
     static CONTEXT: Context = const {
         Context {
-             current: current::HandleCell::new(),
+            thread_id: Cell::new(None),
+
+            current: current::HandleCell::new(),
+
+            scheduler: Scoped::new(),
+
+            current_task_id: Cell::new(None),
 
             // Tracks if the current thread is currently driving a runtime.
             // Note, that if this is set to "entered", the current scheduler
@@ -41,7 +68,120 @@ mini_runtime_thread_local! {
             // within a runtime.
             runtime: Cell::new(EnterRuntime::NotEntered),
 
+            budget: Cell::new(coop::Budget::unconstrained()),
+
             rng: Cell::new(None),
         }
     }
 }
+
+/// Describes the runtime context of the current thread, one field per line.
+///
+/// Meant for diagnostics, e.g. `eprintln!("{}", context::dump())` while
+/// debugging, and the panic messages of a misused `block_on`: whether the
+/// thread entered a runtime, how many handles are set as current, which one
+/// is, and whether the thread's random number generator was seeded.
+pub(crate) fn dump() -> String {
+    let dump = CONTEXT.try_with(|ctx| {
+        let mut out = String::from("thread context:");
+        let _ = write!(out, "\n  thread id: {:?}", ctx.thread_id.get());
+
+        let _ = match ctx.runtime.get() {
+            EnterRuntime::Entered {
+                allow_block_in_place,
+            } => write!(
+                out,
+                "\n  runtime: entered (block_in_place allowed: {allow_block_in_place})"
+            ),
+            EnterRuntime::NotEntered => write!(out, "\n  runtime: not entered"),
+        };
+
+        let _ = write!(out, "\n  handle depth: {}", ctx.current.depth());
+        let _ = match ctx.current.try_describe() {
+            Some(handle) => write!(out, "\n  current handle: {handle}"),
+            None => write!(out, "\n  current handle: borrowed"),
+        };
+
+        let scheduler = ctx.scheduler.with(|scheduler| scheduler.is_some());
+        let _ = write!(
+            out,
+            "\n  scheduler context: {}",
+            if scheduler { "set" } else { "none" }
+        );
+
+        let _ = match ctx.current_task_id.get() {
+            Some(id) => write!(out, "\n  current task: {id}"),
+            None => write!(out, "\n  current task: none"),
+        };
+
+        let rng = ctx.rng.get().is_some();
+        let _ = write!(
+            out,
+            "\n  rng: {}",
+            if rng { "seeded" } else { "not seeded" }
+        );
+        out
+    });
+
+    dump.unwrap_or_else(|_| "thread context: destroyed".to_string())
+}
+
+/// Returns the runtime's ID of the current thread, allocating one on first use.
+pub(crate) fn thread_id() -> Result<ThreadId, AccessError> {
+    CONTEXT.try_with(|ctx| match ctx.thread_id.get() {
+        Some(id) => id,
+        None => {
+            let id = ThreadId::next();
+            ctx.thread_id.set(Some(id));
+            id
+        }
+    })
+}
+
+pub(crate) fn set_current_task_id(id: Option<Id>) -> Option<Id> {
+    CONTEXT
+        .try_with(|ctx| ctx.current_task_id.replace(id))
+        .unwrap_or(None)
+}
+
+pub(crate) fn current_task_id() -> Option<Id> {
+    CONTEXT
+        .try_with(|ctx| ctx.current_task_id.get())
+        .unwrap_or(None)
+}
+
+/// Calls `f` with the coop budget of the current thread.
+pub(crate) fn budget<R>(f: impl FnOnce(&Cell<coop::Budget>) -> R) -> Result<R, AccessError> {
+    CONTEXT.try_with(|ctx| f(&ctx.budget))
+}
+
+/// Calls `f` with the random number generator of the current thread.
+///
+/// The generator is seeded from the runtime's seed generator when the thread
+/// enters the runtime.
+pub(crate) fn with_rng<R>(f: impl FnOnce(&mut FastRand) -> R) -> R {
+    CONTEXT.with(|ctx| {
+        let mut rng = ctx.rng.get().unwrap_or_else(FastRand::new);
+        let ret = f(&mut rng);
+        ctx.rng.set(Some(rng));
+        ret
+    })
+}
+
+/// Sets the scheduler context for the duration of the closure.
+pub(super) fn set_scheduler<R>(v: &scheduler::Context, f: impl FnOnce() -> R) -> R {
+    CONTEXT.with(|c| c.scheduler.set(v, f))
+}
+
+/// Calls `f` with the scheduler context of the current thread, if any.
+pub(super) fn with_scheduler<R>(f: impl FnOnce(Option<&scheduler::Context>) -> R) -> R {
+    let mut f = Some(f);
+    CONTEXT
+        .try_with(|c| {
+            let f = f.take().unwrap();
+            c.scheduler.with(f)
+        })
+        // If the thread-local has been destroyed, act as if there is no
+        // scheduler running on this thread.
+        .unwrap_or_else(|_| (f.take().unwrap())(None))
+}
@@ -1,5 +1,10 @@
+use crate::runtime::park::{ParkThread, UnparkThread};
+use crate::time::error::Elapsed;
 use crate::util::markers::NotSendOrSync;
 use std::marker::PhantomData;
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::{Duration, Instant};
 
 /// Guard tracking that a caller has entered a blocking region.
 #[must_use]
@@ -11,4 +16,74 @@ impl BlockingRegionGuard {
     pub(super) fn new() -> BlockingRegionGuard {
         BlockingRegionGuard { _p: PhantomData }
     }
+
+    /// Blocks the current thread until `f` completes, parking it while `f`
+    /// is pending. Nothing else is driven meanwhile: `f` only completes if
+    /// another thread wakes it.
+    pub(crate) fn block_on<F: Future>(&mut self, f: F) -> F::Output {
+        let mut park = ParkThread::new();
+        match self.park_until(&mut park, f, None) {
+            Ok(v) => v,
+            Err(_) => unreachable!("no deadline"),
+        }
+    }
+
+    /// Like `block_on`, but gives up once `timeout` elapsed, dropping `f`.
+    ///
+    /// The thread parks with a timeout instead of polling `f` in a loop:
+    /// the wakeups of `f`, e.g. by the driver of the runtime on another
+    /// thread, and the deadline both unpark it.
+    pub(crate) fn block_on_timeout<F: Future>(
+        &mut self,
+        f: F,
+        timeout: Duration,
+    ) -> Result<F::Output, Elapsed> {
+        let mut park = ParkThread::new();
+        // A timeout too far away to be represented never elapses.
+        let deadline = Instant::now().checked_add(timeout);
+        self.park_until(&mut park, f, deadline)
+    }
+
+    /// Polls `f` each time `park` is unparked, until `f` completes or
+    /// `deadline` passes.
+    fn park_until<F: Future>(
+        &mut self,
+        park: &mut ParkThread,
+        f: F,
+        deadline: Option<Instant>,
+    ) -> Result<F::Output, Elapsed> {
+        let waker = Waker::from(Arc::new(ParkWaker(park.unpark())));
+        let mut cx = Context::from_waker(&waker);
+
+        pin!(f);
+        loop {
+            if let Poll::Ready(v) = f.as_mut().poll(&mut cx) {
+                return Ok(v);
+            }
+
+            match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        return Err(Elapsed::new());
+                    }
+                    park.park_timeout(deadline - now);
+                }
+                None => park.park(),
+            }
+        }
+    }
+}
+
+/// Unparks the thread blocked in `BlockingRegionGuard::block_on`.
+struct ParkWaker(UnparkThread);
+
+impl Wake for ParkWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.0.unpark();
+    }
 }
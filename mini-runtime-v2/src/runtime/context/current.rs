@@ -7,11 +7,9 @@ use std::marker::PhantomData;
 #[derive(Debug)]
 #[must_use]
 pub(crate) struct SetCurrentGuard {
-    #[allow(dead_code)]
     // The previous handle
     prev: Option<scheduler::Handle>,
 
-    #[allow(dead_code)]
     // The depth for this guard
     depth: usize,
 
@@ -27,6 +25,13 @@ pub(super) struct HandleCell {
     depth: Cell<usize>,
 }
 
+/// Sets `handle` as the current runtime handle of this thread.
+///
+/// Returns `None` if the thread-local context was already destroyed.
+pub(crate) fn try_set_current(handle: &scheduler::Handle) -> Option<SetCurrentGuard> {
+    CONTEXT.try_with(|ctx| ctx.set_current(handle)).ok()
+}
+
 pub(crate) fn with_current<F, R>(f: F) -> Result<R, TryCurrentError>
 where
     F: FnOnce(&scheduler::Handle) -> R,
@@ -56,6 +61,34 @@ impl Context {
     }
 }
 
+impl Drop for SetCurrentGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|ctx| {
+            let depth = ctx.current.depth.get();
+
+            // Restoring `prev` now would bring back a handle that a guard
+            // still alive replaced, and a later drop would restore a handle
+            // that was meant to be gone.
+            if depth != self.depth {
+                if !std::thread::panicking() {
+                    panic!(
+                        "`EnterGuard` values dropped out of order. Guards returned by \
+                         `Handle::enter` must be dropped in the reverse order as they were \
+                         acquired: dropped the guard of depth {}, while the current depth is {}.",
+                        self.depth, depth
+                    );
+                } else {
+                    // Just return... this will leave handles in a wonky state though...
+                    return;
+                }
+            }
+
+            *ctx.current.handle.borrow_mut() = self.prev.take();
+            ctx.current.depth.set(depth - 1);
+        });
+    }
+}
+
 impl HandleCell {
     pub(super) const fn new() -> HandleCell {
         HandleCell {
@@ -63,4 +96,18 @@ impl HandleCell {
             depth: Cell::new(0),
         }
     }
+
+    /// Returns the number of handles set as current and not restored yet.
+    pub(super) fn depth(&self) -> usize {
+        self.depth.get()
+    }
+
+    /// Formats the current handle, `None` if it is being replaced.
+    pub(super) fn try_describe(&self) -> Option<String> {
+        let handle = self.handle.try_borrow().ok()?;
+        Some(match handle.as_ref() {
+            Some(handle) => format!("{handle:?}"),
+            None => "none".to_string(),
+        })
+    }
 }
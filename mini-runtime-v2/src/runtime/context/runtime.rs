@@ -1,4 +1,4 @@
-use super::{BlockingRegionGuard, CONTEXT, SetCurrentGuard};
+use super::{BlockingRegionGuard, CONTEXT, SetCurrentGuard, dump, with_current};
 use crate::runtime::scheduler;
 use crate::util::rand::{FastRand, RngSeed};
 
@@ -7,7 +7,9 @@ use crate::util::rand::{FastRand, RngSeed};
 pub(crate) enum EnterRuntime {
     /// Currently in a runtime context.
     Entered {
-        #[allow(dead_code)]
+        /// Whether `block_in_place` may be called: only the worker threads
+        /// of a multi-thread runtime can hand their work over to another
+        /// thread.
         allow_block_in_place: bool,
     },
 
@@ -24,7 +26,6 @@ pub(crate) struct EnterRuntimeGuard {
     #[allow(dead_code)] // Only tracking the guard.
     pub(crate) handle: SetCurrentGuard,
 
-    #[allow(dead_code)]
     // Tracks the previous random number generator seed
     old_seed: RngSeed,
 }
@@ -82,12 +83,52 @@ where
         return f(&mut guard.blocking);
     }
 
-    panic!(
-        "Cannot start a runtime from within a runtime. This happens \
-            because a function (like `block_on`) attempted to block the \
-            current thread while the thread is being used to drive \
-            asynchronous tasks."
-    );
+    // Blocking on the runtime the thread already drives is a deadlock: the
+    // tasks the thread would wait for can only run on the thread itself.
+    // The dump of the thread's context tells how it got there.
+    match with_current(|current| (current.ptr_eq(handle), format!("{:?}", current))) {
+        Ok((true, current)) => panic!(
+            "Cannot block on a runtime from within a thread driving its tasks: \
+                the thread would wait for tasks that only it can run. The \
+                thread is driving {}.\n{}",
+            current,
+            dump()
+        ),
+        Ok((false, current)) => panic!(
+            "Cannot start a runtime from within a runtime. This happens \
+                because a function (like `block_on`) attempted to block the \
+                current thread while the thread is being used to drive \
+                asynchronous tasks. The thread is driving {}.\n{}",
+            current,
+            dump()
+        ),
+        Err(_) => panic!(
+            "Cannot start a runtime from within a runtime. This happens \
+                because a function (like `block_on`) attempted to block the \
+                current thread while the thread is being used to drive \
+                asynchronous tasks.\n{}",
+            dump()
+        ),
+    }
+}
+
+/// Returns whether the current thread entered a runtime context, and if so
+/// whether it allows `block_in_place`.
+pub(crate) fn current_enter_context() -> EnterRuntime {
+    CONTEXT.with(|c| c.runtime.get())
+}
+
+impl Drop for EnterRuntimeGuard {
+    fn drop(&mut self) {
+        CONTEXT.with(|c| {
+            assert!(c.runtime.get().is_entered());
+            c.runtime.set(EnterRuntime::NotEntered);
+            // Replace the previous RNG seed
+            let mut rng = c.rng.get().unwrap_or_else(FastRand::new);
+            rng.replace_seed(self.old_seed.clone());
+            c.rng.set(Some(rng));
+        });
+    }
 }
 
 impl EnterRuntime {
@@ -0,0 +1,149 @@
+//! Cooperative scheduling.
+//!
+//! A task only gives the thread back to the scheduler when it returns
+//! `Poll::Pending`. A task looping over a resource that is always ready,
+//! e.g. a channel flooded with messages, never does: the other tasks of the
+//! worker, and the drivers, are starved until the resource runs dry.
+//!
+//! To prevent this, each task is given a budget when it is polled. Every
+//! operation on a runtime resource consumes one unit of the budget; once it
+//! is exhausted, the resources return `Poll::Pending` even if they are
+//! ready, after waking the task. The task yields to the scheduler, and is
+//! polled again with a fresh budget once the others had their turn.
+//!
+//! A resource opts in by calling [`poll_proceed`] before doing its work:
+//!
+//! ```ignore
+//! fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+//!     let coop = ready!(coop::poll_proceed(cx));
+//!
+//!     if let Some(value) = self.queue.pop_front() {
+//!         coop.made_progress();
+//!         return Poll::Ready(Some(value));
+//!     }
+//!
+//!     // Register the waker, the budget unit is given back on drop.
+//!     Poll::Pending
+//! }
+//! ```
+//!
+//! Outside of a runtime, e.g. in a future driven by another executor, and
+//! within `task::unconstrained`, the budget is unconstrained.
+
+use crate::runtime::context;
+use std::cell::Cell;
+use std::task::{Context, Poll};
+
+/// Number of operations a task may perform on runtime resources per poll.
+const INITIAL_BUDGET: u8 = 128;
+
+/// Opaque type tracking the amount of budget left, `None` when
+/// unconstrained.
+#[derive(Debug, Copy, Clone)]
+pub(crate) struct Budget(Option<u8>);
+
+impl Budget {
+    /// Budget given to a task each time it is polled.
+    const fn initial() -> Budget {
+        Budget(Some(INITIAL_BUDGET))
+    }
+
+    /// Returns an unconstrained budget: operations never yield.
+    pub(crate) const fn unconstrained() -> Budget {
+        Budget(None)
+    }
+
+    /// Consumes one unit, returns `false` if the budget is exhausted.
+    fn decrement(&mut self) -> bool {
+        match &mut self.0 {
+            Some(0) => false,
+            Some(budget) => {
+                *budget -= 1;
+                true
+            }
+            None => true,
+        }
+    }
+
+    fn is_unconstrained(self) -> bool {
+        self.0.is_none()
+    }
+}
+
+/// Runs `f` with the budget of a task poll.
+///
+/// The scheduler wraps each poll with it. The previous budget is restored
+/// afterwards, even if `f` panics.
+pub(crate) fn budget<R>(f: impl FnOnce() -> R) -> R {
+    with_budget(Budget::initial(), f)
+}
+
+/// Runs `f` without budget: the resources used by `f` never yield, see
+/// `task::unconstrained`.
+pub(crate) fn with_unconstrained<R>(f: impl FnOnce() -> R) -> R {
+    with_budget(Budget::unconstrained(), f)
+}
+
+fn with_budget<R>(budget: Budget, f: impl FnOnce() -> R) -> R {
+    struct ResetGuard {
+        prev: Budget,
+    }
+
+    impl Drop for ResetGuard {
+        fn drop(&mut self) {
+            let _ = context::budget(|cell| cell.set(self.prev));
+        }
+    }
+
+    let _guard = context::budget(|cell| ResetGuard {
+        prev: cell.replace(budget),
+    });
+
+    f()
+}
+
+/// Consumes one unit of the current task's budget.
+///
+/// Returns `Poll::Pending` and wakes the task if the budget is exhausted.
+/// Otherwise, the returned guard gives the unit back when dropped, unless
+/// [`RestoreOnPending::made_progress`] is called: an operation that ends up
+/// `Pending` doesn't count.
+pub(crate) fn poll_proceed(cx: &mut Context<'_>) -> Poll<RestoreOnPending> {
+    context::budget(|cell| {
+        let mut budget = cell.get();
+
+        if budget.decrement() {
+            let restore = RestoreOnPending(Cell::new(cell.get()));
+            cell.set(budget);
+            Poll::Ready(restore)
+        } else {
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .unwrap_or(Poll::Ready(RestoreOnPending(Cell::new(
+        Budget::unconstrained(),
+    ))))
+}
+
+/// Gives the budget unit consumed by [`poll_proceed`] back on drop, unless
+/// the operation made progress.
+pub(crate) struct RestoreOnPending(Cell<Budget>);
+
+impl RestoreOnPending {
+    /// Marks the operation as done: the budget unit stays consumed.
+    pub(crate) fn made_progress(&self) {
+        self.0.set(Budget::unconstrained());
+    }
+}
+
+impl Drop for RestoreOnPending {
+    fn drop(&mut self) {
+        // Don't reset if the budget was unconstrained or if we made
+        // progress.
+        let budget = self.0.get();
+        if !budget.is_unconstrained() {
+            let _ = context::budget(|cell| cell.set(budget));
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! Abstracts out the entire chain of runtime sub-drivers into common types.
+//!
+//! The scheduler parks on the driver when it has no task to run. With the I/O
+//! driver enabled, parking blocks in `mio::Poll`, otherwise it blocks the
+//! thread on a condition variable. On Unix, the signal driver sits on top of
+//! the I/O driver and dispatches the signals received while parked. The time
+//! driver wraps the whole I/O stack: it bounds the park duration by the next
+//! timer deadline.
+
+use crate::runtime::park::{ParkThread, UnparkThread};
+use crate::runtime::{io, time};
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug)]
+pub(crate) struct Driver {
+    inner: TimeDriver,
+}
+
+#[derive(Debug)]
+pub(crate) struct Handle {
+    /// IO driver handle
+    pub(crate) io: IoHandle,
+
+    /// Signal driver handle
+    #[cfg_attr(not(unix), allow(dead_code))]
+    pub(crate) signal: SignalHandle,
+
+    /// Time driver handle
+    pub(crate) time: Option<time::Handle>,
+}
+
+pub(crate) struct Cfg {
+    pub(crate) enable_io: bool,
+    pub(crate) enable_time: bool,
+    pub(crate) nevents: usize,
+    pub(crate) start_paused: bool,
+    pub(crate) timer_resolution: Duration,
+}
+
+#[derive(Debug)]
+enum TimeDriver {
+    Enabled { driver: time::Driver },
+    Disabled(IoStack),
+}
+
+#[derive(Debug)]
+pub(crate) enum IoStack {
+    Enabled(SignalDriver),
+    Disabled(ParkThread),
+}
+
+#[cfg(unix)]
+type SignalDriver = crate::runtime::signal::Driver;
+
+#[cfg(not(unix))]
+type SignalDriver = io::Driver;
+
+#[cfg(unix)]
+pub(crate) type SignalHandle = Option<crate::runtime::signal::Handle>;
+
+#[cfg(not(unix))]
+pub(crate) type SignalHandle = ();
+
+pub(crate) enum IoHandle {
+    Enabled(io::Handle),
+    Disabled(UnparkThread),
+}
+
+impl Driver {
+    pub(crate) fn new(cfg: Cfg) -> std::io::Result<(Self, Handle)> {
+        let (io_stack, io, signal) = create_io_stack(cfg.enable_io, cfg.nevents)?;
+
+        let (inner, time) = if cfg.enable_time {
+            let clock = time::Clock::new(cfg.start_paused);
+            let (driver, handle) = time::Driver::new(io_stack, clock, cfg.timer_resolution);
+            (TimeDriver::Enabled { driver }, Some(handle))
+        } else {
+            (TimeDriver::Disabled(io_stack), None)
+        };
+
+        Ok((Driver { inner }, Handle { io, signal, time }))
+    }
+
+    pub(crate) fn park(&mut self, handle: &Handle) {
+        match &mut self.inner {
+            TimeDriver::Enabled { driver } => driver.park(handle),
+            TimeDriver::Disabled(v) => v.park(handle),
+        }
+    }
+
+    pub(crate) fn park_timeout(&mut self, handle: &Handle, duration: Duration) {
+        match &mut self.inner {
+            TimeDriver::Enabled { driver } => driver.park_timeout(handle, duration),
+            TimeDriver::Disabled(v) => v.park_timeout(handle, duration),
+        }
+    }
+}
+
+fn create_io_stack(
+    enabled: bool,
+    nevents: usize,
+) -> std::io::Result<(IoStack, IoHandle, SignalHandle)> {
+    let ret = if enabled {
+        let (io_driver, handle) = io::Driver::new(nevents)?;
+        let (driver, signal) = create_signal_driver(io_driver, &handle)?;
+        (IoStack::Enabled(driver), IoHandle::Enabled(handle), signal)
+    } else {
+        let park = ParkThread::new();
+        let unpark = park.unpark();
+        (
+            IoStack::Disabled(park),
+            IoHandle::Disabled(unpark),
+            Default::default(),
+        )
+    };
+
+    Ok(ret)
+}
+
+impl IoStack {
+    pub(crate) fn park(&mut self, handle: &Handle) {
+        match self {
+            IoStack::Enabled(v) => v.park(handle.io()),
+            IoStack::Disabled(v) => v.park(),
+        }
+    }
+
+    pub(crate) fn park_timeout(&mut self, handle: &Handle, duration: Duration) {
+        match self {
+            IoStack::Enabled(v) => v.park_timeout(handle.io(), duration),
+            IoStack::Disabled(v) => v.park_timeout(duration),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn create_signal_driver(
+    io_driver: io::Driver,
+    io_handle: &io::Handle,
+) -> std::io::Result<(SignalDriver, SignalHandle)> {
+    let driver = crate::runtime::signal::Driver::new(io_driver, io_handle)?;
+    let handle = driver.handle();
+    Ok((driver, Some(handle)))
+}
+
+#[cfg(not(unix))]
+fn create_signal_driver(
+    io_driver: io::Driver,
+    _io_handle: &io::Handle,
+) -> std::io::Result<(SignalDriver, SignalHandle)> {
+    Ok((io_driver, ()))
+}
+
+impl Handle {
+    pub(crate) fn unpark(&self) {
+        match &self.io {
+            IoHandle::Enabled(handle) => handle.unpark(),
+            IoHandle::Disabled(handle) => handle.unpark(),
+        }
+    }
+
+    /// Returns a reference to the I/O driver handle.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the I/O driver is not enabled.
+    #[track_caller]
+    pub(crate) fn io(&self) -> &io::Handle {
+        match &self.io {
+            IoHandle::Enabled(handle) => handle,
+            IoHandle::Disabled(_) => panic!(
+                "A Mini runtime context was found, but IO is disabled. \
+                 Call `enable_io` on the runtime builder to enable IO."
+            ),
+        }
+    }
+
+    /// Returns a reference to the signal driver handle.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the I/O driver, which the signal driver sits
+    /// on, is not enabled.
+    #[cfg(unix)]
+    #[track_caller]
+    pub(crate) fn signal(&self) -> &crate::runtime::signal::Handle {
+        // Panics with the message of the disabled I/O driver.
+        let _ = self.io();
+        self.signal
+            .as_ref()
+            .expect("the signal driver is enabled with the I/O driver")
+    }
+
+    /// Returns a reference to the time driver handle.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the time driver is not enabled.
+    #[track_caller]
+    pub(crate) fn time(&self) -> &time::Handle {
+        self.time.as_ref().expect(
+            "A Mini runtime context was found, but timers are disabled. \
+             Call `enable_time` on the runtime builder to enable timers.",
+        )
+    }
+
+    /// Returns `true` if the time driver is enabled.
+    pub(crate) fn is_time_enabled(&self) -> bool {
+        self.time.is_some()
+    }
+}
+
+impl fmt::Debug for IoHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IoHandle::Enabled(handle) => handle.fmt(f),
+            IoHandle::Disabled(_) => f.write_str("IoHandle::Disabled"),
+        }
+    }
+}
@@ -0,0 +1,125 @@
+//! Snapshots of runtime state.
+//!
+//! See [`Handle::dump`](crate::runtime::Handle::dump).
+
+use crate::task::Id;
+use std::backtrace::Backtrace;
+use std::fmt;
+use std::panic::Location;
+use std::sync::Arc;
+
+/// A snapshot of the live tasks of a runtime.
+///
+/// The `Display` implementation prints one entry per task with its id, its
+/// name if any, its state, the location it was spawned from and the backtrace captured then.
+#[derive(Debug)]
+pub struct Dump {
+    tasks: Vec<Task>,
+}
+
+/// A snapshot of a single task.
+#[derive(Debug)]
+pub struct Task {
+    id: Id,
+    name: Option<Box<str>>,
+    state: TaskState,
+    spawned_at: &'static Location<'static>,
+    trace: Arc<Backtrace>,
+}
+
+/// What a task was doing when the dump was taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskState {
+    /// Waiting for a wakeup: a task stuck in this state is usually the reason
+    /// a program hangs.
+    Idle,
+    /// Woken and waiting in a run queue to be polled.
+    Notified,
+    /// Being polled.
+    Running,
+}
+
+impl Dump {
+    pub(crate) fn new(tasks: Vec<Task>) -> Dump {
+        Dump { tasks }
+    }
+
+    /// Returns the tasks of the runtime, in spawn order.
+    pub fn tasks(&self) -> &[Task] {
+        &self.tasks
+    }
+}
+
+impl Task {
+    pub(crate) fn new(
+        id: Id,
+        name: Option<Box<str>>,
+        state: TaskState,
+        spawned_at: &'static Location<'static>,
+        trace: Arc<Backtrace>,
+    ) -> Task {
+        Task {
+            id,
+            name,
+            state,
+            spawned_at,
+            trace,
+        }
+    }
+
+    /// Returns the [ID] of the task.
+    ///
+    /// [ID]: crate::task::Id
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Returns the name of the task, if it was spawned with one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// Returns the state of the task.
+    pub fn state(&self) -> TaskState {
+        self.state
+    }
+
+    /// Returns the location in the source code the task was spawned from.
+    pub fn spawned_at(&self) -> &'static Location<'static> {
+        self.spawned_at
+    }
+
+    /// Returns the backtrace captured when the task was spawned.
+    pub fn trace(&self) -> &Backtrace {
+        &self.trace
+    }
+}
+
+impl fmt::Display for Dump {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{} live task(s)", self.tasks.len())?;
+
+        for task in &self.tasks {
+            write!(f, "task {}", task.id)?;
+            if let Some(name) = &task.name {
+                write!(f, " {name:?}")?;
+            }
+            writeln!(f, " [{}] spawned at {}:", task.state, task.spawned_at)?;
+            for line in task.trace.to_string().lines() {
+                writeln!(f, "    {line}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for TaskState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TaskState::Idle => "idle",
+            TaskState::Notified => "notified",
+            TaskState::Running => "running",
+        })
+    }
+}
@@ -1,5 +1,9 @@
-use crate::runtime::scheduler;
+use crate::runtime::{RuntimeFlavor, RuntimeMetrics, context, scheduler, task};
+use crate::task::JoinHandle;
+use crate::time::error::Elapsed;
 use crate::util::error::{CONTEXT_MISSING_ERROR, THREAD_LOCAL_DESTROYED_ERROR};
+use std::marker::PhantomData;
+use std::time::Duration;
 use std::{error, fmt};
 
 /// Handle to the runtime.
@@ -13,6 +17,307 @@ pub struct Handle {
     pub(crate) inner: scheduler::Handle,
 }
 
+impl Handle {
+    /// Returns a `Handle` view over the currently running `Runtime`.
+    ///
+    /// # Panics
+    ///
+    /// This will panic if called outside the context of a Mini runtime.
+    #[track_caller]
+    pub fn current() -> Self {
+        Handle {
+            inner: scheduler::Handle::current(),
+        }
+    }
+
+    /// Returns a `Handle` view over the currently running `Runtime`.
+    ///
+    /// Returns an error if no Runtime has been started.
+    ///
+    /// Contrary to `current`, this never panics. A library can thus use the
+    /// ambient runtime of its caller if there is one, and fall back to a
+    /// runtime of its own otherwise:
+    ///
+    /// ```
+    /// use mini_runtime_v2::runtime::{self, Handle};
+    /// # async fn work() {}
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///
+    /// match Handle::try_current() {
+    ///     Ok(handle) => {
+    ///         handle.spawn(work());
+    ///     }
+    ///     Err(e) if e.is_missing_context() => {
+    ///         let rt = runtime::Builder::new_current_thread().enable_all().build()?;
+    ///         rt.block_on(work());
+    ///     }
+    ///     Err(e) => return Err(e.into()),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn try_current() -> Result<Self, TryCurrentError> {
+        context::with_current(|inner| Handle {
+            inner: inner.clone(),
+        })
+    }
+
+    /// Enters the runtime context.
+    ///
+    /// Until the returned guard is dropped, the runtime is the current one
+    /// of the thread: [`Handle::current`] returns it and [`task::spawn`]
+    /// spawns onto it, without the thread driving the runtime.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::task;
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+    /// let _guard = rt.enter();
+    ///
+    /// // Spawned onto `rt`, the task runs once a thread drives `rt`.
+    /// task::spawn(async { /* ... */ });
+    /// ```
+    ///
+    /// The guards can be nested, the innermost runtime is the current one.
+    ///
+    /// # Panics
+    ///
+    /// Dropping the guards in another order than the reverse of the order
+    /// they were acquired panics: the thread would end up in the context of
+    /// a runtime it left.
+    ///
+    /// [`task::spawn`]: crate::task::spawn
+    pub fn enter(&self) -> EnterGuard<'_> {
+        EnterGuard {
+            _guard: match context::try_set_current(&self.inner) {
+                Some(guard) => guard,
+                None => panic!("{}", THREAD_LOCAL_DESTROYED_ERROR),
+            },
+            _handle_lifetime: PhantomData,
+        }
+    }
+
+    /// Spawns a future onto the runtime.
+    ///
+    /// Unlike [`task::spawn`], this works from any thread, e.g. a thread
+    /// that is not part of the runtime hands work over to it:
+    ///
+    /// ```
+    /// # async fn process(_request: u32) {}
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+    /// # let requests = 0..10;
+    /// let handle = rt.handle().clone();
+    ///
+    /// std::thread::spawn(move || {
+    ///     for request in requests {
+    ///         handle.spawn(process(request));
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// If the runtime was built with [`Builder::max_pending_spawns`] and has
+    /// that many tasks spawned from other threads waiting to be polled, the
+    /// calling thread blocks until the runtime catches up: the producer is
+    /// slowed down to the pace of the runtime. See [`try_spawn`] to handle
+    /// the overload another way.
+    ///
+    /// # Shutdown
+    ///
+    /// Once the runtime is shut down, or while it is shutting down, the
+    /// future is dropped right away without being polled, and the returned
+    /// `JoinHandle` resolves to a [`JoinError`] that [`is_cancelled`].
+    ///
+    /// A spawn from another thread may race with the shutdown. Either the
+    /// task is added to the runtime first, and the shutdown cancels it with
+    /// the other live tasks, or the runtime is closed first, and the spawn
+    /// cancels it. Either way, once both `spawn` and the shutdown returned,
+    /// the future was dropped and the `JoinHandle` is finished: a task is
+    /// never left behind in a runtime nobody drives anymore.
+    ///
+    /// [`task::spawn`]: crate::task::spawn
+    /// [`Builder::max_pending_spawns`]: crate::runtime::Builder::max_pending_spawns
+    /// [`try_spawn`]: Self::try_spawn
+    /// [`JoinError`]: crate::task::JoinError
+    /// [`is_cancelled`]: crate::task::JoinError::is_cancelled
+    #[track_caller]
+    pub fn spawn<F>(&self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.inner.spawn(future, task::SpawnMeta::unnamed())
+    }
+
+    /// Spawns a future onto the runtime like [`spawn`], or returns it back if
+    /// the runtime has too many pending spawns.
+    ///
+    /// The caller decides what to do with the overload, e.g. shed the load:
+    ///
+    /// ```
+    /// # async fn process(_request: u32) {}
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+    /// # let handle = rt.handle();
+    /// # let request = 1;
+    /// if let Err(e) = handle.try_spawn(process(request)) {
+    ///     eprintln!("{e}, request dropped");
+    /// }
+    /// ```
+    ///
+    /// Never fails if the runtime was built without
+    /// [`Builder::max_pending_spawns`].
+    ///
+    /// [`spawn`]: Self::spawn
+    /// [`Builder::max_pending_spawns`]: crate::runtime::Builder::max_pending_spawns
+    #[track_caller]
+    pub fn try_spawn<F>(&self, future: F) -> Result<JoinHandle<F::Output>, TrySpawnError<F>>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.inner
+            .try_spawn(future, task::SpawnMeta::unnamed())
+            .map_err(|future| TrySpawnError { future })
+    }
+
+    /// Returns the flavor of the current `Runtime`.
+    ///
+    /// Library code may adapt to the runtime it runs on, e.g. refuse to block
+    /// the only worker thread of a current-thread runtime.
+    ///
+    /// ```
+    /// use mini_runtime_v2::runtime::{self, Handle, RuntimeFlavor};
+    ///
+    /// let rt = runtime::Builder::new_current_thread().build().unwrap();
+    /// rt.block_on(async {
+    ///     assert_eq!(Handle::current().runtime_flavor(), RuntimeFlavor::CurrentThread);
+    /// });
+    /// ```
+    pub fn runtime_flavor(&self) -> RuntimeFlavor {
+        match self.inner {
+            scheduler::Handle::CurrentThread(_) => RuntimeFlavor::CurrentThread,
+        }
+    }
+
+    /// Runs a future to completion on the current thread, within the
+    /// runtime's context.
+    ///
+    /// Unlike [`Runtime::block_on`], the thread doesn't drive the runtime:
+    /// the tasks spawned by the future run on the thread calling
+    /// `Runtime::block_on`, and the I/O and timer resources only make
+    /// progress while a thread does so.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::task;
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+    /// let handle = rt.handle().clone();
+    ///
+    /// std::thread::spawn(move || {
+    ///     let answer = handle.block_on(async { task::spawn(async { 42 }).await.unwrap() });
+    /// #   assert_eq!(answer, 42);
+    /// });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the current thread already drives a runtime,
+    /// e.g. when called from within a task. Called on the handle of the
+    /// runtime the thread drives, it would deadlock: the panic message tells
+    /// both cases apart and names the runtime the thread drives.
+    ///
+    /// [`Runtime::block_on`]: crate::runtime::Runtime::block_on
+    #[track_caller]
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        context::enter_runtime(&self.inner, false, |blocking| blocking.block_on(future))
+    }
+
+    /// Runs a future to completion like [`block_on`], or gives up once
+    /// `timeout` elapsed.
+    ///
+    /// A thread waiting for the runtime, e.g. for a reply from one of its
+    /// tasks, doesn't hang if the thread driving the runtime stops doing so:
+    ///
+    /// ```
+    /// # use mini_runtime_v2::sync::mpsc;
+    /// # use std::time::Duration;
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    /// # let (tx, mut rx) = mpsc::channel(1);
+    /// # tx.try_send("pong").unwrap();
+    /// let handle = rt.handle().clone();
+    ///
+    /// std::thread::spawn(move || {
+    ///     match handle.block_on_timeout(rx.recv(), Duration::from_secs(1)) {
+    ///         Ok(reply) => println!("{reply:?}"),
+    ///         Err(_) => eprintln!("the runtime didn't reply"),
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// The thread is parked until the future is woken or the timeout
+    /// elapses, whichever comes first. The timeout is measured with the
+    /// system clock: the runtime's clock is only advanced by the thread
+    /// driving it, and may be paused. The future is dropped when the
+    /// timeout elapses.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`block_on`].
+    ///
+    /// [`block_on`]: Self::block_on
+    #[track_caller]
+    pub fn block_on_timeout<F: Future>(
+        &self,
+        future: F,
+        timeout: Duration,
+    ) -> Result<F::Output, Elapsed> {
+        context::enter_runtime(&self.inner, false, |blocking| {
+            blocking.block_on_timeout(future, timeout)
+        })
+    }
+
+    /// Returns a view that lets you get information about how the runtime
+    /// is performing.
+    pub fn metrics(&self) -> RuntimeMetrics {
+        RuntimeMetrics::new(self.clone())
+    }
+
+    /// Captures a snapshot of the runtime's state.
+    ///
+    /// The snapshot lists the tasks that did not complete yet, with their
+    /// [`TaskState`] and the backtrace captured when they were spawned. It is
+    /// meant for debugging a program that hangs: take the dump from another
+    /// thread with a cloned `Handle`, the tasks stuck in the `idle` state are
+    /// waiting for a wakeup that never comes.
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+    /// let handle = rt.handle().clone();
+    ///
+    /// std::thread::spawn(move || {
+    ///     std::thread::sleep(Duration::from_secs(5));
+    ///     eprintln!("{}", handle.dump());
+    /// });
+    /// ```
+    ///
+    /// This method is only available with the `task_dump` feature. Spawning
+    /// captures a backtrace for every task, which is slow.
+    ///
+    /// [`TaskState`]: crate::runtime::dump::TaskState
+    #[cfg(feature = "task_dump")]
+    pub fn dump(&self) -> crate::runtime::dump::Dump {
+        self.inner.owned_tasks().dump()
+    }
+}
+
+/// Runtime context guard, returned by [`Handle::enter`].
+///
+/// Leaves the runtime context when dropped.
+#[derive(Debug)]
+#[must_use = "Creating and dropping a guard does nothing"]
+pub struct EnterGuard<'a> {
+    _guard: context::SetCurrentGuard,
+    _handle_lifetime: PhantomData<&'a Handle>,
+}
+
 enum TryCurrentErrorKind {
     NoContext,
     ThreadLocalDestroyed,
@@ -25,6 +330,19 @@ pub struct TryCurrentError {
 }
 
 impl TryCurrentError {
+    /// Returns `true` if the call failed because there is currently no
+    /// runtime in the Mini context.
+    pub fn is_missing_context(&self) -> bool {
+        matches!(self.kind, TryCurrentErrorKind::NoContext)
+    }
+
+    /// Returns `true` if the call failed because the Mini context thread-local
+    /// had been destroyed. This can usually only happen if in the destructor of
+    /// other thread-locals.
+    pub fn is_thread_local_destroyed(&self) -> bool {
+        matches!(self.kind, TryCurrentErrorKind::ThreadLocalDestroyed)
+    }
+
     pub(crate) fn new_no_context() -> Self {
         Self {
             kind: TryCurrentErrorKind::NoContext,
@@ -58,3 +376,33 @@ impl fmt::Display for TryCurrentError {
 }
 
 impl error::Error for TryCurrentError {}
+
+/// Error returned by [`Handle::try_spawn`] when the runtime has too many
+/// pending spawns.
+///
+/// The future was not spawned, get it back with
+/// [`into_future`](Self::into_future).
+pub struct TrySpawnError<F> {
+    future: F,
+}
+
+impl<F> TrySpawnError<F> {
+    /// Returns the future that was not spawned.
+    pub fn into_future(self) -> F {
+        self.future
+    }
+}
+
+impl<F> fmt::Debug for TrySpawnError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TrySpawnError").finish_non_exhaustive()
+    }
+}
+
+impl<F> fmt::Display for TrySpawnError<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the runtime has too many pending spawns")
+    }
+}
+
+impl<F> error::Error for TrySpawnError<F> {}
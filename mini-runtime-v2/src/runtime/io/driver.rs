@@ -0,0 +1,237 @@
+use crate::runtime::io::{Ready, ScheduledIo};
+use crate::util::loom::sync::{Arc, Mutex};
+use mio::event::Source;
+use mio::{Events, Interest, Poll, Registry, Token, Waker};
+use std::collections::HashMap;
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// Token used by the `mio::Waker` that unparks the driver.
+const TOKEN_WAKEUP: Token = Token(0);
+
+/// Token the signal self-pipe is registered with.
+#[cfg(unix)]
+const TOKEN_SIGNAL: Token = Token(1);
+
+/// First token handed out to I/O resources.
+const TOKEN_FIRST: usize = 2;
+
+/// I/O driver, backed by `mio`.
+pub(crate) struct Driver {
+    /// Reuse the `mio::Events` value across calls to poll.
+    events: Events,
+
+    /// The system event queue.
+    poll: Poll,
+
+    /// Incremented on every turn, stamps the readiness set by the turn.
+    tick: u16,
+
+    /// Readiness of the resources reported by the current turn, coalesced
+    /// per token. Reused across turns.
+    pending: HashMap<Token, Ready>,
+
+    /// Resources to wake at the end of the current turn. Reused across turns.
+    dispatch: Vec<(Arc<ScheduledIo>, Ready)>,
+
+    /// True when an event with the signal token is received
+    #[cfg(unix)]
+    signal_ready: bool,
+}
+
+/// A reference to an I/O driver.
+pub(crate) struct Handle {
+    /// Registers I/O resources.
+    registry: Registry,
+
+    /// Tracks all registrations by token.
+    registrations: Mutex<Registrations>,
+
+    /// Used to wake up the reactor from a call to `turn`.
+    waker: Waker,
+}
+
+struct Registrations {
+    /// Next token to hand out, `0` and `1` are reserved for the waker and
+    /// the signal self-pipe.
+    next_token: usize,
+
+    /// The readiness of every registered resource.
+    io: HashMap<Token, Arc<ScheduledIo>>,
+}
+
+impl Driver {
+    /// Creates a new event loop, returns any error that happened during the
+    /// creation.
+    pub(crate) fn new(nevents: usize) -> io::Result<(Driver, Handle)> {
+        let poll = Poll::new()?;
+        let waker = Waker::new(poll.registry(), TOKEN_WAKEUP)?;
+        let registry = poll.registry().try_clone()?;
+
+        let driver = Driver {
+            events: Events::with_capacity(nevents),
+            poll,
+            tick: 0,
+            pending: HashMap::new(),
+            dispatch: Vec::new(),
+            #[cfg(unix)]
+            signal_ready: false,
+        };
+
+        let handle = Handle {
+            registry,
+            registrations: Mutex::new(Registrations {
+                next_token: TOKEN_FIRST,
+                io: HashMap::new(),
+            }),
+            waker,
+        };
+
+        Ok((driver, handle))
+    }
+
+    pub(crate) fn park(&mut self, handle: &Handle) {
+        self.turn(handle, None);
+    }
+
+    pub(crate) fn park_timeout(&mut self, handle: &Handle, duration: Duration) {
+        self.turn(handle, Some(duration));
+    }
+
+    /// Blocks in `mio::Poll` until an event arrives or `max_wait` elapses, then
+    /// dispatches the readiness to the registered resources.
+    fn turn(&mut self, handle: &Handle, max_wait: Option<Duration>) {
+        self.tick = self.tick.wrapping_add(1);
+
+        match self.poll.poll(&mut self.events, max_wait) {
+            Ok(()) => {}
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => panic!("unexpected error when polling the I/O driver: {e:?}"),
+        }
+
+        for event in self.events.iter() {
+            let token = event.token();
+
+            if token == TOKEN_WAKEUP {
+                // Nothing to do, the event is used to unblock the I/O driver
+                continue;
+            }
+
+            #[cfg(unix)]
+            if token == TOKEN_SIGNAL {
+                self.signal_ready = true;
+                continue;
+            }
+
+            // A resource may be reported by several events, e.g. kqueue has
+            // one per direction.
+            let ready = self.pending.entry(token).or_insert(Ready::EMPTY);
+            *ready = *ready | Ready::from_mio(event);
+        }
+
+        if self.pending.is_empty() {
+            return;
+        }
+
+        {
+            let registrations = handle.registrations.lock().unwrap();
+            for (token, ready) in self.pending.drain() {
+                // The resource may have been deregistered after the event was
+                // received.
+                if let Some(io) = registrations.io.get(&token) {
+                    self.dispatch.push((io.clone(), ready));
+                }
+            }
+        }
+
+        // Wake outside of the lock, a waker may register a new resource.
+        for (io, ready) in self.dispatch.drain(..) {
+            io.set_readiness(self.tick, ready);
+        }
+    }
+}
+
+#[cfg(unix)]
+impl Driver {
+    /// Returns whether the signal self-pipe became readable during the last
+    /// turn, resetting the flag.
+    pub(crate) fn consume_signal_ready(&mut self) -> bool {
+        let ret = self.signal_ready;
+        self.signal_ready = false;
+        ret
+    }
+}
+
+impl fmt::Debug for Driver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("io::Driver")
+    }
+}
+
+impl Handle {
+    /// Forces a reactor blocked in a call to `turn` to wakeup, or otherwise
+    /// makes the next call to `turn` return immediately.
+    pub(crate) fn unpark(&self) {
+        self.waker.wake().expect("failed to wake I/O driver");
+    }
+
+    /// Registers an I/O resource with the reactor for the given `interest`.
+    pub(super) fn add_source(
+        &self,
+        source: &mut impl Source,
+        interest: Interest,
+    ) -> io::Result<(Token, Arc<ScheduledIo>)> {
+        let scheduled_io = Arc::new(ScheduledIo::new());
+
+        let token = {
+            let mut registrations = self.registrations.lock().unwrap();
+            let token = Token(registrations.next_token);
+            registrations.next_token += 1;
+            registrations.io.insert(token, scheduled_io.clone());
+            token
+        };
+
+        if let Err(e) = self.registry.register(source, token, interest) {
+            self.registrations.lock().unwrap().io.remove(&token);
+            return Err(e);
+        }
+
+        Ok((token, scheduled_io))
+    }
+
+    /// Deregisters an I/O resource from the reactor.
+    pub(super) fn deregister_source(
+        &self,
+        token: Token,
+        source: &mut impl Source,
+    ) -> io::Result<()> {
+        self.registrations.lock().unwrap().io.remove(&token);
+        self.registry.deregister(source)
+    }
+
+    /// Forgets a registration whose resource was closed without being
+    /// deregistered first. The OS removes closed resources from the poll set
+    /// on its own.
+    pub(super) fn release(&self, token: Token) {
+        self.registrations.lock().unwrap().io.remove(&token);
+    }
+}
+
+#[cfg(unix)]
+impl Handle {
+    /// Registers the read end of the signal self-pipe with the reactor.
+    pub(crate) fn register_signal_receiver(
+        &self,
+        receiver: &mut mio::net::UnixStream,
+    ) -> io::Result<()> {
+        self.registry
+            .register(receiver, TOKEN_SIGNAL, Interest::READABLE)
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("io::Handle")
+    }
+}
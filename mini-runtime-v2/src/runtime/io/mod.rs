@@ -0,0 +1,25 @@
+//! The I/O driver, also known as the reactor.
+//!
+//! Every async I/O resource (e.g. a `TcpStream`) is registered with `mio`
+//! and gets a [`ScheduledIo`] that tracks its readiness. When the scheduler
+//! has nothing to run, it parks on the driver, which blocks in `mio::Poll`
+//! until an OS event arrives, records the new readiness and wakes the tasks
+//! waiting on it.
+//!
+//! The events of a turn are coalesced per resource: a resource reported
+//! both readable and writable, possibly by two events, is woken once with
+//! the union of its readiness, and only the reader or writer waiting on a
+//! ready direction is woken. Each turn has a tick, stored with the readiness
+//! it sets, so that a task clearing stale readiness after `WouldBlock` does
+//! not clear the readiness of a newer event.
+
+mod driver;
+pub(crate) use driver::{Driver, Handle};
+
+mod registration;
+pub(crate) use registration::Registration;
+
+mod scheduled_io;
+pub(crate) use scheduled_io::ScheduledIo;
+
+pub(crate) use scheduled_io::{Direction, Ready, ReadyEvent};
@@ -0,0 +1,115 @@
+use crate::future::poll_fn;
+use crate::runtime::io::{Direction, ReadyEvent, ScheduledIo};
+use crate::runtime::scheduler;
+use crate::util::loom::sync::Arc;
+use mio::event::Source;
+use mio::{Interest, Token};
+use std::io;
+use std::task::{Context, Poll};
+
+/// Associates an I/O resource with the reactor instance that drives it.
+///
+/// A registration represents an I/O resource registered with a Reactor such
+/// that it will receive task notifications on readiness. This is the lowest
+/// level API for integrating with a reactor.
+///
+/// The association between an I/O resource is made by calling
+/// [`new_with_interest_and_handle`]. Once the association is established, it
+/// remains established until the registration instance is dropped.
+///
+/// [`new_with_interest_and_handle`]: method@Self::new_with_interest_and_handle
+pub(crate) struct Registration {
+    /// Handle to the associated runtime.
+    handle: scheduler::Handle,
+
+    /// Token the resource was registered with.
+    token: Token,
+
+    /// Reference to state stored by the driver.
+    shared: Arc<ScheduledIo>,
+}
+
+impl Registration {
+    /// Registers the I/O resource with the reactor of the provided runtime
+    /// handle, for a specific `Interest`.
+    ///
+    /// # Return
+    ///
+    /// - `Ok` if the registration happened successfully
+    /// - `Err` if an error was encountered during registration
+    #[track_caller]
+    pub(crate) fn new_with_interest_and_handle(
+        io: &mut impl Source,
+        interest: Interest,
+        handle: scheduler::Handle,
+    ) -> io::Result<Registration> {
+        let (token, shared) = handle.driver().io().add_source(io, interest)?;
+
+        Ok(Registration {
+            handle,
+            token,
+            shared,
+        })
+    }
+
+    /// Deregisters the I/O resource from the reactor it is associated with.
+    ///
+    /// This function must be called before the I/O resource associated with
+    /// the registration is dropped.
+    pub(crate) fn deregister(&mut self, io: &mut impl Source) -> io::Result<()> {
+        self.handle.driver().io().deregister_source(self.token, io)
+    }
+
+    pub(crate) fn clear_readiness(&self, event: ReadyEvent) {
+        self.shared.clear_readiness(event);
+    }
+
+    /// Polls for events on the I/O resource's `direction` readiness stream.
+    pub(crate) fn poll_ready(
+        &self,
+        cx: &mut Context<'_>,
+        direction: Direction,
+    ) -> Poll<io::Result<ReadyEvent>> {
+        self.shared.poll_readiness(cx, direction).map(Ok)
+    }
+
+    pub(crate) fn poll_write_ready(&self, cx: &mut Context<'_>) -> Poll<io::Result<ReadyEvent>> {
+        self.poll_ready(cx, Direction::Write)
+    }
+
+    /// Waits for `direction` readiness, then runs `f`. If `f` fails with
+    /// `WouldBlock`, the readiness was stale: it is cleared and the task waits
+    /// for the next event.
+    pub(crate) fn poll_io<R>(
+        &self,
+        cx: &mut Context<'_>,
+        direction: Direction,
+        mut f: impl FnMut() -> io::Result<R>,
+    ) -> Poll<io::Result<R>> {
+        loop {
+            let ev = ready!(self.poll_ready(cx, direction))?;
+
+            match f() {
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    self.clear_readiness(ev);
+                }
+                x => return Poll::Ready(x),
+            }
+        }
+    }
+
+    /// Async version of [`poll_io`](Self::poll_io).
+    pub(crate) async fn async_io<R>(
+        &self,
+        direction: Direction,
+        mut f: impl FnMut() -> io::Result<R>,
+    ) -> io::Result<R> {
+        poll_fn(|cx| self.poll_io(cx, direction, &mut f)).await
+    }
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        self.handle.driver().io().release(self.token);
+    }
+}
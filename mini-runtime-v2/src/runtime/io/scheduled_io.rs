@@ -0,0 +1,212 @@
+use crate::util::loom::sync::Mutex;
+use crate::util::loom::sync::atomic::AtomicUsize;
+use crate::util::loom::sync::atomic::Ordering::{AcqRel, Acquire};
+use std::task::{Context, Poll, Waker};
+
+/// The readiness word packs the readiness bits with the driver tick of the
+/// last event: `| tick (16 bits) | unused | readiness (4 bits) |`.
+const READINESS_MASK: usize = 0b1111;
+const TICK_SHIFT: u32 = 16;
+const TICK_MASK: usize = 0xffff << TICK_SHIFT;
+
+/// Readiness of an I/O resource, as a set of bits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Ready(usize);
+
+impl Ready {
+    pub(crate) const EMPTY: Ready = Ready(0);
+    pub(crate) const READABLE: Ready = Ready(0b0001);
+    pub(crate) const WRITABLE: Ready = Ready(0b0010);
+    pub(crate) const READ_CLOSED: Ready = Ready(0b0100);
+    pub(crate) const WRITE_CLOSED: Ready = Ready(0b1000);
+
+    /// Converts a `mio` event into a readiness set.
+    pub(crate) fn from_mio(event: &mio::event::Event) -> Ready {
+        let mut ready = Ready::EMPTY;
+
+        if event.is_readable() {
+            ready = ready | Ready::READABLE;
+        }
+        if event.is_writable() {
+            ready = ready | Ready::WRITABLE;
+        }
+        if event.is_read_closed() {
+            ready = ready | Ready::READ_CLOSED;
+        }
+        if event.is_write_closed() {
+            ready = ready | Ready::WRITE_CLOSED;
+        }
+        // Errors are reported by the next read or write on the resource, so
+        // both directions are woken up.
+        if event.is_error() {
+            ready = ready | Ready::READ_CLOSED | Ready::WRITE_CLOSED;
+        }
+
+        ready
+    }
+
+    pub(crate) fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub(crate) fn intersection(self, other: Ready) -> Ready {
+        Ready(self.0 & other.0)
+    }
+}
+
+/// Readiness observed by a task, along with the driver tick that set it.
+///
+/// The tick tells apart the readiness a task acted upon from the readiness
+/// set by a later event, see `ScheduledIo::clear_readiness`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct ReadyEvent {
+    pub(crate) ready: Ready,
+    tick: u16,
+}
+
+impl std::ops::BitOr for Ready {
+    type Output = Ready;
+
+    fn bitor(self, other: Ready) -> Ready {
+        Ready(self.0 | other.0)
+    }
+}
+
+/// The direction a task is waiting on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Direction {
+    Read,
+    Write,
+}
+
+impl Direction {
+    /// The readiness bits that unblock a task waiting on this direction.
+    pub(crate) fn mask(self) -> Ready {
+        match self {
+            Direction::Read => Ready::READABLE | Ready::READ_CLOSED,
+            Direction::Write => Ready::WRITABLE | Ready::WRITE_CLOSED,
+        }
+    }
+}
+
+/// Stores the readiness of a registered I/O resource and the wakers of the
+/// tasks waiting for it.
+pub(crate) struct ScheduledIo {
+    /// Readiness bits and tick of the last event, see `READINESS_MASK`.
+    readiness: AtomicUsize,
+    waiters: Mutex<Waiters>,
+}
+
+#[derive(Default)]
+struct Waiters {
+    /// Waker used for `AsyncRead`.
+    reader: Option<Waker>,
+
+    /// Waker used for `AsyncWrite`.
+    writer: Option<Waker>,
+}
+
+impl ScheduledIo {
+    pub(crate) fn new() -> ScheduledIo {
+        ScheduledIo {
+            readiness: AtomicUsize::new(0),
+            waiters: Mutex::new(Waiters::default()),
+        }
+    }
+
+    /// Adds `ready` to the readiness of the resource, stamped with the
+    /// driver `tick`, and wakes the tasks waiting on it. Called by the driver
+    /// once per turn with all the events of the resource.
+    pub(crate) fn set_readiness(&self, tick: u16, ready: Ready) {
+        let _ = self.readiness.fetch_update(AcqRel, Acquire, |current| {
+            let readiness = (current & READINESS_MASK) | ready.0;
+            Some(((tick as usize) << TICK_SHIFT) | readiness)
+        });
+        self.wake(ready);
+    }
+
+    /// Wakes the tasks interested in `ready`.
+    pub(super) fn wake(&self, ready: Ready) {
+        let mut waiters = self.waiters.lock().unwrap();
+
+        let reader = if !ready.intersection(Direction::Read.mask()).is_empty() {
+            waiters.reader.take()
+        } else {
+            None
+        };
+
+        let writer = if !ready.intersection(Direction::Write.mask()).is_empty() {
+            waiters.writer.take()
+        } else {
+            None
+        };
+
+        drop(waiters);
+
+        reader.into_iter().chain(writer).for_each(Waker::wake);
+    }
+
+    /// Polls for readiness in the given direction. When the resource is not
+    /// ready, the waker is stored and notified by the next matching event.
+    pub(crate) fn poll_readiness(
+        &self,
+        cx: &mut Context<'_>,
+        direction: Direction,
+    ) -> Poll<ReadyEvent> {
+        let event = self.ready_event(direction);
+        if !event.ready.is_empty() {
+            return Poll::Ready(event);
+        }
+
+        let mut waiters = self.waiters.lock().unwrap();
+        let slot = match direction {
+            Direction::Read => &mut waiters.reader,
+            Direction::Write => &mut waiters.writer,
+        };
+
+        match slot {
+            Some(existing) if existing.will_wake(cx.waker()) => {}
+            _ => *slot = Some(cx.waker().clone()),
+        }
+
+        // Check again while holding the lock: the driver may have set the
+        // readiness before the waker was stored.
+        let event = self.ready_event(direction);
+        if event.ready.is_empty() {
+            Poll::Pending
+        } else {
+            Poll::Ready(event)
+        }
+    }
+
+    /// Clears the readiness of `event` after an operation returned
+    /// `WouldBlock`.
+    ///
+    /// If the driver received a new event since `event` was observed, the
+    /// readiness is kept: the operation may have raced with it, clearing the
+    /// readiness would lose the event and the task would wait forever.
+    ///
+    /// The closed bits are never cleared, a closed resource stays closed.
+    pub(crate) fn clear_readiness(&self, event: ReadyEvent) {
+        let mask = event.ready.intersection(Ready::READABLE | Ready::WRITABLE);
+
+        let _ = self.readiness.fetch_update(AcqRel, Acquire, |current| {
+            if tick(current) != event.tick {
+                return None;
+            }
+            Some(current & !mask.0)
+        });
+    }
+
+    fn ready_event(&self, direction: Direction) -> ReadyEvent {
+        let current = self.readiness.load(Acquire);
+        ReadyEvent {
+            ready: Ready(current & READINESS_MASK).intersection(direction.mask()),
+            tick: tick(current),
+        }
+    }
+}
+
+fn tick(readiness: usize) -> u16 {
+    ((readiness & TICK_MASK) >> TICK_SHIFT) as u16
+}
@@ -0,0 +1,255 @@
+mod histogram;
+pub(crate) use histogram::Histogram;
+pub use histogram::PollTimeHistogram;
+
+mod worker;
+pub(crate) use worker::WorkerMetrics;
+
+use crate::runtime::{Handle, ThreadId};
+use crate::util::loom::sync::atomic::Ordering::Relaxed;
+
+/// Handle to the runtime's metrics.
+///
+/// This handle is internally reference-counted and can be freely cloned. A
+/// `RuntimeMetrics` handle is obtained using the [`Runtime::metrics`] method.
+///
+/// [`Runtime::metrics`]: crate::runtime::Runtime::metrics()
+#[derive(Clone, Debug)]
+pub struct RuntimeMetrics {
+    handle: Handle,
+}
+
+impl RuntimeMetrics {
+    pub(crate) fn new(handle: Handle) -> Self {
+        Self { handle }
+    }
+
+    /// Returns the number of worker threads used by the runtime.
+    ///
+    /// The tasks of a `current_thread` runtime all run on the thread calling
+    /// `block_on`, so the return value is always `1` for this flavor.
+    ///
+    /// ```
+    /// use mini_runtime_v2::runtime;
+    ///
+    /// let rt = runtime::Builder::new_current_thread().build().unwrap();
+    /// assert_eq!(rt.metrics().num_workers(), 1);
+    /// ```
+    pub fn num_workers(&self) -> usize {
+        self.handle.inner.num_workers()
+    }
+
+    /// Returns the number of tasks in the runtime's injection queue.
+    ///
+    /// The injection queue holds the tasks spawned or woken from outside of
+    /// the thread driving the runtime, until the runtime polls them. A depth
+    /// that keeps growing means the runtime is overloaded: the producers are
+    /// faster than the runtime, see [`Builder::max_pending_spawns`].
+    ///
+    /// [`Builder::max_pending_spawns`]: crate::runtime::Builder::max_pending_spawns
+    pub fn injection_queue_depth(&self) -> usize {
+        self.handle.inner.injection_queue_depth()
+    }
+
+    /// Returns the number of blocking functions waiting for a thread of the
+    /// blocking pool.
+    ///
+    /// A function waits when all the threads, up to
+    /// [`Builder::max_blocking_threads`], are busy running others. A growing
+    /// depth means the pool is saturated: the functions queued when the
+    /// runtime shuts down never run.
+    ///
+    /// [`Builder::max_blocking_threads`]: crate::runtime::Builder::max_blocking_threads
+    pub fn blocking_queue_depth(&self) -> usize {
+        self.handle.inner.blocking_queue_depth()
+    }
+
+    /// Returns the number of timers fired by the time driver.
+    ///
+    /// Always `0` unless the time driver is enabled. The counter is
+    /// monotonically increasing, it is never reset.
+    pub fn timer_fired_count(&self) -> u64 {
+        self.handle.inner.timer_fired_count()
+    }
+
+    /// Returns the number of times the time driver fired timers.
+    ///
+    /// The driver fires all the timers whose deadline elapsed at once: the
+    /// fired count divided by the tick count is the number of timers fired
+    /// per wakeup. Timers with close deadlines fire by larger batches with a
+    /// coarser [`Builder::timer_resolution`].
+    ///
+    /// ```
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    /// let metrics = rt.metrics();
+    /// let per_tick = metrics.timer_fired_count() as f64 / metrics.timer_tick_count() as f64;
+    /// println!("{per_tick:.1} timers fired per tick");
+    /// ```
+    ///
+    /// Always `0` unless the time driver is enabled. The counter is
+    /// monotonically increasing, it is never reset.
+    ///
+    /// [`Builder::timer_resolution`]: crate::runtime::Builder::timer_resolution
+    pub fn timer_tick_count(&self) -> u64 {
+        self.handle.inner.timer_tick_count()
+    }
+
+    /// Returns the [`ThreadId`] of the thread driving the worker `worker`.
+    ///
+    /// The worker of a `current_thread` runtime runs on the thread calling
+    /// `block_on`: the ID is the one of the last thread that did, or `None`
+    /// if no thread did yet. The same ID tags the `worker` tracing span.
+    ///
+    /// ```
+    /// use mini_runtime_v2::runtime;
+    ///
+    /// let rt = runtime::Builder::new_current_thread().build().unwrap();
+    /// assert!(rt.metrics().worker_thread_id(0).is_none());
+    ///
+    /// rt.block_on(async {});
+    /// assert!(rt.metrics().worker_thread_id(0).is_some());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is not lower than [`num_workers`](Self::num_workers).
+    #[track_caller]
+    pub fn worker_thread_id(&self, worker: usize) -> Option<ThreadId> {
+        self.handle.inner.worker_metrics(worker).thread_id()
+    }
+
+    /// Returns the number of times the worker `worker` parked.
+    ///
+    /// A worker parks when it has no task to run: it blocks in the driver
+    /// until an I/O event, a timer or a wakeup from another thread. Parking
+    /// and being unparked cost a system call each, a worker parking once per
+    /// task wastes a significant part of its time there, see
+    /// [`Builder::max_idle_spins`].
+    ///
+    /// The counter is monotonically increasing, it is never reset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is not lower than [`num_workers`](Self::num_workers).
+    ///
+    /// [`Builder::max_idle_spins`]: crate::runtime::Builder::max_idle_spins
+    #[track_caller]
+    pub fn worker_park_count(&self, worker: usize) -> u64 {
+        let metrics = self.handle.inner.worker_metrics(worker);
+        metrics.park_count.load(Relaxed)
+    }
+
+    /// Returns the number of times the worker `worker` resumed from a park.
+    ///
+    /// Lower than [`worker_park_count`](Self::worker_park_count) by one while
+    /// the worker is parked.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is not lower than [`num_workers`](Self::num_workers).
+    #[track_caller]
+    pub fn worker_unpark_count(&self, worker: usize) -> u64 {
+        let metrics = self.handle.inner.worker_metrics(worker);
+        metrics.unpark_count.load(Relaxed)
+    }
+
+    /// Returns the number of times the worker `worker` spun without finding
+    /// work before parking.
+    ///
+    /// Always `0` unless the runtime was built with
+    /// [`Builder::max_idle_spins`]. Compared with the park count, it tells
+    /// whether spinning pays off: a worker spinning `max_idle_spins` times
+    /// before each park burns CPU for nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is not lower than [`num_workers`](Self::num_workers).
+    ///
+    /// [`Builder::max_idle_spins`]: crate::runtime::Builder::max_idle_spins
+    #[track_caller]
+    pub fn worker_idle_spin_count(&self, worker: usize) -> u64 {
+        let metrics = self.handle.inner.worker_metrics(worker);
+        metrics.idle_spin_count.load(Relaxed)
+    }
+
+    /// Returns the number of times a task returned `Pending` without keeping
+    /// its waker, on the worker `worker`.
+    ///
+    /// Always `0` unless the runtime was built with
+    /// [`Builder::detect_lost_wakeups`]. Each one is a task that is never
+    /// polled again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is not lower than [`num_workers`](Self::num_workers).
+    ///
+    /// [`Builder::detect_lost_wakeups`]: crate::runtime::Builder::detect_lost_wakeups
+    #[track_caller]
+    pub fn worker_lost_wakeup_count(&self, worker: usize) -> u64 {
+        let metrics = self.handle.inner.worker_metrics(worker);
+        metrics.lost_wakeup_count.load(Relaxed)
+    }
+
+    /// Returns `true` if the runtime is measuring the poll time of its tasks,
+    /// see [`Builder::enable_metrics_poll_time_histogram`].
+    ///
+    /// [`Builder::enable_metrics_poll_time_histogram`]: crate::runtime::Builder::enable_metrics_poll_time_histogram
+    pub fn poll_time_histogram_enabled(&self) -> bool {
+        self.handle.inner.worker_metrics(0).measures_poll_time()
+    }
+
+    /// Returns the distribution of the task poll durations, aggregated over
+    /// all the workers.
+    ///
+    /// A poll should return quickly: no other task runs on the worker
+    /// meanwhile. Polls in the upper buckets reveal tasks blocking the
+    /// executor, e.g. by calling a blocking function instead of
+    /// [`spawn_blocking`].
+    ///
+    /// ```
+    /// # use mini_runtime_v2::runtime;
+    /// # use std::time::Duration;
+    /// let rt = runtime::Builder::new_current_thread()
+    ///     .enable_metrics_poll_time_histogram()
+    ///     .build()
+    ///     .unwrap();
+    ///
+    /// // ...
+    ///
+    /// let histogram = rt.metrics().poll_time_histogram();
+    /// println!("{} polls took 1ms or more", histogram.count_at_least(Duration::from_millis(1)));
+    /// ```
+    ///
+    /// The histogram is empty unless the runtime was built with
+    /// [`Builder::enable_metrics_poll_time_histogram`].
+    ///
+    /// [`spawn_blocking`]: crate::task::spawn_blocking
+    /// [`Builder::enable_metrics_poll_time_histogram`]: crate::runtime::Builder::enable_metrics_poll_time_histogram
+    pub fn poll_time_histogram(&self) -> PollTimeHistogram {
+        let mut histogram = PollTimeHistogram::new();
+        for worker in 0..self.num_workers() {
+            self.add_worker_histogram(&mut histogram, worker);
+        }
+        histogram
+    }
+
+    /// Returns the distribution of the durations of the task polls run by
+    /// the worker `worker`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is not lower than [`num_workers`](Self::num_workers).
+    #[track_caller]
+    pub fn worker_poll_time_histogram(&self, worker: usize) -> PollTimeHistogram {
+        let mut histogram = PollTimeHistogram::new();
+        self.add_worker_histogram(&mut histogram, worker);
+        histogram
+    }
+
+    #[track_caller]
+    fn add_worker_histogram(&self, histogram: &mut PollTimeHistogram, worker: usize) {
+        if let Some(worker) = &self.handle.inner.worker_metrics(worker).poll_time_histogram {
+            histogram.add(worker);
+        }
+    }
+}
@@ -0,0 +1,144 @@
+use crate::util::loom::sync::atomic::AtomicU64;
+use crate::util::loom::sync::atomic::Ordering::Relaxed;
+use std::fmt;
+use std::ops::Range;
+use std::time::Duration;
+
+/// Number of buckets of a poll time histogram.
+const NUM_BUCKETS: usize = 16;
+
+/// Upper bound of the first bucket, each following bucket is twice as wide
+/// as the previous one.
+const FIRST_BUCKET: Duration = Duration::from_micros(1);
+
+/// Counts durations in fixed log-scale buckets, updated by the worker.
+pub(crate) struct Histogram {
+    buckets: [AtomicU64; NUM_BUCKETS],
+}
+
+impl Histogram {
+    pub(crate) fn new() -> Histogram {
+        Histogram {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+        }
+    }
+
+    pub(crate) fn record(&self, duration: Duration) {
+        self.buckets[bucket_index(duration)].fetch_add(1, Relaxed);
+    }
+
+    fn snapshot(&self) -> [u64; NUM_BUCKETS] {
+        std::array::from_fn(|i| self.buckets[i].load(Relaxed))
+    }
+}
+
+/// Bucket `0` holds the durations below 1µs, bucket `i` the ones in
+/// `[2^(i-1)µs, 2^iµs)`, the last bucket everything above.
+fn bucket_index(duration: Duration) -> usize {
+    let micros = duration.as_micros();
+    if micros == 0 {
+        return 0;
+    }
+
+    // Number of bits needed to represent `micros`.
+    let bits = (u128::BITS - micros.leading_zeros()) as usize;
+    bits.min(NUM_BUCKETS - 1)
+}
+
+fn bucket_range(i: usize) -> Range<Duration> {
+    let start = match i {
+        0 => Duration::ZERO,
+        _ => FIRST_BUCKET * (1 << (i - 1)),
+    };
+    let end = match i {
+        _ if i == NUM_BUCKETS - 1 => Duration::MAX,
+        _ => FIRST_BUCKET * (1 << i),
+    };
+    start..end
+}
+
+/// A snapshot of the distribution of the task poll durations.
+///
+/// The durations are counted in fixed log-scale buckets: the first bucket
+/// holds the polls shorter than 1µs, each following bucket is twice as wide
+/// as the previous one, and the last bucket holds the polls longer than
+/// ~16ms. A task whose polls land in the last buckets blocks the executor:
+/// no other task runs while it is polled.
+///
+/// The `Display` implementation prints one line per non-empty bucket.
+#[derive(Clone, Debug)]
+pub struct PollTimeHistogram {
+    counts: [u64; NUM_BUCKETS],
+}
+
+impl PollTimeHistogram {
+    pub(crate) fn new() -> PollTimeHistogram {
+        PollTimeHistogram {
+            counts: [0; NUM_BUCKETS],
+        }
+    }
+
+    /// Adds the counts of `histogram` to the snapshot.
+    pub(crate) fn add(&mut self, histogram: &Histogram) {
+        for (count, n) in self.counts.iter_mut().zip(histogram.snapshot()) {
+            *count += n;
+        }
+    }
+
+    /// Returns the number of buckets.
+    pub fn num_buckets(&self) -> usize {
+        NUM_BUCKETS
+    }
+
+    /// Returns the range of poll durations counted by bucket `bucket`.
+    ///
+    /// The range of the last bucket ends at `Duration::MAX`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket` is not lower than [`num_buckets`](Self::num_buckets).
+    #[track_caller]
+    pub fn bucket_range(&self, bucket: usize) -> Range<Duration> {
+        assert!(bucket < NUM_BUCKETS, "bucket index out of range");
+        bucket_range(bucket)
+    }
+
+    /// Returns the number of polls whose duration falls in bucket `bucket`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bucket` is not lower than [`num_buckets`](Self::num_buckets).
+    #[track_caller]
+    pub fn bucket_count(&self, bucket: usize) -> u64 {
+        self.counts[bucket]
+    }
+
+    /// Returns the total number of polls recorded.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Returns the number of polls that lasted at least `duration`, rounded
+    /// to the bucket `duration` falls in.
+    pub fn count_at_least(&self, duration: Duration) -> u64 {
+        self.counts[bucket_index(duration)..].iter().sum()
+    }
+}
+
+impl fmt::Display for PollTimeHistogram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, count) in self.counts.iter().enumerate() {
+            if *count == 0 {
+                continue;
+            }
+
+            let range = bucket_range(i);
+            if range.end == Duration::MAX {
+                writeln!(f, "{:>10?} ..         : {count}", range.start)?;
+            } else {
+                writeln!(f, "{:>10?} .. {:<8?}: {count}", range.start, range.end)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,78 @@
+use crate::runtime::ThreadId;
+use crate::runtime::metrics::Histogram;
+use crate::util::loom::sync::atomic::AtomicU64;
+use crate::util::loom::sync::atomic::Ordering::Relaxed;
+use std::time::Duration;
+
+/// Metrics recorded by a worker thread, shared with the `RuntimeMetrics`
+/// handles.
+pub(crate) struct WorkerMetrics {
+    /// `ThreadId` of the thread driving the worker, `0` until one does
+    pub(crate) thread_id: AtomicU64,
+
+    /// Number of times the worker parked, blocking in the driver
+    pub(crate) park_count: AtomicU64,
+
+    /// Number of times the worker resumed from a park
+    pub(crate) unpark_count: AtomicU64,
+
+    /// Number of idle spins that found no work, see
+    /// `Builder::max_idle_spins`
+    pub(crate) idle_spin_count: AtomicU64,
+
+    /// Number of polls that returned `Pending` without the task keeping its
+    /// waker, see `Builder::detect_lost_wakeups`
+    pub(crate) lost_wakeup_count: AtomicU64,
+
+    /// Durations of the task polls, `None` unless enabled with
+    /// `Builder::enable_metrics_poll_time_histogram`.
+    pub(crate) poll_time_histogram: Option<Histogram>,
+}
+
+impl WorkerMetrics {
+    pub(crate) fn new(enable_poll_time_histogram: bool) -> WorkerMetrics {
+        WorkerMetrics {
+            thread_id: AtomicU64::new(0),
+            park_count: AtomicU64::new(0),
+            unpark_count: AtomicU64::new(0),
+            idle_spin_count: AtomicU64::new(0),
+            lost_wakeup_count: AtomicU64::new(0),
+            poll_time_histogram: enable_poll_time_histogram.then(Histogram::new),
+        }
+    }
+
+    pub(crate) fn set_thread_id(&self, thread_id: ThreadId) {
+        self.thread_id.store(thread_id.as_u64().get(), Relaxed);
+    }
+
+    pub(crate) fn thread_id(&self) -> Option<ThreadId> {
+        ThreadId::from_u64(self.thread_id.load(Relaxed))
+    }
+
+    pub(crate) fn incr_park_count(&self) {
+        self.park_count.fetch_add(1, Relaxed);
+    }
+
+    pub(crate) fn incr_unpark_count(&self) {
+        self.unpark_count.fetch_add(1, Relaxed);
+    }
+
+    pub(crate) fn incr_idle_spin_count(&self) {
+        self.idle_spin_count.fetch_add(1, Relaxed);
+    }
+
+    pub(crate) fn incr_lost_wakeup_count(&self) {
+        self.lost_wakeup_count.fetch_add(1, Relaxed);
+    }
+
+    /// Returns whether the poll durations must be measured.
+    pub(crate) fn measures_poll_time(&self) -> bool {
+        self.poll_time_histogram.is_some()
+    }
+
+    pub(crate) fn record_poll_time(&self, duration: Duration) {
+        if let Some(histogram) = &self.poll_time_histogram {
+            histogram.record(duration);
+        }
+    }
+}
@@ -1,13 +1,47 @@
+pub(crate) mod blocking;
 pub(crate) mod context;
 
-mod scheduler;
+pub(crate) mod coop;
+
+mod config;
+pub(crate) use config::Config;
+
+mod driver;
+#[cfg(feature = "task_dump")]
+pub mod dump;
+pub(crate) mod io;
+mod park;
+
+pub(crate) mod scheduler;
+
+#[cfg(unix)]
+pub(crate) mod signal;
 pub(crate) mod task;
+pub(crate) mod time;
+
+mod rng;
+pub use rng::{Rng, rng};
+
+mod thread_id;
+pub use thread_id::ThreadId;
 
 mod handle;
-pub use handle::{Handle, TryCurrentError};
+pub use handle::{EnterGuard, Handle, TryCurrentError, TrySpawnError};
+
+mod task_hooks;
+pub use task_hooks::TaskMeta;
+pub(crate) use task_hooks::{TaskCallback, TaskHooks};
+
+mod metrics;
+pub(crate) use metrics::WorkerMetrics;
+pub use metrics::{PollTimeHistogram, RuntimeMetrics};
 
 mod builder;
 pub use self::builder::Builder;
 
+#[allow(clippy::module_inception)]
 mod runtime;
-pub use runtime::Runtime;
+pub use runtime::{Runtime, RuntimeFlavor};
+
+#[cfg(all(test, loom))]
+mod tests;
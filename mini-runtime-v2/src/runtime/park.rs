@@ -0,0 +1,158 @@
+//! Parks the thread driving the runtime when there is no work to do.
+//!
+//! The implementation follows the classic "park token" protocol used by
+//! `std::thread::park`: an unpark that arrives *before* the thread parks is
+//! not lost, it is remembered in `state` and the next call to `park` returns
+//! immediately.
+
+use crate::util::loom::sync::atomic::AtomicUsize;
+use crate::util::loom::sync::atomic::Ordering::SeqCst;
+use crate::util::loom::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// Blocks the current thread until it is unparked.
+#[derive(Debug)]
+pub(crate) struct ParkThread {
+    inner: Arc<Inner>,
+}
+
+/// Unblocks a thread that was blocked by `ParkThread`.
+#[derive(Clone, Debug)]
+pub(crate) struct UnparkThread {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    /// One of `EMPTY`, `PARKED` or `NOTIFIED`.
+    state: AtomicUsize,
+    mutex: Mutex<()>,
+    condvar: Condvar,
+}
+
+/// Nobody is parked and no notification is pending.
+const EMPTY: usize = 0;
+/// A thread is blocked on the condition variable.
+const PARKED: usize = 1;
+/// An unpark happened, the next `park` must return immediately.
+const NOTIFIED: usize = 2;
+
+impl ParkThread {
+    pub(crate) fn new() -> ParkThread {
+        ParkThread {
+            inner: Arc::new(Inner {
+                state: AtomicUsize::new(EMPTY),
+                mutex: Mutex::new(()),
+                condvar: Condvar::new(),
+            }),
+        }
+    }
+
+    /// Returns a handle that can be used to wake the parked thread.
+    pub(crate) fn unpark(&self) -> UnparkThread {
+        UnparkThread {
+            inner: self.inner.clone(),
+        }
+    }
+
+    /// Blocks the current thread until `unpark` is called.
+    pub(crate) fn park(&mut self) {
+        self.inner.park(None);
+    }
+
+    /// Blocks the current thread until `unpark` is called or `duration` elapses.
+    pub(crate) fn park_timeout(&mut self, duration: Duration) {
+        self.inner.park(Some(duration));
+    }
+}
+
+impl UnparkThread {
+    pub(crate) fn unpark(&self) {
+        self.inner.unpark();
+    }
+}
+
+impl Inner {
+    fn park(&self, timeout: Option<Duration>) {
+        // If we were previously notified then we consume this notification and
+        // return quickly.
+        if self
+            .state
+            .compare_exchange(NOTIFIED, EMPTY, SeqCst, SeqCst)
+            .is_ok()
+        {
+            return;
+        }
+
+        if timeout == Some(Duration::ZERO) {
+            return;
+        }
+
+        // Otherwise we need to coordinate going to sleep.
+        let mut m = self.mutex.lock().unwrap();
+
+        match self.state.compare_exchange(EMPTY, PARKED, SeqCst, SeqCst) {
+            Ok(_) => {}
+            Err(NOTIFIED) => {
+                // We must read here, even though we know it will be `NOTIFIED`.
+                // This is because `unpark` may have been called again since we
+                // read `NOTIFIED` in the `compare_exchange` above.
+                let old = self.state.swap(EMPTY, SeqCst);
+                debug_assert_eq!(old, NOTIFIED, "park state changed unexpectedly");
+                return;
+            }
+            Err(actual) => panic!("inconsistent park state; actual = {actual}"),
+        }
+
+        match timeout {
+            None => loop {
+                m = self.condvar.wait(m).unwrap();
+
+                if self
+                    .state
+                    .compare_exchange(NOTIFIED, EMPTY, SeqCst, SeqCst)
+                    .is_ok()
+                {
+                    // got a notification
+                    return;
+                }
+
+                // spurious wakeup, go back to sleep
+            },
+            Some(timeout) => {
+                let (_m, _result) = self.condvar.wait_timeout(m, timeout).unwrap();
+
+                // Wake up on timeout or notification, either way the state
+                // goes back to `EMPTY`.
+                match self.state.swap(EMPTY, SeqCst) {
+                    NOTIFIED | PARKED => {}
+                    n => panic!("inconsistent park_timeout state: {n}"),
+                }
+            }
+        }
+    }
+
+    fn unpark(&self) {
+        // To ensure the unparked thread will observe any writes we made before
+        // this call, we must perform a release operation that `park` can
+        // synchronize with. To do that we must write `NOTIFIED` even if `state`
+        // is already `NOTIFIED`.
+        match self.state.swap(NOTIFIED, SeqCst) {
+            EMPTY => return,    // no one was waiting
+            NOTIFIED => return, // already unparked
+            PARKED => {}        // gotta go wake someone up
+            _ => panic!("inconsistent state in unpark"),
+        }
+
+        // There is a period between when the parked thread sets `state` to
+        // `PARKED` (or last checked `state` in the case of a spurious wakeup)
+        // and when it actually waits on `condvar`. If we were to notify during
+        // this period it would be ignored and then when the parked thread went
+        // to sleep it would never wake up. Fortunately, it has `mutex` locked
+        // at this stage so we can acquire `mutex` to wait until it is ready to
+        // receive the notification.
+        drop(self.mutex.lock());
+
+        self.condvar.notify_one();
+    }
+}
@@ -0,0 +1,71 @@
+use crate::runtime::context;
+use crate::util::error::CONTEXT_MISSING_ERROR;
+use std::marker::PhantomData;
+use std::ops::Range;
+
+/// Returns the random number generator of the current runtime thread.
+///
+/// Each thread running the runtime has its own fast, non-cryptographic
+/// generator, seeded from the runtime when the thread enters it. It is
+/// meant for scheduling-grade randomness, such as jittering retries,
+/// without pulling in the `rand` crate:
+///
+/// ```
+/// use mini_runtime_v2::runtime;
+/// # use mini_runtime_v2::time::{self, Duration};
+/// # let rt = runtime::Builder::new_current_thread().enable_time().start_paused(true).build().unwrap();
+/// # rt.block_on(async {
+/// # let backoff = Duration::from_secs(1);
+///
+/// let jitter = runtime::rng().gen_range(0..100);
+/// time::sleep(backoff + Duration::from_millis(jitter)).await;
+/// # });
+/// ```
+///
+/// The returned [`Rng`] is tied to the current thread: it cannot be sent to
+/// another thread.
+///
+/// # Panics
+///
+/// This function panics if called outside of a Mini runtime.
+#[track_caller]
+pub fn rng() -> Rng {
+    if !context::current_enter_context().is_entered() {
+        panic!("{}", CONTEXT_MISSING_ERROR);
+    }
+
+    Rng {
+        _not_send: PhantomData,
+    }
+}
+
+/// The random number generator of a runtime thread, see [`rng`].
+#[derive(Debug, Clone)]
+pub struct Rng {
+    /// The generator lives in a thread local.
+    _not_send: PhantomData<*const ()>,
+}
+
+impl Rng {
+    /// Returns a random number in `range`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `range` is empty.
+    #[track_caller]
+    pub fn gen_range(&self, range: Range<u64>) -> u64 {
+        assert!(!range.is_empty(), "cannot sample empty range");
+        range.start + context::with_rng(|rng| rng.fastrand_n_u64(range.end - range.start))
+    }
+
+    /// Shuffles `slice` in place, all permutations being equally likely.
+    pub fn shuffle<T>(&self, slice: &mut [T]) {
+        context::with_rng(|rng| {
+            // Fisher-Yates: swap each element with one at or before it.
+            for i in (1..slice.len()).rev() {
+                let j = rng.fastrand_n_u64(i as u64 + 1) as usize;
+                slice.swap(i, j);
+            }
+        });
+    }
+}
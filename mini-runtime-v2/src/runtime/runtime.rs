@@ -1,5 +1,7 @@
-use crate::runtime::Handle;
+use crate::runtime::blocking::BlockingPool;
 use crate::runtime::scheduler::CurrentThread;
+use crate::runtime::{EnterGuard, Handle, RuntimeMetrics};
+use std::time::Duration;
 
 /// The runtime scheduler is either a multi-thread or a current-thread executor.
 #[derive(Debug)]
@@ -8,26 +10,140 @@ pub(super) enum Scheduler {
     CurrentThread(CurrentThread),
 }
 
+/// The flavor of a `Runtime`, see [`Handle::runtime_flavor`].
+///
+/// Only the current-thread scheduler is implemented so far, the enum is
+/// non-exhaustive so that more flavors can be added.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RuntimeFlavor {
+    /// The flavor that executes all tasks on the current thread.
+    CurrentThread,
+    /// The flavor that executes tasks across multiple threads.
+    MultiThread,
+}
+
+/// The Mini runtime: a scheduler, the drivers and the blocking pool.
+///
+/// # Shutdown
+///
+/// Dropping the runtime cancels all its tasks: the futures that did not
+/// complete are dropped, whether they are waiting in a run queue or for a
+/// wakeup, and their `JoinHandle`s report a cancelled `JoinError`. The
+/// resources held by the tasks are released before `drop` returns. Tasks
+/// spawned from then on, e.g. by a destructor, are cancelled right away.
+///
+/// Blocking functions can't be interrupted: `drop` waits for the running
+/// ones to return. The queued ones, which no thread of the blocking pool
+/// started yet, are cancelled: they never run and their `JoinHandle`s report
+/// a cancelled `JoinError`. To bound the wait, see
+/// [`shutdown_timeout`](Runtime::shutdown_timeout).
 #[derive(Debug)]
 pub struct Runtime {
     /// Task scheduler
     scheduler: Scheduler,
     /// Handle to runtime, also contains driver handles
     handle: Handle,
+    /// Blocking pool handle, used to signal shutdown
+    blocking_pool: BlockingPool,
 }
 
 impl Runtime {
-    pub(super) fn from_parts(scheduler: Scheduler, handle: Handle) -> Runtime {
-        Runtime { scheduler, handle }
+    pub(super) fn from_parts(
+        scheduler: Scheduler,
+        handle: Handle,
+        blocking_pool: BlockingPool,
+    ) -> Runtime {
+        Runtime {
+            scheduler,
+            handle,
+            blocking_pool,
+        }
+    }
+
+    /// Returns a handle to the runtime's spawner.
+    ///
+    /// The returned handle can be used to spawn tasks that run on this
+    /// runtime, and can be cloned to allow moving the `Handle` to other
+    /// threads.
+    pub fn handle(&self) -> &Handle {
+        &self.handle
+    }
+
+    /// Returns a view that lets you get information about how the runtime
+    /// is performing.
+    pub fn metrics(&self) -> RuntimeMetrics {
+        self.handle.metrics()
+    }
+
+    /// Enters the runtime context, see [`Handle::enter`].
+    pub fn enter(&self) -> EnterGuard<'_> {
+        self.handle.enter()
+    }
+
+    /// Shuts down the runtime, waiting at most `duration` for the blocking
+    /// functions to return.
+    ///
+    /// The tasks are cancelled and the queued blocking functions are
+    /// discarded, as when the runtime is dropped. The threads of the
+    /// blocking pool still running a function at the deadline are detached:
+    /// they exit on their own once it returns, possibly after this function
+    /// returned.
+    ///
+    /// ```
+    /// use mini_runtime_v2::{runtime, task};
+    /// use std::time::Duration;
+    ///
+    /// let rt = runtime::Builder::new_current_thread().build().unwrap();
+    /// rt.block_on(async {
+    ///     task::spawn_blocking(|| std::thread::sleep(Duration::from_secs(10)));
+    /// });
+    ///
+    /// // Returns after about 10ms, not 10s.
+    /// rt.shutdown_timeout(Duration::from_millis(10));
+    /// ```
+    pub fn shutdown_timeout(mut self, duration: Duration) {
+        self.shutdown_scheduler();
+        self.blocking_pool.shutdown(Some(duration));
+    }
+
+    /// Shuts down the runtime without waiting for the blocking functions to
+    /// return.
+    ///
+    /// Equivalent to `shutdown_timeout(Duration::ZERO)`, e.g. to drop a
+    /// runtime from an asynchronous context, where blocking is not allowed.
+    pub fn shutdown_background(self) {
+        self.shutdown_timeout(Duration::ZERO);
     }
 
+    #[track_caller]
     pub fn block_on<F: Future>(&self, future: F) -> F::Output {
         self.block_on_inner(future)
     }
 
+    #[track_caller]
     fn block_on_inner<F: Future>(&self, future: F) -> F::Output {
         match &self.scheduler {
             Scheduler::CurrentThread(exec) => exec.block_on(&self.handle.inner, future),
         }
     }
+
+    fn shutdown_scheduler(&mut self) {
+        match &mut self.scheduler {
+            Scheduler::CurrentThread(current_thread) => {
+                current_thread.shutdown(&self.handle.inner);
+            }
+        }
+    }
+}
+
+impl Drop for Runtime {
+    fn drop(&mut self) {
+        // Does nothing if `shutdown_timeout` already shut the runtime down.
+        self.shutdown_scheduler();
+
+        // Blocking functions can't be interrupted, wait for the running ones
+        // to return so that no pool thread outlives the runtime.
+        self.blocking_pool.shutdown(None);
+    }
 }
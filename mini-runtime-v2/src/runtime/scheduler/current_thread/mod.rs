@@ -1,59 +1,294 @@
-use crate::runtime::context;
+use crate::runtime::blocking;
+use crate::runtime::driver::{self, Driver};
 use crate::runtime::scheduler::{self};
-use crate::runtime::task::{self, JoinHandle};
-use crate::util::RngSeedGenerator;
+use crate::runtime::task::{self, JoinHandle, Notified};
+use crate::runtime::{Config, TaskHooks, ThreadId, WorkerMetrics, context, coop};
+use crate::util::atomic_cell::AtomicCell;
+use crate::util::error::THREAD_LOCAL_DESTROYED_ERROR;
+use crate::util::loom::sync::atomic::AtomicBool;
+use crate::util::loom::sync::atomic::Ordering::{AcqRel, Acquire, Release};
+use crate::util::loom::sync::{Arc, Condvar, Mutex};
+use crate::util::{RngSeedGenerator, Wake, waker_ref};
+use std::cell::RefCell;
+use std::collections::VecDeque;
 use std::fmt;
 use std::future::Future;
 use std::pin::Pin;
-use std::sync::Arc;
-use std::thread::ThreadId;
+use std::task::Poll::Ready;
+use std::time::{Duration, Instant};
+
+/// Number of tasks polled before the scheduler checks the injection queue,
+/// so that tasks woken from other threads are not starved by local ones.
+const GLOBAL_QUEUE_INTERVAL: u32 = 31;
+
+/// Default number of tasks polled before the scheduler yields to the parker,
+/// giving the drivers and the `block_on` future a chance to make progress.
+pub(crate) const EVENT_INTERVAL: u32 = 61;
 
 /// Executes tasks on the current thread
-pub(crate) struct CurrentThread {}
+pub(crate) struct CurrentThread {
+    /// Core scheduler data is acquired by a thread entering `block_on`.
+    core: AtomicCell<Core>,
+
+    /// Notifier for waking up other threads to steal the core
+    notify: Notify,
+}
 
 /// Handle to the current thread scheduler
 pub(crate) struct Handle {
+    /// Scheduler state shared across threads
+    shared: Shared,
+
+    /// Resource driver handles
+    pub(crate) driver: driver::Handle,
+
+    /// Blocking pool spawner
+    pub(crate) blocking_spawner: blocking::Spawner,
+
+    /// Hooks called when tasks are spawned and terminated
+    pub(crate) task_hooks: TaskHooks,
+
+    /// Whether to warn about the tasks returning `Pending` without keeping
+    /// their waker
+    pub(crate) detect_lost_wakeups: bool,
+
+    /// Live tasks, cancelled on shutdown and listed by `Handle::dump`
+    pub(crate) owned: task::OwnedTasks,
+
+    /// Metrics recorded by the thread driving the scheduler
+    pub(crate) worker_metrics: WorkerMetrics,
+
     /// Current random number generator seed
     pub(crate) seed_generator: RngSeedGenerator,
 
-    #[allow(dead_code)]
     /// If this is a `LocalRuntime`, flags the owning thread ID.
     pub(crate) local_tid: Option<ThreadId>,
 }
 
+/// Data required for executing the scheduler. The struct is passed around to
+/// a function that will perform the scheduling work and acts as a capability
+/// token.
+struct Core {
+    /// Scheduler run queue
+    tasks: VecDeque<Notified>,
+
+    /// Current tick
+    tick: u32,
+
+    /// Number of tasks polled before checking the drivers, even if more are
+    /// ready: a task waking itself must not starve I/O and timers.
+    event_interval: u32,
+
+    /// Whether to report each round of the scheduling loop, see
+    /// `Builder::enable_scheduler_tracing`.
+    scheduler_tracing: bool,
+
+    /// Runtime driver
+    ///
+    /// The driver is removed before parking, so that the core can be stored
+    /// in the thread-local context while the driver dispatches I/O events.
+    driver: Option<Driver>,
+}
+
+/// Scheduler state shared between threads.
+struct Shared {
+    /// Remote run queue, used by tasks woken or spawned from outside of the
+    /// scheduler.
+    ///
+    /// `None` once the scheduler shut down.
+    inject: Mutex<Option<Inject>>,
+
+    /// Notified when a task is taken from the remote run queue, or when the
+    /// scheduler shuts down, to wake the threads waiting to spawn.
+    inject_not_full: Condvar,
+
+    /// Bound on the remote run queue for spawns, `None` if unbounded.
+    max_pending_spawns: Option<usize>,
+
+    /// Number of times the idle scheduler checks for new work before parking.
+    max_idle_spins: u32,
+
+    /// Indicates whether the blocked on thread was woken.
+    woken: AtomicBool,
+}
+
+/// Remote run queue.
+///
+/// The bound only applies to spawns: a wakeup is never refused, it would be
+/// lost. The queue may thus grow past the bound, spawns wait until it drains
+/// below.
+struct Inject {
+    tasks: VecDeque<Notified>,
+
+    /// Spawns admitted by the bound whose task is not pushed yet: the task is
+    /// created outside of the lock, as it runs the spawn hook.
+    reserved: usize,
+}
+
+/// Whether a remote spawn waits when the remote run queue is full.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Backpressure {
+    Wait,
+    Reject,
+}
+
+/// Thread-local context.
+///
+/// pub(crate) to store in `runtime::context`.
+pub(crate) struct Context {
+    /// Scheduler handle
+    handle: Arc<Handle>,
+
+    /// Scheduler core, enabling the holder of `Context` to execute the
+    /// scheduler.
+    core: RefCell<Option<Box<Core>>>,
+}
+
+/// Wakes threads waiting for the core to become available.
+struct Notify {
+    mutex: Mutex<()>,
+    condvar: Condvar,
+}
+
 impl CurrentThread {
     pub(crate) fn new(
-        seed_generator: RngSeedGenerator,
+        driver: Driver,
+        driver_handle: driver::Handle,
+        blocking_spawner: blocking::Spawner,
+        config: Config,
+        worker_metrics: WorkerMetrics,
         local_tid: Option<ThreadId>,
     ) -> (CurrentThread, Arc<Handle>) {
+        let Config {
+            task_hooks,
+            max_pending_spawns,
+            max_idle_spins,
+            event_interval,
+            detect_lost_wakeups,
+            scheduler_tracing,
+            seed_generator,
+        } = config;
+
         let handle = Arc::new(Handle {
+            shared: Shared {
+                inject: Mutex::new(Some(Inject {
+                    tasks: VecDeque::new(),
+                    reserved: 0,
+                })),
+                inject_not_full: Condvar::new(),
+                max_pending_spawns,
+                max_idle_spins,
+                woken: AtomicBool::new(false),
+            },
+            driver: driver_handle,
+            blocking_spawner,
+            task_hooks,
+            detect_lost_wakeups,
+            owned: task::OwnedTasks::new(),
+            worker_metrics,
             seed_generator,
             local_tid,
         });
-        let scheduler = CurrentThread {};
+
+        let core = AtomicCell::new(Some(Box::new(Core {
+            tasks: VecDeque::with_capacity(64),
+            tick: 0,
+            event_interval,
+            scheduler_tracing,
+            driver: Some(driver),
+        })));
+
+        let scheduler = CurrentThread {
+            core,
+            notify: Notify {
+                mutex: Mutex::new(()),
+                condvar: Condvar::new(),
+            },
+        };
 
         (scheduler, handle)
     }
 
+    #[track_caller]
     pub(crate) fn block_on<F: Future>(&self, handle: &scheduler::Handle, future: F) -> F::Output {
-        // pin!(future);
         // Pinning ensures that the memory address of the future doesn't change after it's been
         // polled.
         // Rust requires you to pin the future before polling it to ensure its memory doesn't move.
-        let mut future = future;
-        unsafe { Pin::new_unchecked(&mut future) };
+        pin!(future);
 
         context::enter_runtime(handle, false, |_blocking| {
-            let _handle = handle.as_current_thread();
+            let handle = handle.as_current_thread();
 
             // Attempt to steal the scheduler core and block_on the future if we can
-            // there, otherwise, lets select on a notification that the core is
-            // available or the future is complete.
+            // there, otherwise, lets wait for a notification that the core is
+            // available.
             loop {
-                println!("starting...");
+                if let Some(core) = self.take_core(handle) {
+                    return core.block_on(future);
+                }
+
+                let guard = self.notify.mutex.lock().unwrap();
+                // Check again while holding the lock, the core may have been
+                // returned in the meantime.
+                if let Some(core) = self.take_core(handle) {
+                    drop(guard);
+                    return core.block_on(future);
+                }
+                drop(self.notify.condvar.wait(guard).unwrap());
             }
         })
     }
+
+    fn take_core(&self, handle: &Arc<Handle>) -> Option<CoreGuard<'_>> {
+        let core = self.core.take()?;
+
+        Some(CoreGuard {
+            context: scheduler::Context::CurrentThread(Context {
+                handle: handle.clone(),
+                core: RefCell::new(Some(core)),
+            }),
+            scheduler: self,
+        })
+    }
+}
+
+impl CurrentThread {
+    /// Cancels every task of the scheduler.
+    ///
+    /// The futures of the tasks are dropped, wherever the tasks are: in a run
+    /// queue, or idle and only referenced by a waker stored in a resource. A
+    /// task spawned or woken from now on is cancelled right away.
+    ///
+    /// Tasks hold a handle to the scheduler, and the scheduler holds the
+    /// notified tasks in its queues, so the queues must be drained explicitly
+    /// to break the cycle. The driver is dropped last, once no future can
+    /// use the resources it drives.
+    pub(crate) fn shutdown(&mut self, handle: &scheduler::Handle) {
+        let handle = handle.as_current_thread();
+
+        // Dropping the futures may need the runtime, e.g. to deregister an
+        // I/O resource or to spawn a task (which is cancelled).
+        let _enter = context::try_set_current(&scheduler::Handle::CurrentThread(handle.clone()));
+
+        handle.owned.close_and_shutdown_all();
+
+        let mut core = self.core.take();
+        if let Some(core) = &mut core {
+            for task in core.tasks.drain(..) {
+                task.shutdown();
+            }
+        }
+
+        let remote = handle.shared.inject.lock().unwrap().take();
+        // The spawners waiting for room see the scheduler is gone, their
+        // task is cancelled.
+        handle.shared.inject_not_full.notify_all();
+        for task in remote.into_iter().flat_map(|inject| inject.tasks) {
+            task.shutdown();
+        }
+
+        drop(core);
+    }
 }
 
 impl fmt::Debug for CurrentThread {
@@ -62,25 +297,398 @@ impl fmt::Debug for CurrentThread {
     }
 }
 
+// ===== impl Core =====
+
+impl Core {
+    /// Get and increment the current tick
+    fn tick(&mut self) {
+        self.tick = self.tick.wrapping_add(1);
+    }
+
+    fn next_task(&mut self, handle: &Handle) -> Option<Notified> {
+        if self.tick.is_multiple_of(GLOBAL_QUEUE_INTERVAL) {
+            handle.next_remote_task().or_else(|| self.tasks.pop_front())
+        } else {
+            self.tasks.pop_front().or_else(|| handle.next_remote_task())
+        }
+    }
+
+    /// Reports a round of the scheduling loop, if enabled.
+    fn trace_tick(&self, tasks_polled: u32, driver_checked: bool) {
+        if !self.scheduler_tracing {
+            return;
+        }
+
+        let tick = self.tick;
+
+        #[cfg(feature = "tracing")]
+        tracing::trace!(tick, tasks_polled, driver_checked, "scheduler tick");
+
+        #[cfg(not(feature = "tracing"))]
+        eprintln!(
+            "scheduler tick {tick}: {tasks_polled} tasks polled, driver checked: {driver_checked}"
+        );
+    }
+}
+
+// ===== impl Context =====
+
+impl Context {
+    /// Execute the closure with the given scheduler core stored in the
+    /// thread-local context.
+    fn enter<R>(&self, core: Box<Core>, f: impl FnOnce() -> R) -> (Box<Core>, R) {
+        // Store the scheduler core in the thread-local context
+        //
+        // A drop-guard is employed at a higher level.
+        *self.core.borrow_mut() = Some(core);
+
+        // Execute the closure
+        let ret = f();
+
+        // Take the scheduler core back
+        let core = self.core.borrow_mut().take().expect("core missing");
+        (core, ret)
+    }
+
+    /// Blocks the current thread until an event is received. Returns `false`
+    /// if there was work to do, and the thread didn't park.
+    fn park(&self, mut core: Box<Core>, handle: &Handle) -> (Box<Core>, bool) {
+        let mut driver = core.driver.take().expect("driver missing");
+
+        // A task may have been woken while the run queue was drained, or the
+        // `block_on` future may have been notified: only park when there is
+        // really nothing to do.
+        let parked = core.tasks.is_empty() && !handle.spin_for_work();
+        if parked {
+            let metrics = &handle.worker_metrics;
+            metrics.incr_park_count();
+            let (c, ()) = self.enter(core, || driver.park(&handle.driver));
+            core = c;
+            metrics.incr_unpark_count();
+        }
+
+        core.driver = Some(driver);
+        (core, parked)
+    }
+
+    /// Checks the driver for new events without blocking the thread.
+    fn park_yield(&self, mut core: Box<Core>, handle: &Handle) -> Box<Core> {
+        let mut driver = core.driver.take().expect("driver missing");
+
+        let (mut core, ()) = self.enter(core, || {
+            driver.park_timeout(&handle.driver, Duration::from_millis(0));
+        });
+
+        core.driver = Some(driver);
+        core
+    }
+}
+
 // ===== impl Handle =====
 
 impl Handle {
-    /// Spawns a future onto the `CurrentThread` scheduler
-    pub(crate) fn spawn<F>(me: &Arc<Self>, _future: F, id: task::Id) -> JoinHandle<F::Output>
+    /// Spawns a future onto the `CurrentThread` scheduler.
+    ///
+    /// A spawn from another thread goes through the remote run queue: if the
+    /// queue is bounded and full, the caller waits for room, or gets the
+    /// future back with `Backpressure::Reject`. The thread driving the
+    /// scheduler is the one draining the queue, its spawns are never bounded.
+    #[track_caller]
+    pub(crate) fn spawn<F>(
+        me: &Arc<Self>,
+        future: F,
+        meta: task::SpawnMeta,
+        backpressure: Backpressure,
+    ) -> Result<JoinHandle<F::Output>, F>
     where
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
-        println!(
-            "Spawns a future onto the `CurrentThread` scheduler {:?} {id}",
-            me
-        );
-        JoinHandle::new()
+        let reserved = match me.shared.max_pending_spawns {
+            Some(max) if !me.is_current() => {
+                if !me.reserve_spawn(max, backpressure) {
+                    return Err(future);
+                }
+                true
+            }
+            _ => false,
+        };
+
+        let (notified, join) =
+            task::new_task(future, scheduler::Handle::CurrentThread(me.clone()), meta);
+
+        if reserved {
+            me.push_remote(notified, true);
+        } else {
+            Handle::schedule(me, notified);
+        }
+
+        Ok(join)
+    }
+
+    /// Schedules a task: pushes it into the local run queue when called from
+    /// the thread driving this scheduler, or into the injection queue and
+    /// unparks the driving thread otherwise.
+    pub(crate) fn schedule(me: &Arc<Self>, task: Notified) {
+        context::with_scheduler(|maybe_cx| match maybe_cx {
+            Some(scheduler::Context::CurrentThread(cx)) if Arc::ptr_eq(me, &cx.handle) => {
+                let mut core = cx.core.borrow_mut();
+
+                // If `None`, the runtime is shutting down, so there is no need
+                // to schedule the task.
+                if let Some(core) = core.as_mut() {
+                    core.tasks.push_back(task);
+                } else {
+                    drop(core);
+                    task.shutdown();
+                }
+            }
+            _ => me.push_remote(task, false),
+        });
+    }
+
+    /// Returns `true` if the current thread drives this scheduler.
+    fn is_current(&self) -> bool {
+        context::with_scheduler(|maybe_cx| match maybe_cx {
+            Some(scheduler::Context::CurrentThread(cx)) => std::ptr::eq(self, &*cx.handle),
+            _ => false,
+        })
+    }
+
+    /// Reserves room in the remote run queue for a spawn. Returns `false` if
+    /// the queue is full and `backpressure` is `Reject`.
+    fn reserve_spawn(&self, max: usize, backpressure: Backpressure) -> bool {
+        let mut inject = self.shared.inject.lock().unwrap();
+
+        loop {
+            match inject.as_mut() {
+                // The scheduler shut down, the task is cancelled when pushed.
+                None => return true,
+                Some(inject) if inject.tasks.len() + inject.reserved < max => {
+                    inject.reserved += 1;
+                    return true;
+                }
+                Some(_) if backpressure == Backpressure::Reject => return false,
+                Some(_) => inject = self.shared.inject_not_full.wait(inject).unwrap(),
+            }
+        }
+    }
+
+    /// Pushes a task into the remote run queue and unparks the driving
+    /// thread. `reserved` releases the room reserved by `reserve_spawn`.
+    fn push_remote(&self, task: Notified, reserved: bool) {
+        let mut inject = self.shared.inject.lock().unwrap();
+
+        // `None` if the runtime shut down, the task is cancelled.
+        if let Some(inject) = inject.as_mut() {
+            if reserved {
+                inject.reserved -= 1;
+            }
+            inject.tasks.push_back(task);
+            self.driver.unpark();
+        } else {
+            drop(inject);
+            task.shutdown();
+        }
+    }
+
+    /// Returns the number of tasks in the remote run queue.
+    pub(crate) fn injection_queue_depth(&self) -> usize {
+        let inject = self.shared.inject.lock().unwrap();
+        inject.as_ref().map_or(0, |inject| inject.tasks.len())
+    }
+
+    /// Polls `task` with a fresh coop budget, measuring the poll when
+    /// enabled.
+    fn run_task(&self, task: Notified) {
+        if !self.worker_metrics.measures_poll_time() {
+            coop::budget(|| task.run());
+            return;
+        }
+
+        let start = Instant::now();
+        coop::budget(|| task.run());
+        self.worker_metrics.record_poll_time(start.elapsed());
+    }
+
+    fn next_remote_task(&self) -> Option<Notified> {
+        let task = self
+            .shared
+            .inject
+            .lock()
+            .unwrap()
+            .as_mut()?
+            .tasks
+            .pop_front();
+
+        if task.is_some() && self.shared.max_pending_spawns.is_some() {
+            self.shared.inject_not_full.notify_one();
+        }
+
+        task
+    }
+
+    /// Returns `true` if there is work from other threads: a remote task or
+    /// a wakeup of the `block_on` future.
+    fn has_remote_work(&self) -> bool {
+        self.shared.woken.load(Acquire)
+            || self
+                .shared
+                .inject
+                .lock()
+                .unwrap()
+                .as_ref()
+                .is_some_and(|inject| !inject.tasks.is_empty())
+    }
+
+    /// Checks for remote work up to `max_idle_spins` times before giving up.
+    fn spin_for_work(&self) -> bool {
+        if self.has_remote_work() {
+            return true;
+        }
+
+        for _ in 0..self.shared.max_idle_spins {
+            std::hint::spin_loop();
+            if self.has_remote_work() {
+                return true;
+            }
+            self.worker_metrics.incr_idle_spin_count();
+        }
+
+        false
+    }
+
+    fn reset_woken(&self) -> bool {
+        self.shared.woken.swap(false, AcqRel)
     }
 }
 
 impl fmt::Debug for Handle {
     fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
-        fmt.debug_struct("current_thread::Handle { ... }").finish()
+        fmt.debug_struct("current_thread::Handle")
+            .field("ptr", &(self as *const Handle))
+            .field("local_tid", &self.local_tid)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Wake for Handle {
+    /// Wakes the thread blocked in `block_on`.
+    fn wake(arc_self: Arc<Self>) {
+        Wake::wake_by_ref(&arc_self);
+    }
+
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.shared.woken.store(true, Release);
+        arc_self.driver.unpark();
+    }
+}
+
+// ===== CoreGuard =====
+
+/// Used to ensure we always place the `Core` value back into its slot in
+/// `CurrentThread`, even if the future panics.
+struct CoreGuard<'a> {
+    context: scheduler::Context,
+    scheduler: &'a CurrentThread,
+}
+
+impl CoreGuard<'_> {
+    #[track_caller]
+    fn block_on<F: Future>(self, future: Pin<&mut F>) -> F::Output {
+        self.enter(|mut core, context| {
+            let handle = &context.handle;
+
+            // This thread is now the worker: everything logged while it
+            // drives the scheduler is tagged with its id.
+            let thread_id = context::thread_id().expect(THREAD_LOCAL_DESTROYED_ERROR);
+            handle.worker_metrics.set_thread_id(thread_id);
+            #[cfg(feature = "tracing")]
+            let _span =
+                tracing::info_span!("worker", thread_id = thread_id.as_u64().get()).entered();
+
+            let waker = waker_ref(handle);
+            let mut cx = std::task::Context::from_waker(&waker);
+            let mut future = future;
+
+            // The future must be polled at least once.
+            handle.shared.woken.store(true, Release);
+
+            'outer: loop {
+                if handle.reset_woken() {
+                    let (c, res) =
+                        context.enter(core, || coop::budget(|| future.as_mut().poll(&mut cx)));
+
+                    core = c;
+
+                    if let Ready(v) = res {
+                        return (core, v);
+                    }
+                }
+
+                let mut tasks_polled = 0;
+
+                for _ in 0..core.event_interval {
+                    core.tick();
+
+                    let task = match core.next_task(handle) {
+                        Some(task) => task,
+                        None => {
+                            let parked;
+                            (core, parked) = context.park(core, handle);
+                            core.trace_tick(tasks_polled, parked);
+
+                            // Try polling the `block_on` future next
+                            continue 'outer;
+                        }
+                    };
+
+                    let (c, ()) = context.enter(core, || handle.run_task(task));
+                    core = c;
+                    tasks_polled += 1;
+                }
+
+                // Yield to the driver, this drives the I/O event loop. If the
+                // `block_on` future was woken by one of the tasks polled
+                // above, it is polled on the next iteration.
+                core = context.park_yield(core, handle);
+                core.trace_tick(tasks_polled, true);
+            }
+        })
+    }
+
+    /// Enters the scheduler context. This sets the queue and other necessary
+    /// scheduler state in the thread-local.
+    fn enter<F, R>(self, f: F) -> R
+    where
+        F: FnOnce(Box<Core>, &Context) -> (Box<Core>, R),
+    {
+        let context = self.context.expect_current_thread();
+
+        // Remove `core` from `context` to pass into the closure.
+        let core = context.core.borrow_mut().take().expect("core missing");
+
+        // Call the closure and place `core` back
+        let (core, ret) = context::set_scheduler(&self.context, || f(core, context));
+
+        *context.core.borrow_mut() = Some(core);
+
+        ret
+    }
+}
+
+impl Drop for CoreGuard<'_> {
+    fn drop(&mut self) {
+        let context = self.context.expect_current_thread();
+
+        if let Some(core) = context.core.borrow_mut().take() {
+            // Replace old scheduler back into the state to allow
+            // other threads to pick it up and drive it.
+            self.scheduler.core.set(core);
+
+            // Wake up other possible threads that could steal the driver.
+            drop(self.scheduler.notify.mutex.lock());
+            self.scheduler.notify.condvar.notify_one();
+        }
     }
 }
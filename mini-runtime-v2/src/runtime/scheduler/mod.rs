@@ -1,11 +1,17 @@
 pub(crate) mod current_thread;
 
+use current_thread::Backpressure;
 pub(crate) use current_thread::CurrentThread;
-use std::sync::Arc;
 
-use crate::runtime::task::Id;
+use crate::runtime::blocking;
+use crate::runtime::context::{self, EnterRuntime};
+use crate::runtime::driver;
+use crate::runtime::task::{Notified, SpawnMeta};
+use crate::runtime::{TaskHooks, WorkerMetrics};
 use crate::task::JoinHandle;
 use crate::util::RngSeedGenerator;
+use crate::util::loom::sync::Arc;
+use std::fmt;
 
 macro_rules! match_flavor {
     ($self:expr, $ty:ident($h:ident) => $e:expr) => {
@@ -15,21 +21,154 @@ macro_rules! match_flavor {
     };
 }
 
-#[allow(dead_code)]
-#[derive(Debug, Clone)]
+#[derive(Clone)]
 pub(crate) enum Handle {
     CurrentThread(Arc<current_thread::Handle>),
 }
 
+/// The scheduler's internal "context", set while the scheduler drives tasks
+/// on the current thread.
+pub(super) enum Context {
+    CurrentThread(current_thread::Context),
+}
+
 impl Handle {
-    pub(crate) fn spawn<F>(&self, future: F, id: Id) -> JoinHandle<F::Output>
+    /// Returns the handle of the runtime the current thread is running on.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime.
+    #[track_caller]
+    pub(crate) fn current() -> Handle {
+        match context::with_current(Clone::clone) {
+            Ok(handle) => handle,
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    pub(crate) fn driver(&self) -> &driver::Handle {
+        match_flavor!(self, Handle(h) => &h.driver)
+    }
+
+    pub(crate) fn blocking_spawner(&self) -> &blocking::Spawner {
+        match_flavor!(self, Handle(h) => &h.blocking_spawner)
+    }
+
+    /// Spawns a task. A spawn from outside of the runtime waits while the
+    /// runtime has too many pending spawns, see `Builder::max_pending_spawns`.
+    #[track_caller]
+    pub(crate) fn spawn<F>(&self, future: F, meta: SpawnMeta) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        match self.spawn_with(future, meta, Backpressure::Wait) {
+            Ok(join) => join,
+            Err(_) => unreachable!("a waiting spawn is never rejected"),
+        }
+    }
+
+    /// Spawns a task like `spawn`, or returns the future back instead of
+    /// waiting for the pending spawns to drain.
+    #[track_caller]
+    pub(crate) fn try_spawn<F>(
+        &self,
+        future: F,
+        meta: SpawnMeta,
+    ) -> Result<JoinHandle<F::Output>, F>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        self.spawn_with(future, meta, Backpressure::Reject)
+    }
+
+    #[track_caller]
+    fn spawn_with<F>(
+        &self,
+        future: F,
+        meta: SpawnMeta,
+        backpressure: Backpressure,
+    ) -> Result<JoinHandle<F::Output>, F>
     where
         F: Future + Send + 'static,
         F::Output: Send + 'static,
     {
-        println!("Try to start spawn in handle...");
         match self {
-            Handle::CurrentThread(h) => current_thread::Handle::spawn(h, future, id),
+            Handle::CurrentThread(h) => {
+                current_thread::Handle::spawn(h, future, meta, backpressure)
+            }
+        }
+    }
+
+    /// Pushes a notified task into the scheduler's run queue.
+    pub(crate) fn schedule(&self, task: Notified) {
+        match_flavor!(self, Handle(h) => current_thread::Handle::schedule(h, task))
+    }
+
+    pub(crate) fn hooks(&self) -> &TaskHooks {
+        match_flavor!(self, Handle(h) => &h.task_hooks)
+    }
+
+    /// Returns `true` if the tasks check that they keep their waker, see
+    /// `Builder::detect_lost_wakeups`.
+    pub(crate) fn detects_lost_wakeups(&self) -> bool {
+        match_flavor!(self, Handle(h) => h.detect_lost_wakeups)
+    }
+
+    /// Counts a task found returning `Pending` without keeping its waker, on
+    /// the worker that polled it.
+    pub(crate) fn record_lost_wakeup(&self) {
+        match_flavor!(self, Handle(h) => h.worker_metrics.incr_lost_wakeup_count())
+    }
+
+    pub(crate) fn owned_tasks(&self) -> &crate::runtime::task::OwnedTasks {
+        match_flavor!(self, Handle(h) => &h.owned)
+    }
+
+    /// Returns the number of tasks waiting in the remote run queue.
+    pub(crate) fn injection_queue_depth(&self) -> usize {
+        match_flavor!(self, Handle(h) => h.injection_queue_depth())
+    }
+
+    pub(crate) fn blocking_queue_depth(&self) -> usize {
+        self.blocking_spawner().queue_depth()
+    }
+
+    /// Returns the number of timers fired, `0` without a time driver.
+    pub(crate) fn timer_fired_count(&self) -> u64 {
+        self.driver()
+            .time
+            .as_ref()
+            .map_or(0, |time| time.fired_count())
+    }
+
+    /// Returns the number of times timers fired, `0` without a time driver.
+    pub(crate) fn timer_tick_count(&self) -> u64 {
+        self.driver()
+            .time
+            .as_ref()
+            .map_or(0, |time| time.tick_count())
+    }
+
+    pub(crate) fn num_workers(&self) -> usize {
+        match self {
+            Handle::CurrentThread(_) => 1,
+        }
+    }
+
+    /// Returns the metrics of the worker `worker`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `worker` is not lower than `num_workers()`.
+    #[track_caller]
+    pub(crate) fn worker_metrics(&self, worker: usize) -> &WorkerMetrics {
+        match self {
+            Handle::CurrentThread(h) => {
+                assert_eq!(worker, 0, "worker index out of range");
+                &h.worker_metrics
+            }
         }
     }
 
@@ -37,9 +176,53 @@ impl Handle {
         match_flavor!(self, Handle(h) => &h.seed_generator)
     }
 
+    /// Returns whether both handles refer to the same runtime.
+    pub(crate) fn ptr_eq(&self, other: &Handle) -> bool {
+        match (self, other) {
+            (Handle::CurrentThread(a), Handle::CurrentThread(b)) => Arc::ptr_eq(a, b),
+        }
+    }
+
     pub(crate) fn as_current_thread(&self) -> &Arc<current_thread::Handle> {
         match self {
             Handle::CurrentThread(handle) => handle,
         }
     }
 }
+
+/// Runs the blocking function `f` on the current thread, see
+/// [`task::block_in_place`].
+///
+/// [`task::block_in_place`]: crate::task::block_in_place
+#[track_caller]
+pub(crate) fn block_in_place<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    match context::current_enter_context() {
+        // The thread drives a runtime, and current-thread is the only flavor:
+        // no other thread could take over its tasks, they would all be stuck
+        // until `f` returns. A multi-thread worker would hand its core over
+        // to a new worker instead, and allow `block_in_place`.
+        EnterRuntime::Entered { .. } => {
+            panic!("can call blocking only when running on the multi-threaded runtime")
+        }
+        // Not driving a runtime, e.g. a `spawn_blocking` thread: blocking is
+        // fine.
+        EnterRuntime::NotEntered => f(),
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match_flavor!(self, Handle(h) => fmt.debug_tuple("CurrentThread").field(&**h).finish())
+    }
+}
+
+impl Context {
+    pub(crate) fn expect_current_thread(&self) -> &current_thread::Context {
+        match self {
+            Context::CurrentThread(context) => context,
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Signal driver
+//!
+//! Signal handlers can't do much: they record the signal in the global
+//! registry and write a byte into the self-pipe. The read end of the pipe is
+//! registered with the I/O driver, so a signal unblocks `mio::Poll`; this
+//! driver then drains the pipe and notifies the `Signal` listeners.
+//!
+//! Dropping the driver closes the `Signal` listeners created on its runtime:
+//! once they received the signals delivered so far, they return `None`.
+
+use crate::runtime::io;
+use crate::signal::registry::globals;
+use mio::net::UnixStream;
+use std::fmt;
+use std::io::{self as std_io, Read};
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::SeqCst;
+use std::time::Duration;
+
+/// Responsible for registering wakeups when an OS signal is received, and
+/// subsequently dispatching notifications to any signal listeners as
+/// appropriate.
+///
+/// Note: this driver relies on having an enabled I/O driver in order to
+/// listen to pipe write wakeups.
+pub(crate) struct Driver {
+    /// Thread parker. The `Driver` park implementation delegates to this.
+    io: io::Driver,
+
+    /// A pipe for receiving wake events from the signal handler
+    receiver: UnixStream,
+
+    /// Set when the driver is dropped, shared with the listeners.
+    closed: Arc<AtomicBool>,
+}
+
+/// A handle to a signal driver, used by the `Signal` listeners to find out
+/// whether the driver is gone.
+#[derive(Debug, Clone)]
+pub(crate) struct Handle {
+    closed: Arc<AtomicBool>,
+}
+
+impl Driver {
+    /// Creates a new signal `Driver` instance that delegates wakeups to `park`.
+    pub(crate) fn new(io: io::Driver, io_handle: &io::Handle) -> std_io::Result<Self> {
+        // Each driver registers its own clone of the receiver rather than
+        // sharing one descriptor between the `mio::Poll` of every runtime.
+        let mut receiver = UnixStream::from_std(globals().receiver.try_clone()?);
+
+        io_handle.register_signal_receiver(&mut receiver)?;
+
+        Ok(Self {
+            io,
+            receiver,
+            closed: Arc::new(AtomicBool::new(false)),
+        })
+    }
+
+    pub(crate) fn handle(&self) -> Handle {
+        Handle {
+            closed: self.closed.clone(),
+        }
+    }
+
+    pub(crate) fn park(&mut self, handle: &io::Handle) {
+        self.io.park(handle);
+        self.process();
+    }
+
+    pub(crate) fn park_timeout(&mut self, handle: &io::Handle, duration: Duration) {
+        self.io.park_timeout(handle, duration);
+        self.process();
+    }
+
+    fn process(&mut self) {
+        // If the signal pipe has not received a readiness event, then there is
+        // nothing else to do.
+        if !self.io.consume_signal_ready() {
+            return;
+        }
+
+        // Drain the pipe completely so we can receive a new readiness event
+        // if another signal has come in.
+        let mut buf = [0; 128];
+        loop {
+            match self.receiver.read(&mut buf) {
+                Ok(0) => panic!("EOF on self-pipe"),
+                Ok(_) => continue, // Keep reading
+                Err(e) if e.kind() == std_io::ErrorKind::WouldBlock => break,
+                Err(e) => panic!("Bad read on self-pipe: {e}"),
+            }
+        }
+
+        // Broadcast any signals which were received
+        globals().broadcast();
+    }
+}
+
+impl Drop for Driver {
+    fn drop(&mut self) {
+        self.closed.store(true, SeqCst);
+        // Wake the listeners still waiting, so that they observe the driver
+        // is gone. The listeners of the other runtimes wake up spuriously.
+        globals().wake_all();
+    }
+}
+
+impl Handle {
+    /// Returns `true` once the driver was dropped.
+    pub(crate) fn is_shutdown(&self) -> bool {
+        self.closed.load(SeqCst)
+    }
+}
+
+impl fmt::Debug for Driver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("signal::Driver")
+    }
+}
@@ -0,0 +1,311 @@
+//! Core task module.
+//!
+//! A spawned task is a single heap allocation, the [`Cell`], laid out in three
+//! parts:
+//!
+//! - the [`Header`], which is not generic, holds everything the scheduler
+//!   needs to know about the task (its id, its [`State`], the scheduler it
+//!   belongs to, and the [`Vtable`] of the generic operations);
+//! - the [`Core`], which holds the stage: either the future or its output;
+//! - the [`Trailer`], which holds the waker of the `JoinHandle`, read once
+//!   the task completes.
+//!
+//! The run queues, the wakers and the `JoinHandle` only know a pointer to the
+//! header, the [`RawTask`](super::raw::RawTask): the cell is `repr(C)`, so
+//! that pointer is also a pointer to the cell, which the functions of the
+//! vtable, generic over the future, cast back.
+
+use crate::runtime::context;
+use crate::runtime::task::raw::{self, Vtable};
+use crate::runtime::task::state::State;
+use crate::runtime::task::{Id, JoinError, SpawnMeta};
+use crate::runtime::{TaskMeta, scheduler};
+use crate::util::loom::sync::atomic::AtomicUsize;
+use std::cell::UnsafeCell;
+use std::future::Future;
+use std::marker::PhantomData;
+use std::panic::{self, Location};
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+/// The task cell. Contains the components of the task.
+///
+/// `repr(C)` keeps the header first: a pointer to the header is a pointer
+/// to the cell.
+#[repr(C)]
+pub(super) struct Cell<T: Future> {
+    /// Hot task state data
+    pub(super) header: Header,
+
+    /// Either the future or the output.
+    pub(super) core: Core<T>,
+
+    /// Cold data, only touched when the task completes.
+    pub(super) trailer: Trailer,
+}
+
+pub(crate) struct Header {
+    /// Flags telling whether the task is polled, notified, complete or
+    /// cancelled, who owns the join waker, and the reference count.
+    pub(super) state: State,
+
+    /// Operations generic over the future.
+    pub(super) vtable: &'static Vtable,
+
+    /// The task's ID, used for populating `JoinError`s and `task::id()`.
+    pub(super) id: Id,
+
+    /// Name given with `task::Builder::name`, reported by the task hooks,
+    /// task dumps and tracing spans.
+    pub(super) name: Option<Box<str>>,
+
+    /// Number of clones of the task's waker alive. A task returning `Pending`
+    /// while it is `0` and the task is not notified is never woken again.
+    pub(super) wakers: AtomicUsize,
+
+    /// The scheduler the task is bound to, used to reschedule it when woken.
+    pub(super) scheduler: scheduler::Handle,
+
+    /// Where the task was spawned from, reported by the task hooks,
+    /// `JoinError`s, task dumps and tracing spans.
+    pub(super) spawned_at: &'static Location<'static>,
+
+    /// Backtrace captured when the task was spawned.
+    #[cfg(feature = "task_dump")]
+    pub(super) trace: std::sync::Arc<std::backtrace::Backtrace>,
+}
+
+/// The part of the cell generic over the future.
+pub(super) struct Core<T: Future> {
+    /// Accessed by whoever the state gives it to: the thread polling or
+    /// cancelling the task while it is `RUNNING`, then the `JoinHandle`, or
+    /// the runtime if there is none, once it is `COMPLETE`.
+    stage: UnsafeCell<Stage<T>>,
+}
+
+pub(super) struct Trailer {
+    /// Waker of the task awaiting the `JoinHandle`.
+    ///
+    /// Owned by the `JoinHandle` while `JOIN_WAKER` is unset, read by the
+    /// runtime once the task is complete and `JOIN_WAKER` is set, see
+    /// [`State`].
+    join_waker: UnsafeCell<Option<Waker>>,
+}
+
+impl<T> Cell<T>
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    /// Allocates a cell for `future`. The task starts notified, with a
+    /// `Notified` and a `JoinHandle` to be created from it.
+    pub(super) fn new(
+        future: T,
+        scheduler: scheduler::Handle,
+        meta: SpawnMeta,
+        spawned_at: &'static Location<'static>,
+    ) -> Box<Cell<T>> {
+        Box::new(Cell {
+            header: Header {
+                state: State::new(),
+                vtable: raw::vtable::<T>(),
+                id: meta.id,
+                name: meta.name,
+                wakers: AtomicUsize::new(0),
+                scheduler,
+                spawned_at,
+                #[cfg(feature = "task_dump")]
+                trace: std::sync::Arc::new(std::backtrace::Backtrace::force_capture()),
+            },
+            core: Core {
+                stage: UnsafeCell::new(Stage::Running(future)),
+            },
+            trailer: Trailer {
+                join_waker: UnsafeCell::new(None),
+            },
+        })
+    }
+}
+
+impl Header {
+    pub(super) fn meta(&self) -> TaskMeta<'_> {
+        TaskMeta {
+            id: self.id,
+            name: self.name.as_deref(),
+            spawned_at: self.spawned_at,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+/// Either the future or the output.
+enum Stage<T: Future> {
+    Running(T),
+    Finished(Result<T::Output, JoinError>),
+    Consumed,
+}
+
+/// Set and clear the task id in the context when the future is executed or
+/// dropped, or when the output produced by the future is dropped.
+pub(crate) struct TaskIdGuard {
+    parent_task_id: Option<Id>,
+}
+
+impl TaskIdGuard {
+    fn enter(id: Id) -> Self {
+        TaskIdGuard {
+            parent_task_id: context::set_current_task_id(Some(id)),
+        }
+    }
+}
+
+impl Drop for TaskIdGuard {
+    fn drop(&mut self) {
+        context::set_current_task_id(self.parent_task_id);
+    }
+}
+
+impl<T: Future> Core<T> {
+    /// Polls the future. Returns `true` once the stage holds the output,
+    /// either because the future completed or because it panicked.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the stage, and the stage
+    /// must hold the future.
+    pub(super) unsafe fn poll(&self, header: &Header, cx: &mut Context<'_>) -> bool {
+        let id = header.id;
+        // Safety: exclusive access, guaranteed by the caller.
+        let stage = unsafe { &mut *self.stage.get() };
+        let future = match stage {
+            Stage::Running(future) => future,
+            _ => unreachable!("unexpected stage"),
+        };
+
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _guard = TaskIdGuard::enter(id);
+
+            // Safety: The future lives inside the cell, which is never
+            // moved while the stage is `Running`.
+            let future = unsafe { Pin::new_unchecked(future) };
+            future.poll(cx)
+        }));
+
+        let output = match res {
+            Ok(Poll::Pending) => return false,
+            Ok(Poll::Ready(output)) => Ok(output),
+            Err(panic) => Err(JoinError::panic(id, header.spawned_at, panic)),
+        };
+
+        // Dropping the future happens while `Stage::Running` is replaced, so
+        // the future may still observe its own task id. As on cancel, a panic
+        // of its destructor is reported instead of the output.
+        let _guard = TaskIdGuard::enter(id);
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            *stage = Stage::Consumed;
+        }));
+
+        *stage = Stage::Finished(match res {
+            Ok(()) => output,
+            Err(panic) => {
+                let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| drop(output)));
+                Err(JoinError::panic(id, header.spawned_at, panic))
+            }
+        });
+        true
+    }
+
+    /// Drops the future and stores `err` as the task output.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the stage.
+    pub(super) unsafe fn cancel(&self, err: JoinError) {
+        let id = err.id();
+        let _guard = TaskIdGuard::enter(id);
+        // Safety: exclusive access, guaranteed by the caller.
+        let stage = unsafe { &mut *self.stage.get() };
+
+        // Dropping the future may panic, in which case the panic is reported
+        // instead of the cancellation.
+        let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            *stage = Stage::Consumed;
+        }));
+
+        *stage = Stage::Finished(match res {
+            Ok(()) => Err(err),
+            Err(panic) => Err(JoinError::panic(id, err.spawned_at(), panic)),
+        });
+    }
+
+    /// Moves the output out of the stage.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the stage.
+    pub(super) unsafe fn take_output(&self) -> Result<T::Output, JoinError> {
+        // Safety: exclusive access, guaranteed by the caller.
+        let stage = unsafe { &mut *self.stage.get() };
+
+        match std::mem::replace(stage, Stage::Consumed) {
+            Stage::Finished(output) => output,
+            _ => panic!("JoinHandle polled after completion"),
+        }
+    }
+
+    /// Drops the output, nobody will read it.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the stage.
+    pub(super) unsafe fn drop_output(&self, id: Id) {
+        // The destructor of the output may observe the task id, as the one
+        // of the future does.
+        let _guard = TaskIdGuard::enter(id);
+        // Safety: exclusive access, guaranteed by the caller.
+        let stage = unsafe { &mut *self.stage.get() };
+
+        // A panic in the destructor is not propagated to the thread dropping
+        // the `JoinHandle`, nor to the scheduler.
+        let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            *stage = Stage::Consumed;
+        }));
+    }
+}
+
+impl Trailer {
+    /// Returns `true` if the join waker would wake the same task as `waker`.
+    ///
+    /// # Safety
+    ///
+    /// Nobody may write the join waker meanwhile.
+    pub(super) unsafe fn will_wake(&self, waker: &Waker) -> bool {
+        // Safety: no concurrent write, guaranteed by the caller.
+        let current = unsafe { &*self.join_waker.get() };
+        current
+            .as_ref()
+            .is_some_and(|current| current.will_wake(waker))
+    }
+
+    /// Replaces the join waker.
+    ///
+    /// # Safety
+    ///
+    /// The caller must have exclusive access to the join waker.
+    pub(super) unsafe fn set_waker(&self, waker: Option<Waker>) {
+        // Safety: exclusive access, guaranteed by the caller.
+        unsafe { *self.join_waker.get() = waker };
+    }
+
+    /// Wakes the join waker, if any.
+    ///
+    /// # Safety
+    ///
+    /// Nobody may write the join waker meanwhile.
+    pub(super) unsafe fn wake_join(&self) {
+        // Safety: no concurrent write, guaranteed by the caller.
+        if let Some(waker) = unsafe { &*self.join_waker.get() } {
+            waker.wake_by_ref();
+        }
+    }
+}
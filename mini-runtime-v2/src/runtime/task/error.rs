@@ -0,0 +1,164 @@
+use crate::runtime::task::Id;
+use std::any::Any;
+use std::fmt;
+use std::io;
+use std::panic::Location;
+use std::sync::Mutex;
+
+/// Task failed to execute to completion.
+pub struct JoinError {
+    repr: Repr,
+    id: Id,
+    spawned_at: &'static Location<'static>,
+}
+
+enum Repr {
+    Cancelled,
+    /// The payload is wrapped in a `Mutex` only to make `JoinError: Sync`, the
+    /// lock is never contended.
+    Panic(Mutex<Box<dyn Any + Send + 'static>>),
+}
+
+impl JoinError {
+    pub(crate) fn cancelled(id: Id, spawned_at: &'static Location<'static>) -> JoinError {
+        JoinError {
+            repr: Repr::Cancelled,
+            id,
+            spawned_at,
+        }
+    }
+
+    pub(crate) fn panic(
+        id: Id,
+        spawned_at: &'static Location<'static>,
+        err: Box<dyn Any + Send + 'static>,
+    ) -> JoinError {
+        JoinError {
+            repr: Repr::Panic(Mutex::new(err)),
+            id,
+            spawned_at,
+        }
+    }
+
+    /// Returns true if the error was caused by the task being cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(&self.repr, Repr::Cancelled)
+    }
+
+    /// Returns true if the error was caused by the task panicking.
+    pub fn is_panic(&self) -> bool {
+        matches!(&self.repr, Repr::Panic(_))
+    }
+
+    /// Consumes the join error, returning the object with which the task panicked.
+    ///
+    /// # Panics
+    ///
+    /// `into_panic()` panics if the `Error` does not represent the underlying
+    /// task terminating with a panic. Use `is_panic` to check the error reason
+    /// or `try_into_panic` for a variant that does not panic.
+    #[track_caller]
+    pub fn into_panic(self) -> Box<dyn Any + Send + 'static> {
+        self.try_into_panic()
+            .expect("`JoinError` reason is not a panic.")
+    }
+
+    /// Consumes the join error, returning the object with which the task
+    /// panicked if the task terminated due to a panic. Otherwise, `self` is
+    /// returned.
+    pub fn try_into_panic(self) -> Result<Box<dyn Any + Send + 'static>, JoinError> {
+        match self.repr {
+            Repr::Panic(p) => Ok(p.into_inner().unwrap_or_else(|e| e.into_inner())),
+            _ => Err(self),
+        }
+    }
+
+    /// Returns a [task ID] that identifies the task which errored relative to
+    /// other currently spawned tasks.
+    ///
+    /// [task ID]: crate::task::Id
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Returns the location in the source code the task was spawned from,
+    /// e.g. the caller of `task::spawn`.
+    pub fn spawned_at(&self) -> &'static Location<'static> {
+        self.spawned_at
+    }
+}
+
+impl fmt::Display for JoinError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            Repr::Cancelled => write!(
+                fmt,
+                "task {} spawned at {} was cancelled",
+                self.id, self.spawned_at
+            ),
+            Repr::Panic(p) => match panic_payload_as_str(p) {
+                Some(panic_str) => {
+                    write!(
+                        fmt,
+                        "task {} spawned at {} panicked with message {:?}",
+                        self.id, self.spawned_at, panic_str
+                    )
+                }
+                None => write!(
+                    fmt,
+                    "task {} spawned at {} panicked",
+                    self.id, self.spawned_at
+                ),
+            },
+        }
+    }
+}
+
+impl fmt::Debug for JoinError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.repr {
+            Repr::Cancelled => write!(
+                fmt,
+                "JoinError::Cancelled({:?}, {})",
+                self.id, self.spawned_at
+            ),
+            Repr::Panic(p) => match panic_payload_as_str(p) {
+                Some(panic_str) => write!(
+                    fmt,
+                    "JoinError::Panic({:?}, {}, {:?}, ...)",
+                    self.id, self.spawned_at, panic_str
+                ),
+                None => write!(
+                    fmt,
+                    "JoinError::Panic({:?}, {}, ...)",
+                    self.id, self.spawned_at
+                ),
+            },
+        }
+    }
+}
+
+impl std::error::Error for JoinError {}
+
+impl From<JoinError> for io::Error {
+    fn from(src: JoinError) -> io::Error {
+        io::Error::other(match src.repr {
+            Repr::Cancelled => "task was cancelled",
+            Repr::Panic(_) => "task panicked",
+        })
+    }
+}
+
+fn panic_payload_as_str(payload: &Mutex<Box<dyn Any + Send>>) -> Option<String> {
+    let payload = payload.lock().unwrap_or_else(|e| e.into_inner());
+
+    // Panic payloads are almost always `String` (if invoked with formatting
+    // arguments) or `&'static str` (if invoked with a string literal).
+    if let Some(s) = payload.downcast_ref::<String>() {
+        return Some(s.clone());
+    }
+
+    payload
+        .downcast_ref::<&'static str>()
+        .map(|s| s.to_string())
+}
@@ -0,0 +1,290 @@
+use crate::runtime::task::core::{Cell, Core, Header, Trailer};
+use crate::runtime::task::raw::RawTask;
+use crate::runtime::task::state::{TransitionToIdle, TransitionToNotified, TransitionToRunning};
+use crate::runtime::task::{JoinError, Notified, Task, waker};
+use crate::util::loom::sync::atomic::Ordering::Acquire;
+use std::future::Future;
+use std::ptr::NonNull;
+use std::task::{Context, Poll, Waker};
+
+/// Typed access to a task cell, from the functions of its vtable.
+pub(super) struct Harness<T: Future> {
+    cell: NonNull<Cell<T>>,
+}
+
+impl<T> Harness<T>
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    /// # Safety
+    ///
+    /// `ptr` must point to the header of a live `Cell<T>`.
+    pub(super) unsafe fn from_raw(ptr: NonNull<Header>) -> Harness<T> {
+        Harness { cell: ptr.cast() }
+    }
+
+    fn header(&self) -> &Header {
+        // Safety: the cell is alive, see `from_raw`.
+        unsafe { &self.cell.as_ref().header }
+    }
+
+    fn core(&self) -> &Core<T> {
+        // Safety: the cell is alive, see `from_raw`.
+        unsafe { &self.cell.as_ref().core }
+    }
+
+    fn trailer(&self) -> &Trailer {
+        // Safety: the cell is alive, see `from_raw`.
+        unsafe { &self.cell.as_ref().trailer }
+    }
+
+    fn raw(&self) -> RawTask {
+        // Safety: the cell is alive, see `from_raw`.
+        unsafe { RawTask::from_raw(self.cell.cast()) }
+    }
+
+    /// Polls the inner future. Called by the scheduler when the task was
+    /// popped from a run queue.
+    pub(super) fn poll(self) {
+        match self.header().state.transition_to_running() {
+            TransitionToRunning::Success => {}
+            TransitionToRunning::Cancelled => return self.cancel(),
+            TransitionToRunning::Failed => return,
+        }
+
+        let header = self.header();
+        let raw = self.raw();
+        let waker = waker::waker_ref(&raw);
+        let mut cx = Context::from_waker(&waker);
+
+        let done = {
+            // Everything logged while the future is polled is tagged with the
+            // id of the task.
+            #[cfg(feature = "tracing")]
+            let _span = tracing::info_span!("task", id = %header.id, name = header.name.as_deref(), spawned_at = %header.spawned_at).entered();
+
+            // Safety: the task is `RUNNING`, this thread has exclusive
+            // access to the stage.
+            unsafe { self.core().poll(header, &mut cx) }
+        };
+
+        if done {
+            return self.complete();
+        }
+
+        match header.state.transition_to_idle() {
+            TransitionToIdle::Ok => {
+                if header.scheduler.detects_lost_wakeups() {
+                    check_waker_kept(header);
+                }
+            }
+            // Woken while it was polled: the wake left it to this thread to
+            // push the task into a run queue again.
+            TransitionToIdle::OkNotified => {
+                raw.ref_inc();
+                raw.schedule();
+            }
+            TransitionToIdle::Cancelled => self.cancel(),
+        }
+    }
+
+    /// Cancels the task without polling it, used when the scheduler shuts down.
+    ///
+    /// A task being polled, by another thread or by the current one while a
+    /// destructor of its future runs, is cancelled by the poll in progress
+    /// once it returns.
+    pub(super) fn shutdown(self) {
+        if self.header().state.transition_to_shutdown() {
+            self.cancel();
+        }
+    }
+
+    /// Moves the output into `dst` if the task has completed, otherwise
+    /// stores `waker` to be notified once it does.
+    ///
+    /// # Safety
+    ///
+    /// `dst` must be valid for writes.
+    pub(super) unsafe fn try_read_output(
+        self,
+        dst: *mut Poll<Result<T::Output, JoinError>>,
+        waker: &Waker,
+    ) {
+        if self.can_read_output(waker) {
+            // Safety: the task is complete and the `JoinHandle` is alive, it
+            // has exclusive access to the stage.
+            let output = unsafe { self.core().take_output() };
+            // Safety: guaranteed by the caller.
+            unsafe { *dst = Poll::Ready(output) };
+        }
+    }
+
+    /// Returns `true` if the task is complete, otherwise makes sure `waker`
+    /// is the join waker.
+    fn can_read_output(&self, waker: &Waker) -> bool {
+        let snapshot = self.header().state.load();
+
+        if snapshot.is_complete() {
+            return true;
+        }
+
+        if snapshot.is_join_waker_set() {
+            // Safety: `JOIN_WAKER` is set, the runtime may read the waker
+            // but nobody writes it.
+            if unsafe { self.trailer().will_wake(waker) } {
+                return false;
+            }
+
+            // Take back exclusive access to replace the waker, unless the
+            // task completed meanwhile.
+            if self.header().state.unset_join_waker().is_err() {
+                return true;
+            }
+        }
+
+        self.set_join_waker(waker.clone())
+    }
+
+    /// Stores the join waker and hands it to the runtime. Returns `true` if
+    /// the task completed meanwhile: the waker will never be woken, the
+    /// output can be read instead.
+    fn set_join_waker(&self, waker: Waker) -> bool {
+        // Safety: `JOIN_WAKER` is unset, the `JoinHandle` has exclusive
+        // access to the waker.
+        unsafe { self.trailer().set_waker(Some(waker)) };
+
+        match self.header().state.set_join_waker() {
+            Ok(_) => false,
+            Err(_) => {
+                // Safety: `JOIN_WAKER` is still unset.
+                unsafe { self.trailer().set_waker(None) };
+                true
+            }
+        }
+    }
+
+    /// Called when the `JoinHandle` is dropped: drops the output if the task
+    /// is complete, the runtime drops it otherwise.
+    pub(super) fn drop_join_handle(self) {
+        let transition = self.header().state.transition_to_join_handle_dropped();
+
+        if transition.drop_output {
+            // Safety: the task is complete and the `JoinHandle` was alive, it
+            // has exclusive access to the stage.
+            unsafe { self.core().drop_output(self.header().id) };
+        }
+
+        if transition.drop_waker {
+            // Safety: `JOIN_WAKER` is unset, the `JoinHandle` has exclusive
+            // access to the waker.
+            unsafe { self.trailer().set_waker(None) };
+        }
+    }
+
+    /// Drops the future of a `RUNNING` task and completes it.
+    fn cancel(self) {
+        let header = self.header();
+        let err = JoinError::cancelled(header.id, header.spawned_at);
+        // Safety: the task is `RUNNING`, this thread has exclusive access to
+        // the stage.
+        unsafe { self.core().cancel(err) };
+        self.complete();
+    }
+
+    /// Called once the stage holds the output of a `RUNNING` task.
+    fn complete(self) {
+        let header = self.header();
+        let snapshot = header.state.transition_to_complete();
+
+        if !snapshot.is_join_interested() {
+            // The `JoinHandle` was dropped, nobody reads the output.
+            // Safety: the task is complete without `JoinHandle`, the runtime
+            // has exclusive access to the stage.
+            unsafe { self.core().drop_output(header.id) };
+        } else if snapshot.is_join_waker_set() {
+            // Safety: `JOIN_WAKER` is set and the task is complete: the
+            // `JoinHandle` doesn't touch the waker until the runtime hands
+            // it back.
+            unsafe { self.trailer().wake_join() };
+
+            if !header
+                .state
+                .unset_join_waker_after_complete()
+                .is_join_interested()
+            {
+                // The `JoinHandle` was dropped meanwhile and left the waker
+                // to the runtime.
+                // Safety: `JOIN_WAKER` is unset and the `JoinHandle` is gone.
+                unsafe { self.trailer().set_waker(None) };
+            }
+        }
+
+        header.scheduler.hooks().terminate(&header.meta());
+
+        // The caller owns a reference too: releasing the one of the list of
+        // live tasks doesn't free the cell.
+        drop(header.scheduler.owned_tasks().remove(header.id));
+    }
+}
+
+impl RawTask {
+    /// Pushes the task into a run queue, handing over a reference owned by
+    /// the caller.
+    pub(super) fn schedule(self) {
+        // The scheduler is cloned first: once pushed, the task may complete
+        // and be freed by another thread.
+        let scheduler = self.header().scheduler.clone();
+        // Safety: the caller hands over its reference.
+        scheduler.schedule(Notified(unsafe { Task::from_raw(self) }));
+    }
+
+    /// Wakes the task.
+    pub(super) fn wake_by_ref(self) {
+        if self.header().state.transition_to_notified_by_ref() == TransitionToNotified::Submit {
+            self.ref_inc();
+            self.schedule();
+        }
+    }
+
+    /// Requests the task to be cancelled the next time it is polled.
+    pub(super) fn abort(self) {
+        if self.header().state.transition_to_notified_and_cancel() == TransitionToNotified::Submit {
+            self.ref_inc();
+            self.schedule();
+        }
+    }
+
+    pub(super) fn is_complete(self) -> bool {
+        self.header().state.load().is_complete()
+    }
+}
+
+/// Warns if the future returned `Pending` without keeping a clone of its
+/// waker, nor waking itself: nothing can wake the task anymore.
+fn check_waker_kept(header: &Header) {
+    // A waker consumed by `wake` is counted as dropped after the task is
+    // notified: seeing no waker, the notification is seen too.
+    if header.wakers.load(Acquire) != 0 || header.state.load().is_notified() {
+        return;
+    }
+
+    header.scheduler.record_lost_wakeup();
+
+    let id = header.id;
+    let spawned_at = header.spawned_at;
+
+    #[cfg(feature = "tracing")]
+    tracing::warn!(
+        %id,
+        name = header.name.as_deref(),
+        %spawned_at,
+        "task returned `Pending` without keeping its waker, it will never be polled again",
+    );
+
+    #[cfg(not(feature = "tracing"))]
+    eprintln!(
+        "warning: task {id} spawned at {spawned_at} returned `Pending` without keeping its \
+         waker, it will never be polled again",
+    );
+}
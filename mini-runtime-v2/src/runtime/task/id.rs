@@ -1,12 +1,42 @@
+use crate::runtime::context;
 use std::fmt;
 use std::num::NonZeroU64;
 use std::sync::atomic::AtomicU64;
 use std::sync::atomic::Ordering::Relaxed;
 
 /// An opaque ID that uniquely identifies a task relative to all other currently running tasks.
+///
+/// The ID of the currently running task can be retrieved with [`id()`] or
+/// [`try_id()`], and the ID of a spawned task with [`JoinHandle::id`]. This
+/// makes it possible to correlate logs and metrics to a specific task.
+///
+/// [`JoinHandle::id`]: crate::task::JoinHandle::id
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
 pub struct Id(pub(crate) NonZeroU64);
 
+/// Returns the [`Id`] of the currently running task.
+///
+/// # Panics
+///
+/// This function panics if called from outside a task. Please note that calls
+/// to `block_on` do not have task IDs, so the method will panic if called from
+/// within a call to `block_on`. For a version of this function that doesn't
+/// panic, see [`try_id()`].
+#[track_caller]
+pub fn id() -> Id {
+    context::current_task_id().expect("Can't get a task id when not inside a task")
+}
+
+/// Returns the [`Id`] of the currently running task, or `None` if called outside
+/// of a task.
+///
+/// This function is similar to [`id()`], except that it returns `None` rather
+/// than panicking if called outside of a task context.
+#[track_caller]
+pub fn try_id() -> Option<Id> {
+    context::current_task_id()
+}
+
 impl Id {
     pub(crate) fn next() -> Self {
         static NEXT_ID: AtomicU64 = AtomicU64::new(1);
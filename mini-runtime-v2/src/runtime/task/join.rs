@@ -1,16 +1,105 @@
+use crate::runtime::task::raw::RawTask;
+use crate::runtime::task::{Id, JoinError};
+use crate::task::AbortOnDropHandle;
+use std::fmt;
+use std::future::Future;
 use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 /// An owned permission to join on a task (await its termination).
 ///
+/// This can be thought of as the equivalent of `std::thread::JoinHandle` for
+/// a task rather than a thread. Dropping a `JoinHandle` *detaches* the task:
+/// it keeps running, but its output can no longer be retrieved. Use
+/// [`abort_on_drop`](JoinHandle::abort_on_drop) to cancel the task instead.
+///
 /// We are using PhantomData, which is a special marker type.
 /// PhantomData consumes no space, but simulates a field of the given type for the purpose
-/// of static analysis.
+/// of static analysis: the task cell is type-erased, the handle is what remembers `T`.
 pub struct JoinHandle<T> {
+    /// Owns a reference to the task.
+    raw: RawTask,
     _p: PhantomData<T>,
 }
 
+// Safety: the output is only moved out of the task, by the handle, and the
+// rest of the task is `Send` and `Sync`, see `Task`.
+unsafe impl<T: Send> Send for JoinHandle<T> {}
+unsafe impl<T: Send> Sync for JoinHandle<T> {}
+
+impl<T> Unpin for JoinHandle<T> {}
+
 impl<T> JoinHandle<T> {
-    pub fn new() -> JoinHandle<T> {
-        JoinHandle { _p: PhantomData }
+    /// # Safety
+    ///
+    /// The caller hands over a reference to a task whose output is a `T`.
+    pub(super) unsafe fn new(raw: RawTask) -> JoinHandle<T> {
+        JoinHandle {
+            raw,
+            _p: PhantomData,
+        }
+    }
+
+    /// Returns a [task ID] that uniquely identifies this task relative to other
+    /// currently spawned tasks.
+    ///
+    /// [task ID]: crate::task::Id
+    pub fn id(&self) -> Id {
+        self.raw.header().id
+    }
+
+    /// Abort the task associated with the handle.
+    ///
+    /// Awaiting a cancelled task might complete as usual if the task was
+    /// already completed at the time it was cancelled, but most likely it
+    /// will fail with a cancelled `JoinError`.
+    pub fn abort(&self) {
+        self.raw.abort();
+    }
+
+    /// Checks if the task associated with this `JoinHandle` has finished.
+    pub fn is_finished(&self) -> bool {
+        self.raw.is_complete()
+    }
+
+    /// Converts the handle into an [`AbortOnDropHandle`], which aborts the
+    /// task when dropped instead of detaching it.
+    pub fn abort_on_drop(self) -> AbortOnDropHandle<T> {
+        AbortOnDropHandle::new(self)
+    }
+}
+
+impl<T: 'static> Future for JoinHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let mut ret = Poll::Pending;
+
+        // Try to read the task output. If the task is not yet complete, the
+        // waker is stored and is notified once the task does complete.
+        // Safety: `ret` is a `Poll<Result<T, JoinError>>`, `T` being the
+        // output of the task, see `new`.
+        unsafe {
+            self.raw
+                .try_read_output(&mut ret as *mut Poll<Self::Output> as *mut (), cx.waker())
+        };
+
+        ret
+    }
+}
+
+impl<T> Drop for JoinHandle<T> {
+    fn drop(&mut self) {
+        self.raw.drop_join_handle();
+        self.raw.drop_reference();
+    }
+}
+
+impl<T> fmt::Debug for JoinHandle<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("JoinHandle")
+            .field("id", &self.id())
+            .finish()
     }
 }
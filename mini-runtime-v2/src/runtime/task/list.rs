@@ -0,0 +1,110 @@
+//! The list of the live tasks of a scheduler.
+//!
+//! The list is used to cancel the tasks that did not complete when the
+//! runtime shuts down, and by `Handle::dump`.
+//!
+//! The list owns a reference to each task, released when the task completes:
+//! a task whose `JoinHandle` and wakers are gone stays alive until the
+//! runtime shuts down and cancels it.
+
+use crate::runtime::task::{Id, Task};
+use crate::util::loom::sync::Mutex;
+use std::collections::HashMap;
+
+pub(crate) struct OwnedTasks {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    tasks: HashMap<Id, Task>,
+
+    /// Set once the runtime shuts down, no task can be added anymore.
+    closed: bool,
+}
+
+impl OwnedTasks {
+    pub(crate) fn new() -> OwnedTasks {
+        OwnedTasks {
+            inner: Mutex::new(Inner {
+                tasks: HashMap::new(),
+                closed: false,
+            }),
+        }
+    }
+
+    /// Adds a task to the list. Returns `false` if the list is closed, in
+    /// which case the caller must cancel the task.
+    pub(super) fn insert(&self, task: &Task) -> bool {
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.closed {
+            return false;
+        }
+
+        inner.tasks.insert(task.id(), task.clone());
+        true
+    }
+
+    /// Removes a completed task from the list. The reference is returned,
+    /// to be released outside of the lock.
+    pub(super) fn remove(&self, id: Id) -> Option<Task> {
+        self.inner.lock().unwrap().tasks.remove(&id)
+    }
+
+    /// Closes the list and cancels every task that did not complete yet.
+    ///
+    /// The futures are dropped on the current thread, except for the tasks
+    /// being polled by another thread (e.g. a running blocking task): the
+    /// poll completes on its own.
+    pub(crate) fn close_and_shutdown_all(&self) {
+        let live: Vec<Task> = {
+            let mut inner = self.inner.lock().unwrap();
+            inner.closed = true;
+            inner.tasks.drain().map(|(_, task)| task).collect()
+        };
+
+        // Outside of the lock: dropping a future may spawn or wake tasks.
+        for task in live {
+            task.raw.shutdown();
+        }
+    }
+
+    /// Returns a snapshot of the tasks that did not complete yet, in spawn
+    /// order.
+    #[cfg(feature = "task_dump")]
+    pub(crate) fn dump(&self) -> crate::runtime::dump::Dump {
+        use crate::runtime::dump::{self, Dump, TaskState};
+
+        let mut live: Vec<Task> = {
+            let inner = self.inner.lock().unwrap();
+            inner.tasks.values().cloned().collect()
+        };
+        // Ids are handed out in spawn order.
+        live.sort_by_key(|task| task.id().0);
+
+        let tasks = live
+            .iter()
+            .map(|task| {
+                let header = task.raw.header();
+                let snapshot = header.state.load();
+                let state = if snapshot.is_running() {
+                    TaskState::Running
+                } else if snapshot.is_notified() {
+                    TaskState::Notified
+                } else {
+                    TaskState::Idle
+                };
+
+                dump::Task::new(
+                    header.id,
+                    header.name.clone(),
+                    state,
+                    header.spawned_at,
+                    header.trace.clone(),
+                )
+            })
+            .collect();
+
+        Dump::new(tasks)
+    }
+}
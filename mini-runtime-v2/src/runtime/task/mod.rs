@@ -1,10 +1,152 @@
 //! The task module.
 //!
 //! The task module contains the code that manages spawned tasks and provides a
-//! safe API for the rest of the runtime to use. Each task in a runtime is
-//! stored in an `OwnedTasks` or `LocalOwnedTasks` object.
+//! safe API for the rest of the runtime to use. A task is a reference-counted
+//! [`Cell`](core::Cell), allocated once and freed by its last owner:
+//!
+//! - `Notified`: the task sits in a run queue and is ready to be polled;
+//! - wakers: created from the cell while it is polled, they turn into a new
+//!   `Notified` when woken;
+//! - `JoinHandle`: reads the output once the task is complete;
+//! - `OwnedTasks`: the list of the live tasks of the scheduler, which
+//!   releases the task once it is complete.
+mod core;
+
+mod error;
+pub use self::error::JoinError;
+
+mod harness;
+
 mod id;
-pub use id::Id;
+pub use id::{Id, id, try_id};
 
 mod join;
 pub use self::join::JoinHandle;
+
+mod list;
+pub(crate) use list::OwnedTasks;
+
+mod raw;
+use raw::RawTask;
+
+mod state;
+#[cfg(all(test, loom))]
+pub(crate) use state::{State, TransitionToIdle, TransitionToNotified, TransitionToRunning};
+
+mod waker;
+
+use crate::runtime::scheduler;
+use std::fmt;
+use std::future::Future;
+use std::panic::Location;
+
+/// An owned reference to a task.
+pub(crate) struct Task {
+    raw: RawTask,
+}
+
+// Safety: the future and its output are `Send`, see `new_task`, and the
+// header is only accessed through atomics and immutable fields.
+unsafe impl Send for Task {}
+unsafe impl Sync for Task {}
+
+impl Task {
+    /// # Safety
+    ///
+    /// The caller hands over a reference it owns.
+    unsafe fn from_raw(raw: RawTask) -> Task {
+        Task { raw }
+    }
+
+    fn id(&self) -> Id {
+        self.raw.header().id
+    }
+}
+
+impl Clone for Task {
+    fn clone(&self) -> Task {
+        self.raw.ref_inc();
+        Task { raw: self.raw }
+    }
+}
+
+impl Drop for Task {
+    fn drop(&mut self) {
+        self.raw.drop_reference();
+    }
+}
+
+/// A task was notified and is ready to be polled.
+pub(crate) struct Notified(Task);
+
+impl Notified {
+    /// Polls the task.
+    pub(crate) fn run(self) {
+        self.0.raw.poll();
+    }
+
+    /// Cancels the task without polling it, used when the scheduler shuts down.
+    pub(crate) fn shutdown(self) {
+        self.0.raw.shutdown();
+    }
+}
+
+impl fmt::Debug for Notified {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_tuple("Notified").field(&self.0.id()).finish()
+    }
+}
+
+/// What the spawner knows about a task before creating it.
+pub(crate) struct SpawnMeta {
+    pub(crate) id: Id,
+
+    /// Name given with `task::Builder::name`.
+    pub(crate) name: Option<Box<str>>,
+}
+
+impl SpawnMeta {
+    pub(crate) fn new(name: Option<&str>) -> SpawnMeta {
+        SpawnMeta {
+            id: Id::next(),
+            name: name.map(Box::from),
+        }
+    }
+
+    pub(crate) fn unnamed() -> SpawnMeta {
+        SpawnMeta::new(None)
+    }
+}
+
+/// Allocates a new task cell for `future`, bound to `scheduler`.
+///
+/// Returns the notified task, which must be pushed into a run queue by the
+/// caller, and the `JoinHandle` for the task output. The runtime's spawn hook
+/// is called before returning.
+///
+/// If the runtime is shutting down, the task is cancelled right away: the
+/// future is dropped without being polled.
+#[track_caller]
+pub(crate) fn new_task<T>(
+    future: T,
+    scheduler: scheduler::Handle,
+    meta: SpawnMeta,
+) -> (Notified, JoinHandle<T::Output>)
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    let raw = RawTask::new(future, scheduler, meta, Location::caller());
+    // Safety: a new task starts with a reference for the `Notified` and one
+    // for the `JoinHandle`.
+    let (notified, join) = unsafe { (Task::from_raw(raw), JoinHandle::new(raw)) };
+
+    let header = raw.header();
+    header.scheduler.hooks().spawn(&header.meta());
+
+    if !header.scheduler.owned_tasks().insert(&notified) {
+        raw.shutdown();
+    }
+
+    (Notified(notified), join)
+}
@@ -0,0 +1,182 @@
+//! Type-erased pointer to a task.
+//!
+//! The run queues hold tasks of every future type side by side: a
+//! [`RawTask`] is a pointer to the [`Header`] of the cell, and the operations
+//! that must know the future type go through the [`Vtable`] stored in the
+//! header, like a `Waker` goes through its `RawWakerVTable`.
+//!
+//! A `RawTask` doesn't own anything: the references to the task are counted
+//! in its state, and owned by the `Task`, `Notified` and `JoinHandle`
+//! wrappers and by the task's wakers. The last one to go frees the cell.
+
+use crate::runtime::task::core::{Cell, Header};
+use crate::runtime::task::harness::Harness;
+use crate::runtime::task::{SpawnMeta, scheduler};
+use std::future::Future;
+use std::panic::Location;
+use std::ptr::NonNull;
+use std::task::Waker;
+
+/// Operations of a task generic over its future.
+pub(crate) struct Vtable {
+    /// Polls the future, see `Harness::poll`.
+    poll: unsafe fn(NonNull<Header>),
+
+    /// Cancels the task if it is idle, see `Harness::shutdown`.
+    shutdown: unsafe fn(NonNull<Header>),
+
+    /// Moves the output into a `*mut Poll<Result<T::Output, JoinError>>` if
+    /// the task is complete, see `Harness::try_read_output`.
+    try_read_output: unsafe fn(NonNull<Header>, *mut (), &Waker),
+
+    /// Gives up the output and the join waker, see
+    /// `Harness::drop_join_handle`.
+    drop_join_handle: unsafe fn(NonNull<Header>),
+
+    /// Frees the cell.
+    dealloc: unsafe fn(NonNull<Header>),
+}
+
+/// Returns the vtable of the tasks running a `T`.
+pub(super) fn vtable<T>() -> &'static Vtable
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    &Vtable {
+        poll: poll::<T>,
+        shutdown: shutdown::<T>,
+        try_read_output: try_read_output::<T>,
+        drop_join_handle: drop_join_handle::<T>,
+        dealloc: dealloc::<T>,
+    }
+}
+
+/// A pointer to a task, not owning a reference.
+#[derive(Clone, Copy)]
+pub(crate) struct RawTask {
+    ptr: NonNull<Header>,
+}
+
+impl RawTask {
+    /// Allocates a task running `future`. The two references it starts with
+    /// are owned by the caller, see `State::new`.
+    pub(super) fn new<T>(
+        future: T,
+        scheduler: scheduler::Handle,
+        meta: SpawnMeta,
+        spawned_at: &'static Location<'static>,
+    ) -> RawTask
+    where
+        T: Future + Send + 'static,
+        T::Output: Send + 'static,
+    {
+        let cell = Box::into_raw(Cell::new(future, scheduler, meta, spawned_at));
+        // Safety: `Box::into_raw` never returns null.
+        let ptr = unsafe { NonNull::new_unchecked(cell.cast::<Header>()) };
+        RawTask { ptr }
+    }
+
+    /// # Safety
+    ///
+    /// `ptr` must point to the header of a live task.
+    pub(super) unsafe fn from_raw(ptr: NonNull<Header>) -> RawTask {
+        RawTask { ptr }
+    }
+
+    pub(super) fn header_ptr(self) -> NonNull<Header> {
+        self.ptr
+    }
+
+    pub(super) fn header(&self) -> &Header {
+        // Safety: the task is alive as long as a reference is owned, and a
+        // `RawTask` is only used by the owner of one.
+        unsafe { self.ptr.as_ref() }
+    }
+
+    pub(super) fn poll(self) {
+        // Safety: the vtable matches the cell.
+        unsafe { (self.header().vtable.poll)(self.ptr) }
+    }
+
+    pub(super) fn shutdown(self) {
+        // Safety: the vtable matches the cell.
+        unsafe { (self.header().vtable.shutdown)(self.ptr) }
+    }
+
+    /// # Safety
+    ///
+    /// `dst` must be a `*mut Poll<Result<T::Output, JoinError>>`, `T` being
+    /// the future of the task.
+    pub(super) unsafe fn try_read_output(self, dst: *mut (), waker: &Waker) {
+        // Safety: the vtable matches the cell, `dst` is guaranteed by the
+        // caller.
+        unsafe { (self.header().vtable.try_read_output)(self.ptr, dst, waker) }
+    }
+
+    pub(super) fn drop_join_handle(self) {
+        // Safety: the vtable matches the cell.
+        unsafe { (self.header().vtable.drop_join_handle)(self.ptr) }
+    }
+
+    /// Takes one more reference to the task.
+    pub(super) fn ref_inc(self) {
+        self.header().state.ref_inc();
+    }
+
+    /// Gives up a reference to the task, freeing the cell if it was the
+    /// last one.
+    pub(super) fn drop_reference(self) {
+        if self.header().state.ref_dec() {
+            // Safety: the vtable matches the cell, and nobody else owns a
+            // reference anymore.
+            unsafe { (self.header().vtable.dealloc)(self.ptr) }
+        }
+    }
+}
+
+unsafe fn poll<T>(ptr: NonNull<Header>)
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    // Safety: the vtable of a `Cell<T>` is only used with `T`.
+    unsafe { Harness::<T>::from_raw(ptr) }.poll();
+}
+
+unsafe fn shutdown<T>(ptr: NonNull<Header>)
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    // Safety: the vtable of a `Cell<T>` is only used with `T`.
+    unsafe { Harness::<T>::from_raw(ptr) }.shutdown();
+}
+
+unsafe fn try_read_output<T>(ptr: NonNull<Header>, dst: *mut (), waker: &Waker)
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    // Safety: the vtable of a `Cell<T>` is only used with `T`, and `dst` is
+    // guaranteed by the caller of `RawTask::try_read_output`.
+    unsafe { Harness::<T>::from_raw(ptr).try_read_output(dst.cast(), waker) };
+}
+
+unsafe fn drop_join_handle<T>(ptr: NonNull<Header>)
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    // Safety: the vtable of a `Cell<T>` is only used with `T`.
+    unsafe { Harness::<T>::from_raw(ptr) }.drop_join_handle();
+}
+
+unsafe fn dealloc<T>(ptr: NonNull<Header>)
+where
+    T: Future + Send + 'static,
+    T::Output: Send + 'static,
+{
+    // Safety: the cell was allocated by `RawTask::new` as a `Box<Cell<T>>`.
+    drop(unsafe { Box::from_raw(ptr.cast::<Cell<T>>().as_ptr()) });
+}
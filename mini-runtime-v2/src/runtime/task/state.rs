@@ -0,0 +1,438 @@
+//! The state of a task, packed in a single atomic word.
+//!
+//! Keeping every flag in one word lets a transition look at all of them at
+//! once: e.g. a wake can tell a task being polled, which must not be pushed
+//! into a run queue, from an idle one, which must, and set `NOTIFIED` in the
+//! same atomic operation. With one atomic per flag, the poll could end
+//! between the two loads and the wake would be lost.
+//!
+//! The word is laid out as `| ref count | CANCELLED | JOIN_WAKER |
+//! JOIN_INTEREST | NOTIFIED | COMPLETE | RUNNING |`, the lifecycle of a task
+//! being:
+//!
+//! ```text
+//!  spawn ──► NOTIFIED ──► RUNNING ──► idle ──► NOTIFIED ──► ...
+//!                            │
+//!                            └──► COMPLETE
+//! ```
+//!
+//! The transitions return what the caller must do next, e.g. push the task
+//! into a run queue or drop its output: the caller owns the outcome.
+
+use crate::util::loom::sync::atomic::AtomicUsize;
+use crate::util::loom::sync::atomic::Ordering::{AcqRel, Acquire};
+use std::fmt;
+
+/// The task is being polled, or cancelled by the shutdown of the runtime.
+const RUNNING: usize = 0b0001;
+
+/// The stage holds the output, the future has been dropped.
+const COMPLETE: usize = 0b0010;
+
+/// The task sits in a run queue, or will be pushed into one by the thread
+/// polling it once the poll ends.
+const NOTIFIED: usize = 0b0100;
+
+/// The `JoinHandle` is alive: the output must be kept for it.
+const JOIN_INTEREST: usize = 0b1000;
+
+/// The join waker is set. While this bit is unset, the `JoinHandle` has
+/// exclusive access to the join waker; once it is set, the runtime reads it
+/// when the task completes.
+const JOIN_WAKER: usize = 0b1_0000;
+
+/// The task was aborted, or the runtime is shutting down: the next poll
+/// drops the future instead.
+const CANCELLED: usize = 0b10_0000;
+
+/// The flags above, the reference count uses the remaining bits.
+const STATE_MASK: usize = RUNNING | COMPLETE | NOTIFIED | JOIN_INTEREST | JOIN_WAKER | CANCELLED;
+
+const REF_COUNT_SHIFT: u32 = STATE_MASK.count_ones();
+const REF_ONE: usize = 1 << REF_COUNT_SHIFT;
+
+/// A new task is notified, its `Notified` is pushed into a run queue, and its
+/// `JoinHandle` is alive: each owns a reference.
+const INITIAL_STATE: usize = (REF_ONE * 2) | NOTIFIED | JOIN_INTEREST;
+
+/// The state of a task.
+pub(crate) struct State {
+    val: AtomicUsize,
+}
+
+/// A copy of the state, as loaded or as returned by a transition.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Snapshot(usize);
+
+/// Outcome of `State::transition_to_running`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum TransitionToRunning {
+    /// Poll the future.
+    Success,
+    /// Drop the future and complete the task.
+    Cancelled,
+    /// The task is complete or already polled by another thread, drop the
+    /// `Notified`.
+    Failed,
+}
+
+/// Outcome of `State::transition_to_idle`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum TransitionToIdle {
+    /// The task waits for a wake.
+    Ok,
+    /// The task was woken while it was polled, push it into a run queue
+    /// again.
+    OkNotified,
+    /// The task was cancelled while it was polled, it is still `RUNNING`:
+    /// drop the future and complete the task.
+    Cancelled,
+}
+
+/// Outcome of `State::transition_to_notified_by_ref` and
+/// `State::transition_to_notified_and_cancel`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum TransitionToNotified {
+    /// Push the task into a run queue.
+    Submit,
+    /// The task is complete, already notified, or being polled: the thread
+    /// polling it takes care of the notification.
+    DoNothing,
+}
+
+/// Outcome of `State::transition_to_join_handle_dropped`.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct TransitionToJoinHandleDrop {
+    /// The `JoinHandle` owns the join waker, it must drop it.
+    pub(crate) drop_waker: bool,
+    /// The task is complete, the `JoinHandle` must drop the output.
+    pub(crate) drop_output: bool,
+}
+
+impl State {
+    pub(crate) fn new() -> State {
+        State {
+            val: AtomicUsize::new(INITIAL_STATE),
+        }
+    }
+
+    pub(crate) fn load(&self) -> Snapshot {
+        Snapshot(self.val.load(Acquire))
+    }
+
+    /// Calls `f` on the current state until it returns the next state and
+    /// the state is swapped for it, or until it returns `None`. Returns the
+    /// output of the last call.
+    fn fetch_update_action<T>(&self, mut f: impl FnMut(Snapshot) -> (T, Option<Snapshot>)) -> T {
+        let mut curr = self.load();
+
+        loop {
+            let (output, next) = f(curr);
+            let Some(next) = next else {
+                return output;
+            };
+
+            match self.val.compare_exchange(curr.0, next.0, AcqRel, Acquire) {
+                Ok(_) => return output,
+                Err(actual) => curr = Snapshot(actual),
+            }
+        }
+    }
+
+    /// Like `fetch_update_action`, but `f` only tells whether the transition
+    /// is allowed. Returns the previous state on success, the current one on
+    /// failure.
+    fn fetch_update(
+        &self,
+        mut f: impl FnMut(Snapshot) -> Option<Snapshot>,
+    ) -> Result<Snapshot, Snapshot> {
+        self.fetch_update_action(|curr| match f(curr) {
+            Some(next) => (Ok(curr), Some(next)),
+            None => (Err(curr), None),
+        })
+    }
+
+    /// Called by the scheduler before polling a notified task: sets
+    /// `RUNNING` and consumes the notification.
+    pub(crate) fn transition_to_running(&self) -> TransitionToRunning {
+        self.fetch_update_action(|mut next| {
+            debug_assert!(next.is_notified(), "polling a task that was not notified");
+
+            if !next.is_idle() {
+                return (TransitionToRunning::Failed, None);
+            }
+
+            next.set_running();
+            next.unset_notified();
+
+            let action = if next.is_cancelled() {
+                TransitionToRunning::Cancelled
+            } else {
+                TransitionToRunning::Success
+            };
+            (action, Some(next))
+        })
+    }
+
+    /// Called once the future returned `Pending`: unsets `RUNNING`, unless
+    /// the task was cancelled meanwhile.
+    pub(crate) fn transition_to_idle(&self) -> TransitionToIdle {
+        self.fetch_update_action(|mut next| {
+            assert!(next.is_running(), "task is not running");
+
+            if next.is_cancelled() {
+                return (TransitionToIdle::Cancelled, None);
+            }
+
+            next.unset_running();
+
+            let action = if next.is_notified() {
+                TransitionToIdle::OkNotified
+            } else {
+                TransitionToIdle::Ok
+            };
+            (action, Some(next))
+        })
+    }
+
+    /// Called once the stage holds the output: unsets `RUNNING` and sets
+    /// `COMPLETE`. Returns the new state, which tells whether the output
+    /// must be dropped or the join waker woken.
+    pub(crate) fn transition_to_complete(&self) -> Snapshot {
+        const DELTA: usize = RUNNING | COMPLETE;
+
+        let prev = Snapshot(self.val.fetch_xor(DELTA, AcqRel));
+        assert!(prev.is_running(), "task is not running");
+        assert!(!prev.is_complete(), "task is already complete");
+
+        Snapshot(prev.0 ^ DELTA)
+    }
+
+    /// Called by a waker: sets `NOTIFIED`, which the caller must turn into a
+    /// push into a run queue when the task is idle.
+    pub(crate) fn transition_to_notified_by_ref(&self) -> TransitionToNotified {
+        self.fetch_update_action(|mut next| {
+            if next.is_complete() || next.is_notified() {
+                return (TransitionToNotified::DoNothing, None);
+            }
+
+            let action = if next.is_running() {
+                // The thread polling the task sees the notification in
+                // `transition_to_idle`.
+                TransitionToNotified::DoNothing
+            } else {
+                TransitionToNotified::Submit
+            };
+
+            next.set_notified();
+            (action, Some(next))
+        })
+    }
+
+    /// Called by `JoinHandle::abort`: sets `CANCELLED`, and `NOTIFIED` for
+    /// the next poll to drop the future.
+    pub(crate) fn transition_to_notified_and_cancel(&self) -> TransitionToNotified {
+        self.fetch_update_action(|mut next| {
+            if next.is_cancelled() || next.is_complete() {
+                return (TransitionToNotified::DoNothing, None);
+            }
+
+            let action = if next.is_running() || next.is_notified() {
+                // The poll in progress, or the next one, sees `CANCELLED`.
+                TransitionToNotified::DoNothing
+            } else {
+                TransitionToNotified::Submit
+            };
+
+            next.set_cancelled();
+            next.set_notified();
+            (action, Some(next))
+        })
+    }
+
+    /// Called when the runtime shuts down: sets `CANCELLED`. Returns `true`
+    /// if the task was idle, in which case it is now `RUNNING` and the caller
+    /// must drop the future and complete the task. Otherwise the thread
+    /// polling the task does.
+    pub(crate) fn transition_to_shutdown(&self) -> bool {
+        let mut idle = false;
+
+        let _ = self.fetch_update(|mut next| {
+            idle = next.is_idle();
+            if idle {
+                next.set_running();
+            }
+            next.set_cancelled();
+            Some(next)
+        });
+
+        idle
+    }
+
+    /// Called by the `JoinHandle` once it stored the join waker. Fails, with
+    /// the waker still owned by the `JoinHandle`, if the task completed
+    /// meanwhile: the output can be read instead.
+    pub(crate) fn set_join_waker(&self) -> Result<Snapshot, Snapshot> {
+        self.fetch_update(|mut next| {
+            assert!(next.is_join_interested(), "no `JoinHandle`");
+            assert!(!next.is_join_waker_set(), "join waker already set");
+
+            if next.is_complete() {
+                return None;
+            }
+
+            next.set_join_waker();
+            Some(next)
+        })
+    }
+
+    /// Called by the `JoinHandle` to get back exclusive access to the join
+    /// waker, to replace it. Fails if the task completed meanwhile: the
+    /// runtime is about to wake the waker, the output can be read instead.
+    pub(crate) fn unset_join_waker(&self) -> Result<Snapshot, Snapshot> {
+        self.fetch_update(|mut next| {
+            assert!(next.is_join_interested(), "no `JoinHandle`");
+            assert!(next.is_join_waker_set(), "join waker is not set");
+
+            if next.is_complete() {
+                return None;
+            }
+
+            next.unset_join_waker();
+            Some(next)
+        })
+    }
+
+    /// Called by the runtime once it woke the join waker of a complete task:
+    /// hands the join waker back to the `JoinHandle`. Returns the new state,
+    /// if the `JoinHandle` was dropped meanwhile the runtime drops the waker.
+    pub(crate) fn unset_join_waker_after_complete(&self) -> Snapshot {
+        let prev = Snapshot(self.val.fetch_and(!JOIN_WAKER, AcqRel));
+        assert!(prev.is_complete(), "task is not complete");
+        assert!(prev.is_join_waker_set(), "join waker is not set");
+
+        Snapshot(prev.0 & !JOIN_WAKER)
+    }
+
+    /// Called when the `JoinHandle` is dropped: unsets `JOIN_INTEREST`, and
+    /// takes back the join waker unless the runtime is about to wake it.
+    pub(crate) fn transition_to_join_handle_dropped(&self) -> TransitionToJoinHandleDrop {
+        self.fetch_update_action(|curr| {
+            assert!(curr.is_join_interested(), "no `JoinHandle`");
+
+            let mut next = curr;
+            next.unset_join_interested();
+
+            if !curr.is_complete() {
+                next.unset_join_waker();
+            }
+
+            let transition = TransitionToJoinHandleDrop {
+                drop_waker: !next.is_join_waker_set(),
+                drop_output: curr.is_complete(),
+            };
+            (transition, Some(next))
+        })
+    }
+
+    /// Increments the reference count.
+    pub(crate) fn ref_inc(&self) {
+        let prev = self.val.fetch_add(REF_ONE, AcqRel);
+        assert!(prev <= isize::MAX as usize, "task reference count overflow");
+    }
+
+    /// Decrements the reference count. Returns `true` if it was the last
+    /// reference, in which case the caller must free the task.
+    pub(crate) fn ref_dec(&self) -> bool {
+        let prev = Snapshot(self.val.fetch_sub(REF_ONE, AcqRel));
+        assert!(prev.ref_count() >= 1, "task reference count underflow");
+        prev.ref_count() == 1
+    }
+}
+
+impl fmt::Debug for State {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.load().fmt(fmt)
+    }
+}
+
+impl Snapshot {
+    /// Neither polled nor complete.
+    pub(crate) fn is_idle(self) -> bool {
+        self.0 & (RUNNING | COMPLETE) == 0
+    }
+
+    pub(crate) fn is_running(self) -> bool {
+        self.0 & RUNNING == RUNNING
+    }
+
+    fn set_running(&mut self) {
+        self.0 |= RUNNING;
+    }
+
+    fn unset_running(&mut self) {
+        self.0 &= !RUNNING;
+    }
+
+    pub(crate) fn is_complete(self) -> bool {
+        self.0 & COMPLETE == COMPLETE
+    }
+
+    pub(crate) fn is_notified(self) -> bool {
+        self.0 & NOTIFIED == NOTIFIED
+    }
+
+    fn set_notified(&mut self) {
+        self.0 |= NOTIFIED;
+    }
+
+    fn unset_notified(&mut self) {
+        self.0 &= !NOTIFIED;
+    }
+
+    pub(crate) fn is_cancelled(self) -> bool {
+        self.0 & CANCELLED == CANCELLED
+    }
+
+    fn set_cancelled(&mut self) {
+        self.0 |= CANCELLED;
+    }
+
+    pub(crate) fn is_join_interested(self) -> bool {
+        self.0 & JOIN_INTEREST == JOIN_INTEREST
+    }
+
+    fn unset_join_interested(&mut self) {
+        self.0 &= !JOIN_INTEREST;
+    }
+
+    pub(crate) fn is_join_waker_set(self) -> bool {
+        self.0 & JOIN_WAKER == JOIN_WAKER
+    }
+
+    fn set_join_waker(&mut self) {
+        self.0 |= JOIN_WAKER;
+    }
+
+    fn unset_join_waker(&mut self) {
+        self.0 &= !JOIN_WAKER;
+    }
+
+    pub(crate) fn ref_count(self) -> usize {
+        self.0 >> REF_COUNT_SHIFT
+    }
+}
+
+impl fmt::Debug for Snapshot {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Snapshot")
+            .field("is_running", &self.is_running())
+            .field("is_complete", &self.is_complete())
+            .field("is_notified", &self.is_notified())
+            .field("is_cancelled", &self.is_cancelled())
+            .field("is_join_interested", &self.is_join_interested())
+            .field("is_join_waker_set", &self.is_join_waker_set())
+            .field("ref_count", &self.ref_count())
+            .finish()
+    }
+}
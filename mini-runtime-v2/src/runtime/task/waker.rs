@@ -0,0 +1,62 @@
+//! The waker of a task.
+//!
+//! Like the wakers of `util::wake`, but over a [`RawTask`] instead of an
+//! `Arc`: a clone takes a reference to the task and a drop gives it back.
+//! The wakers alive are counted too, for the lost wakeup detection.
+
+use crate::runtime::task::core::Header;
+use crate::runtime::task::raw::RawTask;
+use crate::util::WakerRef;
+use crate::util::loom::sync::atomic::Ordering::{Relaxed, Release};
+use std::ptr::NonNull;
+use std::task::{RawWaker, RawWakerVTable};
+
+static WAKER_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(clone_waker, wake_by_val, wake_by_ref, drop_waker);
+
+/// Returns the waker the task is polled with. It doesn't own a reference,
+/// only its clones do.
+pub(super) fn waker_ref(raw: &RawTask) -> WakerRef<'_> {
+    let ptr = raw.header_ptr().as_ptr().cast::<()>();
+    // Safety: the vtable functions expect a pointer to a task header, and
+    // the `WakerRef` is never dropped.
+    unsafe { WakerRef::from_raw(RawWaker::new(ptr, &WAKER_VTABLE)) }
+}
+
+/// # Safety
+///
+/// `ptr` must be the pointer to the header of a live task, see `waker_ref`.
+unsafe fn raw_task(ptr: *const ()) -> RawTask {
+    // Safety: guaranteed by the caller.
+    unsafe { RawTask::from_raw(NonNull::new_unchecked(ptr as *mut Header)) }
+}
+
+unsafe fn clone_waker(ptr: *const ()) -> RawWaker {
+    // Safety: the waker being cloned keeps the task alive.
+    let raw = unsafe { raw_task(ptr) };
+    raw.ref_inc();
+    raw.header().wakers.fetch_add(1, Relaxed);
+    RawWaker::new(ptr, &WAKER_VTABLE)
+}
+
+unsafe fn wake_by_val(ptr: *const ()) {
+    // Safety: the waker owns a reference.
+    let raw = unsafe { raw_task(ptr) };
+    // Counted as dropped once the task is notified: a task seeing no waker
+    // sees the notification too.
+    raw.wake_by_ref();
+    raw.header().wakers.fetch_sub(1, Release);
+    raw.drop_reference();
+}
+
+unsafe fn wake_by_ref(ptr: *const ()) {
+    // Safety: the waker keeps the task alive.
+    unsafe { raw_task(ptr) }.wake_by_ref();
+}
+
+unsafe fn drop_waker(ptr: *const ()) {
+    // Safety: the waker owns a reference.
+    let raw = unsafe { raw_task(ptr) };
+    raw.header().wakers.fetch_sub(1, Release);
+    raw.drop_reference();
+}
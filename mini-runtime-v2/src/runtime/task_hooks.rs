@@ -0,0 +1,93 @@
+use crate::runtime::task::Id;
+use std::fmt;
+use std::marker::PhantomData;
+use std::panic::{self, Location};
+use std::sync::Arc;
+
+/// Callback registered with [`Builder::on_task_spawn`] or
+/// [`Builder::on_task_terminate`].
+///
+/// [`Builder::on_task_spawn`]: crate::runtime::Builder::on_task_spawn
+/// [`Builder::on_task_terminate`]: crate::runtime::Builder::on_task_terminate
+pub(crate) type TaskCallback = Arc<dyn Fn(&TaskMeta<'_>) + Send + Sync>;
+
+/// Task metadata passed to the task hooks.
+///
+/// The lifetime prevents the hooks from keeping the metadata around: it is
+/// only valid for the duration of the call.
+pub struct TaskMeta<'a> {
+    pub(crate) id: Id,
+    pub(crate) name: Option<&'a str>,
+    pub(crate) spawned_at: &'static Location<'static>,
+    pub(crate) _phantom: PhantomData<&'a ()>,
+}
+
+impl TaskMeta<'_> {
+    /// Returns the ID of the task.
+    pub fn id(&self) -> Id {
+        self.id
+    }
+
+    /// Returns the name of the task, if it was spawned with one using
+    /// [`task::Builder::name`].
+    ///
+    /// [`task::Builder::name`]: crate::task::Builder::name
+    pub fn name(&self) -> Option<&str> {
+        self.name
+    }
+
+    /// Returns the location in the source code the task was spawned from,
+    /// e.g. the caller of `task::spawn`.
+    pub fn spawned_at(&self) -> &'static Location<'static> {
+        self.spawned_at
+    }
+}
+
+impl fmt::Debug for TaskMeta<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("TaskMeta")
+            .field("id", &self.id)
+            .field("name", &self.name)
+            .field("spawned_at", &self.spawned_at)
+            .finish()
+    }
+}
+
+/// The task hooks of a runtime, shared by all its tasks.
+#[derive(Clone, Default)]
+pub(crate) struct TaskHooks {
+    pub(crate) task_spawn_callback: Option<TaskCallback>,
+    pub(crate) task_terminate_callback: Option<TaskCallback>,
+}
+
+impl TaskHooks {
+    /// Called once a task is created, before it is first scheduled.
+    pub(crate) fn spawn(&self, meta: &TaskMeta<'_>) {
+        if let Some(f) = &self.task_spawn_callback {
+            f(meta);
+        }
+    }
+
+    /// Called once a task completed, was cancelled or panicked, after its
+    /// `JoinHandle` was notified.
+    ///
+    /// A panic of the hook is swallowed: the task already completed, there
+    /// is nobody to report it to.
+    pub(crate) fn terminate(&self, meta: &TaskMeta<'_>) {
+        if let Some(f) = &self.task_terminate_callback {
+            let _ = panic::catch_unwind(panic::AssertUnwindSafe(|| f(meta)));
+        }
+    }
+}
+
+impl fmt::Debug for TaskHooks {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("TaskHooks")
+            .field("task_spawn_callback", &self.task_spawn_callback.is_some())
+            .field(
+                "task_terminate_callback",
+                &self.task_terminate_callback.is_some(),
+            )
+            .finish()
+    }
+}
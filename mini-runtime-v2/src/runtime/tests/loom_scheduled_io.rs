@@ -0,0 +1,33 @@
+use crate::runtime::io::{Direction, Ready, ScheduledIo};
+use loom::sync::Arc;
+use loom::thread;
+use std::task::{Context, Poll, Waker};
+
+/// Clearing the readiness a task observed must not clear the readiness set
+/// concurrently by a later driver tick, or the event is lost.
+#[test]
+fn clear_readiness_keeps_newer_event() {
+    loom::model(|| {
+        let io = Arc::new(ScheduledIo::new());
+        let mut cx = Context::from_waker(Waker::noop());
+
+        io.set_readiness(1, Ready::READABLE);
+        let Poll::Ready(event) = io.poll_readiness(&mut cx, Direction::Read) else {
+            panic!("the resource is readable");
+        };
+
+        let driver = {
+            let io = io.clone();
+            thread::spawn(move || io.set_readiness(2, Ready::READABLE))
+        };
+
+        // The read returned `WouldBlock`.
+        io.clear_readiness(event);
+        driver.join().unwrap();
+
+        let Poll::Ready(event) = io.poll_readiness(&mut cx, Direction::Read) else {
+            panic!("the event of tick 2 was lost");
+        };
+        assert_eq!(event.ready, Ready::READABLE);
+    });
+}
@@ -0,0 +1,121 @@
+use crate::runtime::task::{State, TransitionToIdle, TransitionToNotified, TransitionToRunning};
+use loom::sync::Arc;
+use loom::thread;
+
+/// A wake while the task is polled must not be lost: either the waker
+/// pushes the task into a run queue, or the thread polling it does once the
+/// poll ends, never both. A wake racing with the start of the poll may also
+/// be merged into the notification the poll consumes.
+#[test]
+fn wake_during_poll_is_not_lost() {
+    loom::model(|| {
+        let state = Arc::new(State::new());
+
+        let waker = {
+            let state = state.clone();
+            thread::spawn(move || state.transition_to_notified_by_ref())
+        };
+
+        assert_eq!(state.transition_to_running(), TransitionToRunning::Success);
+        let idle = state.transition_to_idle();
+        let wake = waker.join().unwrap();
+
+        let submitted = wake == TransitionToNotified::Submit;
+        let rescheduled = idle == TransitionToIdle::OkNotified;
+        assert!(!(submitted && rescheduled), "the task was scheduled twice");
+        assert_eq!(state.load().is_notified(), submitted || rescheduled);
+    });
+}
+
+/// An abort racing with a poll is observed exactly once: by the start of
+/// the poll, by its end, or by a new run of the task.
+#[test]
+fn abort_during_poll_is_observed_once() {
+    loom::model(|| {
+        let state = Arc::new(State::new());
+
+        let abort = {
+            let state = state.clone();
+            thread::spawn(move || state.transition_to_notified_and_cancel())
+        };
+
+        let observed = match state.transition_to_running() {
+            TransitionToRunning::Cancelled => 1,
+            TransitionToRunning::Success => match state.transition_to_idle() {
+                TransitionToIdle::Cancelled => 1,
+                _ => 0,
+            },
+            TransitionToRunning::Failed => panic!("the task is idle"),
+        };
+        let submitted = abort.join().unwrap() == TransitionToNotified::Submit;
+
+        assert_eq!(observed + usize::from(submitted), 1);
+        assert!(state.load().is_cancelled());
+    });
+}
+
+/// The runtime wakes the join waker if, and only if, the `JoinHandle`
+/// managed to hand it over before the task completed.
+#[test]
+fn join_waker_is_woken_iff_set_before_complete() {
+    loom::model(|| {
+        let state = Arc::new(State::new());
+        assert_eq!(state.transition_to_running(), TransitionToRunning::Success);
+
+        let join = {
+            let state = state.clone();
+            thread::spawn(move || state.set_join_waker().is_ok())
+        };
+
+        let snapshot = state.transition_to_complete();
+        let set = join.join().unwrap();
+
+        assert_eq!(snapshot.is_join_waker_set(), set);
+    });
+}
+
+/// The output is dropped exactly once, by the runtime if the `JoinHandle`
+/// was dropped first, by the `JoinHandle` otherwise. The join waker is
+/// dropped exactly once too.
+#[test]
+fn output_and_join_waker_dropped_once() {
+    loom::model(|| {
+        let state = Arc::new(State::new());
+        assert_eq!(state.transition_to_running(), TransitionToRunning::Success);
+        state.set_join_waker().unwrap();
+
+        let join = {
+            let state = state.clone();
+            thread::spawn(move || state.transition_to_join_handle_dropped())
+        };
+
+        let snapshot = state.transition_to_complete();
+        let runtime_drops_output = !snapshot.is_join_interested();
+        let runtime_drops_waker = snapshot.is_join_waker_set()
+            && !state.unset_join_waker_after_complete().is_join_interested();
+        let join = join.join().unwrap();
+
+        assert_ne!(runtime_drops_output, join.drop_output);
+        assert_ne!(runtime_drops_waker, join.drop_waker);
+    });
+}
+
+/// Exactly one of the threads dropping the last references frees the task.
+#[test]
+fn last_ref_dec_frees_once() {
+    loom::model(|| {
+        // The `Notified` and the `JoinHandle`.
+        let state = Arc::new(State::new());
+
+        let other = {
+            let state = state.clone();
+            thread::spawn(move || state.ref_dec())
+        };
+
+        let freed = state.ref_dec();
+        let other_freed = other.join().unwrap();
+
+        assert_ne!(freed, other_freed);
+        assert_eq!(state.load().ref_count(), 0);
+    });
+}
@@ -0,0 +1,15 @@
+use crate::runtime::ThreadId;
+use loom::thread;
+
+/// Concurrent calls to `ThreadId::next` must never hand out the same ID twice.
+#[test]
+fn next_is_unique_across_threads() {
+    loom::model(|| {
+        let th = thread::spawn(ThreadId::next);
+
+        let mine = ThreadId::next();
+        let theirs = th.join().unwrap();
+
+        assert_ne!(mine, theirs);
+    });
+}
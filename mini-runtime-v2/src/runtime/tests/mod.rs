@@ -0,0 +1,3 @@
+mod loom_scheduled_io;
+mod loom_task_state;
+mod loom_thread_id;
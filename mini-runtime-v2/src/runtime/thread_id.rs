@@ -0,0 +1,62 @@
+use std::num::NonZeroU64;
+
+/// An opaque ID that uniquely identifies a thread which has touched the runtime.
+///
+/// Unlike `std::thread::ThreadId`, the value is a plain `NonZeroU64`, so it can
+/// be stored in atomics and compared without going through the standard library.
+///
+/// The ID of the thread driving a worker is returned by
+/// [`RuntimeMetrics::worker_thread_id`].
+///
+/// [`RuntimeMetrics::worker_thread_id`]: crate::runtime::RuntimeMetrics::worker_thread_id
+#[derive(Eq, PartialEq, Clone, Copy, Hash, Debug)]
+pub struct ThreadId(NonZeroU64);
+
+impl ThreadId {
+    /// Generates the next unique thread ID.
+    ///
+    /// A `compare_exchange_weak` loop is used instead of `fetch_add`, so the
+    /// counter never wraps around and hands out an ID that is already in use.
+    pub(crate) fn next() -> Self {
+        use crate::util::loom::sync::atomic::AtomicU64;
+        use crate::util::loom::sync::atomic::Ordering::Relaxed;
+
+        #[cfg(not(all(test, loom)))]
+        static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+        // Loom atomics can't be created in a `const` context, and the counter
+        // must be reset between the executions of a model.
+        #[cfg(all(test, loom))]
+        loom::lazy_static! {
+            static ref NEXT_ID: AtomicU64 = AtomicU64::new(0);
+        }
+
+        let mut last = NEXT_ID.load(Relaxed);
+        loop {
+            let id = match last.checked_add(1) {
+                Some(id) => id,
+                None => exhausted(),
+            };
+
+            match NEXT_ID.compare_exchange_weak(last, id, Relaxed, Relaxed) {
+                Ok(_) => return ThreadId(NonZeroU64::new(id).unwrap()),
+                Err(id) => last = id,
+            }
+        }
+    }
+
+    /// Returns the ID as a number, unique among the threads of the process.
+    pub fn as_u64(&self) -> NonZeroU64 {
+        self.0
+    }
+
+    /// Restores an ID stored with `as_u64`, `0` standing for no ID.
+    pub(crate) fn from_u64(id: u64) -> Option<ThreadId> {
+        NonZeroU64::new(id).map(ThreadId)
+    }
+}
+
+#[cold]
+fn exhausted() -> ! {
+    panic!("failed to generate unique thread ID: bitspace exhausted")
+}
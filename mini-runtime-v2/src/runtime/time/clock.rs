@@ -0,0 +1,79 @@
+//! Source of time for the time driver.
+//!
+//! The clock follows the wall clock until it is paused. A paused clock only
+//! moves forward when [`advance`] is called, or when the runtime has nothing
+//! else to do than waiting for the next timer: the driver then jumps straight
+//! to its deadline instead of sleeping. Tests using timers become instant and
+//! deterministic.
+//!
+//! [`advance`]: crate::time::advance
+
+use crate::util::loom::sync::Mutex;
+use std::time::{Duration, Instant};
+
+pub(crate) struct Clock {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    /// Time of the clock when it was last paused or advanced.
+    base: Instant,
+
+    /// Instant at which the clock was last unfrozen, `None` while paused.
+    unfrozen: Option<Instant>,
+}
+
+impl Clock {
+    pub(crate) fn new(start_paused: bool) -> Clock {
+        let now = Instant::now();
+
+        Clock {
+            inner: Mutex::new(Inner {
+                base: now,
+                unfrozen: if start_paused { None } else { Some(now) },
+            }),
+        }
+    }
+
+    pub(crate) fn now(&self) -> Instant {
+        self.inner.lock().unwrap().now()
+    }
+
+    pub(crate) fn is_paused(&self) -> bool {
+        self.inner.lock().unwrap().unfrozen.is_none()
+    }
+
+    #[track_caller]
+    pub(crate) fn pause(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        assert!(inner.unfrozen.is_some(), "time is already frozen");
+
+        inner.base = inner.now();
+        inner.unfrozen = None;
+    }
+
+    #[track_caller]
+    pub(crate) fn resume(&self) {
+        let mut inner = self.inner.lock().unwrap();
+        assert!(inner.unfrozen.is_none(), "time is not frozen");
+
+        inner.unfrozen = Some(Instant::now());
+    }
+
+    #[track_caller]
+    pub(crate) fn advance(&self, duration: Duration) {
+        let mut inner = self.inner.lock().unwrap();
+        assert!(inner.unfrozen.is_none(), "time is not frozen");
+
+        inner.base += duration;
+    }
+}
+
+impl Inner {
+    fn now(&self) -> Instant {
+        match self.unfrozen {
+            Some(unfrozen) => self.base + unfrozen.elapsed(),
+            None => self.base,
+        }
+    }
+}
@@ -0,0 +1,276 @@
+//! Time driver.
+//!
+//! Timers are kept in a `BTreeMap` ordered by deadline. Before parking, the
+//! driver computes how long it may sleep until the earliest deadline and
+//! parks the I/O stack with that timeout; once unparked, it wakes the tasks
+//! whose deadline elapsed.
+//!
+//! A real runtime uses a hierarchical timer wheel to get O(1) insertion and
+//! removal, an ordered map is good enough here and much easier to follow.
+//!
+//! Deadlines are compared against the runtime's [`Clock`], which can be
+//! paused for tests.
+//!
+//! With a timer resolution, see `Builder::timer_resolution`, the deadlines
+//! are rounded up to the next multiple of the resolution since the driver
+//! was created. The timers due within the same slot then share a deadline:
+//! they fire together, in a single wakeup of the driver, instead of one
+//! wakeup each.
+
+mod clock;
+pub(crate) use clock::Clock;
+
+use crate::runtime::driver::{self, IoStack};
+use crate::util::loom::sync::Mutex;
+use crate::util::loom::sync::atomic::AtomicU64;
+use crate::util::loom::sync::atomic::Ordering::Relaxed;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::task::Waker;
+use std::time::{Duration, Instant};
+
+/// Time implementation that drives [`Sleep`][sleep] and [`Timeout`][timeout].
+///
+/// [sleep]: crate::time::Sleep
+/// [timeout]: crate::time::Timeout
+#[derive(Debug)]
+pub(crate) struct Driver {
+    /// Parker to delegate to.
+    park: IoStack,
+}
+
+/// Handle to the time driver, shared with the timers.
+pub(crate) struct Handle {
+    inner: Mutex<Inner>,
+
+    /// Source of time, possibly paused.
+    clock: Clock,
+
+    /// Granularity of the deadlines, `Duration::ZERO` to keep them exact.
+    resolution: Duration,
+
+    /// Instant the deadlines are rounded from.
+    origin: Instant,
+
+    /// Number of timers fired.
+    fired_count: AtomicU64,
+
+    /// Number of times the driver fired at least one timer.
+    tick_count: AtomicU64,
+}
+
+struct Inner {
+    /// Registered timers, the first entry is the next one to fire.
+    entries: BTreeMap<TimerKey, Waker>,
+
+    /// Used to tell apart timers with the same deadline.
+    next_id: u64,
+}
+
+/// Identifies a timer registered with the driver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct TimerKey {
+    deadline: Instant,
+    id: u64,
+}
+
+impl Driver {
+    /// Creates a new `Driver` instance that uses `park` to block the current
+    /// thread.
+    pub(crate) fn new(park: IoStack, clock: Clock, resolution: Duration) -> (Driver, Handle) {
+        let handle = Handle {
+            inner: Mutex::new(Inner {
+                entries: BTreeMap::new(),
+                next_id: 0,
+            }),
+            origin: clock.now(),
+            clock,
+            resolution,
+            fired_count: AtomicU64::new(0),
+            tick_count: AtomicU64::new(0),
+        };
+
+        (Driver { park }, handle)
+    }
+
+    pub(crate) fn park(&mut self, handle: &driver::Handle) {
+        self.park_internal(handle, None);
+    }
+
+    pub(crate) fn park_timeout(&mut self, handle: &driver::Handle, duration: Duration) {
+        self.park_internal(handle, Some(duration));
+    }
+
+    fn park_internal(&mut self, rt_handle: &driver::Handle, limit: Option<Duration>) {
+        let handle = rt_handle.time();
+
+        let next_timer = handle
+            .next_deadline()
+            .map(|deadline| deadline.saturating_duration_since(handle.now()));
+
+        let timeout = match next_timer {
+            Some(duration) => Some(limit.map_or(duration, |limit| limit.min(duration))),
+            None => limit,
+        };
+
+        match timeout {
+            // A paused clock doesn't move while the thread sleeps: only check
+            // for events, then jump to the next deadline if the scheduler
+            // was going to wait for it.
+            Some(duration) if handle.clock.is_paused() => {
+                self.park.park_timeout(rt_handle, Duration::ZERO);
+
+                if limit.is_none() {
+                    handle.clock.advance(duration);
+                }
+            }
+            Some(duration) => self.park.park_timeout(rt_handle, duration),
+            None => self.park.park(rt_handle),
+        }
+
+        // Process pending timers after waking up
+        handle.process(handle.now());
+    }
+}
+
+impl Handle {
+    /// Returns the current time of the runtime's clock.
+    pub(crate) fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    pub(crate) fn clock(&self) -> &Clock {
+        &self.clock
+    }
+
+    /// Returns the number of timers fired.
+    pub(crate) fn fired_count(&self) -> u64 {
+        self.fired_count.load(Relaxed)
+    }
+
+    /// Returns the number of times the driver fired at least one timer.
+    pub(crate) fn tick_count(&self) -> u64 {
+        self.tick_count.load(Relaxed)
+    }
+
+    /// Rounds `deadline` up to the timer resolution. A timer never fires
+    /// before its deadline, at most one resolution after.
+    fn round(&self, deadline: Instant) -> Instant {
+        if self.resolution.is_zero() {
+            return deadline;
+        }
+
+        let since = deadline.saturating_duration_since(self.origin).as_nanos();
+        let resolution = self.resolution.as_nanos();
+        let slots = since.div_ceil(resolution);
+
+        u64::try_from(slots * resolution)
+            .ok()
+            .and_then(|nanos| self.origin.checked_add(Duration::from_nanos(nanos)))
+            .unwrap_or(deadline)
+    }
+
+    /// Registers `waker` to be woken at `deadline`.
+    ///
+    /// `entry` holds the key of the timer if it was already registered, in
+    /// which case only the waker is updated. Returns `true` if the timer is now
+    /// the earliest one: the driver may be parked with a longer timeout and
+    /// must be unparked to take it into account.
+    pub(crate) fn register(
+        &self,
+        entry: &mut Option<TimerKey>,
+        deadline: Instant,
+        waker: &Waker,
+    ) -> bool {
+        let deadline = self.round(deadline);
+        let mut inner = self.inner.lock().unwrap();
+
+        if let Some(key) = entry
+            && key.deadline == deadline
+            && let Some(current) = inner.entries.get_mut(key)
+        {
+            if !current.will_wake(waker) {
+                *current = waker.clone();
+            }
+            return false;
+        }
+
+        if let Some(key) = entry.take() {
+            inner.entries.remove(&key);
+        }
+
+        let key = TimerKey {
+            deadline,
+            id: inner.next_id,
+        };
+        inner.next_id += 1;
+        inner.entries.insert(key, waker.clone());
+        *entry = Some(key);
+
+        inner.entries.first_key_value().map(|(first, _)| *first) == Some(key)
+    }
+
+    /// Moves the timer `entry` to `deadline`, keeping its waker.
+    ///
+    /// Does nothing if the timer is not registered. A timer that already
+    /// fired is forgotten: it is registered again on the next poll. Returns
+    /// `true` if the timer is now the earliest one, see `register`.
+    pub(crate) fn reset(&self, entry: &mut Option<TimerKey>, deadline: Instant) -> bool {
+        let Some(key) = entry.take() else {
+            return false;
+        };
+
+        let mut inner = self.inner.lock().unwrap();
+        let Some(waker) = inner.entries.remove(&key) else {
+            return false;
+        };
+
+        // The id stays the same: the timer keeps its identity.
+        let key = TimerKey {
+            deadline: self.round(deadline),
+            ..key
+        };
+        inner.entries.insert(key, waker);
+        *entry = Some(key);
+
+        inner.entries.first_key_value().map(|(first, _)| *first) == Some(key)
+    }
+
+    /// Removes the timer, if it didn't fire yet.
+    pub(crate) fn deregister(&self, key: TimerKey) {
+        self.inner.lock().unwrap().entries.remove(&key);
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        let inner = self.inner.lock().unwrap();
+        inner.entries.first_key_value().map(|(key, _)| key.deadline)
+    }
+
+    /// Wakes the timers whose deadline is `now` or earlier.
+    fn process(&self, now: Instant) {
+        let expired = {
+            let mut inner = self.inner.lock().unwrap();
+            let pending = inner.entries.split_off(&TimerKey {
+                deadline: now,
+                id: u64::MAX,
+            });
+            std::mem::replace(&mut inner.entries, pending)
+        };
+
+        if !expired.is_empty() {
+            self.fired_count.fetch_add(expired.len() as u64, Relaxed);
+            self.tick_count.fetch_add(1, Relaxed);
+        }
+
+        // Wake outside of the lock: a woken task may register a new timer.
+        for (_, waker) in expired {
+            waker.wake();
+        }
+    }
+}
+
+impl fmt::Debug for Handle {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("time::Handle").finish()
+    }
+}
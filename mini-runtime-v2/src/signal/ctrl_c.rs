@@ -0,0 +1,38 @@
+use crate::signal::unix::{SignalKind, signal};
+use std::io;
+
+/// Completes when a "ctrl-c" notification is sent to the process.
+///
+/// While signals are handled very differently between Unix and Windows, both
+/// platforms support receiving a signal on "ctrl-c". This function provides a
+/// portable API for receiving this notification; on Unix it listens for
+/// `SIGINT`.
+///
+/// Once the returned future is polled, a listener is registered. The future
+/// will complete on the first received `ctrl-c` **after** the initial call to
+/// either `Future::poll` or `.await`.
+///
+/// # Caveats
+///
+/// On Unix platforms, the first time that a `Signal` instance is registered
+/// for a particular signal kind, an OS signal-handler is installed which
+/// replaces the default platform behavior when that signal is received,
+/// **for the duration of the entire process**.
+///
+/// For example, Unix systems will terminate a process by default when it
+/// receives `SIGINT`. But, when a `Signal` instance is created to listen for
+/// this signal, the next `SIGINT` that arrives will be translated to a stream
+/// event, and the process will continue to execute. **Even if this `Signal`
+/// instance is dropped, subsequent `SIGINT` deliveries will end up captured by
+/// the runtime, and the default platform behavior will NOT be reset**.
+///
+/// # Panics
+///
+/// This function panics if called outside of a Mini runtime or if the
+/// runtime was built without [`enable_io`].
+///
+/// [`enable_io`]: crate::runtime::Builder::enable_io
+pub async fn ctrl_c() -> io::Result<()> {
+    signal(SignalKind::interrupt())?.recv().await;
+    Ok(())
+}
@@ -0,0 +1,30 @@
+//! Asynchronous signal handling for the Mini runtime.
+//!
+//! Note that signal handling is in general a very tricky topic and should be
+//! used with great care. This crate attempts to implement 'best practice' for
+//! signal handling, but it should be evaluated for your own applications'
+//! needs to see if it's suitable.
+//!
+//! Signals are delivered through the runtime's I/O driver, so the runtime
+//! must be built with [`enable_io`].
+//!
+//! ```no_run
+//! use mini_runtime_v2::signal;
+//! # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+//! # rt.block_on(async {
+//!
+//! signal::ctrl_c().await?;
+//! println!("ctrl-c received!");
+//! # Ok::<_, std::io::Error>(())
+//! # })?;
+//! # Ok::<_, std::io::Error>(())
+//! ```
+//!
+//! [`enable_io`]: crate::runtime::Builder::enable_io
+
+mod ctrl_c;
+pub use ctrl_c::ctrl_c;
+
+pub(crate) mod registry;
+
+pub mod unix;
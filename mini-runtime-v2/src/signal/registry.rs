@@ -0,0 +1,149 @@
+//! Process-wide signal state.
+//!
+//! Signal handlers are installed once per process and outlive any runtime,
+//! so the state they touch is a lazily initialized global. It uses `std`
+//! synchronization directly, the signal machinery is not modeled under loom.
+
+use libc::c_int;
+use std::os::unix::net::UnixStream;
+use std::sync::atomic::Ordering::{AcqRel, Acquire, SeqCst};
+use std::sync::atomic::{AtomicBool, AtomicUsize};
+use std::sync::{Mutex, Once, OnceLock};
+use std::task::Waker;
+
+pub(crate) struct Globals {
+    /// Sender half of the self-pipe, written from the signal handler.
+    pub(crate) sender: UnixStream,
+
+    /// Receiver half of the self-pipe, every runtime's signal driver
+    /// registers a clone of it with its I/O driver.
+    pub(crate) receiver: UnixStream,
+
+    /// Per signal number state.
+    registry: Box<[EventInfo]>,
+}
+
+pub(crate) struct EventInfo {
+    /// Set by the signal handler, cleared by `broadcast`.
+    pending: AtomicBool,
+
+    /// Guards the one-time installation of the OS signal handler.
+    pub(crate) init: Once,
+
+    /// Whether the OS signal handler was installed successfully.
+    pub(crate) initialized: AtomicBool,
+
+    /// Incremented every time the signal is broadcast. Listeners compare it
+    /// with the last value they observed.
+    generation: AtomicUsize,
+
+    /// Tasks waiting for the next delivery of the signal.
+    waiters: Mutex<Vec<Waker>>,
+}
+
+impl Globals {
+    /// Marks the signal as pending. Called from the signal handler, so it
+    /// must stay async-signal-safe: a single atomic store.
+    pub(crate) fn record_event(&self, signum: c_int) {
+        if let Some(info) = self.event_info(signum) {
+            info.pending.store(true, SeqCst);
+        }
+    }
+
+    pub(crate) fn event_info(&self, signum: c_int) -> Option<&EventInfo> {
+        usize::try_from(signum)
+            .ok()
+            .and_then(|i| self.registry.get(i))
+    }
+
+    /// Wakes the listeners of every signal received since the last call.
+    pub(crate) fn broadcast(&self) {
+        for info in self.registry.iter() {
+            if !info.pending.swap(false, SeqCst) {
+                continue;
+            }
+
+            let waiters = {
+                let mut waiters = info.waiters.lock().unwrap();
+                info.generation.fetch_add(1, AcqRel);
+                std::mem::take(&mut *waiters)
+            };
+
+            waiters.into_iter().for_each(Waker::wake);
+        }
+    }
+
+    /// Wakes the listeners of every signal, whether it was received or not.
+    pub(crate) fn wake_all(&self) {
+        for info in self.registry.iter() {
+            let waiters = std::mem::take(&mut *info.waiters.lock().unwrap());
+            waiters.into_iter().for_each(Waker::wake);
+        }
+    }
+}
+
+impl EventInfo {
+    fn new() -> EventInfo {
+        EventInfo {
+            pending: AtomicBool::new(false),
+            init: Once::new(),
+            initialized: AtomicBool::new(false),
+            generation: AtomicUsize::new(0),
+            waiters: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the number of times the signal was broadcast so far.
+    pub(crate) fn generation(&self) -> usize {
+        self.generation.load(Acquire)
+    }
+
+    /// Stores the waker to notify on the next broadcast, unless the signal
+    /// was broadcast since `seen`, in which case the new generation is
+    /// returned.
+    pub(crate) fn register_waker(&self, seen: usize, waker: &Waker) -> Option<usize> {
+        let mut waiters = self.waiters.lock().unwrap();
+
+        // `broadcast` bumps the generation while holding the lock, so the
+        // check can't race with it.
+        let generation = self.generation();
+        if generation != seen {
+            return Some(generation);
+        }
+
+        if !waiters.iter().any(|w| w.will_wake(waker)) {
+            waiters.push(waker.clone());
+        }
+
+        None
+    }
+}
+
+fn event_infos() -> Box<[EventInfo]> {
+    #[cfg(target_os = "linux")]
+    let max = libc::SIGRTMAX();
+    #[cfg(not(target_os = "linux"))]
+    let max = 33;
+
+    (0..=max).map(|_| EventInfo::new()).collect()
+}
+
+pub(crate) fn globals() -> &'static Globals {
+    static GLOBALS: OnceLock<Globals> = OnceLock::new();
+
+    GLOBALS.get_or_init(|| {
+        let (receiver, sender) = UnixStream::pair().expect("failed to create UnixStream");
+        receiver
+            .set_nonblocking(true)
+            .expect("failed to set the self-pipe non-blocking");
+        sender
+            .set_nonblocking(true)
+            .expect("failed to set the self-pipe non-blocking");
+
+        Globals {
+            sender,
+            receiver,
+            registry: event_infos(),
+        }
+    })
+}
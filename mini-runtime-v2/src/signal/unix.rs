@@ -0,0 +1,320 @@
+//! Unix-specific types for signal handling.
+//!
+//! This module is only defined on Unix platforms and contains the primary
+//! `Signal` type for receiving notifications of signals.
+
+use crate::future::poll_fn;
+use crate::runtime::scheduler;
+use crate::runtime::signal::Handle;
+use crate::signal::registry::{Globals, globals};
+use libc::c_int;
+use std::io::{self, Write};
+use std::sync::atomic::Ordering::Relaxed;
+use std::task::{Context, Poll};
+
+/// Represents the specific kind of signal to listen for.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq)]
+pub struct SignalKind(c_int);
+
+impl SignalKind {
+    /// Allows for listening to any valid OS signal.
+    ///
+    /// For example, this can be used for listening for platform-specific
+    /// signals.
+    pub const fn from_raw(signum: c_int) -> Self {
+        Self(signum)
+    }
+
+    /// Get the signal's numeric value.
+    pub const fn as_raw_value(&self) -> c_int {
+        self.0
+    }
+
+    /// Represents the `SIGALRM` signal.
+    ///
+    /// On Unix systems this signal is sent when a real-time timer has expired.
+    /// By default, the process is terminated by this signal.
+    pub const fn alarm() -> Self {
+        Self(libc::SIGALRM)
+    }
+
+    /// Represents the `SIGCHLD` signal.
+    ///
+    /// On Unix systems this signal is sent when the status of a child process
+    /// has changed. By default, this signal is ignored.
+    pub const fn child() -> Self {
+        Self(libc::SIGCHLD)
+    }
+
+    /// Represents the `SIGHUP` signal.
+    ///
+    /// On Unix systems this signal is sent when the terminal is disconnected.
+    /// By default, the process is terminated by this signal.
+    pub const fn hangup() -> Self {
+        Self(libc::SIGHUP)
+    }
+
+    /// Represents the `SIGINT` signal.
+    ///
+    /// On Unix systems this signal is sent to interrupt a program.
+    /// By default, the process is terminated by this signal.
+    pub const fn interrupt() -> Self {
+        Self(libc::SIGINT)
+    }
+
+    /// Represents the `SIGPIPE` signal.
+    ///
+    /// On Unix systems this signal is sent when the process attempts to write
+    /// to a pipe which has no reader. By default, the process is terminated by
+    /// this signal.
+    pub const fn pipe() -> Self {
+        Self(libc::SIGPIPE)
+    }
+
+    /// Represents the `SIGQUIT` signal.
+    ///
+    /// On Unix systems this signal is sent to issue a shutdown of the
+    /// process, after which the OS will dump the process core.
+    /// By default, the process is terminated by this signal.
+    pub const fn quit() -> Self {
+        Self(libc::SIGQUIT)
+    }
+
+    /// Represents the `SIGTERM` signal.
+    ///
+    /// On Unix systems this signal is sent to issue a shutdown of the
+    /// process. By default, the process is terminated by this signal.
+    pub const fn terminate() -> Self {
+        Self(libc::SIGTERM)
+    }
+
+    /// Represents the `SIGUSR1` signal.
+    ///
+    /// On Unix systems this is a user defined signal.
+    /// By default, the process is terminated by this signal.
+    pub const fn user_defined1() -> Self {
+        Self(libc::SIGUSR1)
+    }
+
+    /// Represents the `SIGUSR2` signal.
+    ///
+    /// On Unix systems this is a user defined signal.
+    /// By default, the process is terminated by this signal.
+    pub const fn user_defined2() -> Self {
+        Self(libc::SIGUSR2)
+    }
+}
+
+impl From<c_int> for SignalKind {
+    fn from(signum: c_int) -> Self {
+        Self::from_raw(signum)
+    }
+}
+
+impl From<SignalKind> for c_int {
+    fn from(kind: SignalKind) -> Self {
+        kind.as_raw_value()
+    }
+}
+
+/// Our global signal handler for all signals registered by this module.
+///
+/// The purpose of this signal handler is to primarily:
+///
+/// 1. Flag that our specific signal was received (e.g. store an atomic flag)
+/// 2. Wake up the driver by writing a byte to a pipe
+///
+/// Those two operations should both be async-signal safe.
+fn action(globals: &'static Globals, signal: c_int) {
+    globals.record_event(signal);
+
+    // Send a wakeup, ignore any errors (anything reasonably possible is
+    // full pipe and then it will wake up anyway).
+    let mut sender = &globals.sender;
+    drop(sender.write(&[1]));
+}
+
+/// Enables this module to receive signal notifications for the `signal`
+/// provided.
+///
+/// This will register the signal handler if it hasn't already been registered,
+/// returning any error along the way if that fails.
+fn signal_enable(signal: SignalKind) -> io::Result<()> {
+    let signal = signal.0;
+    if signal < 0 || signal_hook_registry::FORBIDDEN.contains(&signal) {
+        return Err(io::Error::other(format!(
+            "Refusing to register signal {signal}"
+        )));
+    }
+
+    let globals = globals();
+    let siginfo = match globals.event_info(signal) {
+        Some(slot) => slot,
+        None => return Err(io::Error::other("signal too large")),
+    };
+    let mut registered = Ok(());
+    siginfo.init.call_once(|| {
+        registered = unsafe {
+            signal_hook_registry::register(signal, move || action(globals, signal)).map(|_| ())
+        };
+        if registered.is_ok() {
+            siginfo.initialized.store(true, Relaxed);
+        }
+    });
+    registered?;
+    // If the call_once failed, it won't be retried on the next attempt to register the signal. In
+    // such case it is not run, registered is still `Ok(())`, initialized is still `false`.
+    if siginfo.initialized.load(Relaxed) {
+        Ok(())
+    } else {
+        Err(io::Error::other("Failed to register signal handler"))
+    }
+}
+
+/// An listener for receiving a particular type of OS signal.
+///
+/// In general signal handling on Unix is a pretty tricky topic, and this
+/// structure is no exception! There are some important limitations to keep in
+/// mind when using `Signal` streams:
+///
+/// * Signals handling in Unix already necessitates coalescing signals
+///   together sometimes. This `Signal` stream is also no exception here in
+///   that it will also coalesce signals. That is, even if the signal handler
+///   for this process runs multiple times, the `Signal` stream may only return
+///   one signal notification. Specifically, before `poll` is called, all
+///   signal notifications are coalesced into one item returned from `poll`.
+///   Once `poll` has been called, however, a further signal is guaranteed to
+///   be yielded as an item.
+///
+/// * Once a signal handler is registered with the process the underlying
+///   libc signal handler is never unregistered.
+///
+/// # Examples
+///
+/// Wait for `SIGHUP`
+///
+/// ```no_run
+/// use mini_runtime_v2::signal::unix::{signal, SignalKind};
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+///
+/// // An infinite stream of hangup signals.
+/// let mut sig = signal(SignalKind::hangup())?;
+///
+/// // Print whenever a HUP signal is received
+/// loop {
+///     sig.recv().await;
+///     println!("got signal HUP");
+/// }
+/// # #[allow(unreachable_code)]
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+#[must_use = "streams do nothing unless polled"]
+#[derive(Debug)]
+pub struct Signal {
+    kind: SignalKind,
+
+    /// The signal driver of the runtime the listener was created on.
+    handle: Handle,
+
+    /// The generation of the signal observed by the last notification.
+    seen: usize,
+}
+
+/// Creates a new listener which will receive notifications when the current
+/// process receives the specified signal `kind`.
+///
+/// This function will create a new stream which binds to the current
+/// runtime. The `Signal` stream will receive notifications whenever a signal
+/// is received, until the runtime shuts down. More documentation can be
+/// found on `Signal` itself, but to reiterate:
+///
+/// * Signals may be coalesced beyond what the kernel already does.
+/// * Once a signal handler is registered with the process the underlying
+///   libc signal handler is never unregistered.
+///
+/// A `Signal` stream can be created for a particular signal number
+/// multiple times. When a signal is received then all the associated
+/// channels will receive the signal notification.
+///
+/// # Errors
+///
+/// * If the lower-level C functions fail for some reason.
+/// * If the previous initialization of this specific signal failed.
+/// * If the signal is one of
+///   [`signal_hook::FORBIDDEN`](fn@signal_hook_registry::register#panics)
+///
+/// # Panics
+///
+/// This function panics if called outside of a Mini runtime or if the
+/// runtime was built without [`enable_io`].
+///
+/// [`enable_io`]: crate::runtime::Builder::enable_io
+#[track_caller]
+pub fn signal(kind: SignalKind) -> io::Result<Signal> {
+    // Signals are dispatched by the I/O driver, make sure it is enabled.
+    let handle = scheduler::Handle::current().driver().signal().clone();
+
+    signal_enable(kind)?;
+
+    let seen = globals()
+        .event_info(kind.0)
+        .expect("signal was enabled")
+        .generation();
+
+    Ok(Signal { kind, handle, seen })
+}
+
+impl Signal {
+    /// Receives the next signal notification event.
+    ///
+    /// `None` is returned once the runtime the listener was created on shut
+    /// down, and the signals delivered before were received.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. If the future is dropped before it
+    /// completes, no signal is lost: the next call observes it.
+    pub async fn recv(&mut self) -> Option<()> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Polls to receive the next signal notification event, outside of an
+    /// `async` context.
+    ///
+    /// This method returns:
+    ///
+    ///  * `Poll::Pending` if no signals are available but the runtime the
+    ///    listener was created on is still running.
+    ///  * `Poll::Ready(Some(()))` if a signal is available.
+    ///  * `Poll::Ready(None)` if the runtime shut down and all signals
+    ///    delivered before were received.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<()>> {
+        let info = globals()
+            .event_info(self.kind.0)
+            .expect("signal was enabled");
+
+        let generation = info.generation();
+        if generation != self.seen {
+            self.seen = generation;
+            return Poll::Ready(Some(()));
+        }
+
+        if self.handle.is_shutdown() {
+            return Poll::Ready(None);
+        }
+
+        match info.register_waker(self.seen, cx.waker()) {
+            Some(generation) => {
+                self.seen = generation;
+                Poll::Ready(Some(()))
+            }
+            // The driver wakes the registered listeners when it is dropped:
+            // check again in case it was dropped before the registration.
+            None if self.handle.is_shutdown() => Poll::Ready(None),
+            None => Poll::Pending,
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Asynchronous iteration.
+//!
+//! A [`Stream`] is to [`Iterator`] what a [`Future`] is to a plain value: it
+//! yields a sequence of values, each of which may not be ready yet. The
+//! trait only has the `poll_next` method; [`StreamExt`] provides the
+//! `next().await` method and the iterator-like combinators.
+//!
+//! ```
+//! use mini_runtime_v2::stream::StreamExt;
+//! # use mini_runtime_v2::sync::mpsc;
+//! # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+//! # rt.block_on(async {
+//! # let (tx, rx) = mpsc::channel(10);
+//! # for n in 0..10 {
+//! #     tx.send(n).await.unwrap();
+//! # }
+//! # drop(tx);
+//!
+//! let mut evens = rx.filter(|n| n % 2 == 0).map(|n| n * 10).take(3);
+//!
+//! while let Some(n) = evens.next().await {
+//!     println!("{n}");
+//! }
+//! # });
+//! ```
+//!
+//! The mpsc receivers and [`TcpListener::incoming`] implement `Stream`.
+//!
+//! [`Future`]: std::future::Future
+//! [`TcpListener::incoming`]: crate::net::TcpListener::incoming
+
+use std::ops::DerefMut;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+mod stream_ext;
+pub use stream_ext::{Filter, Map, Next, StreamExt, Take, Timeout};
+
+/// A stream of values produced asynchronously.
+///
+/// This is the same trait as `futures::Stream`, kept here so the runtime
+/// doesn't depend on the futures crate.
+#[must_use = "streams do nothing unless polled"]
+pub trait Stream {
+    /// Values yielded by the stream.
+    type Item;
+
+    /// Attempts to pull out the next value of this stream, registering the
+    /// current task for wakeup if the value is not yet available, and
+    /// returning `None` if the stream is exhausted.
+    ///
+    /// # Return value
+    ///
+    /// - `Poll::Pending` means that this stream's next value is not ready
+    ///   yet. Implementations will ensure that the current task will be
+    ///   notified when the next value may be ready.
+    /// - `Poll::Ready(Some(val))` means that the stream has successfully
+    ///   produced a value, `val`, and may produce further values on
+    ///   subsequent `poll_next` calls.
+    /// - `Poll::Ready(None)` means that the stream has terminated, and
+    ///   `poll_next` should not be invoked again.
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>>;
+
+    /// Returns the bounds on the remaining length of the stream.
+    ///
+    /// See [`Iterator::size_hint`] for details.
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+macro_rules! deref_stream {
+    () => {
+        type Item = S::Item;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<S::Item>> {
+            Pin::new(&mut **self).poll_next(cx)
+        }
+
+        fn size_hint(&self) -> (usize, Option<usize>) {
+            (**self).size_hint()
+        }
+    };
+}
+
+impl<S: ?Sized + Stream + Unpin> Stream for Box<S> {
+    deref_stream!();
+}
+
+impl<S: ?Sized + Stream + Unpin> Stream for &mut S {
+    deref_stream!();
+}
+
+impl<P> Stream for Pin<P>
+where
+    P: DerefMut + Unpin,
+    P::Target: Stream,
+{
+    type Item = <P::Target as Stream>::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.get_mut().as_mut().poll_next(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (**self).size_hint()
+    }
+}
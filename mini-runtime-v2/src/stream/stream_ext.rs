@@ -0,0 +1,137 @@
+use crate::stream::Stream;
+use std::time::Duration;
+
+mod filter;
+pub use filter::Filter;
+
+mod map;
+pub use map::Map;
+
+mod next;
+pub use next::Next;
+
+mod take;
+pub use take::Take;
+
+mod timeout;
+pub use timeout::Timeout;
+
+/// An extension trait for the [`Stream`] trait that provides a variety of
+/// convenient combinator functions.
+///
+/// It is implemented for every `Stream`, import it to call the methods:
+///
+/// ```
+/// use mini_runtime_v2::stream::StreamExt;
+/// ```
+pub trait StreamExt: Stream {
+    /// Consumes and returns the next value in the stream or `None` if the
+    /// stream is finished.
+    ///
+    /// Equivalent to:
+    ///
+    /// ```ignore
+    /// async fn next(&mut self) -> Option<Self::Item>;
+    /// ```
+    ///
+    /// Note that because `next` doesn't take ownership over the stream,
+    /// the [`Stream`] type must be [`Unpin`]. If you want to use `next` with a
+    /// [`!Unpin`](Unpin) stream, you'll first have to pin the stream.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe. The returned future only holds onto a
+    /// reference to the underlying stream, so dropping it will never lose a
+    /// value.
+    fn next(&mut self) -> Next<'_, Self>
+    where
+        Self: Unpin,
+    {
+        Next::new(self)
+    }
+
+    /// Maps this stream's items to a different type, returning a new stream of
+    /// the resulting type.
+    ///
+    /// The provided closure is executed over all elements of this stream as
+    /// they are made available. It is executed inline with calls to
+    /// [`poll_next`](Stream::poll_next).
+    fn map<T, F>(self, f: F) -> Map<Self, F>
+    where
+        F: FnMut(Self::Item) -> T,
+        Self: Sized,
+    {
+        Map::new(self, f)
+    }
+
+    /// Filters the values produced by this stream according to the provided
+    /// predicate.
+    ///
+    /// As values of this stream are made available, the provided predicate
+    /// `f` will be run against them. If the predicate returns `true`, then
+    /// the stream will yield the value, but if the predicate returns `false`,
+    /// then the value will be discarded and the next value will be produced.
+    fn filter<F>(self, f: F) -> Filter<Self, F>
+    where
+        F: FnMut(&Self::Item) -> bool,
+        Self: Sized,
+    {
+        Filter::new(self, f)
+    }
+
+    /// Creates a new stream of at most `n` items of the underlying stream.
+    ///
+    /// Once `n` items have been yielded from this stream then it will always
+    /// return that the stream is done.
+    fn take(self, n: usize) -> Take<Self>
+    where
+        Self: Sized,
+    {
+        Take::new(self, n)
+    }
+
+    /// Applies a per-item timeout to the passed stream.
+    ///
+    /// `timeout()` takes a `Duration` that represents the maximum amount of
+    /// time each element of the stream has to complete before timing out.
+    ///
+    /// If the wrapped stream yields a value before the deadline is reached,
+    /// the value is returned. Otherwise, an error is returned and the
+    /// deadline restarts: the caller may keep polling the stream, or give
+    /// up on it.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::stream::StreamExt;
+    /// # use mini_runtime_v2::sync::mpsc;
+    /// # use std::time::Duration;
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    /// # rt.block_on(async {
+    /// # let (tx, rx) = mpsc::channel(1);
+    /// # tx.send("hello").await.unwrap();
+    /// # drop(tx);
+    /// let mut rx = rx.timeout(Duration::from_secs(1));
+    ///
+    /// while let Some(res) = rx.next().await {
+    ///     match res {
+    ///         Ok(msg) => println!("{msg}"),
+    ///         Err(_) => println!("idle for 1 second"),
+    ///     }
+    /// }
+    /// # });
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// The returned stream panics when polled outside of a Mini runtime or if
+    /// the runtime was built without [`enable_time`].
+    ///
+    /// [`enable_time`]: crate::runtime::Builder::enable_time
+    fn timeout(self, duration: Duration) -> Timeout<Self>
+    where
+        Self: Sized,
+    {
+        Timeout::new(self, duration)
+    }
+}
+
+impl<St: ?Sized> StreamExt for St where St: Stream {}
@@ -0,0 +1,60 @@
+use crate::stream::Stream;
+use pin_project_lite::pin_project;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Stream for the [`take`](super::StreamExt::take) method.
+    #[derive(Debug)]
+    #[must_use = "streams do nothing unless polled"]
+    pub struct Take<St> {
+        #[pin]
+        stream: St,
+        remaining: usize,
+    }
+}
+
+impl<St> Take<St> {
+    pub(super) fn new(stream: St, remaining: usize) -> Self {
+        Self { stream, remaining }
+    }
+}
+
+impl<St> Stream for Take<St>
+where
+    St: Stream,
+{
+    type Item = St::Item;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.remaining == 0 {
+            // The underlying stream is not polled anymore, it may never end.
+            return Poll::Ready(None);
+        }
+
+        let me = self.project();
+        let next = ready!(me.stream.poll_next(cx));
+        match next {
+            Some(_) => *me.remaining -= 1,
+            None => *me.remaining = 0,
+        }
+
+        Poll::Ready(next)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.remaining == 0 {
+            return (0, Some(0));
+        }
+
+        let (lower, upper) = self.stream.size_hint();
+
+        let lower = lower.min(self.remaining);
+        let upper = match upper {
+            Some(x) if x < self.remaining => Some(x),
+            _ => Some(self.remaining),
+        };
+
+        (lower, upper)
+    }
+}
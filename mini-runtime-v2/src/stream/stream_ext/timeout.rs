@@ -0,0 +1,64 @@
+use crate::stream::Stream;
+use crate::time::error::Elapsed;
+use crate::time::{Sleep, sleep};
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+pin_project! {
+    /// Stream returned by the [`timeout`](super::StreamExt::timeout) method.
+    #[must_use = "streams do nothing unless polled"]
+    #[derive(Debug)]
+    pub struct Timeout<S> {
+        #[pin]
+        stream: S,
+        duration: Duration,
+        // Created on the first poll, `timeout` can be called outside of the
+        // runtime.
+        deadline: Option<Sleep>,
+    }
+}
+
+impl<S: Stream> Timeout<S> {
+    pub(super) fn new(stream: S, duration: Duration) -> Self {
+        Timeout {
+            stream,
+            duration,
+            deadline: None,
+        }
+    }
+}
+
+impl<S: Stream> Stream for Timeout<S> {
+    type Item = Result<S::Item, Elapsed>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let me = self.project();
+
+        if let Poll::Ready(v) = me.stream.poll_next(cx) {
+            // The next item gets a fresh deadline.
+            if let Some(deadline) = me.deadline {
+                deadline.reset_after(*me.duration);
+            }
+            return Poll::Ready(v.map(Ok));
+        }
+
+        let deadline = me.deadline.get_or_insert_with(|| sleep(*me.duration));
+        match Pin::new(&mut *deadline).poll(cx) {
+            Poll::Ready(()) => {
+                deadline.reset_after(*me.duration);
+                Poll::Ready(Some(Err(Elapsed::new())))
+            }
+            Poll::Pending => Poll::Pending,
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (lower, _) = self.stream.size_hint();
+
+        // The timeout stream may insert an error an infinite number of times.
+        (lower, None)
+    }
+}
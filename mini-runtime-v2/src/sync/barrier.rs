@@ -0,0 +1,202 @@
+//! A barrier, to make several tasks wait for each other.
+//!
+//! The barrier counts the tasks waiting on it. The `n`-th one releases all
+//! of them at once and starts a new generation: the barrier can be reused
+//! for the next round right away.
+
+use crate::util::loom::sync::Mutex;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+/// A barrier enables multiple tasks to synchronize the beginning of some
+/// computation.
+///
+/// Starting tasks together, without sleeping in the hope that they are all
+/// ready:
+///
+/// ```
+/// use mini_runtime_v2::sync::Barrier;
+/// use std::sync::Arc;
+/// # use mini_runtime_v2::task;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+/// # rt.block_on(async {
+///
+/// let barrier = Arc::new(Barrier::new(10));
+/// let mut handles = Vec::new();
+///
+/// for _ in 0..10 {
+///     let barrier = barrier.clone();
+///     handles.push(task::spawn(async move {
+///         // Set up the task...
+///         let result = barrier.wait().await;
+///         // ... then run concurrently with the others.
+///         result.is_leader()
+///     }));
+/// }
+///
+/// // Exactly one task is the leader.
+/// let mut leaders = 0;
+/// for handle in handles {
+///     leaders += handle.await.unwrap() as usize;
+/// }
+/// assert_eq!(leaders, 1);
+/// # });
+/// ```
+pub struct Barrier {
+    state: Mutex<State>,
+
+    /// Number of tasks released together.
+    n: usize,
+}
+
+struct State {
+    /// Number of tasks waiting in the current generation.
+    arrived: usize,
+
+    /// Incremented every time the tasks are released.
+    generation: u64,
+
+    /// Tasks waiting to be released, with the id of their `Wait` future.
+    waiters: Vec<(u64, Waker)>,
+
+    /// Id given to the next waiting task.
+    next_waiter_id: u64,
+}
+
+/// Returned by [`Barrier::wait`] when all the tasks have reached the barrier.
+#[derive(Debug, Clone)]
+pub struct BarrierWaitResult(bool);
+
+impl Barrier {
+    /// Creates a new barrier that releases the tasks by groups of `n`.
+    ///
+    /// A barrier of zero behaves like a barrier of one: `wait` returns right
+    /// away.
+    pub fn new(n: usize) -> Barrier {
+        Barrier {
+            state: Mutex::new(State {
+                arrived: 0,
+                generation: 0,
+                waiters: Vec::new(),
+                next_waiter_id: 0,
+            }),
+            n: n.max(1),
+        }
+    }
+
+    /// Waits until all the `n` tasks have called `wait`.
+    ///
+    /// The tasks wait until the `n`-th one calls `wait`, then they are all
+    /// released and the barrier is ready for the next `n` tasks. A single
+    /// task, the last one, is the *leader*, see
+    /// [`BarrierWaitResult::is_leader`].
+    ///
+    /// # Cancel safety
+    ///
+    /// Dropping the future before it completes withdraws the task: it no
+    /// longer counts toward the `n` tasks of the generation.
+    pub async fn wait(&self) -> BarrierWaitResult {
+        Wait {
+            barrier: self,
+            waiter: None,
+        }
+        .await
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        fmt.debug_struct("Barrier")
+            .field("n", &self.n)
+            .field("arrived", &state.arrived)
+            .field("generation", &state.generation)
+            .finish()
+    }
+}
+
+impl BarrierWaitResult {
+    /// Returns `true` if this task released the others.
+    ///
+    /// Exactly one task of each generation is the leader, e.g. to run some
+    /// setup or report once per round.
+    pub fn is_leader(&self) -> bool {
+        self.0
+    }
+}
+
+/// Future returned by [`Barrier::wait`].
+///
+/// Dropping the future before it completes removes the task from the
+/// barrier, unless the task was already released.
+struct Wait<'a> {
+    barrier: &'a Barrier,
+    /// Generation joined and id of the entry in `waiters`, while the task
+    /// waits.
+    waiter: Option<(u64, u64)>,
+}
+
+impl Future for Wait<'_> {
+    type Output = BarrierWaitResult;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<BarrierWaitResult> {
+        let me = self.get_mut();
+        let mut guard = me.barrier.state.lock().unwrap();
+        let state = &mut *guard;
+
+        if let Some((generation, id)) = me.waiter {
+            if state.generation != generation {
+                me.waiter = None;
+                return Poll::Ready(BarrierWaitResult(false));
+            }
+
+            // Spurious poll, the task is still waiting.
+            if let Some((_, waker)) = state.waiters.iter_mut().find(|(w, _)| *w == id)
+                && !waker.will_wake(cx.waker())
+            {
+                *waker = cx.waker().clone();
+            }
+            return Poll::Pending;
+        }
+
+        state.arrived += 1;
+        if state.arrived == me.barrier.n {
+            state.arrived = 0;
+            state.generation = state.generation.wrapping_add(1);
+            let waiters = std::mem::take(&mut state.waiters);
+            drop(guard);
+
+            for (_, waker) in waiters {
+                waker.wake();
+            }
+            return Poll::Ready(BarrierWaitResult(true));
+        }
+
+        let id = state.next_waiter_id;
+        state.next_waiter_id += 1;
+        state.waiters.push((id, cx.waker().clone()));
+        me.waiter = Some((state.generation, id));
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Wait<'_> {
+    fn drop(&mut self) {
+        let Some((generation, id)) = self.waiter else {
+            return;
+        };
+
+        let mut state = self.barrier.state.lock().unwrap();
+
+        // Released, but not polled since.
+        if state.generation != generation {
+            return;
+        }
+
+        state.arrived -= 1;
+        state.waiters.retain(|(w, _)| *w != id);
+    }
+}
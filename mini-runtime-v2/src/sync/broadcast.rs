@@ -0,0 +1,418 @@
+//! A multi-producer, multi-consumer broadcast queue. Each sent value is seen
+//! by all consumers.
+//!
+//! A [`Sender`] is used to broadcast values to **all** connected [`Receiver`]
+//! values. [`Sender`] handles are clone-able, allowing concurrent send and
+//! receive actions. [`Sender`] and [`Receiver`] are both `Send` and `Sync` as
+//! long as `T` is `Send`.
+//!
+//! When a value is sent, **all** [`Receiver`] handles are notified and will
+//! receive the value. The value is stored once inside the channel and cloned
+//! on demand for each receiver.
+//!
+//! New [`Receiver`] handles are created by calling [`Sender::subscribe`]. The
+//! returned [`Receiver`] will receive values sent **after** the call to
+//! `subscribe`.
+//!
+//! # Lagging
+//!
+//! The channel keeps the last `capacity` values. A value is not removed when
+//! all the receivers have seen it, but when a new value is sent to a full
+//! channel. A receiver that falls more than `capacity` values behind misses
+//! the oldest ones: its next call to [`Receiver::recv`] returns
+//! [`RecvError::Lagged`] with the number of skipped values, then the oldest
+//! value still held by the channel.
+//!
+//! # Closing
+//!
+//! When **all** [`Sender`] handles have been dropped, no new values may be
+//! sent. The receivers still receive the values held by the channel, then
+//! [`RecvError::Closed`].
+//!
+//! ```
+//! use mini_runtime_v2::sync::broadcast;
+//!
+//! # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+//! # rt.block_on(async {
+//! let (tx, mut rx1) = broadcast::channel(16);
+//! let mut rx2 = tx.subscribe();
+//!
+//! mini_runtime_v2::spawn(async move {
+//!     assert_eq!(rx1.recv().await.unwrap(), 10);
+//!     assert_eq!(rx1.recv().await.unwrap(), 20);
+//! });
+//!
+//! mini_runtime_v2::spawn(async move {
+//!     assert_eq!(rx2.recv().await.unwrap(), 10);
+//!     assert_eq!(rx2.recv().await.unwrap(), 20);
+//! });
+//!
+//! tx.send(10).unwrap();
+//! tx.send(20).unwrap();
+//! # });
+//! ```
+//!
+//! [`RecvError::Lagged`]: error::RecvError::Lagged
+//! [`RecvError::Closed`]: error::RecvError::Closed
+
+use crate::future::poll_fn;
+use crate::runtime::coop;
+use crate::util::loom::sync::Mutex;
+use error::{RecvError, SendError, TryRecvError};
+use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+/// Sending-half of the [`broadcast`](self) channel.
+///
+/// May be used from many threads. Messages can be sent with
+/// [`send`](Sender::send).
+pub struct Sender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+/// Receiving-half of the [`broadcast`](self) channel.
+///
+/// Must not be used concurrently. Messages may be retrieved using
+/// [`recv`](Receiver::recv).
+pub struct Receiver<T> {
+    shared: Arc<Shared<T>>,
+
+    /// Position of the next value to receive.
+    next: u64,
+
+    /// Identifies the receiver's waker in the channel.
+    id: u64,
+}
+
+struct Shared<T> {
+    state: Mutex<State<T>>,
+}
+
+struct State<T> {
+    /// The last `capacity` values sent.
+    buffer: VecDeque<T>,
+
+    /// Position of the first value of `buffer`, incremented when a value is
+    /// dropped to make room for a new one.
+    head: u64,
+
+    capacity: usize,
+
+    /// Receivers waiting for a value, with their id.
+    waiters: Vec<(u64, Waker)>,
+
+    /// Id given to the next receiver.
+    next_rx_id: u64,
+
+    /// Number of outstanding senders, the channel is closed when it drops to 0.
+    tx_count: usize,
+
+    rx_count: usize,
+}
+
+pub mod error {
+    //! Broadcast error types.
+
+    use std::error::Error;
+    use std::fmt;
+
+    /// Error returned by the [`send`](super::Sender::send) function on a
+    /// [`Sender`](super::Sender): there are no active receivers. The value is
+    /// given back.
+    #[derive(PartialEq, Eq, Clone, Copy)]
+    pub struct SendError<T>(pub T);
+
+    impl<T> fmt::Debug for SendError<T> {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.debug_struct("SendError").finish_non_exhaustive()
+        }
+    }
+
+    impl<T> fmt::Display for SendError<T> {
+        fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(fmt, "channel closed")
+        }
+    }
+
+    impl<T> Error for SendError<T> {}
+
+    /// An error returned from the [`recv`](super::Receiver::recv) function
+    /// on a [`Receiver`](super::Receiver).
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub enum RecvError {
+        /// There are no more active senders implying no further messages
+        /// will ever be sent.
+        Closed,
+
+        /// The receiver lagged too far behind. Attempting to receive again
+        /// will return the oldest message still retained by the channel.
+        ///
+        /// Includes the number of skipped messages.
+        Lagged(u64),
+    }
+
+    impl fmt::Display for RecvError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                RecvError::Closed => write!(f, "channel closed"),
+                RecvError::Lagged(amt) => write!(f, "channel lagged by {amt}"),
+            }
+        }
+    }
+
+    impl Error for RecvError {}
+
+    /// An error returned from the [`try_recv`](super::Receiver::try_recv)
+    /// function on a [`Receiver`](super::Receiver).
+    #[derive(Debug, PartialEq, Eq, Clone)]
+    pub enum TryRecvError {
+        /// The channel is currently empty. There are still active
+        /// [`Sender`](super::Sender) handles, so data may yet become
+        /// available.
+        Empty,
+
+        /// There are no more active senders implying no further messages
+        /// will ever be sent.
+        Closed,
+
+        /// The receiver lagged too far behind and has been forcibly
+        /// disconnected from the oldest messages.
+        ///
+        /// Includes the number of skipped messages.
+        Lagged(u64),
+    }
+
+    impl fmt::Display for TryRecvError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                TryRecvError::Empty => write!(f, "channel empty"),
+                TryRecvError::Closed => write!(f, "channel closed"),
+                TryRecvError::Lagged(amt) => write!(f, "channel lagged by {amt}"),
+            }
+        }
+    }
+
+    impl Error for TryRecvError {}
+}
+
+/// Creates a bounded, multi-producer, multi-consumer channel where each sent
+/// value is broadcasted to all active receivers.
+///
+/// The channel keeps the last `capacity` values, see the [module
+/// documentation](self) for what happens to a receiver falling behind.
+///
+/// # Panics
+///
+/// This will panic if `capacity` is equal to `0`.
+#[track_caller]
+pub fn channel<T: Clone>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(capacity > 0, "broadcast channel capacity cannot be zero");
+
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            buffer: VecDeque::with_capacity(capacity),
+            head: 0,
+            capacity,
+            waiters: Vec::new(),
+            next_rx_id: 1,
+            tx_count: 1,
+            rx_count: 1,
+        }),
+    });
+
+    let rx = Receiver {
+        shared: shared.clone(),
+        next: 0,
+        id: 0,
+    };
+    (Sender { shared }, rx)
+}
+
+impl<T> Sender<T> {
+    /// Attempts to send a value to all active [`Receiver`] handles, returning
+    /// it back if it could not be sent.
+    ///
+    /// A successful send occurs when there is at least one active
+    /// [`Receiver`] handle. On success, the number of subscribed receivers
+    /// is returned. This does not mean that this number of receivers will
+    /// see the message as a receiver may drop or lag before receiving it.
+    pub fn send(&self, value: T) -> Result<usize, SendError<T>> {
+        let (rx_count, waiters) = {
+            let mut state = self.shared.state.lock().unwrap();
+
+            if state.rx_count == 0 {
+                return Err(SendError(value));
+            }
+
+            if state.buffer.len() == state.capacity {
+                state.buffer.pop_front();
+                state.head += 1;
+            }
+            state.buffer.push_back(value);
+
+            (state.rx_count, std::mem::take(&mut state.waiters))
+        };
+
+        for (_, waker) in waiters {
+            waker.wake();
+        }
+
+        Ok(rx_count)
+    }
+
+    /// Creates a new [`Receiver`] handle that will receive values sent
+    /// **after** this call to `subscribe`.
+    pub fn subscribe(&self) -> Receiver<T> {
+        let mut state = self.shared.state.lock().unwrap();
+        state.rx_count += 1;
+
+        let id = state.next_rx_id;
+        state.next_rx_id += 1;
+
+        Receiver {
+            shared: self.shared.clone(),
+            next: state.head + state.buffer.len() as u64,
+            id,
+        }
+    }
+
+    /// Returns the number of active receivers.
+    pub fn receiver_count(&self) -> usize {
+        self.shared.state.lock().unwrap().rx_count
+    }
+}
+
+impl<T: Clone> Receiver<T> {
+    /// Receives the next value for this receiver.
+    ///
+    /// Each [`Receiver`] handle will receive a clone of all values sent
+    /// **after** it has subscribed.
+    ///
+    /// `Err(RecvError::Closed)` is returned when all `Sender` halves have
+    /// dropped, and the values still held by the channel were received.
+    ///
+    /// If the [`Receiver`] handle falls behind, once the channel is full,
+    /// newly sent values will overwrite old values. At this point, a call to
+    /// `recv` will return with `Err(RecvError::Lagged)` and the
+    /// [`Receiver`]'s internal cursor is updated to point to the oldest value
+    /// still held by the channel.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe: if it is used in a `select!` and another
+    /// branch completes first, no value is lost.
+    pub async fn recv(&mut self) -> Result<T, RecvError> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempts to return a pending value on this receiver without awaiting.
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let state = self.shared.state.lock().unwrap();
+
+        match state.recv(&mut self.next) {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(RecvError::Lagged(n))) => Err(TryRecvError::Lagged(n)),
+            Some(Err(RecvError::Closed)) => Err(TryRecvError::Closed),
+            None => Err(TryRecvError::Empty),
+        }
+    }
+
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<T, RecvError>> {
+        // A receiver looping over a flooded channel would never yield.
+        let coop = ready!(coop::poll_proceed(cx));
+
+        let mut state = self.shared.state.lock().unwrap();
+
+        if let Some(res) = state.recv(&mut self.next) {
+            coop.made_progress();
+            return Poll::Ready(res);
+        }
+
+        match state.waiters.iter_mut().find(|(id, _)| *id == self.id) {
+            Some((_, waker)) => {
+                if !waker.will_wake(cx.waker()) {
+                    *waker = cx.waker().clone();
+                }
+            }
+            None => state.waiters.push((self.id, cx.waker().clone())),
+        }
+
+        Poll::Pending
+    }
+}
+
+impl<T: Clone> State<T> {
+    /// Returns the value at position `next` and advances it, a lag or the
+    /// closing of the channel. `None` if the receiver must wait.
+    fn recv(&self, next: &mut u64) -> Option<Result<T, RecvError>> {
+        if *next < self.head {
+            let lagged = self.head - *next;
+            *next = self.head;
+            return Some(Err(RecvError::Lagged(lagged)));
+        }
+
+        let index = (*next - self.head) as usize;
+        if let Some(value) = self.buffer.get(index) {
+            *next += 1;
+            return Some(Ok(value.clone()));
+        }
+
+        if self.tx_count == 0 {
+            return Some(Err(RecvError::Closed));
+        }
+
+        None
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Sender<T> {
+        self.shared.state.lock().unwrap().tx_count += 1;
+
+        Sender {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let waiters = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.tx_count -= 1;
+
+            if state.tx_count != 0 {
+                return;
+            }
+            std::mem::take(&mut state.waiters)
+        };
+
+        // The receivers are closed once they received the values held by
+        // the channel.
+        for (_, waker) in waiters {
+            waker.wake();
+        }
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut state = self.shared.state.lock().unwrap();
+        state.rx_count -= 1;
+        state.waiters.retain(|(id, _)| *id != self.id);
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "broadcast::Sender")
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "broadcast::Receiver")
+    }
+}
@@ -0,0 +1,230 @@
+//! An asynchronously awaitable `CancellationToken`.
+//!
+//! The token allows to signal a cancellation request to one or more tasks.
+
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, Weak};
+use std::task::{Context, Poll, Waker};
+
+/// A token which can be used to signal a cancellation request to one or more
+/// tasks.
+///
+/// Tasks can call [`CancellationToken::cancelled()`] in order to obtain a
+/// Future which will be resolved when cancellation is requested.
+///
+/// Cancellation can be requested through the [`CancellationToken::cancel`]
+/// method. Cancellation propagates to the tokens created with
+/// [`child_token`](CancellationToken::child_token), but not the other way
+/// around: cancelling a child doesn't cancel its parent.
+///
+/// Cloning a token returns a handle to the same token: cancelling a clone
+/// cancels all of them.
+///
+/// ```
+/// use mini_runtime_v2::sync::CancellationToken;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+/// # rt.block_on(async {
+///
+/// let token = CancellationToken::new();
+/// let cloned_token = token.clone();
+///
+/// let join_handle = mini_runtime_v2::spawn(async move {
+///     cloned_token.cancelled().await;
+///     println!("cancelled");
+/// });
+///
+/// token.cancel();
+/// join_handle.await.unwrap();
+/// # });
+/// ```
+#[derive(Clone)]
+pub struct CancellationToken {
+    inner: Arc<TreeNode>,
+}
+
+/// A node of the cancellation tree, shared by a token and its clones.
+struct TreeNode {
+    state: Mutex<State>,
+}
+
+struct State {
+    is_cancelled: bool,
+
+    /// Tokens created with `child_token`, cancelled with this one.
+    children: Vec<Weak<TreeNode>>,
+
+    /// Tasks waiting on `cancelled()`, with the id of their future.
+    waiters: Vec<(u64, Waker)>,
+
+    /// Id given to the next waiting future.
+    next_waiter_id: u64,
+}
+
+impl CancellationToken {
+    /// Creates a new `CancellationToken` in the non-cancelled state.
+    pub fn new() -> CancellationToken {
+        CancellationToken {
+            inner: Arc::new(TreeNode::new(false)),
+        }
+    }
+
+    /// Creates a `CancellationToken` which will get cancelled whenever the
+    /// current token gets cancelled.
+    ///
+    /// If the current token is already cancelled, the child token will get
+    /// returned in cancelled state.
+    pub fn child_token(&self) -> CancellationToken {
+        let mut state = self.inner.state.lock().unwrap();
+        let child = Arc::new(TreeNode::new(state.is_cancelled));
+
+        if !state.is_cancelled {
+            // Forget about the children that were dropped meanwhile.
+            state.children.retain(|child| child.strong_count() > 0);
+            state.children.push(Arc::downgrade(&child));
+        }
+
+        CancellationToken { inner: child }
+    }
+
+    /// Cancels the `CancellationToken` and all child tokens which had been
+    /// derived from it.
+    ///
+    /// This will wake up all tasks which are waiting for cancellation.
+    pub fn cancel(&self) {
+        self.inner.cancel();
+    }
+
+    /// Returns `true` if the `CancellationToken` is cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.state.lock().unwrap().is_cancelled
+    }
+
+    /// Returns a `Future` that gets fulfilled when cancellation is requested.
+    ///
+    /// The future will complete immediately if the token is already
+    /// cancelled when this method is called.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe.
+    pub fn cancelled(&self) -> WaitForCancellationFuture<'_> {
+        WaitForCancellationFuture {
+            token: self,
+            waiter: None,
+        }
+    }
+}
+
+impl Default for CancellationToken {
+    fn default() -> CancellationToken {
+        CancellationToken::new()
+    }
+}
+
+impl fmt::Debug for CancellationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancellationToken")
+            .field("is_cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl TreeNode {
+    fn new(is_cancelled: bool) -> TreeNode {
+        TreeNode {
+            state: Mutex::new(State {
+                is_cancelled,
+                children: Vec::new(),
+                waiters: Vec::new(),
+                next_waiter_id: 0,
+            }),
+        }
+    }
+
+    fn cancel(&self) {
+        let (children, waiters) = {
+            let mut state = self.state.lock().unwrap();
+            if state.is_cancelled {
+                return;
+            }
+            state.is_cancelled = true;
+
+            (
+                std::mem::take(&mut state.children),
+                std::mem::take(&mut state.waiters),
+            )
+        };
+
+        // Wake and recurse outside of the lock.
+        for (_, waker) in waiters {
+            waker.wake();
+        }
+
+        for child in children {
+            if let Some(child) = child.upgrade() {
+                child.cancel();
+            }
+        }
+    }
+}
+
+/// A Future that is resolved once the corresponding [`CancellationToken`]
+/// is cancelled.
+#[must_use = "futures do nothing unless polled"]
+pub struct WaitForCancellationFuture<'a> {
+    token: &'a CancellationToken,
+
+    /// Id of the entry in `waiters`, once registered.
+    waiter: Option<u64>,
+}
+
+impl Future for WaitForCancellationFuture<'_> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let me = self.get_mut();
+        let mut state = me.token.inner.state.lock().unwrap();
+
+        if state.is_cancelled {
+            me.waiter = None;
+            return Poll::Ready(());
+        }
+
+        let registered = me
+            .waiter
+            .and_then(|id| state.waiters.iter_mut().find(|(w, _)| *w == id));
+
+        match registered {
+            Some((_, waker)) => {
+                if !waker.will_wake(cx.waker()) {
+                    *waker = cx.waker().clone();
+                }
+            }
+            None => {
+                let id = state.next_waiter_id;
+                state.next_waiter_id += 1;
+                state.waiters.push((id, cx.waker().clone()));
+                me.waiter = Some(id);
+            }
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for WaitForCancellationFuture<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.waiter {
+            let mut state = self.token.inner.state.lock().unwrap();
+            state.waiters.retain(|(w, _)| *w != id);
+        }
+    }
+}
+
+impl fmt::Debug for WaitForCancellationFuture<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WaitForCancellationFuture").finish()
+    }
+}
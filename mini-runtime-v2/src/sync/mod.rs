@@ -0,0 +1,22 @@
+//! Synchronization primitives for use in asynchronous contexts.
+//!
+//! Unlike the `std::sync` primitives, waiting on them doesn't block the
+//! thread: the task yields back to the scheduler and is woken once it can
+//! make progress.
+
+pub mod broadcast;
+pub mod mpsc;
+
+mod barrier;
+pub use barrier::{Barrier, BarrierWaitResult};
+
+mod cancellation_token;
+pub use cancellation_token::{CancellationToken, WaitForCancellationFuture};
+
+mod once_cell;
+pub use once_cell::{OnceCell, SetError};
+
+mod semaphore;
+pub use semaphore::{
+    AcquireError, OwnedSemaphorePermit, Semaphore, SemaphorePermit, TryAcquireError,
+};
@@ -0,0 +1,312 @@
+use crate::future::poll_fn;
+use crate::stream::Stream;
+use crate::sync::mpsc::chan::{Chan, TryAcquireError};
+use crate::sync::mpsc::error::{SendError, TrySendError};
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Sends values to the associated `Receiver`.
+///
+/// Instances are created by the [`channel`] function.
+pub struct Sender<T> {
+    chan: Arc<Chan<T>>,
+}
+
+/// Receives values from the associated `Sender`.
+///
+/// Instances are created by the [`channel`] function.
+pub struct Receiver<T> {
+    chan: Arc<Chan<T>>,
+}
+
+/// Creates a bounded mpsc channel for communicating between asynchronous
+/// tasks with backpressure.
+///
+/// The channel will buffer up to the provided number of messages. Once the
+/// buffer is full, attempts to send new messages will wait until a message is
+/// received from the channel.
+///
+/// All data sent on `Sender` will become available on `Receiver` in the same
+/// order as it was sent.
+///
+/// # Panics
+///
+/// Panics if the buffer capacity is 0.
+#[track_caller]
+pub fn channel<T>(buffer: usize) -> (Sender<T>, Receiver<T>) {
+    assert!(buffer > 0, "mpsc bounded channel requires buffer > 0");
+
+    let chan = Arc::new(Chan::new(Some(buffer)));
+    let tx = Sender { chan: chan.clone() };
+    let rx = Receiver { chan };
+
+    (tx, rx)
+}
+
+impl<T> Receiver<T> {
+    /// Receives the next value for this receiver.
+    ///
+    /// This method returns `None` if the channel has been closed and there are
+    /// no remaining messages in the channel's buffer. The channel is closed
+    /// when all senders have been dropped, or when [`close`] is called.
+    ///
+    /// [`close`]: Self::close
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Closes the receiving half of a channel without dropping it.
+    ///
+    /// This prevents any further messages from being sent on the channel
+    /// while still enabling the receiver to drain messages that are buffered.
+    /// Senders waiting for capacity, and in [`Sender::closed`], are woken.
+    pub fn close(&mut self) {
+        self.chan.close();
+    }
+
+    /// Polls to receive the next message on this channel.
+    ///
+    /// This method returns:
+    ///
+    ///  * `Poll::Pending` if no messages are available but the channel is not
+    ///    closed, or if a spurious failure happens.
+    ///  * `Poll::Ready(Some(message))` if a message is available.
+    ///  * `Poll::Ready(None)` if the channel has been closed and all messages
+    ///    sent before it was closed have been received.
+    ///
+    /// When the method returns `Poll::Pending`, the `Waker` in the provided
+    /// `Context` is scheduled to receive a wakeup when a message is sent on any
+    /// receiver, or when the channel is closed. Note that on multiple calls to
+    /// `poll_recv`, only the `Waker` from the `Context` passed to the most
+    /// recent call is scheduled to receive a wakeup.
+    ///
+    /// Receiving a message gives its slot back to the senders: the first
+    /// sender waiting for capacity is woken.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.chan.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        self.chan.close();
+        self.chan.drain();
+    }
+}
+
+impl<T> Stream for Receiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<T> fmt::Debug for Receiver<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Receiver")
+            .field("chan", &self.chan)
+            .finish()
+    }
+}
+
+impl<T> Sender<T> {
+    /// Sends a value, waiting until there is capacity.
+    ///
+    /// A successful send occurs when it is determined that the other end of
+    /// the channel has not hung up already. An unsuccessful send would be one
+    /// where the corresponding receiver has already been closed. Note that a
+    /// return value of `Err` means that the data will never be received, but
+    /// a return value of `Ok` does not mean that the data will be received.
+    ///
+    /// # Cancel safety
+    ///
+    /// If `send` is used as the event in a `select!`-like construct and some
+    /// other branch completes first, then it is guaranteed that the message
+    /// was not sent. The message is lost in that case.
+    pub async fn send(&self, value: T) -> Result<(), SendError<T>> {
+        match self.reserve().await {
+            Ok(permit) => {
+                permit.send(value);
+                Ok(())
+            }
+            Err(_) => Err(SendError(value)),
+        }
+    }
+
+    /// Attempts to immediately send a message on this `Sender`.
+    ///
+    /// This method differs from [`send`] by returning immediately if the
+    /// channel's buffer is full or no receiver is waiting to acquire some
+    /// data. Compared with [`send`], this function has two failure cases
+    /// instead of one (one for disconnection, one for a full buffer).
+    ///
+    /// # Errors
+    ///
+    /// If the channel capacity has been reached, i.e., the channel has `n`
+    /// buffered values where `n` is the argument passed to [`channel`], then
+    /// an error is returned.
+    ///
+    /// If the receive half of the channel is closed, either due to [`close`]
+    /// being called or the [`Receiver`] handle dropping, the function returns
+    /// an error. The error includes the value passed to `send`.
+    ///
+    /// [`send`]: Sender::send
+    /// [`close`]: Receiver::close
+    pub fn try_send(&self, message: T) -> Result<(), TrySendError<T>> {
+        match self.chan.try_acquire() {
+            Ok(()) => {}
+            Err(TryAcquireError::Closed) => return Err(TrySendError::Closed(message)),
+            Err(TryAcquireError::NoPermits) => return Err(TrySendError::Full(message)),
+        }
+
+        self.chan.send(message).map_err(TrySendError::Closed)
+    }
+
+    /// Waits for channel capacity. Once capacity to send one message is
+    /// available, it is reserved for the caller.
+    ///
+    /// If the channel is full, the function waits for the number of unreceived
+    /// messages to become less than the channel capacity. Capacity to send one
+    /// message is reserved for the caller. A [`Permit`] is returned to track
+    /// the reserved capacity. The [`send`] function on [`Permit`] consumes the
+    /// reserved capacity.
+    ///
+    /// Dropping [`Permit`] without sending a message releases the capacity
+    /// back to the channel.
+    ///
+    /// ```
+    /// # use mini_runtime_v2::sync::mpsc;
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+    /// # rt.block_on(async {
+    /// let (tx, mut rx) = mpsc::channel(1);
+    ///
+    /// // Reserve capacity
+    /// let permit = tx.reserve().await.unwrap();
+    ///
+    /// // Trying to send directly on the `tx` will fail due to no
+    /// // available capacity.
+    /// assert!(tx.try_send(123).is_err());
+    ///
+    /// // Sending on the permit succeeds
+    /// permit.send(456);
+    ///
+    /// assert_eq!(rx.recv().await.unwrap(), 456);
+    /// # });
+    /// ```
+    ///
+    /// # Cancel safety
+    ///
+    /// Waiting senders are served in order. If the future is dropped while
+    /// waiting, the sender loses its place in the queue; capacity it was
+    /// notified about goes to the next waiting sender.
+    ///
+    /// [`send`]: Permit::send
+    pub async fn reserve(&self) -> Result<Permit<'_, T>, SendError<()>> {
+        if self.chan.acquire().await.is_err() {
+            return Err(SendError(()));
+        }
+
+        Ok(Permit { chan: &self.chan })
+    }
+
+    /// Checks if the channel has been closed. This happens when the
+    /// [`Receiver`] is dropped, or when the [`Receiver::close`] method is
+    /// called.
+    pub fn is_closed(&self) -> bool {
+        self.chan.is_closed()
+    }
+
+    /// Completes when the channel is closed: the [`Receiver`] is dropped, or
+    /// the [`Receiver::close`] method is called.
+    ///
+    /// A producer selects on `closed` to stop generating work once the
+    /// consumer is gone, without waiting for its next `send` to fail.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe.
+    pub async fn closed(&self) {
+        self.chan.closed().await;
+    }
+
+    /// Returns the current capacity of the channel.
+    ///
+    /// The capacity goes down when sending a value by calling [`send`] or by
+    /// reserving capacity with [`reserve`]. The capacity goes up when values
+    /// are received by the [`Receiver`].
+    ///
+    /// [`send`]: Sender::send
+    /// [`reserve`]: Sender::reserve
+    pub fn capacity(&self) -> usize {
+        self.chan.permits().expect("bounded channel has a capacity")
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.chan.inc_tx();
+        Sender {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        self.chan.dec_tx();
+    }
+}
+
+impl<T> fmt::Debug for Sender<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Sender")
+            .field("chan", &self.chan)
+            .finish()
+    }
+}
+
+// ===== impl Permit =====
+
+/// Permits to send one value into the channel.
+///
+/// `Permit` values are returned by [`Sender::reserve()`] and are used to
+/// guarantee channel capacity before generating a message to send.
+pub struct Permit<'a, T> {
+    chan: &'a Chan<T>,
+}
+
+impl<T> Permit<'_, T> {
+    /// Sends a value using the reserved capacity.
+    ///
+    /// Capacity for the message has already been reserved. The message is
+    /// sent to the receiver and the permit is consumed. The operation will
+    /// succeed even if the receiver half has been closed. See
+    /// [`Receiver::close`] for more details on performing a clean shutdown.
+    pub fn send(self, value: T) {
+        // The value is dropped if the receiver is gone, like the values still
+        // in the queue when it was dropped.
+        let _ = self.chan.send(value);
+
+        // The permit is consumed by the value now in the queue, the receiver
+        // gives it back.
+        std::mem::forget(self);
+    }
+}
+
+impl<T> Drop for Permit<'_, T> {
+    fn drop(&mut self) {
+        // The permit was not used, return the capacity to the channel.
+        self.chan.release();
+    }
+}
+
+impl<T> fmt::Debug for Permit<'_, T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Permit")
+            .field("chan", &self.chan)
+            .finish()
+    }
+}
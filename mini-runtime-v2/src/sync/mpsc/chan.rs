@@ -0,0 +1,370 @@
+//! State shared by the senders and the receiver of a channel.
+//!
+//! The values are queued in a lock-free linked list, see [`List`]: sending
+//! on an unbounded channel, and receiving, never take a lock. The receiver
+//! registers its waker in an `AtomicWaker` before checking the list again,
+//! a sender wakes it after pushing.
+//!
+//! The bounded channel adds a semaphore in front of the list: a sender first
+//! acquires one of the `bound` permits, then pushes its value; the receiver
+//! gives the permit back when it pops the value. Senders waiting for a permit
+//! are queued and served in order: the first `permits` waiters of the queue
+//! are the ones entitled to a permit, and a sender that did not wait only
+//! gets one when all the waiters are served.
+//!
+//! Receiving and acquiring a permit consume the task's coop budget, so that
+//! a task looping over a channel that is always ready still yields.
+
+use crate::runtime::coop;
+use crate::sync::CancellationToken;
+use crate::sync::mpsc::list::List;
+use crate::util::AtomicWaker;
+use crate::util::loom::sync::Mutex;
+use crate::util::loom::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll, Waker};
+
+pub(super) struct Chan<T> {
+    /// Values sent and not received yet.
+    list: List<T>,
+
+    /// Receiver waiting for a value.
+    rx_waker: AtomicWaker,
+
+    /// Number of outstanding senders, the channel is closed when it drops to 0.
+    tx_count: AtomicUsize,
+
+    /// Set when the receiver is closed or dropped.
+    rx_closed: AtomicBool,
+
+    /// Cancelled right after `rx_closed` is set, wakes the senders waiting
+    /// in `closed`.
+    rx_closed_notify: CancellationToken,
+
+    /// Permits of a bounded channel, `None` for an unbounded channel.
+    semaphore: Option<Mutex<Semaphore>>,
+}
+
+struct Semaphore {
+    /// Permits available to the senders.
+    permits: usize,
+
+    /// Senders waiting for a permit, with the id of their `Acquire` future.
+    waiters: VecDeque<(u64, Waker)>,
+
+    /// Id given to the next waiting sender.
+    next_waiter_id: u64,
+}
+
+/// The receiver is gone, the value can't be sent.
+pub(super) struct Closed;
+
+/// Why a permit could not be acquired without waiting.
+pub(super) enum TryAcquireError {
+    Closed,
+    NoPermits,
+}
+
+impl<T> Chan<T> {
+    pub(super) fn new(bound: Option<usize>) -> Chan<T> {
+        Chan {
+            list: List::new(),
+            rx_waker: AtomicWaker::new(),
+            tx_count: AtomicUsize::new(1),
+            rx_closed: AtomicBool::new(false),
+            rx_closed_notify: CancellationToken::new(),
+            semaphore: bound.map(|permits| {
+                Mutex::new(Semaphore {
+                    permits,
+                    waiters: VecDeque::new(),
+                    next_waiter_id: 0,
+                })
+            }),
+        }
+    }
+
+    /// Waits for a permit to send one value.
+    pub(super) fn acquire(&self) -> Acquire<'_, T> {
+        Acquire {
+            chan: self,
+            waiter: None,
+            done: false,
+        }
+    }
+
+    /// Acquires a permit without waiting.
+    pub(super) fn try_acquire(&self) -> Result<(), TryAcquireError> {
+        let Some(semaphore) = &self.semaphore else {
+            return match self.is_closed() {
+                true => Err(TryAcquireError::Closed),
+                false => Ok(()),
+            };
+        };
+
+        // `close` sets the flag before locking: checked under the lock, the
+        // flag can't be missed by a sender which then waits.
+        let mut semaphore = semaphore.lock().unwrap();
+        if self.is_closed() {
+            return Err(TryAcquireError::Closed);
+        }
+
+        if !semaphore.has_spare_permit() {
+            return Err(TryAcquireError::NoPermits);
+        }
+
+        semaphore.permits -= 1;
+        Ok(())
+    }
+
+    /// Gives back a permit acquired but not used to send a value, or taken
+    /// by a value which was received.
+    pub(super) fn release(&self) {
+        let Some(semaphore) = &self.semaphore else {
+            return;
+        };
+
+        let waker = semaphore.lock().unwrap().add_permit();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+
+    /// Returns the number of permits available, `None` for an unbounded
+    /// channel.
+    pub(super) fn permits(&self) -> Option<usize> {
+        let semaphore = self.semaphore.as_ref()?;
+        Some(semaphore.lock().unwrap().permits)
+    }
+
+    /// Pushes a value, the caller must hold a permit (or the channel must be
+    /// unbounded).
+    pub(super) fn send(&self, value: T) -> Result<(), T> {
+        if self.is_closed() {
+            return Err(value);
+        }
+
+        self.list.push(value);
+        self.rx_waker.wake();
+        Ok(())
+    }
+
+    pub(super) fn poll_recv(&self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        // A receiver looping over a flooded channel would never yield.
+        let coop = ready!(coop::poll_proceed(cx));
+
+        if let Some(value) = self.pop() {
+            coop.made_progress();
+            return Poll::Ready(Some(value));
+        }
+
+        self.rx_waker.register_by_ref(cx.waker());
+
+        // A value sent, or the last sender dropped, before the waker was
+        // registered didn't wake the receiver: check again. The values sent
+        // before the channel closed are all linked once it is seen closed.
+        let closed = self.tx_count.load(Ordering::Acquire) == 0 || self.is_closed();
+
+        if let Some(value) = self.pop() {
+            coop.made_progress();
+            return Poll::Ready(Some(value));
+        }
+
+        if closed {
+            coop.made_progress();
+            return Poll::Ready(None);
+        }
+
+        Poll::Pending
+    }
+
+    /// Pops a value and gives its permit back.
+    fn pop(&self) -> Option<T> {
+        // Safety: only the receiver, through `poll_recv` and `drain`, pops.
+        let value = unsafe { self.list.pop() }?;
+        self.release();
+        Some(value)
+    }
+
+    pub(super) fn is_closed(&self) -> bool {
+        self.rx_closed.load(Ordering::Acquire)
+    }
+
+    /// Waits until the receiving half is closed.
+    pub(super) async fn closed(&self) {
+        self.rx_closed_notify.cancelled().await;
+    }
+
+    /// Closes the receiving half: pending and future sends fail, the values
+    /// already in the queue can still be received.
+    pub(super) fn close(&self) {
+        self.rx_closed.store(true, Ordering::Release);
+        self.rx_closed_notify.cancel();
+
+        let Some(semaphore) = &self.semaphore else {
+            return;
+        };
+        let waiters = std::mem::take(&mut semaphore.lock().unwrap().waiters);
+
+        for (_, waker) in waiters {
+            waker.wake();
+        }
+    }
+
+    /// Drops the values that were never received, called when the receiver
+    /// is dropped.
+    pub(super) fn drain(&self) {
+        while self.pop().is_some() {}
+    }
+
+    pub(super) fn inc_tx(&self) {
+        self.tx_count.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Wakes the receiver when the last sender is dropped.
+    pub(super) fn dec_tx(&self) {
+        if self.tx_count.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.rx_waker.wake();
+        }
+    }
+}
+
+impl Semaphore {
+    /// Returns a permit, and the waker of the sender to notify.
+    ///
+    /// The sender stays queued until it takes the permit, so that a sender
+    /// which did not wait can't take it first.
+    fn add_permit(&mut self) -> Option<Waker> {
+        self.permits += 1;
+        self.waiters
+            .get(self.permits - 1)
+            .map(|(_, waker)| waker.clone())
+    }
+
+    /// Returns `true` if a permit is left once every waiting sender got one.
+    fn has_spare_permit(&self) -> bool {
+        self.permits > self.waiters.len()
+    }
+}
+
+impl<T> fmt::Debug for Chan<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Chan")
+            .field("permits", &self.permits())
+            .field("tx_count", &self.tx_count.load(Ordering::Relaxed))
+            .field("rx_closed", &self.is_closed())
+            .finish()
+    }
+}
+
+/// Future returned by [`Chan::acquire`].
+///
+/// Dropping the future before it completes removes the sender from the wait
+/// queue. If it was entitled to a permit, the next waiting sender becomes
+/// entitled to it and is notified, so that the permit is not lost.
+pub(super) struct Acquire<'a, T> {
+    chan: &'a Chan<T>,
+    /// Id of the entry in `waiters`, once the sender had to wait.
+    waiter: Option<u64>,
+    done: bool,
+}
+
+impl<T> Future for Acquire<'_, T> {
+    type Output = Result<(), Closed>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let coop = ready!(coop::poll_proceed(cx));
+
+        let me = self.get_mut();
+
+        // Unbounded channel
+        let Some(semaphore) = &me.chan.semaphore else {
+            coop.made_progress();
+            me.done = true;
+            return match me.chan.is_closed() {
+                true => Poll::Ready(Err(Closed)),
+                false => Poll::Ready(Ok(())),
+            };
+        };
+
+        let mut semaphore = semaphore.lock().unwrap();
+        let semaphore = &mut *semaphore;
+
+        if me.chan.is_closed() {
+            coop.made_progress();
+            me.done = true;
+            return Poll::Ready(Err(Closed));
+        }
+
+        let position = me
+            .waiter
+            .and_then(|id| semaphore.waiters.iter().position(|(w, _)| *w == id));
+
+        match position {
+            // One of the first `permits` waiters: take the permit and leave
+            // the queue.
+            Some(pos) if pos < semaphore.permits => {
+                semaphore.permits -= 1;
+                semaphore.waiters.remove(pos);
+            }
+            // Still waiting, keep the position in the queue.
+            Some(pos) => {
+                let (_, waker) = &mut semaphore.waiters[pos];
+                if !waker.will_wake(cx.waker()) {
+                    *waker = cx.waker().clone();
+                }
+                return Poll::Pending;
+            }
+            None if semaphore.has_spare_permit() => {
+                semaphore.permits -= 1;
+            }
+            None => {
+                let id = *me.waiter.get_or_insert_with(|| {
+                    let id = semaphore.next_waiter_id;
+                    semaphore.next_waiter_id += 1;
+                    id
+                });
+                semaphore.waiters.push_back((id, cx.waker().clone()));
+                return Poll::Pending;
+            }
+        }
+
+        coop.made_progress();
+        me.done = true;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T> Drop for Acquire<'_, T> {
+    fn drop(&mut self) {
+        let Some(id) = self.waiter else { return };
+        if self.done {
+            return;
+        }
+        let Some(semaphore) = &self.chan.semaphore else {
+            return;
+        };
+
+        let waker = {
+            let mut semaphore = semaphore.lock().unwrap();
+            let Some(pos) = semaphore.waiters.iter().position(|(w, _)| *w == id) else {
+                return;
+            };
+            semaphore.waiters.remove(pos);
+
+            // The sender was entitled to a permit: it goes to the waiter that
+            // moved up to the last entitled position.
+            if pos < semaphore.permits {
+                semaphore.waiters.get(semaphore.permits - 1).cloned()
+            } else {
+                None
+            }
+        };
+
+        if let Some((_, waker)) = waker {
+            waker.wake();
+        }
+    }
+}
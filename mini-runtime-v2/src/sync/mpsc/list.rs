@@ -0,0 +1,112 @@
+//! A lock-free, multi-producer single-consumer queue: a singly linked list
+//! of values.
+//!
+//! This is Dmitry Vyukov's non-intrusive MPSC queue. The list always starts
+//! with a stub node, whose value was already received. A sender appends a
+//! node in two steps: it swaps the node in as the new `tail`, then links it
+//! as the `next` of the previous tail. The receiver pops by following the
+//! stub's `next`: that node becomes the new stub once its value is moved
+//! out, and the old stub is freed.
+//!
+//! Between the two steps of a push, the list is cut at the previous tail:
+//! the receiver sees it empty until the link is written. The sender wakes
+//! the receiver after linking, so the value is not missed, only seen a bit
+//! later.
+//!
+//! Every value is a heap allocation: the memory used by the queue grows
+//! with the number of values sent and not received, without any bound.
+
+use crate::util::loom::sync::atomic::AtomicPtr;
+use crate::util::loom::sync::atomic::Ordering::{AcqRel, Acquire, Relaxed, Release};
+use std::marker::PhantomData;
+use std::ptr;
+
+pub(super) struct List<T> {
+    /// The stub node, only used by the receiver.
+    head: AtomicPtr<Node<T>>,
+
+    /// The last node, where the senders append.
+    tail: AtomicPtr<Node<T>>,
+
+    _values: PhantomData<T>,
+}
+
+struct Node<T> {
+    next: AtomicPtr<Node<T>>,
+
+    /// `None` once received, for the stub node.
+    value: Option<T>,
+}
+
+// Safety: the values are moved from the senders to the receiver, and never
+// shared.
+unsafe impl<T: Send> Send for List<T> {}
+unsafe impl<T: Send> Sync for List<T> {}
+
+impl<T> List<T> {
+    pub(super) fn new() -> List<T> {
+        let stub = Node::alloc(None);
+        List {
+            head: AtomicPtr::new(stub),
+            tail: AtomicPtr::new(stub),
+            _values: PhantomData,
+        }
+    }
+
+    /// Appends `value` to the list. Never blocks, whatever the other senders
+    /// and the receiver do.
+    pub(super) fn push(&self, value: T) {
+        let node = Node::alloc(Some(value));
+        let prev = self.tail.swap(node, AcqRel);
+
+        // Safety: `prev` is freed by the receiver only once its `next` is
+        // set, which is done here, by the only sender which got `prev`.
+        unsafe { (*prev).next.store(node, Release) };
+    }
+
+    /// Removes the first value of the list.
+    ///
+    /// Returns `None` if the list is empty, or if the sender of the first
+    /// value has not linked it yet.
+    ///
+    /// # Safety
+    ///
+    /// There must be a single receiver: `pop` must not be called
+    /// concurrently.
+    pub(super) unsafe fn pop(&self) -> Option<T> {
+        let head = self.head.load(Relaxed);
+
+        // Safety: the stub is only freed by `pop`, which is not concurrent.
+        let next = unsafe { (*head).next.load(Acquire) };
+        if next.is_null() {
+            return None;
+        }
+
+        self.head.store(next, Relaxed);
+
+        // Safety: `next` is the new stub, only the receiver accesses its
+        // value. The old stub is linked, no sender uses it anymore.
+        unsafe {
+            let value = (*next).value.take();
+            drop(Box::from_raw(head));
+            value
+        }
+    }
+}
+
+impl<T> Drop for List<T> {
+    fn drop(&mut self) {
+        // Safety: `&mut self` rules out any concurrent `push` or `pop`.
+        while unsafe { self.pop() }.is_some() {}
+        drop(unsafe { Box::from_raw(self.head.load(Relaxed)) });
+    }
+}
+
+impl<T> Node<T> {
+    fn alloc(value: Option<T>) -> *mut Node<T> {
+        Box::into_raw(Box::new(Node {
+            next: AtomicPtr::new(ptr::null_mut()),
+            value,
+        }))
+    }
+}
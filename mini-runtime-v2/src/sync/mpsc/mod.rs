@@ -0,0 +1,100 @@
+//! A multi-producer, single-consumer queue for sending values between
+//! asynchronous tasks.
+//!
+//! This module provides two variants of the channel: bounded and unbounded.
+//! The bounded variant has a limit on the number of messages that the channel
+//! can store, and if this limit is reached, trying to send another message
+//! will wait until a message is received from the channel. An unbounded
+//! channel has an infinite capacity, so the `send` method will always complete
+//! immediately.
+//!
+//! Each channel has a single receiver and any number of senders. When all
+//! the senders are dropped, the receiver gets the remaining messages and then
+//! `None`. When the receiver is dropped, sending fails with a [`SendError`]
+//! which gives the value back.
+//!
+//! # Backpressure
+//!
+//! The capacity of a bounded channel is what slows a fast producer down to
+//! the pace of the consumer: `send` waits while the channel is full. A sender
+//! that can't wait uses [`Sender::try_send`], which fails with
+//! [`TrySendError::Full`] instead. [`Sender::reserve`] waits for capacity
+//! *before* the value is produced, the returned [`Permit`] then sends without
+//! waiting. This is useful when producing the value is expensive, or when
+//! the value must not be lost if the wait is cancelled.
+//!
+//! An unbounded channel never pushes back: if the consumer falls behind, the
+//! queue, and the memory used, grows without limit.
+//!
+//! ```
+//! use mini_runtime_v2::sync::mpsc;
+//! # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+//! # rt.block_on(async {
+//!
+//! let (tx, mut rx) = mpsc::channel(100);
+//!
+//! mini_runtime_v2::spawn(async move {
+//!     for i in 0..10 {
+//!         tx.send(i).await.unwrap();
+//!     }
+//! });
+//!
+//! while let Some(i) = rx.recv().await {
+//!     println!("got = {i}");
+//! }
+//! # });
+//! ```
+//!
+//! # Closing
+//!
+//! A consumer shuts down gracefully with [`Receiver::close`]: the senders
+//! can't send anymore, and the messages already in the channel are still
+//! received, followed by `None`. Dropping the receiver closes the channel
+//! the same way, then drops the messages that were never received.
+//!
+//! In both cases the channel is closed first: `send` calls waiting for
+//! capacity fail right away, and [`Sender::closed`] completes. A producer
+//! that doesn't send for a while, e.g. because producing the next message
+//! is slow, selects on `closed` to stop early instead of discovering the
+//! consumer is gone on its next `send`:
+//!
+//! ```
+//! # use mini_runtime_v2::{select, sync::mpsc};
+//! # async fn produce() -> u32 {
+//! #     std::future::pending().await
+//! # }
+//! # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+//! # rt.block_on(async {
+//! # let (tx, rx) = mpsc::channel(1);
+//! # drop(rx);
+//! loop {
+//!     select! {
+//!         message = produce() => {
+//!             if tx.send(message).await.is_err() {
+//!                 break;
+//!             }
+//!         }
+//!         _ = tx.closed() => break,
+//!     }
+//! }
+//! # });
+//! ```
+//!
+//! A message whose `send` completed before `close` was called is always
+//! received, after the ones sent before it. A `send` racing with `close` may
+//! succeed, its message is then dropped with the channel, never received.
+//!
+//! [`SendError`]: error::SendError
+//! [`TrySendError::Full`]: error::TrySendError::Full
+
+pub(super) mod bounded;
+pub use self::bounded::{Permit, Receiver, Sender, channel};
+
+mod chan;
+
+mod list;
+
+pub(super) mod unbounded;
+pub use self::unbounded::{UnboundedReceiver, UnboundedSender, unbounded_channel};
+
+pub mod error;
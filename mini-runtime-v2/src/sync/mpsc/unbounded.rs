@@ -0,0 +1,165 @@
+use crate::future::poll_fn;
+use crate::stream::Stream;
+use crate::sync::mpsc::chan::Chan;
+use crate::sync::mpsc::error::SendError;
+use std::fmt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Send values to the associated `UnboundedReceiver`.
+///
+/// Instances are created by the [`unbounded_channel`] function.
+pub struct UnboundedSender<T> {
+    chan: Arc<Chan<T>>,
+}
+
+/// Receive values from the associated `UnboundedSender`.
+///
+/// Instances are created by the [`unbounded_channel`] function.
+pub struct UnboundedReceiver<T> {
+    chan: Arc<Chan<T>>,
+}
+
+/// Creates an unbounded mpsc channel for communicating between asynchronous
+/// tasks without backpressure.
+///
+/// A `send` on this channel will always succeed as long as the receive half
+/// has not been closed. If the receiver falls behind, messages will be
+/// arbitrarily buffered.
+///
+/// The values are queued in a lock-free linked list: `send` never takes a
+/// lock, even with many senders on many threads, and each value queued is a
+/// heap allocation.
+///
+/// **Note** that the amount of available system memory is an implicit bound
+/// to the channel. Using an `unbounded` channel has the ability of causing the
+/// process to run out of memory. In this case, the process will be aborted.
+pub fn unbounded_channel<T>() -> (UnboundedSender<T>, UnboundedReceiver<T>) {
+    let chan = Arc::new(Chan::new(None));
+    let tx = UnboundedSender { chan: chan.clone() };
+    let rx = UnboundedReceiver { chan };
+
+    (tx, rx)
+}
+
+impl<T> UnboundedReceiver<T> {
+    /// Receives the next value for this receiver.
+    ///
+    /// This method returns `None` if the channel has been closed and there are
+    /// no remaining messages in the channel's buffer.
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Closes the receiving half of a channel, without dropping it.
+    ///
+    /// This prevents any further messages from being sent on the channel
+    /// while still enabling the receiver to drain messages that are buffered.
+    /// Senders waiting in [`UnboundedSender::closed`] are woken.
+    pub fn close(&mut self) {
+        self.chan.close();
+    }
+
+    /// Polls to receive the next message on this channel.
+    ///
+    /// This method returns:
+    ///
+    ///  * `Poll::Pending` if no messages are available but the channel is not
+    ///    closed.
+    ///  * `Poll::Ready(Some(message))` if a message is available.
+    ///  * `Poll::Ready(None)` if the channel has been closed and all messages
+    ///    sent before it was closed have been received.
+    ///
+    /// When the method returns `Poll::Pending`, the `Waker` in the provided
+    /// `Context` is scheduled to receive a wakeup when a message is sent, or
+    /// when the channel is closed.
+    pub fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.chan.poll_recv(cx)
+    }
+}
+
+impl<T> Drop for UnboundedReceiver<T> {
+    fn drop(&mut self) {
+        self.chan.close();
+        self.chan.drain();
+    }
+}
+
+impl<T> Stream for UnboundedReceiver<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<T>> {
+        self.get_mut().poll_recv(cx)
+    }
+}
+
+impl<T> fmt::Debug for UnboundedReceiver<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("UnboundedReceiver")
+            .field("chan", &self.chan)
+            .finish()
+    }
+}
+
+impl<T> UnboundedSender<T> {
+    /// Attempts to send a message on this `UnboundedSender` without blocking.
+    ///
+    /// This method is not marked async because sending a message to an
+    /// unbounded channel never requires any form of waiting. Because of this,
+    /// the `send` method can be used in both synchronous and asynchronous
+    /// code without problems.
+    ///
+    /// If the receive half of the channel is closed, either due to [`close`]
+    /// being called or the [`UnboundedReceiver`] having been dropped, this
+    /// function returns an error. The error includes the value passed to
+    /// `send`.
+    ///
+    /// [`close`]: UnboundedReceiver::close
+    pub fn send(&self, message: T) -> Result<(), SendError<T>> {
+        self.chan.send(message).map_err(SendError)
+    }
+
+    /// Checks if the channel has been closed. This happens when the
+    /// [`UnboundedReceiver`] is dropped, or when the
+    /// [`UnboundedReceiver::close`] method is called.
+    pub fn is_closed(&self) -> bool {
+        self.chan.is_closed()
+    }
+
+    /// Completes when the channel is closed: the [`UnboundedReceiver`] is
+    /// dropped, or the [`UnboundedReceiver::close`] method is called.
+    ///
+    /// A producer selects on `closed` to stop generating work once the
+    /// consumer is gone, sending never fails until then.
+    ///
+    /// # Cancel safety
+    ///
+    /// This method is cancel safe.
+    pub async fn closed(&self) {
+        self.chan.closed().await;
+    }
+}
+
+impl<T> Clone for UnboundedSender<T> {
+    fn clone(&self) -> Self {
+        self.chan.inc_tx();
+        UnboundedSender {
+            chan: self.chan.clone(),
+        }
+    }
+}
+
+impl<T> Drop for UnboundedSender<T> {
+    fn drop(&mut self) {
+        self.chan.dec_tx();
+    }
+}
+
+impl<T> fmt::Debug for UnboundedSender<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("UnboundedSender")
+            .field("chan", &self.chan)
+            .finish()
+    }
+}
@@ -0,0 +1,220 @@
+//! A cell initialized once, by an asynchronous function.
+//!
+//! The value lives in a `std::sync::OnceLock`, reads don't take any lock.
+//! The initializers are serialized by a semaphore with a single permit: the
+//! first task to get it runs its initializer, the others wait for the permit
+//! and find the cell set. If the initializer fails or is cancelled, the
+//! permit goes to the next waiting task, which runs its own.
+
+use crate::sync::Semaphore;
+use std::convert::Infallible;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::sync::OnceLock;
+
+/// A thread-safe cell that can be written to only once.
+///
+/// The asynchronous counterpart of [`std::sync::OnceLock`]: the value is
+/// computed by an `async` function, and the tasks calling
+/// [`get_or_init`](OnceCell::get_or_init) concurrently wait for a single
+/// initializer instead of blocking their thread.
+///
+/// A lazily opened connection, shared by all the tasks:
+///
+/// ```
+/// use mini_runtime_v2::sync::OnceCell;
+/// # struct Connection;
+/// # impl Connection {
+/// #     async fn open(_addr: &str) -> Connection {
+/// #         Connection
+/// #     }
+/// # }
+///
+/// static CONNECTION: OnceCell<Connection> = OnceCell::const_new();
+///
+/// async fn connection() -> &'static Connection {
+///     CONNECTION.get_or_init(|| Connection::open("db.example:5432")).await
+/// }
+/// ```
+pub struct OnceCell<T> {
+    value: OnceLock<T>,
+
+    /// A single permit, held by the running initializer.
+    init: Semaphore,
+}
+
+/// Errors that can be returned from [`OnceCell::set`].
+#[derive(Debug, PartialEq, Eq)]
+pub enum SetError<T> {
+    /// The cell was already initialized. The value is given back.
+    AlreadyInitializedError(T),
+
+    /// The cell is being initialized. The value is given back.
+    InitializingError(T),
+}
+
+impl<T> OnceCell<T> {
+    /// Creates a new empty `OnceCell` instance.
+    pub fn new() -> OnceCell<T> {
+        OnceCell {
+            value: OnceLock::new(),
+            init: Semaphore::new(1),
+        }
+    }
+
+    /// Creates a new empty `OnceCell` instance, in a constant context, e.g.
+    /// a `static`.
+    #[cfg(not(all(test, loom)))]
+    pub const fn const_new() -> OnceCell<T> {
+        OnceCell {
+            value: OnceLock::new(),
+            init: Semaphore::const_new(1),
+        }
+    }
+
+    /// Creates a new `OnceCell` that contains `value`, if `Some`.
+    pub fn new_with(value: Option<T>) -> OnceCell<T> {
+        let cell = OnceCell::new();
+        if let Some(value) = value {
+            let _ = cell.value.set(value);
+        }
+        cell
+    }
+
+    /// Returns `true` if the cell holds a value.
+    pub fn initialized(&self) -> bool {
+        self.value.get().is_some()
+    }
+
+    /// Returns a reference to the value, or `None` if the cell is empty.
+    pub fn get(&self) -> Option<&T> {
+        self.value.get()
+    }
+
+    /// Returns a mutable reference to the value, or `None` if the cell is
+    /// empty.
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        self.value.get_mut()
+    }
+
+    /// Sets the value of the cell.
+    ///
+    /// Fails if the cell already holds a value, or if an initializer is
+    /// running: `set` doesn't wait for it.
+    pub fn set(&self, value: T) -> Result<(), SetError<T>> {
+        if self.initialized() {
+            return Err(SetError::AlreadyInitializedError(value));
+        }
+
+        let Ok(_permit) = self.init.try_acquire() else {
+            return Err(SetError::InitializingError(value));
+        };
+
+        // An initializer may have completed before the permit was acquired.
+        self.value
+            .set(value)
+            .map_err(SetError::AlreadyInitializedError)
+    }
+
+    /// Returns the value of the cell, initializing it with `f` if the cell
+    /// is empty.
+    ///
+    /// Concurrent calls are deduplicated: a single `f` runs at a time, the
+    /// other callers wait for it and return its value.
+    ///
+    /// # Cancel safety
+    ///
+    /// If the returned future is dropped while `f` runs, the cell stays
+    /// empty and the next waiting caller runs its own `f`.
+    pub async fn get_or_init<F, Fut>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let Ok(value) = self
+            .get_or_try_init(|| async { Ok::<T, Infallible>(f().await) })
+            .await;
+        value
+    }
+
+    /// Returns the value of the cell, initializing it with `f` if the cell
+    /// is empty.
+    ///
+    /// Like [`get_or_init`](OnceCell::get_or_init), but `f` can fail: the
+    /// error is returned, the cell stays empty and the next waiting caller
+    /// runs its own `f`.
+    pub async fn get_or_try_init<E, F, Fut>(&self, f: F) -> Result<&T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+
+        // The semaphore is never closed.
+        let _permit = self.init.acquire().await.unwrap();
+
+        // Initialized by the task which held the permit before.
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+
+        let value = f().await?;
+        Ok(self.value.get_or_init(|| value))
+    }
+
+    /// Takes the value out of the cell, leaving it empty.
+    pub fn take(&mut self) -> Option<T> {
+        self.value.take()
+    }
+
+    /// Consumes the cell, returning the value it holds, if any.
+    pub fn into_inner(self) -> Option<T> {
+        self.value.into_inner()
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> OnceCell<T> {
+        OnceCell::new()
+    }
+}
+
+impl<T> From<T> for OnceCell<T> {
+    fn from(value: T) -> OnceCell<T> {
+        OnceCell::new_with(Some(value))
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OnceCell<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("OnceCell")
+            .field("value", &self.get())
+            .finish()
+    }
+}
+
+impl<T> SetError<T> {
+    /// Returns `true` if the cell was already initialized.
+    pub fn is_already_init_err(&self) -> bool {
+        matches!(self, SetError::AlreadyInitializedError(_))
+    }
+
+    /// Returns `true` if the cell was being initialized.
+    pub fn is_initializing_err(&self) -> bool {
+        matches!(self, SetError::InitializingError(_))
+    }
+}
+
+impl<T> fmt::Display for SetError<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetError::AlreadyInitializedError(_) => write!(fmt, "cell already initialized"),
+            SetError::InitializingError(_) => write!(fmt, "cell is being initialized"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> Error for SetError<T> {}
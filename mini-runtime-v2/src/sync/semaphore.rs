@@ -0,0 +1,448 @@
+//! A counting semaphore, to limit the concurrency of asynchronous tasks.
+//!
+//! The semaphore holds a number of permits. A task acquires a permit before
+//! entering the guarded section and gives it back by dropping the permit.
+//! Tasks waiting for a permit are queued and woken one per released permit,
+//! in order, like the senders of a bounded channel.
+
+use crate::runtime::coop;
+use crate::util::loom::sync::Mutex;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, Waker};
+
+/// Counting semaphore performing asynchronous permit acquisition.
+///
+/// A semaphore maintains a set of permits. Permits are used to synchronize
+/// access to a shared resource: [`acquire`](Semaphore::acquire) waits until
+/// a permit is available, the permit is given back to the semaphore when the
+/// returned [`SemaphorePermit`] is dropped.
+///
+/// Limiting the number of connections handled at once:
+///
+/// ```no_run
+/// use mini_runtime_v2::sync::Semaphore;
+/// use std::sync::Arc;
+/// # use mini_runtime_v2::net::{TcpListener, TcpStream};
+/// # use mini_runtime_v2::task;
+/// # async fn process(_socket: TcpStream) {}
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+/// # let listener = TcpListener::bind("127.0.0.1:8080").await?;
+///
+/// let limit = Arc::new(Semaphore::new(100));
+///
+/// loop {
+///     let permit = limit.clone().acquire_owned().await.unwrap();
+///     let (socket, _) = listener.accept().await?;
+///
+///     task::spawn(async move {
+///         process(socket).await;
+///         drop(permit);
+///     });
+/// }
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+pub struct Semaphore {
+    state: Mutex<State>,
+}
+
+struct State {
+    permits: usize,
+
+    /// Tasks waiting for a permit, with the id of their `Acquire` future.
+    waiters: VecDeque<(u64, Waker)>,
+
+    /// Id given to the next waiting task.
+    next_waiter_id: u64,
+
+    /// Set by `close`, no permit can be acquired anymore.
+    closed: bool,
+}
+
+/// A permit from the semaphore, given back when dropped.
+///
+/// Returned by [`Semaphore::acquire`] and [`Semaphore::try_acquire`].
+#[must_use]
+pub struct SemaphorePermit<'a> {
+    sem: &'a Semaphore,
+    permits: usize,
+}
+
+/// An owned permit from the semaphore, given back when dropped.
+///
+/// Returned by [`Semaphore::acquire_owned`] and
+/// [`Semaphore::try_acquire_owned`]. Unlike [`SemaphorePermit`], it can be
+/// moved into a spawned task.
+#[must_use]
+pub struct OwnedSemaphorePermit {
+    sem: Arc<Semaphore>,
+    permits: usize,
+}
+
+/// Error returned from the [`Semaphore::acquire`] function: the semaphore
+/// was closed.
+#[derive(Debug, PartialEq, Eq)]
+pub struct AcquireError(());
+
+/// Error returned from the [`Semaphore::try_acquire`] function.
+#[derive(Debug, PartialEq, Eq)]
+pub enum TryAcquireError {
+    /// The semaphore has been closed and cannot issue new permits.
+    Closed,
+
+    /// The semaphore has no available permits.
+    NoPermits,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the initial number of permits.
+    pub fn new(permits: usize) -> Semaphore {
+        Semaphore {
+            state: Mutex::new(State {
+                permits,
+                waiters: VecDeque::new(),
+                next_waiter_id: 0,
+                closed: false,
+            }),
+        }
+    }
+
+    /// Creates a new semaphore with the initial number of permits, in a
+    /// constant context, e.g. a `static`.
+    #[cfg(not(all(test, loom)))]
+    pub const fn const_new(permits: usize) -> Semaphore {
+        Semaphore {
+            state: Mutex::new(State {
+                permits,
+                waiters: VecDeque::new(),
+                next_waiter_id: 0,
+                closed: false,
+            }),
+        }
+    }
+
+    /// Returns the current number of available permits.
+    pub fn available_permits(&self) -> usize {
+        self.state.lock().unwrap().permits
+    }
+
+    /// Adds `n` new permits to the semaphore, waking up to `n` waiting tasks.
+    pub fn add_permits(&self, n: usize) {
+        let wakers = {
+            let mut state = self.state.lock().unwrap();
+            state.permits += n;
+
+            let woken = n.min(state.waiters.len());
+            state.waiters.drain(..woken).collect::<Vec<_>>()
+        };
+
+        for (_, waker) in wakers {
+            waker.wake();
+        }
+    }
+
+    /// Acquires a permit from the semaphore.
+    ///
+    /// If the semaphore has been closed, this returns an [`AcquireError`].
+    /// Otherwise, this returns a [`SemaphorePermit`] representing the
+    /// acquired permit.
+    ///
+    /// # Cancel safety
+    ///
+    /// Dropping the future gives up the task's place in the queue. A permit
+    /// the task was woken for is passed on to the next waiting task.
+    pub async fn acquire(&self) -> Result<SemaphorePermit<'_>, AcquireError> {
+        Acquire::new(self).await?;
+        Ok(SemaphorePermit {
+            sem: self,
+            permits: 1,
+        })
+    }
+
+    /// Acquires a permit from the semaphore, owning a reference to it.
+    ///
+    /// The semaphore must be wrapped in an [`Arc`] to call this method. See
+    /// [`acquire`](Semaphore::acquire) for the errors and cancel safety.
+    pub async fn acquire_owned(self: Arc<Self>) -> Result<OwnedSemaphorePermit, AcquireError> {
+        Acquire::new(&self).await?;
+        Ok(OwnedSemaphorePermit {
+            sem: self,
+            permits: 1,
+        })
+    }
+
+    /// Tries to acquire a permit from the semaphore without waiting.
+    ///
+    /// Returns [`TryAcquireError::Closed`] if the semaphore has been closed
+    /// and [`TryAcquireError::NoPermits`] if there are no permits left.
+    pub fn try_acquire(&self) -> Result<SemaphorePermit<'_>, TryAcquireError> {
+        self.try_acquire_inner()?;
+        Ok(SemaphorePermit {
+            sem: self,
+            permits: 1,
+        })
+    }
+
+    /// Tries to acquire a permit from the semaphore without waiting, owning
+    /// a reference to it.
+    ///
+    /// See [`try_acquire`](Semaphore::try_acquire) for the errors.
+    pub fn try_acquire_owned(self: Arc<Self>) -> Result<OwnedSemaphorePermit, TryAcquireError> {
+        self.try_acquire_inner()?;
+        Ok(OwnedSemaphorePermit {
+            sem: self,
+            permits: 1,
+        })
+    }
+
+    /// Closes the semaphore.
+    ///
+    /// The tasks waiting for a permit, and all the later calls to `acquire`,
+    /// fail with an [`AcquireError`]. The permits already acquired are not
+    /// affected.
+    pub fn close(&self) {
+        let waiters = {
+            let mut state = self.state.lock().unwrap();
+            state.closed = true;
+            std::mem::take(&mut state.waiters)
+        };
+
+        for (_, waker) in waiters {
+            waker.wake();
+        }
+    }
+
+    /// Returns `true` if the semaphore is closed.
+    pub fn is_closed(&self) -> bool {
+        self.state.lock().unwrap().closed
+    }
+
+    fn try_acquire_inner(&self) -> Result<(), TryAcquireError> {
+        let mut state = self.state.lock().unwrap();
+
+        if state.closed {
+            return Err(TryAcquireError::Closed);
+        }
+        if state.permits == 0 {
+            return Err(TryAcquireError::NoPermits);
+        }
+
+        state.permits -= 1;
+        Ok(())
+    }
+
+    /// Polls for an owned permit, queueing the task under `waiter` if there
+    /// is none available. Once done polling, a queued `waiter` must be passed
+    /// to `cancel_acquire`.
+    pub(crate) fn poll_acquire_owned(
+        self: &Arc<Self>,
+        cx: &mut Context<'_>,
+        waiter: &mut Option<u64>,
+    ) -> Poll<Result<OwnedSemaphorePermit, AcquireError>> {
+        ready!(self.poll_acquire(cx, waiter))?;
+        Poll::Ready(Ok(OwnedSemaphorePermit {
+            sem: self.clone(),
+            permits: 1,
+        }))
+    }
+
+    /// Polls for a permit, queueing the task under `waiter` if there is
+    /// none available.
+    fn poll_acquire(
+        &self,
+        cx: &mut Context<'_>,
+        waiter: &mut Option<u64>,
+    ) -> Poll<Result<(), AcquireError>> {
+        let coop = ready!(coop::poll_proceed(cx));
+
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+
+        if state.closed {
+            coop.made_progress();
+            *waiter = None;
+            return Poll::Ready(Err(AcquireError(())));
+        }
+
+        if state.permits == 0 {
+            let queued = waiter.and_then(|id| state.waiters.iter_mut().find(|(w, _)| *w == id));
+
+            match queued {
+                // Spurious poll, keep the position in the queue.
+                Some((_, waker)) => {
+                    if !waker.will_wake(cx.waker()) {
+                        *waker = cx.waker().clone();
+                    }
+                }
+                None => {
+                    let id = *waiter.get_or_insert_with(|| {
+                        let id = state.next_waiter_id;
+                        state.next_waiter_id += 1;
+                        id
+                    });
+                    state.waiters.push_back((id, cx.waker().clone()));
+                }
+            }
+            return Poll::Pending;
+        }
+
+        state.permits -= 1;
+        if let Some(id) = waiter.take() {
+            state.waiters.retain(|(w, _)| *w != id);
+        }
+
+        coop.made_progress();
+        Poll::Ready(Ok(()))
+    }
+
+    /// Removes the task queued under `waiter` by `poll_acquire`, which gave
+    /// up waiting. If it was already woken, the wakeup is passed on to the
+    /// next waiting task, so that the permit is not lost.
+    pub(crate) fn cancel_acquire(&self, waiter: u64) {
+        let waker = {
+            let mut state = self.state.lock().unwrap();
+            let len = state.waiters.len();
+            state.waiters.retain(|(w, _)| *w != waiter);
+
+            // Still queued: the task was never woken.
+            if state.waiters.len() != len || state.permits == 0 {
+                return;
+            }
+            state.waiters.pop_front()
+        };
+
+        if let Some((_, waker)) = waker {
+            waker.wake();
+        }
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = self.state.lock().unwrap();
+        fmt.debug_struct("Semaphore")
+            .field("permits", &state.permits)
+            .field("waiters", &state.waiters.len())
+            .field("closed", &state.closed)
+            .finish()
+    }
+}
+
+/// Future waiting for a permit of a semaphore.
+///
+/// Dropping the future before it completes removes the task from the wait
+/// queue, see [`Semaphore::cancel_acquire`].
+pub(crate) struct Acquire<'a> {
+    sem: &'a Semaphore,
+    /// Id of the entry in `waiters`, while the task is queued.
+    waiter: Option<u64>,
+}
+
+impl<'a> Acquire<'a> {
+    fn new(sem: &'a Semaphore) -> Acquire<'a> {
+        Acquire { sem, waiter: None }
+    }
+}
+
+impl Future for Acquire<'_> {
+    type Output = Result<(), AcquireError>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let me = self.get_mut();
+        me.sem.poll_acquire(cx, &mut me.waiter)
+    }
+}
+
+impl Drop for Acquire<'_> {
+    fn drop(&mut self) {
+        if let Some(id) = self.waiter {
+            self.sem.cancel_acquire(id);
+        }
+    }
+}
+
+impl SemaphorePermit<'_> {
+    /// Forgets the permit **without** releasing it back to the semaphore.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.sem.add_permits(self.permits);
+    }
+}
+
+impl fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("SemaphorePermit")
+            .field("sem", &self.sem)
+            .finish()
+    }
+}
+
+impl OwnedSemaphorePermit {
+    /// Forgets the permit **without** releasing it back to the semaphore.
+    pub fn forget(mut self) {
+        self.permits = 0;
+    }
+
+    /// Returns the semaphore the permit was acquired from.
+    pub fn semaphore(&self) -> &Arc<Semaphore> {
+        &self.sem
+    }
+}
+
+impl Drop for OwnedSemaphorePermit {
+    fn drop(&mut self) {
+        self.sem.add_permits(self.permits);
+    }
+}
+
+impl fmt::Debug for OwnedSemaphorePermit {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("OwnedSemaphorePermit")
+            .field("sem", &self.sem)
+            .finish()
+    }
+}
+
+impl fmt::Display for AcquireError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "semaphore closed")
+    }
+}
+
+impl Error for AcquireError {}
+
+impl TryAcquireError {
+    /// Returns `true` if the error was caused by a closed semaphore.
+    pub fn is_closed(&self) -> bool {
+        matches!(self, TryAcquireError::Closed)
+    }
+
+    /// Returns `true` if the error was caused by calling `try_acquire` on a
+    /// semaphore with no available permits.
+    pub fn is_no_permits(&self) -> bool {
+        matches!(self, TryAcquireError::NoPermits)
+    }
+}
+
+impl fmt::Display for TryAcquireError {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TryAcquireError::Closed => write!(fmt, "semaphore closed"),
+            TryAcquireError::NoPermits => write!(fmt, "no permits available"),
+        }
+    }
+}
+
+impl Error for TryAcquireError {}
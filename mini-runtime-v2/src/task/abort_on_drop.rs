@@ -0,0 +1,78 @@
+use crate::runtime::task::{Id, JoinError, JoinHandle};
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A [`JoinHandle`] that aborts its task when dropped.
+///
+/// Dropping a `JoinHandle` detaches the task, which keeps running. A helper
+/// spawning a task to do part of its work usually wants the opposite: if the
+/// helper is cancelled, its child task must not outlive it. Holding an
+/// `AbortOnDropHandle` guarantees that:
+///
+/// ```
+/// # use mini_runtime_v2::task;
+/// # struct Body;
+/// # struct Error;
+/// # async fn report_progress() {}
+/// # async fn fetch(_url: &str) -> Result<Body, Error> { Ok(Body) }
+/// async fn fetch_with_progress(url: &str) -> Result<Body, Error> {
+///     let progress = task::spawn(report_progress()).abort_on_drop();
+///
+///     // `progress` is aborted when this future completes, fails with `?`,
+///     // or is dropped before completion, e.g. by a `timeout`.
+///     fetch(url).await
+/// }
+/// ```
+///
+/// Awaiting an `AbortOnDropHandle` returns the output of the task, like the
+/// `JoinHandle` it wraps.
+///
+/// Created with [`JoinHandle::abort_on_drop`] or [`AbortOnDropHandle::new`].
+#[must_use = "dropping an AbortOnDropHandle aborts the task immediately"]
+pub struct AbortOnDropHandle<T>(JoinHandle<T>);
+
+impl<T> AbortOnDropHandle<T> {
+    /// Wraps `handle`, aborting its task when dropped.
+    pub fn new(handle: JoinHandle<T>) -> Self {
+        AbortOnDropHandle(handle)
+    }
+
+    /// Returns the [`Id`] of the task.
+    pub fn id(&self) -> Id {
+        self.0.id()
+    }
+
+    /// Aborts the task without waiting for the handle to be dropped.
+    pub fn abort(&self) {
+        self.0.abort();
+    }
+
+    /// Checks if the task has finished.
+    pub fn is_finished(&self) -> bool {
+        self.0.is_finished()
+    }
+}
+
+impl<T> Drop for AbortOnDropHandle<T> {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+impl<T: 'static> Future for AbortOnDropHandle<T> {
+    type Output = Result<T, JoinError>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        Pin::new(&mut self.0).poll(cx)
+    }
+}
+
+impl<T> fmt::Debug for AbortOnDropHandle<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("AbortOnDropHandle")
+            .field("id", &self.id())
+            .finish()
+    }
+}
@@ -0,0 +1,79 @@
+use crate::task::JoinHandle;
+
+/// Runs the provided closure on a thread where blocking is acceptable.
+///
+/// In general, issuing a blocking call or performing a lot of compute in a
+/// future without yielding is problematic, as it may prevent the executor
+/// from driving other futures forward. This function runs the provided
+/// closure on a thread dedicated to blocking operations.
+///
+/// Threads are spawned on demand, up to the limit set by
+/// [`Builder::max_blocking_threads`], and exit after being idle for
+/// [`Builder::thread_keep_alive`].
+///
+/// Blocking closures can't be aborted: calling `abort` on the returned
+/// `JoinHandle` only has an effect if the closure didn't start yet. When the
+/// runtime is dropped, it waits for the running closures to return.
+///
+/// ```
+/// # use mini_runtime_v2::task;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build()?;
+/// # rt.block_on(async {
+/// let contents = task::spawn_blocking(|| std::fs::read_to_string("Cargo.toml")).await??;
+/// # assert!(contents.contains("[package]"));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// # })?;
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Panics
+///
+/// This function panics if called outside of a Mini runtime.
+///
+/// [`Builder::max_blocking_threads`]: crate::runtime::Builder::max_blocking_threads
+/// [`Builder::thread_keep_alive`]: crate::runtime::Builder::thread_keep_alive
+#[track_caller]
+pub fn spawn_blocking<F, R>(f: F) -> JoinHandle<R>
+where
+    F: FnOnce() -> R + Send + 'static,
+    R: Send + 'static,
+{
+    crate::runtime::blocking::spawn_blocking(f)
+}
+
+/// Runs the provided blocking function on the current thread without
+/// blocking the executor.
+///
+/// On a multi-thread runtime, the worker thread calling this function would
+/// hand its other tasks over to a new worker before running `f`, so they keep
+/// making progress. This runtime only has the current-thread flavor, which
+/// has no other thread to hand them to: from within the runtime, this
+/// function always panics, use [`spawn_blocking`] instead.
+///
+/// Called outside of a runtime, e.g. from a `spawn_blocking` closure, this
+/// function simply runs `f`, which unlike with [`spawn_blocking`] may borrow
+/// from the caller.
+///
+/// ```
+/// # use mini_runtime_v2::task;
+/// // Outside of a runtime: `f` runs right away.
+/// let manifest = task::block_in_place(|| std::fs::read_to_string("Cargo.toml"))?;
+/// # assert!(manifest.contains("[package]"));
+/// # Ok::<_, std::io::Error>(())
+/// ```
+///
+/// # Panics
+///
+/// This function panics if called from within a runtime, e.g. from a task or
+/// `block_on`: blocking the only thread of a current-thread runtime would
+/// stall all its tasks. Check [`Handle::runtime_flavor`] first, or use
+/// [`spawn_blocking`] instead.
+///
+/// [`Handle::runtime_flavor`]: crate::runtime::Handle::runtime_flavor
+#[track_caller]
+pub fn block_in_place<F, R>(f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    crate::runtime::scheduler::block_in_place(f)
+}
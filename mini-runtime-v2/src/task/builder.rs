@@ -0,0 +1,80 @@
+use crate::runtime::{Handle, context, task};
+use crate::task::JoinHandle;
+
+/// Factory which is used to configure the properties of a new task.
+///
+/// Methods can be chained in order to configure it, then [`spawn`] spawns the
+/// task:
+///
+/// ```no_run
+/// use mini_runtime_v2::task;
+/// # use mini_runtime_v2::net::{TcpListener, TcpStream};
+/// # async fn handle(_socket: TcpStream) {}
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_all().build()?;
+/// # rt.block_on(async {
+/// # let listener = TcpListener::bind("127.0.0.1:8080").await?;
+///
+/// let (socket, peer) = listener.accept().await?;
+/// task::Builder::new()
+///     .name(&format!("conn-{peer}"))
+///     .spawn(handle(socket));
+/// # Ok::<_, std::io::Error>(())
+/// # })?;
+/// # Ok::<_, std::io::Error>(())
+/// ```
+///
+/// The name of a task is reported by the task hooks ([`TaskMeta::name`]), by
+/// task dumps and by the `task` tracing span. With many tasks running the
+/// same code, it tells apart the one that misbehaves.
+///
+/// [`spawn`]: Builder::spawn
+/// [`TaskMeta::name`]: crate::runtime::TaskMeta::name
+#[derive(Default, Debug)]
+pub struct Builder<'a> {
+    name: Option<&'a str>,
+}
+
+impl<'a> Builder<'a> {
+    /// Creates a new task builder.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Assigns a name to the task which will be spawned.
+    pub fn name(&self, name: &'a str) -> Self {
+        Self { name: Some(name) }
+    }
+
+    /// Spawns a task with this builder's settings on the current runtime.
+    ///
+    /// See [`task::spawn`](crate::task::spawn) for more details.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if called outside of a Mini runtime.
+    #[track_caller]
+    pub fn spawn<F>(self, future: F) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        let meta = task::SpawnMeta::new(self.name);
+        match context::with_current(Clone::clone) {
+            Ok(handle) => handle.spawn(future, meta),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Spawns a task with this builder's settings on the provided runtime
+    /// handle, from any thread.
+    ///
+    /// See [`Handle::spawn`] for more details.
+    #[track_caller]
+    pub fn spawn_on<F>(self, future: F, handle: &Handle) -> JoinHandle<F::Output>
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        handle.inner.spawn(future, task::SpawnMeta::new(self.name))
+    }
+}
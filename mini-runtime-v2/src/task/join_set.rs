@@ -0,0 +1,138 @@
+use crate::future::poll_fn;
+use crate::runtime::task::{Id, JoinError, JoinHandle};
+use crate::task::spawn;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// A collection of tasks spawned on the runtime.
+///
+/// A `JoinSet` can be used to await the completion of some or all of the tasks
+/// in the set. The set is not ordered, and the tasks will be returned in the
+/// order they complete.
+///
+/// All of the tasks must have the same return type `T`.
+///
+/// When the `JoinSet` is dropped, all tasks in the `JoinSet` are immediately
+/// aborted.
+///
+/// Instead of collecting `JoinHandle`s into a `Vec` and awaiting them with
+/// `futures::join_all`:
+///
+/// ```
+/// # use mini_runtime_v2::task::JoinSet;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+/// # rt.block_on(async {
+/// let mut set = JoinSet::new();
+///
+/// for i in 0..10 {
+///     set.spawn(async move { i });
+/// }
+///
+/// while let Some(res) = set.join_next().await {
+///     let out = res?;
+///     // ...
+/// #   assert!(out < 10);
+/// }
+/// # Ok::<_, mini_runtime_v2::task::JoinError>(())
+/// # }).unwrap();
+/// ```
+pub struct JoinSet<T> {
+    /// Handles of the tasks that were not joined yet.
+    handles: Vec<JoinHandle<T>>,
+}
+
+impl<T> JoinSet<T> {
+    /// Create a new `JoinSet`.
+    pub fn new() -> Self {
+        Self {
+            handles: Vec::new(),
+        }
+    }
+
+    /// Returns the number of tasks currently in the `JoinSet`.
+    pub fn len(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Returns whether the `JoinSet` is empty.
+    pub fn is_empty(&self) -> bool {
+        self.handles.is_empty()
+    }
+}
+
+impl<T: Send + 'static> JoinSet<T> {
+    /// Spawn the provided task on the `JoinSet`, returning its [`Id`] so the
+    /// caller can correlate the output.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if called outside of a Mini runtime.
+    #[track_caller]
+    pub fn spawn<F>(&mut self, task: F) -> Id
+    where
+        F: Future<Output = T> + Send + 'static,
+    {
+        let handle = spawn(task);
+        let id = handle.id();
+        self.handles.push(handle);
+        id
+    }
+
+    /// Waits until one of the tasks in the set completes and returns its output.
+    ///
+    /// Returns `None` if the set is empty.
+    pub async fn join_next(&mut self) -> Option<Result<T, JoinError>> {
+        poll_fn(|cx| self.poll_join_next(cx)).await
+    }
+
+    /// Polls for one of the tasks in the set to complete.
+    ///
+    /// If this returns `Poll::Ready(Some(_))`, then the task that completed is
+    /// removed from the set. When the method returns `Poll::Pending`, every
+    /// remaining task has stored the waker from `cx`, so the caller is notified
+    /// as soon as one of them completes.
+    pub fn poll_join_next(&mut self, cx: &mut Context<'_>) -> Poll<Option<Result<T, JoinError>>> {
+        if self.handles.is_empty() {
+            return Poll::Ready(None);
+        }
+
+        for i in 0..self.handles.len() {
+            if let Poll::Ready(res) = Pin::new(&mut self.handles[i]).poll(cx) {
+                // The order of the tasks doesn't matter, so the removal can be O(1).
+                self.handles.swap_remove(i);
+                return Poll::Ready(Some(res));
+            }
+        }
+
+        Poll::Pending
+    }
+
+    /// Aborts all tasks on this `JoinSet`.
+    ///
+    /// This does not remove the tasks from the `JoinSet`. To wait for the tasks
+    /// to complete cancellation, you should call `join_next` in a loop until
+    /// the `JoinSet` is empty.
+    pub fn abort_all(&mut self) {
+        self.handles.iter().for_each(|handle| handle.abort());
+    }
+}
+
+impl<T> Drop for JoinSet<T> {
+    fn drop(&mut self) {
+        self.handles.iter().for_each(|handle| handle.abort());
+    }
+}
+
+impl<T> fmt::Debug for JoinSet<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JoinSet").field("len", &self.len()).finish()
+    }
+}
+
+impl<T> Default for JoinSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
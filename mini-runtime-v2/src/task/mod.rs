@@ -1,6 +1,27 @@
 //! Asynchronous green-threads.
 
-pub use crate::runtime::task::JoinHandle;
+pub use crate::runtime::task::{Id, JoinError, JoinHandle, id, try_id};
+
+mod builder;
+pub use builder::Builder;
 
 mod spawn;
-pub use spawn::spawn;
+pub use spawn::{spawn, try_spawn};
+
+mod blocking;
+pub use blocking::{block_in_place, spawn_blocking};
+
+mod abort_on_drop;
+pub use abort_on_drop::AbortOnDropHandle;
+
+mod join_set;
+pub use join_set::JoinSet;
+
+mod scope;
+pub use scope::{Scope, scope};
+
+mod unconstrained;
+pub use unconstrained::{Unconstrained, unconstrained};
+
+mod yield_now;
+pub use yield_now::yield_now;
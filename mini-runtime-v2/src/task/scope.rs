@@ -0,0 +1,182 @@
+use crate::future::poll_fn;
+use crate::sync::CancellationToken;
+use crate::task::{Id, JoinError, JoinSet};
+use std::fmt;
+use std::future::Future;
+use std::panic;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+/// Creates a scope for spawning tasks.
+///
+/// The function passed to `scope` is given a [`Scope`], which can be used to
+/// spawn child tasks. The scope doesn't return before all the tasks spawned
+/// through it are done:
+///
+/// - once `f` completes, `scope` waits for the remaining children to complete;
+/// - if a child panics, the other children are cancelled, and the panic is
+///   propagated to the caller once they are all gone;
+/// - [`Scope::cancel`] cancels all the children, `scope` then returns as soon
+///   as `f` completes.
+///
+/// This is *structured concurrency*: like a function call, a scope doesn't
+/// leave work running behind it, so a caller can reason about the tasks the
+/// same way it reasons about the stack.
+///
+/// Children run as regular tasks: they must be `'static` and can't borrow
+/// from the caller. The guarantee only holds while the scope is awaited: if
+/// the future returned by `scope` is dropped before it completes, the
+/// children are aborted but not waited for.
+///
+/// ```
+/// use mini_runtime_v2::task;
+/// # use mini_runtime_v2::sync::mpsc;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+/// # rt.block_on(async {
+///
+/// let (tx, mut rx) = mpsc::channel(3);
+///
+/// task::scope(async |scope| {
+///     for i in 0..3 {
+///         let tx = tx.clone();
+///         scope.spawn(async move { tx.send(i * 10).await.unwrap() });
+///     }
+/// })
+/// .await;
+///
+/// // All the children are done
+/// drop(tx);
+/// while let Some(v) = rx.recv().await { println!("{v}"); }
+/// # });
+/// ```
+///
+/// # Panics
+///
+/// Resumes the panic of the first child that panicked, or of `f`.
+pub async fn scope<F, R>(f: F) -> R
+where
+    F: AsyncFnOnce(&Scope) -> R,
+{
+    let scope = Scope {
+        tasks: Mutex::new(JoinSet::new()),
+        token: CancellationToken::new(),
+    };
+
+    let body = f(&scope);
+    pin!(body);
+
+    let mut output = None;
+    let mut panicked = None;
+
+    poll_fn(|cx| {
+        if output.is_none()
+            && let Poll::Ready(v) = body.as_mut().poll(cx)
+        {
+            output = Some(v);
+        }
+
+        // Reap the children that completed, so that a panic is noticed right
+        // away instead of once `f` returns.
+        loop {
+            match scope.poll_join_next(cx) {
+                Poll::Ready(Some(Err(e))) if e.is_panic() => {
+                    if panicked.is_none() {
+                        scope.cancel();
+                        panicked = Some(e.into_panic());
+                    }
+                }
+                Poll::Ready(Some(_)) => {}
+                // `f` may still spawn children.
+                Poll::Ready(None) if output.is_none() => return Poll::Pending,
+                Poll::Ready(None) => return Poll::Ready(()),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    })
+    .await;
+
+    if let Some(payload) = panicked {
+        panic::resume_unwind(payload);
+    }
+
+    output.expect("scope body completed")
+}
+
+/// A scope to spawn tasks in, see [`scope`].
+pub struct Scope {
+    /// Children that were not reaped yet.
+    tasks: Mutex<JoinSet<()>>,
+
+    /// Cancelled to stop all the children.
+    token: CancellationToken,
+}
+
+impl Scope {
+    /// Spawns a child task in the scope, returning its [`Id`].
+    ///
+    /// The task is polled until it completes or the scope is cancelled,
+    /// whichever comes first. A child spawned after the scope was cancelled
+    /// never runs.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if called outside of a Mini runtime.
+    #[track_caller]
+    pub fn spawn<F>(&self, task: F) -> Id
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let token = self.token.clone();
+
+        self.tasks.lock().unwrap().spawn(async move {
+            let cancelled = token.cancelled();
+            pin!(cancelled, task);
+
+            poll_fn(|cx| {
+                // The task goes first: woken by the cancellation, a task
+                // waiting on the scope token completes in this last poll.
+                if task.as_mut().poll(cx).is_ready() {
+                    return Poll::Ready(());
+                }
+                cancelled.as_mut().poll(cx)
+            })
+            .await
+        })
+    }
+
+    /// Cancels all the children of the scope.
+    ///
+    /// Each child is polled one last time, then dropped at the `.await`
+    /// point it is suspended on. A child that needs to clean up before it
+    /// stops can watch [`token`](Scope::token): its `cancelled()` future
+    /// completes in that last poll, the clean up must not await anything
+    /// else.
+    pub fn cancel(&self) {
+        self.token.cancel();
+    }
+
+    /// Returns `true` if the scope was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+
+    /// Returns a token cancelled with the scope.
+    ///
+    /// Cancelling the returned token doesn't cancel the scope.
+    pub fn token(&self) -> CancellationToken {
+        self.token.child_token()
+    }
+
+    fn poll_join_next(&self, cx: &mut Context<'_>) -> Poll<Option<Result<(), JoinError>>> {
+        self.tasks.lock().unwrap().poll_join_next(cx)
+    }
+}
+
+impl fmt::Debug for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Scope")
+            .field("tasks", &self.tasks.lock().unwrap().len())
+            .field("is_cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
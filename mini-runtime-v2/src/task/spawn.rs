@@ -1,3 +1,4 @@
+use crate::runtime::{TryCurrentError, context, task};
 use crate::task::JoinHandle;
 
 /// Spawns a new asynchronous task, returning a
@@ -16,20 +17,53 @@ use crate::task::JoinHandle;
 /// F: Future - F must implement the Future trait — meaning it’s a future that can be awaited;
 /// + Send  - The future must be safe to move to another thread (sendable across threads);
 /// + 'static - The future owns all the data it references and doesn’t borrow non-static references.
-/// In other words, it must live for the entire duration of the program
-/// (or be completely self-contained).
+///   In other words, it must live for the entire duration of the program
+///   (or be completely self-contained).
 ///
 /// F::Output: Send + 'static - The result the future produces must also be sendable across
 /// threads and live for 'static.
+///
+/// # Panics
+///
+/// This function panics if called outside of a Mini runtime, see
+/// [`try_spawn`] for a version that returns an error instead.
+#[track_caller]
 pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
 where
     F: Future + Send + 'static,
     F::Output: Send + 'static,
 {
-    use crate::runtime::{context, task};
-    let id = task::Id::next();
-    match context::with_current(|handle| handle.spawn(future, id)) {
+    match try_spawn(future) {
         Ok(join_handle) => join_handle,
         Err(e) => panic!("{}", e),
     }
 }
+
+/// Spawns a new asynchronous task like [`spawn`], or returns an error if
+/// called outside of a Mini runtime.
+///
+/// Library code that may run without a runtime can degrade gracefully
+/// instead of panicking:
+///
+/// ```
+/// # use mini_runtime_v2::task;
+/// # async fn report(_metrics: ()) {}
+/// # let metrics = ();
+/// if task::try_spawn(report(metrics)).is_err() {
+///     eprintln!("no runtime, the metrics are not reported");
+/// }
+/// ```
+///
+/// The future is dropped without being polled when an error is returned.
+#[track_caller]
+pub fn try_spawn<F>(future: F) -> Result<JoinHandle<F::Output>, TryCurrentError>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    let meta = task::SpawnMeta::unnamed();
+    // Spawned outside of the closure, which would hide the caller's location
+    // from the task hooks.
+    let handle = context::with_current(Clone::clone)?;
+    Ok(handle.spawn(future, meta))
+}
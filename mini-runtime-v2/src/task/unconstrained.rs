@@ -0,0 +1,64 @@
+use crate::runtime::coop;
+use pin_project_lite::pin_project;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+pin_project! {
+    /// Future for the [`unconstrained`](unconstrained) method.
+    #[must_use = "Unconstrained does nothing unless polled"]
+    #[derive(Debug)]
+    pub struct Unconstrained<F> {
+        #[pin]
+        inner: F,
+    }
+}
+
+impl<F> Future for Unconstrained<F>
+where
+    F: Future,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let inner = self.project().inner;
+        coop::with_unconstrained(|| inner.poll(cx))
+    }
+}
+
+/// Turns off cooperative scheduling for a future.
+///
+/// Each task gets a budget of operations on runtime resources per poll: once
+/// it is spent, the resources return `Pending` even if they are ready, and
+/// the task yields to the scheduler. The operations performed by the future
+/// passed to `unconstrained` don't count: it only yields when a resource is
+/// really not ready.
+///
+/// This trades fairness for throughput. Draining a flooded channel in an
+/// unconstrained future doesn't pay for a trip through the scheduler every
+/// few messages, but the other tasks of the worker, timers included, wait
+/// until the channel runs dry:
+///
+/// ```
+/// # use mini_runtime_v2::{sync::mpsc, task};
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+/// # rt.block_on(async {
+/// # let (tx, mut rx) = mpsc::unbounded_channel();
+/// # for i in 0..1000 {
+/// #     tx.send(i).unwrap();
+/// # }
+/// # drop(tx);
+/// let count = task::unconstrained(async {
+///     let mut count = 0;
+///     while rx.recv().await.is_some() {
+///         count += 1;
+///     }
+///     count
+/// })
+/// .await;
+/// # assert_eq!(count, 1000);
+/// # });
+/// ```
+pub fn unconstrained<F>(inner: F) -> Unconstrained<F> {
+    Unconstrained { inner }
+}
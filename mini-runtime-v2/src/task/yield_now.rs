@@ -0,0 +1,38 @@
+use crate::future::poll_fn;
+use std::task::Poll;
+
+/// Yields execution back to the Mini runtime.
+///
+/// A task yields by awaiting on `yield_now()`: it returns `Pending` once,
+/// after waking itself, and is pushed back into the run queue. The other
+/// tasks ready to run are polled before it resumes.
+///
+/// ```
+/// # use mini_runtime_v2::task;
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().build().unwrap();
+/// # rt.block_on(async {
+/// let handle = task::spawn(async {
+///     // Let the task that spawned this one run first.
+///     task::yield_now().await;
+///     "yielded"
+/// });
+/// # assert_eq!(handle.await.unwrap(), "yielded");
+/// # });
+/// ```
+///
+/// The scheduler may still check the drivers less often than the task
+/// yields: a yield doesn't guarantee that pending I/O or timers are
+/// processed before the task is polled again.
+pub async fn yield_now() {
+    let mut yielded = false;
+    poll_fn(|cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
@@ -0,0 +1,105 @@
+//! Pausing and advancing the runtime's clock, for tests.
+
+use crate::future::poll_fn;
+use crate::runtime::scheduler;
+use std::task::Poll;
+use std::time::Duration;
+
+/// Pauses time.
+///
+/// The current value of the runtime's clock is saved and all subsequent
+/// timers are compared against it: they only fire once the clock is moved
+/// forward. The clock is moved either explicitly with [`advance`], or
+/// automatically when the runtime has no work to do: instead of sleeping
+/// until the next timer, the clock jumps to its deadline.
+///
+/// A test doing `sleep(Duration::from_secs(3600)).await` on a paused runtime
+/// completes right away, and the timers fire in the same order as they
+/// would have with the wall clock.
+///
+/// ```
+/// # use mini_runtime_v2::time::{self, Duration, Instant};
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+/// # rt.block_on(async {
+/// time::pause();
+///
+/// let start = Instant::now();
+/// time::sleep(Duration::from_secs(3600)).await;
+/// // Barely any wall-clock time elapsed
+/// assert!(start.elapsed() < Duration::from_secs(1));
+/// # });
+/// ```
+///
+/// # Panics
+///
+/// Panics if time is already frozen, if called outside of a Mini runtime or
+/// if the runtime was built without [`enable_time`].
+///
+/// [`enable_time`]: crate::runtime::Builder::enable_time
+#[track_caller]
+pub fn pause() {
+    scheduler::Handle::current().driver().time().clock().pause();
+}
+
+/// Resumes time.
+///
+/// Clears the saved time, the clock advances like the wall clock again from
+/// its current value.
+///
+/// # Panics
+///
+/// Panics if time is not frozen, if called outside of a Mini runtime or if
+/// the runtime was built without [`enable_time`].
+///
+/// [`enable_time`]: crate::runtime::Builder::enable_time
+#[track_caller]
+pub fn resume() {
+    scheduler::Handle::current()
+        .driver()
+        .time()
+        .clock()
+        .resume();
+}
+
+/// Advances time.
+///
+/// Moves the paused clock forward by `duration`, then yields to the
+/// scheduler. The timers that elapsed fire the next time the scheduler
+/// checks the time driver.
+///
+/// ```
+/// # use mini_runtime_v2::time::{self, Duration};
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_time().start_paused(true).build().unwrap();
+/// # rt.block_on(async {
+/// let sleep = time::sleep(Duration::from_secs(10));
+///
+/// time::advance(Duration::from_secs(10)).await;
+/// assert!(sleep.is_elapsed());
+/// # });
+/// ```
+///
+/// # Panics
+///
+/// Panics if time is not frozen, if called outside of a Mini runtime or if
+/// the runtime was built without [`enable_time`].
+///
+/// [`enable_time`]: crate::runtime::Builder::enable_time
+pub async fn advance(duration: Duration) {
+    scheduler::Handle::current()
+        .driver()
+        .time()
+        .clock()
+        .advance(duration);
+
+    // Yield once, letting the tasks woken by the timers run.
+    let mut yielded = false;
+    poll_fn(move |cx| {
+        if yielded {
+            return Poll::Ready(());
+        }
+        yielded = true;
+        cx.waker().wake_by_ref();
+        Poll::Pending
+    })
+    .await
+}
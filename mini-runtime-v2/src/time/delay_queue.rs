@@ -0,0 +1,283 @@
+//! A queue of values that are yielded once their delay expired.
+//!
+//! See [`DelayQueue`] for more details.
+
+use crate::runtime::scheduler;
+use crate::stream::Stream;
+use crate::time::{Sleep, sleep, sleep_until};
+use std::collections::BTreeSet;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// A queue of delayed values.
+///
+/// Once a value is inserted, it is yielded by the queue when its delay has
+/// expired, in deadline order. Inserting returns a [`Key`], used to
+/// [`remove`] the value or [`reset`] its delay before it expires.
+///
+/// The typical use is to track many timeouts at once from a single task,
+/// e.g. the idle timeouts of the connections of a server: every message
+/// pushes the timeout of its connection back, and the connections whose
+/// timeout expired are closed.
+///
+/// ```
+/// # use mini_runtime_v2::stream::StreamExt;
+/// # use mini_runtime_v2::time::DelayQueue;
+/// # use std::time::Duration;
+/// # const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_time().start_paused(true).build().unwrap();
+/// # rt.block_on(async {
+/// # let peer = "127.0.0.1:4000";
+/// let mut idle = DelayQueue::new();
+/// let key = idle.insert(peer, IDLE_TIMEOUT);
+///
+/// // On activity:
+/// idle.reset(&key, IDLE_TIMEOUT);
+///
+/// // In the main loop:
+/// while let Some(expired) = idle.next().await {
+///     println!("{} was idle for too long", expired.get_ref());
+/// }
+/// # });
+/// ```
+///
+/// The queue implements [`Stream`]. Polling an empty queue yields `None`,
+/// but the queue can still be used: values inserted afterwards are yielded
+/// by the next polls.
+///
+/// Unlike Tokio's, this queue is not a timer wheel: the deadlines are kept
+/// in a `BTreeSet`, as the time driver does with its timers, and the queue
+/// is backed by a single [`Sleep`], reset to the earliest deadline. However
+/// many values it holds, it uses one timer of the time driver.
+///
+/// # Panics
+///
+/// The methods taking a `Key` panic if the key was not returned by this
+/// queue, or if its value was already removed or yielded.
+///
+/// [`remove`]: DelayQueue::remove
+/// [`reset`]: DelayQueue::reset
+pub struct DelayQueue<T> {
+    /// The values, indexed by their key. `None` in the free slots.
+    slots: Vec<Option<Slot<T>>>,
+
+    /// Indices of the free slots, reused before growing `slots`.
+    free: Vec<usize>,
+
+    /// Deadlines of the values, the first one is the next to expire.
+    expirations: BTreeSet<(Instant, usize)>,
+
+    /// Timer set to the earliest deadline, created on first use.
+    delay: Option<Sleep>,
+}
+
+struct Slot<T> {
+    value: T,
+    deadline: Instant,
+}
+
+/// Token to a value stored in a [`DelayQueue`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    index: usize,
+}
+
+/// A value whose delay expired, yielded by a [`DelayQueue`].
+#[derive(Debug)]
+pub struct Expired<T> {
+    value: T,
+    deadline: Instant,
+    key: Key,
+}
+
+impl<T> DelayQueue<T> {
+    /// Creates an empty queue.
+    pub fn new() -> DelayQueue<T> {
+        DelayQueue {
+            slots: Vec::new(),
+            free: Vec::new(),
+            expirations: BTreeSet::new(),
+            delay: None,
+        }
+    }
+
+    /// Inserts `value`, yielded once `timeout` has elapsed.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_time`].
+    ///
+    /// [`enable_time`]: crate::runtime::Builder::enable_time
+    #[track_caller]
+    pub fn insert(&mut self, value: T, timeout: Duration) -> Key {
+        self.insert_at(value, deadline_after(timeout))
+    }
+
+    /// Inserts `value`, yielded once `deadline` is reached.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_time`].
+    ///
+    /// [`enable_time`]: crate::runtime::Builder::enable_time
+    #[track_caller]
+    pub fn insert_at(&mut self, value: T, deadline: Instant) -> Key {
+        let index = self.free.pop().unwrap_or_else(|| {
+            self.slots.push(None);
+            self.slots.len() - 1
+        });
+
+        self.slots[index] = Some(Slot { value, deadline });
+        self.expirations.insert((deadline, index));
+
+        Key { index }
+    }
+
+    /// Removes the value of `key` from the queue and returns it.
+    #[track_caller]
+    pub fn remove(&mut self, key: &Key) -> Expired<T> {
+        let slot = self.slots[key.index].take().expect("invalid key");
+        self.expirations.remove(&(slot.deadline, key.index));
+        self.free.push(key.index);
+
+        Expired {
+            value: slot.value,
+            deadline: slot.deadline,
+            key: *key,
+        }
+    }
+
+    /// Resets the delay of the value of `key` to `timeout` from now.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if called outside of a Mini runtime or if the
+    /// runtime was built without [`enable_time`].
+    ///
+    /// [`enable_time`]: crate::runtime::Builder::enable_time
+    #[track_caller]
+    pub fn reset(&mut self, key: &Key, timeout: Duration) {
+        self.reset_at(key, deadline_after(timeout));
+    }
+
+    /// Resets the deadline of the value of `key`.
+    #[track_caller]
+    pub fn reset_at(&mut self, key: &Key, deadline: Instant) {
+        let slot = self.slots[key.index].as_mut().expect("invalid key");
+        self.expirations.remove(&(slot.deadline, key.index));
+        slot.deadline = deadline;
+        self.expirations.insert((deadline, key.index));
+    }
+
+    /// Returns the deadline of the value of `key`.
+    #[track_caller]
+    pub fn deadline(&self, key: &Key) -> Instant {
+        self.slots[key.index]
+            .as_ref()
+            .expect("invalid key")
+            .deadline
+    }
+
+    /// Returns the number of values in the queue.
+    pub fn len(&self) -> usize {
+        self.expirations.len()
+    }
+
+    /// Returns `true` if the queue holds no value.
+    pub fn is_empty(&self) -> bool {
+        self.expirations.is_empty()
+    }
+
+    /// Removes all the values from the queue.
+    pub fn clear(&mut self) {
+        self.slots.clear();
+        self.free.clear();
+        self.expirations.clear();
+    }
+
+    /// Attempts to pull out the next value whose delay expired.
+    ///
+    /// Returns `Poll::Ready(None)` if the queue is empty. Otherwise, returns
+    /// `Poll::Pending` until the earliest deadline is reached, and registers
+    /// the current task to be woken then.
+    pub fn poll_expired(&mut self, cx: &mut Context<'_>) -> Poll<Option<Expired<T>>> {
+        let Some(&(deadline, index)) = self.expirations.first() else {
+            return Poll::Ready(None);
+        };
+
+        // Values may have been inserted, reset or removed since the last
+        // poll: move the timer to the current earliest deadline.
+        let delay = self.delay.get_or_insert_with(|| sleep_until(deadline));
+        if delay.deadline() != deadline {
+            Pin::new(&mut *delay).reset(deadline);
+        }
+        ready!(Pin::new(delay).poll(cx));
+
+        Poll::Ready(Some(self.remove(&Key { index })))
+    }
+}
+
+impl<T> Stream for DelayQueue<T> {
+    type Item = Expired<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Expired<T>>> {
+        self.get_mut().poll_expired(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len(), Some(self.len()))
+    }
+}
+
+impl<T> Default for DelayQueue<T> {
+    fn default() -> Self {
+        DelayQueue::new()
+    }
+}
+
+impl<T> Unpin for DelayQueue<T> {}
+
+impl<T: fmt::Debug> fmt::Debug for DelayQueue<T> {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("DelayQueue")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T> Expired<T> {
+    /// Returns a reference to the value.
+    pub fn get_ref(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns a mutable reference to the value.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    /// Consumes `self` and returns the value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Returns the deadline the value expired at.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Returns the key the value was stored under.
+    pub fn key(&self) -> Key {
+        self.key
+    }
+}
+
+#[track_caller]
+fn deadline_after(timeout: Duration) -> Instant {
+    sleep::deadline_after(&scheduler::Handle::current(), timeout)
+}
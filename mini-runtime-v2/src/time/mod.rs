@@ -0,0 +1,54 @@
+//! Utilities for tracking time.
+//!
+//! This module provides a number of types for executing code after a set
+//! period of time.
+//!
+//! * [`Sleep`] is a future that does no work and completes at a specific
+//!   [`Instant`] in time.
+//!
+//! * [`Timeout`]: Wraps a future or stream, setting an upper bound to the
+//!   amount of time it is allowed to execute. If the future or stream does
+//!   not complete in time, then it is canceled and an error is returned.
+//!
+//! * [`DelayQueue`]: A queue of values yielded once their delay expired,
+//!   sharing a single timer. Useful to track many timeouts from one task.
+//!
+//! These types must be used from within the context of a runtime built with
+//! [`enable_time`].
+//!
+//! ```
+//! use mini_runtime_v2::time::{self, Duration};
+//! # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+//! # rt.block_on(async {
+//! # async fn long_future() {
+//! #     time::sleep(Duration::from_secs(1)).await;
+//! # }
+//!
+//! if time::timeout(Duration::from_millis(10), long_future()).await.is_err() {
+//!     println!("did not receive value within 10 ms");
+//! }
+//! # });
+//! ```
+//!
+//! Tests can [`pause`] the runtime's clock: timers then fire as soon as the
+//! runtime has nothing else to do, without waiting for the wall clock.
+//!
+//! [`enable_time`]: crate::runtime::Builder::enable_time
+
+mod clock;
+pub use clock::{advance, pause, resume};
+
+pub mod delay_queue;
+pub use delay_queue::DelayQueue;
+
+pub mod error;
+
+mod sleep;
+pub use sleep::{Sleep, sleep, sleep_until};
+
+mod timeout;
+pub use timeout::{Timeout, timeout, timeout_at};
+
+// Re-export for convenience
+#[doc(no_inline)]
+pub use std::time::{Duration, Instant};
@@ -0,0 +1,211 @@
+use crate::runtime::scheduler;
+use crate::runtime::time::TimerKey;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+/// Waits until `deadline` is reached.
+///
+/// No work is performed while awaiting on the sleep future to complete.
+///
+/// # Panics
+///
+/// This function panics if called outside of a Mini runtime or if the runtime
+/// was built without [`enable_time`].
+///
+/// [`enable_time`]: crate::runtime::Builder::enable_time
+#[track_caller]
+pub fn sleep_until(deadline: Instant) -> Sleep {
+    Sleep::new(deadline)
+}
+
+/// Waits until `duration` has elapsed.
+///
+/// Equivalent to `sleep_until(Instant::now() + duration)`. An asynchronous
+/// analog to `std::thread::sleep`.
+///
+/// No work is performed while awaiting on the sleep future to complete.
+///
+/// ```
+/// use mini_runtime_v2::time::{sleep, Duration};
+/// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+/// # rt.block_on(async {
+///
+/// sleep(Duration::from_millis(100)).await;
+/// println!("100 ms have elapsed");
+/// # });
+/// ```
+///
+/// # Panics
+///
+/// This function panics if called outside of a Mini runtime or if the runtime
+/// was built without [`enable_time`].
+///
+/// [`enable_time`]: crate::runtime::Builder::enable_time
+#[track_caller]
+pub fn sleep(duration: Duration) -> Sleep {
+    Sleep::new_after(scheduler::Handle::current(), duration)
+}
+
+/// Returns the instant `duration` after the current time of the runtime's
+/// clock, which may be paused.
+pub(crate) fn deadline_after(handle: &scheduler::Handle, duration: Duration) -> Instant {
+    let now = handle.driver().time().now();
+    now.checked_add(duration).unwrap_or_else(|| far_future(now))
+}
+
+/// Future returned by [`sleep`](sleep) and [`sleep_until`](sleep_until).
+///
+/// The timer is registered with the time driver on the first poll and
+/// removed when the `Sleep` is dropped.
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Sleep {
+    handle: scheduler::Handle,
+
+    deadline: Instant,
+
+    /// Key of the timer in the time driver, once registered.
+    entry: Option<TimerKey>,
+}
+
+impl Sleep {
+    #[track_caller]
+    pub(crate) fn new(deadline: Instant) -> Sleep {
+        Sleep::new_with_handle(deadline, scheduler::Handle::current())
+    }
+
+    /// Creates a `Sleep` completing `duration` after the current time of the
+    /// runtime's clock.
+    #[track_caller]
+    pub(crate) fn new_after(handle: scheduler::Handle, duration: Duration) -> Sleep {
+        let deadline = deadline_after(&handle, duration);
+        Sleep::new_with_handle(deadline, handle)
+    }
+
+    #[track_caller]
+    fn new_with_handle(deadline: Instant, handle: scheduler::Handle) -> Sleep {
+        // Fail early if the time driver is disabled
+        let _ = handle.driver().time();
+
+        Sleep {
+            handle,
+            deadline,
+            entry: None,
+        }
+    }
+
+    /// Returns the instant at which the future will complete.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+
+    /// Returns `true` if `Sleep` has elapsed.
+    ///
+    /// A `Sleep` instance is elapsed when the requested duration has elapsed.
+    pub fn is_elapsed(&self) -> bool {
+        self.handle.driver().time().now() >= self.deadline
+    }
+
+    /// Resets the `Sleep` instance to a new deadline.
+    ///
+    /// Calling this function allows changing the instant at which the `Sleep`
+    /// future completes without having to create new associated state: a
+    /// registered timer is moved to the new deadline in the time driver,
+    /// keeping its waker. The same `Sleep` can thus serve every iteration of
+    /// a loop, e.g. as an idle timeout pushed back on every message:
+    ///
+    /// ```
+    /// # use mini_runtime_v2::{pin, sync::mpsc, time};
+    /// # use std::time::{Duration, Instant};
+    /// # const IDLE_TIMEOUT: Duration = Duration::from_secs(10);
+    /// # fn handle(_msg: u32) {}
+    /// # let rt = mini_runtime_v2::runtime::Builder::new_current_thread().enable_time().start_paused(true).build().unwrap();
+    /// # rt.block_on(async {
+    /// # let (tx, mut rx) = mpsc::channel(1);
+    /// # mini_runtime_v2::spawn(async move {
+    /// #     tx.send(1).await.unwrap();
+    /// #     time::sleep(IDLE_TIMEOUT * 2).await;
+    /// # });
+    /// let idle = time::sleep(IDLE_TIMEOUT);
+    /// pin!(idle);
+    ///
+    /// loop {
+    ///     match time::timeout_at(idle.deadline(), rx.recv()).await {
+    ///         Ok(Some(msg)) => {
+    ///             handle(msg);
+    ///             idle.as_mut().reset(Instant::now() + IDLE_TIMEOUT);
+    ///         }
+    ///         Ok(None) | Err(_) => break,
+    ///     }
+    /// }
+    /// # });
+    /// ```
+    ///
+    /// This function can be called both before and after the future has
+    /// completed.
+    pub fn reset(self: Pin<&mut Self>, deadline: Instant) {
+        self.get_mut().reset_inner(deadline);
+    }
+
+    /// Resets the deadline to `duration` from now, as seen by the runtime's
+    /// clock.
+    pub(crate) fn reset_after(&mut self, duration: Duration) {
+        let now = self.handle.driver().time().now();
+        self.reset_inner(now.checked_add(duration).unwrap_or_else(|| far_future(now)));
+    }
+
+    fn reset_inner(&mut self, deadline: Instant) {
+        self.deadline = deadline;
+
+        if self.handle.driver().time().reset(&mut self.entry, deadline) {
+            // The driver may be parked until a later deadline.
+            self.handle.driver().unpark();
+        }
+    }
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let me = self.get_mut();
+
+        if me.is_elapsed() {
+            if let Some(key) = me.entry.take() {
+                me.handle.driver().time().deregister(key);
+            }
+            return Poll::Ready(());
+        }
+
+        let time = me.handle.driver().time();
+        if time.register(&mut me.entry, me.deadline, cx.waker()) {
+            // The driver may be parked until a later deadline.
+            me.handle.driver().unpark();
+        }
+
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(key) = self.entry.take() {
+            self.handle.driver().time().deregister(key);
+        }
+    }
+}
+
+impl fmt::Debug for Sleep {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("Sleep")
+            .field("deadline", &self.deadline)
+            .finish()
+    }
+}
+
+/// Roughly 30 years after `now`, used for durations overflowing `Instant`.
+pub(crate) fn far_future(now: Instant) -> Instant {
+    now + Duration::from_secs(86400 * 365 * 30)
+}
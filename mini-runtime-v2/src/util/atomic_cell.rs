@@ -1,6 +1,6 @@
+use crate::util::loom::sync::atomic::AtomicPtr;
+use crate::util::loom::sync::atomic::Ordering::AcqRel;
 use std::ptr;
-use std::sync::atomic::AtomicPtr;
-use std::sync::atomic::Ordering::AcqRel;
 
 /// A thread-safe mutable memory location.
 ///
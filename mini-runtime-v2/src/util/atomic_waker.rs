@@ -0,0 +1,120 @@
+use crate::util::loom::sync::atomic::AtomicUsize;
+use crate::util::loom::sync::atomic::Ordering::{AcqRel, Acquire, Release};
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::task::Waker;
+
+/// The waker of a single task, registered and woken without a lock.
+///
+/// One task registers its waker with [`register_by_ref`], before checking
+/// the condition it waits for; any thread makes the condition true, then
+/// calls [`wake`]. Whatever the interleaving, the task either sees the
+/// condition or is woken.
+///
+/// The waker slot is guarded by a small state machine instead of a mutex:
+/// `REGISTERING` is set while `register_by_ref` writes the slot, `WAKING`
+/// while `wake` takes it out. A `wake` racing with a registration leaves
+/// `WAKING` set, and the registering thread wakes the new waker itself.
+///
+/// [`register_by_ref`]: AtomicWaker::register_by_ref
+/// [`wake`]: AtomicWaker::wake
+pub(crate) struct AtomicWaker {
+    state: AtomicUsize,
+    waker: UnsafeCell<Option<Waker>>,
+}
+
+/// Nobody is accessing the waker slot.
+const WAITING: usize = 0;
+
+/// A task is writing a new waker into the slot.
+const REGISTERING: usize = 0b01;
+
+/// The waker is being taken out of the slot to be woken.
+const WAKING: usize = 0b10;
+
+// Safety: the waker slot is only accessed by the thread which moved the
+// state out of `WAITING`, see `register_by_ref` and `take`.
+unsafe impl Send for AtomicWaker {}
+unsafe impl Sync for AtomicWaker {}
+
+impl AtomicWaker {
+    pub(crate) fn new() -> AtomicWaker {
+        AtomicWaker {
+            state: AtomicUsize::new(WAITING),
+            waker: UnsafeCell::new(None),
+        }
+    }
+
+    /// Registers `waker` to be woken by the next call to `wake`.
+    ///
+    /// Only one task may register at a time: concurrent registrations are a
+    /// bug of the caller, one of them is ignored.
+    pub(crate) fn register_by_ref(&self, waker: &Waker) {
+        match self
+            .state
+            .compare_exchange(WAITING, REGISTERING, Acquire, Acquire)
+            .unwrap_or_else(|actual| actual)
+        {
+            WAITING => {
+                // Safety: the `REGISTERING` bit gives exclusive access to the
+                // slot, `take` doesn't touch it until the bit is cleared.
+                let slot = unsafe { &mut *self.waker.get() };
+                match slot {
+                    Some(current) if current.will_wake(waker) => {}
+                    _ => *slot = Some(waker.clone()),
+                }
+
+                if let Err(actual) =
+                    self.state
+                        .compare_exchange(REGISTERING, WAITING, AcqRel, Acquire)
+                {
+                    // `wake` was called meanwhile, and couldn't take the
+                    // waker: wake it here.
+                    debug_assert_eq!(actual, REGISTERING | WAKING);
+                    let waker = slot.take();
+                    self.state.swap(WAITING, AcqRel);
+
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                }
+            }
+            WAKING => {
+                // The previous waker is being woken, the new one may have
+                // missed the notification: wake it right away.
+                waker.wake_by_ref();
+            }
+            state => {
+                debug_assert!(state == REGISTERING || state == REGISTERING | WAKING);
+            }
+        }
+    }
+
+    /// Wakes the registered waker, if any.
+    pub(crate) fn wake(&self) {
+        if let Some(waker) = self.take() {
+            waker.wake();
+        }
+    }
+
+    /// Takes the registered waker out of the slot.
+    fn take(&self) -> Option<Waker> {
+        match self.state.fetch_or(WAKING, AcqRel) {
+            WAITING => {
+                // Safety: the `WAKING` bit gives exclusive access to the slot.
+                let waker = unsafe { (*self.waker.get()).take() };
+                self.state.fetch_and(!WAKING, Release);
+                waker
+            }
+            // A registration is in progress, it sees the `WAKING` bit and
+            // wakes its waker; or another `wake` is taking the waker.
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for AtomicWaker {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.debug_struct("AtomicWaker").finish_non_exhaustive()
+    }
+}
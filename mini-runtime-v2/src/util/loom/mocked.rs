@@ -0,0 +1,9 @@
+pub(crate) mod sync {
+    pub(crate) use loom::sync::{Arc, Condvar, Mutex};
+
+    pub(crate) mod atomic {
+        pub(crate) use loom::sync::atomic::{
+            AtomicBool, AtomicPtr, AtomicU64, AtomicUsize, Ordering,
+        };
+    }
+}
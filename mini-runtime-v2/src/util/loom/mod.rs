@@ -0,0 +1,23 @@
+//! This module abstracts over `loom` and `std::sync` depending on whether we
+//! are running the loom model tests or not.
+//!
+//! Every synchronization primitive whose memory ordering matters (the pointer
+//! in `AtomicCell`, the counter behind `ThreadId::next` and the `Arc` behind
+//! the waker vtable) must be imported from here instead of from `std`. When
+//! the crate is built with `RUSTFLAGS="--cfg loom"`, the types are swapped
+//! for the `loom` versions, which lets `loom::model` explore every possible
+//! interleaving of the threads in a test.
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release loom_
+//! ```
+
+#[cfg(not(all(test, loom)))]
+mod std;
+#[cfg(not(all(test, loom)))]
+pub(crate) use self::std::*;
+
+#[cfg(all(test, loom))]
+mod mocked;
+#[cfg(all(test, loom))]
+pub(crate) use self::mocked::*;
@@ -1,5 +1,7 @@
 pub(crate) mod error;
 
+pub(crate) mod loom;
+
 pub(crate) mod rand;
 pub(crate) use self::rand::RngSeedGenerator;
 
@@ -7,6 +9,13 @@ pub(crate) mod markers;
 
 pub(crate) mod atomic_cell;
 
+mod atomic_waker;
+pub(crate) use atomic_waker::AtomicWaker;
+
 mod wake;
-pub(crate) use wake::WakerRef;
-pub(crate) use wake::{Wake, waker_ref};
+#[cfg_attr(not(loom), allow(unused_imports))]
+pub(crate) use wake::waker;
+pub(crate) use wake::{Wake, WakerRef, waker_ref};
+
+#[cfg(all(test, loom))]
+mod tests;
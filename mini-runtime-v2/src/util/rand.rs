@@ -70,6 +70,13 @@ impl FastRand {
         (mul >> 32) as u32
     }
 
+    /// Returns a random number in `0..n`, `n` must not be zero.
+    pub(crate) fn fastrand_n_u64(&mut self, n: u64) -> u64 {
+        // Same reduction as `fastrand_n`, over 64 bits.
+        let x = (self.fastrand() as u64) << 32 | self.fastrand() as u64;
+        ((x as u128).wrapping_mul(n as u128) >> 64) as u64
+    }
+
     fn fastrand(&mut self) -> u32 {
         let mut s1 = self.one;
         let s0 = self.two;
@@ -86,7 +93,7 @@ impl FastRand {
 
 mod loon_rand {
     use std::collections::hash_map::RandomState;
-    use std::hash::{BuildHasher, Hash, Hasher};
+    use std::hash::BuildHasher;
     use std::sync::atomic::AtomicU32;
     use std::sync::atomic::Ordering::Relaxed;
 
@@ -95,12 +102,8 @@ mod loon_rand {
     pub(crate) fn seed() -> u64 {
         let rand_state = RandomState::new();
 
-        let mut hasher = rand_state.build_hasher();
-
-        // Hash some unique-ish data to generate some new state
-        COUNTER.fetch_add(1, Relaxed).hash(&mut hasher);
-
-        // Get the seed
-        hasher.finish()
+        // Hash some unique-ish data to generate some new state, the hash is
+        // the seed.
+        rand_state.hash_one(COUNTER.fetch_add(1, Relaxed))
     }
 }
@@ -0,0 +1,53 @@
+use crate::util::atomic_cell::AtomicCell;
+use loom::sync::Arc;
+use loom::thread;
+
+/// Two threads swap their own values into the cell. Whatever the
+/// interleaving, every value must come out exactly once: either through one
+/// of the swaps or by the final `take`.
+#[test]
+fn concurrent_swap_never_loses_or_duplicates_values() {
+    loom::model(|| {
+        let cell = Arc::new(AtomicCell::new(Some(Box::new(0))));
+
+        let th = {
+            let cell = cell.clone();
+            thread::spawn(move || cell.swap(Some(Box::new(1))))
+        };
+
+        let mine = cell.swap(Some(Box::new(2)));
+        let theirs = th.join().unwrap();
+        let last = cell.take();
+
+        let mut seen: Vec<i32> = [mine, theirs, last]
+            .into_iter()
+            .flatten()
+            .map(|v| *v)
+            .collect();
+        seen.sort();
+
+        assert_eq!(seen, vec![0, 1, 2]);
+    });
+}
+
+/// A value published through `set` on one thread must be fully visible to the
+/// thread that takes it out of the cell.
+#[test]
+fn set_publishes_value_to_taker() {
+    loom::model(|| {
+        let cell = Arc::new(AtomicCell::<Vec<u8>>::new(None));
+
+        let th = {
+            let cell = cell.clone();
+            thread::spawn(move || cell.set(Box::new(vec![1, 2, 3])))
+        };
+
+        let taken = cell.take();
+        th.join().unwrap();
+
+        match taken {
+            Some(v) => assert_eq!(*v, vec![1, 2, 3]),
+            None => assert_eq!(*cell.take().unwrap(), vec![1, 2, 3]),
+        }
+    });
+}
@@ -0,0 +1,48 @@
+use crate::util::loom::sync::Arc;
+use crate::util::loom::sync::atomic::AtomicBool;
+use crate::util::loom::sync::atomic::AtomicU64;
+use crate::util::loom::sync::atomic::Ordering::SeqCst;
+use crate::util::{AtomicWaker, Wake, waker_ref};
+use loom::thread;
+
+struct Counter {
+    wakes: AtomicU64,
+}
+
+impl Wake for Counter {
+    fn wake(arc_self: Arc<Self>) {
+        Wake::wake_by_ref(&arc_self);
+    }
+
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.wakes.fetch_add(1, SeqCst);
+    }
+}
+
+/// A thread sets a flag then wakes, while the waiting thread registers then
+/// checks the flag: whatever the interleaving, the waiter sees the flag or
+/// is woken.
+#[test]
+fn wake_is_never_lost() {
+    loom::model(|| {
+        let counter = Arc::new(Counter {
+            wakes: AtomicU64::new(0),
+        });
+        let waker = Arc::new(AtomicWaker::new());
+        let ready = Arc::new(AtomicBool::new(false));
+
+        let th = {
+            let (waker, ready) = (waker.clone(), ready.clone());
+            thread::spawn(move || {
+                ready.store(true, SeqCst);
+                waker.wake();
+            })
+        };
+
+        waker.register_by_ref(&waker_ref(&counter));
+        let seen = ready.load(SeqCst);
+        th.join().unwrap();
+
+        assert!(seen || counter.wakes.load(SeqCst) == 1);
+    });
+}
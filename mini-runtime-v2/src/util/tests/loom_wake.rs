@@ -0,0 +1,119 @@
+use crate::util::loom::sync::Arc;
+use crate::util::loom::sync::atomic::AtomicU64;
+use crate::util::loom::sync::atomic::Ordering::SeqCst;
+use crate::util::{Wake, waker, waker_ref};
+use loom::thread;
+
+struct Counter {
+    wakes: AtomicU64,
+}
+
+impl Wake for Counter {
+    fn wake(arc_self: Arc<Self>) {
+        Wake::wake_by_ref(&arc_self);
+    }
+
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        arc_self.wakes.fetch_add(1, SeqCst);
+    }
+}
+
+/// A waker cloned out of a `WakerRef` is sent to another thread and consumed
+/// there while the original thread wakes by reference. Both wakes must be
+/// observed and the strong count must be back to one once every waker is gone.
+#[test]
+fn cloned_waker_wakes_from_another_thread() {
+    loom::model(|| {
+        let counter = Arc::new(Counter {
+            wakes: AtomicU64::new(0),
+        });
+
+        let waker = waker(counter.clone());
+        let th = thread::spawn(move || waker.wake());
+
+        waker_ref(&counter).wake_by_ref();
+        th.join().unwrap();
+
+        assert_eq!(counter.wakes.load(SeqCst), 2);
+        assert_eq!(Arc::strong_count(&counter), 1);
+    });
+}
+
+/// Cloning and dropping wakers concurrently must keep the reference count
+/// balanced, otherwise the `Arc` would either leak or be freed too early.
+#[test]
+fn concurrent_clone_and_drop_keeps_refcount_balanced() {
+    loom::model(|| {
+        let counter = Arc::new(Counter {
+            wakes: AtomicU64::new(0),
+        });
+
+        let w1 = waker_ref(&counter).clone();
+        let w2 = w1.clone();
+
+        let th = thread::spawn(move || {
+            let w3 = w2.clone();
+            drop(w2);
+            w3.wake_by_ref();
+        });
+
+        drop(w1);
+        th.join().unwrap();
+
+        assert_eq!(counter.wakes.load(SeqCst), 1);
+        assert_eq!(Arc::strong_count(&counter), 1);
+    });
+}
+
+/// Each path of the vtable moves the strong count by the reference it owns:
+/// `clone` takes one, `wake` and `drop` give it back, `wake_by_ref` and the
+/// `WakerRef` itself leave it untouched.
+#[test]
+fn vtable_paths_keep_strong_count() {
+    loom::model(|| {
+        let counter = Arc::new(Counter {
+            wakes: AtomicU64::new(0),
+        });
+
+        let borrowed = waker_ref(&counter);
+        borrowed.wake_by_ref();
+        assert_eq!(Arc::strong_count(&counter), 1);
+
+        let owned = borrowed.to_owned();
+        drop(borrowed);
+        assert_eq!(Arc::strong_count(&counter), 2);
+
+        let cloned = owned.clone();
+        assert_eq!(Arc::strong_count(&counter), 3);
+
+        cloned.wake_by_ref();
+        assert_eq!(Arc::strong_count(&counter), 3);
+
+        cloned.wake();
+        assert_eq!(Arc::strong_count(&counter), 2);
+
+        drop(owned);
+        assert_eq!(Arc::strong_count(&counter), 1);
+        assert_eq!(counter.wakes.load(SeqCst), 3);
+    });
+}
+
+/// `waker` moves the `Arc` into the `Waker`: the count it was given is the
+/// one the waker owns, and it goes away with the last waker.
+#[test]
+fn owned_waker_takes_over_the_arc() {
+    loom::model(|| {
+        let counter = Arc::new(Counter {
+            wakes: AtomicU64::new(0),
+        });
+
+        let owned = waker(counter.clone());
+        assert_eq!(Arc::strong_count(&counter), 2);
+
+        let th = thread::spawn(move || owned.wake());
+        th.join().unwrap();
+
+        assert_eq!(counter.wakes.load(SeqCst), 1);
+        assert_eq!(Arc::strong_count(&counter), 1);
+    });
+}
@@ -0,0 +1,3 @@
+mod loom_atomic_cell;
+mod loom_atomic_waker;
+mod loom_wake;
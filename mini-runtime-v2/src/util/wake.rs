@@ -10,57 +10,70 @@
 //! Here's the breakdown of the "why":
 //!
 //! 1. Asynchronous Tasks and Wakers: In Rust's async/await ecosystem, when a Future cannot complete
-//! immediately (e.g., waiting for I/O), it returns Poll::Pending and provides a Waker
-//! to the runtime (or executor). The executor saves this Waker. When the event the Future
-//! is waiting on occurs, the Waker is used to notify the executor that the task associated with
-//! that Future is now ready to make progress and should be polled again.
+//!    immediately (e.g., waiting for I/O), it returns Poll::Pending and provides a Waker
+//!    to the runtime (or executor). The executor saves this Waker. When the event the Future
+//!    is waiting on occurs, the Waker is used to notify the executor that the task associated with
+//!    that Future is now ready to make progress and should be polled again.
 //!
 //! 2. Shared State: Asynchronous tasks often need to share state. For example, multiple tasks
-//! might wait on the same network connection or a shared queue. Arc (Atomically Reference Counted)
-//! is the standard Rust type for sharing data safely across threads and tasks.
-//! The state that needs to trigger a wake-up (like a completion signal on a network stream)
-//! is typically part of this shared state, wrapped inside an Arc.
+//!    might wait on the same network connection or a shared queue. Arc (Atomically Reference Counted)
+//!    is the standard Rust type for sharing data safely across threads and tasks.
+//!    The state that needs to trigger a wake-up (like a completion signal on a network stream)
+//!    is typically part of this shared state, wrapped inside an Arc.
 //!
 //! 3. The Waker Interface: The standard library's Waker is designed to be efficient and flexible,
-//! working with various underlying mechanisms. However, the core mechanism for creating a Waker
-//! from raw components is Waker::from_raw, which requires a RawWaker and a RawWakerVTable.
-//! - RawWaker: This struct simply holds a *const () pointer (the "data") and a reference to a
-//! RawWakerVTable.
-//! - RawWakerVTable: This struct holds function pointers for the four essential low-level
-//! operations: clone, wake, wake_by_ref, and drop. These functions receive the *const () data
-//! pointer and must know how to perform the respective operation using that pointer.
+//!    working with various underlying mechanisms. However, the core mechanism for creating a Waker
+//!    from raw components is Waker::from_raw, which requires a RawWaker and a RawWakerVTable.
+//!    - RawWaker: This struct simply holds a *const () pointer (the "data") and a reference to a
+//!      RawWakerVTable.
+//!    - RawWakerVTable: This struct holds function pointers for the four essential low-level
+//!      operations: clone, wake, wake_by_ref, and drop. These functions receive the *const () data
+//!      pointer and must know how to perform the respective operation using that pointer.
 //!
 //! 4. The Problem: The Waker infrastructure doesn't inherently know how to handle an Arc<T>.
-//! If your task state is in an Arc<MyTaskState>, and MyTaskState knows how to perform the wake
-//! operation, you need a way to create a Waker whose internal data pointer is the pointer to the
-//! Arc<MyTaskState>'s contents, and whose RawWakerVTable functions correctly manipulate that
-//! specific Arc.
+//!    If your task state is in an Arc<MyTaskState>, and MyTaskState knows how to perform the wake
+//!    operation, you need a way to create a Waker whose internal data pointer is the pointer to the
+//!    Arc<MyTaskState>'s contents, and whose RawWakerVTable functions correctly manipulate that
+//!    specific Arc.
 //!
 //! 5. This Code's Solution:
-//! - The Wake Trait: Defines a standard way for a type W within an Arc to expose its wake
-//! functionality (wake and wake_by_ref).
-//! - waker_vtable: This function creates the crucial bridge. It generates a RawWakerVTable
-//! specifically designed to work with pointers originating from Arc<W>. The functions in this
-//! vtable (clone_arc_raw, etc.) use unsafe code to convert the raw *const () pointer back into
-//! an Arc<W> (or manipulate its reference count directly) to perform the required operations.
-//! - waker_ref: This function provides a safe, convenient entry point. Given a borrow of an Arc<W>,
-//! it uses the Arc::as_ptr method to get the raw data pointer and pairs it with the waker_vtable
-//! to create a Waker wrapped in WakerRef. WakerRef adds a lifetime constraint to ensure
-//! the resulting Waker doesn't outlive the borrowed Arc.
-//! - The unsafe Helpers (clone_arc_raw, etc.): These are the core implementations for the vtable.
-//! They use unsafe because they directly manipulate raw pointers and the Arc's internal state
-//! (increment_strong_count, from_raw). This is necessary because the RawWaker interface operates
-//! at a very low level, requiring manual memory management details for the specific data type
-//! it wraps (in this case, Arc). The safety relies on the invariant that the *const () passed
-//! to these functions is indeed a valid pointer to the data inside an Arc<T> that was created
-//! by Arc::as_ptr or a similar mechanism, and that the reference counts are managed correctly
-//! by these functions.
+//!    - The Wake Trait: Defines a standard way for a type W within an Arc to expose its wake
+//!      functionality (wake and wake_by_ref).
+//!    - waker_vtable: This function creates the crucial bridge. It generates a RawWakerVTable
+//!      specifically designed to work with pointers originating from Arc<W>. The functions in this
+//!      vtable (clone_arc_raw, etc.) use unsafe code to convert the raw *const () pointer back into
+//!      an Arc<W> (or manipulate its reference count directly) to perform the required operations.
+//!    - waker_ref: This function provides a safe, convenient entry point. Given a borrow of an Arc<W>,
+//!      it uses the Arc::as_ptr method to get the raw data pointer and pairs it with the waker_vtable
+//!      to create a Waker wrapped in WakerRef. WakerRef adds a lifetime constraint to ensure
+//!      the resulting Waker doesn't outlive the borrowed Arc.
+//!    - The unsafe Helpers (clone_arc_raw, etc.): These are the core implementations for the vtable.
+//!      They use unsafe because they directly manipulate raw pointers and the Arc's internal state
+//!      (increment_strong_count, from_raw). This is necessary because the RawWaker interface operates
+//!      at a very low level, requiring manual memory management details for the specific data type
+//!      it wraps (in this case, Arc). The safety relies on the invariant that the *const () passed
+//!      to these functions is indeed a valid pointer to the data inside an Arc<T> that was created
+//!      by Arc::as_ptr or a similar mechanism, and that the reference counts are managed correctly
+//!      by these functions.
+//!
+//! 6. Delegating to the Standard Library: `std::task::Wake` does the same for
+//!    `Arc<impl std::task::Wake>`, with the vtable of the standard library.
+//!    The wakers are built with it, through the `StdWake` adapter, unless
+//!    the `raw_waker_vtable` feature is enabled: every waker then goes through
+//!    the hand-rolled vtable, to step through it in a debugger. Conversely, a
+//!    type implementing `std::task::Wake` implements `Wake`, so either trait
+//!    can be picked.
+//!
+//! The tasks of the runtime don't live in an `Arc`: their wakers have their
+//! own vtable, see `runtime::task::waker`, and only borrow `WakerRef`.
 
+use crate::util::loom::sync::Arc;
 use std::marker::PhantomData;
 use std::mem::ManuallyDrop;
 use std::ops::Deref;
-use std::sync::Arc;
-use std::task::{RawWaker, RawWakerVTable, Waker};
+#[cfg(any(loom, feature = "raw_waker_vtable"))]
+use std::task::RawWakerVTable;
+use std::task::{RawWaker, Waker};
 
 /// A trait defining the necessary operations for a type that can be woken
 /// up when shared via an `Arc`.
@@ -88,6 +101,44 @@ pub(crate) trait Wake: Send + Sync + Sized + 'static {
     fn wake_by_ref(arc_self: &Arc<Self>);
 }
 
+/// A type implementing `std::task::Wake` implements `Wake`, its wakers only
+/// go through the standard library's vtable.
+///
+/// Not with loom: its `Arc` is not the one of `std::task::Wake`.
+#[cfg(not(loom))]
+impl<T: std::task::Wake + Send + Sync + 'static> Wake for T {
+    fn wake(arc_self: Arc<Self>) {
+        std::task::Wake::wake(arc_self);
+    }
+
+    fn wake_by_ref(arc_self: &Arc<Self>) {
+        std::task::Wake::wake_by_ref(arc_self);
+    }
+}
+
+/// Adapts a `Wake` type to `std::task::Wake`, to build its wakers with the
+/// vtable of the standard library.
+///
+/// `repr(transparent)` gives it the layout of `W`: an `Arc<W>` is an
+/// `Arc<StdWake<W>>` once its pointer is cast.
+#[cfg(not(any(loom, feature = "raw_waker_vtable")))]
+#[repr(transparent)]
+struct StdWake<W>(W);
+
+#[cfg(not(any(loom, feature = "raw_waker_vtable")))]
+impl<W: Wake> std::task::Wake for StdWake<W> {
+    fn wake(self: Arc<Self>) {
+        // Safety: same layout, the reference count is handed over.
+        W::wake(unsafe { Arc::from_raw(Arc::into_raw(self).cast::<W>()) });
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        // Safety: same layout, `ManuallyDrop` leaves the count untouched.
+        let arc = ManuallyDrop::new(unsafe { Arc::<W>::from_raw(Arc::as_ptr(self).cast()) });
+        W::wake_by_ref(&arc);
+    }
+}
+
 /// A `Waker` that is only valid for a given lifetime `'a`.
 ///
 /// This struct wraps a standard `Waker` but uses `PhantomData` to tie
@@ -120,6 +171,33 @@ impl Deref for WakerRef<'_> {
     }
 }
 
+impl<'a> WakerRef<'a> {
+    /// Wraps a `Waker` that must not be dropped, e.g. because it doesn't own
+    /// the reference its vtable gives back on drop.
+    ///
+    /// # Safety
+    ///
+    /// The functions of the vtable must be safe to call with the data
+    /// pointer for `'a`.
+    pub(crate) unsafe fn from_raw(raw: RawWaker) -> WakerRef<'a> {
+        WakerRef {
+            // Safety: guaranteed by the caller.
+            waker: ManuallyDrop::new(unsafe { Waker::from_raw(raw) }),
+            _p: PhantomData,
+        }
+    }
+
+    /// Returns a `Waker` that outlives the borrow, owning a reference of
+    /// the `Arc` it was created from.
+    ///
+    /// Same as cloning the dereferenced `Waker`, spelled out: the clone goes
+    /// through the vtable, which increments the strong count.
+    #[cfg_attr(not(loom), allow(dead_code))] // The runtime only borrows wakers.
+    pub(crate) fn to_owned(&self) -> Waker {
+        Waker::clone(&self.waker)
+    }
+}
+
 /// Creates a reference to a `Waker` (`WakerRef`) from a reference to `Arc<impl Wake>`.
 ///
 /// This function is the primary way to create a temporary `Waker` from an `Arc`
@@ -131,6 +209,24 @@ impl Deref for WakerRef<'_> {
 /// be handled by the `waker_vtable` which knows how to correctly manipulate
 /// the `Arc`'s reference count and call the `Wake` trait methods.
 pub(crate) fn waker_ref<W: Wake>(wake: &Arc<W>) -> WakerRef<'_> {
+    #[cfg(not(any(loom, feature = "raw_waker_vtable")))]
+    {
+        // Safety: the `Arc` is only borrowed, the `Waker` doesn't own a
+        // reference: it is never dropped, only its clones are.
+        let arc = unsafe { Arc::<StdWake<W>>::from_raw(Arc::as_ptr(wake).cast()) };
+        WakerRef {
+            waker: ManuallyDrop::new(Waker::from(arc)),
+            _p: PhantomData,
+        }
+    }
+
+    #[cfg(any(loom, feature = "raw_waker_vtable"))]
+    raw_waker_ref(wake)
+}
+
+/// Like `waker_ref`, with the hand-rolled vtable of this module.
+#[cfg(any(loom, feature = "raw_waker_vtable"))]
+fn raw_waker_ref<W: Wake>(wake: &Arc<W>) -> WakerRef<'_> {
     // Get a raw pointer to the data managed by the Arc.
     let ptr = Arc::as_ptr(wake).cast::<()>();
 
@@ -148,12 +244,24 @@ pub(crate) fn waker_ref<W: Wake>(wake: &Arc<W>) -> WakerRef<'_> {
     }
 }
 
+/// Creates a `Waker` owning `wake`, the counterpart of
+/// `Waker::from(Arc<impl std::task::Wake>)` for `Wake` types.
+///
+/// A `From<Arc<W>> for Waker` impl is not allowed by the orphan rule, and
+/// would overlap with the one of the standard library anyway.
+#[cfg_attr(not(loom), allow(dead_code))] // The runtime only borrows wakers.
+pub(crate) fn waker<W: Wake>(wake: Arc<W>) -> Waker {
+    // The owned waker takes a reference of its own, `wake` is dropped.
+    waker_ref(&wake).to_owned()
+}
+
 /// Generates a `RawWakerVTable` tailored for `Arc<W>` where `W` implements `Wake`.
 ///
 /// This vtable provides the necessary function pointers (`clone`, `wake`, `wake_by_ref`, `drop`)
 /// for a `RawWaker` whose data pointer points to the contents of an `Arc<W>`.
 /// These functions correctly interact with the `Arc`'s reference count and call
 /// the `Wake` trait methods.
+#[cfg(any(loom, feature = "raw_waker_vtable"))]
 fn waker_vtable<W: Wake>() -> &'static RawWakerVTable {
     // Define the low-level operations for the RawWaker.
     &RawWakerVTable::new(
@@ -176,10 +284,11 @@ fn waker_vtable<W: Wake>() -> &'static RawWakerVTable {
 /// # Safety
 /// This function is unsafe because it assumes `data` is a valid pointer to the data
 /// within an `Arc<T>`.
+#[cfg(any(loom, feature = "raw_waker_vtable"))]
 unsafe fn clone_arc_raw<T: Wake>(data: *const ()) -> RawWaker {
     // Increment the strong count of the Arc pointed to by `data`.
     // This is the core of cloning an Arc-based Waker.
-    Arc::<T>::increment_strong_count(data as *const T);
+    unsafe { Arc::<T>::increment_strong_count(data as *const T) };
     // Return a new RawWaker with the same data pointer and vtable.
     RawWaker::new(data, waker_vtable::<T>())
 }
@@ -188,18 +297,18 @@ unsafe fn clone_arc_raw<T: Wake>(data: *const ()) -> RawWaker {
 ///
 /// This function is called when `Waker::wake()` is called on a `Waker` created
 /// from `waker_ref`. It reconstructs the `Arc<T>` from the raw pointer using
-/// `Arc::from_raw` (which takes ownership of one reference count) and then
-/// calls the `Wake::wake` method, consuming the `Arc`.
+/// `Arc::from_raw` (which takes ownership of one reference count) and hands it
+/// to `Wake::wake`.
 ///
 /// # Safety
 /// This function is unsafe because it assumes `data` is a valid pointer to the data
 /// within an `Arc<T>` and that the `RawWaker` held a valid reference count
 /// that can now be consumed via `Arc::from_raw`.
+#[cfg(any(loom, feature = "raw_waker_vtable"))]
 unsafe fn wake_arc_raw<T: Wake>(data: *const ()) {
     // Reconstruct the Arc from the raw pointer. This takes ownership
     // of the reference count held by the RawWaker.
-    let arc: Arc<T> = Arc::from_raw(data as *const T);
-    // Call the wake method on the Arc. This consumes the Arc.
+    let arc: Arc<T> = unsafe { Arc::from_raw(data.cast()) };
     Wake::wake(arc);
 }
 
@@ -215,11 +324,12 @@ unsafe fn wake_arc_raw<T: Wake>(data: *const ()) {
 /// This function is unsafe because it assumes `data` is a valid pointer to the data
 /// within an `Arc<T>`. It relies on the correct use of `ManuallyDrop` to avoid
 /// incorrect reference count manipulation.
+#[cfg(any(loom, feature = "raw_waker_vtable"))]
 unsafe fn wake_by_ref_arc_raw<T: Wake>(data: *const ()) {
     // Reconstruct the Arc from the raw pointer and wrap it in ManuallyDrop.
     // This gives us a temporary Arc value to borrow from, but prevents
     // the Arc's drop implementation (which would decrement the count) from running.
-    let arc = ManuallyDrop::new(Arc::<T>::from_raw(data.cast()));
+    let arc = ManuallyDrop::new(unsafe { Arc::<T>::from_raw(data.cast()) });
     // Call the wake_by_ref method using a reference to the Arc.
     Wake::wake_by_ref(&arc);
     // ManuallyDrop ensures the Arc isn't dropped here.
@@ -236,10 +346,11 @@ unsafe fn wake_by_ref_arc_raw<T: Wake>(data: *const ()) {
 /// This function is unsafe because it assumes `data` is a valid pointer to the data
 /// within an `Arc<T>` and that the `RawWaker` held a valid reference count
 /// that can now be consumed via `Arc::from_raw` and dropped.
+#[cfg(any(loom, feature = "raw_waker_vtable"))]
 unsafe fn drop_arc_raw<T: Wake>(data: *const ()) {
     // Reconstruct the Arc from the raw pointer. This takes ownership
     // of the reference count held by the RawWaker.
-    let arc: Arc<T> = Arc::from_raw(data.cast());
+    let arc: Arc<T> = unsafe { Arc::from_raw(data.cast()) };
     // Drop the Arc, decrementing its strong count.
     drop(arc);
 }
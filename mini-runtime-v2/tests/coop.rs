@@ -0,0 +1,125 @@
+use mini_runtime_v2::sync::mpsc;
+use mini_runtime_v2::time::{self, Duration};
+use mini_runtime_v2::{spawn, task};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+
+mod support;
+use support::rt_paused;
+
+/// Budget of a task poll, in operations on runtime resources.
+const BUDGET: usize = 128;
+
+/// Number of polls after which the scheduler checks the drivers.
+const EVENT_INTERVAL: usize = 61;
+
+#[test]
+fn flooded_channel_does_not_starve_timer() {
+    // Draining the channel takes far longer than one scheduler tick.
+    const MESSAGES: usize = 100 * EVENT_INTERVAL * BUDGET;
+
+    rt_paused().block_on(async {
+        let (tx, mut rx) = mpsc::channel(MESSAGES);
+        for i in 0..MESSAGES {
+            tx.try_send(i).unwrap();
+        }
+        drop(tx);
+
+        let received = Arc::new(AtomicUsize::new(0));
+
+        let timer = spawn({
+            let received = received.clone();
+            async move {
+                time::sleep(Duration::from_millis(1)).await;
+                received.load(SeqCst)
+            }
+        });
+        // Let the timer task register its timer.
+        task::yield_now().await;
+
+        let consumer = spawn({
+            let received = received.clone();
+            async move {
+                // `recv` is always ready: without a budget, the loop never
+                // yields until the channel is drained.
+                while rx.recv().await.is_some() {
+                    received.fetch_add(1, SeqCst);
+                }
+            }
+        });
+
+        // The timer is due, it fires the next time the scheduler checks the
+        // time driver: after at most one event interval of consumer polls.
+        time::advance(Duration::from_millis(1)).await;
+
+        let received_when_fired = timer.await.unwrap();
+        assert!(
+            received_when_fired <= (EVENT_INTERVAL + 1) * BUDGET,
+            "the timer was starved: {received_when_fired} messages received before it fired",
+        );
+
+        consumer.await.unwrap();
+        assert_eq!(received.load(SeqCst), MESSAGES);
+    });
+}
+
+#[test]
+fn budget_is_per_poll() {
+    rt_paused().block_on(async {
+        let (tx, mut rx) = mpsc::channel(1);
+
+        // Each round trip makes progress: the budget is spent, then refilled
+        // when the task is polled again.
+        let producer = task::spawn(async move {
+            for i in 0..10 * BUDGET {
+                tx.send(i).await.unwrap();
+            }
+        });
+
+        for i in 0..10 * BUDGET {
+            assert_eq!(rx.recv().await, Some(i));
+        }
+        assert_eq!(rx.recv().await, None);
+
+        producer.await.unwrap();
+    });
+}
+
+#[test]
+fn unconstrained_future_does_not_yield() {
+    const MESSAGES: usize = 10 * EVENT_INTERVAL * BUDGET;
+
+    rt_paused().block_on(async {
+        let (tx, mut rx) = mpsc::channel(MESSAGES);
+        for i in 0..MESSAGES {
+            tx.try_send(i).unwrap();
+        }
+        drop(tx);
+
+        let received = Arc::new(AtomicUsize::new(0));
+
+        let timer = spawn({
+            let received = received.clone();
+            async move {
+                time::sleep(Duration::from_millis(1)).await;
+                received.load(SeqCst)
+            }
+        });
+        task::yield_now().await;
+
+        let consumer = spawn(task::unconstrained({
+            let received = received.clone();
+            async move {
+                while rx.recv().await.is_some() {
+                    received.fetch_add(1, SeqCst);
+                }
+            }
+        }));
+
+        time::advance(Duration::from_millis(1)).await;
+
+        // The consumer drained the channel in a single poll.
+        assert_eq!(timer.await.unwrap(), MESSAGES);
+        consumer.await.unwrap();
+    });
+}
@@ -0,0 +1,61 @@
+use mini_runtime_v2::fs::{self, File};
+use mini_runtime_v2::io::{AsyncReadExt, AsyncWriteExt};
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+mod support;
+use support::rt;
+
+/// Returns a path in the temporary directory, unique to the test process.
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("mini-runtime-v2-{}-{name}", std::process::id()))
+}
+
+#[test]
+fn write_read_round_trip() {
+    let path = temp_path("round-trip");
+
+    rt().block_on(async {
+        fs::write(&path, "hello\nworld\n").await.unwrap();
+
+        assert_eq!(fs::read(&path).await.unwrap(), b"hello\nworld\n");
+        assert_eq!(fs::read_to_string(&path).await.unwrap(), "hello\nworld\n");
+    });
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn file_write_read_round_trip() {
+    let path = temp_path("file");
+    // Several writes, each moved to the blocking pool.
+    let chunks: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; 10_000]).collect();
+
+    rt().block_on(async {
+        let mut file = File::create(&path).await.unwrap();
+        for chunk in &chunks {
+            file.write_all(chunk).await.unwrap();
+        }
+        file.flush().await.unwrap();
+        drop(file);
+
+        let mut contents = Vec::new();
+        let mut file = File::open(&path).await.unwrap();
+        file.read_to_end(&mut contents).await.unwrap();
+        assert_eq!(contents, chunks.concat());
+        assert_eq!(file.metadata().await.unwrap().len(), contents.len() as u64);
+    });
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn open_missing_file() {
+    rt().block_on(async {
+        let err = File::open(temp_path("missing")).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+
+        let err = fs::read(temp_path("missing")).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+    });
+}
@@ -0,0 +1,61 @@
+use mini_runtime_v2::future::FutureExt;
+use mini_runtime_v2::task;
+use std::pin::pin;
+use std::time::Duration;
+
+mod support;
+use support::rt;
+
+#[test]
+fn counts_polls() {
+    rt().block_on(async {
+        let mut fut = pin!(
+            async {
+                task::yield_now().await;
+                task::yield_now().await;
+                42
+            }
+            .instrument_poll("yields")
+        );
+        assert_eq!(fut.poll_count(), 0);
+
+        assert_eq!(fut.as_mut().await, 42);
+        assert_eq!(fut.poll_count(), 3);
+        assert!(fut.longest_poll() <= fut.busy_duration());
+    });
+}
+
+#[test]
+fn measures_blocking_poll() {
+    rt().block_on(async {
+        let mut fut = pin!(
+            async {
+                task::yield_now().await;
+                std::thread::sleep(Duration::from_millis(20));
+            }
+            .instrument_poll("blocking")
+        );
+        fut.as_mut().await;
+
+        assert_eq!(fut.poll_count(), 2);
+        assert!(fut.longest_poll() >= Duration::from_millis(20));
+        assert!(fut.busy_duration() >= fut.longest_poll());
+    });
+}
+
+#[test]
+fn dropped_before_completion() {
+    rt().block_on(async {
+        let fut = async {
+            task::yield_now().await;
+            unreachable!();
+        }
+        .instrument_poll("cancelled");
+
+        // Polled once, then dropped.
+        mini_runtime_v2::select! {
+            _ = fut => unreachable!(),
+            _ = async {} => {}
+        }
+    });
+}
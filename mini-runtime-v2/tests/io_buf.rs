@@ -0,0 +1,75 @@
+use mini_runtime_v2::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, BufWriter};
+
+mod support;
+use support::rt;
+
+/// The lines span several fills of a buffer smaller than them.
+#[test]
+fn read_line_across_buffer_fills() {
+    rt().block_on(async {
+        let data: &[u8] = b"first line\nsecond\n\nno newline";
+        let mut reader = BufReader::with_capacity(4, data);
+
+        let mut lines = Vec::new();
+        loop {
+            let mut line = String::new();
+            let n = reader.read_line(&mut line).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            assert_eq!(n, line.len());
+            lines.push(line);
+        }
+
+        assert_eq!(lines, ["first line\n", "second\n", "\n", "no newline"]);
+    });
+}
+
+#[test]
+fn read_until_keeps_the_delimiter() {
+    rt().block_on(async {
+        let data: &[u8] = b"a,bc,";
+        let mut reader = BufReader::new(data);
+        let mut buf = Vec::new();
+
+        assert_eq!(reader.read_until(b',', &mut buf).await.unwrap(), 2);
+        assert_eq!(buf, b"a,");
+        // Appends to the buffer.
+        assert_eq!(reader.read_until(b',', &mut buf).await.unwrap(), 3);
+        assert_eq!(buf, b"a,bc,");
+        assert_eq!(reader.read_until(b',', &mut buf).await.unwrap(), 0);
+    });
+}
+
+#[test]
+fn read_line_rejects_invalid_utf8() {
+    rt().block_on(async {
+        let data: &[u8] = b"\xff\n";
+        let mut reader = BufReader::new(data);
+        let mut line = String::from("kept");
+
+        let err = reader.read_line(&mut line).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        // The string is left as it was.
+        assert_eq!(line, "kept");
+    });
+}
+
+#[test]
+fn buf_writer_writes_on_flush() {
+    rt().block_on(async {
+        let mut writer = BufWriter::with_capacity(8, Vec::new());
+
+        writer.write_all(b"abc").await.unwrap();
+        assert!(writer.get_ref().is_empty());
+        assert_eq!(writer.buffer(), b"abc");
+
+        // Doesn't fit: the buffered data is written out first.
+        writer.write_all(b"defghi").await.unwrap();
+        assert_eq!(writer.get_ref(), b"abc");
+
+        writer.flush().await.unwrap();
+        assert_eq!(writer.get_ref(), b"abcdefghi");
+        assert!(writer.buffer().is_empty());
+    });
+}
@@ -0,0 +1,48 @@
+use mini_runtime_v2::io::{self, AsyncReadExt, AsyncWriteExt};
+use mini_runtime_v2::net::{TcpListener, TcpStream};
+use mini_runtime_v2::task;
+
+mod support;
+use support::rt;
+
+#[test]
+fn copy_slice_to_vec() {
+    rt().block_on(async {
+        let mut reader: &[u8] = b"hello";
+        let mut writer: Vec<u8> = vec![];
+
+        let n = io::copy(&mut reader, &mut writer).await.unwrap();
+        assert_eq!(n, 5);
+        assert_eq!(writer, b"hello");
+    });
+}
+
+/// An echo server copying the socket into itself, over loopback.
+#[test]
+fn copy_echo_over_loopback() {
+    // Larger than the buffer of `copy`, so that it loops.
+    let payload: Vec<u8> = (0..64 * 1024).map(|i| i as u8).collect();
+
+    rt().block_on(async move {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let server = task::spawn(async move {
+            let (socket, _) = listener.accept().await.unwrap();
+            let (mut rd, mut wr) = (&socket, &socket);
+            io::copy(&mut rd, &mut wr).await.unwrap()
+        });
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        // The payload fits in the socket buffers: it can be written before
+        // the echo is read.
+        stream.write_all(&payload).await.unwrap();
+        // EOF, so that `copy` returns.
+        stream.shutdown().await.unwrap();
+
+        let mut echoed = Vec::new();
+        stream.read_to_end(&mut echoed).await.unwrap();
+        assert_eq!(echoed, payload);
+        assert_eq!(server.await.unwrap(), payload.len() as u64);
+    });
+}
@@ -0,0 +1,109 @@
+use mini_runtime_v2::future::poll_fn;
+use mini_runtime_v2::select;
+use mini_runtime_v2::sync::mpsc;
+use std::future::{self, Future};
+use std::task::Poll;
+
+mod support;
+use support::rt;
+
+/// A future that never completes.
+fn never<T>() -> impl Future<Output = T> {
+    future::pending()
+}
+
+#[test]
+fn ready_branch_wins() {
+    rt().block_on(async {
+        let value = select! {
+            v = never::<u32>() => v,
+            v = async { 2 } => v * 10,
+        };
+        assert_eq!(value, 20);
+    });
+}
+
+#[test]
+fn mismatched_pattern_disables_branch() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::channel::<u32>(3);
+        drop(tx);
+
+        // `recv` returns `None` right away, the branch is disabled and the
+        // other one completes later.
+        let mut polls = 0;
+        let value = select! {
+            Some(v) = rx.recv() => v,
+            v = poll_fn(|cx| {
+                polls += 1;
+                if polls < 3 {
+                    cx.waker().wake_by_ref();
+                    return Poll::Pending;
+                }
+                Poll::Ready(7)
+            }) => v,
+        };
+        assert_eq!(value, 7);
+    });
+}
+
+#[test]
+fn else_branch_when_all_disabled() {
+    rt().block_on(async {
+        let value = select! {
+            Some(v) = async { None::<u32> } => v,
+            Ok(v) = async { Err::<u32, ()>(()) } => v,
+            else => 0,
+        };
+        assert_eq!(value, 0);
+    });
+}
+
+#[test]
+#[should_panic(expected = "all branches are disabled")]
+fn panics_without_else_branch() {
+    rt().block_on(async {
+        select! {
+            Some(_) = async { None::<u32> } => {}
+        }
+    });
+}
+
+#[test]
+fn handler_controls_enclosing_loop() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::channel(3);
+        for i in 1..=3 {
+            tx.try_send(i).unwrap();
+        }
+        drop(tx);
+
+        let mut sum = 0;
+        loop {
+            select! {
+                Some(v) = rx.recv() => {
+                    if v == 2 {
+                        continue;
+                    }
+                    sum += v;
+                }
+                else => break,
+            }
+        }
+        assert_eq!(sum, 4);
+    });
+}
+
+#[test]
+fn handler_can_use_what_futures_borrowed() {
+    rt().block_on(async {
+        let mut values = vec![1];
+        let len = select! {
+            _ = async { values.push(2) } => {
+                values.push(3);
+                values.len()
+            }
+        };
+        assert_eq!(len, 3);
+    });
+}
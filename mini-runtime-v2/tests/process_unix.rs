@@ -0,0 +1,59 @@
+#![cfg(unix)]
+
+use mini_runtime_v2::process::Command;
+use mini_runtime_v2::time::{self, Duration};
+
+mod support;
+use support::rt;
+
+/// Returns whether a process, or a zombie not reaped yet, has the `pid`.
+fn exists(pid: u32) -> bool {
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[test]
+fn output_collects_stdout() {
+    rt().block_on(async {
+        let output = Command::new("echo").arg("hello").output().await.unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout, b"hello\n");
+        assert!(output.stderr.is_empty());
+    });
+}
+
+#[test]
+fn wait_returns_exit_status() {
+    rt().block_on(async {
+        let mut child = Command::new("sh").args(["-c", "exit 3"]).spawn().unwrap();
+        assert!(child.id().is_some());
+
+        let status = child.wait().await.unwrap();
+        assert_eq!(status.code(), Some(3));
+        // The child was reaped: its id may be reused.
+        assert_eq!(child.id(), None);
+        // Waiting again returns the same status.
+        assert_eq!(child.wait().await.unwrap(), status);
+    });
+}
+
+/// A child dropped while still running is queued as an orphan, and reaped
+/// once it exited, the next time a child is spawned or waited on.
+#[test]
+fn dropped_child_is_reaped() {
+    rt().block_on(async {
+        let child = Command::new("sleep").arg("0.1").spawn().unwrap();
+        let pid = child.id().unwrap();
+        drop(child);
+        assert!(exists(pid));
+
+        for _ in 0..100 {
+            if !exists(pid) {
+                return;
+            }
+            time::sleep(Duration::from_millis(20)).await;
+            Command::new("true").status().await.unwrap();
+        }
+        panic!("the dropped child {pid} was not reaped");
+    });
+}
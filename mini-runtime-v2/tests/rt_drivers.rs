@@ -0,0 +1,68 @@
+use mini_runtime_v2::net::{TcpListener, TcpStream};
+use mini_runtime_v2::runtime::{self, Runtime};
+use mini_runtime_v2::time::{self, Duration, Instant, sleep, sleep_until};
+
+fn rt(configure: impl FnOnce(&mut runtime::Builder) -> &mut runtime::Builder) -> Runtime {
+    configure(&mut runtime::Builder::new_current_thread())
+        .build()
+        .unwrap()
+}
+
+/// Returns a blocking listener, connections complete in its backlog.
+fn std_listener() -> std::net::TcpListener {
+    std::net::TcpListener::bind("127.0.0.1:0").unwrap()
+}
+
+#[test]
+#[should_panic(expected = "timers are disabled")]
+fn sleep_without_time_driver() {
+    rt(|b| b).block_on(async {
+        sleep(Duration::from_millis(1)).await;
+    });
+}
+
+#[test]
+#[should_panic(expected = "timers are disabled")]
+fn sleep_until_without_time_driver() {
+    rt(|b| b.enable_io()).block_on(async {
+        sleep_until(Instant::now()).await;
+    });
+}
+
+#[test]
+#[should_panic(expected = "timers are disabled")]
+fn timeout_without_time_driver() {
+    rt(|b| b.enable_io()).block_on(async {
+        let _ = time::timeout(Duration::from_millis(1), async {}).await;
+    });
+}
+
+#[test]
+#[should_panic(expected = "IO is disabled")]
+fn connect_without_io_driver() {
+    let listener = std_listener();
+    let addr = listener.local_addr().unwrap();
+
+    rt(|b| b.enable_time()).block_on(async {
+        let _ = TcpStream::connect(addr).await;
+    });
+}
+
+#[test]
+#[should_panic(expected = "IO is disabled")]
+fn bind_without_io_driver() {
+    rt(|b| b).block_on(async {
+        let _ = TcpListener::bind("127.0.0.1:0").await;
+    });
+}
+
+#[test]
+fn enable_all_enables_both_drivers() {
+    let listener = std_listener();
+    let addr = listener.local_addr().unwrap();
+
+    rt(|b| b.enable_all()).block_on(async {
+        sleep(Duration::from_millis(1)).await;
+        TcpStream::connect(addr).await.unwrap();
+    });
+}
@@ -0,0 +1,77 @@
+use mini_runtime_v2::future::poll_fn;
+use mini_runtime_v2::net::TcpListener;
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::task;
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering::SeqCst};
+use std::task::Poll;
+
+/// Default number of polls after which the scheduler checks the drivers.
+const EVENT_INTERVAL: usize = 61;
+
+/// Accepts a connection while a task waking itself on every poll keeps the
+/// run queue from ever becoming empty, and returns the number of polls of
+/// that task before the connection was accepted.
+fn polls_before_accept(builder: &mut runtime::Builder) -> usize {
+    let rt = builder.enable_io().build().unwrap();
+
+    rt.block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let polls = Arc::new(AtomicUsize::new(0));
+        let done = Arc::new(AtomicBool::new(false));
+
+        let server = task::spawn({
+            let polls = polls.clone();
+            async move {
+                listener.accept().await.unwrap();
+                polls.load(SeqCst)
+            }
+        });
+        // Let the server wait for the socket to become readable.
+        task::yield_now().await;
+
+        let spinner = task::spawn({
+            let (polls, done) = (polls.clone(), done.clone());
+            poll_fn(move |cx| {
+                if done.load(SeqCst) {
+                    return Poll::Ready(());
+                }
+                let n = polls.fetch_add(1, SeqCst);
+                assert!(n < 1_000_000, "the ready socket was starved");
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            })
+        });
+
+        let _client = TcpStream::connect(addr).unwrap();
+
+        let polls = server.await.unwrap();
+        done.store(true, SeqCst);
+        spinner.await.unwrap();
+        polls
+    })
+}
+
+#[test]
+fn self_waking_task_does_not_starve_io() {
+    let polls = polls_before_accept(&mut runtime::Builder::new_current_thread());
+    assert!(
+        polls <= 2 * EVENT_INTERVAL,
+        "{polls} polls before accepting"
+    );
+}
+
+#[test]
+fn event_interval_is_configurable() {
+    let polls = polls_before_accept(runtime::Builder::new_current_thread().event_interval(5));
+    assert!(polls <= 2 * 5, "{polls} polls before accepting");
+}
+
+#[test]
+#[should_panic(expected = "Event interval cannot be set to 0")]
+fn zero_event_interval_panics() {
+    runtime::Builder::new_current_thread().event_interval(0);
+}
@@ -0,0 +1,166 @@
+use mini_runtime_v2::runtime::{self, Handle};
+use mini_runtime_v2::sync::mpsc;
+use mini_runtime_v2::time;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread;
+use std::time::{Duration, Instant};
+
+mod support;
+use support::rt;
+
+#[test]
+fn try_current_outside_runtime() {
+    let err = Handle::try_current().unwrap_err();
+    assert!(err.is_missing_context());
+    assert!(!err.is_thread_local_destroyed());
+    assert!(err.to_string().contains("there is no reactor running"));
+}
+
+#[test]
+fn try_current_inside_runtime() {
+    let rt = rt();
+
+    let n = rt.block_on(async {
+        let handle = Handle::try_current().unwrap();
+
+        // Also set while a task is polled.
+        let task = handle.spawn(async { Handle::try_current().is_ok() });
+        assert!(task.await.unwrap());
+
+        handle.spawn(async { 42 }).await.unwrap()
+    });
+    assert_eq!(n, 42);
+
+    // Only set while the runtime is entered.
+    assert!(Handle::try_current().unwrap_err().is_missing_context());
+}
+
+#[test]
+fn try_current_is_per_thread() {
+    rt().block_on(async {
+        // A thread spawned from within the runtime has no ambient runtime.
+        let res = thread::spawn(|| Handle::try_current().map(drop))
+            .join()
+            .unwrap();
+        assert!(res.unwrap_err().is_missing_context());
+    });
+}
+
+#[test]
+fn nested_block_on_panic_dumps_context() {
+    let outer = rt();
+    let inner = rt();
+
+    let msg = outer.block_on(async {
+        let err = panic::catch_unwind(AssertUnwindSafe(|| inner.block_on(async {}))).unwrap_err();
+        err.downcast::<String>().unwrap()
+    });
+
+    assert!(msg.contains("Cannot start a runtime from within a runtime"));
+    assert!(msg.contains("thread context:"));
+    assert!(msg.contains("runtime: entered (block_in_place allowed: false)"));
+    assert!(msg.contains("handle depth: 1"));
+    assert!(msg.contains("rng: seeded"));
+}
+
+#[test]
+fn enter_sets_current_until_dropped() {
+    let rt = rt();
+
+    let guard = rt.enter();
+    let task = Handle::current().spawn(async { 42 });
+    drop(guard);
+
+    assert!(Handle::try_current().unwrap_err().is_missing_context());
+    assert_eq!(rt.block_on(task).unwrap(), 42);
+}
+
+#[test]
+fn nested_enter_restores_previous() {
+    let a = rt();
+    let b = rt();
+
+    let guard_a = a.enter();
+    {
+        let _guard_b = b.enter();
+
+        // The innermost runtime is the current one.
+        Handle::current().spawn(async {});
+        assert_eq!(a.metrics().injection_queue_depth(), 0);
+        assert_eq!(b.metrics().injection_queue_depth(), 1);
+    }
+
+    Handle::current().spawn(async {});
+    assert_eq!(a.metrics().injection_queue_depth(), 1);
+    assert_eq!(b.metrics().injection_queue_depth(), 1);
+
+    drop(guard_a);
+    assert!(Handle::try_current().unwrap_err().is_missing_context());
+}
+
+#[test]
+fn enter_within_block_on() {
+    let a = rt();
+    let b = rt();
+
+    a.block_on(async {
+        {
+            let _guard = b.enter();
+            Handle::current().spawn(async {});
+        }
+        assert_eq!(b.metrics().injection_queue_depth(), 1);
+
+        // Back in the context of `a`.
+        Handle::current().spawn(async { 1 }).await.unwrap()
+    });
+}
+
+#[test]
+#[should_panic(expected = "`EnterGuard` values dropped out of order")]
+fn enter_guards_dropped_out_of_order() {
+    let a = rt();
+    let b = rt();
+
+    let guard_a = a.enter();
+    let _guard_b = b.enter();
+
+    drop(guard_a);
+}
+
+#[test]
+fn block_on_timeout_woken_by_driving_thread() {
+    let rt = runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap();
+    let handle = rt.handle().clone();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let waiter = thread::spawn(move || handle.block_on_timeout(rx.recv(), Duration::from_secs(10)));
+
+    // The timer fires on this thread, the send unparks the waiting one.
+    rt.block_on(async {
+        time::sleep(Duration::from_millis(10)).await;
+        tx.send(42).unwrap();
+    });
+
+    assert_eq!(waiter.join().unwrap(), Ok(Some(42)));
+}
+
+#[test]
+fn block_on_timeout_elapses_without_driving_thread() {
+    let rt = rt();
+    let handle = rt.handle().clone();
+
+    let start = Instant::now();
+    let res = thread::spawn(move || {
+        // Nobody drives the runtime: the task never runs.
+        let task = handle.spawn(async { 42 });
+        handle.block_on_timeout(task, Duration::from_millis(20))
+    })
+    .join()
+    .unwrap();
+
+    assert!(res.is_err());
+    assert!(start.elapsed() >= Duration::from_millis(20));
+}
@@ -0,0 +1,77 @@
+use mini_runtime_v2::future::poll_fn;
+use mini_runtime_v2::runtime::{self, Runtime};
+use mini_runtime_v2::sync::mpsc;
+use mini_runtime_v2::task;
+use std::future::Future;
+use std::task::Poll;
+
+fn rt(detect: bool) -> Runtime {
+    runtime::Builder::new_current_thread()
+        .detect_lost_wakeups(detect)
+        .build()
+        .unwrap()
+}
+
+/// Returns `Pending` without storing the waker: never woken.
+fn forgets_waker() -> impl Future<Output = ()> + Send {
+    poll_fn(|_cx| Poll::Pending)
+}
+
+#[test]
+fn reports_task_forgetting_its_waker() {
+    let rt = rt(true);
+    rt.block_on(async {
+        let handle = task::spawn(forgets_waker());
+        for _ in 0..3 {
+            task::yield_now().await;
+        }
+        handle.abort();
+    });
+
+    assert_eq!(rt.metrics().worker_lost_wakeup_count(0), 1);
+}
+
+#[test]
+fn well_behaved_tasks_are_not_reported() {
+    let rt = rt(true);
+    rt.block_on(async {
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let receiver = task::spawn(async move {
+            let mut sum = 0;
+            while let Some(n) = rx.recv().await {
+                sum += n;
+            }
+            sum
+        });
+
+        let sender = task::spawn(async move {
+            for n in 0..10 {
+                tx.send(n).await.unwrap();
+                task::yield_now().await;
+            }
+        });
+
+        // Awaits the `JoinHandle`s from a task too.
+        let joiner = task::spawn(async move {
+            sender.await.unwrap();
+            receiver.await.unwrap()
+        });
+
+        assert_eq!(joiner.await.unwrap(), 45);
+    });
+
+    assert_eq!(rt.metrics().worker_lost_wakeup_count(0), 0);
+}
+
+#[test]
+fn disabled_by_default() {
+    let rt = rt(false);
+    rt.block_on(async {
+        let handle = task::spawn(forgets_waker());
+        task::yield_now().await;
+        handle.abort();
+    });
+
+    assert_eq!(rt.metrics().worker_lost_wakeup_count(0), 0);
+}
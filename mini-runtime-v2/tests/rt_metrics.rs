@@ -0,0 +1,119 @@
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::task;
+use mini_runtime_v2::time::{self, Duration, Instant};
+use std::sync::Arc;
+use std::thread;
+
+mod support;
+use support::rt;
+
+#[test]
+fn worker_thread_id_follows_block_on_thread() {
+    let rt = Arc::new(rt());
+    assert_eq!(rt.metrics().worker_thread_id(0), None);
+
+    rt.block_on(async {});
+    let main_id = rt.metrics().worker_thread_id(0).unwrap();
+
+    // Stable for a given thread.
+    rt.block_on(async {});
+    assert_eq!(rt.metrics().worker_thread_id(0), Some(main_id));
+
+    let other_id = thread::spawn({
+        let rt = rt.clone();
+        move || {
+            rt.block_on(async {});
+            rt.metrics().worker_thread_id(0).unwrap()
+        }
+    })
+    .join()
+    .unwrap();
+    assert_ne!(other_id, main_id);
+    assert_eq!(rt.metrics().worker_thread_id(0), Some(other_id));
+}
+
+#[test]
+fn blocking_queue_depth_counts_waiting_functions() {
+    let rt = runtime::Builder::new_current_thread()
+        .max_blocking_threads(1)
+        .build()
+        .unwrap();
+    let (release, released) = std::sync::mpsc::channel::<()>();
+    assert_eq!(rt.metrics().blocking_queue_depth(), 0);
+
+    let handles = rt.block_on(async {
+        let first = task::spawn_blocking(move || released.recv().unwrap());
+        let second = task::spawn_blocking(|| {});
+        let third = task::spawn_blocking(|| {});
+        [first, second, third]
+    });
+
+    // The first function runs on the pool's only thread.
+    while rt.metrics().blocking_queue_depth() != 2 {
+        thread::sleep(std::time::Duration::from_millis(1));
+    }
+
+    release.send(()).unwrap();
+    rt.block_on(async {
+        for handle in handles {
+            handle.await.unwrap();
+        }
+    });
+    assert_eq!(rt.metrics().blocking_queue_depth(), 0);
+}
+
+/// Current time of the runtime's paused clock.
+fn now() -> Instant {
+    time::sleep(Duration::ZERO).deadline()
+}
+
+/// Runs `n` sleeps of 1 to `n` ms on a paused clock, returns the number of
+/// timers fired and of ticks.
+fn sleep_ticks(n: u64, resolution: Duration) -> (u64, u64) {
+    let rt = runtime::Builder::new_current_thread()
+        .start_paused(true)
+        .enable_time()
+        .timer_resolution(resolution)
+        .build()
+        .unwrap();
+
+    rt.block_on(async {
+        let start = now();
+        let sleeps: Vec<_> = (1..=n)
+            .map(|ms| {
+                task::spawn(async move {
+                    let duration = Duration::from_millis(ms);
+                    time::sleep(duration).await;
+
+                    // Never early, at most a resolution late.
+                    let elapsed = now() - start;
+                    assert!(elapsed >= duration, "{elapsed:?} < {duration:?}");
+                    assert!(elapsed <= duration + resolution);
+                })
+            })
+            .collect();
+        for sleep in sleeps {
+            sleep.await.unwrap();
+        }
+    });
+
+    let metrics = rt.metrics();
+    (metrics.timer_fired_count(), metrics.timer_tick_count())
+}
+
+#[test]
+fn timer_resolution_batches_timers() {
+    // A tick per deadline.
+    assert_eq!(sleep_ticks(20, Duration::ZERO), (20, 20));
+
+    // Rounded up to 10 and 20 ms.
+    assert_eq!(sleep_ticks(20, Duration::from_millis(10)), (20, 2));
+}
+
+#[test]
+fn timer_metrics_without_time_driver() {
+    let rt = rt();
+    rt.block_on(async {});
+    assert_eq!(rt.metrics().timer_fired_count(), 0);
+    assert_eq!(rt.metrics().timer_tick_count(), 0);
+}
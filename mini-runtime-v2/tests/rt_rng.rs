@@ -0,0 +1,38 @@
+use mini_runtime_v2::runtime;
+
+mod support;
+use support::rt;
+
+#[test]
+fn gen_range_stays_in_range() {
+    rt().block_on(async {
+        let rng = runtime::rng();
+        let mut seen = [false; 10];
+        for _ in 0..1000 {
+            let n = rng.gen_range(10..20);
+            assert!((10..20).contains(&n));
+            seen[(n - 10) as usize] = true;
+        }
+        assert!(seen.iter().all(|&seen| seen));
+
+        assert_eq!(rng.gen_range(7..8), 7);
+    });
+}
+
+#[test]
+fn shuffle_permutes() {
+    rt().block_on(async {
+        let mut values: Vec<u32> = (0..100).collect();
+        runtime::rng().shuffle(&mut values);
+        assert_ne!(values, (0..100).collect::<Vec<_>>());
+
+        values.sort();
+        assert_eq!(values, (0..100).collect::<Vec<_>>());
+    });
+}
+
+#[test]
+#[should_panic(expected = "there is no reactor running")]
+fn rng_outside_runtime_panics() {
+    runtime::rng();
+}
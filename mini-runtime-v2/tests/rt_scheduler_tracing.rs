@@ -0,0 +1,134 @@
+#![cfg(feature = "tracing")]
+
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::task;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Fields of a `scheduler tick` event.
+#[derive(Debug, Default, Clone, Copy)]
+struct Tick {
+    tick: u64,
+    tasks_polled: u64,
+    driver_checked: bool,
+}
+
+impl Visit for Tick {
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "tick" => self.tick = value,
+            "tasks_polled" => self.tasks_polled = value,
+            _ => {}
+        }
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        if field.name() == "driver_checked" {
+            self.driver_checked = value;
+        }
+    }
+
+    fn record_debug(&mut self, _: &Field, _: &dyn std::fmt::Debug) {}
+}
+
+/// Records the `scheduler tick` events.
+#[derive(Default, Clone)]
+struct Ticks(Arc<Mutex<Vec<Tick>>>);
+
+impl Subscriber for Ticks {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut is_tick = false;
+        event.record(&mut |field: &Field, value: &dyn std::fmt::Debug| {
+            is_tick |= field.name() == "message" && format!("{value:?}") == "scheduler tick";
+        });
+
+        if is_tick {
+            let mut tick = Tick::default();
+            event.record(&mut tick);
+            self.0.lock().unwrap().push(tick);
+        }
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+/// Runs three tasks yielding `yields` times each, and returns the ticks
+/// reported.
+fn ticks(builder: &mut runtime::Builder, yields: usize) -> Vec<Tick> {
+    let ticks = Ticks::default();
+    let rt = builder.build().unwrap();
+
+    tracing::subscriber::with_default(ticks.clone(), || {
+        rt.block_on(async {
+            let handles: Vec<_> = (0..3)
+                .map(|_| {
+                    task::spawn(async move {
+                        for _ in 0..yields {
+                            task::yield_now().await;
+                        }
+                    })
+                })
+                .collect();
+
+            for handle in handles {
+                handle.await.unwrap();
+            }
+        })
+    });
+
+    ticks.0.lock().unwrap().clone()
+}
+
+#[test]
+fn reports_every_task_poll() {
+    let ticks = ticks(
+        runtime::Builder::new_current_thread().enable_scheduler_tracing(true),
+        2,
+    );
+
+    assert!(!ticks.is_empty());
+    // Each task is polled once more than it yields.
+    assert_eq!(ticks.iter().map(|t| t.tasks_polled).sum::<u64>(), 9);
+    assert!(ticks.windows(2).all(|w| w[0].tick < w[1].tick));
+}
+
+#[test]
+fn driver_is_checked_after_event_interval_polls() {
+    let ticks = ticks(
+        runtime::Builder::new_current_thread()
+            .event_interval(2)
+            .enable_scheduler_tracing(true),
+        4,
+    );
+
+    assert!(ticks.iter().all(|t| t.tasks_polled <= 2));
+    // A full round always ends with a check of the drivers.
+    assert!(
+        ticks
+            .iter()
+            .filter(|t| t.tasks_polled == 2)
+            .all(|t| t.driver_checked)
+    );
+}
+
+#[test]
+fn disabled_by_default() {
+    let ticks = ticks(&mut runtime::Builder::new_current_thread(), 2);
+    assert!(ticks.is_empty());
+}
@@ -0,0 +1,225 @@
+mod support;
+
+use mini_runtime_v2::runtime;
+use mini_runtime_v2::sync::mpsc;
+use mini_runtime_v2::{spawn, task};
+use std::future;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+use std::time::{Duration, Instant};
+use support::rt;
+
+/// Counts its drops.
+struct Resource(Arc<AtomicUsize>);
+
+impl Drop for Resource {
+    fn drop(&mut self) {
+        self.0.fetch_add(1, SeqCst);
+    }
+}
+
+#[test]
+fn drop_cancels_idle_and_queued_tasks() {
+    let rt = rt();
+    let dropped = Arc::new(AtomicUsize::new(0));
+
+    let (tx, mut rx) = mpsc::channel::<()>(1);
+    rt.block_on(async {
+        // Waits on a channel: only referenced by the waker in the channel.
+        let resource = Resource(dropped.clone());
+        spawn(async move {
+            let _resource = resource;
+            rx.recv().await;
+        });
+
+        // Never polled: sits in the run queue.
+        let resource = Resource(dropped.clone());
+        spawn(async move {
+            let _resource = resource;
+            future::pending::<()>().await;
+        });
+    });
+
+    // From another thread, the task goes to the injection queue.
+    let resource = Resource(dropped.clone());
+    let handle = rt.handle().clone();
+    std::thread::spawn(move || {
+        handle.block_on(async move {
+            spawn(async move {
+                let _resource = resource;
+            });
+        });
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(dropped.load(SeqCst), 0);
+    drop(rt);
+    assert_eq!(dropped.load(SeqCst), 3);
+
+    // The channel's waker doesn't keep the cancelled task alive.
+    drop(tx);
+}
+
+#[test]
+fn join_handle_reports_cancellation_after_drop() {
+    let rt = rt();
+    let mut handle = None;
+    rt.block_on(async {
+        handle = Some(spawn(future::pending::<()>()));
+    });
+    let handle = handle.unwrap();
+
+    drop(rt);
+
+    assert!(handle.is_finished());
+    let err = support::rt().block_on(handle).unwrap_err();
+    assert!(err.is_cancelled());
+}
+
+#[test]
+fn spawn_after_shutdown_is_cancelled() {
+    let rt = rt();
+    let handle = rt.handle().clone();
+    drop(rt);
+
+    let dropped = Arc::new(AtomicUsize::new(0));
+    let resource = Resource(dropped.clone());
+    let join = handle.spawn(async move {
+        let _resource = resource;
+        panic!("spawned future polled after shutdown");
+    });
+
+    // Dropped without being polled.
+    assert_eq!(dropped.load(SeqCst), 1);
+    assert!(join.is_finished());
+    let err = self::rt().block_on(join).unwrap_err();
+    assert!(err.is_cancelled());
+}
+
+#[test]
+fn spawns_racing_with_shutdown_are_cancelled() {
+    const SPAWNS: usize = 100;
+
+    for _ in 0..20 {
+        let rt = rt();
+        let handle = rt.handle().clone();
+        let dropped = Arc::new(AtomicUsize::new(0));
+
+        let spawner = std::thread::spawn({
+            let dropped = dropped.clone();
+            move || {
+                (0..SPAWNS)
+                    .map(|_| {
+                        let resource = Resource(dropped.clone());
+                        handle.spawn(async move {
+                            let _resource = resource;
+                            future::pending::<()>().await;
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            }
+        });
+
+        drop(rt);
+        let joins = spawner.join().unwrap();
+
+        // Spawned before or after the runtime closed, no task is left
+        // behind.
+        assert_eq!(dropped.load(SeqCst), SPAWNS);
+        for join in joins {
+            assert!(join.is_finished());
+            let err = self::rt().block_on(join).unwrap_err();
+            assert!(err.is_cancelled());
+        }
+    }
+}
+
+#[test]
+fn spawn_from_destructor_during_shutdown() {
+    struct SpawnOnDrop(Arc<AtomicUsize>);
+
+    impl Drop for SpawnOnDrop {
+        fn drop(&mut self) {
+            let resource = Resource(self.0.clone());
+            let handle = spawn(async move {
+                let _resource = resource;
+            });
+            assert!(handle.is_finished());
+        }
+    }
+
+    let rt = rt();
+    let dropped = Arc::new(AtomicUsize::new(0));
+
+    rt.block_on(async {
+        let guard = SpawnOnDrop(dropped.clone());
+        spawn(async move {
+            let _guard = guard;
+            future::pending::<()>().await;
+        });
+        task::try_spawn(async {}).unwrap();
+    });
+
+    drop(rt);
+    assert_eq!(dropped.load(SeqCst), 1);
+}
+
+#[test]
+fn shutdown_timeout_waits_for_blocking_functions() {
+    let rt = rt();
+    let done = Arc::new(AtomicUsize::new(0));
+    let (started_tx, started) = std::sync::mpsc::channel();
+
+    rt.block_on(async {
+        let done = done.clone();
+        task::spawn_blocking(move || {
+            started_tx.send(()).unwrap();
+            std::thread::sleep(Duration::from_millis(50));
+            done.fetch_add(1, SeqCst);
+        });
+    });
+
+    // Only the functions already running are waited for.
+    started.recv().unwrap();
+    rt.shutdown_timeout(Duration::from_secs(10));
+    assert_eq!(done.load(SeqCst), 1);
+}
+
+#[test]
+fn shutdown_timeout_detaches_blocking_functions_and_cancels_queued_ones() {
+    let rt = runtime::Builder::new_current_thread()
+        .max_blocking_threads(1)
+        .build()
+        .unwrap();
+    let (release, released) = std::sync::mpsc::channel::<()>();
+    let (done_tx, done_rx) = std::sync::mpsc::channel();
+
+    let mut queued = None;
+    rt.block_on(async {
+        task::spawn_blocking(move || {
+            let _ = released.recv();
+            done_tx.send("running").unwrap();
+        });
+        queued = Some(task::spawn_blocking(|| {
+            panic!("queued blocking function ran")
+        }));
+    });
+    let queued = queued.unwrap();
+
+    // The pool's only thread runs the first function, the second waits.
+    while rt.metrics().blocking_queue_depth() != 1 {
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let start = Instant::now();
+    rt.shutdown_timeout(Duration::from_millis(10));
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let err = support::rt().block_on(queued).unwrap_err();
+    assert!(err.is_cancelled());
+
+    // The detached thread completes the running function.
+    release.send(()).unwrap();
+    assert_eq!(done_rx.recv().unwrap(), "running");
+}
@@ -0,0 +1,78 @@
+use mini_runtime_v2::runtime::{self, Runtime};
+use std::thread;
+use std::time::Duration;
+
+fn rt(max_pending_spawns: usize) -> Runtime {
+    runtime::Builder::new_current_thread()
+        .max_pending_spawns(max_pending_spawns)
+        .build()
+        .unwrap()
+}
+
+/// No thread drives the runtime: the spawns stay in the injection queue
+/// until `block_on` is called.
+#[test]
+fn try_spawn_rejects_when_full() {
+    let rt = rt(2);
+    let handle = rt.handle().clone();
+
+    let first = handle.try_spawn(async { 1 }).unwrap();
+    let second = handle.try_spawn(async { 2 }).unwrap();
+    assert_eq!(rt.metrics().injection_queue_depth(), 2);
+
+    // The future is given back.
+    let err = handle.try_spawn(async { 3 }).unwrap_err();
+    assert_eq!(rt.metrics().injection_queue_depth(), 2);
+    let third = err.into_future();
+
+    // Driving the runtime drains the queue.
+    let (a, b) = rt.block_on(async { (first.await.unwrap(), second.await.unwrap()) });
+    assert_eq!((a, b), (1, 2));
+    assert_eq!(rt.metrics().injection_queue_depth(), 0);
+
+    let third = handle.try_spawn(third).unwrap();
+    assert_eq!(rt.block_on(third).unwrap(), 3);
+}
+
+#[test]
+fn blocked_spawn_resumes_once_drained() {
+    let rt = rt(1);
+    let handle = rt.handle().clone();
+
+    let first = handle.spawn(async { 1 });
+
+    let spawner = thread::spawn(move || handle.spawn(async { 2 }));
+    // The queue is full: the spawner waits for the runtime to poll.
+    thread::sleep(Duration::from_millis(50));
+    assert!(!spawner.is_finished());
+    assert_eq!(rt.metrics().injection_queue_depth(), 1);
+
+    assert_eq!(rt.block_on(first).unwrap(), 1);
+
+    let second = spawner.join().unwrap();
+    assert_eq!(rt.block_on(second).unwrap(), 2);
+}
+
+/// The tasks spawned from within the runtime are not bounded.
+#[test]
+fn local_spawns_are_not_bounded() {
+    let rt = rt(1);
+
+    let sum = rt.block_on(async {
+        let handles: Vec<_> = (0..10)
+            .map(|i| mini_runtime_v2::spawn(async move { i }))
+            .collect();
+        let mut sum = 0;
+        for handle in handles {
+            sum += handle.await.unwrap();
+        }
+        sum
+    });
+    assert_eq!(sum, 45);
+}
+
+#[test]
+#[should_panic(expected = "Max pending spawns cannot be set to 0")]
+fn max_pending_spawns_zero_panics() {
+    runtime::Builder::new_current_thread().max_pending_spawns(0);
+}
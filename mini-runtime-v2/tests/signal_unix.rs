@@ -0,0 +1,58 @@
+#![cfg(unix)]
+
+use mini_runtime_v2::runtime::{self, Runtime};
+use mini_runtime_v2::signal::unix::{SignalKind, signal};
+use std::thread;
+use std::time::Duration;
+
+fn rt() -> Runtime {
+    runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn recv_raised_signal() {
+    rt().block_on(async {
+        let mut sig = signal(SignalKind::user_defined1()).unwrap();
+
+        assert_eq!(unsafe { libc::raise(libc::SIGUSR1) }, 0);
+        assert_eq!(sig.recv().await, Some(()));
+    });
+}
+
+#[test]
+fn recv_returns_none_after_runtime_shutdown() {
+    let mut sig = rt().block_on(async { signal(SignalKind::user_defined2()).unwrap() });
+
+    // The runtime is gone: the listener is closed, even when polled from
+    // another runtime.
+    rt().block_on(async {
+        assert_eq!(sig.recv().await, None);
+    });
+}
+
+/// A listener waiting on another runtime is woken up when the runtime it was
+/// created on shuts down.
+#[test]
+fn waiting_recv_returns_none_on_shutdown() {
+    let origin = rt();
+    let mut sig = origin.block_on(async { signal(SignalKind::user_defined2()).unwrap() });
+
+    let waiter = thread::spawn(move || rt().block_on(sig.recv()));
+    // Give the waiter time to register its waker.
+    thread::sleep(Duration::from_millis(50));
+
+    drop(origin);
+    assert_eq!(waiter.join().unwrap(), None);
+}
+
+#[test]
+#[should_panic(expected = "IO is disabled")]
+fn signal_panics_without_io() {
+    let rt = runtime::Builder::new_current_thread().build().unwrap();
+    rt.block_on(async {
+        let _ = signal(SignalKind::user_defined1());
+    });
+}
@@ -0,0 +1,97 @@
+use mini_runtime_v2::stream::{Stream, StreamExt};
+use mini_runtime_v2::sync::mpsc;
+use mini_runtime_v2::task;
+use mini_runtime_v2::time::{self, Duration};
+
+mod support;
+use support::rt_paused;
+
+/// Returns a receiver of the `values`, which ends after them.
+fn stream_of(values: impl IntoIterator<Item = i32>) -> mpsc::UnboundedReceiver<i32> {
+    let (tx, rx) = mpsc::unbounded_channel();
+    for value in values {
+        tx.send(value).unwrap();
+    }
+    rx
+}
+
+#[test]
+fn next_until_the_end() {
+    rt_paused().block_on(async {
+        let mut rx = stream_of([1, 2]);
+
+        assert_eq!(rx.next().await, Some(1));
+        assert_eq!(rx.next().await, Some(2));
+        assert_eq!(rx.next().await, None);
+    });
+}
+
+#[test]
+fn filter_map_take() {
+    rt_paused().block_on(async {
+        let mut evens = stream_of(0..10)
+            .filter(|n| n % 2 == 0)
+            .map(|n| n * 10)
+            .take(3);
+
+        let mut out = Vec::new();
+        while let Some(n) = evens.next().await {
+            out.push(n);
+        }
+        assert_eq!(out, [0, 20, 40]);
+    });
+}
+
+/// `take` ends once `n` values were yielded, even if the stream doesn't.
+#[test]
+fn take_ends_an_endless_stream() {
+    rt_paused().block_on(async {
+        let (tx, rx) = mpsc::unbounded_channel();
+        tx.send(1).unwrap();
+        tx.send(2).unwrap();
+
+        let mut first = rx.take(1);
+        assert_eq!(first.size_hint(), (0, Some(1)));
+        assert_eq!(first.next().await, Some(1));
+        assert_eq!(first.next().await, None);
+        assert_eq!(first.size_hint(), (0, Some(0)));
+
+        // The sender is still open.
+        drop(tx);
+    });
+}
+
+#[test]
+fn take_ends_with_the_stream() {
+    rt_paused().block_on(async {
+        let mut rx = stream_of([1]).take(5);
+
+        assert_eq!(rx.next().await, Some(1));
+        assert_eq!(rx.next().await, None);
+        assert_eq!(rx.size_hint(), (0, Some(0)));
+    });
+}
+
+/// Each value gets its own deadline: the stream reports every period without
+/// a value, and keeps yielding the values sent afterwards.
+#[test]
+fn timeout_per_item() {
+    rt_paused().block_on(async {
+        let (tx, rx) = mpsc::unbounded_channel();
+        task::spawn(async move {
+            time::sleep(Duration::from_millis(10)).await;
+            tx.send(1).unwrap();
+            time::sleep(Duration::from_millis(250)).await;
+            tx.send(2).unwrap();
+        });
+
+        let mut rx = rx.timeout(Duration::from_millis(100));
+        let mut out = Vec::new();
+        while let Some(res) = rx.next().await {
+            out.push(res.ok());
+        }
+
+        // 1 at 10ms, timeouts at 110ms and 210ms, 2 at 260ms.
+        assert_eq!(out, [Some(1), None, None, Some(2)]);
+    });
+}
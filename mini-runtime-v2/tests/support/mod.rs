@@ -0,0 +1,23 @@
+//! Helpers shared by the integration tests.
+
+// Each test file builds its own copy and uses only some of the helpers.
+#![allow(dead_code)]
+
+use mini_runtime_v2::runtime::{self, Runtime};
+
+/// Builds a current_thread runtime with all the drivers enabled.
+pub fn rt() -> Runtime {
+    runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+/// Builds a current_thread runtime whose clock starts paused.
+pub fn rt_paused() -> Runtime {
+    runtime::Builder::new_current_thread()
+        .enable_time()
+        .start_paused(true)
+        .build()
+        .unwrap()
+}
@@ -0,0 +1,89 @@
+use mini_runtime_v2::future::poll_fn;
+use mini_runtime_v2::sync::Barrier;
+use mini_runtime_v2::task;
+use std::future::Future;
+use std::pin::pin;
+use std::sync::Arc;
+use std::task::Poll;
+
+mod support;
+use support::rt;
+
+#[test]
+fn releases_all_tasks_with_one_leader() {
+    rt().block_on(async {
+        let barrier = Arc::new(Barrier::new(3));
+
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let barrier = barrier.clone();
+                task::spawn(async move { barrier.wait().await.is_leader() })
+            })
+            .collect();
+
+        task::yield_now().await;
+        assert!(handles.iter().all(|handle| !handle.is_finished()));
+
+        // The last task to arrive releases the others.
+        assert!(barrier.wait().await.is_leader());
+        for handle in handles {
+            assert!(!handle.await.unwrap());
+        }
+    });
+}
+
+#[test]
+fn barrier_is_reusable() {
+    rt().block_on(async {
+        let barrier = Arc::new(Barrier::new(2));
+
+        let other = task::spawn({
+            let barrier = barrier.clone();
+            async move {
+                let mut leaders = 0;
+                for _ in 0..3 {
+                    leaders += barrier.wait().await.is_leader() as usize;
+                }
+                leaders
+            }
+        });
+
+        let mut leaders = 0;
+        for _ in 0..3 {
+            leaders += barrier.wait().await.is_leader() as usize;
+        }
+        leaders += other.await.unwrap();
+
+        // One leader per generation.
+        assert_eq!(leaders, 3);
+    });
+}
+
+#[test]
+fn dropped_wait_no_longer_counts() {
+    rt().block_on(async {
+        let barrier = Barrier::new(2);
+
+        {
+            let mut wait = pin!(barrier.wait());
+            let polled = poll_fn(|cx| Poll::Ready(wait.as_mut().poll(cx).is_pending())).await;
+            assert!(polled);
+        }
+
+        // Without the withdrawal, this task would be the leader.
+        let mut wait = pin!(barrier.wait());
+        let polled = poll_fn(|cx| Poll::Ready(wait.as_mut().poll(cx).is_pending())).await;
+        assert!(polled);
+    });
+}
+
+#[test]
+fn barrier_of_zero_or_one_never_waits() {
+    rt().block_on(async {
+        for n in [0, 1] {
+            let barrier = Barrier::new(n);
+            assert!(barrier.wait().await.is_leader());
+            assert!(barrier.wait().await.is_leader());
+        }
+    });
+}
@@ -0,0 +1,80 @@
+use mini_runtime_v2::sync::broadcast::{
+    self,
+    error::{RecvError, SendError, TryRecvError},
+};
+use mini_runtime_v2::task;
+
+mod support;
+use support::rt;
+
+#[test]
+fn every_receiver_sees_every_value() {
+    let (tx, mut rx1) = broadcast::channel(4);
+    let mut rx2 = tx.subscribe();
+
+    assert_eq!(tx.send(1).unwrap(), 2);
+    assert_eq!(tx.send(2).unwrap(), 2);
+
+    for rx in [&mut rx1, &mut rx2] {
+        assert_eq!(rx.try_recv(), Ok(1));
+        assert_eq!(rx.try_recv(), Ok(2));
+        assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+    }
+
+    // A new receiver only sees the values sent after it subscribed.
+    let mut rx3 = tx.subscribe();
+    assert_eq!(rx3.try_recv(), Err(TryRecvError::Empty));
+    tx.send(3).unwrap();
+    assert_eq!(rx3.try_recv(), Ok(3));
+}
+
+#[test]
+fn slow_receiver_lags() {
+    let (tx, mut rx) = broadcast::channel(2);
+
+    for i in 0..5 {
+        tx.send(i).unwrap();
+    }
+
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Lagged(3)));
+    assert_eq!(rx.try_recv(), Ok(3));
+    assert_eq!(rx.try_recv(), Ok(4));
+    assert_eq!(rx.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn send_without_receivers_fails() {
+    let (tx, rx) = broadcast::channel(1);
+    drop(rx);
+
+    assert_eq!(tx.receiver_count(), 0);
+    assert_eq!(tx.send("lost"), Err(SendError("lost")));
+}
+
+#[test]
+fn recv_wakes_on_send_and_close() {
+    rt().block_on(async {
+        let (tx, mut rx) = broadcast::channel(4);
+
+        let handle = task::spawn(async move {
+            let mut received = Vec::new();
+            loop {
+                match rx.recv().await {
+                    Ok(value) => received.push(value),
+                    Err(RecvError::Closed) => return received,
+                    Err(RecvError::Lagged(n)) => panic!("lagged by {n}"),
+                }
+            }
+        });
+
+        // Let the receiver wait first.
+        task::spawn(async {}).await.unwrap();
+        tx.send(1).unwrap();
+        let tx2 = tx.clone();
+        drop(tx);
+        tx2.send(2).unwrap();
+        drop(tx2);
+
+        assert_eq!(handle.await.unwrap(), vec![1, 2]);
+    });
+}
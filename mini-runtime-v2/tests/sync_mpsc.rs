@@ -0,0 +1,327 @@
+use mini_runtime_v2::sync::mpsc::{self, error::TrySendError};
+use mini_runtime_v2::task;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+use std::thread;
+
+mod support;
+use support::rt;
+
+const MESSAGES: usize = 1_000;
+
+/// A message holding memory, which counts the live messages.
+struct Payload {
+    _data: Vec<u8>,
+    live: Arc<Live>,
+}
+
+#[derive(Default)]
+struct Live {
+    current: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl Payload {
+    fn new(live: &Arc<Live>) -> Payload {
+        let current = live.current.fetch_add(1, SeqCst) + 1;
+        live.peak.fetch_max(current, SeqCst);
+        Payload {
+            _data: vec![0; 1024],
+            live: live.clone(),
+        }
+    }
+}
+
+impl Drop for Payload {
+    fn drop(&mut self) {
+        self.live.current.fetch_sub(1, SeqCst);
+    }
+}
+
+/// The consumer is slower than the producer. The bounded channel makes the
+/// producer wait: the memory held stays under the capacity. The unbounded
+/// channel buffers the backlog, which grows with the number of messages.
+#[test]
+fn slow_consumer_memory_bounded_vs_unbounded() {
+    let capacity = 8;
+
+    let bounded = Arc::new(Live::default());
+    rt().block_on({
+        let live = bounded.clone();
+        async move {
+            let (tx, mut rx) = mpsc::channel(capacity);
+            let producer = task::spawn(async move {
+                for _ in 0..MESSAGES {
+                    tx.send(Payload::new(&live))
+                        .await
+                        .unwrap_or_else(|_| panic!());
+                }
+            });
+            while let Some(payload) = rx.recv().await {
+                drop(payload);
+                task::yield_now().await;
+            }
+            producer.await.unwrap();
+        }
+    });
+
+    let unbounded = Arc::new(Live::default());
+    rt().block_on({
+        let live = unbounded.clone();
+        async move {
+            let (tx, mut rx) = mpsc::unbounded_channel();
+            let producer = task::spawn(async move {
+                for _ in 0..MESSAGES {
+                    tx.send(Payload::new(&live)).unwrap_or_else(|_| panic!());
+                    task::yield_now().await;
+                }
+            });
+            while let Some(payload) = rx.recv().await {
+                drop(payload);
+                task::yield_now().await;
+                task::yield_now().await;
+            }
+            producer.await.unwrap();
+        }
+    });
+
+    // The queue, plus the message being created.
+    let bounded_peak = bounded.peak.load(SeqCst);
+    assert!(bounded_peak <= capacity + 1, "bounded peak: {bounded_peak}");
+
+    // The backlog grows with the number of messages.
+    let unbounded_peak = unbounded.peak.load(SeqCst);
+    assert!(
+        unbounded_peak > MESSAGES / 4,
+        "unbounded peak: {unbounded_peak}"
+    );
+
+    assert_eq!(bounded.current.load(SeqCst), 0);
+    assert_eq!(unbounded.current.load(SeqCst), 0);
+}
+
+#[test]
+fn unbounded_many_threads_send() {
+    const THREADS: usize = 4;
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let senders: Vec<_> = (0..THREADS)
+        .map(|t| {
+            let tx = tx.clone();
+            thread::spawn(move || {
+                for i in 0..MESSAGES {
+                    tx.send((t, i)).unwrap();
+                }
+            })
+        })
+        .collect();
+    drop(tx);
+
+    let received = rt().block_on(async move {
+        let mut next = [0; THREADS];
+        while let Some((t, i)) = rx.recv().await {
+            // The values of a sender are received in order.
+            assert_eq!(i, next[t]);
+            next[t] += 1;
+        }
+        next
+    });
+
+    for sender in senders {
+        sender.join().unwrap();
+    }
+    assert_eq!(received, [MESSAGES; THREADS]);
+}
+
+#[test]
+fn dropping_receiver_drops_queued_values() {
+    let live = Arc::new(Live::default());
+    let (tx, rx) = mpsc::unbounded_channel();
+
+    for _ in 0..3 {
+        tx.send(Payload::new(&live)).unwrap_or_else(|_| panic!());
+    }
+    assert_eq!(live.current.load(SeqCst), 3);
+
+    drop(rx);
+    assert_eq!(live.current.load(SeqCst), 0);
+    assert!(tx.send(Payload::new(&live)).is_err());
+}
+
+#[test]
+fn closed_completes_when_receiver_dropped() {
+    rt().block_on(async {
+        let (tx, rx) = mpsc::channel::<u32>(1);
+        let tx2 = tx.clone();
+
+        let waiting = task::spawn(async move { tx2.closed().await });
+        task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        drop(rx);
+        waiting.await.unwrap();
+
+        // Already closed: completes right away.
+        tx.closed().await;
+    });
+}
+
+#[test]
+fn closed_completes_on_close() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::unbounded_channel::<u32>();
+
+        let waiting = task::spawn(async move { tx.closed().await });
+        task::yield_now().await;
+        assert!(!waiting.is_finished());
+
+        rx.close();
+        waiting.await.unwrap();
+    });
+}
+
+/// The messages sent before `close` are still received, then `None`; the
+/// senders waiting for capacity fail, with their message given back.
+#[test]
+fn close_receives_messages_in_flight() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::channel(2);
+        tx.send(1).await.unwrap();
+        tx.send(2).await.unwrap();
+
+        let blocked = task::spawn({
+            let tx = tx.clone();
+            async move { tx.send(3).await }
+        });
+        task::yield_now().await;
+        assert!(!blocked.is_finished());
+
+        rx.close();
+        assert_eq!(blocked.await.unwrap().unwrap_err().0, 3);
+        assert!(tx.send(4).await.is_err());
+        assert!(tx.is_closed());
+
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(rx.recv().await, None);
+    });
+}
+
+/// A producer selecting on `closed` stops as soon as the consumer leaves,
+/// while it is busy producing the next message.
+#[test]
+fn producer_stops_on_closed() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let producer = task::spawn(async move {
+            tx.send(0).await.unwrap();
+
+            // Producing the next message takes forever.
+            mini_runtime_v2::select! {
+                _ = std::future::pending::<()>() => unreachable!(),
+                _ = tx.closed() => "stopped",
+            }
+        });
+
+        assert_eq!(rx.recv().await, Some(0));
+        drop(rx);
+        assert_eq!(producer.await.unwrap(), "stopped");
+    });
+}
+
+#[test]
+fn try_send_full_and_closed() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::channel(1);
+
+        tx.try_send(1).unwrap();
+        let err = tx.try_send(2).unwrap_err();
+        assert!(matches!(err, TrySendError::Full(2)));
+        assert_eq!(tx.capacity(), 0);
+
+        // Receiving gives the capacity back.
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(tx.capacity(), 1);
+        tx.try_send(3).unwrap();
+
+        drop(rx);
+        let err = tx.try_send(4).unwrap_err();
+        assert!(matches!(err, TrySendError::Closed(4)));
+    });
+}
+
+#[test]
+fn reserve_holds_capacity() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::channel(1);
+
+        let permit = tx.reserve().await.unwrap();
+        assert_eq!(tx.capacity(), 0);
+        assert!(matches!(tx.try_send(123), Err(TrySendError::Full(123))));
+
+        permit.send(456);
+        assert_eq!(rx.recv().await, Some(456));
+
+        // A dropped permit gives the capacity back.
+        let permit = tx.reserve().await.unwrap();
+        drop(permit);
+        assert_eq!(tx.capacity(), 1);
+    });
+}
+
+#[test]
+fn reserve_waits_for_capacity() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::channel(1);
+        tx.send(1).await.unwrap();
+
+        let reserving = task::spawn({
+            let tx = tx.clone();
+            async move {
+                let permit = tx.reserve().await.unwrap();
+                permit.send(2);
+            }
+        });
+        task::yield_now().await;
+        assert!(!reserving.is_finished());
+
+        assert_eq!(rx.recv().await, Some(1));
+        reserving.await.unwrap();
+        assert_eq!(rx.recv().await, Some(2));
+
+        drop(rx);
+        assert!(tx.reserve().await.is_err());
+    });
+}
+
+/// The senders waiting for capacity get it in the order they started
+/// waiting, and a sender that did not wait can't take it from them.
+#[test]
+fn waiting_senders_are_served_in_order() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::channel(1);
+        tx.send(0).await.unwrap();
+
+        let senders: Vec<_> = (1..=3)
+            .map(|i| {
+                let tx = tx.clone();
+                task::spawn(async move { tx.send(i).await.unwrap() })
+            })
+            .collect();
+        task::yield_now().await;
+
+        for i in 0..3 {
+            assert_eq!(rx.recv().await, Some(i));
+            // The capacity released is set aside for the oldest waiter.
+            assert!(matches!(tx.try_send(99), Err(TrySendError::Full(99))));
+        }
+        assert_eq!(rx.recv().await, Some(3));
+
+        for sender in senders {
+            sender.await.unwrap();
+        }
+        tx.try_send(4).unwrap();
+        assert_eq!(rx.recv().await, Some(4));
+    });
+}
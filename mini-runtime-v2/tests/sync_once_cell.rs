@@ -0,0 +1,88 @@
+use mini_runtime_v2::future::poll_fn;
+use mini_runtime_v2::sync::{OnceCell, SetError};
+use mini_runtime_v2::task;
+use std::future::{self, Future};
+use std::pin::pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering::SeqCst};
+use std::task::Poll;
+
+mod support;
+use support::rt;
+
+#[test]
+fn concurrent_initializers_run_once() {
+    rt().block_on(async {
+        let cell = Arc::new(OnceCell::new());
+        let runs = Arc::new(AtomicUsize::new(0));
+
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let (cell, runs) = (cell.clone(), runs.clone());
+                task::spawn(async move {
+                    *cell
+                        .get_or_init(|| async {
+                            runs.fetch_add(1, SeqCst);
+                            // Let the other tasks try meanwhile.
+                            task::yield_now().await;
+                            i
+                        })
+                        .await
+                })
+            })
+            .collect();
+
+        let mut values = Vec::new();
+        for handle in handles {
+            values.push(handle.await.unwrap());
+        }
+
+        assert_eq!(runs.load(SeqCst), 1);
+        assert!(values.iter().all(|&value| value == values[0]));
+        assert_eq!(cell.get(), Some(&values[0]));
+    });
+}
+
+#[test]
+fn failed_or_cancelled_initializer_leaves_cell_empty() {
+    rt().block_on(async {
+        let cell = OnceCell::new();
+
+        let err = cell.get_or_try_init(|| async { Err("unavailable") }).await;
+        assert_eq!(err, Err("unavailable"));
+        assert!(!cell.initialized());
+
+        {
+            let mut init = pin!(cell.get_or_init(future::pending));
+            let pending = poll_fn(|cx| Poll::Ready(init.as_mut().poll(cx).is_pending())).await;
+            assert!(pending);
+
+            // The running initializer holds the cell.
+            assert_eq!(cell.set(1), Err(SetError::InitializingError(1)));
+        }
+
+        assert_eq!(*cell.get_or_init(|| async { 2 }).await, 2);
+        assert_eq!(cell.set(3), Err(SetError::AlreadyInitializedError(3)));
+    });
+}
+
+#[test]
+fn static_cell() {
+    static CELL: OnceCell<String> = OnceCell::const_new();
+
+    rt().block_on(async {
+        let value = CELL.get_or_init(|| async { "value".to_string() }).await;
+        assert_eq!(value, "value");
+    });
+    assert_eq!(CELL.get().map(String::as_str), Some("value"));
+}
+
+#[test]
+fn take_and_into_inner() {
+    let mut cell = OnceCell::from(1);
+    assert_eq!(cell.take(), Some(1));
+    assert_eq!(cell.get(), None);
+
+    cell.set(2).unwrap();
+    assert_eq!(cell.into_inner(), Some(2));
+}
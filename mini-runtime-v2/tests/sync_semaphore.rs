@@ -0,0 +1,98 @@
+use mini_runtime_v2::future::poll_fn;
+use mini_runtime_v2::sync::{Semaphore, TryAcquireError, mpsc};
+use mini_runtime_v2::task;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+
+mod support;
+use support::rt;
+
+/// Polls `fut` once, returns `true` if it is pending.
+async fn is_pending<F: Future + Unpin>(fut: &mut F) -> bool {
+    poll_fn(|cx| Poll::Ready(Pin::new(&mut *fut).poll(cx).is_pending())).await
+}
+
+#[test]
+fn try_acquire_and_release() {
+    let sem = Semaphore::new(2);
+
+    let first = sem.try_acquire().unwrap();
+    let second = sem.try_acquire().unwrap();
+    assert_eq!(sem.available_permits(), 0);
+    assert_eq!(sem.try_acquire().unwrap_err(), TryAcquireError::NoPermits);
+
+    drop(first);
+    assert_eq!(sem.available_permits(), 1);
+
+    second.forget();
+    assert_eq!(sem.available_permits(), 1);
+}
+
+#[test]
+fn waiters_are_woken_in_order() {
+    rt().block_on(async {
+        let sem = Arc::new(Semaphore::new(1));
+        let permit = sem.clone().acquire_owned().await.unwrap();
+        let (tx, mut rx) = mpsc::channel(3);
+
+        for i in 0..3 {
+            let (sem, tx) = (sem.clone(), tx.clone());
+            task::spawn(async move {
+                let _permit = sem.acquire().await.unwrap();
+                tx.try_send(i).unwrap();
+                task::yield_now().await;
+            });
+        }
+        // Let the tasks queue up.
+        task::yield_now().await;
+
+        drop(permit);
+        for i in 0..3 {
+            assert_eq!(rx.recv().await, Some(i));
+        }
+    });
+}
+
+#[test]
+fn dropped_acquire_passes_on_wakeup() {
+    rt().block_on(async {
+        let sem = Arc::new(Semaphore::new(0));
+
+        let mut first = Box::pin(sem.acquire());
+        assert!(is_pending(&mut first).await);
+
+        let second = task::spawn({
+            let sem = sem.clone();
+            async move { sem.acquire().await.unwrap().forget() }
+        });
+        // Let `second` queue up behind `first`.
+        task::yield_now().await;
+
+        // Wakes `first`, which gives up: the wakeup goes to `second`.
+        sem.add_permits(1);
+        drop(first);
+        task::yield_now().await;
+
+        assert!(second.is_finished());
+        assert_eq!(sem.available_permits(), 0);
+    });
+}
+
+#[test]
+fn close_fails_waiters() {
+    rt().block_on(async {
+        let sem = Arc::new(Semaphore::new(0));
+
+        let waiter = task::spawn({
+            let sem = sem.clone();
+            async move { sem.acquire().await.map(drop) }
+        });
+        task::yield_now().await;
+
+        sem.close();
+        assert!(waiter.await.unwrap().is_err());
+        assert_eq!(sem.try_acquire().unwrap_err(), TryAcquireError::Closed);
+    });
+}
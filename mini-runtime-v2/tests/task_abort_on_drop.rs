@@ -0,0 +1,129 @@
+use mini_runtime_v2::sync::mpsc;
+use mini_runtime_v2::task::{self, AbortOnDropHandle};
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+mod support;
+use support::rt;
+
+/// Sends a message when dropped, i.e. when the task owning it is done.
+struct OnDrop(mpsc::Sender<&'static str>);
+
+impl Drop for OnDrop {
+    fn drop(&mut self) {
+        let _ = self.0.try_send("dropped");
+    }
+}
+
+#[test]
+fn dropped_join_handle_detaches() {
+    rt().block_on(async {
+        let (go_tx, mut go_rx) = mpsc::channel::<()>(4);
+        let (tx, mut rx) = mpsc::channel(4);
+
+        let handle = task::spawn(async move {
+            let _on_drop = OnDrop(tx.clone());
+            go_rx.recv().await;
+            tx.try_send("finished").unwrap();
+        });
+        drop(handle);
+
+        // The task keeps running and completes.
+        go_tx.try_send(()).unwrap();
+        assert_eq!(rx.recv().await, Some("finished"));
+        assert_eq!(rx.recv().await, Some("dropped"));
+    });
+}
+
+#[test]
+fn dropped_abort_on_drop_handle_aborts() {
+    rt().block_on(async {
+        let (go_tx, mut go_rx) = mpsc::channel::<()>(4);
+        let (tx, mut rx) = mpsc::channel(4);
+
+        let handle = task::spawn(async move {
+            let _on_drop = OnDrop(tx.clone());
+            tx.try_send("started").unwrap();
+            go_rx.recv().await;
+            tx.try_send("finished").unwrap();
+        })
+        .abort_on_drop();
+        assert_eq!(rx.recv().await, Some("started"));
+        drop(handle);
+
+        // The future is dropped before it could finish, with its senders.
+        assert_eq!(rx.recv().await, Some("dropped"));
+        assert_eq!(rx.recv().await, None);
+        drop(go_tx);
+    });
+}
+
+#[test]
+fn abort_on_drop_handle_returns_output() {
+    rt().block_on(async {
+        let handle = AbortOnDropHandle::new(task::spawn(async { 42 }));
+        assert_eq!(handle.await.unwrap(), 42);
+
+        let handle = task::spawn(std::future::pending::<()>()).abort_on_drop();
+        handle.abort();
+        assert!(handle.await.unwrap_err().is_cancelled());
+    });
+}
+
+#[test]
+fn output_is_dropped_with_the_join_handle() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::channel(4);
+
+        // Detached: nobody reads the output, it is dropped on completion.
+        let output = OnDrop(tx.clone());
+        drop(task::spawn(async move { output }));
+        assert_eq!(rx.recv().await, Some("dropped"));
+
+        // Joined: the output is kept for the `JoinHandle` until it goes.
+        let output = OnDrop(tx.clone());
+        let handle = task::spawn(async move {
+            let output = output;
+            tx.try_send("finished").unwrap();
+            output
+        });
+        assert_eq!(rx.recv().await, Some("finished"));
+        assert!(handle.is_finished());
+        drop(handle);
+        assert_eq!(rx.recv().await, Some("dropped"));
+        assert_eq!(rx.recv().await, None);
+    });
+}
+
+/// Completes on the first poll, and panics when dropped.
+struct PanicOnDrop;
+
+impl Future for PanicOnDrop {
+    type Output = u32;
+
+    fn poll(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<u32> {
+        Poll::Ready(42)
+    }
+}
+
+impl Drop for PanicOnDrop {
+    fn drop(&mut self) {
+        panic!("future dropped");
+    }
+}
+
+#[test]
+fn panic_dropping_completed_future_is_reported() {
+    rt().block_on(async {
+        let err = task::spawn(PanicOnDrop).await.unwrap_err();
+        assert!(err.is_panic());
+        assert_eq!(
+            *err.into_panic().downcast::<&str>().unwrap(),
+            "future dropped"
+        );
+
+        // The scheduler is unaffected.
+        assert_eq!(task::spawn(async { 1 }).await.unwrap(), 1);
+    });
+}
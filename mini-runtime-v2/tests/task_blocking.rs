@@ -0,0 +1,32 @@
+mod support;
+
+use mini_runtime_v2::task;
+use support::rt;
+
+#[test]
+#[should_panic(expected = "can call blocking only when running on the multi-threaded runtime")]
+fn block_in_place_panics_in_block_on() {
+    rt().block_on(async {
+        task::block_in_place(|| {});
+    });
+}
+
+#[test]
+fn block_in_place_panics_in_a_task() {
+    let err = rt()
+        .block_on(async { task::spawn(async { task::block_in_place(|| {}) }).await })
+        .unwrap_err();
+    assert!(err.is_panic());
+}
+
+#[test]
+fn block_in_place_runs_outside_of_the_runtime() {
+    let mut value = 0;
+    task::block_in_place(|| value += 1);
+    assert_eq!(value, 1);
+
+    let from_blocking = rt()
+        .block_on(async { task::spawn_blocking(|| task::block_in_place(|| 2)).await })
+        .unwrap();
+    assert_eq!(from_blocking, 2);
+}
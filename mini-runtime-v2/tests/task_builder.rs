@@ -0,0 +1,72 @@
+use mini_runtime_v2::runtime::{self, Runtime};
+use mini_runtime_v2::task;
+use std::sync::{Arc, Mutex};
+
+/// Builds a runtime recording the names of the spawned tasks.
+fn rt(names: &Arc<Mutex<Vec<Option<String>>>>) -> Runtime {
+    let names = names.clone();
+    runtime::Builder::new_current_thread()
+        .on_task_spawn(move |meta| {
+            names.lock().unwrap().push(meta.name().map(str::to_owned));
+        })
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn named_task_reports_its_name() {
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let rt = rt(&names);
+
+    rt.block_on(async {
+        let conn = 123;
+        let out = task::Builder::new()
+            .name(&format!("conn-{conn}"))
+            .spawn(async { 42 })
+            .await
+            .unwrap();
+        assert_eq!(out, 42);
+
+        task::spawn(async {}).await.unwrap();
+    });
+
+    assert_eq!(*names.lock().unwrap(), [Some("conn-123".to_owned()), None]);
+}
+
+#[test]
+fn spawn_on_from_another_thread() {
+    let names = Arc::new(Mutex::new(Vec::new()));
+    let rt = rt(&names);
+
+    let handle = rt.handle().clone();
+    let join = std::thread::spawn(move || {
+        task::Builder::new()
+            .name("remote")
+            .spawn_on(async { 7 }, &handle)
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(rt.block_on(join).unwrap(), 7);
+    assert_eq!(*names.lock().unwrap(), [Some("remote".to_owned())]);
+}
+
+#[cfg(feature = "task_dump")]
+#[test]
+fn dump_shows_task_name() {
+    let rt = runtime::Builder::new_current_thread().build().unwrap();
+
+    rt.block_on(async {
+        let task = task::Builder::new()
+            .name("sleeper")
+            .spawn(std::future::pending::<()>());
+        task::spawn(async {}).await.unwrap();
+
+        let dump = runtime::Handle::current().dump();
+        let names: Vec<_> = dump.tasks().iter().map(|task| task.name()).collect();
+        assert_eq!(names, [Some("sleeper")]);
+        assert!(dump.to_string().contains("\"sleeper\""));
+
+        task.abort();
+    });
+}
@@ -0,0 +1,55 @@
+use mini_runtime_v2::task;
+use std::collections::HashSet;
+
+mod support;
+use support::rt;
+
+#[test]
+fn id_is_unique_per_task() {
+    rt().block_on(async {
+        let handles: Vec<_> = (0..16).map(|_| task::spawn(async { task::id() })).collect();
+
+        let mut ids = HashSet::new();
+        for handle in handles {
+            let expected = handle.id();
+            let id = handle.await.unwrap();
+            // The id seen from within the task is the one of its handle.
+            assert_eq!(id, expected);
+            assert!(ids.insert(id), "duplicate task id: {id}");
+        }
+    });
+}
+
+#[test]
+fn try_id_outside_task() {
+    assert_eq!(task::try_id(), None);
+
+    rt().block_on(async {
+        // `block_on` is not a task.
+        assert_eq!(task::try_id(), None);
+
+        let id = task::spawn(async { task::try_id() }).await.unwrap();
+        assert!(id.is_some());
+    });
+
+    // Not set anymore once the task was polled.
+    assert_eq!(task::try_id(), None);
+}
+
+#[test]
+#[should_panic(expected = "Can't get a task id when not inside a task")]
+fn id_outside_task_panics() {
+    task::id();
+}
+
+/// Leaving `block_on` restores the context: the thread can enter a runtime
+/// again.
+#[test]
+fn block_on_restores_context() {
+    let rt = rt();
+    for _ in 0..3 {
+        let id = rt.block_on(async { task::spawn(async { task::id() }).await.unwrap() });
+        assert_eq!(task::try_id(), None);
+        assert!(id.to_string().parse::<u64>().is_ok());
+    }
+}
@@ -0,0 +1,91 @@
+use mini_runtime_v2::sync::mpsc;
+use mini_runtime_v2::task::JoinSet;
+
+mod support;
+use support::rt;
+
+/// Sends a message when dropped, i.e. when the task owning it is done.
+struct OnDrop(mpsc::UnboundedSender<&'static str>);
+
+impl Drop for OnDrop {
+    fn drop(&mut self) {
+        let _ = self.0.send("dropped");
+    }
+}
+
+#[test]
+fn join_next_on_empty_set() {
+    rt().block_on(async {
+        let mut set = JoinSet::<()>::new();
+        assert!(set.is_empty());
+        assert!(set.join_next().await.is_none());
+    });
+}
+
+/// The outputs are returned in the order the tasks complete, not the order
+/// they were spawned in.
+#[test]
+fn join_next_in_completion_order() {
+    rt().block_on(async {
+        let mut set = JoinSet::new();
+        let mut gates = Vec::new();
+        for i in 0..3 {
+            let (go_tx, mut go_rx) = mpsc::unbounded_channel::<()>();
+            set.spawn(async move {
+                go_rx.recv().await;
+                i
+            });
+            gates.push(go_tx);
+        }
+        assert_eq!(set.len(), 3);
+
+        for i in [2, 0, 1] {
+            gates[i].send(()).unwrap();
+            assert_eq!(set.join_next().await.unwrap().unwrap(), i);
+        }
+        assert!(set.is_empty());
+        assert!(set.join_next().await.is_none());
+    });
+}
+
+#[test]
+fn abort_all_cancels_tasks() {
+    rt().block_on(async {
+        let mut set = JoinSet::new();
+        for _ in 0..3 {
+            set.spawn(std::future::pending::<()>());
+        }
+
+        set.abort_all();
+        // Aborted tasks stay in the set until joined.
+        assert_eq!(set.len(), 3);
+        while let Some(res) = set.join_next().await {
+            assert!(res.unwrap_err().is_cancelled());
+        }
+    });
+}
+
+#[test]
+fn drop_aborts_remaining_tasks() {
+    rt().block_on(async {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+
+        let mut set = JoinSet::new();
+        for _ in 0..3 {
+            let on_drop = OnDrop(tx.clone());
+            set.spawn(async move {
+                let _on_drop = on_drop;
+                std::future::pending::<()>().await
+            });
+        }
+        drop(tx);
+
+        drop(set);
+
+        // The futures are dropped without completing, with their senders.
+        for _ in 0..3 {
+            assert_eq!(rx.recv().await, Some("dropped"));
+        }
+        assert_eq!(rx.recv().await, None);
+    });
+}
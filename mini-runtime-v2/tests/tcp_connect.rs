@@ -0,0 +1,59 @@
+use mini_runtime_v2::net::{self, TcpListener, TcpSocket, TcpStream};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+mod support;
+use support::rt;
+
+#[test]
+fn connect_resolves_host_names() {
+    rt().block_on(async {
+        let listener = TcpListener::bind("localhost:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let stream = TcpStream::connect(("localhost", port)).await.unwrap();
+        let (_, peer) = listener.accept().await.unwrap();
+        assert_eq!(peer, stream.local_addr().unwrap());
+    });
+}
+
+#[test]
+fn lookup_host_converts_ip_addresses() {
+    rt().block_on(async {
+        let addrs: Vec<_> = net::lookup_host("127.0.0.1:80").await.unwrap().collect();
+        assert_eq!(addrs, ["127.0.0.1:80".parse::<SocketAddr>().unwrap()]);
+
+        let addrs: Vec<_> = net::lookup_host(("localhost", 80)).await.unwrap().collect();
+        assert!(addrs.iter().all(|addr| addr.ip().is_loopback()));
+        assert!(!addrs.is_empty());
+    });
+}
+
+#[test]
+fn connect_races_a_stalled_address() {
+    rt().block_on(async {
+        // A listener with a full backlog drops the SYNs: connecting to it
+        // hangs until the OS connect timeout.
+        let stalled = TcpSocket::new_v4().unwrap();
+        stalled.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let stalled = stalled.listen(0).unwrap();
+        let stalled_addr = stalled.local_addr().unwrap();
+        let mut backlog = Vec::new();
+        while let Ok(Ok(stream)) = mini_runtime_v2::time::timeout(
+            Duration::from_millis(100),
+            TcpStream::connect(stalled_addr),
+        )
+        .await
+        {
+            backlog.push(stream);
+        }
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addrs = [stalled_addr, listener.local_addr().unwrap()];
+
+        let start = Instant::now();
+        let stream = TcpStream::connect(&addrs[..]).await.unwrap();
+        assert_eq!(stream.peer_addr().unwrap(), addrs[1]);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    });
+}
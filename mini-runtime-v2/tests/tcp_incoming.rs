@@ -0,0 +1,53 @@
+use mini_runtime_v2::future::poll_fn;
+use mini_runtime_v2::net::TcpListener;
+use mini_runtime_v2::stream::{Stream, StreamExt};
+use mini_runtime_v2::sync::Semaphore;
+use std::net::TcpStream;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::Poll;
+
+mod support;
+use support::rt;
+
+#[test]
+fn limited_incoming_waits_for_a_permit() {
+    rt().block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let limit = Arc::new(Semaphore::new(1));
+        let mut incoming = listener.incoming().with_limit(limit.clone());
+
+        let _first_client = TcpStream::connect(addr).unwrap();
+        let second_client = TcpStream::connect(addr).unwrap();
+
+        let (_first, permit) = incoming.next().await.unwrap().unwrap();
+        assert_eq!(limit.available_permits(), 0);
+
+        // The second connection is not accepted while the first one holds
+        // the permit.
+        let pending = poll_fn(|cx| Poll::Ready(Pin::new(&mut incoming).poll_next(cx).is_pending()));
+        assert!(pending.await);
+
+        drop(permit);
+        let (second, _permit) = incoming.next().await.unwrap().unwrap();
+        assert_eq!(
+            second.peer_addr().unwrap(),
+            second_client.local_addr().unwrap()
+        );
+    });
+}
+
+#[test]
+fn closing_the_semaphore_ends_the_stream() {
+    rt().block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        let limit = Arc::new(Semaphore::new(1));
+        let mut incoming = listener.incoming().with_limit(limit.clone());
+
+        limit.close();
+        assert!(incoming.next().await.is_none());
+    });
+}
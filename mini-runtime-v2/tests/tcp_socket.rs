@@ -0,0 +1,71 @@
+use mini_runtime_v2::net::{TcpListener, TcpSocket, TcpStream};
+use std::time::Duration;
+
+mod support;
+use support::rt;
+
+#[test]
+fn socket_options_apply_before_listen_and_connect() {
+    rt().block_on(async {
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.set_reuseaddr(true).unwrap();
+        assert!(socket.reuseaddr().unwrap());
+        socket.bind("127.0.0.1:0".parse().unwrap()).unwrap();
+        let addr = socket.local_addr().unwrap();
+        let listener = socket.listen(16).unwrap();
+
+        let socket = TcpSocket::new_v4().unwrap();
+        socket.set_nodelay(true).unwrap();
+        socket.set_linger(Some(Duration::from_secs(1))).unwrap();
+
+        // The connection completes in the listen backlog, before `accept`.
+        let client = socket.connect(addr).await.unwrap();
+        let (server, peer) = listener.accept().await.unwrap();
+
+        assert_eq!(peer, client.local_addr().unwrap());
+        assert_eq!(server.peer_addr().unwrap(), client.local_addr().unwrap());
+        assert!(client.nodelay().unwrap());
+        assert_eq!(client.linger().unwrap(), Some(Duration::from_secs(1)));
+    });
+}
+
+#[test]
+fn stream_and_listener_options() {
+    rt().block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        listener.set_ttl(42).unwrap();
+        assert_eq!(listener.ttl().unwrap(), 42);
+
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+
+        stream.set_ttl(7).unwrap();
+        assert_eq!(stream.ttl().unwrap(), 7);
+
+        stream.set_nodelay(true).unwrap();
+        assert!(stream.nodelay().unwrap());
+
+        assert_eq!(stream.linger().unwrap(), None);
+        stream.set_linger(Some(Duration::ZERO)).unwrap();
+        assert_eq!(stream.linger().unwrap(), Some(Duration::ZERO));
+    });
+}
+
+#[test]
+fn connect_to_closed_port_fails() {
+    rt().block_on(async {
+        // Bind then drop a listener to get a port nobody listens on.
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let err = TcpSocket::new_v4()
+            .unwrap()
+            .connect(addr)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionRefused);
+    });
+}
@@ -0,0 +1,108 @@
+use mini_runtime_v2::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use mini_runtime_v2::net::{TcpListener, TcpStream};
+use mini_runtime_v2::task::{self, JoinHandle};
+use std::net::SocketAddr;
+
+mod support;
+use support::rt;
+
+/// Spawns a server answering each line with the line in upper case, reading
+/// and writing through the owned halves of the socket. Returns the number
+/// of lines answered.
+async fn upper_server() -> (SocketAddr, JoinHandle<usize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = task::spawn(async move {
+        let (socket, _) = listener.accept().await.unwrap();
+        let (rd, mut wr) = socket.into_split();
+        let mut rd = BufReader::new(rd);
+
+        let mut lines = 0;
+        let mut line = String::new();
+        while rd.read_line(&mut line).await.unwrap() > 0 {
+            wr.write_all(line.to_uppercase().as_bytes()).await.unwrap();
+            line.clear();
+            lines += 1;
+        }
+        // Dropping the write half shuts the socket down.
+        lines
+    });
+
+    (addr, server)
+}
+
+#[test]
+fn read_line_over_split_halves() {
+    rt().block_on(async {
+        let (addr, server) = upper_server().await;
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        let (rd, mut wr) = stream.split();
+        let mut rd = BufReader::new(rd);
+
+        // Both lines in a single write, split back by `read_line`.
+        wr.write_all(b"hello\nworld\n").await.unwrap();
+
+        let mut line = String::new();
+        rd.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "HELLO\n");
+        line.clear();
+        rd.read_line(&mut line).await.unwrap();
+        assert_eq!(line, "WORLD\n");
+
+        wr.shutdown().await.unwrap();
+        line.clear();
+        assert_eq!(rd.read_line(&mut line).await.unwrap(), 0);
+        assert_eq!(server.await.unwrap(), 2);
+    });
+}
+
+#[test]
+fn owned_halves_in_separate_tasks() {
+    rt().block_on(async {
+        let (addr, server) = upper_server().await;
+
+        let (rd, mut wr) = TcpStream::connect(addr).await.unwrap().into_split();
+        let writer = task::spawn(async move {
+            for word in ["one\n", "two\n", "three\n"] {
+                wr.write_all(word.as_bytes()).await.unwrap();
+            }
+            // Dropped: shuts the write half down.
+        });
+
+        let mut rd = BufReader::new(rd);
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        while rd.read_line(&mut line).await.unwrap() > 0 {
+            lines.push(std::mem::take(&mut line));
+        }
+
+        writer.await.unwrap();
+        assert_eq!(lines, ["ONE\n", "TWO\n", "THREE\n"]);
+        assert_eq!(server.await.unwrap(), 3);
+    });
+}
+
+#[test]
+fn reunite_halves() {
+    rt().block_on(async {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let a = TcpStream::connect(addr).await.unwrap();
+        let b = TcpStream::connect(addr).await.unwrap();
+        let local = a.local_addr().unwrap();
+
+        let (a_rd, a_wr) = a.into_split();
+        let (b_rd, b_wr) = b.into_split();
+
+        // Halves of different sockets are given back.
+        let err = a_rd.reunite(b_wr).unwrap_err();
+        let (a_rd, b_wr) = (err.0, err.1);
+
+        let a = a_rd.reunite(a_wr).unwrap();
+        assert_eq!(a.local_addr().unwrap(), local);
+        b_wr.reunite(b_rd).unwrap();
+    });
+}
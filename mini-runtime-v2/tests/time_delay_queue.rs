@@ -0,0 +1,76 @@
+use mini_runtime_v2::future::poll_fn;
+use mini_runtime_v2::stream::StreamExt;
+use mini_runtime_v2::time::{self, DelayQueue, Duration, Instant};
+
+mod support;
+use support::rt_paused;
+
+/// Current time of the runtime's paused clock.
+fn now() -> Instant {
+    time::sleep(Duration::ZERO).deadline()
+}
+
+fn ms(n: u64) -> Duration {
+    Duration::from_millis(n)
+}
+
+#[test]
+fn yields_in_deadline_order() {
+    rt_paused().block_on(async {
+        let start = now();
+        let mut queue = DelayQueue::new();
+        for n in [30, 10, 20] {
+            queue.insert(n, ms(n));
+        }
+        assert_eq!(queue.len(), 3);
+
+        for n in [10, 20, 30] {
+            let expired = queue.next().await.unwrap();
+            assert_eq!(*expired.get_ref(), n);
+            assert_eq!(expired.deadline(), start + ms(n));
+        }
+
+        // Empty, but still usable.
+        assert!(queue.next().await.is_none());
+        queue.insert(40, ms(10));
+        assert_eq!(queue.next().await.unwrap().into_inner(), 40);
+    });
+}
+
+#[test]
+fn reset_and_remove() {
+    rt_paused().block_on(async {
+        let mut queue = DelayQueue::new();
+        let a = queue.insert("a", ms(10));
+        let b = queue.insert("b", ms(20));
+        let c = queue.insert("c", ms(30));
+
+        queue.reset(&a, ms(40));
+        assert_eq!(queue.remove(&b).into_inner(), "b");
+
+        assert_eq!(queue.next().await.unwrap().key(), c);
+        assert_eq!(queue.next().await.unwrap().key(), a);
+        assert!(queue.is_empty());
+    });
+}
+
+#[test]
+fn earlier_insert_wakes_waiting_task() {
+    rt_paused().block_on(async {
+        let start = now();
+        let mut queue = DelayQueue::new();
+        queue.insert("late", ms(100));
+
+        // Register the timer at the late deadline.
+        let pending =
+            poll_fn(|cx| std::task::Poll::Ready(queue.poll_expired(cx).is_pending())).await;
+        assert!(pending);
+
+        // The paused clock jumps to the next timer: it must be moved to the
+        // early deadline.
+        queue.insert("early", ms(10));
+        let expired = queue.next().await.unwrap();
+        assert_eq!(*expired.get_ref(), "early");
+        assert_eq!(now(), start + ms(10));
+    });
+}
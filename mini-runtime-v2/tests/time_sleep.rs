@@ -0,0 +1,47 @@
+use mini_runtime_v2::future::poll_fn;
+use mini_runtime_v2::pin;
+use mini_runtime_v2::time::{self, Duration, Instant};
+use std::task::Poll;
+
+mod support;
+use support::rt_paused;
+
+#[test]
+fn reset_registered_sleep_moves_deadline() {
+    rt_paused().block_on(async {
+        let start = Instant::now();
+        let sleep = time::sleep(Duration::from_secs(10));
+        pin!(sleep);
+
+        // Register the timer.
+        let registered = poll_fn(|cx| Poll::Ready(sleep.as_mut().poll(cx).is_pending())).await;
+        assert!(registered);
+
+        // Earlier and later deadlines are both honored.
+        sleep.as_mut().reset(start + Duration::from_secs(1));
+        sleep.as_mut().await;
+        assert_eq!(sleep.deadline(), start + Duration::from_secs(1));
+
+        sleep.as_mut().reset(start + Duration::from_secs(30));
+        assert!(!sleep.is_elapsed());
+        sleep.as_mut().await;
+        assert!(sleep.is_elapsed());
+    });
+}
+
+#[test]
+fn reset_sleep_in_loop() {
+    rt_paused().block_on(async {
+        let sleep = time::sleep(Duration::from_millis(10));
+        pin!(sleep);
+
+        for _ in 0..5 {
+            sleep.as_mut().await;
+            assert!(sleep.is_elapsed());
+
+            let next = sleep.deadline() + Duration::from_millis(10);
+            sleep.as_mut().reset(next);
+            assert!(!sleep.is_elapsed());
+        }
+    });
+}
@@ -0,0 +1,60 @@
+use mini_runtime_v2::net::UdpSocket;
+use mini_runtime_v2::task;
+use std::sync::Arc;
+
+mod support;
+use support::rt;
+
+#[test]
+fn send_to_recv_from() {
+    rt().block_on(async {
+        let a = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let b = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        let a_addr = a.local_addr().unwrap();
+        let b_addr = b.local_addr().unwrap();
+
+        assert_eq!(a.send_to(b"ping", b_addr).await.unwrap(), 4);
+
+        let mut buf = [0; 16];
+        let (n, from) = b.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"ping");
+        assert_eq!(from, a_addr);
+
+        b.send_to(b"pong", from).await.unwrap();
+        let (n, from) = a.recv_from(&mut buf).await.unwrap();
+        assert_eq!(&buf[..n], b"pong");
+        assert_eq!(from, b_addr);
+    });
+}
+
+/// `recv_from` waits for a datagram sent later by another task.
+#[test]
+fn echo_server_task() {
+    rt().block_on(async {
+        let server = Arc::new(UdpSocket::bind("127.0.0.1:0").await.unwrap());
+        let server_addr = server.local_addr().unwrap();
+
+        let echo = task::spawn({
+            let server = server.clone();
+            async move {
+                let mut buf = [0; 64];
+                for _ in 0..3 {
+                    let (n, from) = server.recv_from(&mut buf).await.unwrap();
+                    server.send_to(&buf[..n], from).await.unwrap();
+                }
+            }
+        });
+
+        let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+        client.connect(server_addr).await.unwrap();
+        assert_eq!(client.peer_addr().unwrap(), server_addr);
+
+        let mut buf = [0; 64];
+        for msg in [&b"one"[..], b"two", b"three"] {
+            client.send(msg).await.unwrap();
+            let n = client.recv(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], msg);
+        }
+        echo.await.unwrap();
+    });
+}
@@ -0,0 +1,362 @@
+//! A minimal HTTP/1.1 server on top of the mini runtime.
+//!
+//! Every request gets the same static response, its body is the first
+//! command line argument:
+//!
+//! ```bash
+//! cargo run --example http -- "Hello from mio"
+//! curl -v http://127.0.0.1:8080/
+//! ```
+//!
+//! Connections are kept alive unless the client asks otherwise, and request
+//! bodies sized by `Content-Length` are read. Chunked bodies are rejected.
+//!
+//! A request carrying `Upgrade: websocket` switches the connection to the
+//! WebSocket protocol, and the server then echoes every message it receives:
+//!
+//! ```bash
+//! websocat ws://127.0.0.1:8080/
+//! ```
+
+mod websocket;
+
+use crate::websocket::{Frame, Opcode};
+use mini_runtime::codec::{Decoder, Encoder};
+use mini_runtime::{Config, Context, Handler, MiniRuntime};
+use std::io;
+use tutorial_util::Result;
+
+/// Longest accepted request line and headers.
+const MAX_HEAD_LENGTH: usize = 8 * 1024;
+/// Longest accepted request body.
+const MAX_BODY_LENGTH: usize = 1024 * 1024;
+
+#[derive(Debug)]
+struct Request {
+    method: String,
+    path: String,
+    /// The minor version: `HTTP/1.0` or `HTTP/1.1`.
+    minor_version: u8,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    /// Returns the value of the header `name`, compared case-insensitively.
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// HTTP/1.1 connections are persistent unless the client sends
+    /// `Connection: close`, HTTP/1.0 ones only with `Connection: keep-alive`.
+    fn keep_alive(&self) -> bool {
+        match self.header("Connection") {
+            Some(value) if value.eq_ignore_ascii_case("close") => false,
+            Some(value) if value.eq_ignore_ascii_case("keep-alive") => true,
+            _ => self.minor_version == 1,
+        }
+    }
+
+    /// Returns the client's `Sec-WebSocket-Key` if this is a WebSocket opening
+    /// handshake.
+    fn websocket_key(&self) -> Option<&str> {
+        let upgrade = self.header("Upgrade")?;
+        let connection = self.header("Connection")?;
+        let is_upgrade = self.method == "GET"
+            && upgrade.eq_ignore_ascii_case("websocket")
+            && connection
+                .split(',')
+                .any(|token| token.trim().eq_ignore_ascii_case("upgrade"));
+
+        if is_upgrade {
+            self.header("Sec-WebSocket-Key")
+        } else {
+            None
+        }
+    }
+}
+
+struct Response {
+    status: u16,
+    reason: &'static str,
+    headers: Vec<(&'static str, String)>,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn ok(body: Vec<u8>, keep_alive: bool) -> Self {
+        let connection = if keep_alive { "keep-alive" } else { "close" };
+        Self {
+            status: 200,
+            reason: "OK",
+            headers: vec![
+                ("Content-Type", "text/plain; charset=utf-8".to_string()),
+                ("Connection", connection.to_string()),
+            ],
+            body,
+        }
+    }
+
+    /// Accepts a WebSocket opening handshake.
+    fn switching_protocols(websocket_key: &str) -> Self {
+        Self {
+            status: 101,
+            reason: "Switching Protocols",
+            headers: vec![
+                ("Upgrade", "websocket".to_string()),
+                ("Connection", "Upgrade".to_string()),
+                ("Sec-WebSocket-Accept", websocket::accept_key(websocket_key)),
+            ],
+            body: Vec::new(),
+        }
+    }
+}
+
+/// What a connection receives: HTTP requests until it is upgraded, then
+/// WebSocket frames.
+enum Message {
+    Request(Request),
+    WebSocket(Frame),
+}
+
+/// Decodes requests and encodes responses, or WebSocket frames once the
+/// connection was upgraded.
+///
+/// The head of a request is complete at the first empty line. When it has a
+/// `Content-Length`, the decoder then waits for that many body bytes.
+#[derive(Default)]
+struct HttpCodec {
+    /// A request whose head was parsed, waiting for `body_length` bytes of
+    /// body.
+    pending: Option<(Request, usize)>,
+
+    /// Whether the connection switched to the WebSocket protocol.
+    websocket: bool,
+}
+
+impl HttpCodec {
+    /// Decodes the bytes following the current request as WebSocket frames.
+    fn upgrade(&mut self) {
+        self.websocket = true;
+    }
+
+    fn decode_request(&mut self, src: &mut Vec<u8>) -> io::Result<Option<Request>> {
+        let body_length = match &self.pending {
+            Some((_, body_length)) => *body_length,
+            None => {
+                let Some(end) = src.windows(4).position(|w| w == b"\r\n\r\n") else {
+                    if src.len() > MAX_HEAD_LENGTH {
+                        return Err(invalid("request head too long"));
+                    }
+                    return Ok(None);
+                };
+
+                let head: Vec<u8> = src.drain(..end + 4).collect();
+                let (request, body_length) = parse_head(&head[..end])?;
+                self.pending = Some((request, body_length));
+                body_length
+            }
+        };
+
+        if src.len() < body_length {
+            return Ok(None);
+        }
+
+        let (mut request, _) = self.pending.take().expect("request head was parsed");
+        request.body = src.drain(..body_length).collect();
+        Ok(Some(request))
+    }
+}
+
+impl Decoder for HttpCodec {
+    type Item = Message;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut Vec<u8>) -> io::Result<Option<Message>> {
+        if self.websocket {
+            Ok(websocket::decode_frame(src)?.map(Message::WebSocket))
+        } else {
+            Ok(self.decode_request(src)?.map(Message::Request))
+        }
+    }
+}
+
+impl Encoder<Response> for HttpCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, response: Response, dst: &mut Vec<u8>) -> io::Result<()> {
+        let mut head = format!("HTTP/1.1 {} {}\r\n", response.status, response.reason);
+        for (name, value) in &response.headers {
+            head.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        // Informational responses have no body, not even an empty one.
+        if response.status >= 200 {
+            head.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+        }
+        head.push_str("\r\n");
+
+        dst.extend_from_slice(head.as_bytes());
+        dst.extend_from_slice(&response.body);
+        Ok(())
+    }
+}
+
+impl Encoder<Frame> for HttpCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, frame: Frame, dst: &mut Vec<u8>) -> io::Result<()> {
+        websocket::encode_frame(frame, dst);
+        Ok(())
+    }
+}
+
+/// Parses the request line and the headers, returns the request and the
+/// length of its body.
+fn parse_head(head: &[u8]) -> io::Result<(Request, usize)> {
+    let head = std::str::from_utf8(head).map_err(|_| invalid("request head is not UTF-8"))?;
+    let mut lines = head.split("\r\n");
+
+    let request_line = lines.next().unwrap_or_default();
+    let mut parts = request_line.split(' ');
+    let (Some(method), Some(path), Some(version), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid("malformed request line"));
+    };
+    let minor_version = match version {
+        "HTTP/1.0" => 0,
+        "HTTP/1.1" => 1,
+        _ => return Err(invalid("unsupported HTTP version")),
+    };
+
+    let headers = lines
+        .map(|line| {
+            let (name, value) = line
+                .split_once(':')
+                .ok_or_else(|| invalid("malformed header"))?;
+            Ok((name.trim().to_string(), value.trim().to_string()))
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let request = Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        minor_version,
+        headers,
+        body: Vec::new(),
+    };
+
+    if request.header("Transfer-Encoding").is_some() {
+        return Err(invalid("chunked request bodies are not supported"));
+    }
+    let body_length = match request.header("Content-Length") {
+        Some(length) => length
+            .parse::<usize>()
+            .map_err(|_| invalid("invalid Content-Length"))?,
+        None => 0,
+    };
+    if body_length > MAX_BODY_LENGTH {
+        return Err(invalid("request body too long"));
+    }
+
+    Ok((request, body_length))
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Answers every HTTP request with `body`, and echoes the messages of
+/// upgraded WebSocket connections.
+struct HttpServer {
+    body: String,
+}
+
+impl HttpServer {
+    fn on_request(&mut self, request: Request, cx: &mut Context<'_, HttpCodec>) -> Result<()> {
+        println!(
+            "📨 {} {} from {:?} ({} byte body)",
+            request.method,
+            request.path,
+            cx.token(),
+            request.body.len()
+        );
+
+        if let Some(key) = request.websocket_key() {
+            println!("🔀 Upgrading {:?} to WebSocket", cx.token());
+            cx.send(Response::switching_protocols(key))?;
+            cx.codec_mut().upgrade();
+            return Ok(());
+        }
+
+        let keep_alive = request.keep_alive();
+        cx.send(Response::ok(self.body.clone().into_bytes(), keep_alive))?;
+
+        // The response is still written, then the client sees the end of the
+        // stream.
+        if !keep_alive {
+            cx.shutdown_write();
+        }
+        Ok(())
+    }
+
+    fn on_websocket_frame(&mut self, frame: Frame, cx: &mut Context<'_, HttpCodec>) -> Result<()> {
+        match frame.opcode {
+            // Echo data frames as they come, fragments included.
+            Opcode::Text | Opcode::Binary | Opcode::Continuation => {
+                if frame.opcode == Opcode::Text {
+                    let text = String::from_utf8_lossy(&frame.payload);
+                    println!("📨 WebSocket text from {:?}: {}", cx.token(), text);
+                } else {
+                    let length = frame.payload.len();
+                    println!("📨 WebSocket {length} bytes from {:?}", cx.token());
+                }
+                cx.send(Frame {
+                    mask: None,
+                    ..frame
+                })?;
+            }
+            Opcode::Ping => cx.send(Frame::new(Opcode::Pong, frame.payload))?,
+            Opcode::Pong => {}
+            // Answer with the status code the client sent, then the client
+            // closes the TCP connection.
+            Opcode::Close => {
+                println!("👋 WebSocket closed by {:?}", cx.token());
+                let status = frame.payload.get(..2).unwrap_or_default().to_vec();
+                cx.send(Frame::new(Opcode::Close, status))?;
+                cx.shutdown_write();
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Handler for HttpServer {
+    type Codec = HttpCodec;
+    type Timer = ();
+
+    fn new_codec(&mut self) -> HttpCodec {
+        HttpCodec::default()
+    }
+
+    fn on_frame(&mut self, message: Message, cx: &mut Context<'_, HttpCodec>) -> Result<()> {
+        match message {
+            Message::Request(request) => self.on_request(request, cx),
+            Message::WebSocket(frame) => self.on_websocket_frame(frame, cx),
+        }
+    }
+}
+
+fn main() -> Result<()> {
+    let body = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "Hello, World!\n".to_string());
+
+    let address = "127.0.0.1:8080".parse()?;
+    let mut runtime = MiniRuntime::new(address, Config::default(), HttpServer { body })?;
+    mini_runtime::on_sigint(runtime.shutdown_handle())?;
+    runtime.run()
+}
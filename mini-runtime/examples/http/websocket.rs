@@ -0,0 +1,279 @@
+//! The WebSocket protocol (RFC 6455): the opening handshake and the framing
+//! of messages once the connection was upgraded.
+
+use std::io;
+
+/// Appended to the client's key before hashing it, see RFC 6455 section 1.3.
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Longest accepted frame payload.
+const MAX_PAYLOAD_LENGTH: usize = 1024 * 1024;
+
+/// Returns the `Sec-WebSocket-Accept` header value answering the client's
+/// `Sec-WebSocket-Key`: the base64 encoded SHA-1 of the key followed by the
+/// protocol's GUID. It proves that the server understood the handshake.
+pub fn accept_key(key: &str) -> String {
+    base64(&sha1(
+        format!("{}{}", key.trim(), HANDSHAKE_GUID).as_bytes(),
+    ))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Opcode {
+    Continuation,
+    Text,
+    Binary,
+    Close,
+    Ping,
+    Pong,
+}
+
+impl Opcode {
+    fn from_u8(opcode: u8) -> io::Result<Self> {
+        Ok(match opcode {
+            0x0 => Opcode::Continuation,
+            0x1 => Opcode::Text,
+            0x2 => Opcode::Binary,
+            0x8 => Opcode::Close,
+            0x9 => Opcode::Ping,
+            0xA => Opcode::Pong,
+            _ => return Err(invalid("unknown opcode")),
+        })
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            Opcode::Continuation => 0x0,
+            Opcode::Text => 0x1,
+            Opcode::Binary => 0x2,
+            Opcode::Close => 0x8,
+            Opcode::Ping => 0x9,
+            Opcode::Pong => 0xA,
+        }
+    }
+
+    fn is_control(self) -> bool {
+        matches!(self, Opcode::Close | Opcode::Ping | Opcode::Pong)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Frame {
+    /// Whether this is the last frame of a message.
+    pub fin: bool,
+    pub opcode: Opcode,
+    /// The payload, unmasked.
+    pub payload: Vec<u8>,
+    /// The key the payload is masked with on the wire. Clients must mask
+    /// their frames, servers must not.
+    pub mask: Option<[u8; 4]>,
+}
+
+impl Frame {
+    /// Returns an unmasked frame holding a whole message, as sent by servers.
+    pub fn new(opcode: Opcode, payload: Vec<u8>) -> Self {
+        Self {
+            fin: true,
+            opcode,
+            payload,
+            mask: None,
+        }
+    }
+}
+
+/// Decodes a frame out of `src`, returns `Ok(None)` if it is not complete.
+///
+/// ```text
+///  0                   1                   2                   3
+///  0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1 2 3 4 5 6 7 8 9 0 1
+/// +-+-+-+-+-------+-+-------------+-------------------------------+
+/// |F|R|R|R| opcode|M| Payload len |    Extended payload length    |
+/// |I|S|S|S|  (4)  |A|     (7)     |             (16/64)           |
+/// |N|V|V|V|       |S|             |   (if payload len==126/127)   |
+/// | |1|2|3|       |K|             |                               |
+/// +-+-+-+-+-------+-+-------------+ - - - - - - - - - - - - - - - +
+/// |     Extended payload length continued, if payload len == 127  |
+/// + - - - - - - - - - - - - - - - +-------------------------------+
+/// |                               |Masking-key, if MASK set to 1  |
+/// +-------------------------------+-------------------------------+
+/// | Masking-key (continued)       |          Payload Data         |
+/// +-------------------------------- - - - - - - - - - - - - - - - +
+/// ```
+pub fn decode_frame(src: &mut Vec<u8>) -> io::Result<Option<Frame>> {
+    if src.len() < 2 {
+        return Ok(None);
+    }
+
+    let fin = src[0] & 0x80 != 0;
+    if src[0] & 0x70 != 0 {
+        return Err(invalid("reserved bits set without a negotiated extension"));
+    }
+    let opcode = Opcode::from_u8(src[0] & 0x0F)?;
+    let masked = src[1] & 0x80 != 0;
+
+    let (payload_length, mut offset) = match src[1] & 0x7F {
+        126 => {
+            let Some(bytes) = src.get(2..4) else {
+                return Ok(None);
+            };
+            (u16::from_be_bytes(bytes.try_into().unwrap()) as u64, 4)
+        }
+        127 => {
+            let Some(bytes) = src.get(2..10) else {
+                return Ok(None);
+            };
+            (u64::from_be_bytes(bytes.try_into().unwrap()), 10)
+        }
+        length => (length as u64, 2),
+    };
+
+    if opcode.is_control() && (!fin || payload_length > 125) {
+        return Err(invalid("fragmented or oversized control frame"));
+    }
+    if payload_length > MAX_PAYLOAD_LENGTH as u64 {
+        return Err(invalid("frame payload too long"));
+    }
+    let payload_length = payload_length as usize;
+
+    let mask = if masked {
+        let Some(bytes) = src.get(offset..offset + 4) else {
+            return Ok(None);
+        };
+        offset += 4;
+        Some(<[u8; 4]>::try_from(bytes).unwrap())
+    } else {
+        None
+    };
+
+    if src.len() < offset + payload_length {
+        return Ok(None);
+    }
+
+    let mut frame: Vec<u8> = src.drain(..offset + payload_length).collect();
+    let mut payload = frame.split_off(offset);
+    if let Some(mask) = mask {
+        apply_mask(&mut payload, mask);
+    }
+
+    Ok(Some(Frame {
+        fin,
+        opcode,
+        payload,
+        mask,
+    }))
+}
+
+/// Appends `frame` to `dst`, masking its payload if the frame has a key.
+pub fn encode_frame(frame: Frame, dst: &mut Vec<u8>) {
+    let fin = if frame.fin { 0x80 } else { 0 };
+    dst.push(fin | frame.opcode.as_u8());
+
+    let mask_bit = if frame.mask.is_some() { 0x80 } else { 0 };
+    let length = frame.payload.len();
+    if length < 126 {
+        dst.push(mask_bit | length as u8);
+    } else if let Ok(length) = u16::try_from(length) {
+        dst.push(mask_bit | 126);
+        dst.extend_from_slice(&length.to_be_bytes());
+    } else {
+        dst.push(mask_bit | 127);
+        dst.extend_from_slice(&(length as u64).to_be_bytes());
+    }
+
+    let mut payload = frame.payload;
+    if let Some(mask) = frame.mask {
+        dst.extend_from_slice(&mask);
+        apply_mask(&mut payload, mask);
+    }
+    dst.extend_from_slice(&payload);
+}
+
+/// XORs the payload with the key, which both masks and unmasks it.
+fn apply_mask(payload: &mut [u8], mask: [u8; 4]) {
+    for (i, byte) in payload.iter_mut().enumerate() {
+        *byte ^= mask[i % 4];
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// SHA-1 (RFC 3174). Broken as a cryptographic hash, but it is what the
+/// handshake requires and it is short enough to write out here.
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [0x67452301, 0xEFCDAB89, 0x98BADCFE, 0x10325476, 0xC3D2E1F0];
+
+    // Pad with a 1 bit, zeros, and the length in bits, to a multiple of 64
+    // bytes.
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64) * 8).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes(word.try_into().unwrap());
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..20 => ((b & c) | (!b & d), 0x5A827999),
+                20..40 => (b ^ c ^ d, 0x6ED9EBA1),
+                40..60 => ((b & c) | (b & d) | (c & d), 0x8F1BBCDC),
+                _ => (b ^ c ^ d, 0xCA62C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(*word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+
+        for (h, v) in h.iter_mut().zip([a, b, c, d, e]) {
+            *h = h.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0u8; 20];
+    for (chunk, h) in digest.chunks_mut(4).zip(h) {
+        chunk.copy_from_slice(&h.to_be_bytes());
+    }
+    digest
+}
+
+/// Standard base64 with padding (RFC 4648).
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let bytes = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
@@ -0,0 +1,57 @@
+use crate::connection::Connection;
+use mio::Token;
+use slab::Slab;
+
+/// Token of the first client, the ones below are reserved for the runtime.
+const FIRST_CLIENT: usize = 1;
+
+/// The open connections, indexed by their poll token.
+///
+/// Connections live in a slab: the slot of a closed connection is reused by
+/// the next accepted one, so tokens stay small on a long-running server and a
+/// lookup is a plain vector index.
+pub(crate) struct Clients<C> {
+    slab: Slab<Connection<C>>,
+}
+
+impl<C> Clients<C> {
+    pub(crate) fn new() -> Self {
+        Self { slab: Slab::new() }
+    }
+
+    /// Returns the token the next inserted connection will get, so that the
+    /// socket can be registered before it is inserted.
+    pub(crate) fn next_token(&self) -> Token {
+        Token(self.slab.vacant_key() + FIRST_CLIENT)
+    }
+
+    pub(crate) fn insert(&mut self, client: Connection<C>) -> Token {
+        Token(self.slab.insert(client) + FIRST_CLIENT)
+    }
+
+    pub(crate) fn get_mut(&mut self, token: Token) -> Option<&mut Connection<C>> {
+        self.slab.get_mut(token.0.checked_sub(FIRST_CLIENT)?)
+    }
+
+    pub(crate) fn remove(&mut self, token: Token) -> Option<Connection<C>> {
+        self.slab.try_remove(token.0.checked_sub(FIRST_CLIENT)?)
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (Token, &Connection<C>)> {
+        self.slab
+            .iter()
+            .map(|(key, client)| (Token(key + FIRST_CLIENT), client))
+    }
+
+    pub(crate) fn tokens(&self) -> Vec<Token> {
+        self.iter().map(|(token, _)| token).collect()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.slab.len()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.slab.is_empty()
+    }
+}
@@ -0,0 +1,87 @@
+use crate::codec::{Decoder, Encoder};
+use std::{io, mem};
+
+/// A codec splitting the stream into lines of UTF-8 text.
+///
+/// Lines end with `\n`, an optional `\r` before it is stripped too. Encoded
+/// lines get a `\n` appended.
+#[derive(Debug, Clone)]
+pub struct LinesCodec {
+    /// Index in the buffer where the search for the next `\n` resumes, so the
+    /// bytes of a long partial line are scanned only once.
+    next_index: usize,
+
+    /// Longest accepted line, not counting the line terminator. Without a
+    /// limit, a peer that never sends `\n` makes the buffer grow unbounded.
+    max_length: usize,
+}
+
+impl LinesCodec {
+    /// Returns a codec failing with `InvalidData` on lines longer than
+    /// `max_length` bytes.
+    pub fn new_with_max_length(max_length: usize) -> Self {
+        Self {
+            next_index: 0,
+            max_length,
+        }
+    }
+}
+
+impl Decoder for LinesCodec {
+    type Item = String;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut Vec<u8>) -> io::Result<Option<String>> {
+        // Look one byte past the limit: a line of exactly `max_length` bytes
+        // is followed by its `\n`.
+        let read_to = src.len().min(self.max_length.saturating_add(1));
+
+        match src[self.next_index..read_to]
+            .iter()
+            .position(|b| *b == b'\n')
+        {
+            Some(offset) => {
+                let newline = self.next_index + offset;
+                self.next_index = 0;
+                let line: Vec<u8> = src.drain(..=newline).collect();
+                to_line(&line[..line.len() - 1]).map(Some)
+            }
+            None if src.len() > self.max_length => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line longer than {} bytes", self.max_length),
+            )),
+            None => {
+                self.next_index = read_to;
+                Ok(None)
+            }
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut Vec<u8>) -> io::Result<Option<String>> {
+        match self.decode(src)? {
+            Some(line) => Ok(Some(line)),
+            None if src.is_empty() => Ok(None),
+            // The last line of the stream doesn't need a terminator.
+            None => {
+                self.next_index = 0;
+                to_line(&mem::take(src)).map(Some)
+            }
+        }
+    }
+}
+
+impl<T: AsRef<str>> Encoder<T> for LinesCodec {
+    type Error = io::Error;
+
+    fn encode(&mut self, line: T, dst: &mut Vec<u8>) -> io::Result<()> {
+        dst.extend_from_slice(line.as_ref().as_bytes());
+        dst.push(b'\n');
+        Ok(())
+    }
+}
+
+fn to_line(bytes: &[u8]) -> io::Result<String> {
+    let bytes = bytes.strip_suffix(b"\r").unwrap_or(bytes);
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "line is not valid UTF-8"))
+}
@@ -0,0 +1,51 @@
+//! Turns the raw bytes of a connection into frames and back.
+//!
+//! A read returns whatever the kernel has buffered: half a message, or several
+//! messages at once. A `Decoder` accumulates the bytes read from a connection
+//! and splits them into complete frames, an `Encoder` serializes the frames
+//! written to it. This mirrors the `tokio_util::codec` traits, with a `Vec<u8>`
+//! standing in for `BytesMut`.
+
+mod lines_codec;
+
+pub use lines_codec::LinesCodec;
+
+use std::io;
+
+/// Decodes frames from a buffer of bytes read from a connection.
+pub trait Decoder {
+    /// The type of decoded frames.
+    type Item;
+
+    /// The type of unrecoverable frame decoding errors. Reading from the
+    /// connection may fail too, so it must accept `io::Error`s.
+    type Error: From<io::Error>;
+
+    /// Attempts to decode a frame from `src`, removing its bytes from the
+    /// buffer.
+    ///
+    /// Returns `Ok(None)` when `src` doesn't contain a whole frame yet: the
+    /// connection reads more data and calls `decode` again.
+    fn decode(&mut self, src: &mut Vec<u8>) -> Result<Option<Self::Item>, Self::Error>;
+
+    /// Called instead of `decode` once the peer closed its write side, when
+    /// no more bytes will be appended to `src`.
+    ///
+    /// By default, a buffer that still contains a partial frame is an error.
+    fn decode_eof(&mut self, src: &mut Vec<u8>) -> Result<Option<Self::Item>, Self::Error> {
+        match self.decode(src)? {
+            Some(frame) => Ok(Some(frame)),
+            None if src.is_empty() => Ok(None),
+            None => Err(io::Error::other("bytes remaining on stream").into()),
+        }
+    }
+}
+
+/// Encodes frames into the bytes written to a connection.
+pub trait Encoder<Item> {
+    /// The type of encoding errors.
+    type Error: From<io::Error>;
+
+    /// Appends the bytes of `item` to `dst`.
+    fn encode(&mut self, item: Item, dst: &mut Vec<u8>) -> Result<(), Self::Error>;
+}
@@ -0,0 +1,272 @@
+use crate::codec::{Decoder, Encoder};
+use crate::timer::TimerId;
+use mio::net::TcpStream;
+use mio::{Interest, Registry, Token};
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
+use std::time::Instant;
+
+/// A client connection, framed with the codec `C`.
+///
+/// Bytes read from the socket accumulate in `inbound` until the codec decodes
+/// a whole frame from them, so a frame split across several reads is
+/// reassembled. Frames sent to the peer are encoded into `outbound`.
+///
+/// The socket is non-blocking: a write may accept only part of the data, or
+/// fail with `WouldBlock` when the kernel send buffer is full. The bytes that
+/// could not be written stay in `outbound` and are flushed on the next
+/// WRITABLE event.
+///
+/// Both directions of the connection close independently. A peer that shuts
+/// down its write side still receives the data queued for it, and the server
+/// can half-close with `shutdown_write` while it keeps reading.
+pub(crate) struct Connection<C> {
+    pub(crate) socket: TcpStream,
+
+    codec: C,
+
+    /// Bytes received but not decoded yet.
+    inbound: Vec<u8>,
+
+    /// Bytes encoded but not written yet.
+    outbound: Vec<u8>,
+
+    /// Interest currently registered with the poll.
+    registered: Interest,
+
+    /// Last time data was read from or written to the socket.
+    last_activity: Instant,
+
+    /// The peer shut down its write side: no more data will be read.
+    read_closed: bool,
+
+    /// `shutdown_write` was called: the write side is shut down once the
+    /// outbound buffer is flushed.
+    write_shutdown_requested: bool,
+
+    /// The write side is shut down, the peer saw the end of the stream.
+    write_closed: bool,
+
+    /// The idle timer, see `Config::idle_timeout`.
+    idle_timer: Option<TimerId>,
+
+    /// The pending timers set by the handler. The connection stays open
+    /// while there are some, and they are cancelled when it closes.
+    timers: Vec<TimerId>,
+}
+
+/// What happened while reading from a connection.
+pub(crate) enum ReadOutcome {
+    /// The socket has no more data for now.
+    WouldBlock,
+    /// The peer closed the connection.
+    Closed,
+}
+
+impl<C> Connection<C> {
+    pub(crate) fn new(socket: TcpStream, codec: C) -> Self {
+        Self {
+            socket,
+            codec,
+            inbound: Vec::new(),
+            outbound: Vec::new(),
+            registered: Interest::READABLE,
+            last_activity: Instant::now(),
+            read_closed: false,
+            write_shutdown_requested: false,
+            write_closed: false,
+            idle_timer: None,
+            timers: Vec::new(),
+        }
+    }
+
+    pub(crate) fn register(&mut self, registry: &Registry, token: Token) -> io::Result<()> {
+        self.registered = self.interest();
+        registry.register(&mut self.socket, token, self.registered)
+    }
+
+    /// Registers WRITABLE interest when data is waiting to be written, and
+    /// drops it once the outbound buffer is empty.
+    pub(crate) fn update_interest(&mut self, registry: &Registry, token: Token) -> io::Result<()> {
+        let interest = self.interest();
+        if interest != self.registered {
+            registry.reregister(&mut self.socket, token, interest)?;
+            self.registered = interest;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn deregister(&mut self, registry: &Registry) -> io::Result<()> {
+        registry.deregister(&mut self.socket)
+    }
+
+    /// Reads everything available on the socket into the inbound buffer.
+    /// Returns the number of bytes read and why reading stopped.
+    ///
+    /// mio is edge-triggered: a READABLE event is reported once per batch of
+    /// incoming data, so the socket must be read until `WouldBlock`, otherwise
+    /// the data left in the kernel buffer is not reported again.
+    pub(crate) fn read_available(&mut self) -> io::Result<(usize, ReadOutcome)> {
+        let mut buffer = [0; 1024];
+        let mut total = 0;
+        loop {
+            match self.socket.read(&mut buffer) {
+                Ok(0) => {
+                    self.read_closed = true;
+                    return Ok((total, ReadOutcome::Closed));
+                }
+                Ok(n) => {
+                    self.last_activity = Instant::now();
+                    self.inbound.extend_from_slice(&buffer[..n]);
+                    total += n;
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    return Ok((total, ReadOutcome::WouldBlock));
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Decodes the next complete frame out of the data read so far.
+    ///
+    /// Once the peer closed its side, the rest of the buffer is handed to
+    /// `Decoder::decode_eof`.
+    pub(crate) fn next_frame(&mut self) -> Result<Option<C::Item>, C::Error>
+    where
+        C: Decoder,
+    {
+        if self.read_closed {
+            self.codec.decode_eof(&mut self.inbound)
+        } else {
+            self.codec.decode(&mut self.inbound)
+        }
+    }
+
+    /// Returns the codec, e.g. to switch protocols after an upgrade.
+    pub(crate) fn codec_mut(&mut self) -> &mut C {
+        &mut self.codec
+    }
+
+    /// Encodes `item` into the outbound buffer, it is written by the next
+    /// `flush`.
+    ///
+    /// Frames sent after `shutdown_write` are discarded.
+    pub(crate) fn send<I>(&mut self, item: I) -> Result<(), C::Error>
+    where
+        C: Encoder<I>,
+    {
+        if !self.write_shutdown_requested {
+            self.codec.encode(item, &mut self.outbound)?;
+        }
+        Ok(())
+    }
+
+    /// Half-closes the connection from the server side: the data already
+    /// queued is still written, then the peer reads the end of the stream.
+    /// Reading from the peer goes on until it closes its side too.
+    pub(crate) fn shutdown_write(&mut self) {
+        self.write_shutdown_requested = true;
+    }
+
+    /// Writes as much of the outbound buffer as the socket accepts, and shuts
+    /// down the write side once the buffer is empty if `shutdown_write` was
+    /// called.
+    ///
+    /// Returns the number of bytes written.
+    pub(crate) fn flush(&mut self) -> io::Result<usize> {
+        let written = self.write_outbound()?;
+        if self.write_shutdown_requested && !self.write_closed && !self.has_pending_writes() {
+            self.socket.shutdown(Shutdown::Write)?;
+            self.write_closed = true;
+        }
+        Ok(written)
+    }
+
+    fn write_outbound(&mut self) -> io::Result<usize> {
+        let mut written = 0;
+        let res = loop {
+            if written == self.outbound.len() {
+                break Ok(written);
+            }
+
+            match self.socket.write(&self.outbound[written..]) {
+                Ok(0) => break Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => written += n,
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break Ok(written),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => break Err(e),
+            }
+        };
+
+        if written > 0 {
+            self.last_activity = Instant::now();
+        }
+        self.outbound.drain(..written);
+        res
+    }
+
+    pub(crate) fn set_idle_timer(&mut self, id: TimerId) {
+        self.idle_timer = Some(id);
+    }
+
+    pub(crate) fn track_timer(&mut self, id: TimerId) {
+        self.timers.push(id);
+    }
+
+    /// Forgets a timer that fired or was cancelled. Returns `false` if the
+    /// timer doesn't belong to this connection.
+    pub(crate) fn untrack_timer(&mut self, id: TimerId) -> bool {
+        if self.idle_timer == Some(id) {
+            self.idle_timer = None;
+            return true;
+        }
+        match self.timers.iter().position(|timer| *timer == id) {
+            Some(index) => {
+                self.timers.swap_remove(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns all the pending timers, including the idle one.
+    pub(crate) fn take_timers(&mut self) -> Vec<TimerId> {
+        let mut timers = std::mem::take(&mut self.timers);
+        timers.extend(self.idle_timer.take());
+        timers
+    }
+
+    pub(crate) fn last_activity(&self) -> Instant {
+        self.last_activity
+    }
+
+    pub(crate) fn has_pending_writes(&self) -> bool {
+        !self.outbound.is_empty()
+    }
+
+    pub(crate) fn is_read_closed(&self) -> bool {
+        self.read_closed
+    }
+
+    /// Returns whether the connection has nothing left to do: the peer won't
+    /// send anything anymore, everything queued for it was written and no
+    /// timer may queue more.
+    pub(crate) fn is_finished(&self) -> bool {
+        self.read_closed && !self.has_pending_writes() && self.timers.is_empty()
+    }
+
+    /// The events to poll for: WRITABLE only matters while there is something
+    /// to write, otherwise every poll would report the idle socket writable.
+    /// READABLE is dropped once the peer closed its side.
+    fn interest(&self) -> Interest {
+        match (self.read_closed, self.has_pending_writes()) {
+            (false, true) => Interest::READABLE | Interest::WRITABLE,
+            (true, true) => Interest::WRITABLE,
+            // Only timers are pending, or the connection is finished and about
+            // to be closed: mio needs some interest, READABLE is harmless.
+            _ => Interest::READABLE,
+        }
+    }
+}
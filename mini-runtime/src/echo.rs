@@ -0,0 +1,58 @@
+use crate::codec::LinesCodec;
+use crate::handler::{Context, Handler};
+use std::time::Duration;
+use tutorial_util::Result;
+
+/// Request line answered with the metrics instead of an echo.
+pub const METRICS_REQUEST: &str = "GET /metrics";
+
+/// Longest line a client may send; reaching it without a line terminator
+/// closes the connection.
+pub const MAX_LINE_LENGTH: usize = 64 * 1024;
+
+/// Echoes every line back to the client.
+///
+/// `delay <millis> <text>` echoes `text` after the given delay, without
+/// blocking the other clients or the following lines. `bye` is echoed, then
+/// the server half-closes the connection.
+#[derive(Debug, Default)]
+pub struct Echo {
+    /// Whether a client sending `GET /metrics` receives the metrics of the
+    /// server instead of its own request.
+    pub expose_metrics: bool,
+}
+
+impl Handler for Echo {
+    type Codec = LinesCodec;
+    type Timer = String;
+
+    fn new_codec(&mut self) -> LinesCodec {
+        LinesCodec::new_with_max_length(MAX_LINE_LENGTH)
+    }
+
+    fn on_frame(&mut self, line: String, cx: &mut Context<'_, LinesCodec, String>) -> Result<()> {
+        println!("📨 Received from {:?}: {}", cx.token(), line);
+
+        if self.expose_metrics && line == METRICS_REQUEST {
+            let metrics = cx.metrics().to_string();
+            cx.send(metrics.trim_end())?;
+        } else if let Some(delayed) = line.strip_prefix("delay ") {
+            let (millis, text) = delayed.split_once(' ').unwrap_or((delayed, ""));
+            cx.set_timer(Duration::from_millis(millis.parse()?), text.to_string());
+        } else {
+            cx.send(&line)?;
+        }
+
+        // The handler may half-close the connection: the echo is still
+        // written, then the client reads the end of the stream.
+        if line == "bye" {
+            cx.shutdown_write();
+        }
+        Ok(())
+    }
+
+    fn on_timer(&mut self, text: String, cx: &mut Context<'_, LinesCodec, String>) -> Result<()> {
+        cx.send(text)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,134 @@
+use crate::codec::{Decoder, Encoder};
+use crate::connection::Connection;
+use crate::metrics::Metrics;
+use crate::timer::{ConnectionTimers, Timeout, TimerId};
+use mio::Token;
+use std::time::{Duration, Instant};
+use tutorial_util::Result;
+
+/// The protocol served by a `MiniRuntime`.
+///
+/// The runtime owns the sockets: it reads, buffers and writes the bytes and
+/// decodes them with the handler's codec. The handler only sees whole frames
+/// and answers them through the `Context`.
+pub trait Handler {
+    /// The codec framing the connections.
+    type Codec: Decoder;
+
+    /// The data attached to the timers set by the handler, handed back to
+    /// `on_timer` when they fire.
+    type Timer;
+
+    /// Returns the codec of a newly accepted connection.
+    fn new_codec(&mut self) -> Self::Codec;
+
+    /// Handles a frame decoded from the connection of `cx`.
+    ///
+    /// Returning an error closes the connection.
+    fn on_frame(
+        &mut self,
+        frame: <Self::Codec as Decoder>::Item,
+        cx: &mut Context<'_, Self::Codec, Self::Timer>,
+    ) -> Result<()>;
+
+    /// Handles a timer set with `Context::set_timer` on the connection of
+    /// `cx`. Timers of a closed connection never fire.
+    ///
+    /// Returning an error closes the connection.
+    fn on_timer(
+        &mut self,
+        timer: Self::Timer,
+        cx: &mut Context<'_, Self::Codec, Self::Timer>,
+    ) -> Result<()> {
+        let _ = (timer, cx);
+        Ok(())
+    }
+
+    /// Called once the connection identified by `token` is closed, to drop
+    /// the state the handler keeps for it. The token may be reused by a
+    /// connection accepted afterwards.
+    fn on_close(&mut self, token: Token) {
+        let _ = token;
+    }
+}
+
+/// The connection a frame was received on, or a timer fired for, as seen by a
+/// `Handler`.
+pub struct Context<'a, C, T = ()> {
+    token: Token,
+    connection: &'a mut Connection<C>,
+    metrics: &'a Metrics,
+    timers: &'a mut ConnectionTimers<T>,
+}
+
+impl<'a, C, T> Context<'a, C, T> {
+    pub(crate) fn new(
+        token: Token,
+        connection: &'a mut Connection<C>,
+        metrics: &'a Metrics,
+        timers: &'a mut ConnectionTimers<T>,
+    ) -> Self {
+        Self {
+            token,
+            connection,
+            metrics,
+            timers,
+        }
+    }
+
+    /// Returns the token identifying the connection.
+    pub fn token(&self) -> Token {
+        self.token
+    }
+
+    /// Returns the counters of the runtime.
+    pub fn metrics(&self) -> &Metrics {
+        self.metrics
+    }
+
+    /// Returns the codec of the connection.
+    ///
+    /// Frames are decoded one at a time: changing the codec's state, e.g. on a
+    /// protocol upgrade, affects how the bytes following the current frame are
+    /// decoded.
+    pub fn codec_mut(&mut self) -> &mut C {
+        self.connection.codec_mut()
+    }
+
+    /// Encodes `item` to be written to the peer once the handler returns.
+    pub fn send<I>(&mut self, item: I) -> Result<(), C::Error>
+    where
+        C: Encoder<I>,
+    {
+        self.connection.send(item)
+    }
+
+    /// Calls `Handler::on_timer` with `timer` for this connection once `delay`
+    /// elapsed, e.g. to send a delayed response.
+    pub fn set_timer(&mut self, delay: Duration, timer: T) -> TimerId {
+        let id = self.timers.insert(
+            Instant::now() + delay,
+            (self.token, Timeout::Handler(timer)),
+        );
+        self.connection.track_timer(id);
+        id
+    }
+
+    /// Cancels a timer of this connection that did not fire yet, returning its
+    /// data.
+    pub fn cancel_timer(&mut self, id: TimerId) -> Option<T> {
+        if !self.connection.untrack_timer(id) {
+            return None;
+        }
+        match self.timers.remove(id)? {
+            (_, Timeout::Handler(timer)) => Some(timer),
+            (_, Timeout::Idle) => unreachable!("idle timers are not handed out"),
+        }
+    }
+
+    /// Half-closes the connection: the frames already sent are written, then
+    /// the peer reads the end of the stream.
+    pub fn shutdown_write(&mut self) {
+        self.connection.shutdown_write();
+    }
+}
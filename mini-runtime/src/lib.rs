@@ -0,0 +1,28 @@
+//! A single-threaded reactor built directly on mio.
+//!
+//! `MiniRuntime` accepts connections, buffers reads and writes, and hands the
+//! frames decoded by a codec to a `Handler` implementing the protocol. `Echo`
+//! is the handler of the echo server bin.
+
+pub mod codec;
+
+mod clients;
+mod connection;
+mod echo;
+mod handler;
+mod metrics;
+mod mini_runtime;
+mod remote;
+mod shutdown;
+mod timer;
+
+pub use echo::{Echo, MAX_LINE_LENGTH, METRICS_REQUEST};
+pub use handler::{Context, Handler};
+pub use metrics::Metrics;
+pub use mini_runtime::{Config, MiniRuntime};
+pub use remote::{EventLoop, Remote};
+pub use shutdown::{ShutdownHandle, on_sigint};
+pub use timer::TimerId;
+
+/// Identifies a connection, see `Context::token`.
+pub use mio::Token;
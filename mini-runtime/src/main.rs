@@ -1,10 +1,29 @@
-use crate::mini_runtime::MiniRuntime;
-use std::error::Error;
+use mini_runtime::{Config, Echo, EventLoop, MiniRuntime};
+use std::io::{self, BufRead};
+use std::thread;
+use tutorial_util::Result;
 
-mod mini_runtime;
-
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> Result<()> {
     let address = "127.0.0.1:9000".parse()?;
-    let mut runtime = MiniRuntime::new(address)?;
+    let echo = Echo {
+        expose_metrics: true,
+    };
+    let mut runtime = MiniRuntime::new(address, Config::default(), echo)?;
+    mini_runtime::on_sigint(runtime.shutdown_handle())?;
+
+    // Every line typed in the server's terminal is sent to all the clients.
+    let remote = runtime.remote();
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines().map_while(Result::ok) {
+            let job = move |event_loop: &mut EventLoop<'_, Echo>| {
+                let sent = event_loop.broadcast(&line);
+                println!("📣 Broadcast to {} client(s): {}", sent, line);
+            };
+            if remote.execute(job).is_err() {
+                break;
+            }
+        }
+    });
+
     runtime.run()
 }
@@ -0,0 +1,29 @@
+use std::fmt;
+
+/// Counters describing the activity of a `MiniRuntime`.
+///
+/// Formatted with `Display`, the metrics use the Prometheus text format so
+/// the echo server can answer a `/metrics` scrape.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Metrics {
+    /// Connections accepted since the start.
+    pub accepted_connections: u64,
+    /// Bytes received from the clients.
+    pub bytes_read: u64,
+    /// Bytes written to the clients.
+    pub bytes_written: u64,
+    /// Connections currently open.
+    pub active_clients: u64,
+    /// Times the runtime waited for events.
+    pub poll_iterations: u64,
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "accepted_connections {}", self.accepted_connections)?;
+        writeln!(f, "bytes_read {}", self.bytes_read)?;
+        writeln!(f, "bytes_written {}", self.bytes_written)?;
+        writeln!(f, "active_clients {}", self.active_clients)?;
+        writeln!(f, "poll_iterations {}", self.poll_iterations)
+    }
+}
@@ -1,23 +1,113 @@
-use mio::net::{TcpListener, TcpStream};
-use mio::{Events, Interest, Poll, Token};
-use std::collections::HashMap;
-use std::error::Error;
-use std::io::{Read, Write};
+use crate::clients::Clients;
+use crate::codec::Decoder;
+use crate::connection::{Connection, ReadOutcome};
+use crate::handler::{Context, Handler};
+use crate::metrics::Metrics;
+use crate::remote::{EventLoop, Remote};
+use crate::shutdown::ShutdownHandle;
+use crate::timer::{ConnectionTimers, Timeout, Timers};
+use mio::event::Event;
+use mio::net::TcpListener;
+use mio::{Events, Interest, Poll, Token, Waker};
+use std::fmt;
+use std::io;
 use std::net::SocketAddr;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tutorial_util::Result;
 
 const SERVER: Token = Token(0);
+/// Token of the waker interrupting `poll` on shutdown or when a `Remote`
+/// queued a job. Client tokens are slab indexes right above `SERVER`, so they
+/// never reach it.
+const WAKER: Token = Token(usize::MAX);
 
-pub(crate) struct MiniRuntime {
+/// How long the listener stays out of the poll after `accept` failed, e.g.
+/// because the process ran out of file descriptors.
+const ACCEPT_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Tunables of the server.
+#[derive(Debug, Clone)]
+pub struct Config {
+    /// Connections without any read or write for this long are closed. This
+    /// catches peers that disappeared without sending a FIN, e.g. after a
+    /// crash or a network failure.
+    pub idle_timeout: Duration,
+
+    /// How long a shutdown waits for the pending writes to be flushed before
+    /// the remaining connections are closed anyway.
+    pub shutdown_grace: Duration,
+
+    /// Maximum number of open connections. Once reached, the listener is
+    /// removed from the poll and new connections wait in the kernel's accept
+    /// backlog until a client disconnects.
+    pub max_connections: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            idle_timeout: Duration::from_secs(60),
+            shutdown_grace: Duration::from_secs(5),
+            max_connections: 1024,
+        }
+    }
+}
+
+/// An event loop serving the protocol implemented by `H`.
+pub struct MiniRuntime<H: Handler> {
     poll: Poll,
     events: Events,
     listener: TcpListener,
-    clients: HashMap<Token, TcpStream>,
-    next_token: usize,
+    handler: H,
+    clients: Clients<H::Codec>,
+    timers: ConnectionTimers<H::Timer>,
+    config: Config,
+    shutdown: ShutdownHandle,
+    remote: Remote<H>,
+
+    /// Whether the listener is registered with the poll.
+    accepting: bool,
+
+    /// When the listener is registered again after an accept error.
+    accept_retry: Option<Instant>,
+
+    metrics: Metrics,
 }
 
-impl MiniRuntime {
-    pub fn new(address: SocketAddr) -> Result<Self, Box<dyn Error>> {
+/// The readiness reported by an event, copied out of `Events` so that the
+/// runtime can be borrowed mutably while handling it.
+#[derive(Clone, Copy)]
+struct Readiness {
+    token: Token,
+    readable: bool,
+    writable: bool,
+    closed: bool,
+}
+
+impl From<&Event> for Readiness {
+    fn from(event: &Event) -> Self {
+        Self {
+            token: event.token(),
+            readable: event.is_readable(),
+            writable: event.is_writable(),
+            closed: event.is_read_closed() || event.is_error(),
+        }
+    }
+}
+
+impl<H> MiniRuntime<H>
+where
+    H: Handler,
+    <H::Codec as Decoder>::Error: fmt::Display,
+{
+    /// Binds the listener to `address` and sets up the event loop.
+    pub fn new(address: SocketAddr, config: Config, handler: H) -> Result<Self> {
+        assert!(
+            config.max_connections > 0,
+            "max_connections must be positive"
+        );
+
         let poll = Poll::new()?;
         let mut listener = TcpListener::bind(address)?;
 
@@ -25,81 +115,459 @@ impl MiniRuntime {
             .register(&mut listener, SERVER, Interest::READABLE)?;
 
         let events = Events::with_capacity(128);
+        // A poll supports a single waker, shared by all the remote handles.
+        let waker = Arc::new(Waker::new(poll.registry(), WAKER)?);
+        let shutdown = ShutdownHandle::new(waker.clone());
+        let remote = Remote::new(waker);
 
-        println!("🟢 Echo server listening on {}", address);
+        println!("🟢 Listening on {}", listener.local_addr()?);
 
         Ok(Self {
             poll,
             events,
             listener,
-            clients: HashMap::new(),
-            next_token: SERVER.0 + 1,
+            handler,
+            clients: Clients::new(),
+            timers: Timers::new(),
+            config,
+            shutdown,
+            remote,
+            accepting: true,
+            accept_retry: None,
+            metrics: Metrics::default(),
         })
     }
 
-    pub(crate) fn run(&mut self) -> Result<(), Box<dyn Error>> {
+    /// Binds the listener to a port of the loopback interface picked by the
+    /// OS, see `local_addr`. Tests run servers side by side this way.
+    pub fn new_ephemeral(config: Config, handler: H) -> Result<Self> {
+        Self::new(SocketAddr::from(([127, 0, 0, 1], 0)), config, handler)
+    }
+
+    /// Returns the address the listener is bound to.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Returns the handler, e.g. to inspect its state between two
+    /// `run_until`.
+    pub fn handler(&self) -> &H {
+        &self.handler
+    }
+
+    /// Returns a snapshot of the server's counters.
+    pub fn metrics(&self) -> Metrics {
+        self.metrics
+    }
+
+    /// Returns a handle that stops `run` from another thread or a signal
+    /// handler.
+    pub fn shutdown_handle(&self) -> ShutdownHandle {
+        self.shutdown.clone()
+    }
+
+    /// Returns a handle that runs jobs on the event loop from other threads.
+    pub fn remote(&self) -> Remote<H> {
+        self.remote.clone()
+    }
+
+    /// Serves the clients until a shutdown is requested, then shuts down
+    /// gracefully.
+    pub fn run(&mut self) -> Result<()> {
         println!(
-            "🟢 Mini Tokio Echo Server running on {:?}",
+            "🟢 Mini Tokio server running on {:?}",
             self.listener.local_addr()?
         );
-        loop {
-            self.poll
-                .poll(&mut self.events, Some(Duration::from_secs(10)))?;
+        self.run_until(|_| false)
+    }
+
+    /// Serves the clients until `condition` returns `true`, or until a
+    /// shutdown is requested.
+    ///
+    /// The condition is checked before the first poll and after the events
+    /// and timers of every poll were handled. Once it holds, `run_until`
+    /// returns with the connections still open: calling it again resumes the
+    /// loop. A shutdown closes them, like `run`.
+    ///
+    /// Tests drive the runtime on their own thread this way, the clients on
+    /// other threads, and stop as soon as the server saw what they expect:
+    ///
+    /// ```no_run
+    /// # use mini_runtime::{Config, Echo, MiniRuntime};
+    /// # let mut runtime = MiniRuntime::new_ephemeral(Config::default(), Echo::default())?;
+    /// runtime.run_until(|runtime| runtime.metrics().accepted_connections == 2)?;
+    /// # Ok::<_, tutorial_util::Error>(())
+    /// ```
+    ///
+    /// The loop only wakes up on events and timers: a condition that becomes
+    /// true without either, e.g. a flag set by another thread, is only seen
+    /// on the next wakeup. Executing a no-op job with a `Remote` wakes it.
+    pub fn run_until<F>(&mut self, mut condition: F) -> Result<()>
+    where
+        F: FnMut(&Self) -> bool,
+    {
+        while !self.shutdown.is_requested() {
+            if condition(self) {
+                return Ok(());
+            }
+
+            // Block until the next timer or accept retry is due, or
+            // indefinitely without either: the shutdown waker interrupts the
+            // wait anyway.
+            let deadline = match (self.timers.next_deadline(), self.accept_retry) {
+                (Some(timer), Some(retry)) => Some(timer.min(retry)),
+                (timer, retry) => timer.or(retry),
+            };
+            let timeout =
+                deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+            self.poll(timeout)?;
 
             // ✅ Workaround for borrow checker
-            let tokens: Vec<Token> = self.events.iter().map(|event| event.token()).collect();
+            let ready: Vec<Readiness> = self.events.iter().map(Readiness::from).collect();
 
-            for token in tokens {
-                match token {
+            for event in ready {
+                match event.token {
                     SERVER => self.accept_client()?,
-                    token => self.handle_client(token)?,
+                    // A shutdown is checked by the loop condition.
+                    WAKER => self.run_remote_jobs()?,
+                    _ => self.handle_client(event)?,
                 }
             }
+
+            self.fire_timers()?;
+            self.retry_accepting();
+        }
+
+        self.shutdown_gracefully()
+    }
+
+    /// Runs the jobs queued by `Remote` handles.
+    fn run_remote_jobs(&mut self) -> Result<()> {
+        for job in self.remote.take_jobs() {
+            let mut event_loop = EventLoop::new(
+                &mut self.handler,
+                &mut self.clients,
+                &mut self.timers,
+                &self.metrics,
+            );
+            job(&mut event_loop);
+
+            let mut touched = event_loop.into_touched();
+            touched.sort_unstable();
+            touched.dedup();
+            for token in touched {
+                self.flush_client(token, false)?;
+            }
         }
+        Ok(())
     }
 
-    fn handle_client(&mut self, token: Token) -> Result<(), Box<dyn Error>> {
-        if let Some(socket) = self.clients.get_mut(&token) {
-            // Read data from client
-            let mut buffer = [0; 1024];
-            match socket.read(&mut buffer) {
-                Ok(0) => {
-                    println!("🔌 Connection closed: {:?}", token);
-                    self.clients.remove(&token);
+    /// Runs the timers whose deadline passed.
+    fn fire_timers(&mut self) -> Result<()> {
+        let now = Instant::now();
+        while let Some((id, (token, timeout))) = self.timers.pop_expired(now) {
+            let Some(client) = self.clients.get_mut(token) else {
+                continue;
+            };
+            client.untrack_timer(id);
+
+            match timeout {
+                // The timer is not moved on every read or write: when it
+                // fires, it is set again if the connection was active since.
+                Timeout::Idle => {
+                    let deadline = client.last_activity() + self.config.idle_timeout;
+                    if deadline <= now {
+                        println!("⏰ Connection idle, closing: {:?}", token);
+                        self.close_client(token);
+                    } else {
+                        let id = self.timers.insert(deadline, (token, Timeout::Idle));
+                        client.set_idle_timer(id);
+                    }
                 }
-                Ok(n) => {
-                    let received = &buffer[..n];
-                    println!(
-                        "📨 Received from {:?}: {}",
-                        token,
-                        String::from_utf8_lossy(received)
-                    );
-                    socket.write_all(received)?; // Echo back
+                Timeout::Handler(timer) => {
+                    let mut cx = Context::new(token, client, &self.metrics, &mut self.timers);
+                    if let Err(e) = self.handler.on_timer(timer, &mut cx) {
+                        eprintln!("❌ Handler error on {:?}: {}", token, e);
+                        self.close_client(token);
+                        continue;
+                    }
+                    self.flush_client(token, false)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Stops accepting connections, gives the clients `shutdown_grace` to
+    /// receive the data already queued for them and closes every connection.
+    ///
+    /// Incoming data is no longer read, so nothing new gets queued: the
+    /// runtime only waits for WRITABLE events until the buffers are empty.
+    fn shutdown_gracefully(&mut self) -> Result<()> {
+        println!(
+            "🛑 Shutting down, {} connection(s) open",
+            self.clients.len()
+        );
+        self.pause_accepting()?;
+
+        let deadline = Instant::now() + self.config.shutdown_grace;
+        loop {
+            let flushed: Vec<Token> = self
+                .clients
+                .iter()
+                .filter(|(_, client)| !client.has_pending_writes())
+                .map(|(token, _)| token)
+                .collect();
+            for token in flushed {
+                self.close_client(token);
+            }
+
+            let now = Instant::now();
+            if self.clients.is_empty() || now >= deadline {
+                break;
+            }
+
+            self.poll(Some(deadline - now))?;
+
+            let writable: Vec<Token> = self
+                .events
+                .iter()
+                .filter(|event| event.is_writable() || event.is_error())
+                .map(|event| event.token())
+                .collect();
+            for token in writable {
+                let Some(client) = self.clients.get_mut(token) else {
+                    continue;
+                };
+                match client.flush() {
+                    Ok(written) => self.metrics.bytes_written += written as u64,
+                    Err(e) => {
+                        eprintln!("❌ Write error: {}", e);
+                        self.close_client(token);
+                    }
+                }
+            }
+        }
+
+        for token in self.clients.tokens() {
+            println!("⏰ Pending writes not flushed, closing: {:?}", token);
+            self.close_client(token);
+        }
+
+        println!("🛑 Server stopped\n{}", self.metrics());
+        Ok(())
+    }
+
+    /// Waits for events, treating a signal interrupting the wait (EINTR) as a
+    /// wakeup without events.
+    fn poll(&mut self, timeout: Option<Duration>) -> io::Result<()> {
+        self.metrics.poll_iterations += 1;
+        match self.poll.poll(&mut self.events, timeout) {
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+                self.events.clear();
+                Ok(())
+            }
+            res => res,
+        }
+    }
+
+    fn handle_client(&mut self, event: Readiness) -> Result<()> {
+        let token = event.token;
+        // A token may already belong to a new connection when an event for the
+        // closed one comes later in the same batch; that only causes a
+        // spurious wakeup, reads and writes then just hit `WouldBlock`.
+        let Some(client) = self.clients.get_mut(token) else {
+            return Ok(());
+        };
+
+        if (event.readable || event.closed) && !client.is_read_closed() {
+            match client.read_available() {
+                Ok((read, outcome)) => {
+                    self.metrics.bytes_read += read as u64;
+                    if let ReadOutcome::Closed = outcome {
+                        println!("🔌 Client closed its write side: {:?}", token);
+                    }
                 }
-                Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => {}
                 Err(e) => {
                     eprintln!("❌ Read error: {}", e);
-                    self.clients.remove(&token);
+                    self.close_client(token);
+                    return Ok(());
+                }
+            }
+
+            // Handle every complete frame, a partial one stays buffered until
+            // the rest of it arrives.
+            loop {
+                let frame = match client.next_frame() {
+                    Ok(Some(frame)) => frame,
+                    Ok(None) => break,
+                    Err(e) => {
+                        eprintln!("❌ Invalid request from {:?}: {}", token, e);
+                        self.close_client(token);
+                        return Ok(());
+                    }
+                };
+
+                let mut cx = Context::new(token, client, &self.metrics, &mut self.timers);
+                if let Err(e) = self.handler.on_frame(frame, &mut cx) {
+                    eprintln!("❌ Handler error on {:?}: {}", token, e);
+                    self.close_client(token);
+                    return Ok(());
                 }
             }
         }
+
+        self.flush_client(token, event.writable)
+    }
+
+    /// Writes what the handler queued, closes the connection if it has nothing
+    /// left to do, and updates its interest otherwise.
+    fn flush_client(&mut self, token: Token, writable: bool) -> Result<()> {
+        let Some(client) = self.clients.get_mut(token) else {
+            return Ok(());
+        };
+
+        // Flush on WRITABLE events, and right after handling: most of the time
+        // the socket accepts the whole response and no WRITABLE interest is
+        // needed. A half-closed client still gets the responses queued for it.
+        if writable || client.has_pending_writes() {
+            match client.flush() {
+                Ok(written) => self.metrics.bytes_written += written as u64,
+                Err(e) => {
+                    eprintln!("❌ Write error: {}", e);
+                    self.close_client(token);
+                    return Ok(());
+                }
+            }
+        }
+
+        if client.is_finished() {
+            println!("🔌 Connection closed: {:?}", token);
+            self.close_client(token);
+            return Ok(());
+        }
+
+        client.update_interest(self.poll.registry(), token)?;
+        Ok(())
+    }
+
+    fn close_client(&mut self, token: Token) {
+        if let Some(mut client) = self.clients.remove(token) {
+            // The socket is closed when dropped, which also removes it from
+            // the poll; deregistering explicitly keeps the poll state obvious.
+            let _ = client.deregister(self.poll.registry());
+            self.metrics.active_clients -= 1;
+
+            for id in client.take_timers() {
+                self.timers.remove(id);
+            }
+            self.handler.on_close(token);
+        }
+
+        if !self.accepting
+            && !self.shutdown.is_requested()
+            && self.clients.len() < self.config.max_connections
+        {
+            println!("🟢 Below the connection limit, accepting again");
+            if let Err(e) = self.resume_accepting() {
+                eprintln!("❌ Failed to resume accepting: {}", e);
+            }
+        }
+    }
+
+    /// Removes the listener from the poll: pending connections stay queued in
+    /// the kernel instead of being accepted.
+    fn pause_accepting(&mut self) -> io::Result<()> {
+        if self.accepting {
+            self.poll.registry().deregister(&mut self.listener)?;
+            self.accepting = false;
+        }
+        Ok(())
+    }
+
+    /// Registers the listener again. Connections that queued up in the
+    /// meantime are reported by the first poll after the registration.
+    fn resume_accepting(&mut self) -> io::Result<()> {
+        if !self.accepting {
+            self.poll
+                .registry()
+                .register(&mut self.listener, SERVER, Interest::READABLE)?;
+            self.accepting = true;
+        }
         Ok(())
     }
 
-    fn accept_client(&mut self) -> Result<(), Box<dyn Error>> {
-        // Accept new client
-        let (mut socket, addr) = self.listener.accept()?;
-        println!("✅ New connection from {}", addr);
+    /// Registers the listener again once the backoff after an accept error
+    /// expired, unless the connection limit or a shutdown keeps it paused.
+    fn retry_accepting(&mut self) {
+        let Some(retry) = self.accept_retry else {
+            return;
+        };
+        if retry > Instant::now() {
+            return;
+        }
+        self.accept_retry = None;
 
-        let token = Token(self.next_token);
-        self.next_token += 1;
-        self.poll.registry().register(
-            &mut socket,
-            token,
-            Interest::READABLE.add(Interest::WRITABLE),
-        )?;
+        if !self.shutdown.is_requested()
+            && self.clients.len() < self.config.max_connections
+            && let Err(e) = self.resume_accepting()
+        {
+            eprintln!("❌ Failed to resume accepting: {}", e);
+        }
+    }
 
-        self.clients.insert(token, socket);
+    /// Accepts every pending connection.
+    ///
+    /// Like the client sockets, the listener is edge-triggered: one READABLE
+    /// event may stand for several queued connections, so `accept` is called
+    /// until it returns `WouldBlock` or the connection limit is reached.
+    fn accept_client(&mut self) -> Result<()> {
+        while self.accepting {
+            let (socket, addr) = match self.listener.accept() {
+                Ok(accepted) => accepted,
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                // The peer gave up before we got to it, try the next one.
+                Err(ref e) if e.kind() == io::ErrorKind::ConnectionAborted => continue,
+                Err(e) => {
+                    // E.g. out of file descriptors. The connection stays in
+                    // the backlog, so accepting again right away fails the
+                    // same way, and returning would miss it: the listener is
+                    // edge-triggered and reports no new event for it. Keep
+                    // serving the existing clients and register the listener
+                    // again a bit later, which reports the backlog.
+                    eprintln!("❌ Accept error, retrying in {:?}: {}", ACCEPT_BACKOFF, e);
+                    self.pause_accepting()?;
+                    self.accept_retry = Some(Instant::now() + ACCEPT_BACKOFF);
+                    return Ok(());
+                }
+            };
+            println!("✅ New connection from {}", addr);
+
+            let token = self.clients.next_token();
+
+            // Only READABLE: WRITABLE is added while there is data to flush.
+            let mut client = Connection::new(socket, self.handler.new_codec());
+            client.register(self.poll.registry(), token)?;
+
+            let idle_deadline = Instant::now() + self.config.idle_timeout;
+            let idle_timer = self.timers.insert(idle_deadline, (token, Timeout::Idle));
+            client.set_idle_timer(idle_timer);
+
+            let inserted = self.clients.insert(client);
+            debug_assert_eq!(inserted, token);
+            self.metrics.accepted_connections += 1;
+            self.metrics.active_clients += 1;
+
+            if self.clients.len() >= self.config.max_connections {
+                println!(
+                    "⛔ Connection limit of {} reached, pausing accept",
+                    self.config.max_connections
+                );
+                self.pause_accepting()?;
+            }
+        }
         Ok(())
     }
 }
@@ -0,0 +1,143 @@
+use crate::clients::Clients;
+use crate::codec::Encoder;
+use crate::handler::{Context, Handler};
+use crate::metrics::Metrics;
+use crate::timer::ConnectionTimers;
+use mio::{Token, Waker};
+use std::fmt;
+use std::io;
+use std::sync::{Arc, Mutex};
+
+/// A closure injected into the event loop by a `Remote`.
+type Job<H> = Box<dyn FnOnce(&mut EventLoop<'_, H>) + Send>;
+
+/// Runs closures on the thread of a running `MiniRuntime`.
+///
+/// The handle can be cloned and sent to other threads. `execute` queues a job
+/// and wakes the poll through the runtime's `mio::Waker`; the loop then runs
+/// the queued jobs with access to the handler and the connections, e.g. to
+/// broadcast a message to every client.
+pub struct Remote<H: Handler> {
+    inner: Arc<Inner<H>>,
+}
+
+struct Inner<H: Handler> {
+    jobs: Mutex<Vec<Job<H>>>,
+    waker: Arc<Waker>,
+}
+
+impl<H: Handler> Remote<H> {
+    pub(crate) fn new(waker: Arc<Waker>) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                jobs: Mutex::new(Vec::new()),
+                waker,
+            }),
+        }
+    }
+
+    /// Runs `job` on the event loop, after the events of the current poll
+    /// iteration were handled.
+    ///
+    /// Jobs queued once the runtime stopped are never run.
+    pub fn execute<F>(&self, job: F) -> io::Result<()>
+    where
+        F: FnOnce(&mut EventLoop<'_, H>) + Send + 'static,
+    {
+        self.inner.jobs.lock().unwrap().push(Box::new(job));
+        self.inner.waker.wake()
+    }
+
+    /// Removes the queued jobs, in the order they were queued.
+    pub(crate) fn take_jobs(&self) -> Vec<Job<H>> {
+        std::mem::take(&mut *self.inner.jobs.lock().unwrap())
+    }
+}
+
+impl<H: Handler> Clone for Remote<H> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<H: Handler> fmt::Debug for Remote<H> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Remote").finish_non_exhaustive()
+    }
+}
+
+/// The event loop, as seen by a job injected through a `Remote`.
+pub struct EventLoop<'a, H: Handler> {
+    handler: &'a mut H,
+    clients: &'a mut Clients<H::Codec>,
+    timers: &'a mut ConnectionTimers<H::Timer>,
+    metrics: &'a Metrics,
+
+    /// The connections the job may have sent data to, flushed once it
+    /// returns.
+    touched: Vec<Token>,
+}
+
+impl<'a, H: Handler> EventLoop<'a, H> {
+    pub(crate) fn new(
+        handler: &'a mut H,
+        clients: &'a mut Clients<H::Codec>,
+        timers: &'a mut ConnectionTimers<H::Timer>,
+        metrics: &'a Metrics,
+    ) -> Self {
+        Self {
+            handler,
+            clients,
+            timers,
+            metrics,
+            touched: Vec::new(),
+        }
+    }
+
+    pub fn handler(&mut self) -> &mut H {
+        self.handler
+    }
+
+    /// Returns the tokens of the open connections.
+    pub fn tokens(&self) -> Vec<Token> {
+        self.clients.tokens()
+    }
+
+    /// Calls `f` with the connection of `token`, returns `None` if it is
+    /// closed.
+    pub fn with_client<R>(
+        &mut self,
+        token: Token,
+        f: impl FnOnce(&mut Context<'_, H::Codec, H::Timer>) -> R,
+    ) -> Option<R> {
+        let connection = self.clients.get_mut(token)?;
+        self.touched.push(token);
+        let mut cx = Context::new(token, connection, self.metrics, self.timers);
+        Some(f(&mut cx))
+    }
+
+    /// Sends `item` to every open connection, returns how many connections it
+    /// was sent to.
+    pub fn broadcast<I>(&mut self, item: I) -> usize
+    where
+        I: Clone,
+        H::Codec: Encoder<I>,
+        <H::Codec as Encoder<I>>::Error: fmt::Display,
+    {
+        let mut sent = 0;
+        for token in self.tokens() {
+            match self.with_client(token, |cx| cx.send(item.clone())) {
+                Some(Ok(())) => sent += 1,
+                Some(Err(e)) => eprintln!("❌ Failed to broadcast to {:?}: {}", token, e),
+                None => {}
+            }
+        }
+        sent
+    }
+
+    pub(crate) fn into_touched(self) -> Vec<Token> {
+        self.touched
+    }
+}
@@ -0,0 +1,46 @@
+use mio::Waker;
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Asks a running `MiniRuntime` to stop.
+///
+/// The handle can be cloned and moved to other threads or into a signal
+/// handler. Requesting a shutdown sets a flag and wakes the poll through a
+/// `mio::Waker`, so `run` notices it even while blocked without any I/O.
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    requested: Arc<AtomicBool>,
+    waker: Arc<Waker>,
+}
+
+impl ShutdownHandle {
+    pub(crate) fn new(waker: Arc<Waker>) -> Self {
+        Self {
+            requested: Arc::new(AtomicBool::new(false)),
+            waker,
+        }
+    }
+
+    /// Requests the runtime to stop accepting connections, flush the pending
+    /// writes and close every client.
+    ///
+    /// Only an atomic store and a write to the waker's eventfd happen here,
+    /// both of which are safe to do from a signal handler.
+    pub fn shutdown(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        let _ = self.waker.wake();
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+}
+
+/// Requests a shutdown through `handle` when the process receives SIGINT.
+pub fn on_sigint(handle: ShutdownHandle) -> io::Result<()> {
+    // SAFETY: the action only performs async-signal-safe operations, see
+    // `ShutdownHandle::shutdown`.
+    unsafe { signal_hook_registry::register(libc::SIGINT, move || handle.shutdown()) }?;
+    Ok(())
+}
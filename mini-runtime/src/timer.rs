@@ -0,0 +1,76 @@
+use mio::Token;
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+/// Identifies a timer set with `Context::set_timer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimerId {
+    deadline: Instant,
+    /// Tells apart timers with the same deadline, fired in insertion order.
+    seq: u64,
+}
+
+impl TimerId {
+    /// Returns when the timer fires.
+    pub fn deadline(&self) -> Instant {
+        self.deadline
+    }
+}
+
+/// What a connection timer does when it fires.
+pub(crate) enum Timeout<T> {
+    /// Closes the connection unless it was active since the timer was set.
+    Idle,
+    /// Calls `Handler::on_timer` with the handler's data.
+    Handler(T),
+}
+
+/// The timers of all the connections, with the token of their connection.
+pub(crate) type ConnectionTimers<T> = Timers<(Token, Timeout<T>)>;
+
+/// The pending timers, ordered by deadline.
+///
+/// The earliest deadline bounds how long the runtime may block in `poll`, and
+/// the expired timers are popped after every poll. Inserting and removing a
+/// timer costs O(log n), finding the next deadline is O(log n) as well.
+pub(crate) struct Timers<T> {
+    entries: BTreeMap<TimerId, T>,
+    next_seq: u64,
+}
+
+impl<T> Timers<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+            next_seq: 0,
+        }
+    }
+
+    pub(crate) fn insert(&mut self, deadline: Instant, value: T) -> TimerId {
+        let id = TimerId {
+            deadline,
+            seq: self.next_seq,
+        };
+        self.next_seq += 1;
+        self.entries.insert(id, value);
+        id
+    }
+
+    pub(crate) fn remove(&mut self, id: TimerId) -> Option<T> {
+        self.entries.remove(&id)
+    }
+
+    pub(crate) fn next_deadline(&self) -> Option<Instant> {
+        self.entries.first_key_value().map(|(id, _)| id.deadline)
+    }
+
+    /// Removes and returns the earliest timer if its deadline is not after
+    /// `now`.
+    pub(crate) fn pop_expired(&mut self, now: Instant) -> Option<(TimerId, T)> {
+        if self.next_deadline()? <= now {
+            self.entries.pop_first()
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use mini_runtime::{Config, Echo, MiniRuntime};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::thread;
+use std::time::Duration;
+use tutorial_util::Error;
+
+/// A client fails instead of hanging if an answer never comes.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+fn runtime(expose_metrics: bool) -> MiniRuntime<Echo> {
+    MiniRuntime::new_ephemeral(Config::default(), Echo { expose_metrics }).unwrap()
+}
+
+/// A blocking client speaking lines.
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Client {
+    fn connect(address: SocketAddr) -> Client {
+        let stream = TcpStream::connect(address).unwrap();
+        stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+        Client {
+            writer: stream.try_clone().unwrap(),
+            reader: BufReader::new(stream),
+        }
+    }
+
+    fn send(&mut self, data: &str) {
+        self.writer.write_all(data.as_bytes()).unwrap();
+    }
+
+    /// Reads a line, without its terminator.
+    fn recv(&mut self) -> String {
+        let mut line = String::new();
+        self.reader.read_line(&mut line).unwrap();
+        assert!(line.ends_with('\n'), "incomplete line: {line:?}");
+        line.pop();
+        line
+    }
+
+    /// Reads until the server closes the connection.
+    fn recv_to_end(&mut self) -> String {
+        let mut rest = String::new();
+        self.reader.read_to_string(&mut rest).unwrap();
+        rest
+    }
+}
+
+/// Runs the runtime until `clients` clients came and left.
+fn serve(runtime: &mut MiniRuntime<Echo>, clients: u64) {
+    runtime
+        .run_until(|runtime| {
+            let metrics = runtime.metrics();
+            metrics.accepted_connections == clients && metrics.active_clients == 0
+        })
+        .unwrap();
+}
+
+#[test]
+fn echoes_lines_from_concurrent_clients() {
+    const CLIENTS: u64 = 20;
+
+    let mut runtime = runtime(false);
+    let address = runtime.local_addr().unwrap();
+
+    let clients: Vec<_> = (0..CLIENTS)
+        .map(|n| {
+            thread::spawn(move || {
+                let mut client = Client::connect(address);
+                for i in 0..5 {
+                    let line = format!("client {n} line {i}");
+                    client.send(&format!("{line}\n"));
+                    assert_eq!(client.recv(), line);
+                }
+            })
+        })
+        .collect();
+
+    serve(&mut runtime, CLIENTS);
+
+    for client in clients {
+        client.join().unwrap();
+    }
+
+    let metrics = runtime.metrics();
+    assert!(metrics.bytes_read > 0);
+    assert_eq!(metrics.bytes_read, metrics.bytes_written);
+}
+
+#[test]
+fn line_split_across_reads() {
+    let mut runtime = runtime(false);
+    let address = runtime.local_addr().unwrap();
+
+    let client = thread::spawn(move || {
+        let mut client = Client::connect(address);
+        client.send("hel");
+        thread::sleep(Duration::from_millis(50));
+        client.send("lo\r\nwor");
+        thread::sleep(Duration::from_millis(50));
+        client.send("ld\n");
+
+        assert_eq!(client.recv(), "hello");
+        assert_eq!(client.recv(), "world");
+    });
+
+    serve(&mut runtime, 1);
+    client.join().unwrap();
+}
+
+#[test]
+fn delayed_echo_does_not_block_the_next_lines() {
+    let mut runtime = runtime(false);
+    let address = runtime.local_addr().unwrap();
+
+    let client = thread::spawn(move || {
+        let mut client = Client::connect(address);
+        client.send("delay 100 later\nnow\n");
+
+        assert_eq!(client.recv(), "now");
+        assert_eq!(client.recv(), "later");
+    });
+
+    serve(&mut runtime, 1);
+    client.join().unwrap();
+}
+
+#[test]
+fn bye_half_closes_after_the_echo() {
+    let mut runtime = runtime(false);
+    let address = runtime.local_addr().unwrap();
+
+    let client = thread::spawn(move || {
+        let mut client = Client::connect(address);
+        client.send("bye\n");
+        assert_eq!(client.recv_to_end(), "bye\n");
+    });
+
+    serve(&mut runtime, 1);
+    client.join().unwrap();
+}
+
+#[test]
+fn answers_metrics_request() {
+    let mut runtime = runtime(true);
+    let address = runtime.local_addr().unwrap();
+
+    let client = thread::spawn(move || {
+        let mut client = Client::connect(address);
+        client.send(&format!("{}\n", mini_runtime::METRICS_REQUEST));
+        assert_eq!(client.recv(), "accepted_connections 1");
+    });
+
+    serve(&mut runtime, 1);
+    client.join().unwrap();
+}
+
+/// `run_until` returns with the connections open, the next call serves them.
+#[test]
+fn run_until_resumes_with_open_connections() {
+    let mut runtime = runtime(false);
+    let address = runtime.local_addr().unwrap();
+
+    let (connected_tx, connected_rx) = std::sync::mpsc::channel();
+    let client = thread::spawn(move || {
+        let mut client = Client::connect(address);
+        client.send("first\n");
+        assert_eq!(client.recv(), "first");
+
+        // The server stopped polling meanwhile: the line waits in the socket.
+        connected_rx.recv().unwrap();
+        client.send("second\n");
+        assert_eq!(client.recv(), "second");
+    });
+
+    runtime
+        .run_until(|runtime| runtime.metrics().bytes_written == "first\n".len() as u64)
+        .unwrap();
+    assert_eq!(runtime.metrics().active_clients, 1);
+    connected_tx.send(()).unwrap();
+
+    runtime
+        .run_until(|runtime| runtime.metrics().active_clients == 0)
+        .unwrap();
+    client.join().unwrap();
+    assert_eq!(runtime.metrics().accepted_connections, 1);
+}
+
+/// The errors tell what went wrong, e.g. a port already taken.
+#[test]
+fn new_on_address_in_use() {
+    let runtime = runtime(false);
+    let address = runtime.local_addr().unwrap();
+
+    match MiniRuntime::new(address, Config::default(), Echo::default()) {
+        Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::AddrInUse),
+        Err(e) => panic!("unexpected error: {e}"),
+        Ok(_) => panic!("bound {address} twice"),
+    }
+}
@@ -1,18 +1,18 @@
 mod own_future;
 
+use crate::own_future::Delay;
+use futures::task;
 use std::collections::VecDeque;
 use std::pin::Pin;
 use std::task::Context;
 use std::time::{Duration, Instant};
-use futures::task;
-use crate::own_future::Delay;
 
 fn main() {
     let mut mini_tokio = MiniTokio::new();
 
     mini_tokio.spawn(async {
         let when = Instant::now() + Duration::from_millis(10);
-        let future = Delay { when };
+        let future = Delay::new(when);
 
         let out = future.await;
         assert_eq!(out, "done");
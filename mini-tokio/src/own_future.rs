@@ -1,21 +1,127 @@
 use std::pin::Pin;
-use std::task::{Context, Poll};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
 use std::time::Instant;
 
+/// A future completing at `when`.
+///
+/// The first poll spawns a timer thread sleeping until the deadline, which then
+/// wakes the task: the task is polled once more, instead of being woken on
+/// every poll and busy-waiting for the deadline.
 pub(crate) struct Delay {
-    pub(crate) when: Instant,
+    when: Instant,
+    /// The waker of the task awaiting the delay, shared with the timer thread.
+    /// `None` until the timer thread is spawned.
+    waker: Option<Arc<Mutex<Waker>>>,
+}
+
+impl Delay {
+    pub(crate) fn new(when: Instant) -> Self {
+        Self { when, waker: None }
+    }
 }
 
 impl Future for Delay {
     type Output = &'static str;
 
-    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         if Instant::now() >= self.when {
             println!("Hello World!");
-            Poll::Ready("done")
+            return Poll::Ready("done");
+        }
+
+        if let Some(waker) = &self.waker {
+            // The future may move to another task between polls, e.g. when it
+            // is raced in a `select!`: the timer must wake the task that polled
+            // it last, not the first one.
+            let mut waker = waker.lock().unwrap();
+            if !waker.will_wake(cx.waker()) {
+                *waker = cx.waker().clone();
+            }
         } else {
-            cx.waker().wake_by_ref();
-            Poll::Pending
+            let when = self.when;
+            let waker = Arc::new(Mutex::new(cx.waker().clone()));
+            self.waker = Some(waker.clone());
+
+            thread::spawn(move || {
+                let now = Instant::now();
+                if now < when {
+                    thread::sleep(when - now);
+                }
+                waker.lock().unwrap().wake_by_ref();
+            });
+        }
+
+        Poll::Pending
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::Wake;
+    use std::time::Duration;
+
+    #[derive(Default)]
+    struct CountingWaker {
+        wakes: AtomicUsize,
+    }
+
+    impl Wake for CountingWaker {
+        fn wake(self: Arc<Self>) {
+            self.wake_by_ref();
         }
+
+        fn wake_by_ref(self: &Arc<Self>) {
+            self.wakes.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    impl CountingWaker {
+        fn wakes(&self) -> usize {
+            self.wakes.load(Ordering::SeqCst)
+        }
+    }
+
+    #[test]
+    fn wakes_once_at_the_deadline() {
+        let counter = Arc::new(CountingWaker::default());
+        let waker = Waker::from(counter.clone());
+        let mut cx = Context::from_waker(&waker);
+
+        let mut delay = Delay::new(Instant::now() + Duration::from_millis(50));
+        assert!(Pin::new(&mut delay).poll(&mut cx).is_pending());
+        // Not woken right away: the task doesn't spin until the deadline.
+        assert_eq!(counter.wakes(), 0);
+
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(counter.wakes(), 1);
+        assert_eq!(Pin::new(&mut delay).poll(&mut cx), Poll::Ready("done"));
+    }
+
+    #[test]
+    fn wakes_the_last_polling_task() {
+        let first = Arc::new(CountingWaker::default());
+        let second = Arc::new(CountingWaker::default());
+
+        let mut delay = Delay::new(Instant::now() + Duration::from_millis(50));
+        let waker = Waker::from(first.clone());
+        assert!(
+            Pin::new(&mut delay)
+                .poll(&mut Context::from_waker(&waker))
+                .is_pending()
+        );
+        let waker = Waker::from(second.clone());
+        assert!(
+            Pin::new(&mut delay)
+                .poll(&mut Context::from_waker(&waker))
+                .is_pending()
+        );
+
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(first.wakes(), 0);
+        assert_eq!(second.wakes(), 1);
     }
 }
@@ -0,0 +1,133 @@
+use mio::{Events, Poll, Registry};
+use std::io;
+use std::time::Duration;
+
+/// A `Poll` and the `Events` buffer it fills.
+///
+/// ```no_run
+/// use mio::net::TcpListener;
+/// use mio::{Interest, Token};
+/// use mio_common::EventLoop;
+/// use std::time::Duration;
+///
+/// # fn main() -> std::io::Result<()> {
+/// let mut event_loop = EventLoop::builder()
+///     .timeout(Duration::from_secs(10))
+///     .build()?;
+///
+/// let mut listener = TcpListener::bind("127.0.0.1:9000".parse().unwrap())?;
+/// event_loop
+///     .registry()
+///     .register(&mut listener, Token(0), Interest::READABLE)?;
+///
+/// loop {
+///     let ready = event_loop.poll()?;
+///     for event in &ready {
+///         // `ready.registry()` registers the accepted sockets.
+///     }
+/// }
+/// # }
+/// ```
+pub struct EventLoop {
+    poll: Poll,
+    events: Events,
+    timeout: Option<Duration>,
+}
+
+/// Configures an `EventLoop`.
+#[derive(Debug, Clone)]
+pub struct Builder {
+    events_capacity: usize,
+    timeout: Option<Duration>,
+}
+
+/// The events returned by one `poll`, along with the registry so that
+/// sources can be registered while the events are handled.
+pub struct Ready<'a> {
+    registry: &'a Registry,
+    events: &'a Events,
+}
+
+impl EventLoop {
+    pub fn builder() -> Builder {
+        Builder {
+            events_capacity: 128,
+            timeout: None,
+        }
+    }
+
+    /// Returns an event loop with the default settings: room for 128 events
+    /// per poll, and no timeout.
+    pub fn new() -> io::Result<Self> {
+        Self::builder().build()
+    }
+
+    pub fn registry(&self) -> &Registry {
+        self.poll.registry()
+    }
+
+    /// Waits for events, at most for the timeout given to the builder.
+    pub fn poll(&mut self) -> io::Result<Ready<'_>> {
+        self.poll_timeout(self.timeout)
+    }
+
+    /// Waits for events, at most for `timeout`, or until some are ready if
+    /// `None`. A wait interrupted by a signal returns no events.
+    pub fn poll_timeout(&mut self, timeout: Option<Duration>) -> io::Result<Ready<'_>> {
+        match self.poll.poll(&mut self.events, timeout) {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => self.events.clear(),
+            Err(e) => return Err(e),
+        }
+        Ok(Ready {
+            registry: self.poll.registry(),
+            events: &self.events,
+        })
+    }
+}
+
+impl Builder {
+    /// The maximum number of events returned by one poll.
+    pub fn events_capacity(mut self, capacity: usize) -> Self {
+        self.events_capacity = capacity;
+        self
+    }
+
+    /// How long `EventLoop::poll` waits for events.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn build(self) -> io::Result<EventLoop> {
+        Ok(EventLoop {
+            poll: Poll::new()?,
+            events: Events::with_capacity(self.events_capacity),
+            timeout: self.timeout,
+        })
+    }
+}
+
+impl<'a> Ready<'a> {
+    pub fn registry(&self) -> &'a Registry {
+        self.registry
+    }
+
+    pub fn iter(&self) -> mio::event::Iter<'a> {
+        self.events.iter()
+    }
+
+    /// Returns `true` if the poll timed out without any event.
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl<'a> IntoIterator for &Ready<'a> {
+    type Item = &'a mio::event::Event;
+    type IntoIter = mio::event::Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
@@ -0,0 +1,11 @@
+//! The poll-loop scaffolding shared by the mio examples.
+//!
+//! Every example creates a `Poll` and an `Events` buffer, registers sources
+//! under hand-picked tokens and keeps a map from tokens to its own state.
+//! `EventLoop` bundles the first part, `Tokens` the second.
+
+mod event_loop;
+mod tokens;
+
+pub use event_loop::{Builder, EventLoop, Ready};
+pub use tokens::Tokens;
@@ -0,0 +1,82 @@
+use mio::Token;
+use std::collections::HashMap;
+use std::collections::hash_map;
+use std::io;
+
+/// The state of the registered sources, indexed by their token.
+///
+/// Tokens are handed out in increasing order from a first token, the ones
+/// below it are left for sources with a fixed token, such as a listener.
+///
+/// ```
+/// use mio::Token;
+/// use mio_common::Tokens;
+///
+/// const SERVER: Token = Token(0);
+///
+/// let mut clients: Tokens<&str> = Tokens::starting_at(Token(SERVER.0 + 1));
+/// let token = clients.insert("first client");
+/// assert_eq!(token, Token(1));
+/// assert_eq!(clients.get(token), Some(&"first client"));
+/// ```
+#[derive(Debug)]
+pub struct Tokens<T> {
+    entries: HashMap<Token, T>,
+    next: usize,
+}
+
+impl<T> Tokens<T> {
+    pub fn starting_at(first: Token) -> Self {
+        Self {
+            entries: HashMap::new(),
+            next: first.0,
+        }
+    }
+
+    pub fn insert(&mut self, value: T) -> Token {
+        let token = Token(self.next);
+        self.next += 1;
+        self.entries.insert(token, value);
+        token
+    }
+
+    /// Calls `register` with the next token, and stores the value it returns
+    /// under that token. Nothing is stored if registering fails.
+    ///
+    /// This suits values owning their source, which must be registered before
+    /// being moved into the map.
+    pub fn insert_with<F>(&mut self, register: F) -> io::Result<Token>
+    where
+        F: FnOnce(Token) -> io::Result<T>,
+    {
+        let token = Token(self.next);
+        let value = register(token)?;
+        self.next += 1;
+        self.entries.insert(token, value);
+        Ok(token)
+    }
+
+    pub fn get(&self, token: Token) -> Option<&T> {
+        self.entries.get(&token)
+    }
+
+    pub fn get_mut(&mut self, token: Token) -> Option<&mut T> {
+        self.entries.get_mut(&token)
+    }
+
+    pub fn remove(&mut self, token: Token) -> Option<T> {
+        self.entries.remove(&token)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> hash_map::Iter<'_, Token, T> {
+        self.entries.iter()
+    }
+}
@@ -1,19 +1,18 @@
-use mio::{Events, Poll};
-use std::error::Error;
+use mio_common::EventLoop;
 use std::time::Duration;
+use tutorial_util::Result;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    // Create a Poll instance
-    let mut poll = Poll::new()?;
-
-    // Create a structure to receive polled events
-    let mut events = Events::with_capacity(128);
+fn main() -> Result<()> {
+    // Create a Poll instance, along with a structure to receive polled events
+    let mut event_loop = EventLoop::builder()
+        .timeout(Duration::from_millis(500))
+        .build()?;
 
     println!("Starting mio event loop...");
     // Wait for events, but none will be received because no
     // `event::Source`s have been registered with this `Poll` instance.
-    poll.poll(&mut events, Some(Duration::from_millis(500)))?;
-    assert!(events.is_empty());
+    let ready = event_loop.poll()?;
+    assert!(ready.is_empty());
 
     println!("Poll completed, no events yet.");
 
@@ -1,13 +1,14 @@
 use mio::net::TcpStream;
-use mio::{Events, Interest, Poll, Token};
-use std::error::Error;
+use mio::{Interest, Token};
+use mio_common::EventLoop;
 use std::time::Duration;
 use std::{net, thread};
+use tutorial_util::Result;
 
 const CLIENT: Token = Token(1);
 const RETRY_INTERVAL: Duration = Duration::from_millis(500);
 
-fn main() -> Result<(), Box<dyn Error>> {
+fn main() -> Result<()> {
     let address: net::SocketAddr = "127.0.0.1:9000".parse()?;
 
     loop {
@@ -16,12 +17,13 @@ fn main() -> Result<(), Box<dyn Error>> {
         // Try to open socket
         match TcpStream::connect(address) {
             Ok(mut stream) => {
-                // Create a Poll instance
-                let mut poll = Poll::new()?;
-                // Create a structure to receive polled events
-                let mut events = Events::with_capacity(128);
+                // Create a Poll instance, along with a structure to receive polled events
+                let mut event_loop = EventLoop::builder()
+                    .timeout(Duration::from_secs(2))
+                    .build()?;
                 // Register the stream with the poller
-                poll.registry()
+                event_loop
+                    .registry()
                     .register(&mut stream, CLIENT, Interest::WRITABLE)?;
 
                 println!("Starting mio event loop...");
@@ -29,9 +31,9 @@ fn main() -> Result<(), Box<dyn Error>> {
                 // Wait until socket becomes writable
                 'poll_loop: loop {
                     // Wait for events
-                    poll.poll(&mut events, Some(Duration::from_secs(2)))?;
+                    let ready = event_loop.poll()?;
 
-                    for event in &events {
+                    for event in &ready {
                         if event.token() == CLIENT && event.is_writable() {
                             if let Some(e) = stream.take_error()? {
                                 println!("❌ Connection failed: {}. Retrying...", e);
@@ -1,66 +1,236 @@
+use mio::event::Event;
 use mio::net::TcpStream;
-use mio::{Events, Interest, Poll, Token};
-use std::error::Error;
-use std::net;
+use mio::{Interest, Registry, Token};
+use mio_common::EventLoop;
+use std::io::{self, Read};
+use std::net::SocketAddr;
 use std::time::{Duration, Instant};
+use tutorial_util::Result;
 
 const CLIENT: Token = Token(1);
 
-fn main() -> Result<(), Box<dyn Error>> {
-    // Create a Poll instance
-    let mut poll = Poll::new()?;
+/// How long the handshake may take before the connect is given up.
+const CONNECT_TIMEOUT: Duration = Duration::from_millis(500);
 
-    // Register the stream with the poller
-    let stream = registry(&poll)?;
+/// The lifecycle of a non-blocking connect, from the `connect` call to the
+/// connection being closed.
+///
+/// ```text
+/// connect ─▶ Connecting ─┬─▶ Connected ─▶ Closed
+///              ▲    │    ├─▶ Refused
+///              └────┘    ├─▶ TimedOut
+///       spurious wakeup  └─▶ Failed
+/// ```
+enum Connection {
+    /// The handshake is in progress, the stream is registered WRITABLE.
+    Connecting {
+        stream: TcpStream,
+        deadline: Instant,
+    },
+    /// The handshake completed, the stream is registered READABLE to notice
+    /// the server closing the connection.
+    Connected { stream: TcpStream },
+    /// The server closed the connection.
+    Closed,
+    /// Nobody listens on the address.
+    Refused(io::Error),
+    /// The handshake did not complete before the deadline, e.g. the address
+    /// drops the packets.
+    TimedOut,
+    /// Any other failure, e.g. the network is unreachable.
+    Failed(io::Error),
+}
+
+/// Connects to the server and follows the connection until it is closed.
+/// An address dropping the packets, e.g. `10.255.255.1:9000` on most
+/// networks, shows the timeout.
+///
+/// ```text
+/// cargo run --bin client -- [ADDRESS]
+/// ```
+fn main() -> Result<()> {
+    let address: SocketAddr = std::env::args()
+        .nth(1)
+        .as_deref()
+        .unwrap_or("127.0.0.1:9000")
+        .parse()?;
+
+    // Create a Poll instance, along with a structure to receive polled events
+    let mut event_loop = EventLoop::new()?;
 
-    // Create a structure to receive polled events
-    let mut events = Events::with_capacity(128);
+    let mut connection = Connection::start(event_loop.registry(), address)?;
 
     println!("Starting mio event loop...");
 
-    let start = Instant::now();
-    let timeout = Duration::from_millis(500);
-    loop {
-        let elapsed = start.elapsed();
-        if elapsed >= timeout {
-            // Connection timed out
-            println!("Connection timed out, stopping mio event loop...");
-            return Ok(());
-        }
+    while !connection.is_done() {
+        // Wait for events, until the deadline of the handshake if any
+        let ready = event_loop.poll_timeout(connection.remaining())?;
 
-        let remaining = timeout - elapsed;
-
-        // Wait for events
-        poll.poll(&mut events, Some(remaining))?;
-
-        for event in &events {
-            if event.token() == CLIENT && event.is_writable() {
-                // Fix 2 check take_error().
-                if let Some(e) = stream.take_error()? {
-                    println!("❌ Connection failed: {}. Exiting", e);
-                } else {
-                    println!(
-                        "✅ Client successfully connected from {}!",
-                        stream.local_addr()?
-                    );
-                }
-                return Ok(());
+        if ready.is_empty() {
+            connection = connection.on_timeout();
+        }
+        for event in &ready {
+            if event.token() == CLIENT {
+                connection = connection.on_event(ready.registry(), event)?;
             }
         }
     }
+
+    match connection {
+        Connection::Closed => println!("🔌 Server closed the connection. Exiting"),
+        Connection::Refused(e) => println!("❌ Connection refused: {}. Exiting", e),
+        Connection::TimedOut => println!("⏰ Connection timed out. Exiting"),
+        Connection::Failed(e) => println!("❌ Connection failed: {}. Exiting", e),
+        Connection::Connecting { .. } | Connection::Connected { .. } => unreachable!(),
+    }
+    Ok(())
 }
 
-/// Fix 1 - should return TcpStream, keeping the stream alive for the duration of the event loop.
-fn registry(poll: &Poll) -> Result<TcpStream, Box<dyn Error>> {
-    // Connect to a specific port
-    let address: net::SocketAddr = "127.0.0.1:9000".parse()?;
-    let mut stream = TcpStream::connect(address)?;
+impl Connection {
+    /// Starts connecting to `address`. The `connect` call returns right away,
+    /// the outcome of the handshake is reported by the next events.
+    fn start(registry: &Registry, address: SocketAddr) -> Result<Connection> {
+        println!("🔵 Client attempting to connect to {}", address);
+
+        let mut stream = match TcpStream::connect(address) {
+            Ok(stream) => stream,
+            // Some failures are known without a handshake, e.g. no route to
+            // the host.
+            Err(e) => return Ok(Connection::failed(e)),
+        };
 
-    // Register the stream with the poller
-    poll.registry()
-        .register(&mut stream, CLIENT, Interest::WRITABLE)?;
+        // The stream becomes writable once the handshake completed, or failed.
+        registry.register(&mut stream, CLIENT, Interest::WRITABLE)?;
 
-    println!("🔵 Client attempting to connect to {}", address);
+        Ok(Connection::Connecting {
+            stream,
+            deadline: Instant::now() + CONNECT_TIMEOUT,
+        })
+    }
+
+    /// Returns `true` once the connection reached a final state.
+    fn is_done(&self) -> bool {
+        !matches!(
+            self,
+            Connection::Connecting { .. } | Connection::Connected { .. }
+        )
+    }
 
-    Ok(stream)
+    /// How long to wait for the next event: until the deadline while
+    /// connecting, for as long as it takes once connected.
+    fn remaining(&self) -> Option<Duration> {
+        match self {
+            Connection::Connecting { deadline, .. } => {
+                Some(deadline.saturating_duration_since(Instant::now()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Called when a poll returned no event.
+    fn on_timeout(self) -> Connection {
+        match self {
+            Connection::Connecting { deadline, .. } if Instant::now() >= deadline => {
+                // Dropping the stream deregisters it and aborts the handshake.
+                Connection::TimedOut
+            }
+            other => other,
+        }
+    }
+
+    /// Advances the state machine on an event of the stream.
+    fn on_event(self, registry: &Registry, event: &Event) -> Result<Connection> {
+        match self {
+            Connection::Connecting { stream, deadline } => {
+                Connection::on_connecting(stream, deadline, registry, event)
+            }
+            Connection::Connected { stream } => Connection::on_connected(stream, event),
+            done => Ok(done),
+        }
+    }
+
+    fn on_connecting(
+        mut stream: TcpStream,
+        deadline: Instant,
+        registry: &Registry,
+        event: &Event,
+    ) -> Result<Connection> {
+        // A failed handshake is reported as an error event, both halves of
+        // the connection closed: `take_error` tells why.
+        if event.is_error() || event.is_write_closed() {
+            let e = stream
+                .take_error()?
+                .unwrap_or_else(|| io::ErrorKind::ConnectionAborted.into());
+            return Ok(Connection::failed(e));
+        }
+
+        if !event.is_writable() {
+            return Ok(Connection::Connecting { stream, deadline });
+        }
+
+        // Writable does not guarantee the handshake completed: the peer
+        // address is only known once it did.
+        match stream.peer_addr() {
+            Ok(peer) => {
+                println!(
+                    "✅ Client successfully connected from {} to {}!",
+                    stream.local_addr()?,
+                    peer
+                );
+
+                // Only the server closing the connection is of interest now,
+                // and an idle connected stream would always be writable.
+                registry.reregister(&mut stream, CLIENT, Interest::READABLE)?;
+                Ok(Connection::Connected { stream })
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotConnected => {
+                // Spurious wakeup, the handshake is still in progress: mio is
+                // edge-triggered, re-arm the interest to be woken again.
+                println!("💤 Spurious wakeup, still connecting...");
+                registry.reregister(&mut stream, CLIENT, Interest::WRITABLE)?;
+                Ok(Connection::Connecting { stream, deadline })
+            }
+            Err(e) => Ok(Connection::failed(e)),
+        }
+    }
+
+    fn on_connected(mut stream: TcpStream, event: &Event) -> Result<Connection> {
+        if event.is_error() {
+            let e = stream
+                .take_error()?
+                .unwrap_or_else(|| io::ErrorKind::ConnectionReset.into());
+            return Ok(Connection::failed(e));
+        }
+
+        // The read half is closed once the server sent its FIN: the event
+        // reports it, without reading up to the end of the stream.
+        if event.is_read_closed() {
+            return Ok(Connection::Closed);
+        }
+
+        // The server sends nothing: drain whatever arrives until `WouldBlock`,
+        // mio is edge-triggered.
+        let mut buffer = [0; 1024];
+        loop {
+            match stream.read(&mut buffer) {
+                Ok(0) => return Ok(Connection::Closed),
+                Ok(n) => println!("📨 Received {} bytes", n),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    return Ok(Connection::Connected { stream });
+                }
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Ok(Connection::failed(e)),
+            }
+        }
+    }
+
+    /// Classifies a connect error.
+    fn failed(e: io::Error) -> Connection {
+        match e.kind() {
+            io::ErrorKind::ConnectionRefused => Connection::Refused(e),
+            // The OS gave up on the handshake before our deadline.
+            io::ErrorKind::TimedOut => Connection::TimedOut,
+            _ => Connection::Failed(e),
+        }
+    }
 }
@@ -1,26 +1,24 @@
 use mio::net::TcpListener;
-use mio::{Events, Interest, Poll, Token};
-use std::error::Error;
+use mio::{Interest, Registry, Token};
+use mio_common::EventLoop;
 use std::net;
 use std::time::Duration;
+use tutorial_util::Result;
 
 const SERVER: Token = Token(0);
 
-fn main() -> Result<(), Box<dyn Error>> {
-    // Create a Poll instance
-    let mut poll = Poll::new()?;
+fn main() -> Result<()> {
+    // Create a Poll instance, along with a structure to receive polled events
+    let mut event_loop = EventLoop::new()?;
 
     // Register the listener with the poller
-    let listener = registry(&poll)?;
-
-    // Create a structure to receive polled events
-    let mut events = Events::with_capacity(128);
+    let listener = registry(event_loop.registry())?;
 
     println!("Starting mio event loop...");
     loop {
-        poll.poll(&mut events, Some(Duration::from_secs(10)))?;
+        let ready = event_loop.poll_timeout(Some(Duration::from_secs(10)))?;
 
-        for event in &events {
+        for event in &ready {
             if event.token() == SERVER && event.is_readable() {
                 let (_, addr) = listener.accept()?;
                 println!("✅ Server accepted connection from {}", addr);
@@ -32,14 +30,13 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
 }
 
-fn registry(poll: &Poll) -> Result<TcpListener, Box<dyn Error>> {
+fn registry(registry: &Registry) -> Result<TcpListener> {
     // Bind to a specific port
     let address: net::SocketAddr = "127.0.0.1:9000".parse()?;
     let mut listener = TcpListener::bind(address)?;
 
     // Register the listener with the poller
-    poll.registry()
-        .register(&mut listener, SERVER, Interest::READABLE)?;
+    registry.register(&mut listener, SERVER, Interest::READABLE)?;
 
     println!("🟢 Server listening on {}", address);
 
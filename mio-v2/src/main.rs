@@ -1,17 +1,15 @@
 use mio::net::TcpStream;
-use mio::{Events, Interest, Poll, Token};
-use std::error::Error;
+use mio::{Interest, Registry, Token};
+use mio_common::EventLoop;
 use std::net;
 use std::time::{Duration, Instant};
+use tutorial_util::Result;
 
-fn main() -> Result<(), Box<dyn Error>> {
-    // Create a Poll instance
-    let mut poll = Poll::new()?;
+fn main() -> Result<()> {
+    // Create a Poll instance, along with a structure to receive polled events
+    let mut event_loop = EventLoop::new()?;
 
-    registry(&poll)?;
-
-    // Create a structure to receive polled events
-    let mut events = Events::with_capacity(128);
+    registry(event_loop.registry())?;
 
     println!("Starting mio event loop...");
 
@@ -30,9 +28,9 @@ fn main() -> Result<(), Box<dyn Error>> {
         let remaining = timeout - elapsed;
 
         // Wait for events
-        poll.poll(&mut events, Some(remaining))?;
+        let ready = event_loop.poll_timeout(Some(remaining))?;
 
-        for event in &events {
+        for event in &ready {
             if event.token() == Token(0) {
                 // Something (probably) happened on the socket.
                 println!("Got something on our socket, stopping mio event loop...");
@@ -42,7 +40,7 @@ fn main() -> Result<(), Box<dyn Error>> {
     }
 }
 
-fn registry(poll: &Poll) -> Result<(), Box<dyn Error>> {
+fn registry(registry: &Registry) -> Result<()> {
     // Bind a dummy listener on a random port
     let address: net::SocketAddr = "127.0.0.1:0".parse()?;
     let listener = net::TcpListener::bind(address)?;
@@ -51,7 +49,7 @@ fn registry(poll: &Poll) -> Result<(), Box<dyn Error>> {
     let mut socket = TcpStream::connect(listener.local_addr()?)?;
 
     // Register the listener with the poller
-    poll.registry().register(
+    registry.register(
         &mut socket,
         Token(0),
         Interest::READABLE | Interest::WRITABLE,
@@ -0,0 +1,154 @@
+use mio::net::TcpStream;
+use mio::{Interest, Token};
+use mio_common::EventLoop;
+use std::io::{self, Read, Write};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tutorial_util::{Error, Result};
+
+const CLIENT: Token = Token(0);
+
+/// How long to wait for the connection or for an echo before giving up.
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+const DEFAULT_PAYLOADS: &[&str] = &["hello", "mio", "echo server"];
+
+/// Sends each payload to the echo server, waits for it to come back, and
+/// checks it is the same.
+///
+/// ```text
+/// cargo run --bin client -- [--addr 127.0.0.1:9000] [payload...]
+/// ```
+fn main() -> Result<()> {
+    let (address, payloads) = parse_args()?;
+
+    let mut event_loop = EventLoop::builder()
+        .events_capacity(16)
+        .timeout(TIMEOUT)
+        .build()?;
+
+    let mut stream = TcpStream::connect(address)?;
+    event_loop
+        .registry()
+        .register(&mut stream, CLIENT, Interest::READABLE | Interest::WRITABLE)?;
+    wait_connected(&mut event_loop, &stream)?;
+    println!("🟢 Connected to {}", address);
+
+    let mut round_trips = Vec::with_capacity(payloads.len());
+    let mut failures = 0;
+
+    for payload in &payloads {
+        let started = Instant::now();
+        write_all(&mut event_loop, &mut stream, payload.as_bytes())?;
+        let echo = read_exact(&mut event_loop, &mut stream, payload.len())?;
+        let round_trip = started.elapsed();
+
+        if echo == payload.as_bytes() {
+            println!("✅ {:?} echoed in {:?}", payload, round_trip);
+            round_trips.push(round_trip);
+        } else {
+            println!(
+                "❌ {:?} echoed as {:?}",
+                payload,
+                String::from_utf8_lossy(&echo)
+            );
+            failures += 1;
+        }
+    }
+
+    if let (Some(min), Some(max)) = (round_trips.iter().min(), round_trips.iter().max()) {
+        let average = round_trips.iter().sum::<Duration>() / round_trips.len() as u32;
+        println!(
+            "📊 Round trip: min {:?}, avg {:?}, max {:?}",
+            min, average, max
+        );
+    }
+
+    if failures > 0 {
+        return Err(Error::Protocol(format!(
+            "{} of {} echoes did not match",
+            failures,
+            payloads.len()
+        )));
+    }
+    Ok(())
+}
+
+/// Parses `[--addr ADDRESS] [PAYLOAD...]`.
+fn parse_args() -> Result<(SocketAddr, Vec<String>)> {
+    let mut address: SocketAddr = "127.0.0.1:9000".parse()?;
+    let mut payloads = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--addr" {
+            let value = args
+                .next()
+                .ok_or_else(|| Error::Parse("--addr needs a value".to_string()))?;
+            address = value.parse()?;
+        } else {
+            payloads.push(arg);
+        }
+    }
+
+    if payloads.is_empty() {
+        payloads = DEFAULT_PAYLOADS.iter().map(|p| p.to_string()).collect();
+    }
+    Ok((address, payloads))
+}
+
+/// Waits for the next events on the stream, failing with `Error::Timeout`
+/// after `TIMEOUT`.
+fn wait(event_loop: &mut EventLoop) -> Result<()> {
+    if event_loop.poll()?.is_empty() {
+        return Err(Error::Timeout);
+    }
+    Ok(())
+}
+
+/// A non-blocking connect returns right away: the socket becomes writable
+/// once the handshake completed, or failed.
+fn wait_connected(event_loop: &mut EventLoop, stream: &TcpStream) -> Result<()> {
+    loop {
+        wait(event_loop)?;
+
+        if let Some(e) = stream.take_error()? {
+            return Err(e.into());
+        }
+        match stream.peer_addr() {
+            Ok(_) => return Ok(()),
+            // Spurious wakeup, the handshake is still in progress.
+            Err(e) if e.kind() == io::ErrorKind::NotConnected => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+fn write_all(event_loop: &mut EventLoop, stream: &mut TcpStream, mut data: &[u8]) -> Result<()> {
+    while !data.is_empty() {
+        match stream.write(data) {
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::WriteZero).into()),
+            Ok(n) => data = &data[n..],
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => wait(event_loop)?,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(())
+}
+
+/// Reads exactly `len` bytes: the echo may arrive in several pieces.
+fn read_exact(event_loop: &mut EventLoop, stream: &mut TcpStream, len: usize) -> Result<Vec<u8>> {
+    let mut received = vec![0; len];
+    let mut filled = 0;
+    while filled < len {
+        match stream.read(&mut received[filled..]) {
+            Ok(0) => return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into()),
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => wait(event_loop)?,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Ok(received)
+}
@@ -0,0 +1,184 @@
+//! An echo server on a single-threaded mio event loop.
+//!
+//! The server bin binds the well-known port, the tests bind an ephemeral one
+//! and drive the server from client threads.
+
+use mio::event::Event;
+use mio::net::{TcpListener, TcpStream};
+use mio::{Interest, Registry, Token};
+use mio_common::{EventLoop, Tokens};
+use std::io::{self, Read, Write};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+const SERVER: Token = Token(0);
+
+/// An echo server: every byte received on a connection is written back.
+pub struct Server {
+    event_loop: EventLoop,
+    listener: TcpListener,
+    clients: Tokens<Client>,
+}
+
+/// A connected client and the bytes waiting to be echoed back to it.
+struct Client {
+    socket: TcpStream,
+    /// Received but not written yet: the socket accepted only part of the
+    /// echo, the rest is written on the next WRITABLE event.
+    pending: Vec<u8>,
+}
+
+impl Server {
+    /// Binds the server to `address`, use port 0 to let the OS pick one.
+    pub fn bind(address: SocketAddr) -> io::Result<Server> {
+        let event_loop = EventLoop::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+
+        let mut listener = TcpListener::bind(address)?;
+        event_loop
+            .registry()
+            .register(&mut listener, SERVER, Interest::READABLE)?;
+
+        Ok(Server {
+            event_loop,
+            listener,
+            clients: Tokens::starting_at(Token(SERVER.0 + 1)),
+        })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Serves the clients, forever. Only fails if polling fails.
+    pub fn run(mut self) -> io::Result<()> {
+        loop {
+            let ready = self.event_loop.poll()?;
+
+            for event in &ready {
+                match event.token() {
+                    SERVER => accept_all(&self.listener, ready.registry(), &mut self.clients),
+
+                    token => {
+                        if let Some(client) = self.clients.get_mut(token) {
+                            match handle_client(ready.registry(), token, client, event) {
+                                Ok(true) => {}
+                                Ok(false) => {
+                                    println!("🔌 Connection closed: {:?}", token);
+                                    self.clients.remove(token);
+                                }
+                                Err(e) => {
+                                    eprintln!("❌ Connection error on {:?}: {}", token, e);
+                                    self.clients.remove(token);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Accepts until `WouldBlock`: several clients may connect between two
+/// polls, and the edge-triggered listener reports them with a single event.
+fn accept_all(listener: &TcpListener, registry: &Registry, clients: &mut Tokens<Client>) {
+    loop {
+        let (mut socket, addr) = match listener.accept() {
+            Ok(accepted) => accepted,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            // E.g. the client reset the connection while it was queued, or
+            // the process is out of file descriptors: keep serving the others.
+            Err(e) => {
+                eprintln!("❌ Accept error: {}", e);
+                return;
+            }
+        };
+        println!("✅ New connection from {}", addr);
+
+        // WRITABLE is only registered while an echo is pending, otherwise
+        // every poll reports the idle socket writable.
+        let registered = clients.insert_with(|token| {
+            registry.register(&mut socket, token, Interest::READABLE)?;
+            Ok(Client {
+                socket,
+                pending: Vec::new(),
+            })
+        });
+        if let Err(e) = registered {
+            eprintln!("❌ Cannot register {}: {}", addr, e);
+        }
+    }
+}
+
+/// Handles the readiness of a client: reads on READABLE events, writes on
+/// WRITABLE ones. Returns `false` once the client closed the connection.
+fn handle_client(
+    registry: &Registry,
+    token: Token,
+    client: &mut Client,
+    event: &Event,
+) -> io::Result<bool> {
+    let mut open = true;
+
+    if event.is_readable() {
+        open = read_available(token, client)?;
+    }
+
+    // Try writing right away: most of the time the socket accepts the whole
+    // echo, and the WRITABLE event is only needed for the rest.
+    if event.is_writable() || !client.pending.is_empty() {
+        write_pending(client)?;
+    }
+
+    let interest = if client.pending.is_empty() {
+        Interest::READABLE
+    } else {
+        Interest::READABLE | Interest::WRITABLE
+    };
+    registry.reregister(&mut client.socket, token, interest)?;
+
+    Ok(open)
+}
+
+/// Reads until `WouldBlock`: mio is edge-triggered, so data left in the socket
+/// is not reported again. Returns `false` if the client closed the connection.
+fn read_available(token: Token, client: &mut Client) -> io::Result<bool> {
+    let mut buffer = [0; 1024];
+    loop {
+        match client.socket.read(&mut buffer) {
+            Ok(0) => return Ok(false),
+            Ok(n) => {
+                let received = &buffer[..n];
+                println!(
+                    "📨 Received from {:?}: {}",
+                    token,
+                    String::from_utf8_lossy(received)
+                );
+                client.pending.extend_from_slice(received); // Echo back
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(true),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Writes as much of the pending echo as the socket accepts.
+fn write_pending(client: &mut Client) -> io::Result<()> {
+    while !client.pending.is_empty() {
+        match client.socket.write(&client.pending) {
+            Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+            Ok(n) => {
+                client.pending.drain(..n);
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,122 @@
+use mio_v3::Server;
+use std::io::{Read, Write};
+use std::net::{Shutdown, SocketAddr, TcpStream};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::Duration;
+
+/// A test fails instead of hanging if an echo never comes back.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Starts a server on an ephemeral port. It runs until the test process
+/// exits.
+fn start_server() -> SocketAddr {
+    let server = Server::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let address = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    address
+}
+
+fn connect(address: SocketAddr) -> TcpStream {
+    let stream = TcpStream::connect(address).unwrap();
+    stream.set_read_timeout(Some(TIMEOUT)).unwrap();
+    stream.set_write_timeout(Some(TIMEOUT)).unwrap();
+    stream
+}
+
+/// Sends `payload` from another thread while the echo is read: a client
+/// reading only once it sent everything would fill both socket buffers.
+fn echo(stream: &mut TcpStream, payload: Vec<u8>) -> Vec<u8> {
+    let mut writer = stream.try_clone().unwrap();
+    let len = payload.len();
+    let sender = thread::spawn(move || writer.write_all(&payload));
+
+    let mut echo = vec![0; len];
+    stream.read_exact(&mut echo).unwrap();
+    sender.join().unwrap().unwrap();
+    echo
+}
+
+/// A payload in which every offset has a recognizable value.
+fn payload(len: usize, seed: u8) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8 ^ seed).collect()
+}
+
+#[test]
+fn echoes_concurrent_connections() {
+    const CLIENTS: usize = 50;
+    const MESSAGES: usize = 10;
+
+    let address = start_server();
+    let barrier = Arc::new(Barrier::new(CLIENTS));
+
+    let clients: Vec<_> = (0..CLIENTS)
+        .map(|client| {
+            let barrier = barrier.clone();
+            thread::spawn(move || {
+                // All the clients connect at once.
+                barrier.wait();
+                let mut stream = connect(address);
+
+                for message in 0..MESSAGES {
+                    let sent = format!("client {client} message {message}").into_bytes();
+                    let received = echo(&mut stream, sent.clone());
+                    assert_eq!(received, sent);
+                }
+            })
+        })
+        .collect();
+
+    for client in clients {
+        client.join().unwrap();
+    }
+}
+
+#[test]
+fn echoes_large_payloads() {
+    let address = start_server();
+
+    // Far more than the server's 1 KiB read buffer and the socket buffers:
+    // the echo spans many reads and partial writes.
+    for (seed, len) in [(1, 64 * 1024), (2, 4 * 1024 * 1024)] {
+        let mut stream = connect(address);
+        let sent = payload(len, seed);
+        let received = echo(&mut stream, sent.clone());
+        assert!(received == sent, "corrupted echo of {len} bytes");
+    }
+}
+
+#[test]
+fn large_payloads_on_concurrent_connections() {
+    const CLIENTS: usize = 8;
+
+    let address = start_server();
+
+    let clients: Vec<_> = (0..CLIENTS)
+        .map(|client| {
+            thread::spawn(move || {
+                let mut stream = connect(address);
+                let sent = payload(512 * 1024, client as u8);
+                let received = echo(&mut stream, sent.clone());
+                assert!(received == sent, "corrupted echo for client {client}");
+            })
+        })
+        .collect();
+
+    for client in clients {
+        client.join().unwrap();
+    }
+}
+
+#[test]
+fn serves_after_client_disconnects() {
+    let address = start_server();
+
+    let mut first = connect(address);
+    assert_eq!(echo(&mut first, b"first".to_vec()), b"first");
+    first.shutdown(Shutdown::Both).unwrap();
+    drop(first);
+
+    let mut second = connect(address);
+    assert_eq!(echo(&mut second, b"second".to_vec()), b"second");
+}
@@ -0,0 +1,186 @@
+use mio::net::UdpSocket;
+use mio::{Interest, Token};
+use mio_common::EventLoop;
+use mio_v4::MAX_DATAGRAM;
+use mio_v4::sequence::{self, Tracker};
+use std::io;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+use tutorial_util::{Error, Result};
+
+const CLIENT: Token = Token(0);
+
+/// How long to wait for the next echo before the missing ones are counted
+/// as lost.
+const QUIET_PERIOD: Duration = Duration::from_secs(1);
+
+struct Args {
+    address: SocketAddr,
+    count: u64,
+    size: usize,
+}
+
+/// Sends a burst of numbered datagrams to the echo server as fast as the
+/// socket accepts them, then reports how many echoes were lost, reordered
+/// or duplicated.
+///
+/// ```text
+/// cargo run --bin client -- [--addr 127.0.0.1:9000] [--count 256] [--size 64]
+/// ```
+fn main() -> Result<()> {
+    let Args {
+        address,
+        count,
+        size,
+    } = parse_args()?;
+
+    let mut event_loop = EventLoop::builder()
+        .events_capacity(16)
+        .timeout(QUIET_PERIOD)
+        .build()?;
+
+    let local: SocketAddr = if address.is_ipv4() {
+        "0.0.0.0:0"
+    } else {
+        "[::]:0"
+    }
+    .parse()?;
+    let mut socket = UdpSocket::bind(local)?;
+    // Only the datagrams of the server are received, and `send` needs no
+    // address.
+    socket.connect(address)?;
+    event_loop
+        .registry()
+        .register(&mut socket, CLIENT, Interest::READABLE | Interest::WRITABLE)?;
+    println!(
+        "🔵 Sending {} datagrams of {} bytes to {}",
+        count, size, address
+    );
+
+    let payload = vec![b'x'; size];
+    let mut datagram = Vec::with_capacity(sequence::HEADER_LEN + size);
+    let mut buffer = vec![0; MAX_DATAGRAM];
+    let mut tracker = Tracker::default();
+
+    let mut sent = 0;
+    let mut would_block = 0;
+    let started = Instant::now();
+    let mut send_time = Duration::ZERO;
+
+    loop {
+        if sent < count {
+            // Send until the socket would block: the rest of the burst waits
+            // for the next WRITABLE event.
+            while sent < count {
+                sequence::encode(sent, &payload, &mut datagram);
+                match socket.send(&datagram) {
+                    Ok(_) => sent += 1,
+                    Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                        would_block += 1;
+                        break;
+                    }
+                    Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                    Err(e) => return Err(e.into()),
+                }
+
+                // The echoes come back while the burst is sent: left in the
+                // socket, they would overflow its receive buffer.
+                receive_all(&socket, &mut buffer, &mut tracker, size)?;
+            }
+
+            // Everything is sent: an idle socket would be reported writable
+            // on every poll.
+            if sent == count {
+                send_time = started.elapsed();
+                event_loop
+                    .registry()
+                    .reregister(&mut socket, CLIENT, Interest::READABLE)?;
+            }
+        }
+
+        receive_all(&socket, &mut buffer, &mut tracker, size)?;
+        if tracker.received() == count {
+            break;
+        }
+
+        if event_loop.poll()?.is_empty() {
+            if sent < count {
+                return Err(Error::Timeout);
+            }
+            // The missing echoes are not coming anymore.
+            break;
+        }
+    }
+
+    println!(
+        "📊 Sent {} datagrams in {:?}, the socket would block {} times",
+        sent, send_time, would_block
+    );
+    println!(
+        "📊 Received {} echoes: {} lost, {} reordered, {} duplicated",
+        tracker.received(),
+        count - tracker.received(),
+        tracker.reordered(),
+        tracker.duplicates()
+    );
+    Ok(())
+}
+
+/// Parses `[--addr ADDRESS] [--count N] [--size BYTES]`.
+fn parse_args() -> Result<Args> {
+    let mut args = Args {
+        address: "127.0.0.1:9000".parse()?,
+        count: 256,
+        size: 64,
+    };
+
+    let mut argv = std::env::args().skip(1);
+    while let Some(arg) = argv.next() {
+        let value = argv
+            .next()
+            .ok_or_else(|| Error::Parse(format!("{} needs a value", arg)))?;
+        match arg.as_str() {
+            "--addr" => args.address = value.parse()?,
+            "--count" => args.count = value.parse()?,
+            "--size" => args.size = value.parse()?,
+            _ => return Err(Error::Parse(format!("unknown argument {}", arg))),
+        }
+    }
+
+    if args.size > MAX_DATAGRAM - sequence::HEADER_LEN {
+        return Err(Error::Parse(format!(
+            "--size must be at most {}",
+            MAX_DATAGRAM - sequence::HEADER_LEN
+        )));
+    }
+    Ok(args)
+}
+
+/// Receives the echoes until `WouldBlock`: mio is edge-triggered, so
+/// datagrams left in the socket are not reported again.
+fn receive_all(
+    socket: &UdpSocket,
+    buffer: &mut [u8],
+    tracker: &mut Tracker,
+    size: usize,
+) -> Result<()> {
+    loop {
+        let n = match socket.recv(buffer) {
+            Ok(n) => n,
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(()),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            // On a connected socket, the ICMP "port unreachable" answering a
+            // datagram is reported here: nobody listens on the address.
+            Err(e) => return Err(e.into()),
+        };
+
+        match sequence::decode(&buffer[..n]) {
+            Some((seq, payload)) if payload.len() == size => {
+                tracker.record(seq);
+            }
+            _ => {
+                return Err(Error::Protocol(format!("unexpected echo of {} bytes", n)));
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+use mio_v4::Server;
+use std::net::SocketAddr;
+use tutorial_util::Result;
+
+fn main() -> Result<()> {
+    let address: SocketAddr = "127.0.0.1:9000".parse()?;
+    let server = Server::bind(address)?;
+
+    println!("🟢 UDP echo server listening on {}", address);
+
+    server.run()?;
+    Ok(())
+}
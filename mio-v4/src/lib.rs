@@ -0,0 +1,197 @@
+//! A UDP echo server on a single-threaded mio event loop.
+//!
+//! Every datagram starts with a sequence number, see [`sequence`]: the server
+//! tracks the numbers received from each peer and reports the losses and
+//! reorderings, the client does the same with the echoes.
+//!
+//! The server bin binds the well-known port, the tests bind an ephemeral one
+//! and send datagrams from client threads.
+
+pub mod sequence;
+
+use mio::net::UdpSocket;
+use mio::{Interest, Registry, Token};
+use mio_common::EventLoop;
+use sequence::{Arrival, Tracker};
+use std::collections::{HashMap, VecDeque};
+use std::io;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+const SERVER: Token = Token(0);
+
+/// The largest payload of a UDP datagram.
+pub const MAX_DATAGRAM: usize = 65_507;
+
+/// Echoes waiting for the socket to accept them. Past this bound, the new
+/// datagrams are dropped without echo, as the kernel does when its buffers
+/// are full.
+const MAX_PENDING: usize = 1024;
+
+/// A UDP echo server: every datagram received is sent back to its origin.
+pub struct Server {
+    event_loop: EventLoop,
+    echo: Echo,
+}
+
+/// The socket and the echoes waiting to be sent, apart from the event loop
+/// borrowed while the events are handled.
+struct Echo {
+    socket: UdpSocket,
+    /// The sequence numbers received from each peer.
+    peers: HashMap<SocketAddr, Tracker>,
+    /// Received but not echoed yet: a burst of datagrams may fill the send
+    /// buffer, the rest is sent on the next WRITABLE event.
+    pending: VecDeque<(Vec<u8>, SocketAddr)>,
+    /// Whether the socket is registered WRITABLE.
+    writable: bool,
+}
+
+impl Server {
+    /// Binds the server to `address`, use port 0 to let the OS pick one.
+    pub fn bind(address: SocketAddr) -> io::Result<Server> {
+        let event_loop = EventLoop::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+
+        let mut socket = UdpSocket::bind(address)?;
+        event_loop
+            .registry()
+            .register(&mut socket, SERVER, Interest::READABLE)?;
+
+        Ok(Server {
+            event_loop,
+            echo: Echo {
+                socket,
+                peers: HashMap::new(),
+                pending: VecDeque::new(),
+                writable: false,
+            },
+        })
+    }
+
+    /// Returns the address the server listens on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.echo.socket.local_addr()
+    }
+
+    /// Serves the peers, forever. Only fails if polling fails.
+    pub fn run(mut self) -> io::Result<()> {
+        let mut buffer = vec![0; MAX_DATAGRAM];
+
+        loop {
+            let ready = self.event_loop.poll()?;
+
+            for event in &ready {
+                if event.token() != SERVER {
+                    continue;
+                }
+
+                if event.is_readable() {
+                    self.echo.receive_all(&mut buffer);
+                }
+
+                // Try sending right away: most of the time the socket accepts
+                // the whole burst, and the WRITABLE event is only needed for
+                // the rest.
+                if let Err(e) = self.echo.send_pending() {
+                    eprintln!("❌ Send error: {}", e);
+                }
+                self.echo.update_interest(ready.registry())?;
+            }
+        }
+    }
+}
+
+impl Echo {
+    /// Receives until `WouldBlock`: mio is edge-triggered, so datagrams left
+    /// in the socket are not reported again.
+    fn receive_all(&mut self, buffer: &mut [u8]) {
+        loop {
+            let (n, peer) = match self.socket.recv_from(buffer) {
+                Ok(received) => received,
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => return,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                // E.g. an ICMP error about an echo sent earlier: keep serving
+                // the others.
+                Err(e) => {
+                    eprintln!("❌ Receive error: {}", e);
+                    continue;
+                }
+            };
+
+            let datagram = &buffer[..n];
+            let Some((seq, _)) = sequence::decode(datagram) else {
+                eprintln!(
+                    "❌ Datagram of {} bytes from {} has no sequence number",
+                    n, peer
+                );
+                continue;
+            };
+            self.track(peer, seq);
+
+            if self.pending.len() >= MAX_PENDING {
+                eprintln!("🗑️ Too many pending echoes, #{} from {} dropped", seq, peer);
+                continue;
+            }
+            self.pending.push_back((datagram.to_vec(), peer));
+        }
+    }
+
+    /// Records the sequence number of a datagram from `peer`, and reports
+    /// the ones out of order.
+    fn track(&mut self, peer: SocketAddr, seq: u64) {
+        let tracker = self.peers.entry(peer).or_insert_with(|| {
+            println!("✅ New peer {}", peer);
+            Tracker::default()
+        });
+
+        match tracker.record(seq) {
+            Arrival::InOrder => {}
+            Arrival::Gap { missing } => {
+                println!(
+                    "⚠️ {}: #{} received, {} missing before it",
+                    peer, seq, missing
+                )
+            }
+            Arrival::Late => println!("🔀 {}: #{} arrived late", peer, seq),
+            Arrival::Duplicate => println!("♊ {}: #{} received twice", peer, seq),
+        }
+    }
+
+    /// Sends the pending echoes until the socket would block.
+    fn send_pending(&mut self) -> io::Result<()> {
+        while let Some((datagram, peer)) = self.pending.front() {
+            match self.socket.send_to(datagram, *peer) {
+                // A datagram is sent whole or not at all.
+                Ok(_) => {
+                    self.pending.pop_front();
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    // The echo can't be sent to this peer, don't retry it.
+                    self.pending.pop_front();
+                    return Err(e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// WRITABLE is only registered while echoes are pending, otherwise every
+    /// poll reports the idle socket writable.
+    fn update_interest(&mut self, registry: &Registry) -> io::Result<()> {
+        let writable = !self.pending.is_empty();
+        if writable != self.writable {
+            let interest = if writable {
+                Interest::READABLE | Interest::WRITABLE
+            } else {
+                Interest::READABLE
+            };
+            registry.reregister(&mut self.socket, SERVER, interest)?;
+            self.writable = writable;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashSet;
+
+/// Length of the sequence number heading every datagram.
+pub const HEADER_LEN: usize = 8;
+
+/// Writes a datagram into `buf`: the sequence number, big-endian, followed
+/// by the payload.
+///
+/// ```
+/// use mio_v4::sequence;
+///
+/// let mut datagram = Vec::new();
+/// sequence::encode(7, b"ping", &mut datagram);
+/// assert_eq!(sequence::decode(&datagram), Some((7, &b"ping"[..])));
+/// ```
+pub fn encode(seq: u64, payload: &[u8], buf: &mut Vec<u8>) {
+    buf.clear();
+    buf.extend_from_slice(&seq.to_be_bytes());
+    buf.extend_from_slice(payload);
+}
+
+/// Splits a datagram into its sequence number and its payload, `None` if it
+/// is too short to hold a sequence number.
+pub fn decode(datagram: &[u8]) -> Option<(u64, &[u8])> {
+    let (header, payload) = datagram.split_first_chunk::<HEADER_LEN>()?;
+    Some((u64::from_be_bytes(*header), payload))
+}
+
+/// How a datagram arrived, compared to the ones before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrival {
+    /// The next sequence number.
+    InOrder,
+    /// Ahead of the next sequence number: the `missing` ones in between were
+    /// lost, or are late.
+    Gap { missing: u64 },
+    /// Behind a sequence number already received: overtaken by a later
+    /// datagram.
+    Late,
+    /// A sequence number already received.
+    Duplicate,
+}
+
+/// Tracks the sequence numbers received from a peer, to tell the lost,
+/// reordered and duplicated datagrams apart.
+///
+/// UDP doesn't keep the datagrams in order, nor retransmit the lost ones: a
+/// gap in the sequence numbers is a loss until the missing datagrams arrive
+/// late.
+///
+/// ```
+/// use mio_v4::sequence::{Arrival, Tracker};
+///
+/// let mut tracker = Tracker::default();
+/// assert_eq!(tracker.record(0), Arrival::InOrder);
+/// assert_eq!(tracker.record(3), Arrival::Gap { missing: 2 });
+/// assert_eq!(tracker.record(1), Arrival::Late);
+/// assert_eq!(tracker.lost(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct Tracker {
+    seen: HashSet<u64>,
+    /// The sequence number following the highest one received.
+    next: u64,
+    reordered: u64,
+    duplicates: u64,
+}
+
+impl Tracker {
+    pub fn record(&mut self, seq: u64) -> Arrival {
+        if !self.seen.insert(seq) {
+            self.duplicates += 1;
+            return Arrival::Duplicate;
+        }
+
+        if seq < self.next {
+            self.reordered += 1;
+            return Arrival::Late;
+        }
+
+        let missing = seq - self.next;
+        self.next = seq + 1;
+        if missing == 0 {
+            Arrival::InOrder
+        } else {
+            Arrival::Gap { missing }
+        }
+    }
+
+    /// The number of distinct sequence numbers received.
+    pub fn received(&self) -> u64 {
+        self.seen.len() as u64
+    }
+
+    /// The number of sequence numbers below the highest one received that
+    /// are still missing. Losses at the tail are only known to the sender.
+    pub fn lost(&self) -> u64 {
+        self.next - self.received()
+    }
+
+    /// The number of datagrams that arrived after a later one.
+    pub fn reordered(&self) -> u64 {
+        self.reordered
+    }
+
+    /// The number of datagrams received more than once.
+    pub fn duplicates(&self) -> u64 {
+        self.duplicates
+    }
+}
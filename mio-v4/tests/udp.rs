@@ -0,0 +1,113 @@
+use mio_v4::Server;
+use mio_v4::sequence::{self, Arrival, Tracker};
+use std::net::{SocketAddr, UdpSocket};
+use std::thread;
+use std::time::Duration;
+
+/// A test fails instead of hanging if an echo never comes back.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Starts a server on an ephemeral port. It runs until the test process
+/// exits.
+fn start_server() -> SocketAddr {
+    let server = Server::bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let address = server.local_addr().unwrap();
+    thread::spawn(move || server.run());
+    address
+}
+
+fn connect(address: SocketAddr) -> UdpSocket {
+    let socket = UdpSocket::bind("127.0.0.1:0").unwrap();
+    socket.connect(address).unwrap();
+    socket.set_read_timeout(Some(TIMEOUT)).unwrap();
+    socket
+}
+
+/// Receives an echo and splits it into its sequence number and payload.
+fn recv(socket: &UdpSocket) -> (u64, Vec<u8>) {
+    let mut buffer = [0; 1024];
+    let n = socket.recv(&mut buffer).unwrap();
+    let (seq, payload) = sequence::decode(&buffer[..n]).unwrap();
+    (seq, payload.to_vec())
+}
+
+#[test]
+fn echoes_numbered_datagrams() {
+    // Few enough to fit in the socket buffers: loopback loses nothing.
+    const DATAGRAMS: u64 = 100;
+
+    let socket = connect(start_server());
+    let mut datagram = Vec::new();
+    for seq in 0..DATAGRAMS {
+        sequence::encode(seq, format!("datagram {seq}").as_bytes(), &mut datagram);
+        socket.send(&datagram).unwrap();
+    }
+
+    let mut tracker = Tracker::default();
+    for _ in 0..DATAGRAMS {
+        let (seq, payload) = recv(&socket);
+        assert_eq!(payload, format!("datagram {seq}").into_bytes());
+        tracker.record(seq);
+    }
+
+    assert_eq!(tracker.received(), DATAGRAMS);
+    assert_eq!(tracker.lost(), 0);
+    assert_eq!(tracker.reordered(), 0);
+    assert_eq!(tracker.duplicates(), 0);
+}
+
+#[test]
+fn echoes_each_peer() {
+    let address = start_server();
+    let sockets: Vec<_> = (0..5).map(|_| connect(address)).collect();
+
+    let mut datagram = Vec::new();
+    for (seq, socket) in sockets.iter().enumerate() {
+        sequence::encode(seq as u64, b"hello", &mut datagram);
+        socket.send(&datagram).unwrap();
+    }
+
+    for (seq, socket) in sockets.iter().enumerate() {
+        assert_eq!(recv(socket), (seq as u64, b"hello".to_vec()));
+    }
+}
+
+#[test]
+fn drops_datagrams_without_sequence_number() {
+    let socket = connect(start_server());
+
+    socket.send(b"bad").unwrap();
+    let mut datagram = Vec::new();
+    sequence::encode(0, b"good", &mut datagram);
+    socket.send(&datagram).unwrap();
+
+    // Only the valid datagram is echoed.
+    assert_eq!(recv(&socket), (0, b"good".to_vec()));
+}
+
+#[test]
+fn tracker_tells_losses_reorderings_and_duplicates_apart() {
+    let mut tracker = Tracker::default();
+
+    assert_eq!(tracker.record(0), Arrival::InOrder);
+    assert_eq!(tracker.record(1), Arrival::InOrder);
+    assert_eq!(tracker.record(4), Arrival::Gap { missing: 2 });
+    assert_eq!(tracker.lost(), 2);
+
+    // 3 was overtaken by 4: not lost after all.
+    assert_eq!(tracker.record(3), Arrival::Late);
+    assert_eq!(tracker.lost(), 1);
+    assert_eq!(tracker.record(3), Arrival::Duplicate);
+    assert_eq!(tracker.record(5), Arrival::InOrder);
+
+    assert_eq!(tracker.received(), 5);
+    assert_eq!(tracker.lost(), 1);
+    assert_eq!(tracker.reordered(), 1);
+    assert_eq!(tracker.duplicates(), 1);
+}
+
+#[test]
+fn decode_rejects_short_datagrams() {
+    assert_eq!(sequence::decode(&[0; 7]), None);
+    assert_eq!(sequence::decode(&[0; 8]), Some((0, &[][..])));
+}
@@ -0,0 +1,177 @@
+use crate::scoped::Scoped;
+use std::error::Error;
+use std::fmt;
+use std::thread::LocalKey;
+
+/// A scoped thread-local, declared with `scoped_thread_local!`.
+///
+/// It's a `Scoped<T>` stored in a `thread_local!`, along with the policy to
+/// apply when it's accessed while no value is set.
+pub struct ScopedKey<T: 'static> {
+    inner: &'static LocalKey<Scoped<T>>,
+    not_set: NotSetPolicy<T>,
+}
+
+/// What `ScopedKey::with` does when no value is set.
+pub enum NotSetPolicy<T: 'static> {
+    /// Panic: the code expected to run within a `set` scope, and doesn't.
+    Panic,
+    /// Run the closure with the value returned by the function instead, e.g.
+    /// a default context for the code running outside of any request.
+    ///
+    /// A function rather than a `&'static T`, so that the key can be shared
+    /// between threads whether or not `T` is `Sync`.
+    Fallback(fn() -> &'static T),
+}
+
+/// The error returned by `ScopedKey::try_with` when no value is set.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AccessError;
+
+impl<T: 'static> ScopedKey<T> {
+    #[doc(hidden)]
+    pub const fn new(inner: &'static LocalKey<Scoped<T>>, not_set: NotSetPolicy<T>) -> Self {
+        Self { inner, not_set }
+    }
+
+    /// Sets `t` as the value of this key for the duration of `f`. The previous
+    /// value is restored once `f` returns, or panics.
+    pub fn set<F, R>(&'static self, t: &T, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        self.inner.with(|scoped| scoped.set(t, f))
+    }
+
+    /// Executes `f` with the value of the innermost `set` scope.
+    ///
+    /// # Panics
+    ///
+    /// Panics if no value is set and the key was declared with the default
+    /// `NotSetPolicy::Panic`.
+    #[track_caller]
+    pub fn with<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.inner.with(|scoped| {
+            scoped.with(|value| match (value, &self.not_set) {
+                (Some(value), _) => f(value),
+                (None, NotSetPolicy::Fallback(fallback)) => f(fallback()),
+                (None, NotSetPolicy::Panic) => panic!(
+                    "cannot access a scoped thread local variable without calling `set` first"
+                ),
+            })
+        })
+    }
+
+    /// Executes `f` with the value of the innermost `set` scope, or fails if
+    /// none is set. The `NotSetPolicy` doesn't apply.
+    pub fn try_with<F, R>(&'static self, f: F) -> Result<R, AccessError>
+    where
+        F: FnOnce(&T) -> R,
+    {
+        self.inner
+            .with(|scoped| scoped.with(|value| value.map(f)))
+            .ok_or(AccessError)
+    }
+
+    /// Returns `true` if a value is set on the current thread.
+    pub fn is_set(&'static self) -> bool {
+        self.inner.with(Scoped::is_set)
+    }
+
+    /// Returns the number of nested `set` scopes running on the current thread.
+    pub fn depth(&'static self) -> usize {
+        self.inner.with(Scoped::depth)
+    }
+
+    /// Executes `f` with the values of all the nested `set` scopes, outermost
+    /// first.
+    pub fn with_all<F, R>(&'static self, f: F) -> R
+    where
+        F: FnOnce(&[&T]) -> R,
+    {
+        self.inner.with(|scoped| scoped.with_all(f))
+    }
+}
+
+impl fmt::Display for AccessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("scoped thread local variable not set")
+    }
+}
+
+impl Error for AccessError {}
+
+#[cfg(test)]
+mod tests {
+    use crate::{AccessError, NotSetPolicy};
+    use std::panic::{self, AssertUnwindSafe};
+
+    crate::scoped_thread_local!(static ID: u64);
+    crate::scoped_thread_local!(static WITH_FALLBACK: u64 = NotSetPolicy::Fallback(|| &0));
+
+    #[test]
+    fn set_is_visible_only_within_the_scope() {
+        assert!(!ID.is_set());
+        ID.set(&1, || {
+            assert!(ID.is_set());
+            assert_eq!(ID.with(|id| *id), 1);
+        });
+        assert!(!ID.is_set());
+    }
+
+    #[test]
+    fn nested_scopes_shadow_and_restore() {
+        ID.set(&1, || {
+            ID.set(&2, || {
+                assert_eq!(ID.with(|id| *id), 2);
+                assert_eq!(ID.depth(), 2);
+                assert_eq!(
+                    ID.with_all(|ids| ids.iter().map(|id| **id).collect::<Vec<_>>()),
+                    [1, 2]
+                );
+            });
+            assert_eq!(ID.with(|id| *id), 1);
+            assert_eq!(ID.depth(), 1);
+        });
+        assert_eq!(ID.depth(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "without calling `set` first")]
+    fn with_panics_when_not_set() {
+        ID.with(|_| ());
+    }
+
+    #[test]
+    fn try_with_fails_when_not_set() {
+        assert_eq!(ID.try_with(|id| *id), Err(AccessError));
+        assert_eq!(ID.set(&1, || ID.try_with(|id| *id)), Ok(1));
+    }
+
+    #[test]
+    fn fallback_policy() {
+        assert_eq!(WITH_FALLBACK.with(|id| *id), 0);
+        assert_eq!(WITH_FALLBACK.set(&1, || WITH_FALLBACK.with(|id| *id)), 1);
+        // `try_with` still tells that nothing is set.
+        assert_eq!(WITH_FALLBACK.try_with(|id| *id), Err(AccessError));
+    }
+
+    #[test]
+    fn panic_during_scope_restores_previous_value() {
+        let result = panic::catch_unwind(|| ID.set(&1, || panic!("boom")));
+        assert!(result.is_err());
+        assert!(!ID.is_set());
+
+        ID.set(&1, || {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                ID.set(&2, || panic!("boom"));
+            }));
+            assert!(result.is_err());
+            assert_eq!(ID.with(|id| *id), 1);
+            assert_eq!(ID.depth(), 1);
+        });
+    }
+}
@@ -0,0 +1,57 @@
+//! Scoped thread-local storage.
+//!
+//! A scoped thread-local holds a reference to a value for the duration of a
+//! closure, and is unset again once the closure returns or panics. Nested
+//! scopes shadow the outer ones and are all visible through `with_all`.
+//!
+//! ```
+//! scope::scoped_thread_local!(static REQUEST_ID: u64);
+//!
+//! fn log(message: &str) {
+//!     REQUEST_ID.with(|id| println!("[Request ID: {}] {}", id, message));
+//! }
+//!
+//! REQUEST_ID.set(&101, || log("Handling request 101."));
+//! assert!(!REQUEST_ID.is_set());
+//! ```
+
+mod key;
+mod scoped;
+
+pub use key::{AccessError, NotSetPolicy, ScopedKey};
+pub use scoped::Scoped;
+
+/// Declares a new scoped thread-local key of type `ScopedKey<T>`.
+///
+/// Accessing the key with `ScopedKey::with` outside of any `set` panics. A
+/// `NotSetPolicy` may be given after `=` to change that:
+///
+/// ```
+/// use scope::{NotSetPolicy, scoped_thread_local};
+///
+/// scoped_thread_local!(static REQUEST_ID: u64);
+/// scoped_thread_local!(static TENANT: &'static str = NotSetPolicy::Fallback(|| &"default"));
+///
+/// TENANT.with(|tenant| assert_eq!(*tenant, "default"));
+/// TENANT.set(&"acme", || TENANT.with(|tenant| assert_eq!(*tenant, "acme")));
+/// ```
+#[macro_export]
+macro_rules! scoped_thread_local {
+    ($(#[$attrs:meta])* $vis:vis static $name:ident: $ty:ty) => {
+        $crate::scoped_thread_local!(
+            $(#[$attrs])* $vis static $name: $ty = $crate::NotSetPolicy::Panic
+        );
+    };
+    ($(#[$attrs:meta])* $vis:vis static $name:ident: $ty:ty = $policy:expr) => {
+        $(#[$attrs])*
+        $vis static $name: $crate::ScopedKey<$ty> = $crate::ScopedKey::new(
+            {
+                ::std::thread_local! {
+                    static SCOPED: $crate::Scoped<$ty> = const { $crate::Scoped::new() };
+                }
+                &SCOPED
+            },
+            $policy,
+        );
+    };
+}
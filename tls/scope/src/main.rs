@@ -1,5 +1,4 @@
-mod scoped;
-use crate::scoped::Scoped;
+use scope::scoped_thread_local;
 
 // --- Simple Use Case: Request ID Propagation ---
 
@@ -8,21 +7,35 @@ use crate::scoped::Scoped;
 // without modifying the function signatures of log or process_step.
 // The scoping ensures the context is only active when needed and automatically cleaned up.
 
-// Define a thread-local static using the Scoped struct.
-// This will hold an optional u64 representing the current request ID.
-thread_local! {
-    static CURRENT_REQUEST_ID: Scoped<u64> = const {Scoped::new()};
-}
+// Define a scoped thread-local: a Scoped<u64> stored in a thread_local!, which
+// holds the current request ID while a request is being handled.
+scoped_thread_local!(static CURRENT_REQUEST_ID: u64);
 
 /// A simple logging function that includes the current request ID if set.
 fn log(message: &str) {
-    // Use the thread_local!'s with to get a reference to the Scoped instance,
-    // then call the Scoped's with method.
-    CURRENT_REQUEST_ID.with(|scoped_instance| {
-        scoped_instance.with(|request_id| match request_id {
-            Some(id) => println!("[Request ID: {}] {}", id, message),
-            None => println!("{}", message),
-        });
+    // `try_with` doesn't panic outside of a request, unlike `with`.
+    if CURRENT_REQUEST_ID
+        .try_with(|id| println!("[Request ID: {}] {}", id, message))
+        .is_err()
+    {
+        println!("{}", message);
+    }
+}
+
+/// Prints the whole chain of request IDs currently set, outermost first,
+/// e.g. `101 → 202` within the nested operation.
+fn log_context_stack() {
+    if !CURRENT_REQUEST_ID.is_set() {
+        println!("[Context] no request");
+        return;
+    }
+    CURRENT_REQUEST_ID.with_all(|request_ids| {
+        let chain: Vec<String> = request_ids.iter().map(|id| id.to_string()).collect();
+        println!(
+            "[Context depth {}] {}",
+            CURRENT_REQUEST_ID.depth(),
+            chain.join(" → ")
+        );
     });
 }
 
@@ -36,30 +49,27 @@ fn main() {
     log("Application starting.");
 
     let request_id_1 = 101;
-    // Use the thread_local!'s with to get a reference to the Scoped instance,
-    // then call the Scoped's set method.
-    CURRENT_REQUEST_ID.with(|scoped_instance| {
-        // Set the request ID for the scope of this closure
-        scoped_instance.set(&request_id_1, || {
-            log("Handling request 101.");
-            process_step("Authentication");
+    // Set the request ID for the scope of this closure
+    CURRENT_REQUEST_ID.set(&request_id_1, || {
+        log("Handling request 101.");
+        log_context_stack();
+        process_step("Authentication");
 
-            let request_id_2 = 202;
-            // Nest another scope with a different request ID
-            CURRENT_REQUEST_ID.with(|inner_scoped_instance| {
-                inner_scoped_instance.set(&request_id_2, || {
-                    log("Handling a nested operation for request 202.");
-                    process_step("Sub-process A");
-                    process_step("Sub-process B");
-                    log("Nested operation finished.");
-                }); // The inner Scoped::set scope ends here
-            }); // The inner thread_local!::with scope ends here, but doesn't change the Scoped value
+        let request_id_2 = 202;
+        // Nest another scope with a different request ID
+        CURRENT_REQUEST_ID.set(&request_id_2, || {
+            log("Handling a nested operation for request 202.");
+            log_context_stack();
+            process_step("Sub-process A");
+            process_step("Sub-process B");
+            log("Nested operation finished.");
+        }); // The inner scope ends here, CURRENT_REQUEST_ID is back to 101
 
-            process_step("Authorization");
-            log("Request 101 finished.");
-        }); // The outer Scoped::set scope ends here, CURRENT_REQUEST_ID is reset to None
-    }); // The outer thread_local!::with scope ends here, but doesn't change the Scoped value
+        process_step("Authorization");
+        log("Request 101 finished.");
+    }); // The outer scope ends here, CURRENT_REQUEST_ID is reset to None
 
     // Log after the request handling scopes have ended
     log("Application shutting down.");
+    log_context_stack();
 }
@@ -26,22 +26,46 @@ use std::ptr;
 ///
 /// It uses a raw pointer internally, allowing it to represent an unset state
 /// (null pointer) and to be set with a temporary borrow of `T`.
-/// The `pub(super)` visibility restricts its use to the parent module (`context`)
-/// and its submodules.
-pub(super) struct Scoped<T> {
-    /// Stores a raw pointer to the current value of `T`.
+///
+/// A `Scoped<T>` is meant to be stored in a `thread_local!`. The
+/// `scoped_thread_local!` macro declares one, wrapped in a `ScopedKey` that
+/// saves the `LocalKey::with` call on every access.
+pub struct Scoped<T> {
+    /// Stores a raw pointer to the innermost `Frame`, i.e. the one pushed by
+    /// the most recent `set` call still running.
     ///
     /// - `Cell`: Used for interior mutability, as thread-local storage typically
     ///   requires modification without an exclusive reference (`&mut self`) to the
     ///   thread-local static.
-    /// - `*const T`: A raw pointer is used instead of `Option<&'a T>` to:
+    /// - `*const Frame<T>`: A raw pointer is used instead of `Option<&'a Frame<T>>` to:
     ///   1. Represent an empty or unset state with `ptr::null()`.
     ///   2. Avoid complex lifetime annotations that would be needed if `Scoped`
     ///      itself tried to manage the lifetime of the borrowed `T` across `set`
     ///      and `with` calls directly in its type signature. The lifetime
     ///      management is effectively handled by the `set` method's RAII guard
     ///      and the caller's responsibility to ensure the borrowed `T` is valid.
-    pub inner: Cell<*const T>,
+    ///
+    /// The field is private: a pointer stored from outside of `set` would
+    /// break the guarantees the `unsafe` blocks rely on.
+    inner: Cell<*const Frame<T>>,
+}
+
+/// One level of nesting: the value given to a `set` call, linked to the frame
+/// of the enclosing `set` call.
+///
+/// Frames live on the stack of their `set` call, so the chain of frames mirrors
+/// the chain of nested scopes and costs no allocation.
+struct Frame<T> {
+    /// The value set by this scope.
+    value: *const T,
+    /// The frame of the enclosing scope, or null for the outermost one.
+    prev: *const Frame<T>,
+}
+
+impl<T> Default for Scoped<T> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<T> Scoped<T> {
@@ -84,8 +108,8 @@ impl<T> Scoped<T> {
         /// thread-local value is restored to the `cell` when this struct
         /// goes out of scope, even if the enclosed closure panics.
         struct Reset<'a, T> {
-            cell: &'a Cell<*const T>, // Reference to the Scoped's inner Cell.
-            prev: *const T,           // The pointer value to restore.
+            cell: &'a Cell<*const Frame<T>>, // Reference to the Scoped's inner Cell.
+            prev: *const Frame<T>,           // The pointer value to restore.
         }
 
         impl<T> Drop for Reset<'_, T> {
@@ -97,9 +121,14 @@ impl<T> Scoped<T> {
 
         // Get the current pointer value to be restored later.
         let prev_ptr = self.inner.get();
-        // Set the new value `t` by storing its pointer.
+        // Push a frame for `t`, linked to the enclosing one. The frame lives on
+        // this function's stack, which outlives the closure `f`.
         // `t` is a `&T`, so `t as *const _` casts it to `*const T`.
-        self.inner.set(t as *const _);
+        let frame = Frame {
+            value: t as *const _,
+            prev: prev_ptr,
+        };
+        self.inner.set(&frame as *const _);
 
         // Create the RAII guard. The `_` prefix for `_reset` indicates that
         // its binding is primarily for its side effect (the Drop implementation).
@@ -153,17 +182,62 @@ impl<T> Scoped<T> {
     where
         F: FnOnce(Option<&T>) -> R,
     {
-        // Get the current pointer value from the cell
-        let val_ptr = self.inner.get();
+        // Get the current frame pointer from the cell
+        let frame_ptr = self.inner.get();
 
-        if val_ptr.is_null() {
+        if frame_ptr.is_null() {
             // If null, no value is set, call closure with None
             f(None)
         } else {
-            // If not null, it points to a valid T reference set by `set`.
-            // Dereference the raw pointer to get a reference to T.
-            // This is safe because `set` ensures the pointer validity within its scope.
-            unsafe { f(Some(&*val_ptr)) }
+            // If not null, it points to a valid frame pushed by `set`, which
+            // points to a valid T.
+            // This is safe because `set` ensures the pointers validity within its scope.
+            unsafe { f(Some(&*(*frame_ptr).value)) }
+        }
+    }
+
+    /// Returns `true` if a value is currently set, i.e. if this is called from
+    /// within a `set` closure.
+    pub fn is_set(&self) -> bool {
+        !self.inner.get().is_null()
+    }
+
+    /// Returns the number of nested `set` calls currently running, `0` if no
+    /// value is set.
+    pub fn depth(&self) -> usize {
+        let mut depth = 0;
+        let mut frame_ptr = self.inner.get();
+        while !frame_ptr.is_null() {
+            depth += 1;
+            // Safety: see `with_all`.
+            frame_ptr = unsafe { (*frame_ptr).prev };
+        }
+        depth
+    }
+
+    /// Executes a closure `f` with all the values currently set, from the
+    /// outermost scope to the innermost one, e.g. to print the whole context
+    /// stack in diagnostics. The slice is empty if no value is set.
+    ///
+    /// # Safety
+    /// Each frame is pushed on the stack of a `set` call, which runs until the
+    /// frames of the scopes nested in it are popped: while a frame is the
+    /// current one, all the frames it links to, and the values they point to,
+    /// are valid. See `with` for the rest of the reasoning.
+    pub fn with_all<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&[&T]) -> R,
+    {
+        let mut values = Vec::with_capacity(self.depth());
+        let mut frame_ptr = self.inner.get();
+        while !frame_ptr.is_null() {
+            unsafe {
+                values.push(&*(*frame_ptr).value);
+                frame_ptr = (*frame_ptr).prev;
+            }
         }
+        // The chain links each frame to its enclosing one: innermost first.
+        values.reverse();
+        f(&values)
     }
 }
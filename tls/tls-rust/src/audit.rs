@@ -0,0 +1,154 @@
+use crate::response::{Response, ResponseStatus};
+use serde::ser::SerializeStruct;
+use serde::{Serialize, Serializer};
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::Mutex;
+
+/// The login attempts of all the users, most recent last.
+///
+/// The login context belongs to the thread serving a client, but an audit
+/// trail must see every thread, like the buckets of the `RateLimiter`: the
+/// log is shared by the services of all the threads behind an `Arc`, and
+/// its events sit behind a `Mutex`.
+///
+/// Events are only appended. The log keeps the last `capacity` of them, the
+/// oldest are dropped to make room and counted, see `dropped`.
+pub struct AuditLog {
+    capacity: usize,
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    events: VecDeque<AuditEvent>,
+    dropped: u64,
+}
+
+/// A login attempt, as recorded by the `AuditLog`.
+///
+/// It serializes to one JSON object:
+///
+/// ```json
+/// {"username":"user1","timestamp":1718000000,"outcome":"AuthError"}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuditEvent {
+    /// Who tried to log in.
+    pub(crate) username: String,
+
+    /// When, in seconds since the Unix epoch: the time of the response.
+    pub(crate) timestamp: u64,
+
+    /// The status answered to the attempt.
+    pub(crate) outcome: ResponseStatus,
+}
+
+impl AuditLog {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            capacity,
+            inner: Mutex::new(Inner {
+                events: VecDeque::with_capacity(capacity),
+                dropped: 0,
+            }),
+        }
+    }
+
+    /// Records the login attempt of `username`, answered with `response`.
+    pub(crate) fn record(&self, username: &str, response: &Response) {
+        let event = AuditEvent {
+            username: username.to_string(),
+            timestamp: response.timestamp,
+            outcome: response.status,
+        };
+
+        let mut inner = self.inner.lock().unwrap();
+        if inner.events.len() == self.capacity {
+            inner.events.pop_front();
+            inner.dropped += 1;
+        }
+        inner.events.push_back(event);
+    }
+
+    /// Returns a copy of the events kept, oldest first.
+    pub fn events(&self) -> Vec<AuditEvent> {
+        self.inner.lock().unwrap().events.iter().cloned().collect()
+    }
+
+    /// Returns the number of events dropped to make room for newer ones.
+    pub fn dropped(&self) -> u64 {
+        self.inner.lock().unwrap().dropped
+    }
+
+    /// Writes the events kept to `out` as JSON lines, one event per line,
+    /// oldest first.
+    ///
+    /// The events are copied first: the lock isn't held while writing to a
+    /// possibly slow `out`.
+    pub fn export_json_lines(&self, mut out: impl Write) -> io::Result<()> {
+        for event in self.events() {
+            serde_json::to_writer(&mut out, &event)?;
+            out.write_all(b"\n")?;
+        }
+        out.flush()
+    }
+}
+
+impl Serialize for AuditEvent {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("AuditEvent", 3)?;
+        state.serialize_field("username", &self.username)?;
+        state.serialize_field("timestamp", &self.timestamp)?;
+        state.serialize_field("outcome", &self.outcome)?;
+        state.end()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(status: ResponseStatus) -> Response {
+        Response {
+            timestamp: 1718000000,
+            ..Response::new(status, "")
+        }
+    }
+
+    #[test]
+    fn keeps_the_most_recent_events() {
+        let log = AuditLog::new(2);
+
+        log.record("user1", &response(ResponseStatus::AuthError));
+        log.record("user1", &response(ResponseStatus::Success));
+        log.record("user2", &response(ResponseStatus::TooManyRequests));
+
+        let events = log.events();
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].username, "user1");
+        assert_eq!(events[0].outcome, ResponseStatus::Success);
+        assert_eq!(events[1].username, "user2");
+        assert_eq!(log.dropped(), 1);
+    }
+
+    #[test]
+    fn exports_json_lines() {
+        let log = AuditLog::new(8);
+        log.record("user1", &response(ResponseStatus::AuthError));
+        log.record("user1", &response(ResponseStatus::Success));
+
+        let mut out = Vec::new();
+        log.export_json_lines(&mut out).unwrap();
+
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            concat!(
+                r#"{"username":"user1","timestamp":1718000000,"outcome":"AuthError"}"#,
+                "\n",
+                r#"{"username":"user1","timestamp":1718000000,"outcome":"Success"}"#,
+                "\n",
+            )
+        );
+    }
+}
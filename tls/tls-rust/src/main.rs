@@ -1,19 +1,32 @@
+use crate::audit::AuditLog;
+use crate::password::Pbkdf2;
+use crate::rate_limiter::RateLimiter;
 use crate::request::Request;
 use crate::request_handler::RequestHandler;
+use crate::response::ResponseStatus;
 use crate::service_v2::Service;
-use std::thread;
+use crate::session_store::SessionStore;
+use std::io;
+use std::sync::Arc;
 use std::time::Duration;
+use std::{env, thread};
 use tracing_subscriber::FmtSubscriber;
 use tracing_subscriber::fmt::format;
 use tracing_subscriber::fmt::time::UtcTime;
+use tutorial_util::Result;
 
+mod audit;
+mod password;
+mod rate_limiter;
 mod request;
 mod request_handler;
 mod response;
+mod server;
 //mod service_v1;
 mod service_v2;
+mod session_store;
 
-fn main() {
+fn main() -> Result<()> {
     let subscriber = FmtSubscriber::builder()
         .with_timer(UtcTime::rfc_3339())
         .with_thread_ids(true) // Enable printing thread IDs
@@ -24,18 +37,44 @@ fn main() {
     tracing::subscriber::set_global_default(subscriber)
         .expect("Failed to set global default subscriber");
 
-    thread::spawn(|| {
-        RequestHandler::new(Service::new(), vec![Request::new("user1", "pass1")]).run()
-    });
-    thread::spawn(|| {
-        RequestHandler::new(Service::new(), vec![Request::new("user2", "pass2")]).run()
-    });
+    // `cargo run -- server [address]` serves the requests over TCP instead of
+    // sending them from threads in this process.
+    let mut args = env::args().skip(1);
+    if args.next().as_deref() == Some("server") {
+        let address = args.next().unwrap_or_else(|| "127.0.0.1:7000".to_string());
+        return server::run(address.parse()?);
+    }
 
-    thread::sleep(Duration::from_millis(1000));
+    // The login attempts of a user are limited across all the threads: user1
+    // sends 4 requests from 2 threads, and only 3 are allowed.
+    let rate_limiter = Arc::new(RateLimiter::new(3, 0.1));
+    // Every attempt, from any thread, ends up in the same audit log.
+    let audit_log = Arc::new(AuditLog::new(16));
 
-    let handle = thread::spawn(|| {
+    let outcomes = RequestHandler::new(
+        Service::new()
+            // Fewer iterations than the default, hashing is only a demo here.
+            .with_password_verifier(Arc::new(Pbkdf2::new(1_000)))
+            .with_rate_limiter(rate_limiter.clone())
+            .with_audit_log(audit_log.clone()),
+        vec![
+            Request::new("user1", "pass1"),
+            Request::new("user2", "pass2"),
+        ],
+    )
+    .run_parallel(2);
+    assert_eq!(
+        statuses(&outcomes),
+        [ResponseStatus::Success, ResponseStatus::Success]
+    );
+
+    let service = Service::new()
+        .with_session_ttl(Duration::from_secs(60))
+        .with_rate_limiter(rate_limiter)
+        .with_audit_log(audit_log.clone());
+    let outcomes = thread::spawn(move || {
         RequestHandler::new(
-            Service::new(),
+            service,
             vec![
                 Request::new("user1", "wrong_pass"),
                 Request::new("user1", "pass1"),
@@ -43,7 +82,47 @@ fn main() {
             ],
         )
         .run()
-    });
+    })
+    .join()
+    .unwrap();
+    assert_eq!(
+        statuses(&outcomes),
+        [
+            ResponseStatus::AuthError,
+            ResponseStatus::Success,
+            ResponseStatus::TooManyRequests,
+        ]
+    );
+
+    // A token is valid for every service sharing the session store, not
+    // only on the thread which logged in.
+    let sessions = Arc::new(SessionStore::new());
+    let login = thread::spawn({
+        let sessions = sessions.clone();
+        move || {
+            Service::new()
+                .with_session_store(sessions)
+                .get(&Request::new("user2", "pass2"))
+        }
+    })
+    .join()
+    .unwrap();
+    let token = login
+        .session_token
+        .expect("a login returns a session token");
+    let service = Service::new().with_session_store(sessions);
+    assert_eq!(service.validate(&token).status, ResponseStatus::Success);
+    assert_eq!(service.validate("forged").status, ResponseStatus::AuthError);
+
+    println!("Audit log:");
+    audit_log.export_json_lines(io::stdout().lock())?;
+    if audit_log.dropped() > 0 {
+        println!("({} older attempts dropped)", audit_log.dropped());
+    }
+
+    Ok(())
+}
 
-    handle.join().unwrap();
+fn statuses(outcomes: &[(Request, ResponseStatus)]) -> Vec<ResponseStatus> {
+    outcomes.iter().map(|(_, status)| *status).collect()
 }
@@ -0,0 +1,100 @@
+use sha2::Sha256;
+use subtle::ConstantTimeEq;
+
+/// Iterations of PBKDF2 by default. Each one costs a guess the same work, a
+/// production service would rather go for hundreds of thousands.
+const DEFAULT_ITERATIONS: u32 = 10_000;
+
+const SALT_LEN: usize = 16;
+
+/// A password as it is stored: never in clear, only a key derived from it and
+/// a random salt, so that equal passwords get different hashes and an attacker
+/// can't look them up in precomputed tables.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PasswordHash {
+    salt: Vec<u8>,
+    iterations: u32,
+    key: [u8; 32],
+}
+
+/// Hashes passwords for storage, and checks a password against its hash.
+///
+/// A slow, salted key derivation such as PBKDF2 or Argon2 sits behind it.
+pub trait PasswordVerifier: Send + Sync {
+    fn hash(&self, password: &str) -> PasswordHash;
+
+    /// Returns whether `password` matches `hash`, in a time independent of
+    /// where they differ.
+    fn verify(&self, password: &str, hash: &PasswordHash) -> bool;
+}
+
+/// PBKDF2 with HMAC-SHA256, from the `pbkdf2` and `sha2` crates.
+#[derive(Debug, Clone, Copy)]
+pub struct Pbkdf2 {
+    iterations: u32,
+}
+
+impl Pbkdf2 {
+    pub fn new(iterations: u32) -> Self {
+        assert!(iterations > 0, "iterations must be positive");
+        Self { iterations }
+    }
+}
+
+impl Default for Pbkdf2 {
+    fn default() -> Self {
+        Self::new(DEFAULT_ITERATIONS)
+    }
+}
+
+impl PasswordVerifier for Pbkdf2 {
+    fn hash(&self, password: &str) -> PasswordHash {
+        let salt = random_bytes(SALT_LEN);
+        let key = derive_key(password, &salt, self.iterations);
+        PasswordHash {
+            salt,
+            iterations: self.iterations,
+            key,
+        }
+    }
+
+    fn verify(&self, password: &str, hash: &PasswordHash) -> bool {
+        // The hash keeps its own iteration count: raising `iterations` doesn't
+        // invalidate the passwords hashed before.
+        let key = derive_key(password, &hash.salt, hash.iterations);
+        // Without returning at the first difference: the time taken doesn't
+        // tell an attacker how many leading bytes they got right.
+        key.ct_eq(&hash.key).into()
+    }
+}
+
+/// Derives a 32 bytes key from `password`.
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> [u8; 32] {
+    let mut key = [0; 32];
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, &mut key);
+    key
+}
+
+/// Returns `len` unpredictable bytes, from the OS's cryptographic random
+/// number generator.
+pub(crate) fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0; len];
+    getrandom::fill(&mut bytes).expect("the OS random number generator failed");
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_only_the_hashed_password() {
+        let verifier = Pbkdf2::new(100);
+        let hash = verifier.hash("pass1");
+
+        assert!(verifier.verify("pass1", &hash));
+        assert!(!verifier.verify("pass2", &hash));
+        // Salted: the same password hashes differently every time.
+        assert_ne!(verifier.hash("pass1"), hash);
+    }
+}
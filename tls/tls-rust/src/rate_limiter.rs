@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// A token bucket per username.
+///
+/// Each request takes a token from the bucket of its user, and the buckets
+/// refill at a steady rate up to their capacity: a user may send a burst of
+/// `capacity` requests, then `refill_per_second` requests per second.
+///
+/// Unlike the login context, which belongs to the thread serving a client,
+/// the buckets must be shared by all the threads: a user sending requests
+/// from several threads at once is limited all the same. So they sit behind a
+/// `Mutex`, like the login context of `service_v1`.
+pub struct RateLimiter {
+    capacity: f64,
+    refill_per_second: f64,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(capacity: u32, refill_per_second: f64) -> Self {
+        assert!(capacity > 0, "capacity must be positive");
+        Self {
+            capacity: capacity as f64,
+            refill_per_second,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Takes a token from the bucket of `username`, returns `false` if the
+    /// bucket is empty.
+    pub fn try_acquire(&self, username: &str) -> bool {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(username.to_string()).or_insert(Bucket {
+            tokens: self.capacity,
+            refilled_at: now,
+        });
+
+        let elapsed = now.duration_since(bucket.refilled_at).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_second).min(self.capacity);
+        bucket.refilled_at = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use std::time::Duration;
+
+    #[test]
+    fn burst_is_limited_to_the_capacity() {
+        let limiter = RateLimiter::new(2, 0.0);
+
+        assert!(limiter.try_acquire("user1"));
+        assert!(limiter.try_acquire("user1"));
+        assert!(!limiter.try_acquire("user1"));
+        // Each user has a bucket of their own.
+        assert!(limiter.try_acquire("user2"));
+    }
+
+    #[test]
+    fn bucket_refills_over_time() {
+        let limiter = RateLimiter::new(1, 20.0);
+
+        assert!(limiter.try_acquire("user1"));
+        assert!(!limiter.try_acquire("user1"));
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(limiter.try_acquire("user1"));
+    }
+}
@@ -1,6 +1,7 @@
 use crate::request::Request;
-use crate::response::ResponseStatus;
+use crate::response::{Response, ResponseStatus};
 use crate::service_v2::Service;
+use std::thread;
 use tracing::{Level, event};
 
 pub struct RequestHandler {
@@ -13,22 +14,168 @@ impl RequestHandler {
         Self { service, requests }
     }
 
-    pub fn run(&self) {
+    /// Sends the requests in order on the current thread, then logs out the
+    /// user of the last one.
+    ///
+    /// Returns the status of each request, in order. The logout is only
+    /// logged.
+    pub fn run(&self) -> Vec<(Request, ResponseStatus)> {
         event!(
             Level::INFO,
             "Starting request handler with {} requests",
             self.requests.len()
         );
-        for request in &self.requests {
-            event!(Level::INFO, "Sending request: {}", request);
-            let response = self.service.get(request);
-            match response.status {
-                ResponseStatus::Success => event!(Level::INFO, "Got response: Success"),
-                ResponseStatus::SuccessAlreadyLoggedIn => {
-                    event!(Level::INFO, "Got response: SuccessAlreadyLoggedIn")
-                }
-                ResponseStatus::AuthError => println!("Got response: AuthError"),
-            }
+        let outcomes = self.send_all(&self.requests);
+
+        if let Some(request) = self.requests.last() {
+            event!(Level::INFO, "Logging out: {}", request.username());
+            let response = self.service.logout(request);
+            log_response("Got logout response", &response);
         }
+        outcomes
+    }
+
+    /// Sends the requests from `n_workers` threads, each one sending a
+    /// contiguous slice of them in order.
+    ///
+    /// Returns the status of each request, in the order of the requests. The
+    /// login context is per thread: a login is only seen by the requests of
+    /// the same worker, and ends with it, without a logout. The rate limiter
+    /// is shared by all of them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n_workers` is 0.
+    pub fn run_parallel(&self, n_workers: usize) -> Vec<(Request, ResponseStatus)> {
+        assert!(n_workers > 0, "n_workers must be positive");
+        event!(
+            Level::INFO,
+            "Starting request handler with {} requests on {} workers",
+            self.requests.len(),
+            n_workers
+        );
+
+        let chunk_size = self.requests.len().div_ceil(n_workers).max(1);
+        thread::scope(|scope| {
+            let workers: Vec<_> = self
+                .requests
+                .chunks(chunk_size)
+                .map(|requests| scope.spawn(|| self.send_all(requests)))
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        })
+    }
+
+    fn send_all(&self, requests: &[Request]) -> Vec<(Request, ResponseStatus)> {
+        requests
+            .iter()
+            .map(|request| {
+                event!(Level::INFO, "Sending request: {}", request);
+                let response = self.service.get(request);
+                log_response("Got response", &response);
+                (request.clone(), response.status)
+            })
+            .collect()
+    }
+}
+
+/// Logs the JSON form of `response`, as it would be sent over the wire.
+fn log_response(what: &str, response: &Response) {
+    let json = serde_json::to_string(response).expect("a response serializes to JSON");
+    match response.status {
+        ResponseStatus::Success | ResponseStatus::SuccessAlreadyLoggedIn => {
+            event!(Level::INFO, "{}: {}", what, json)
+        }
+        ResponseStatus::AuthError | ResponseStatus::TooManyRequests => {
+            event!(Level::WARN, "{}: {}", what, json)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate_limiter::RateLimiter;
+    use std::sync::Arc;
+
+    fn statuses(outcomes: Vec<(Request, ResponseStatus)>) -> Vec<ResponseStatus> {
+        outcomes.into_iter().map(|(_, status)| status).collect()
+    }
+
+    #[test]
+    fn run_returns_the_status_of_each_request() {
+        let requests = vec![
+            Request::new("user1", "wrong_pass"),
+            Request::new("user1", "pass1"),
+            Request::new("user1", "pass1"),
+        ];
+        let outcomes = RequestHandler::new(Service::new(), requests.clone()).run();
+
+        assert_eq!(
+            outcomes.iter().map(|(r, _)| r).collect::<Vec<_>>(),
+            requests.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            statuses(outcomes),
+            [
+                ResponseStatus::AuthError,
+                ResponseStatus::Success,
+                ResponseStatus::SuccessAlreadyLoggedIn,
+            ]
+        );
+    }
+
+    #[test]
+    fn run_parallel_keeps_the_order_of_the_requests() {
+        let requests = vec![
+            Request::new("user1", "pass1"),
+            Request::new("user1", "pass1"),
+            Request::new("user2", "wrong_pass"),
+            Request::new("user2", "pass2"),
+        ];
+        let handler = RequestHandler::new(Service::new(), requests);
+
+        // Each worker has its own login context.
+        assert_eq!(
+            statuses(handler.run_parallel(2)),
+            [
+                ResponseStatus::Success,
+                ResponseStatus::SuccessAlreadyLoggedIn,
+                ResponseStatus::AuthError,
+                ResponseStatus::Success,
+            ]
+        );
+        assert_eq!(
+            statuses(handler.run_parallel(4)),
+            [
+                ResponseStatus::Success,
+                ResponseStatus::Success,
+                ResponseStatus::AuthError,
+                ResponseStatus::Success,
+            ]
+        );
+    }
+
+    #[test]
+    fn run_parallel_shares_the_rate_limiter() {
+        let service = Service::new().with_rate_limiter(Arc::new(RateLimiter::new(2, 0.0)));
+        let requests = vec![Request::new("user1", "wrong_pass"); 6];
+
+        let outcomes = statuses(RequestHandler::new(service, requests).run_parallel(3));
+        let limited = outcomes
+            .iter()
+            .filter(|status| **status == ResponseStatus::TooManyRequests)
+            .count();
+        assert_eq!(limited, 4);
+    }
+
+    #[test]
+    #[should_panic(expected = "n_workers must be positive")]
+    fn run_parallel_needs_a_worker() {
+        RequestHandler::new(Service::new(), Vec::new()).run_parallel(0);
     }
 }
@@ -1,9 +1,158 @@
+use serde::de::{self, MapAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The answer of the `Service` to a request.
+///
+/// It serializes to JSON, ready to be sent over the wire:
+///
+/// ```json
+/// {"status":"Success","message":"Logged in as user1","session_token":"5f0c…","timestamp":1718000000}
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Response {
     pub(crate) status: ResponseStatus,
+
+    /// What happened, for humans: e.g. why the request was rejected.
+    pub(crate) message: String,
+
+    /// The token of the session the request belongs to, once logged in.
+    pub(crate) session_token: Option<String>,
+
+    /// When the response was created, in seconds since the Unix epoch.
+    pub(crate) timestamp: u64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub(crate) enum ResponseStatus {
     Success,
     SuccessAlreadyLoggedIn,
     AuthError,
+    TooManyRequests,
+}
+
+impl Response {
+    pub(crate) fn new(status: ResponseStatus, message: impl Into<String>) -> Self {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self {
+            status,
+            message: message.into(),
+            session_token: None,
+            timestamp,
+        }
+    }
+
+    pub(crate) fn with_session_token(mut self, session_token: impl Into<String>) -> Self {
+        self.session_token = Some(session_token.into());
+        self
+    }
+}
+
+const FIELDS: &[&str] = &["status", "message", "session_token", "timestamp"];
+const STATUSES: &[&str] = &[
+    "Success",
+    "SuccessAlreadyLoggedIn",
+    "AuthError",
+    "TooManyRequests",
+];
+
+impl Serialize for Response {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Response", FIELDS.len())?;
+        state.serialize_field("status", &self.status)?;
+        state.serialize_field("message", &self.message)?;
+        state.serialize_field("session_token", &self.session_token)?;
+        state.serialize_field("timestamp", &self.timestamp)?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Response {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_struct("Response", FIELDS, ResponseVisitor)
+    }
+}
+
+struct ResponseVisitor;
+
+impl<'de> Visitor<'de> for ResponseVisitor {
+    type Value = Response;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a response")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Response, A::Error> {
+        let mut status = None;
+        let mut message = None;
+        let mut session_token = None;
+        let mut timestamp = None;
+
+        while let Some(key) = map.next_key::<String>()? {
+            match key.as_str() {
+                "status" => status = Some(map.next_value()?),
+                "message" => message = Some(map.next_value()?),
+                "session_token" => session_token = Some(map.next_value()?),
+                "timestamp" => timestamp = Some(map.next_value()?),
+                other => return Err(de::Error::unknown_field(other, FIELDS)),
+            }
+        }
+
+        Ok(Response {
+            status: status.ok_or_else(|| de::Error::missing_field("status"))?,
+            message: message.ok_or_else(|| de::Error::missing_field("message"))?,
+            session_token: session_token.unwrap_or(None),
+            timestamp: timestamp.ok_or_else(|| de::Error::missing_field("timestamp"))?,
+        })
+    }
+}
+
+/// A status is serialized as its name, e.g. `"AuthError"`.
+impl Serialize for ResponseStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let index = *self as u32;
+        serializer.serialize_unit_variant("ResponseStatus", index, STATUSES[index as usize])
+    }
+}
+
+impl<'de> Deserialize<'de> for ResponseStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        match name.as_str() {
+            "Success" => Ok(ResponseStatus::Success),
+            "SuccessAlreadyLoggedIn" => Ok(ResponseStatus::SuccessAlreadyLoggedIn),
+            "AuthError" => Ok(ResponseStatus::AuthError),
+            "TooManyRequests" => Ok(ResponseStatus::TooManyRequests),
+            other => Err(de::Error::unknown_variant(other, STATUSES)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json_round_trip() {
+        let response = Response::new(ResponseStatus::Success, "Logged in as user1")
+            .with_session_token("0123456789abcdef");
+
+        let json = serde_json::to_string(&response).unwrap();
+        assert!(json.starts_with(r#"{"status":"Success","message":"Logged in as user1","session_token":"0123456789abcdef","timestamp":"#));
+        assert_eq!(serde_json::from_str::<Response>(&json).unwrap(), response);
+    }
+
+    #[test]
+    fn json_without_session_token() {
+        let json =
+            r#"{"status":"AuthError","message":"Invalid username or password","timestamp":0}"#;
+
+        let response: Response = serde_json::from_str(json).unwrap();
+        assert_eq!(response.status, ResponseStatus::AuthError);
+        assert_eq!(response.session_token, None);
+    }
 }
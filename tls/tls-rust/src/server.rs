@@ -0,0 +1,104 @@
+use crate::request::Request;
+use crate::response::{Response, ResponseStatus};
+use crate::service_v2::{Service, SessionContext};
+use mini_runtime::codec::LinesCodec;
+use mini_runtime::{Config, Context, Handler, MiniRuntime, Token};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use tracing::{Level, event};
+use tutorial_util::Result;
+
+/// Longest request line, longer ones close the connection.
+const MAX_LINE_LENGTH: usize = 1024;
+
+/// Serves the `Service` over TCP, one request per line:
+///
+/// ```text
+/// LOGIN <user> <password>   ->  SUCCESS <token> | ALREADY_LOGGED_IN <token> | AUTH_ERROR | TOO_MANY_REQUESTS
+/// LOGOUT <user>             ->  SUCCESS | AUTH_ERROR
+/// VALIDATE <token>          ->  SUCCESS <token> | AUTH_ERROR
+/// REFRESH <token>           ->  SUCCESS <token> | AUTH_ERROR
+/// ```
+///
+/// A token can be validated or refreshed from any connection. Any other line
+/// is answered with `ERROR <reason>`.
+struct AuthServer {
+    service: Service,
+
+    /// The login context of each connection.
+    sessions: HashMap<Token, SessionContext>,
+}
+
+impl Handler for AuthServer {
+    type Codec = LinesCodec;
+    type Timer = ();
+
+    fn new_codec(&mut self) -> LinesCodec {
+        LinesCodec::new_with_max_length(MAX_LINE_LENGTH)
+    }
+
+    fn on_frame(&mut self, line: String, cx: &mut Context<'_, LinesCodec>) -> Result<()> {
+        let session = self.sessions.entry(cx.token()).or_default();
+        let reply = match line.split_whitespace().collect::<Vec<_>>()[..] {
+            ["LOGIN", username, password] => {
+                let request = Request::new(username, password);
+                reply_line(
+                    self.service
+                        .with_context(session, |service| service.get(&request)),
+                )
+            }
+            ["LOGOUT", username] => {
+                let request = Request::new(username, "");
+                reply_line(
+                    self.service
+                        .with_context(session, |service| service.logout(&request)),
+                )
+            }
+            ["VALIDATE", token] => reply_line(self.service.validate(token)),
+            ["REFRESH", token] => reply_line(self.service.refresh(token)),
+            _ => {
+                event!(
+                    Level::WARN,
+                    "Malformed request from {:?}: {}",
+                    cx.token(),
+                    line
+                );
+                "ERROR expected LOGIN <user> <password>, LOGOUT <user>, VALIDATE <token> \
+                 or REFRESH <token>"
+                    .to_string()
+            }
+        };
+        cx.send(reply)?;
+        Ok(())
+    }
+
+    fn on_close(&mut self, token: Token) {
+        // Tokens are reused: the next client must not inherit this login.
+        self.sessions.remove(&token);
+    }
+}
+
+/// Returns the status of `response`, followed by its session token if any.
+fn reply_line(response: Response) -> String {
+    let status = match response.status {
+        ResponseStatus::Success => "SUCCESS",
+        ResponseStatus::SuccessAlreadyLoggedIn => "ALREADY_LOGGED_IN",
+        ResponseStatus::AuthError => "AUTH_ERROR",
+        ResponseStatus::TooManyRequests => "TOO_MANY_REQUESTS",
+    };
+    match response.session_token {
+        Some(token) => format!("{status} {token}"),
+        None => status.to_string(),
+    }
+}
+
+/// Runs the auth service on `address` until Ctrl-C.
+pub fn run(address: SocketAddr) -> Result<()> {
+    let server = AuthServer {
+        service: Service::new(),
+        sessions: HashMap::new(),
+    };
+    let mut runtime = MiniRuntime::new(address, Config::default(), server)?;
+    mini_runtime::on_sigint(runtime.shutdown_handle())?;
+    runtime.run()
+}
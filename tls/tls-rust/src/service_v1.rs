@@ -38,20 +38,20 @@ impl Service {
                 "User {} has been logged in already",
                 request.username()
             );
-            return Response {
-                status: ResponseStatus::SuccessAlreadyLoggedIn,
-            };
+            return Response::new(
+                ResponseStatus::SuccessAlreadyLoggedIn,
+                format!("Already logged in as {}", request.username()),
+            );
         }
         if let Some(password) = credentials_look_up(request.username()) {
             if password == request.password() {
                 ctx.insert(request.username().to_string());
-                return Response {
-                    status: ResponseStatus::Success,
-                };
+                return Response::new(
+                    ResponseStatus::Success,
+                    format!("Logged in as {}", request.username()),
+                );
             }
         }
-        Response {
-            status: ResponseStatus::AuthError,
-        }
+        Response::new(ResponseStatus::AuthError, "Invalid username or password")
     }
 }
@@ -1,49 +1,542 @@
+use crate::audit::AuditLog;
+use crate::password::{self, PasswordHash, PasswordVerifier, Pbkdf2};
+use crate::rate_limiter::RateLimiter;
 use crate::request::Request;
 use crate::response::{Response, ResponseStatus};
+use crate::session_store::SessionStore;
 use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
 use tracing::{Level, event};
 
-fn credentials_look_up(username: &str) -> Option<&'static str> {
-    match username {
-        "user1" => Some("pass1"),
-        "user2" => Some("pass2"),
-        _ => None,
+/// How long a login stays valid by default.
+const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(30 * 60);
+
+/// Login attempts a user may send at once by default, and how many more per
+/// second afterwards.
+const DEFAULT_BURST: u32 = 5;
+const DEFAULT_REFILL_PER_SECOND: f64 = 1.0;
+
+/// Login attempts kept by the audit log by default.
+const DEFAULT_AUDIT_CAPACITY: usize = 1024;
+
+/// The registered users, with their passwords in clear, hashed by the
+/// service's `PasswordVerifier`.
+const USERS: [(&str, &str); 2] = [("user1", "pass1"), ("user2", "pass2")];
+
+/// The password hashes of the registered users, made by one
+/// `PasswordVerifier`: only that verifier understands their format.
+struct Credentials {
+    hashes: HashMap<&'static str, PasswordHash>,
+
+    /// Checked instead of a missing user's hash, so that an unknown username
+    /// takes as long to reject as a wrong password and can't be told apart
+    /// by timing.
+    dummy_hash: PasswordHash,
+}
+
+impl Credentials {
+    fn new(verifier: &dyn PasswordVerifier) -> Self {
+        Self {
+            hashes: USERS
+                .into_iter()
+                .map(|(username, password)| (username, verifier.hash(password)))
+                .collect(),
+            dummy_hash: verifier.hash("dummy password"),
+        }
     }
 }
 
+/// The credentials hashed by the default `Pbkdf2`, shared by the services
+/// using it rather than hashed again for each one.
+fn default_credentials() -> Arc<Credentials> {
+    static DEFAULT: OnceLock<Arc<Credentials>> = OnceLock::new();
+    DEFAULT
+        .get_or_init(|| Arc::new(Credentials::new(&Pbkdf2::default())))
+        .clone()
+}
+
+/// Returns an unpredictable token identifying a session.
+fn new_session_token() -> String {
+    password::random_bytes(16)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
 thread_local! {
+    /// The token of the session logged in on the current thread. The session
+    /// itself is in the `SessionStore`, where any thread can validate it.
     static LOGIN_CONTEXT: RefCell<Option<String>> = const { RefCell::new(None) };
 }
 
-pub struct Service {}
+/// The login context of one client, for servers serving many clients on the
+/// same thread, see `Service::with_context`.
+#[derive(Default)]
+pub struct SessionContext(Option<String>);
+
+pub struct Service {
+    /// How long a session lasts after the login or a refresh, it's purged
+    /// on the next access once expired.
+    session_ttl: Duration,
+
+    /// The open sessions, it may be shared by the services of several
+    /// threads: a token is then valid on all of them.
+    sessions: Arc<SessionStore>,
+
+    /// Limits the login attempts of each user, it may be shared by the
+    /// services of several threads.
+    rate_limiter: Arc<RateLimiter>,
+
+    /// Records the login attempts, it may be shared by the services of
+    /// several threads.
+    audit_log: Arc<AuditLog>,
+
+    password_verifier: Arc<dyn PasswordVerifier>,
+
+    /// The registered users, hashed by `password_verifier`.
+    credentials: Arc<Credentials>,
+}
 
 impl Service {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            session_ttl: DEFAULT_SESSION_TTL,
+            sessions: Arc::new(SessionStore::new()),
+            rate_limiter: Arc::new(RateLimiter::new(DEFAULT_BURST, DEFAULT_REFILL_PER_SECOND)),
+            audit_log: Arc::new(AuditLog::new(DEFAULT_AUDIT_CAPACITY)),
+            password_verifier: Arc::new(Pbkdf2::default()),
+            credentials: default_credentials(),
+        }
+    }
+
+    pub fn with_session_ttl(mut self, session_ttl: Duration) -> Self {
+        self.session_ttl = session_ttl;
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    pub fn with_session_store(mut self, sessions: Arc<SessionStore>) -> Self {
+        self.sessions = sessions;
+        self
+    }
+
+    pub fn with_audit_log(mut self, audit_log: Arc<AuditLog>) -> Self {
+        self.audit_log = audit_log;
+        self
+    }
+
+    /// Replaces the default `Pbkdf2`. The users' passwords are hashed again
+    /// with `password_verifier`, so that it only checks hashes it made.
+    pub fn with_password_verifier(mut self, password_verifier: Arc<dyn PasswordVerifier>) -> Self {
+        self.credentials = Arc::new(Credentials::new(&*password_verifier));
+        self.password_verifier = password_verifier;
+        self
+    }
+
+    /// Runs `f` with `context` installed as the thread's login context, and
+    /// saves the context back once `f` returns.
+    ///
+    /// An event loop serves all its clients on one thread: the thread-local
+    /// context must be swapped for each client's, like a task-local one.
+    pub fn with_context<R>(&self, context: &mut SessionContext, f: impl FnOnce(&Self) -> R) -> R {
+        let outer = LOGIN_CONTEXT.replace(context.0.take());
+        let result = f(self);
+        context.0 = LOGIN_CONTEXT.replace(outer);
+        result
     }
 
+    /// Logs the user of the request in, and records the attempt in the
+    /// audit log, whatever its outcome.
     pub(crate) fn get(&self, request: &Request) -> Response {
+        let response = self.login(request);
+        self.audit_log.record(request.username(), &response);
+        response
+    }
+
+    fn login(&self, request: &Request) -> Response {
         event!(Level::INFO, "Got request: {}", request);
 
-        if let Some(username) = LOGIN_CONTEXT.take() {
-            event!(Level::INFO, "User {} has been logged in already", username);
-            return Response {
-                status: ResponseStatus::SuccessAlreadyLoggedIn,
-            };
-        }
-        match credentials_look_up(request.username()) {
-            Some(expected_password) if expected_password == request.password() => {
-                LOGIN_CONTEXT.with(|ctx| {
-                    *ctx.borrow_mut() = Some(request.username().to_string());
-                });
-
-                Response {
-                    status: ResponseStatus::Success,
-                }
+        if !self.rate_limiter.try_acquire(request.username()) {
+            event!(Level::WARN, "User {} is rate limited", request.username());
+            return Response::new(
+                ResponseStatus::TooManyRequests,
+                "Too many login attempts, retry later",
+            );
+        }
+
+        // The session of the thread only stands for its own user: another
+        // user's credentials are checked.
+        match self.current_session() {
+            Some((token, username)) if username == request.username() => {
+                event!(Level::INFO, "User {} has been logged in already", username);
+                return Response::new(
+                    ResponseStatus::SuccessAlreadyLoggedIn,
+                    format!("Already logged in as {}", username),
+                )
+                .with_session_token(token);
+            }
+            _ => {}
+        }
+        if !self.password_matches(request) {
+            return Response::new(ResponseStatus::AuthError, "Invalid username or password");
+        }
+
+        let token = new_session_token();
+        self.sessions
+            .insert(&token, request.username(), self.session_ttl);
+        let response = Response::new(
+            ResponseStatus::Success,
+            format!("Logged in as {}", request.username()),
+        )
+        .with_session_token(&token);
+        LOGIN_CONTEXT.set(Some(token));
+        response
+    }
+
+    /// Checks that `token` identifies an open session, from any thread
+    /// sharing the session store.
+    pub(crate) fn validate(&self, token: &str) -> Response {
+        match self.sessions.get(token) {
+            Some(session) => Response::new(
+                ResponseStatus::Success,
+                format!("Session of {} is valid", session.username),
+            )
+            .with_session_token(token),
+            None => Response::new(ResponseStatus::AuthError, "Invalid or expired session"),
+        }
+    }
+
+    /// Extends the session of `token` to `session_ttl` from now. An expired
+    /// session can't be refreshed: the user must log in again.
+    pub(crate) fn refresh(&self, token: &str) -> Response {
+        match self.sessions.refresh(token, self.session_ttl) {
+            Some(session) => {
+                event!(
+                    Level::INFO,
+                    "Session of user {} refreshed",
+                    session.username
+                );
+                Response::new(
+                    ResponseStatus::Success,
+                    format!("Session of {} refreshed", session.username),
+                )
+                .with_session_token(token)
+            }
+            None => Response::new(ResponseStatus::AuthError, "Invalid or expired session"),
+        }
+    }
+
+    fn password_matches(&self, request: &Request) -> bool {
+        match self.credentials.hashes.get(request.username()) {
+            Some(hash) => self.password_verifier.verify(request.password(), hash),
+            None => {
+                self.password_verifier
+                    .verify(request.password(), &self.credentials.dummy_hash);
+                false
+            }
+        }
+    }
+
+    /// Ends the session of the request's user. Fails with `AuthError` if that
+    /// user isn't logged in, or their session expired.
+    pub(crate) fn logout(&self, request: &Request) -> Response {
+        event!(Level::INFO, "Got logout request: {}", request.username());
+
+        let logged_out = match self.current_session() {
+            Some((token, username)) if username == request.username() => {
+                LOGIN_CONTEXT.set(None);
+                self.sessions.remove(&token)
+            }
+            _ => None,
+        };
+        match logged_out {
+            Some(session) => {
+                event!(Level::INFO, "User {} logged out", session.username);
+                Response::new(
+                    ResponseStatus::Success,
+                    format!("Logged out {}", session.username),
+                )
+            }
+            None => Response::new(
+                ResponseStatus::AuthError,
+                format!("{} is not logged in", request.username()),
+            ),
+        }
+    }
+
+    /// Returns the token and the user of the session logged in on the
+    /// current thread. Forgets the token if its session expired, or was
+    /// closed from another thread.
+    fn current_session(&self) -> Option<(String, String)> {
+        let token = LOGIN_CONTEXT.with_borrow(|token| token.clone())?;
+        match self.sessions.get(&token) {
+            Some(session) => Some((token, session.username)),
+            None => {
+                event!(Level::INFO, "Session {} expired", token);
+                LOGIN_CONTEXT.set(None);
+                None
             }
-            _ => Response {
-                status: ResponseStatus::AuthError,
-            },
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering::Relaxed;
+    use std::thread;
+
+    // Each test runs on its own thread, so with its own login context.
+
+    #[test]
+    fn login_is_remembered_until_the_session_expires() {
+        let service = Service::new().with_session_ttl(Duration::from_millis(50));
+        let request = Request::new("user1", "pass1");
+
+        assert!(matches!(
+            service.get(&request).status,
+            ResponseStatus::Success
+        ));
+        assert!(matches!(
+            service.get(&request).status,
+            ResponseStatus::SuccessAlreadyLoggedIn
+        ));
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(matches!(
+            service.get(&request).status,
+            ResponseStatus::Success
+        ));
+    }
+
+    #[test]
+    fn expired_session_needs_valid_credentials() {
+        let service = Service::new().with_session_ttl(Duration::from_millis(50));
+
+        service.get(&Request::new("user1", "pass1"));
+        assert!(matches!(
+            service.get(&Request::new("user1", "wrong_pass")).status,
+            ResponseStatus::SuccessAlreadyLoggedIn
+        ));
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(matches!(
+            service.get(&Request::new("user1", "wrong_pass")).status,
+            ResponseStatus::AuthError
+        ));
+    }
+
+    #[test]
+    fn session_does_not_log_another_user_in() {
+        let service = Service::new();
+
+        service.get(&Request::new("user1", "pass1"));
+        let response = service.get(&Request::new("user2", "pass2"));
+        assert_eq!(response.status, ResponseStatus::Success);
+        assert_eq!(response.message, "Logged in as user2");
+    }
+
+    #[test]
+    fn session_token_is_not_given_to_another_user() {
+        let audit_log = Arc::new(AuditLog::new(8));
+        let service = Service::new().with_audit_log(audit_log.clone());
+
+        let token = service.get(&Request::new("user1", "pass1")).session_token;
+        assert!(token.is_some());
+
+        let response = service.get(&Request::new("user2", "wrong_pass"));
+        assert_eq!(response.status, ResponseStatus::AuthError);
+        assert_eq!(response.session_token, None);
+
+        let last = audit_log.events().pop().unwrap();
+        assert_eq!(last.username, "user2");
+        assert_eq!(last.outcome, ResponseStatus::AuthError);
+    }
+
+    #[test]
+    fn login_attempts_are_rate_limited() {
+        let service = Service::new().with_rate_limiter(Arc::new(RateLimiter::new(2, 0.0)));
+        let request = Request::new("user1", "wrong_pass");
+
+        assert!(matches!(
+            service.get(&request).status,
+            ResponseStatus::AuthError
+        ));
+        assert!(matches!(
+            service.get(&request).status,
+            ResponseStatus::AuthError
+        ));
+        assert!(matches!(
+            service.get(&Request::new("user1", "pass1")).status,
+            ResponseStatus::TooManyRequests
+        ));
+    }
+
+    #[test]
+    fn login_attempts_are_audited() {
+        let audit_log = Arc::new(AuditLog::new(8));
+        let service = Service::new().with_audit_log(audit_log.clone());
+
+        service.get(&Request::new("user1", "wrong_pass"));
+        service.get(&Request::new("user1", "pass1"));
+        service.logout(&Request::new("user1", "pass1"));
+
+        // Another thread, with its own login context, shares the log.
+        let other = Service::new().with_audit_log(audit_log.clone());
+        thread::spawn(move || other.get(&Request::new("user2", "pass2")))
+            .join()
+            .unwrap();
+
+        let events: Vec<_> = audit_log
+            .events()
+            .into_iter()
+            .map(|event| (event.username, event.outcome))
+            .collect();
+        assert_eq!(
+            events,
+            [
+                ("user1".to_string(), ResponseStatus::AuthError),
+                ("user1".to_string(), ResponseStatus::Success),
+                ("user2".to_string(), ResponseStatus::Success),
+            ]
+        );
+    }
+
+    #[test]
+    fn token_is_valid_on_other_threads() {
+        let sessions = Arc::new(SessionStore::new());
+        let service = Service::new().with_session_store(sessions.clone());
+
+        let token = service
+            .get(&Request::new("user1", "pass1"))
+            .session_token
+            .unwrap();
+
+        let other = Service::new().with_session_store(sessions);
+        let status = thread::spawn(move || other.validate(&token).status)
+            .join()
+            .unwrap();
+        assert_eq!(status, ResponseStatus::Success);
+        assert_eq!(service.validate("forged").status, ResponseStatus::AuthError);
+    }
+
+    #[test]
+    fn refresh_extends_the_session() {
+        let service = Service::new().with_session_ttl(Duration::from_millis(100));
+        let request = Request::new("user1", "pass1");
+
+        let token = service.get(&request).session_token.unwrap();
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(service.refresh(&token).status, ResponseStatus::Success);
+
+        thread::sleep(Duration::from_millis(60));
+        assert_eq!(service.validate(&token).status, ResponseStatus::Success);
+        assert!(matches!(
+            service.get(&request).status,
+            ResponseStatus::SuccessAlreadyLoggedIn
+        ));
+
+        thread::sleep(Duration::from_millis(120));
+        assert_eq!(service.refresh(&token).status, ResponseStatus::AuthError);
+    }
+
+    #[test]
+    fn logout_closes_the_session() {
+        let service = Service::new();
+        let request = Request::new("user1", "pass1");
+
+        let token = service.get(&request).session_token.unwrap();
+        service.logout(&request);
+        assert_eq!(service.validate(&token).status, ResponseStatus::AuthError);
+    }
+
+    #[test]
+    fn logout_ends_the_session() {
+        let service = Service::new();
+        let request = Request::new("user1", "pass1");
+
+        service.get(&request);
+        assert!(matches!(
+            service.logout(&request).status,
+            ResponseStatus::Success
+        ));
+        assert!(matches!(
+            service.get(&request).status,
+            ResponseStatus::Success
+        ));
+    }
+
+    #[test]
+    fn logout_fails_without_a_session() {
+        let service = Service::new().with_session_ttl(Duration::from_millis(50));
+        let request = Request::new("user1", "pass1");
+
+        assert!(matches!(
+            service.logout(&request).status,
+            ResponseStatus::AuthError
+        ));
+
+        service.get(&request);
+        assert!(matches!(
+            service.logout(&Request::new("user2", "pass2")).status,
+            ResponseStatus::AuthError
+        ));
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(matches!(
+            service.logout(&request).status,
+            ResponseStatus::AuthError
+        ));
+    }
+
+    /// Only checks the hashes it made itself.
+    #[derive(Default)]
+    struct RecordingVerifier {
+        hashes: Mutex<Vec<PasswordHash>>,
+        verified: AtomicUsize,
+    }
+
+    impl PasswordVerifier for RecordingVerifier {
+        fn hash(&self, password: &str) -> PasswordHash {
+            let hash = Pbkdf2::new(1).hash(password);
+            self.hashes.lock().unwrap().push(hash.clone());
+            hash
+        }
+
+        fn verify(&self, password: &str, hash: &PasswordHash) -> bool {
+            assert!(self.hashes.lock().unwrap().contains(hash));
+            self.verified.fetch_add(1, Relaxed);
+            Pbkdf2::new(1).verify(password, hash)
+        }
+    }
+
+    #[test]
+    fn custom_verifier_checks_its_own_hashes() {
+        let verifier = Arc::new(RecordingVerifier::default());
+        let service = Service::new().with_password_verifier(verifier.clone());
+        // The registered users and the dummy hash.
+        assert_eq!(verifier.hashes.lock().unwrap().len(), 3);
+
+        assert_eq!(
+            service.get(&Request::new("unknown", "pass1")).status,
+            ResponseStatus::AuthError
+        );
+        assert_eq!(
+            service.get(&Request::new("user1", "wrong_pass")).status,
+            ResponseStatus::AuthError
+        );
+        assert_eq!(
+            service.get(&Request::new("user1", "pass1")).status,
+            ResponseStatus::Success
+        );
+        assert_eq!(verifier.verified.load(Relaxed), 3);
+    }
+}
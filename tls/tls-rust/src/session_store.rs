@@ -0,0 +1,149 @@
+use std::collections::HashMap;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Number of shards by default, see `SessionStore`.
+const DEFAULT_SHARDS: usize = 16;
+
+/// The open sessions, by token, shared by the services of all the threads.
+///
+/// Like a `DashMap`, the map is split in shards, each behind its own
+/// `Mutex`: a token always lands in the same shard, and threads looking up
+/// tokens of different shards don't wait for each other.
+///
+/// A session expires a fixed time after the login or its last refresh. The
+/// expired sessions are dropped when looked up.
+pub struct SessionStore {
+    shards: Box<[Mutex<HashMap<String, Session>>]>,
+    hasher: RandomState,
+}
+
+/// A logged in user.
+#[derive(Debug, Clone)]
+pub(crate) struct Session {
+    pub(crate) username: String,
+    pub(crate) expires_at: Instant,
+}
+
+impl SessionStore {
+    pub fn new() -> Self {
+        Self::with_shards(DEFAULT_SHARDS)
+    }
+
+    pub fn with_shards(shards: usize) -> Self {
+        assert!(shards > 0, "shards must be positive");
+        Self {
+            shards: (0..shards).map(|_| Mutex::new(HashMap::new())).collect(),
+            hasher: RandomState::new(),
+        }
+    }
+
+    fn shard(&self, token: &str) -> &Mutex<HashMap<String, Session>> {
+        let index = self.hasher.hash_one(token) as usize % self.shards.len();
+        &self.shards[index]
+    }
+
+    /// Opens a session for `username` under `token`, for `ttl`.
+    pub(crate) fn insert(&self, token: &str, username: &str, ttl: Duration) {
+        let session = Session {
+            username: username.to_string(),
+            expires_at: Instant::now() + ttl,
+        };
+        self.shard(token)
+            .lock()
+            .unwrap()
+            .insert(token.to_string(), session);
+    }
+
+    /// Returns the session of `token`, `None` if there is none or it expired.
+    pub(crate) fn get(&self, token: &str) -> Option<Session> {
+        let mut shard = self.shard(token).lock().unwrap();
+        let session = shard.get(token)?;
+        if session.expires_at <= Instant::now() {
+            shard.remove(token);
+            return None;
+        }
+        Some(session.clone())
+    }
+
+    /// Extends the session of `token` to `ttl` from now. Returns the session,
+    /// `None` if there is none or it expired.
+    pub(crate) fn refresh(&self, token: &str, ttl: Duration) -> Option<Session> {
+        let now = Instant::now();
+        let mut shard = self.shard(token).lock().unwrap();
+        let session = shard.get_mut(token)?;
+        if session.expires_at <= now {
+            shard.remove(token);
+            return None;
+        }
+        session.expires_at = now + ttl;
+        Some(session.clone())
+    }
+
+    /// Closes the session of `token`, returning it if it was still open.
+    pub(crate) fn remove(&self, token: &str) -> Option<Session> {
+        let session = self.shard(token).lock().unwrap().remove(token)?;
+        (session.expires_at > Instant::now()).then_some(session)
+    }
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn sessions_expire() {
+        let store = SessionStore::new();
+        store.insert("token", "user1", Duration::from_millis(50));
+
+        assert_eq!(store.get("token").unwrap().username, "user1");
+        assert!(store.get("other").is_none());
+
+        thread::sleep(Duration::from_millis(60));
+        assert!(store.get("token").is_none());
+        assert!(store.refresh("token", Duration::from_secs(60)).is_none());
+    }
+
+    #[test]
+    fn refresh_extends_the_session() {
+        let store = SessionStore::with_shards(1);
+        store.insert("token", "user1", Duration::from_millis(50));
+
+        thread::sleep(Duration::from_millis(30));
+        assert!(store.refresh("token", Duration::from_millis(50)).is_some());
+
+        thread::sleep(Duration::from_millis(30));
+        assert!(store.get("token").is_some());
+        assert!(store.remove("token").is_some());
+        assert!(store.get("token").is_none());
+    }
+
+    #[test]
+    fn shared_by_threads() {
+        let store = Arc::new(SessionStore::new());
+
+        let writers: Vec<_> = (0..4)
+            .map(|i| {
+                let store = store.clone();
+                thread::spawn(move || {
+                    store.insert(&format!("token{i}"), "user1", Duration::from_secs(60))
+                })
+            })
+            .collect();
+        for writer in writers {
+            writer.join().unwrap();
+        }
+
+        assert!((0..4).all(|i| store.get(&format!("token{i}")).is_some()));
+    }
+}
@@ -2,10 +2,140 @@
 mod tests {
 
     use futures::future::join_all;
+    use std::thread;
     use std::time::Instant;
     use tokio::task::JoinHandle;
     use tokio::time::{Duration, sleep};
 
+    // Duration of each CPU-bound task in the comparison tests below
+    const CPU_TASK: Duration = Duration::from_millis(200);
+    const CPU_TASKS: usize = 3;
+
+    // Attempts of the timing assertions that depend on the machine being idle
+    const TIMING_ATTEMPTS: usize = 3;
+
+    // Wall-clock interval during which a task was running
+    #[derive(Debug, Clone, Copy)]
+    struct Span {
+        start: Instant,
+        end: Instant,
+    }
+
+    // Keeps the CPU busy for `duration` without ever yielding to the runtime,
+    // like a compression or a hash computation would.
+    fn busy_loop(duration: Duration) -> Span {
+        let start = Instant::now();
+        while start.elapsed() < duration {
+            std::hint::spin_loop();
+        }
+        Span {
+            start,
+            end: Instant::now(),
+        }
+    }
+
+    // Returns true if at least two of the spans overlap in time.
+    fn any_overlap(spans: &[Span]) -> bool {
+        let mut spans = spans.to_vec();
+        spans.sort_by_key(|span| span.start);
+        spans.windows(2).any(|w| w[1].start < w[0].end)
+    }
+
+    // Returns true if the machine can run the CPU-bound tasks in parallel, so
+    // that the speedup assertions are meaningful.
+    fn enough_cores() -> bool {
+        thread::available_parallelism().is_ok_and(|n| n.get() >= CPU_TASKS)
+    }
+
+    // Runs the CPU-bound tasks with `run` and returns their spans and the
+    // total elapsed time.
+    async fn run_cpu_tasks<F, Fut>(run: F) -> (Vec<Span>, Duration)
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Span> + Send + 'static,
+    {
+        let start = Instant::now();
+        let tasks: Vec<JoinHandle<Span>> = (0..CPU_TASKS).map(|_| tokio::spawn(run())).collect();
+        let spans = join_all(tasks)
+            .await
+            .into_iter()
+            .map(|res| res.expect("A spawned task panicked"))
+            .collect();
+        (spans, start.elapsed())
+    }
+
+    // Asserts that the CPU-bound tasks ran in parallel: their spans overlap
+    // and, on a machine with enough cores, the total time is well below the
+    // sum of their durations. The timing part is retried, a busy CI machine
+    // may not schedule the threads right away.
+    async fn assert_parallel<F, Fut>(run: F)
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = Span> + Send + 'static,
+    {
+        let sum = CPU_TASK * CPU_TASKS as u32;
+        for attempt in 1..=TIMING_ATTEMPTS {
+            let (spans, elapsed) = run_cpu_tasks(&run).await;
+            println!("Attempt {}: {:?} for {:?} of work", attempt, elapsed, sum);
+
+            // Even on a single core, the OS time-slices the threads.
+            assert!(any_overlap(&spans), "Tasks should overlap: {:?}", spans);
+
+            // Generous threshold: 2/3 of the sum, where the ideal is 1/3.
+            if !enough_cores() || elapsed < sum * 2 / 3 {
+                return;
+            }
+        }
+        panic!(
+            "Tasks should run in parallel in less than {:?}",
+            sum * 2 / 3
+        );
+    }
+
+    // On `current_thread`, a task blocking the thread blocks the whole
+    // runtime: the busy loops run one after the other.
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_cpu_bound_tasks_do_not_overlap_on_current_thread() {
+        let (spans, elapsed) = run_cpu_tasks(|| async { busy_loop(CPU_TASK) }).await;
+
+        // Deterministic: the loops can't be interleaved.
+        assert!(
+            !any_overlap(&spans),
+            "Tasks should not overlap: {:?}",
+            spans
+        );
+        assert!(
+            elapsed >= CPU_TASK * CPU_TASKS as u32,
+            "Total duration ({:?}) should be at least the sum of the task durations",
+            elapsed
+        );
+    }
+
+    // On `multi_thread`, each worker thread runs its own busy loop.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+    async fn test_cpu_bound_tasks_overlap_on_multi_thread() {
+        assert_parallel(|| async { busy_loop(CPU_TASK) }).await;
+    }
+
+    // `spawn_blocking` moves the busy loop to the blocking pool, the
+    // `current_thread` runtime thread only awaits its completion.
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_spawn_blocking_overlaps_on_current_thread() {
+        assert_parallel(|| async {
+            tokio::task::spawn_blocking(|| busy_loop(CPU_TASK))
+                .await
+                .expect("The blocking task panicked")
+        })
+        .await;
+    }
+
+    // `block_in_place` hands the worker's other tasks over to another thread
+    // before blocking. Only available on `multi_thread`.
+    #[tokio::test(flavor = "multi_thread", worker_threads = 3)]
+    async fn test_block_in_place_overlaps_on_multi_thread() {
+        assert_parallel(|| async { tokio::task::block_in_place(|| busy_loop(CPU_TASK)) }).await;
+    }
+
     // Use the tokio::test attribute with the "current_thread" flavor.
     // This sets up a single-threaded runtime specifically for this test function.
     #[tokio::test(flavor = "current_thread")]
@@ -76,4 +206,58 @@ mod tests {
 
         Ok(()) // Return Ok(()) to indicate the test passed
     }
+
+    // Same scenario as above, on a paused clock: `tokio::time` only moves
+    // when told to, the virtual elapsed time is exact and the test takes no
+    // wall-clock time.
+    #[tokio::test(start_paused = true)]
+    async fn test_single_thread_concurrency_with_paused_clock() {
+        let durations_ms = [300, 200, 250];
+        let start = tokio::time::Instant::now();
+
+        let tasks: Vec<JoinHandle<u64>> = durations_ms
+            .iter()
+            .map(|&ms| {
+                tokio::spawn(async move {
+                    sleep(Duration::from_millis(ms)).await;
+                    ms
+                })
+            })
+            .collect();
+
+        // Let the tasks register their timers, then move the clock to the
+        // last deadline: every timer fires at once.
+        tokio::task::yield_now().await;
+        let max = Duration::from_millis(*durations_ms.iter().max().unwrap());
+        tokio::time::advance(max).await;
+
+        let results: Vec<u64> = join_all(tasks)
+            .await
+            .into_iter()
+            .map(|res| res.expect("A spawned task panicked"))
+            .collect();
+        assert_eq!(results, durations_ms);
+
+        // Concurrent: the virtual time is the longest task, not the sum.
+        assert_eq!(start.elapsed(), max);
+    }
+
+    // With a paused clock, the runtime also advances time on its own when
+    // it has nothing else to do, to the next timer.
+    #[tokio::test(start_paused = true)]
+    async fn test_single_thread_concurrency_with_auto_advance() {
+        let durations_ms = [300, 200, 250];
+        let start = tokio::time::Instant::now();
+
+        let tasks: Vec<JoinHandle<()>> = durations_ms
+            .iter()
+            .map(|&ms| tokio::spawn(sleep(Duration::from_millis(ms))))
+            .collect();
+        for res in join_all(tasks).await {
+            res.expect("A spawned task panicked");
+        }
+
+        let max = Duration::from_millis(*durations_ms.iter().max().unwrap());
+        assert_eq!(start.elapsed(), max);
+    }
 }
@@ -0,0 +1,124 @@
+use std::fmt;
+use std::io;
+use std::net::AddrParseError;
+use std::num::{ParseFloatError, ParseIntError};
+use std::str::Utf8Error;
+use std::string::FromUtf8Error;
+
+/// The failures of the examples.
+///
+/// `?` converts the errors of the standard library: `io::Error` into `Io`,
+/// the errors of `str::parse` and of the UTF-8 conversions into `Parse`.
+/// `Timeout` and `Protocol` are returned explicitly:
+///
+/// ```
+/// use tutorial_util::{Error, Result};
+///
+/// fn port(arg: &str) -> Result<u16> {
+///     Ok(arg.parse()?)
+/// }
+///
+/// assert!(matches!(port("http"), Err(Error::Parse(_))));
+/// ```
+#[derive(Debug)]
+pub enum Error {
+    /// An I/O operation failed.
+    Io(io::Error),
+
+    /// The input, e.g. an argument or a number in a request, is malformed.
+    Parse(String),
+
+    /// Nothing happened before the deadline, e.g. no answer from a peer.
+    Timeout,
+
+    /// The peer broke the protocol, e.g. with an unexpected answer.
+    Protocol(String),
+}
+
+/// `std::result::Result` with `Error` as the default error.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+impl Error {
+    /// Returns `true` for `Error::Io` errors of the given kind.
+    pub fn is_io(&self, kind: io::ErrorKind) -> bool {
+        matches!(self, Error::Io(e) if e.kind() == kind)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {e}"),
+            Error::Parse(reason) => write!(f, "parse error: {reason}"),
+            Error::Timeout => f.write_str("timed out"),
+            Error::Protocol(reason) => write!(f, "protocol error: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+macro_rules! parse_errors {
+    ($($error:ty),*) => {
+        $(
+            impl From<$error> for Error {
+                fn from(e: $error) -> Self {
+                    Error::Parse(e.to_string())
+                }
+            }
+        )*
+    };
+}
+
+parse_errors!(
+    AddrParseError,
+    ParseIntError,
+    ParseFloatError,
+    Utf8Error,
+    FromUtf8Error
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions() {
+        let io: Error = io::Error::from(io::ErrorKind::AddrInUse).into();
+        assert!(io.is_io(io::ErrorKind::AddrInUse));
+        assert!(!io.is_io(io::ErrorKind::NotFound));
+
+        let parse: Error = "x".parse::<u64>().unwrap_err().into();
+        assert!(matches!(parse, Error::Parse(_)));
+
+        let addr: Error = "localhost"
+            .parse::<std::net::SocketAddr>()
+            .unwrap_err()
+            .into();
+        assert_eq!(
+            addr.to_string(),
+            "parse error: invalid socket address syntax"
+        );
+    }
+
+    #[test]
+    fn source() {
+        use std::error::Error as _;
+
+        assert!(Error::from(io::Error::other("boom")).source().is_some());
+        assert!(Error::Timeout.source().is_none());
+    }
+}
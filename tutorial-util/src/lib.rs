@@ -0,0 +1,9 @@
+//! Helpers shared by the examples of the tutorial.
+//!
+//! The examples used to return `Box<dyn Error>`, which is convenient until a
+//! test wants to tell a timeout from a malformed request. `Error` names the
+//! few kinds of failures they run into, `Result` defaults to it.
+
+mod error;
+
+pub use error::{Error, Result};